@@ -148,6 +148,8 @@ fn run_performance_test(points: usize, format: DataFormat) -> Result<()> {
     let format_str = match format {
         DataFormat::Binary => "binary",
         DataFormat::BinaryCompressed => "compressed",
+        #[cfg(feature = "zstd")]
+        DataFormat::BinaryZstd => "zstd",
         DataFormat::Ascii => "ascii",
     };
     let tmp_file = format!("perf_{}_{}.pcd", points, format_str);
@@ -206,6 +208,8 @@ fn create_synthetic_pcd<P: AsRef<Path>>(path: P) -> Result<()> {
         viewpoint: [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0],
         points,
         data: DataFormat::Binary,
+        comments: vec![],
+        extras: vec![],
     };
 
     // 2. Prepare Data (SoA)
@@ -54,8 +54,8 @@ fn main() -> Result<()> {
     if let Some(ts_col) = block.get_column("timestamp") {
         if let Some(timestamps) = ts_col.as_f64() {
             println!("前 5 个点的时间戳:");
-            for i in 0..block.len.min(5) {
-                println!("  点 {}: {:.6}", i, timestamps[i]);
+            for (i, ts) in timestamps.iter().enumerate().take(5) {
+                println!("  点 {}: {:.6}", i, ts);
             }
         }
     }
@@ -64,8 +64,8 @@ fn main() -> Result<()> {
     if let Some(ring_col) = block.get_column("ring") {
         if let Some(rings) = ring_col.as_u16() {
             println!("\n前 5 个点的线束编号:");
-            for i in 0..block.len.min(5) {
-                println!("  点 {}: ring {}", i, rings[i]);
+            for (i, ring) in rings.iter().enumerate().take(5) {
+                println!("  点 {}: ring {}", i, ring);
             }
         }
     }
@@ -108,8 +108,8 @@ fn main() -> Result<()> {
                 .collect();
             
             println!("前 5 个点到原点的距离:");
-            for i in 0..5.min(distances.len()) {
-                println!("  点 {}: {:.3} 米", i, distances[i]);
+            for (i, d) in distances.iter().enumerate().take(5) {
+                println!("  点 {}: {:.3} 米", i, d);
             }
         }
     }
@@ -118,10 +118,10 @@ fn main() -> Result<()> {
     // 方法 7: 遍历所有字段（查看 schema）
     // ============================================
     println!("\n=== 方法 7: 查看所有字段 ===");
-    println!("Schema: {:?}", block.schema());
+    println!("Schema: {:?}", block.schema().names().collect::<Vec<_>>());
     println!("字段数量: {}", block.num_columns());
     
-    for (idx, field_name) in block.schema().iter().enumerate() {
+    for (idx, field_name) in block.schema().names().enumerate() {
         let col = block.get_column_by_index(idx).unwrap();
         println!("  字段 {}: {} (长度: {})", idx, field_name, col.len());
     }
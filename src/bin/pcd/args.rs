@@ -0,0 +1,105 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Minimal `--name value` / `--name=value` argument parsing.
+
+use crate::usage_error;
+use rs_pcd::Result;
+
+pub struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    /// Split `raw` into positional arguments and options. Options listed in
+    /// `with_value` take an argument, those in `flags` are booleans. `--help`
+    /// (or `-h`) is always accepted.
+    pub fn parse(raw: &[String], with_value: &[&str], flags: &[&str]) -> Result<Self> {
+        let mut positional = Vec::new();
+        let mut options = Vec::new();
+        let mut iter = raw.iter();
+        while let Some(arg) = iter.next() {
+            if arg == "-h" {
+                options.push(("help".to_string(), None));
+                continue;
+            }
+            let Some(name) = arg.strip_prefix("--") else {
+                positional.push(arg.clone());
+                continue;
+            };
+            if name.is_empty() {
+                // `--` ends option parsing.
+                positional.extend(iter.by_ref().cloned());
+                break;
+            }
+            let (name, inline) = match name.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (name, None),
+            };
+            if with_value.contains(&name) {
+                let value = match inline {
+                    Some(value) => value,
+                    None => iter
+                        .next()
+                        .cloned()
+                        .ok_or_else(|| usage_error(format!("--{} needs a value", name)))?,
+                };
+                options.push((name.to_string(), Some(value)));
+            } else if inline.is_some() {
+                return Err(usage_error(format!("--{} takes no value", name)));
+            } else if name == "help" || flags.contains(&name) {
+                options.push((name.to_string(), None));
+            } else {
+                return Err(usage_error(format!("unknown option --{}", name)));
+            }
+        }
+        Ok(Self {
+            positional,
+            options,
+        })
+    }
+
+    pub fn positional(&self) -> &[String] {
+        &self.positional
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(n, _)| n == name)
+    }
+
+    /// Last value given for `--name`.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values(name).last().copied()
+    }
+
+    /// All values given for a repeatable `--name`, in order.
+    pub fn values(&self, name: &str) -> Vec<&str> {
+        self.options
+            .iter()
+            .filter(|(n, _)| n == name)
+            .filter_map(|(_, v)| v.as_deref())
+            .collect()
+    }
+
+    /// `--name` parsed as `T`, if given.
+    pub fn parsed<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>> {
+        self.value(name)
+            .map(|v| {
+                v.parse()
+                    .map_err(|_| usage_error(format!("invalid value for --{}: {}", name, v)))
+            })
+            .transpose()
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `pcd convert`: translate between PCD data formats and other point files.
+
+use crate::args::Args;
+use crate::{CHUNK_POINTS, FileKind, header_for, help, load, parse_format, save, usage_error};
+use rs_pcd::io::{PcdReader, PcdStreamWriter};
+use rs_pcd::{DataFormat, Result};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+const HELP: &str = "\
+Usage: pcd convert [options] <input> <output>
+
+Convert a point file. Types are chosen by extension: .pcd (.pcd.gz with the
+flate2 feature), KITTI .bin, .csv/.txt/.xyz and .pts.
+
+PCD to ascii or binary PCD is streamed in chunks, so it works on files larger
+than memory. Other conversions, and binary_compressed or gzip output, hold the
+cloud in memory.
+
+Options:
+  --format <fmt>  PCD output format: ascii, binary or binary_compressed
+                  [default: binary]
+  --chunk <N>     Points per chunk when streaming [default: 65536]";
+
+pub fn run(raw: &[String]) -> Result<()> {
+    let args = Args::parse(raw, &["format", "chunk"], &[])?;
+    if help(&args, HELP) {
+        return Ok(());
+    }
+    let [input, output] = args.positional() else {
+        return Err(usage_error("expected <input> <output>"));
+    };
+    let format = match args.value("format") {
+        Some(value) => parse_format(value)?,
+        None => DataFormat::Binary,
+    };
+    let chunk = args.parsed("chunk")?.unwrap_or(CHUNK_POINTS);
+    convert(Path::new(input), Path::new(output), format, chunk)
+}
+
+fn convert(input: &Path, output: &Path, format: DataFormat, chunk: usize) -> Result<()> {
+    let streamable = FileKind::of(input)? == FileKind::Pcd
+        && FileKind::of(output)? == FileKind::Pcd
+        && output.extension().is_none_or(|e| e != "gz")
+        && matches!(format, DataFormat::Ascii | DataFormat::Binary);
+    if streamable {
+        let mut reader = PcdReader::open(input)?;
+        let mut header = reader.header().clone();
+        header.data = format;
+        let file = BufWriter::new(File::create(output)?);
+        let mut writer = PcdStreamWriter::new(file, &header)?;
+        while let Some(block) = reader.read_chunk(chunk)? {
+            writer.write_chunk(&block)?;
+        }
+        writer.finish()?;
+        return Ok(());
+    }
+
+    let (header, block) = load(input)?;
+    let header = match header {
+        Some(mut header) => {
+            header.data = format;
+            header
+        }
+        None => header_for(&block, format)?,
+    };
+    save(output, &header, &block)
+}
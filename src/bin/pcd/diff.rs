@@ -0,0 +1,145 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `pcd diff`: compare two PCD files field by field.
+
+use crate::args::Args;
+use crate::{CHUNK_POINTS, help, usage_error};
+use rs_pcd::io::PcdReader;
+use rs_pcd::storage::{CloudDiff, PointBlock, Tolerance};
+use rs_pcd::{PcdError, Result};
+use std::io::BufRead;
+
+const HELP: &str = "\
+Usage: pcd diff [options] <a> <b>
+
+Compare two PCD files: fields only in one of them, type changes, point
+counts and, for every common field, the largest absolute and relative error.
+Values are equal when within either tolerance; NaN equals NaN. Exits with
+status 1 if the files differ. Data is decoded in chunks.
+
+Options:
+  --abs <tol>     Absolute tolerance [default: 0]
+  --rel <tol>     Relative tolerance [default: 0]
+  --chunk <N>     Points decoded per chunk [default: 65536]";
+
+pub fn run(raw: &[String]) -> Result<()> {
+    let args = Args::parse(raw, &["abs", "rel", "chunk"], &[])?;
+    if help(&args, HELP) {
+        return Ok(());
+    }
+    let [a, b] = args.positional() else {
+        return Err(usage_error("expected <a> <b>"));
+    };
+    let tolerance = Tolerance::new(
+        args.parsed("abs")?.unwrap_or(0.0),
+        args.parsed("rel")?.unwrap_or(0.0),
+    );
+    let chunk = args.parsed("chunk")?.unwrap_or(CHUNK_POINTS);
+
+    let diff = diff(a, b, tolerance, chunk)?;
+    print(&diff);
+    if diff.is_match() {
+        Ok(())
+    } else {
+        Err(PcdError::Other("files differ".to_string()))
+    }
+}
+
+/// Chunks of one file, consumed a point range at a time.
+struct Chunks<R: BufRead> {
+    reader: PcdReader<R>,
+    block: PointBlock,
+    pos: usize,
+    chunk: usize,
+}
+
+impl<R: BufRead> Chunks<R> {
+    /// Points left in the current chunk, reading the next one if needed.
+    fn available(&mut self) -> Result<usize> {
+        if self.pos == self.block.len {
+            match self.reader.read_chunk(self.chunk)? {
+                Some(block) => (self.block, self.pos) = (block, 0),
+                None => return Ok(0),
+            }
+        }
+        Ok(self.block.len - self.pos)
+    }
+
+    fn take(&mut self, n: usize) -> PointBlock {
+        let block = self.block.slice(self.pos..self.pos + n);
+        self.pos += n;
+        block
+    }
+}
+
+fn diff(a: &str, b: &str, tolerance: Tolerance, chunk: usize) -> Result<CloudDiff> {
+    let (a, b) = (PcdReader::open(a)?, PcdReader::open(b)?);
+    let (points_a, points_b) = (a.header().points, b.header().points);
+    let mut diff = CloudDiff::new(&a.header().schema()?, &b.header().schema()?, tolerance);
+
+    // Compressed data arrives as one chunk, so the two sides may be chunked
+    // differently; compare their overlap each time.
+    let mut a = Chunks {
+        reader: a,
+        block: PointBlock::default(),
+        pos: 0,
+        chunk,
+    };
+    let mut b = Chunks {
+        reader: b,
+        block: PointBlock::default(),
+        pos: 0,
+        chunk,
+    };
+    loop {
+        let n = a.available()?.min(b.available()?);
+        if n == 0 {
+            break;
+        }
+        diff.compare(&a.take(n), &b.take(n));
+    }
+    diff.points_a = points_a;
+    diff.points_b = points_b;
+    Ok(diff)
+}
+
+fn print(diff: &CloudDiff) {
+    for name in &diff.only_in_a {
+        println!("only in a   {}", name);
+    }
+    for name in &diff.only_in_b {
+        println!("only in b   {}", name);
+    }
+    for (name, a, b) in &diff.type_changes {
+        println!("type        {} {:?} -> {:?}", name, a, b);
+    }
+    println!("points      {} {}", diff.points_a, diff.points_b);
+    println!(
+        "{:<16} {:>14} {:>14} {:>10} {:>10}",
+        "field", "max abs", "max rel", "mismatch", "first"
+    );
+    for field in &diff.fields {
+        println!(
+            "{:<16} {:>14} {:>14} {:>10} {:>10}",
+            field.name,
+            field.max_abs,
+            field.max_rel,
+            field.mismatches,
+            field
+                .first_mismatch
+                .map_or("-".to_string(), |i| i.to_string())
+        );
+    }
+}
@@ -0,0 +1,125 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `pcd filter`: keep the points matching field predicates, a box and/or
+//! having no NaNs.
+
+use crate::args::Args;
+use crate::{CHUNK_POINTS, FileKind, header_for, help, load, parse_format, save, usage_error};
+use rs_pcd::io::{PcdReader, PcdStreamWriter};
+use rs_pcd::storage::{BoundingBox, PointFilter};
+use rs_pcd::{DataFormat, Result};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+const HELP: &str = "\
+Usage: pcd filter [options] <input> <output>
+
+Keep the points meeting every given condition. The output is unorganized.
+PCD to ascii or binary PCD is streamed in two passes (count, then write);
+other conversions hold the cloud in memory.
+
+Options:
+  --where <expr>      Field predicate such as \"intensity>0.1\" or \"ring=3\";
+                      operators <, <=, >, >=, =, !=. Repeatable.
+  --crop <box>        Keep points inside xmin,ymin,zmin,xmax,ymax,zmax
+  --remove-nan        Drop points with a NaN in any float field
+  --format <fmt>      PCD output format: ascii, binary or binary_compressed
+                      [default: binary]
+  --chunk <N>         Points per chunk when streaming [default: 65536]";
+
+pub fn run(raw: &[String]) -> Result<()> {
+    let args = Args::parse(raw, &["where", "crop", "format", "chunk"], &["remove-nan"])?;
+    if help(&args, HELP) {
+        return Ok(());
+    }
+    let [input, output] = args.positional() else {
+        return Err(usage_error("expected <input> <output>"));
+    };
+    let mut filter = PointFilter::new().drop_nan(args.flag("remove-nan"));
+    for expr in args.values("where") {
+        filter = filter.predicate(expr.parse()?);
+    }
+    if let Some(value) = args.value("crop") {
+        filter = filter.crop(parse_box(value)?);
+    }
+    let format = match args.value("format") {
+        Some(value) => parse_format(value)?,
+        None => DataFormat::Binary,
+    };
+    let chunk = args.parsed("chunk")?.unwrap_or(CHUNK_POINTS);
+    run_filter(Path::new(input), Path::new(output), &filter, format, chunk)
+}
+
+fn parse_box(value: &str) -> Result<BoundingBox> {
+    let bounds: Vec<f64> = value
+        .split(',')
+        .map(|v| v.trim().parse())
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|_| usage_error(format!("invalid --crop box `{}`", value)))?;
+    let [x0, y0, z0, x1, y1, z1] = bounds[..] else {
+        return Err(usage_error("--crop needs xmin,ymin,zmin,xmax,ymax,zmax"));
+    };
+    Ok(BoundingBox::new([x0, y0, z0], [x1, y1, z1]))
+}
+
+fn run_filter(
+    input: &Path,
+    output: &Path,
+    filter: &PointFilter,
+    format: DataFormat,
+    chunk: usize,
+) -> Result<()> {
+    let streamable = FileKind::of(input)? == FileKind::Pcd
+        && FileKind::of(output)? == FileKind::Pcd
+        && output.extension().is_none_or(|e| e != "gz")
+        && matches!(format, DataFormat::Ascii | DataFormat::Binary);
+    if streamable {
+        // The header needs the point count, so count the survivors first.
+        let mut reader = PcdReader::open(input)?;
+        let mut kept = 0;
+        while let Some(block) = reader.read_chunk(chunk)? {
+            kept += filter.mask(&block)?.iter().filter(|&&k| k).count();
+        }
+
+        let mut reader = PcdReader::open(input)?;
+        let mut header = reader.header().clone();
+        header.width = kept as u32;
+        header.height = 1;
+        header.points = kept;
+        header.data = format;
+        let file = BufWriter::new(File::create(output)?);
+        let mut writer = PcdStreamWriter::new(file, &header)?;
+        while let Some(block) = reader.read_chunk(chunk)? {
+            writer.write_chunk(&filter.apply(&block)?)?;
+        }
+        writer.finish()?;
+        return Ok(());
+    }
+
+    let (header, block) = load(input)?;
+    let block = filter.apply(&block)?;
+    let header = match header {
+        Some(mut header) => {
+            header.width = block.len as u32;
+            header.height = 1;
+            header.points = block.len;
+            header.data = format;
+            header
+        }
+        None => header_for(&block, format)?,
+    };
+    save(output, &header, &block)
+}
@@ -0,0 +1,133 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `pcd info`: header summary and per-field statistics.
+
+use crate::args::Args;
+use crate::{CHUNK_POINTS, help, usage_error};
+use rs_pcd::Result;
+use rs_pcd::io::PcdReader;
+use rs_pcd::storage::ColumnStats;
+
+const HELP: &str = "\
+Usage: pcd info [options] <file>...
+
+Print the header, point count, estimated decoded size and, for every field,
+min/max and NaN count. Data is decoded in chunks, so files of any size work.
+
+Options:
+  --header-only   Skip the data section (no statistics)
+  --chunk <N>     Points decoded per chunk [default: 65536]";
+
+pub fn run(raw: &[String]) -> Result<()> {
+    let args = Args::parse(raw, &["chunk"], &["header-only"])?;
+    if help(&args, HELP) {
+        return Ok(());
+    }
+    if args.positional().is_empty() {
+        return Err(usage_error("no input files"));
+    }
+    let chunk = args.parsed("chunk")?.unwrap_or(CHUNK_POINTS);
+
+    for (i, path) in args.positional().iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        info(path, chunk, args.flag("header-only"))?;
+    }
+    Ok(())
+}
+
+fn info(path: &str, chunk: usize, header_only: bool) -> Result<()> {
+    let mut reader = PcdReader::open(path)?;
+    let header = reader.header().clone();
+
+    println!("{}", path);
+    println!("  version     {}", header.version);
+    println!("  data        {}", header.data);
+    println!(
+        "  size        {} x {} ({})",
+        header.width,
+        header.height,
+        if header.is_organized() {
+            "organized"
+        } else {
+            "unorganized"
+        }
+    );
+    println!("  points      {}", header.points);
+    let vp: Vec<String> = header.viewpoint.iter().map(|v| v.to_string()).collect();
+    println!("  viewpoint   {}", vp.join(" "));
+    println!("  point step  {} bytes", header.total_point_step());
+    println!(
+        "  memory      {} (decoded, estimated)",
+        human_bytes(header.points * header.total_point_step())
+    );
+
+    let mut stats = vec![ColumnStats::default(); header.fields.len()];
+    if !header_only {
+        while let Some(block) = reader.read_chunk(chunk)? {
+            for (acc, column) in stats.iter_mut().zip(block.columns()) {
+                acc.update(column);
+            }
+        }
+    }
+
+    println!(
+        "  {:<16} {:<4} {:>5} {:>14} {:>14} {:>8}",
+        "field", "type", "count", "min", "max", "nan"
+    );
+    for (i, name) in header.fields.iter().enumerate() {
+        let (min, max, nan) = if header_only {
+            ("-".to_string(), "-".to_string(), "-".to_string())
+        } else if stats[i].has_range() {
+            (
+                stats[i].min.to_string(),
+                stats[i].max.to_string(),
+                stats[i].nan_count.to_string(),
+            )
+        } else {
+            (
+                "-".to_string(),
+                "-".to_string(),
+                stats[i].nan_count.to_string(),
+            )
+        };
+        println!(
+            "  {:<16} {:<4} {:>5} {:>14} {:>14} {:>8}",
+            name,
+            format!("{}{}", header.types[i], header.sizes[i]),
+            header.counts[i],
+            min,
+            max,
+            nan
+        );
+    }
+    Ok(())
+}
+
+fn human_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
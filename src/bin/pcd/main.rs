@@ -0,0 +1,168 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `pcd` command-line tool (feature `cli`).
+
+mod args;
+mod convert;
+mod diff;
+mod filter;
+mod info;
+mod merge;
+mod validate;
+
+use rs_pcd::header::PcdHeaderBuilder;
+use rs_pcd::io::CsvOptions;
+use rs_pcd::storage::{PointBlock, PointCloud};
+use rs_pcd::{DataFormat, PcdError, PcdHeader, Result};
+use std::path::Path;
+use std::process::ExitCode;
+
+/// Points decoded per chunk when streaming.
+pub const CHUNK_POINTS: usize = 1 << 16;
+
+const USAGE: &str = "\
+Usage: pcd <command> [options]
+
+Commands:
+  info <file>...    Print header, point counts and per-field statistics
+  convert <in> <out>
+                    Convert between PCD data formats, KITTI, CSV and PTS
+  diff <a> <b>      Compare schemas, point counts and per-field errors
+  filter <in> <out> Keep points matching predicates, a crop box or no NaNs
+  merge --output <out> <in>...
+                    Concatenate PCDs with matching fields
+  validate <file>...
+                    Check header/data consistency; --repair fixes files
+
+Run `pcd <command> --help` for the options of a command.";
+
+fn main() -> ExitCode {
+    let argv: Vec<String> = std::env::args().skip(1).collect();
+    let Some((command, rest)) = argv.split_first() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    };
+    let result = match command.as_str() {
+        "info" => info::run(rest),
+        "convert" => convert::run(rest),
+        "diff" => diff::run(rest),
+        "filter" => filter::run(rest),
+        "merge" => merge::run(rest),
+        "validate" => validate::run(rest),
+        "-h" | "--help" | "help" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => {
+            eprintln!("pcd: unknown command `{}`\n\n{}", command, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("pcd {}: {}", command, e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Error for malformed command lines.
+pub fn usage_error(msg: impl Into<String>) -> PcdError {
+    PcdError::Other(msg.into())
+}
+
+/// Print a command's help text if requested; returns whether it did.
+pub fn help(args: &args::Args, text: &str) -> bool {
+    if args.flag("help") {
+        println!("{}", text);
+    }
+    args.flag("help")
+}
+
+/// Point file types, chosen by extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    /// `.pcd`, or `.pcd.gz` with the `flate2` feature.
+    Pcd,
+    /// KITTI `.bin`.
+    Kitti,
+    /// `.csv`, `.txt` or `.xyz`.
+    Csv,
+    Pts,
+}
+
+impl FileKind {
+    pub fn of(path: &Path) -> Result<Self> {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        let name = name.strip_suffix(".gz").unwrap_or(&name);
+        match name.rsplit_once('.').map(|(_, ext)| ext) {
+            Some("pcd") => Ok(FileKind::Pcd),
+            Some("bin") => Ok(FileKind::Kitti),
+            Some("csv" | "txt" | "xyz") => Ok(FileKind::Csv),
+            Some("pts") => Ok(FileKind::Pts),
+            _ => Err(usage_error(format!(
+                "cannot tell the file type of {}; use .pcd, .bin, .csv, .txt, .xyz or .pts",
+                path.display()
+            ))),
+        }
+    }
+}
+
+/// Read a whole point file of any supported kind. PCD files also return
+/// their header.
+pub fn load(path: &Path) -> Result<(Option<PcdHeader>, PointBlock)> {
+    match FileKind::of(path)? {
+        FileKind::Pcd => {
+            let (header, block) = PointCloud::open(path)?.into_parts();
+            Ok((Some(header), block))
+        }
+        FileKind::Kitti => Ok((None, rs_pcd::io::read_kitti_bin(path)?)),
+        FileKind::Csv => Ok((
+            None,
+            rs_pcd::io::read_csv_file(path, &CsvOptions::default())?,
+        )),
+        FileKind::Pts => Ok((None, rs_pcd::io::read_pts_file(path)?)),
+    }
+}
+
+/// Write `block` to a point file of any supported kind. `header` is used for
+/// PCD output.
+pub fn save(path: &Path, header: &PcdHeader, block: &PointBlock) -> Result<()> {
+    match FileKind::of(path)? {
+        FileKind::Pcd => rs_pcd::io::write_pcd_file(path, header, block),
+        FileKind::Kitti => rs_pcd::io::write_kitti_bin(path, block),
+        FileKind::Csv => rs_pcd::io::write_csv_file(path, block, &CsvOptions::default()),
+        FileKind::Pts => rs_pcd::io::write_pts_file(path, block),
+    }
+}
+
+/// Unorganized header describing the columns of `block`.
+pub fn header_for(block: &PointBlock, format: DataFormat) -> Result<PcdHeader> {
+    PcdHeaderBuilder::from_schema(block.to_schema())
+        .width(block.len as u32)
+        .data_format(format)
+        .build()
+}
+
+/// Parse a `DATA` keyword given on the command line.
+pub fn parse_format(value: &str) -> Result<DataFormat> {
+    value
+        .parse()
+        .map_err(|_| usage_error(format!("unknown data format `{}`", value)))
+}
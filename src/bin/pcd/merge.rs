@@ -0,0 +1,79 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `pcd merge`: concatenate PCDs with the same fields.
+
+use crate::args::Args;
+use crate::{CHUNK_POINTS, FileKind, help, parse_format, save, usage_error};
+use rs_pcd::io::{MergeOptions, merge_pcd_files, read_merged_pcd_files, read_pcd_header};
+use rs_pcd::{DataFormat, Result};
+use std::path::Path;
+
+const HELP: &str = "\
+Usage: pcd merge [options] --output <file> <input>...
+
+Concatenate PCD files whose fields (names, types, sizes and counts) match,
+in any order. The output is unorganized and uses the field order of the first
+input. ascii and binary PCD output is streamed chunk by chunk.
+
+Options:
+  --output <file>     Output file (required)
+  --apply-viewpoint   Transform x/y/z of each input by its VIEWPOINT; the
+                      output gets the identity viewpoint. Otherwise the
+                      first input's VIEWPOINT is kept.
+  --format <fmt>      PCD output format: ascii, binary or binary_compressed
+                      [default: binary]
+  --chunk <N>         Points per chunk when streaming [default: 65536]";
+
+pub fn run(raw: &[String]) -> Result<()> {
+    let args = Args::parse(raw, &["output", "format", "chunk"], &["apply-viewpoint"])?;
+    if help(&args, HELP) {
+        return Ok(());
+    }
+    let output = args
+        .value("output")
+        .ok_or_else(|| usage_error("--output is required"))?;
+    if args.positional().is_empty() {
+        return Err(usage_error("no input files"));
+    }
+    let format = match args.value("format") {
+        Some(value) => parse_format(value)?,
+        None => DataFormat::Binary,
+    };
+    let options = MergeOptions {
+        format,
+        apply_viewpoint: args.flag("apply-viewpoint"),
+        chunk_points: args.parsed("chunk")?.unwrap_or(CHUNK_POINTS),
+    };
+    let inputs = args.positional();
+    let output = Path::new(output);
+    if !options.apply_viewpoint {
+        let viewpoints = inputs
+            .iter()
+            .map(|input| Ok(read_pcd_header(input)?.viewpoint))
+            .collect::<Result<Vec<_>>>()?;
+        if viewpoints.iter().any(|v| *v != viewpoints[0]) {
+            eprintln!(
+                "pcd merge: warning: inputs have different VIEWPOINTs; keeping the first \
+                 (use --apply-viewpoint to transform points)"
+            );
+        }
+    }
+    if FileKind::of(output)? == FileKind::Pcd {
+        merge_pcd_files(inputs, output, &options)?;
+        return Ok(());
+    }
+    let cloud = read_merged_pcd_files(inputs, &options)?;
+    save(output, &cloud.header, &cloud.data)
+}
@@ -0,0 +1,102 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `pcd validate`: check header/data consistency and optionally repair.
+
+use crate::args::Args;
+use crate::{help, usage_error};
+use rs_pcd::io::{repair_pcd, validate_pcd};
+use rs_pcd::{PcdError, Result};
+use std::path::{Path, PathBuf};
+
+const HELP: &str = "\
+Usage: pcd validate [options] <file>...
+
+Check that POINTS matches WIDTH x HEIGHT and the data section: its length
+for binary, its lines for ascii and the block sizes for compressed data.
+Exits with status 1 if a file has problems that were not repaired.
+
+With --repair, the header is rewritten to the points actually present and
+trailing garbage is dropped. When POINTS and WIDTH x HEIGHT disagree, the one
+matching the data is kept. Files are replaced in place unless --output is
+given.
+
+Options:
+  --repair          Fix the files that have problems
+  --output <file>   Write the repaired file here (single input only)";
+
+pub fn run(raw: &[String]) -> Result<()> {
+    let args = Args::parse(raw, &["output"], &["repair"])?;
+    if help(&args, HELP) {
+        return Ok(());
+    }
+    let inputs = args.positional();
+    if inputs.is_empty() {
+        return Err(usage_error("no input files"));
+    }
+    let output = args.value("output").map(PathBuf::from);
+    if output.is_some() && (!args.flag("repair") || inputs.len() > 1) {
+        return Err(usage_error("--output needs --repair and a single input"));
+    }
+
+    let mut failed = 0;
+    for input in inputs {
+        let path = Path::new(input);
+        let validation = validate_pcd(path)?;
+        if validation.is_valid() {
+            println!("{}: ok", input);
+            continue;
+        }
+        for issue in &validation.issues {
+            println!("{}: {}", input, issue);
+        }
+        if !args.flag("repair") {
+            failed += 1;
+            continue;
+        }
+        match repair(path, output.as_deref()) {
+            Ok(()) => println!("{}: repaired", input),
+            Err(e) => {
+                println!("{}: {}", input, e);
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(PcdError::Other(format!("{} file(s) invalid", failed)));
+    }
+    Ok(())
+}
+
+fn repair(path: &Path, output: Option<&Path>) -> Result<()> {
+    // `--output` naming the input itself is an in-place repair.
+    let same_file = |output: &Path| match (path.canonicalize(), output.canonicalize()) {
+        (Ok(path), Ok(output)) => path == output,
+        _ => false,
+    };
+    if let Some(output) = output.filter(|output| !same_file(output)) {
+        repair_pcd(path, output)?;
+        return Ok(());
+    }
+    // Write next to the original, then swap it in.
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".repair");
+    let tmp = PathBuf::from(tmp);
+    if let Err(e) = repair_pcd(path, &tmp) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e);
+    }
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
@@ -0,0 +1,201 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Raw LiDAR packet captures (feature `pcap`).
+//!
+//! UDP packets from a pcap file are decoded into one `PointBlock` per sweep
+//! with the columns `x y z intensity` (F32), `ring` (U16) and `timestamp`
+//! (F64, seconds), matching [`PointBlock::xyzirt`]. Returns without a target
+//! (range 0) are dropped. The first and last sweeps of a capture are usually
+//! partial and are yielded as is.
+
+use crate::error::Result;
+use crate::header::ValueType;
+use crate::storage::PointBlock;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+pub mod ouster;
+pub mod pcap;
+pub mod velodyne;
+
+pub use ouster::OusterIntrinsics;
+pub use pcap::{PcapReader, UdpPacket};
+
+/// Sensor that produced the capture.
+#[derive(Debug, Clone)]
+pub enum SensorModel {
+    /// Velodyne VLP-16 or VLP-32C, detected from the factory bytes.
+    Velodyne,
+    /// Ouster OS-series, legacy UDP profile, with the sensor's intrinsics.
+    Ouster(OusterIntrinsics),
+}
+
+/// Options for decoding a capture.
+#[derive(Debug, Clone)]
+pub struct CaptureOptions {
+    pub model: SensorModel,
+    /// Only decode packets sent to this UDP port. `None` accepts any port
+    /// and relies on the packet size to recognize data packets.
+    pub port: Option<u16>,
+    /// Drop returns closer than this, in meters. Default is 0.
+    pub min_range: f32,
+}
+
+impl Default for CaptureOptions {
+    fn default() -> Self {
+        Self {
+            model: SensorModel::Velodyne,
+            port: None,
+            min_range: 0.0,
+        }
+    }
+}
+
+/// Decodes packets of one sensor family and splits them into sweeps.
+trait PacketDecoder {
+    /// Decode one packet, pushing sweeps completed by it to `out`.
+    fn decode(&mut self, packet: &UdpPacket, out: &mut VecDeque<PointBlock>) -> Result<()>;
+
+    /// Return the sweep in progress, if any.
+    fn flush(&mut self) -> Option<PointBlock>;
+}
+
+/// Iterator over the sweeps of a capture.
+pub struct SweepReader<R: Read> {
+    packets: PcapReader<R>,
+    decoder: Box<dyn PacketDecoder>,
+    port: Option<u16>,
+    ready: VecDeque<PointBlock>,
+    done: bool,
+}
+
+impl<R: Read> SweepReader<R> {
+    pub fn new(reader: R, options: CaptureOptions) -> Result<Self> {
+        let decoder: Box<dyn PacketDecoder> = match options.model {
+            SensorModel::Velodyne => Box::new(velodyne::VelodyneDecoder::new(options.min_range)),
+            SensorModel::Ouster(intrinsics) => {
+                Box::new(ouster::OusterDecoder::new(intrinsics, options.min_range))
+            }
+        };
+        Ok(Self {
+            packets: PcapReader::new(reader)?,
+            decoder,
+            port: options.port,
+            ready: VecDeque::new(),
+            done: false,
+        })
+    }
+}
+
+impl<R: Read> Iterator for SweepReader<R> {
+    type Item = Result<PointBlock>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(block) = self.ready.pop_front() {
+                return Some(Ok(block));
+            }
+            if self.done {
+                return None;
+            }
+            match self.packets.next() {
+                Some(Ok(packet)) => {
+                    if self.port.is_some_and(|p| p != packet.dst_port) {
+                        continue;
+                    }
+                    if let Err(e) = self.decoder.decode(&packet, &mut self.ready) {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                }
+                Some(Err(e)) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+                None => {
+                    self.done = true;
+                    self.ready.extend(self.decoder.flush());
+                }
+            }
+        }
+    }
+}
+
+/// Open a pcap file and iterate its sweeps.
+pub fn read_sweeps<P: AsRef<Path>>(
+    path: P,
+    options: CaptureOptions,
+) -> Result<SweepReader<BufReader<File>>> {
+    SweepReader::new(BufReader::new(File::open(path)?), options)
+}
+
+/// Column buffers for the sweep being assembled.
+#[derive(Debug, Default)]
+struct SweepBuffer {
+    x: Vec<f32>,
+    y: Vec<f32>,
+    z: Vec<f32>,
+    intensity: Vec<f32>,
+    ring: Vec<u16>,
+    timestamp: Vec<f64>,
+}
+
+impl SweepBuffer {
+    fn push(&mut self, p: [f32; 3], intensity: f32, ring: u16, timestamp: f64) {
+        self.x.push(p[0]);
+        self.y.push(p[1]);
+        self.z.push(p[2]);
+        self.intensity.push(intensity);
+        self.ring.push(ring);
+        self.timestamp.push(timestamp);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.x.is_empty()
+    }
+
+    /// Move the buffered points into a new block, leaving the buffer empty.
+    fn take(&mut self) -> PointBlock {
+        let schema = [
+            ("x", ValueType::F32),
+            ("y", ValueType::F32),
+            ("z", ValueType::F32),
+            ("intensity", ValueType::F32),
+            ("ring", ValueType::U16),
+            ("timestamp", ValueType::F64),
+        ]
+        .map(|(n, t)| (n.to_string(), t));
+        let mut block = PointBlock::new(&schema, 0);
+        let buf = std::mem::take(self);
+        block.len = buf.x.len();
+        *block.get_column_mut("x").unwrap().as_f32_mut().unwrap() = buf.x;
+        *block.get_column_mut("y").unwrap().as_f32_mut().unwrap() = buf.y;
+        *block.get_column_mut("z").unwrap().as_f32_mut().unwrap() = buf.z;
+        *block
+            .get_column_mut("intensity")
+            .unwrap()
+            .as_f32_mut()
+            .unwrap() = buf.intensity;
+        *block.get_column_mut("ring").unwrap().as_u16_mut().unwrap() = buf.ring;
+        *block
+            .get_column_mut("timestamp")
+            .unwrap()
+            .as_f64_mut()
+            .unwrap() = buf.timestamp;
+        block
+    }
+}
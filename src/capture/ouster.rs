@@ -0,0 +1,144 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ouster OS-series lidar packets, legacy UDP profile.
+//!
+//! A packet holds 16 measurement blocks (one azimuth column each) of
+//! `16 + 12 * channels + 4` bytes; the channel count is inferred from the
+//! packet size. Range is in millimeters, `signal` becomes the intensity and
+//! the channel index the ring. A sweep ends when the frame id changes.
+
+use super::{PacketDecoder, SweepBuffer, UdpPacket};
+use crate::error::{PcdError, Result};
+use crate::storage::PointBlock;
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+
+const COLUMNS_PER_PACKET: usize = 16;
+const VALID_BLOCK: u32 = 0xFFFF_FFFF;
+
+/// Beam geometry from the sensor metadata (`beam_intrinsics`).
+#[derive(Debug, Clone)]
+pub struct OusterIntrinsics {
+    /// Elevation of each beam in degrees, top beam first.
+    pub beam_altitude_angles: Vec<f64>,
+    /// Horizontal offset of each beam in degrees.
+    pub beam_azimuth_angles: Vec<f64>,
+    /// Columns per revolution (512, 1024 or 2048).
+    pub columns_per_frame: u32,
+    /// Distance from the lidar origin to the beam origin in millimeters.
+    pub lidar_origin_to_beam_origin_mm: f64,
+}
+
+impl OusterIntrinsics {
+    /// Evenly spaced beams over `vertical_fov` degrees centered on the
+    /// horizon, with no azimuth offsets. Useful when no metadata is at hand.
+    pub fn uniform(channels: usize, vertical_fov: f64, columns_per_frame: u32) -> Self {
+        let step = if channels > 1 {
+            vertical_fov / (channels - 1) as f64
+        } else {
+            0.0
+        };
+        Self {
+            beam_altitude_angles: (0..channels)
+                .map(|i| vertical_fov / 2.0 - step * i as f64)
+                .collect(),
+            beam_azimuth_angles: vec![0.0; channels],
+            columns_per_frame,
+            lidar_origin_to_beam_origin_mm: 0.0,
+        }
+    }
+}
+
+pub(crate) struct OusterDecoder {
+    intrinsics: OusterIntrinsics,
+    min_range: f32,
+    sweep: SweepBuffer,
+    frame_id: Option<u16>,
+}
+
+impl OusterDecoder {
+    pub(crate) fn new(intrinsics: OusterIntrinsics, min_range: f32) -> Self {
+        Self {
+            intrinsics,
+            min_range,
+            sweep: SweepBuffer::default(),
+            frame_id: None,
+        }
+    }
+}
+
+impl PacketDecoder for OusterDecoder {
+    fn decode(&mut self, packet: &UdpPacket, out: &mut VecDeque<PointBlock>) -> Result<()> {
+        let data = &packet.payload;
+        let channels = self.intrinsics.beam_altitude_angles.len();
+        let block_size = 16 + 12 * channels + 4;
+        if channels == 0 || data.len() != COLUMNS_PER_PACKET * block_size {
+            return Ok(());
+        }
+        if self.intrinsics.beam_azimuth_angles.len() != channels {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Ouster intrinsics: {} altitude angles but {} azimuth angles",
+                channels,
+                self.intrinsics.beam_azimuth_angles.len()
+            )));
+        }
+
+        let n = self.intrinsics.lidar_origin_to_beam_origin_mm;
+        let width = self.intrinsics.columns_per_frame.max(1) as f64;
+
+        for block in data.chunks_exact(block_size) {
+            let status = u32::from_le_bytes(block[block_size - 4..].try_into().unwrap());
+            if status != VALID_BLOCK {
+                continue;
+            }
+            let timestamp = u64::from_le_bytes(block[0..8].try_into().unwrap()) as f64 * 1e-9;
+            let measurement_id = u16::from_le_bytes([block[8], block[9]]) as f64;
+            let frame_id = u16::from_le_bytes([block[10], block[11]]);
+            if self.frame_id.is_some_and(|f| f != frame_id) && !self.sweep.is_empty() {
+                out.push_back(self.sweep.take());
+            }
+            self.frame_id = Some(frame_id);
+
+            let theta_encoder = 2.0 * PI * (1.0 - measurement_id / width);
+            for ch in 0..channels {
+                let rec = &block[16 + ch * 12..28 + ch * 12];
+                let range_mm =
+                    (u32::from_le_bytes(rec[0..4].try_into().unwrap()) & 0x000F_FFFF) as f64;
+                if range_mm == 0.0 || (range_mm * 1e-3) < self.min_range as f64 {
+                    continue;
+                }
+                let signal = u16::from_le_bytes([rec[6], rec[7]]);
+
+                let theta = theta_encoder - self.intrinsics.beam_azimuth_angles[ch].to_radians();
+                let phi = self.intrinsics.beam_altitude_angles[ch].to_radians();
+                let r = range_mm - n;
+                let x = r * theta.cos() * phi.cos() + n * theta_encoder.cos();
+                let y = r * theta.sin() * phi.cos() + n * theta_encoder.sin();
+                let z = r * phi.sin();
+                self.sweep.push(
+                    [(x * 1e-3) as f32, (y * 1e-3) as f32, (z * 1e-3) as f32],
+                    signal as f32,
+                    ch as u16,
+                    timestamp,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Option<PointBlock> {
+        (!self.sweep.is_empty()).then(|| self.sweep.take())
+    }
+}
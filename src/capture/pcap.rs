@@ -0,0 +1,255 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Classic libpcap file reader that yields IPv4 UDP payloads.
+//!
+//! Ethernet (with or without an 802.1Q tag), Linux cooked (SLL) and raw IP
+//! link types are understood. Fragmented IP datagrams, as produced by Ouster
+//! sensors on a standard MTU, are reassembled; at most
+//! [`MAX_PENDING_DATAGRAMS`] wait for missing fragments at a time, the oldest
+//! being dropped first.
+
+use crate::error::{PcdError, Result};
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use std::collections::{HashMap, VecDeque};
+use std::io::Read;
+
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+
+/// Largest frame accepted whatever the snapshot length, libpcap's own limit.
+const MAX_FRAME: u32 = 262_144;
+/// Largest IPv4 datagram.
+const MAX_DATAGRAM: usize = 65_535;
+/// Fragmented datagrams kept while waiting for the rest of their fragments.
+pub const MAX_PENDING_DATAGRAMS: usize = 64;
+
+/// IPv4 fragment: byte offset, payload, more-fragments flag.
+type Fragment = (usize, Vec<u8>, bool);
+/// IPv4 identification, then source and destination addresses.
+type DatagramKey = (u16, [u8; 8]);
+
+/// A UDP datagram captured at `time` (seconds since the Unix epoch).
+#[derive(Debug, Clone)]
+pub struct UdpPacket {
+    pub time: f64,
+    pub src_port: u16,
+    pub dst_port: u16,
+    pub payload: Vec<u8>,
+}
+
+/// Iterator over the UDP datagrams of a pcap stream.
+pub struct PcapReader<R: Read> {
+    reader: R,
+    big_endian: bool,
+    nanosecond: bool,
+    linktype: u32,
+    snaplen: u32,
+    // (ip id, src, dst) -> fragments as (byte offset, data, more fragments)
+    fragments: HashMap<DatagramKey, Vec<Fragment>>,
+    // Keys of `fragments`, oldest first.
+    pending: VecDeque<DatagramKey>,
+}
+
+impl<R: Read> PcapReader<R> {
+    /// Read the global header and prepare to iterate packets.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut header = [0u8; 24];
+        reader.read_exact(&mut header)?;
+        let (big_endian, nanosecond) = match header[..4] {
+            [0xd4, 0xc3, 0xb2, 0xa1] => (false, false),
+            [0xa1, 0xb2, 0xc3, 0xd4] => (true, false),
+            [0x4d, 0x3c, 0xb2, 0xa1] => (false, true),
+            [0xa1, 0xb2, 0x3c, 0x4d] => (true, true),
+            _ => {
+                return Err(PcdError::InvalidDataFormat(
+                    "Not a pcap file (pcapng is not supported)".to_string(),
+                ));
+            }
+        };
+        let read_u32 = |b: &[u8]| {
+            if big_endian {
+                BigEndian::read_u32(b)
+            } else {
+                LittleEndian::read_u32(b)
+            }
+        };
+        let snaplen = read_u32(&header[16..20]);
+        let linktype = read_u32(&header[20..24]);
+        if !matches!(
+            linktype,
+            LINKTYPE_ETHERNET | LINKTYPE_RAW | LINKTYPE_LINUX_SLL
+        ) {
+            return Err(PcdError::UnsupportedDataFormat(format!(
+                "pcap link type {}",
+                linktype
+            )));
+        }
+        Ok(Self {
+            reader,
+            big_endian,
+            nanosecond,
+            linktype,
+            snaplen,
+            fragments: HashMap::new(),
+            pending: VecDeque::new(),
+        })
+    }
+
+    fn read_u32(&self, b: &[u8]) -> u32 {
+        if self.big_endian {
+            BigEndian::read_u32(b)
+        } else {
+            LittleEndian::read_u32(b)
+        }
+    }
+
+    /// Read the next captured frame, or `None` at end of file.
+    fn next_frame(&mut self) -> Result<Option<(f64, Vec<u8>)>> {
+        let mut record = [0u8; 16];
+        match self.reader.read_exact(&mut record) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+        let sec = self.read_u32(&record[0..4]) as f64;
+        let frac = self.read_u32(&record[4..8]) as f64;
+        let incl_len = self.read_u32(&record[8..12]);
+        // Some writers leave the snapshot length at 0.
+        let limit = match self.snaplen {
+            0 => MAX_FRAME,
+            snaplen => snaplen.min(MAX_FRAME),
+        };
+        if incl_len > limit {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "pcap record of {} bytes exceeds the snapshot length {}",
+                incl_len, limit
+            )));
+        }
+        let time = sec + frac / if self.nanosecond { 1e9 } else { 1e6 };
+        let mut data = vec![0u8; incl_len as usize];
+        self.reader.read_exact(&mut data)?;
+        Ok(Some((time, data)))
+    }
+
+    /// Extract the IPv4 packet from a link-layer frame.
+    fn ip_packet<'a>(&self, frame: &'a [u8]) -> Option<&'a [u8]> {
+        let (ethertype, start) = match self.linktype {
+            LINKTYPE_ETHERNET => {
+                let mut ethertype = BigEndian::read_u16(frame.get(12..14)?);
+                let mut start = 14;
+                if ethertype == 0x8100 {
+                    ethertype = BigEndian::read_u16(frame.get(16..18)?);
+                    start = 18;
+                }
+                (ethertype, start)
+            }
+            LINKTYPE_LINUX_SLL => (BigEndian::read_u16(frame.get(14..16)?), 16),
+            _ => (0x0800, 0),
+        };
+        if ethertype != 0x0800 {
+            return None;
+        }
+        frame.get(start..)
+    }
+
+    /// Parse an IPv4 packet, reassembling fragments. Returns the complete
+    /// UDP datagram once all of its fragments have arrived. Repeated
+    /// fragments are ignored, and a datagram growing past the IPv4 maximum
+    /// is dropped.
+    fn udp_datagram(&mut self, ip: &[u8]) -> Option<Vec<u8>> {
+        if ip.len() < 20 || ip[0] >> 4 != 4 || ip[9] != 17 {
+            return None;
+        }
+        let ihl = (ip[0] & 0x0f) as usize * 4;
+        let total_len = (BigEndian::read_u16(&ip[2..4]) as usize).min(ip.len());
+        let body = ip.get(ihl..total_len)?;
+        let flags_offset = BigEndian::read_u16(&ip[6..8]);
+        let more = flags_offset & 0x2000 != 0;
+        let offset = (flags_offset & 0x1fff) as usize * 8;
+        if !more && offset == 0 {
+            return Some(body.to_vec());
+        }
+
+        let mut key_addr = [0u8; 8];
+        key_addr.copy_from_slice(&ip[12..20]);
+        let key = (BigEndian::read_u16(&ip[4..6]), key_addr);
+        if !self.fragments.contains_key(&key) {
+            if self.fragments.len() >= MAX_PENDING_DATAGRAMS {
+                if let Some(oldest) = self.pending.pop_front() {
+                    self.fragments.remove(&oldest);
+                }
+            }
+            self.pending.push_back(key);
+        }
+        if offset + body.len() > MAX_DATAGRAM {
+            self.drop_datagram(&key);
+            return None;
+        }
+        let parts = self.fragments.entry(key).or_default();
+        if let Err(i) = parts.binary_search_by_key(&offset, |p| p.0) {
+            parts.insert(i, (offset, body.to_vec(), more));
+        }
+
+        // Complete when fragments are contiguous from 0 and the last has MF=0.
+        let mut next = 0;
+        for (off, data, _) in parts.iter() {
+            if *off != next {
+                return None;
+            }
+            next += data.len();
+        }
+        if parts.last().is_some_and(|p| p.2) {
+            return None;
+        }
+        let parts = self.drop_datagram(&key)?;
+        Some(parts.into_iter().flat_map(|p| p.1).collect())
+    }
+
+    /// Forget the fragments of datagram `key`, returning them.
+    fn drop_datagram(&mut self, key: &DatagramKey) -> Option<Vec<Fragment>> {
+        self.pending.retain(|k| k != key);
+        self.fragments.remove(key)
+    }
+}
+
+impl<R: Read> Iterator for PcapReader<R> {
+    type Item = Result<UdpPacket>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (time, frame) = match self.next_frame() {
+                Ok(Some(f)) => f,
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            };
+            let Some(ip) = self.ip_packet(&frame) else {
+                continue;
+            };
+            let Some(udp) = self.udp_datagram(ip) else {
+                continue;
+            };
+            if udp.len() < 8 {
+                continue;
+            }
+            return Some(Ok(UdpPacket {
+                time,
+                src_port: BigEndian::read_u16(&udp[0..2]),
+                dst_port: BigEndian::read_u16(&udp[2..4]),
+                payload: udp[8..].to_vec(),
+            }));
+        }
+    }
+}
@@ -0,0 +1,180 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Velodyne VLP-16 / VLP-32C data packets.
+//!
+//! Each 1206-byte packet holds 12 firing blocks of 32 returns, a top-of-hour
+//! timestamp in microseconds and two factory bytes (return mode, product
+//! id). Azimuths are interpolated across each block using the documented
+//! firing timing, and a sweep ends when the block azimuth wraps around.
+
+use super::{PacketDecoder, SweepBuffer, UdpPacket};
+use crate::error::{PcdError, Result};
+use crate::storage::PointBlock;
+use std::collections::VecDeque;
+
+/// UDP payload size of a data packet.
+pub const PACKET_SIZE: usize = 1206;
+
+const BLOCKS: usize = 12;
+const BLOCK_SIZE: usize = 100;
+const DISTANCE_UNIT: f32 = 0.002;
+const DUAL_RETURN: u8 = 0x39;
+const PRODUCT_VLP16: u8 = 0x22;
+const PRODUCT_VLP32C: u8 = 0x28;
+
+/// Elevation in degrees by laser id.
+const VLP16_ELEVATION: [f32; 16] = [
+    -15.0, 1.0, -13.0, 3.0, -11.0, 5.0, -9.0, 7.0, -7.0, 9.0, -5.0, 11.0, -3.0, 13.0, -1.0, 15.0,
+];
+const VLP32C_ELEVATION: [f32; 32] = [
+    -25.0, -1.0, -1.667, -15.639, -11.31, 0.0, -0.667, -8.843, -7.254, 0.333, -0.333, -6.148,
+    -5.333, 1.333, 0.667, -4.0, -4.667, 1.667, 1.0, -3.667, -3.333, 3.333, 2.333, -2.667, -3.0,
+    7.0, 4.667, -2.333, -2.0, 15.0, 10.333, -1.333,
+];
+/// Horizontal offset in degrees by laser id.
+const VLP32C_AZIMUTH_OFFSET: [f32; 32] = [
+    1.4, -4.2, 1.4, -1.4, 1.4, -1.4, 4.2, -1.4, 1.4, -4.2, 1.4, -1.4, 4.2, -1.4, 4.2, -1.4, 1.4,
+    -4.2, 1.4, -4.2, 4.2, -1.4, 1.4, -1.4, 1.4, -1.4, 1.4, -4.2, 4.2, -1.4, 1.4, -1.4,
+];
+
+pub(crate) struct VelodyneDecoder {
+    min_range: f32,
+    sweep: SweepBuffer,
+    last_azimuth: Option<u16>,
+    rings16: [u16; 16],
+    rings32: [u16; 32],
+}
+
+impl VelodyneDecoder {
+    pub(crate) fn new(min_range: f32) -> Self {
+        Self {
+            min_range,
+            sweep: SweepBuffer::default(),
+            last_azimuth: None,
+            rings16: rings(&VLP16_ELEVATION),
+            rings32: rings(&VLP32C_ELEVATION),
+        }
+    }
+}
+
+impl PacketDecoder for VelodyneDecoder {
+    fn decode(&mut self, packet: &UdpPacket, out: &mut VecDeque<PointBlock>) -> Result<()> {
+        let data = &packet.payload;
+        if data.len() != PACKET_SIZE {
+            return Ok(());
+        }
+        let vlp16 = match data[1205] {
+            PRODUCT_VLP16 => true,
+            PRODUCT_VLP32C => false,
+            id => {
+                return Err(PcdError::UnsupportedDataFormat(format!(
+                    "Velodyne product id 0x{:02x}",
+                    id
+                )));
+            }
+        };
+        let dual = data[1204] == DUAL_RETURN;
+        let step = if dual { 2 } else { 1 };
+
+        // Top-of-hour microseconds, anchored to the capture clock.
+        let toh = u32::from_le_bytes(data[1200..1204].try_into().unwrap()) as f64 * 1e-6;
+        let mut t0 = (packet.time / 3600.0).floor() * 3600.0 + toh;
+        if t0 - packet.time > 1800.0 {
+            t0 -= 3600.0;
+        } else if packet.time - t0 > 1800.0 {
+            t0 += 3600.0;
+        }
+
+        let azimuth: Vec<u16> = (0..BLOCKS)
+            .map(|b| u16::from_le_bytes([data[b * BLOCK_SIZE + 2], data[b * BLOCK_SIZE + 3]]))
+            .collect();
+
+        for b in 0..BLOCKS {
+            let block = &data[b * BLOCK_SIZE..(b + 1) * BLOCK_SIZE];
+            if block[0] != 0xFF || block[1] != 0xEE {
+                return Err(PcdError::InvalidDataFormat(format!(
+                    "Velodyne block {} has a bad flag",
+                    b
+                )));
+            }
+            let az = azimuth[b];
+            if self.last_azimuth.is_some_and(|last| az < last) && !self.sweep.is_empty() {
+                out.push_back(self.sweep.take());
+            }
+            self.last_azimuth = Some(az);
+
+            // Azimuth advance over one block, in hundredths of a degree.
+            let gap = if b + step < BLOCKS {
+                (azimuth[b + step] as i32 - az as i32).rem_euclid(36000)
+            } else {
+                (az as i32 - azimuth[b - step] as i32).rem_euclid(36000)
+            } as f32;
+            let sequence = (b / step) as f64;
+
+            for ch in 0..32 {
+                let rec = &block[4 + ch * 3..7 + ch * 3];
+                let distance = u16::from_le_bytes([rec[0], rec[1]]);
+                if distance == 0 {
+                    continue;
+                }
+                let range = distance as f32 * DISTANCE_UNIT;
+                if range < self.min_range {
+                    continue;
+                }
+
+                let (dt, azimuth, elevation, ring) = if vlp16 {
+                    let firing = (ch / 16) as f64;
+                    let laser = ch % 16;
+                    let offset = 55.296 * firing + 2.304 * laser as f64;
+                    (
+                        110.592 * sequence + offset,
+                        az as f32 + gap * (offset / 110.592) as f32,
+                        VLP16_ELEVATION[laser],
+                        self.rings16[laser],
+                    )
+                } else {
+                    let offset = 2.304 * (ch / 2) as f64;
+                    (
+                        55.296 * sequence + offset,
+                        az as f32
+                            + gap * (offset / 55.296) as f32
+                            + VLP32C_AZIMUTH_OFFSET[ch] * 100.0,
+                        VLP32C_ELEVATION[ch],
+                        self.rings32[ch],
+                    )
+                };
+
+                let (sin_a, cos_a) = (azimuth / 100.0).to_radians().sin_cos();
+                let (sin_e, cos_e) = elevation.to_radians().sin_cos();
+                self.sweep.push(
+                    [range * cos_e * sin_a, range * cos_e * cos_a, range * sin_e],
+                    rec[2] as f32,
+                    ring,
+                    t0 + dt * 1e-6,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Option<PointBlock> {
+        (!self.sweep.is_empty()).then(|| self.sweep.take())
+    }
+}
+
+/// Ring number of each laser: its rank by elevation, lowest first.
+fn rings<const N: usize>(elevation: &[f32; N]) -> [u16; N] {
+    elevation.map(|e| elevation.iter().filter(|&&o| o < e).count() as u16)
+}
@@ -12,16 +12,41 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::binary::Values;
 use crate::error::{PcdError, Result};
 use crate::header::ValueType;
 use crate::layout::PcdLayout;
-use crate::storage::PointBlock;
+use crate::storage::rgb::is_packed_color;
+use crate::storage::{Column, PointBlock};
 use std::io::BufRead;
 
+/// Points an [`AsciiReader`] decodes between progress reports.
+pub const PROGRESS_POINTS: usize = 1 << 16;
+
+/// Handling of ascii lines with fewer values than the fields need, as
+/// written by exporters that omit trailing zero columns. A line with no
+/// values at all is always malformed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShortLines {
+    /// Fail with `InvalidDataFormat`.
+    #[default]
+    Error,
+    /// Fill the missing values with 0.
+    Zero,
+    /// Fill missing float values with NaN and integer values with 0.
+    Nan,
+}
+
 pub struct AsciiReader<'a, R: BufRead> {
     reader: &'a mut R,
     layout: &'a PcdLayout,
     points_to_read: usize,
+    first_line: usize,
+    skip_malformed: bool,
+    short_lines: ShortLines,
+    lines_read: usize,
+    skipped: Vec<usize>,
+    progress: Option<&'a mut dyn FnMut(usize) -> Result<()>>,
 }
 
 impl<'a, R: BufRead> AsciiReader<'a, R> {
@@ -30,113 +55,224 @@ impl<'a, R: BufRead> AsciiReader<'a, R> {
             reader,
             layout,
             points_to_read,
+            first_line: 1,
+            skip_malformed: false,
+            short_lines: ShortLines::Error,
+            lines_read: 0,
+            skipped: Vec::new(),
+            progress: None,
         }
     }
 
-    pub fn decode(&mut self, output: &mut PointBlock) -> Result<()> {
-        output.resize(self.points_to_read);
-
-        let required_cols: Vec<String> =
-            self.layout.fields.iter().map(|f| f.name.clone()).collect();
-
-        // Ensure all columns exist
-        for name in &required_cols {
-            if output.get_column(name).is_none() {
-                return Err(PcdError::LayoutMismatch {
-                    expected: 0,
-                    got: 0,
-                }); // Todo: better error
-            }
-        }
+    /// Set the number of the first line read, counted from 1 at the start of
+    /// the data section, which errors report. Defaults to 1.
+    #[must_use]
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.first_line = line;
+        self
+    }
+
+    /// Skip lines that do not parse instead of failing; they are listed by
+    /// [`skipped_lines`](Self::skipped_lines). Reaching the end of the data
+    /// early then shortens the block instead of failing.
+    #[must_use]
+    pub fn skip_malformed(mut self, skip: bool) -> Self {
+        self.skip_malformed = skip;
+        self
+    }
 
-        let mut columns = output
-            .get_columns_mut(&required_cols)
-            .ok_or_else(|| PcdError::Other("Failed to mutate columns".to_string()))?;
+    /// Set how lines with too few values are handled. Defaults to
+    /// [`ShortLines::Error`].
+    #[must_use]
+    pub fn short_lines(mut self, short_lines: ShortLines) -> Self {
+        self.short_lines = short_lines;
+        self
+    }
 
-        let mut line_buffer = String::new();
+    /// Call `progress` with the number of points decoded so far after every
+    /// [`PROGRESS_POINTS`] points and at the end. An error it returns, e.g.
+    /// [`PcdError::Cancelled`], stops decoding and is returned.
+    #[must_use]
+    pub fn progress(mut self, progress: &'a mut dyn FnMut(usize) -> Result<()>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
 
-        for i in 0..self.points_to_read {
-            line_buffer.clear();
-            let bytes = self.reader.read_line(&mut line_buffer)?;
-            if bytes == 0 {
+    /// Lines consumed by [`decode`](Self::decode), including skipped ones.
+    pub fn lines_read(&self) -> usize {
+        self.lines_read
+    }
+
+    /// Numbers of the lines skipped as malformed.
+    pub fn skipped_lines(&self) -> &[usize] {
+        &self.skipped
+    }
+
+    pub fn decode(&mut self, output: &mut PointBlock) -> Result<()> {
+        let columns = super::binary::prepare_columns(self.layout, self.points_to_read, output)?;
+        let mut values: Vec<Values> = columns.into_iter().map(Values::new).collect();
+        let points = self.decode_values(&mut values)?;
+        if points < self.points_to_read {
+            super::resize_block(output, self.layout, points);
+        }
+        Ok(())
+    }
+
+    /// Decode into `columns`, the values of each field of the layout from
+    /// the first point read on, and return the number of points decoded.
+    /// That is fewer than asked only when skipping malformed lines.
+    pub(super) fn decode_values(&mut self, columns: &mut [Values<'_>]) -> Result<usize> {
+        // Lines are parsed in place from the reader's buffer; only a line
+        // split across two fills is copied into `carry`.
+        let mut carry = Vec::new();
+        let mut i = 0;
+        while i < self.points_to_read {
+            let line = self.first_line + self.lines_read;
+            let buf = self.reader.fill_buf()?;
+            if buf.is_empty() {
+                if self.skip_malformed {
+                    self.report(i)?;
+                    return Ok(i);
+                }
                 return Err(PcdError::Io(std::io::Error::new(
                     std::io::ErrorKind::UnexpectedEof,
-                    "Unexpected EOF in ASCII data",
+                    format!("Unexpected EOF in ASCII data at line {}", line),
                 )));
             }
+            let parsed = match memchr::memchr(b'\n', buf) {
+                Some(end) => {
+                    let parsed = parse_line(&buf[..end], self.layout, columns, i, self.short_lines);
+                    self.reader.consume(end + 1);
+                    parsed
+                }
+                None => {
+                    carry.clear();
+                    self.reader.read_until(b'\n', &mut carry)?;
+                    parse_line(&carry, self.layout, columns, i, self.short_lines)
+                }
+            };
+            self.lines_read += 1;
 
-            let tokens: Vec<&str> = line_buffer.split_whitespace().collect();
-            let mut token_idx = 0;
+            match parsed {
+                Ok(()) => {
+                    i += 1;
+                    if i.is_multiple_of(PROGRESS_POINTS) {
+                        self.report(i)?;
+                    }
+                }
+                Err(_) if self.skip_malformed => self.skipped.push(line),
+                Err(PcdError::InvalidDataFormat(msg)) => {
+                    return Err(PcdError::InvalidDataFormat(format!(
+                        "ASCII data line {}: {}",
+                        line, msg
+                    )));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        if !self.points_to_read.is_multiple_of(PROGRESS_POINTS) {
+            self.report(self.points_to_read)?;
+        }
+        Ok(self.points_to_read)
+    }
 
-            for (field_idx, field) in self.layout.fields.iter().enumerate() {
-                let col = &mut columns[field_idx];
-                let count = field.count;
+    fn report(&mut self, points: usize) -> Result<()> {
+        match self.progress.as_mut() {
+            Some(progress) => progress(points),
+            None => Ok(()),
+        }
+    }
+}
 
-                for k in 0..count {
-                    if token_idx >= tokens.len() {
-                        return Err(PcdError::InvalidDataFormat(format!(
-                            "Not enough tokens for point {}, field {}",
-                            i, field.name
-                        )));
-                    }
-                    let token = tokens[token_idx];
-                    token_idx += 1;
-
-                    let idx = i * count + k;
-
-                    match field.type_ {
-                        ValueType::U8 => {
-                            let val = token.parse::<u8>().map_err(|_| {
-                                PcdError::InvalidDataFormat(format!("Invalid u8: {}", token))
-                            })?;
-                            col.as_u8_mut().unwrap()[idx] = val;
-                        }
-                        ValueType::I8 => {
-                            let val = token.parse::<i8>().map_err(|_| {
-                                PcdError::InvalidDataFormat(format!("Invalid i8: {}", token))
-                            })?;
-                            col.as_i8_mut().unwrap()[idx] = val;
-                        }
-                        ValueType::U16 => {
-                            let val = token.parse::<u16>().map_err(|_| {
-                                PcdError::InvalidDataFormat(format!("Invalid u16: {}", token))
-                            })?;
-                            col.as_u16_mut().unwrap()[idx] = val;
-                        }
-                        ValueType::I16 => {
-                            let val = token.parse::<i16>().map_err(|_| {
-                                PcdError::InvalidDataFormat(format!("Invalid i16: {}", token))
-                            })?;
-                            col.as_i16_mut().unwrap()[idx] = val;
-                        }
-                        ValueType::U32 => {
-                            let val = token.parse::<u32>().map_err(|_| {
-                                PcdError::InvalidDataFormat(format!("Invalid u32: {}", token))
-                            })?;
-                            col.as_u32_mut().unwrap()[idx] = val;
-                        }
-                        ValueType::I32 => {
-                            let val = token.parse::<i32>().map_err(|_| {
-                                PcdError::InvalidDataFormat(format!("Invalid i32: {}", token))
-                            })?;
-                            col.as_i32_mut().unwrap()[idx] = val;
-                        }
-                        ValueType::F32 => {
-                            let val = token.parse::<f32>().map_err(|_| {
-                                PcdError::InvalidDataFormat(format!("Invalid f32: {}", token))
-                            })?;
-                            col.as_f32_mut().unwrap()[idx] = val;
-                        }
-                        ValueType::F64 => {
-                            let val = token.parse::<f64>().map_err(|_| {
-                                PcdError::InvalidDataFormat(format!("Invalid f64: {}", token))
-                            })?;
-                            col.as_f64_mut().unwrap()[idx] = val;
-                        }
+/// Parse the values of point `i` from one line.
+fn parse_line(
+    line: &[u8],
+    layout: &PcdLayout,
+    columns: &mut [Values<'_>],
+    i: usize,
+    short_lines: ShortLines,
+) -> Result<()> {
+    let line = core::str::from_utf8(line)
+        .map_err(|_| PcdError::InvalidDataFormat("Invalid UTF-8".to_string()))?;
+    let mut tokens = line.split_ascii_whitespace().peekable();
+    if tokens.peek().is_none() {
+        return Err(PcdError::InvalidDataFormat("Empty line".to_string()));
+    }
+    for (field, col) in layout.fields.iter().zip(columns.iter_mut()) {
+        let count = field.count;
+        for k in 0..count {
+            let idx = i * count + k;
+            match tokens.next() {
+                Some(token)
+                    if field.type_ == ValueType::F32
+                        && is_packed_color(&field.name)
+                        && token.bytes().all(|b| b.is_ascii_digit()) =>
+                {
+                    let bits = token.parse::<u32>().map_err(|_| {
+                        PcdError::InvalidDataFormat(format!("Invalid packed color: {}", token))
+                    })?;
+                    if let Values::F32(v) = col {
+                        v[idx] = f32::from_bits(bits);
                     }
                 }
+                Some(token) => parse_value(col, idx, token)?,
+                None if short_lines != ShortLines::Error => fill_value(col, idx, short_lines),
+                None => {
+                    return Err(PcdError::InvalidDataFormat(format!(
+                        "Not enough tokens for field {}",
+                        field.name
+                    )));
+                }
             }
         }
-        Ok(())
     }
+    Ok(())
+}
+
+/// Store the fill value for a value missing from a short line.
+fn fill_value(col: &mut Values, idx: usize, short_lines: ShortLines) {
+    let nan = short_lines == ShortLines::Nan;
+    match col {
+        Values::U8(v) => v[idx] = 0,
+        Values::U16(v) => v[idx] = 0,
+        Values::U32(v) => v[idx] = 0,
+        Values::U64(v) => v[idx] = 0,
+        Values::I8(v) => v[idx] = 0,
+        Values::I16(v) => v[idx] = 0,
+        Values::I32(v) => v[idx] = 0,
+        Values::I64(v) => v[idx] = 0,
+        Values::F32(v) => v[idx] = if nan { f32::NAN } else { 0.0 },
+        Values::F64(v) => v[idx] = if nan { f64::NAN } else { 0.0 },
+    }
+}
+
+/// Parse a single ASCII token and store it at `idx` in `col`, as the
+/// column's type. Floats go through core's parser, which implements the
+/// Eisel-Lemire fast path.
+pub(crate) fn parse_token(col: &mut Column, idx: usize, token: &str) -> Result<()> {
+    parse_value(&mut Values::new(col), idx, token)
+}
+
+/// [`parse_token`] into a range of a column.
+fn parse_value(values: &mut Values, idx: usize, token: &str) -> Result<()> {
+    macro_rules! parse {
+        ($v:expr, $t:ty) => {
+            $v[idx] = token.parse::<$t>().map_err(|_| {
+                PcdError::InvalidDataFormat(format!("Invalid {}: {}", stringify!($t), token))
+            })?
+        };
+    }
+    match values {
+        Values::U8(v) => parse!(v, u8),
+        Values::U16(v) => parse!(v, u16),
+        Values::U32(v) => parse!(v, u32),
+        Values::U64(v) => parse!(v, u64),
+        Values::I8(v) => parse!(v, i8),
+        Values::I16(v) => parse!(v, i16),
+        Values::I32(v) => parse!(v, i32),
+        Values::I64(v) => parse!(v, i64),
+        Values::F32(v) => parse!(v, f32),
+        Values::F64(v) => parse!(v, f64),
+    }
+    Ok(())
 }
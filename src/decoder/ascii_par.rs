@@ -0,0 +1,133 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::ascii::{AsciiReader, ShortLines};
+use super::binary::Values;
+use crate::error::{PcdError, Result};
+use crate::layout::PcdLayout;
+use crate::storage::PointBlock;
+use rayon::prelude::*;
+
+// Chunks per rayon thread, so uneven lines still balance.
+const CHUNKS_PER_THREAD: usize = 4;
+
+/// Decodes an in-memory ascii data section by splitting it into line-aligned
+/// chunks. Each chunk is parsed by an [`AsciiReader`] straight into its
+/// range of the output columns.
+pub struct AsciiParallelDecoder<'a> {
+    layout: &'a PcdLayout,
+    points: usize,
+    first_line: usize,
+    short_lines: ShortLines,
+}
+
+impl<'a> AsciiParallelDecoder<'a> {
+    pub fn new(layout: &'a PcdLayout, points: usize) -> Self {
+        Self {
+            layout,
+            points,
+            first_line: 1,
+            short_lines: ShortLines::Error,
+        }
+    }
+
+    /// See [`AsciiReader::at_line`].
+    #[must_use]
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.first_line = line;
+        self
+    }
+
+    /// See [`AsciiReader::short_lines`].
+    #[must_use]
+    pub fn short_lines(mut self, short_lines: ShortLines) -> Self {
+        self.short_lines = short_lines;
+        self
+    }
+
+    pub fn decode_par(&self, data: &[u8], output: &mut PointBlock) -> Result<()> {
+        let columns = super::binary::prepare_columns(self.layout, self.points, output)?;
+
+        let chunks = split_lines(data, rayon::current_num_threads() * CHUNKS_PER_THREAD);
+        let lines: Vec<usize> = chunks.par_iter().map(|chunk| count_lines(chunk)).collect();
+        let total: usize = lines.iter().sum();
+        if total < self.points {
+            return Err(PcdError::Io(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!(
+                    "Unexpected EOF in ASCII data at line {}",
+                    self.first_line + total
+                ),
+            )));
+        }
+
+        // Each task parses the lines of a chunk into the values of its
+        // points in every column; chunks past `points` are not parsed.
+        let mut rest: Vec<Values> = columns.into_iter().map(Values::new).collect();
+        let mut tasks = Vec::with_capacity(chunks.len());
+        let mut start = 0;
+        for (&chunk, &lines) in chunks.iter().zip(&lines) {
+            let n = self.points.saturating_sub(start).min(lines);
+            if n == 0 {
+                break;
+            }
+            let values: Vec<Values> = rest
+                .iter_mut()
+                .zip(&self.layout.fields)
+                .map(|(v, field)| v.take_front(n * field.count))
+                .collect();
+            tasks.push((chunk, start, n, values));
+            start += n;
+        }
+
+        let results: Vec<Result<usize>> = tasks
+            .into_par_iter()
+            .map(|(mut chunk, start, n, mut values)| {
+                AsciiReader::new(&mut chunk, self.layout, n)
+                    .at_line(self.first_line + start)
+                    .short_lines(self.short_lines)
+                    .decode_values(&mut values)
+            })
+            .collect();
+        // The error of the earliest chunk, as a sequential decode reports.
+        for result in results {
+            result?;
+        }
+        Ok(())
+    }
+}
+
+/// Split `data` into about `parts` pieces, each ending just after a newline
+/// (or at the end of `data`).
+fn split_lines(data: &[u8], parts: usize) -> Vec<&[u8]> {
+    let target = data.len().div_ceil(parts.max(1)).max(1);
+    let mut chunks = Vec::with_capacity(parts);
+    let mut rest = data;
+    while !rest.is_empty() {
+        let end = match memchr::memchr(b'\n', &rest[target.min(rest.len() - 1)..]) {
+            Some(pos) => target.min(rest.len() - 1) + pos + 1,
+            None => rest.len(),
+        };
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks
+}
+
+/// Lines in a chunk, counting an unterminated last line.
+fn count_lines(chunk: &[u8]) -> usize {
+    let newlines = memchr::memchr_iter(b'\n', chunk).count();
+    newlines + usize::from(chunk.last().is_some_and(|&b| b != b'\n'))
+}
@@ -14,91 +14,292 @@
 
 use crate::error::{PcdError, Result};
 use crate::header::ValueType;
-use crate::layout::PcdLayout;
-use crate::storage::PointBlock;
+use crate::layout::{FieldLayout, PcdLayout};
+use crate::storage::{Column, PointBlock};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use std::io::Read;
 
-/// Batch size for buffered reading - minimizes syscalls while keeping memory footprint reasonable
-const BATCH_SIZE: usize = 1024;
+/// Default bytes read per batch - minimizes syscalls while keeping memory footprint reasonable
+#[cfg(feature = "std")]
+pub const DEFAULT_BATCH_BYTES: usize = 256 << 10;
 
+/// Bytes of records `decode_binary` decodes at a time.
+const SLICE_RUN: usize = 256 << 10;
+
+#[cfg(feature = "std")]
 pub struct BinaryReader<'a, R: Read> {
     reader: &'a mut R,
     layout: &'a PcdLayout,
     points_to_read: usize,
+    offset: u64,
+    first_point: usize,
+    batch_bytes: usize,
+    progress: Option<&'a mut dyn FnMut(usize) -> Result<()>>,
 }
 
+#[cfg(feature = "std")]
 impl<'a, R: Read> BinaryReader<'a, R> {
     pub fn new(reader: &'a mut R, layout: &'a PcdLayout, points_to_read: usize) -> Self {
         Self {
             reader,
             layout,
             points_to_read,
+            offset: 0,
+            first_point: 0,
+            batch_bytes: DEFAULT_BATCH_BYTES,
+            progress: None,
         }
     }
 
-    pub fn decode(&mut self, output: &mut PointBlock) -> Result<()> {
-        let required_cols: Vec<String> =
-            self.layout.fields.iter().map(|f| f.name.clone()).collect();
-
-        // Ensure all columns exist
-        for name in &required_cols {
-            if output.get_column(name).is_none() {
-                return Err(PcdError::LayoutMismatch {
-                    expected: 0,
-                    got: 0,
-                });
-            }
-        }
+    /// Read about `bytes` of records at a time, rounded down to whole points
+    /// but at least one. Defaults to [`DEFAULT_BATCH_BYTES`]; larger batches
+    /// mean fewer reads from high-latency sources such as network files.
+    #[must_use]
+    pub fn batch_bytes(mut self, bytes: usize) -> Self {
+        self.batch_bytes = bytes;
+        self
+    }
 
-        output.resize(self.points_to_read);
+    /// Call `progress` with the number of points decoded so far after each
+    /// batch. An error it returns, e.g. [`PcdError::Cancelled`], stops
+    /// decoding and is returned.
+    #[must_use]
+    pub fn progress(mut self, progress: &'a mut dyn FnMut(usize) -> Result<()>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
 
-        // Get mutable references to all columns at once
-        let mut columns = output.get_columns_mut(&required_cols).ok_or_else(|| {
-            PcdError::Other("Failed to acquire columns mutable borrow".to_string())
-        })?;
+    /// Set the file offset of the data and the index of its first point,
+    /// which errors report. Both default to 0.
+    #[must_use]
+    pub fn at(mut self, offset: u64, first_point: usize) -> Self {
+        self.offset = offset;
+        self.first_point = first_point;
+        self
+    }
 
+    pub fn decode(&mut self, output: &mut PointBlock) -> Result<()> {
+        let mut columns = prepare_columns(self.layout, self.points_to_read, output)?;
         let point_step = self.layout.total_size;
-        
+
         // Batch read optimization: read multiple points at once to reduce syscalls
-        let batch_bytes = point_step * BATCH_SIZE;
-        let mut batch_buffer = vec![0u8; batch_bytes];
+        let batch_points =
+            (self.batch_bytes / point_step.max(1)).clamp(1, self.points_to_read.max(1));
+        let mut batch_buffer = vec![0u8; batch_points * point_step];
 
         let mut point_idx = 0;
         while point_idx < self.points_to_read {
-            let batch_end = (point_idx + BATCH_SIZE).min(self.points_to_read);
+            let batch_end = (point_idx + batch_points).min(self.points_to_read);
             let points_in_batch = batch_end - point_idx;
             let read_size = points_in_batch * point_step;
 
-            self.reader.read_exact(&mut batch_buffer[..read_size])?;
-
-            // Process all points in this batch
-            for batch_offset in 0..points_in_batch {
-                let buffer_start = batch_offset * point_step;
-                let i = point_idx + batch_offset;
-
-                for (field_idx, field) in self.layout.fields.iter().enumerate() {
-                    let col = &mut columns[field_idx];
-                    let start = buffer_start + field.offset;
-                    let end = start + field.size;
-                    let data = &batch_buffer[start..end];
-                    let dest_start = i * field.count;
-
-                    decode_field(col, field.type_, field.count, data, dest_start);
-                }
+            let got = read_full(self.reader, &mut batch_buffer[..read_size])?;
+            if got < read_size {
+                return Err(super::corrupt_record(
+                    self.layout,
+                    self.offset,
+                    self.first_point,
+                    point_idx * point_step + got,
+                    "unexpected end of data",
+                ));
             }
+            decode_records(
+                &mut columns,
+                self.layout,
+                &batch_buffer[..read_size],
+                point_idx,
+            );
 
             point_idx = batch_end;
+            if let Some(progress) = self.progress.as_mut() {
+                progress(point_idx)?;
+            }
         }
 
         Ok(())
     }
 }
 
+/// Fill `buf` from `reader`, returning fewer bytes only at end of input.
+#[cfg(feature = "std")]
+pub(crate) fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize> {
+    let mut got = 0;
+    while got < buf.len() {
+        match reader.read(&mut buf[got..]) {
+            Ok(0) => break,
+            Ok(n) => got += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(got)
+}
+
+/// Decode the first `points` records of `data` into `output`. Available
+/// without `std`.
+pub fn decode_binary(
+    data: &[u8],
+    layout: &PcdLayout,
+    points: usize,
+    output: &mut PointBlock,
+) -> Result<()> {
+    let needed = points.saturating_mul(layout.total_size);
+    if data.len() < needed {
+        return Err(PcdError::BufferTooSmall {
+            expected: needed,
+            got: data.len(),
+        });
+    }
+    let mut columns = prepare_columns(layout, points, output)?;
+    // Runs of records small enough to stay in cache while each field is
+    // gathered from them.
+    let run_points = (SLICE_RUN / layout.total_size.max(1)).max(1);
+    for (k, run) in data[..needed].chunks(run_points * layout.total_size.max(1)).enumerate() {
+        decode_records(&mut columns, layout, run, k * run_points);
+    }
+    Ok(())
+}
+
+/// Resize `output` to `points` and borrow the columns of `layout` in order.
+pub(super) fn prepare_columns<'b>(
+    layout: &PcdLayout,
+    points: usize,
+    output: &'b mut PointBlock,
+) -> Result<Vec<&'b mut Column>> {
+    let required_cols: Vec<String> = layout.fields.iter().map(|f| f.name.clone()).collect();
+    super::check_columns(output, layout)?;
+    super::resize_block(output, layout, points);
+
+    // Get mutable references to all columns at once
+    output
+        .get_columns_mut(&required_cols)
+        .ok_or_else(|| PcdError::Other("Failed to acquire columns mutable borrow".to_string()))
+}
+
+/// Decode whole records from `batch`, the first of which is point `first`.
+fn decode_records(columns: &mut [&mut Column], layout: &PcdLayout, batch: &[u8], first: usize) {
+    let point_step = layout.total_size;
+    if point_step == 0 {
+        return;
+    }
+    for (col, field) in columns.iter_mut().zip(&layout.fields) {
+        let mut values = Values::new(col);
+        values.take_front(first * field.count);
+        values.gather(field, batch, point_step);
+    }
+}
+
+/// The values of one column from some point on, which can be split so that
+/// each rayon task writes its own part of every column.
+pub(super) enum Values<'a> {
+    U8(&'a mut [u8]),
+    U16(&'a mut [u16]),
+    U32(&'a mut [u32]),
+    U64(&'a mut [u64]),
+    I8(&'a mut [i8]),
+    I16(&'a mut [i16]),
+    I32(&'a mut [i32]),
+    I64(&'a mut [i64]),
+    F32(&'a mut [f32]),
+    F64(&'a mut [f64]),
+}
+
+impl<'a> Values<'a> {
+    pub(super) fn new(column: &'a mut Column) -> Self {
+        match column {
+            Column::U8(v) => Values::U8(v),
+            Column::U16(v) => Values::U16(v),
+            Column::U32(v) => Values::U32(v),
+            Column::U64(v) => Values::U64(v),
+            Column::I8(v) => Values::I8(v),
+            Column::I16(v) => Values::I16(v),
+            Column::I32(v) => Values::I32(v),
+            Column::I64(v) => Values::I64(v),
+            Column::F32(v) => Values::F32(v),
+            Column::F64(v) => Values::F64(v),
+        }
+    }
+
+    /// Split off the first `mid` values, keeping the rest in `self`.
+    pub(super) fn take_front(&mut self, mid: usize) -> Self {
+        macro_rules! split {
+            ($variant:ident, $v:expr) => {{
+                let (front, back) = core::mem::take($v).split_at_mut(mid);
+                *$v = back;
+                Values::$variant(front)
+            }};
+        }
+        match self {
+            Values::U8(v) => split!(U8, v),
+            Values::U16(v) => split!(U16, v),
+            Values::U32(v) => split!(U32, v),
+            Values::U64(v) => split!(U64, v),
+            Values::I8(v) => split!(I8, v),
+            Values::I16(v) => split!(I16, v),
+            Values::I32(v) => split!(I32, v),
+            Values::I64(v) => split!(I64, v),
+            Values::F32(v) => split!(F32, v),
+            Values::F64(v) => split!(F64, v),
+        }
+    }
+
+    /// Gather `field` from each record of `records` into these values.
+    pub(super) fn gather(&mut self, field: &FieldLayout, records: &[u8], point_step: usize) {
+        macro_rules! gather {
+            ($v:expr, $t:ty) => {{
+                const N: usize = core::mem::size_of::<$t>();
+                if field.count == 1 {
+                    let at = field.offset;
+                    let values: &mut [$t] = $v;
+                    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+                    let (values, records) = {
+                        // Safety: the values are plain numbers, which any
+                        // bytes are a valid encoding of.
+                        let bytes = unsafe {
+                            core::slice::from_raw_parts_mut(
+                                values.as_mut_ptr() as *mut u8,
+                                core::mem::size_of_val(values),
+                            )
+                        };
+                        let done = super::simd::gather(N, records, point_step, at, bytes);
+                        (&mut values[done..], &records[done * point_step..])
+                    };
+                    for (x, record) in values.iter_mut().zip(records.chunks_exact(point_step)) {
+                        *x = <$t>::from_le_bytes(record[at..at + N].try_into().unwrap());
+                    }
+                    return;
+                }
+                let rows = records.chunks_exact(point_step);
+                for (values, record) in $v.chunks_exact_mut(field.count).zip(rows) {
+                    let src = &record[field.offset..field.offset + field.size];
+                    for (x, bytes) in values.iter_mut().zip(src.chunks_exact(N)) {
+                        *x = <$t>::from_le_bytes(bytes.try_into().unwrap());
+                    }
+                }
+            }};
+        }
+        match self {
+            Values::U8(v) => gather!(v, u8),
+            Values::U16(v) => gather!(v, u16),
+            Values::U32(v) => gather!(v, u32),
+            Values::U64(v) => gather!(v, u64),
+            Values::I8(v) => gather!(v, i8),
+            Values::I16(v) => gather!(v, i16),
+            Values::I32(v) => gather!(v, i32),
+            Values::I64(v) => gather!(v, i64),
+            Values::F32(v) => gather!(v, f32),
+            Values::F64(v) => gather!(v, f64),
+        }
+    }
+}
+
 /// Decode a single field from raw bytes into the column.
 /// Uses platform-optimized path for Little Endian systems.
 #[inline]
-fn decode_field(
-    col: &mut crate::storage::Column,
+pub(super) fn decode_field(
+    col: &mut Column,
     value_type: ValueType,
     count: usize,
     data: &[u8],
@@ -127,10 +328,18 @@ fn decode_field(
             let vec = col.as_u32_mut().unwrap();
             decode_u32_slice(&data[..count * 4], &mut vec[dest_start..dest_start + count]);
         }
+        ValueType::U64 => {
+            let vec = col.as_u64_mut().unwrap();
+            decode_u64_slice(&data[..count * 8], &mut vec[dest_start..dest_start + count]);
+        }
         ValueType::I32 => {
             let vec = col.as_i32_mut().unwrap();
             decode_i32_slice(&data[..count * 4], &mut vec[dest_start..dest_start + count]);
         }
+        ValueType::I64 => {
+            let vec = col.as_i64_mut().unwrap();
+            decode_i64_slice(&data[..count * 8], &mut vec[dest_start..dest_start + count]);
+        }
         ValueType::F32 => {
             let vec = col.as_f32_mut().unwrap();
             decode_f32_slice(&data[..count * 4], &mut vec[dest_start..dest_start + count]);
@@ -152,7 +361,7 @@ fn decode_f32_slice(src: &[u8], dest: &mut [f32]) {
     // On LE platforms, the byte order matches, so direct copy is valid
     assert!(src.len() >= dest.len() * 4);
     unsafe {
-        std::ptr::copy_nonoverlapping(
+        core::ptr::copy_nonoverlapping(
             src.as_ptr(),
             dest.as_mut_ptr() as *mut u8,
             dest.len() * 4,
@@ -174,7 +383,7 @@ fn decode_f32_slice(src: &[u8], dest: &mut [f32]) {
 fn decode_f64_slice(src: &[u8], dest: &mut [f64]) {
     assert!(src.len() >= dest.len() * 8);
     unsafe {
-        std::ptr::copy_nonoverlapping(
+        core::ptr::copy_nonoverlapping(
             src.as_ptr(),
             dest.as_mut_ptr() as *mut u8,
             dest.len() * 8,
@@ -196,7 +405,7 @@ fn decode_f64_slice(src: &[u8], dest: &mut [f64]) {
 fn decode_u16_slice(src: &[u8], dest: &mut [u16]) {
     assert!(src.len() >= dest.len() * 2);
     unsafe {
-        std::ptr::copy_nonoverlapping(
+        core::ptr::copy_nonoverlapping(
             src.as_ptr(),
             dest.as_mut_ptr() as *mut u8,
             dest.len() * 2,
@@ -218,7 +427,7 @@ fn decode_u16_slice(src: &[u8], dest: &mut [u16]) {
 fn decode_i16_slice(src: &[u8], dest: &mut [i16]) {
     assert!(src.len() >= dest.len() * 2);
     unsafe {
-        std::ptr::copy_nonoverlapping(
+        core::ptr::copy_nonoverlapping(
             src.as_ptr(),
             dest.as_mut_ptr() as *mut u8,
             dest.len() * 2,
@@ -240,7 +449,7 @@ fn decode_i16_slice(src: &[u8], dest: &mut [i16]) {
 fn decode_u32_slice(src: &[u8], dest: &mut [u32]) {
     assert!(src.len() >= dest.len() * 4);
     unsafe {
-        std::ptr::copy_nonoverlapping(
+        core::ptr::copy_nonoverlapping(
             src.as_ptr(),
             dest.as_mut_ptr() as *mut u8,
             dest.len() * 4,
@@ -257,12 +466,34 @@ fn decode_u32_slice(src: &[u8], dest: &mut [u32]) {
     }
 }
 
+#[cfg(target_endian = "little")]
+#[inline]
+fn decode_u64_slice(src: &[u8], dest: &mut [u64]) {
+    assert!(src.len() >= dest.len() * 8);
+    unsafe {
+        core::ptr::copy_nonoverlapping(
+            src.as_ptr(),
+            dest.as_mut_ptr() as *mut u8,
+            dest.len() * 8,
+        );
+    }
+}
+
+#[cfg(not(target_endian = "little"))]
+#[inline]
+fn decode_u64_slice(src: &[u8], dest: &mut [u64]) {
+    use byteorder::{ByteOrder, LittleEndian};
+    for (i, chunk) in src.chunks_exact(8).enumerate() {
+        dest[i] = LittleEndian::read_u64(chunk);
+    }
+}
+
 #[cfg(target_endian = "little")]
 #[inline]
 fn decode_i32_slice(src: &[u8], dest: &mut [i32]) {
     assert!(src.len() >= dest.len() * 4);
     unsafe {
-        std::ptr::copy_nonoverlapping(
+        core::ptr::copy_nonoverlapping(
             src.as_ptr(),
             dest.as_mut_ptr() as *mut u8,
             dest.len() * 4,
@@ -278,3 +509,25 @@ fn decode_i32_slice(src: &[u8], dest: &mut [i32]) {
         dest[i] = LittleEndian::read_i32(chunk);
     }
 }
+
+#[cfg(target_endian = "little")]
+#[inline]
+fn decode_i64_slice(src: &[u8], dest: &mut [i64]) {
+    assert!(src.len() >= dest.len() * 8);
+    unsafe {
+        core::ptr::copy_nonoverlapping(
+            src.as_ptr(),
+            dest.as_mut_ptr() as *mut u8,
+            dest.len() * 8,
+        );
+    }
+}
+
+#[cfg(not(target_endian = "little"))]
+#[inline]
+fn decode_i64_slice(src: &[u8], dest: &mut [i64]) {
+    use byteorder::{ByteOrder, LittleEndian};
+    for (i, chunk) in src.chunks_exact(8).enumerate() {
+        dest[i] = LittleEndian::read_i64(chunk);
+    }
+}
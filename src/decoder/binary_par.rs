@@ -12,17 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::binary::Values;
 use crate::error::{PcdError, Result};
-use crate::header::ValueType;
 use crate::layout::PcdLayout;
 use crate::storage::PointBlock;
-use byteorder::{ByteOrder, LittleEndian};
 use rayon::prelude::*;
 
-// Wrapper to make raw pointers Sync+Send for Rayon
-struct SyncPtr(*mut u8);
-unsafe impl Sync for SyncPtr {}
-unsafe impl Send for SyncPtr {}
+/// Bytes of records each rayon task decodes.
+const DECODE_TASK: usize = 256 << 10;
 
 pub struct BinaryParallelDecoder<'a> {
     layout: &'a PcdLayout,
@@ -44,116 +41,34 @@ impl<'a> BinaryParallelDecoder<'a> {
             });
         }
 
-        output.resize(self.points);
-
-        // Collect raw pointers for columns
-        let mut col_ptrs = Vec::new();
-        for field in &self.layout.fields {
-            if let Some(col) = output.get_column_mut(&field.name) {
-                let (ptr, _len_bytes) = unsafe { col.as_ptr_mut() };
-                // Calculate length in elements (already consistent with resize)
-                let len = col.len();
-                col_ptrs.push((field, SyncPtr(ptr), len, field.type_));
-            }
+        // Missing columns would leave their field undecoded, and columns of
+        // another type would be filled from the wrong bytes.
+        let columns = super::binary::prepare_columns(self.layout, self.points, output)?;
+        if point_step == 0 {
+            return Ok(());
         }
 
-        // Rayon parallel loop
-        // Input data is AoS. size = points * stride.
-        // We iterate over chunks of bytes corresponding to points concurrently.
-        data.par_chunks_exact(point_step)
-            .enumerate()
-            .for_each(|(i, point_data)| {
-                for (field, ptr_wrapper, len, vtype) in &col_ptrs {
-                    let ptr = ptr_wrapper.0;
-
-                    if i >= *len {
-                        continue;
-                    }
-
-                    let field_offset_in_point = field.offset;
-                    let src_slice =
-                        &point_data[field_offset_in_point..field_offset_in_point + field.size];
+        // Input data is AoS. Each task takes a run of whole records, trailing
+        // bytes past `points` records excluded, and the matching values of
+        // every column.
+        let task_points = (DECODE_TASK / point_step).max(1);
+        let mut rest: Vec<Values> = columns.into_iter().map(Values::new).collect();
+        let mut tasks = Vec::with_capacity(self.points.div_ceil(task_points));
+        for records in data[..total_bytes].chunks(task_points * point_step) {
+            let n = records.len() / point_step;
+            let values: Vec<Values> = rest
+                .iter_mut()
+                .zip(&self.layout.fields)
+                .map(|(v, field)| v.take_front(n * field.count))
+                .collect();
+            tasks.push((records, values));
+        }
 
-                    match vtype {
-                        ValueType::U8 => {
-                            let u8_ptr = ptr;
-                            for k in 0..field.count {
-                                unsafe {
-                                    *u8_ptr.add(i * field.count + k) = src_slice[k];
-                                }
-                            }
-                        }
-                        ValueType::I8 => {
-                            let i8_ptr = ptr as *mut i8;
-                            for k in 0..field.count {
-                                unsafe {
-                                    *i8_ptr.add(i * field.count + k) = src_slice[k] as i8;
-                                }
-                            }
-                        }
-                        ValueType::U16 => {
-                            let u16_ptr = ptr as *mut u16;
-                            for k in 0..field.count {
-                                let offset = k * 2;
-                                let val = LittleEndian::read_u16(&src_slice[offset..offset + 2]);
-                                unsafe {
-                                    *u16_ptr.add(i * field.count + k) = val;
-                                }
-                            }
-                        }
-                        ValueType::I16 => {
-                            let i16_ptr = ptr as *mut i16;
-                            for k in 0..field.count {
-                                let offset = k * 2;
-                                let val = LittleEndian::read_i16(&src_slice[offset..offset + 2]);
-                                unsafe {
-                                    *i16_ptr.add(i * field.count + k) = val;
-                                }
-                            }
-                        }
-                        ValueType::U32 => {
-                            let u32_ptr = ptr as *mut u32;
-                            for k in 0..field.count {
-                                let offset = k * 4;
-                                let val = LittleEndian::read_u32(&src_slice[offset..offset + 4]);
-                                unsafe {
-                                    *u32_ptr.add(i * field.count + k) = val;
-                                }
-                            }
-                        }
-                        ValueType::I32 => {
-                            let i32_ptr = ptr as *mut i32;
-                            for k in 0..field.count {
-                                let offset = k * 4;
-                                let val = LittleEndian::read_i32(&src_slice[offset..offset + 4]);
-                                unsafe {
-                                    *i32_ptr.add(i * field.count + k) = val;
-                                }
-                            }
-                        }
-                        ValueType::F32 => {
-                            let f32_ptr = ptr as *mut f32;
-                            for k in 0..field.count {
-                                let offset = k * 4;
-                                let val = LittleEndian::read_f32(&src_slice[offset..offset + 4]);
-                                unsafe {
-                                    *f32_ptr.add(i * field.count + k) = val;
-                                }
-                            }
-                        }
-                        ValueType::F64 => {
-                            let f64_ptr = ptr as *mut f64;
-                            for k in 0..field.count {
-                                let offset = k * 8;
-                                let val = LittleEndian::read_f64(&src_slice[offset..offset + 8]);
-                                unsafe {
-                                    *f64_ptr.add(i * field.count + k) = val;
-                                }
-                            }
-                        }
-                    }
-                }
-            });
+        tasks.into_par_iter().for_each(|(records, mut values)| {
+            for (v, field) in values.iter_mut().zip(&self.layout.fields) {
+                v.gather(field, records, point_step);
+            }
+        });
 
         Ok(())
     }
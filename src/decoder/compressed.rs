@@ -12,178 +12,441 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::binary::decode_field;
+#[cfg(feature = "std")]
+use super::binary::read_full;
 use crate::error::{PcdError, Result};
-use crate::header::ValueType;
+use crate::header::DataFormat;
 use crate::layout::PcdLayout;
-use crate::storage::PointBlock;
-use byteorder::{ByteOrder, LittleEndian, ReadBytesExt};
-use lzf;
+use crate::storage::{Column, PointBlock};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use byteorder::{ByteOrder, LittleEndian};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "std")]
 use std::io::Read;
 
+/// Compression codec of a `binary_compressed`-style data section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    Lzf,
+    /// `DATA binary_zstd` extension.
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Codec {
+    /// The codec of a compressed `format`, `None` for ASCII and binary.
+    #[must_use]
+    pub fn of(format: DataFormat) -> Option<Codec> {
+        match format {
+            DataFormat::BinaryCompressed => Some(Codec::Lzf),
+            #[cfg(feature = "zstd")]
+            DataFormat::BinaryZstd => Some(Codec::Zstd),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
 pub struct CompressedReader<'a, R: Read> {
     reader: &'a mut R,
     layout: &'a PcdLayout,
     points_to_read: usize,
+    codec: Codec,
+    offset: u64,
+    first_point: usize,
 }
 
+#[cfg(feature = "std")]
 impl<'a, R: Read> CompressedReader<'a, R> {
     pub fn new(reader: &'a mut R, layout: &'a PcdLayout, points_to_read: usize) -> Self {
         Self {
             reader,
             layout,
             points_to_read,
+            codec: Codec::Lzf,
+            offset: 0,
+            first_point: 0,
         }
     }
 
+    /// Set the file offset of the data and the index of its first point,
+    /// which errors report. Both default to 0.
+    #[must_use]
+    pub fn at(mut self, offset: u64, first_point: usize) -> Self {
+        self.offset = offset;
+        self.first_point = first_point;
+        self
+    }
+
+    /// Decode with `codec` instead of LZF.
+    #[must_use]
+    pub fn with_codec(mut self, codec: Codec) -> Self {
+        self.codec = codec;
+        self
+    }
+
     pub fn decode(&mut self, output: &mut PointBlock) -> Result<()> {
-        let compressed_size = self.reader.read_u32::<LittleEndian>()? as usize;
-        let uncompressed_size = self.reader.read_u32::<LittleEndian>()? as usize;
+        let mut sizes = [0u8; 8];
+        let got = read_full(self.reader, &mut sizes)?;
+        if got < 8 {
+            return Err(self.truncated(got));
+        }
+        let compressed_size = LittleEndian::read_u32(&sizes[..4]) as usize;
+        let uncompressed_size = LittleEndian::read_u32(&sizes[4..]) as usize;
 
         let mut compressed_data = vec![0u8; compressed_size];
-        self.reader.read_exact(&mut compressed_data)?;
-
-        // Decompress
-        let decompressed = lzf::decompress(&compressed_data, uncompressed_size)
-            .map_err(|e| PcdError::Decompression(format!("{:?}", e)))?;
-
-        if decompressed.len() != uncompressed_size {
-            return Err(PcdError::Decompression(format!(
-                "Size mismatch: expected {}, got {}",
-                uncompressed_size,
-                decompressed.len()
-            )));
+        let got = read_full(self.reader, &mut compressed_data)?;
+        if got < compressed_size {
+            return Err(self.truncated(8 + got));
         }
 
-        // Validate buffer size against layout
-        // SoA layout: sum(field.count * sizeof(type) * num_points)
-        // Wait, layout.total_size is per-point size (stride).
-        // Total bytes should be layout.total_size * points_to_read.
-        let expected_bytes = self.layout.total_size * self.points_to_read;
-        if uncompressed_size != expected_bytes {
-            return Err(PcdError::LayoutMismatch {
-                expected: expected_bytes,
-                got: uncompressed_size,
-            });
+        decode_payload(
+            self.codec,
+            &compressed_data,
+            uncompressed_size,
+            self.layout,
+            self.points_to_read,
+            output,
+        )
+        .map_err(|e| super::relocate(e, self.offset, self.first_point))
+    }
+
+    fn truncated(&self, pos: usize) -> PcdError {
+        PcdError::Corrupt {
+            offset: self.offset + pos as u64,
+            point: self.first_point,
+            field: None,
+            msg: "unexpected end of data".to_string(),
         }
+    }
+}
+
+/// Decode a whole data section held in `data`, starting with its two size
+/// words. Available without `std`.
+pub fn decode_compressed(
+    data: &[u8],
+    layout: &PcdLayout,
+    points: usize,
+    codec: Codec,
+    output: &mut PointBlock,
+) -> Result<()> {
+    if data.len() < 8 {
+        return Err(PcdError::BufferTooSmall {
+            expected: 8,
+            got: data.len(),
+        });
+    }
+    let compressed_size = LittleEndian::read_u32(&data[..4]) as usize;
+    let uncompressed_size = LittleEndian::read_u32(&data[4..8]) as usize;
+    let compressed = data
+        .get(8..8 + compressed_size)
+        .ok_or(PcdError::BufferTooSmall {
+            expected: 8 + compressed_size,
+            got: data.len(),
+        })?;
+    decode_payload(codec, compressed, uncompressed_size, layout, points, output)
+}
+
+fn decode_payload(
+    codec: Codec,
+    compressed_data: &[u8],
+    uncompressed_size: usize,
+    layout: &PcdLayout,
+    points_to_read: usize,
+    output: &mut PointBlock,
+) -> Result<()> {
+    // Validate buffer size against layout
+    // SoA layout: sum(field.count * sizeof(type) * num_points), without the
+    // padding a record stride may add.
+    let expected_bytes = layout.packed_size() * points_to_read;
+    if uncompressed_size != expected_bytes {
+        return Err(PcdError::LayoutMismatch {
+            expected: expected_bytes,
+            got: uncompressed_size,
+        });
+    }
+
+    super::check_columns(output, layout)?;
+    super::resize_block(output, layout, points_to_read);
+    let names: Vec<String> = layout.fields.iter().map(|f| f.name.clone()).collect();
+    let columns = output
+        .get_columns_mut(&names)
+        .ok_or_else(|| PcdError::Other("Failed to acquire columns mutable borrow".to_string()))?;
+
+    // Incompressible data is stored as is, e.g. by our writer for tiny
+    // clouds; LZF output is always strictly smaller than its input.
+    if compressed_data.len() == uncompressed_size {
+        convert_fields(columns, layout, points_to_read, compressed_data);
+        return Ok(());
+    }
+
+    match codec {
+        Codec::Lzf => {}
+        #[cfg(feature = "zstd")]
+        Codec::Zstd => return decode_zstd(compressed_data, columns, layout, points_to_read),
+    }
+
+    // Offsets are relative to the size words before the payload.
+    let produced =
+        decompress_fields(compressed_data, columns, layout, points_to_read).map_err(|e| {
+            super::corrupt_column(
+                layout,
+                points_to_read,
+                e.output,
+                8 + e.input as u64,
+                0,
+                e.msg,
+            )
+        })?;
+    if produced != uncompressed_size {
+        return Err(super::corrupt_column(
+            layout,
+            points_to_read,
+            produced,
+            8 + compressed_data.len() as u64,
+            0,
+            &format!(
+                "decompressed to {} bytes, expected {}",
+                produced, uncompressed_size
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Decompress zstd data into `columns`. `uncompressed_size` was checked
+/// against the layout, which bounds the allocation.
+#[cfg(feature = "zstd")]
+fn decode_zstd(
+    data: &[u8],
+    columns: Vec<&mut Column>,
+    layout: &PcdLayout,
+    points: usize,
+) -> Result<()> {
+    let expected = layout.packed_size() * points;
+    let payload = zstd::bulk::decompress(data, expected)
+        .map_err(|e| super::corrupt_column(layout, points, 0, 8, 0, &format!("zstd: {}", e)))?;
+    if payload.len() != expected {
+        return Err(super::corrupt_column(
+            layout,
+            points,
+            payload.len(),
+            8 + data.len() as u64,
+            0,
+            &format!(
+                "decompressed to {} bytes, expected {}",
+                payload.len(),
+                expected
+            ),
+        ));
+    }
+    convert_fields(columns, layout, points, &payload);
+    Ok(())
+}
+
+/// Decompress LZF data into `columns`, returning the bytes it held. On
+/// little-endian targets column memory holds exactly the SoA bytes of its
+/// field, so the output goes straight there.
+#[cfg(target_endian = "little")]
+fn decompress_fields(
+    data: &[u8],
+    columns: Vec<&mut Column>,
+    _layout: &PcdLayout,
+    _points: usize,
+) -> core::result::Result<usize, LzfError> {
+    let mut segments: Vec<&mut [u8]> = columns.into_iter().map(Column::as_le_bytes_mut).collect();
+    lzf_decompress_into(data, &mut segments)
+}
 
-        // Initialize output - verify all columns exist
-        for field in &self.layout.fields {
-            if output.get_column(&field.name).is_none() {
-                // Schema mismatch - column doesn't exist
-                // For now we just skip the check and let get_column_mut fail below
+/// Decompress LZF data into `columns` through one buffer, returning the
+/// bytes it held.
+#[cfg(not(target_endian = "little"))]
+fn decompress_fields(
+    data: &[u8],
+    columns: Vec<&mut Column>,
+    layout: &PcdLayout,
+    points: usize,
+) -> core::result::Result<usize, LzfError> {
+    let mut buffer = vec![0u8; layout.packed_size() * points];
+    let written = lzf_decompress_into(data, &mut [&mut buffer[..]])?;
+    if written == buffer.len() {
+        convert_fields(columns, layout, points, &buffer);
+    }
+    Ok(written)
+}
+
+/// Convert SoA field data into `columns`, one per field of `layout`.
+fn convert_fields(columns: Vec<&mut Column>, layout: &PcdLayout, points: usize, data: &[u8]) {
+    // Fields are stored one after another ([Field1 All Points][Field2 All
+    // Points]...), with the values of a count > 1 field kept together per
+    // point, so each field block converts like `count * points` values of
+    // one binary record.
+    let mut blocks = Vec::with_capacity(layout.fields.len());
+    let mut rest = data;
+    for field in &layout.fields {
+        let (block, tail) = rest.split_at(field.size * points);
+        blocks.push(block);
+        rest = tail;
+    }
+
+    #[cfg(feature = "rayon")]
+    columns
+        .into_par_iter()
+        .zip(&layout.fields)
+        .zip(blocks)
+        .for_each(|((col, field), block)| {
+            decode_field(col, field.type_, field.count * points, block, 0)
+        });
+    #[cfg(not(feature = "rayon"))]
+    for ((col, field), block) in columns.into_iter().zip(&layout.fields).zip(blocks) {
+        decode_field(col, field.type_, field.count * points, block, 0);
+    }
+}
+
+/// Where and why LZF decompression failed: the offset of the failing
+/// instruction in the compressed input and the bytes output so far.
+struct LzfError {
+    input: usize,
+    output: usize,
+    msg: &'static str,
+}
+
+/// LZF decompression (liblzf format) into consecutive output segments,
+/// e.g. the memory of several columns, returning the bytes written. Kept
+/// in-crate so decoding needs no `std`. Error strings match those of the
+/// `lzf` crate.
+fn lzf_decompress_into(
+    data: &[u8],
+    out: &mut [&mut [u8]],
+) -> core::result::Result<usize, LzfError> {
+    let mut out = Segments::new(out);
+    let mut i = 0;
+    while i < data.len() {
+        let at = i;
+        let fail = |out: &Segments, msg| LzfError {
+            input: at,
+            output: out.written,
+            msg,
+        };
+        let ctrl = data[i] as usize;
+        i += 1;
+        if ctrl < 32 {
+            // Literal run of ctrl + 1 bytes.
+            let len = ctrl + 1;
+            if out.written + len > out.capacity {
+                return Err(fail(&out, "BufferTooSmall"));
             }
+            let literal = data
+                .get(i..i + len)
+                .ok_or_else(|| fail(&out, "DataCorrupted"))?;
+            out.literal(literal);
+            i += len;
+        } else {
+            // Back reference of len + 2 bytes, which may overlap the output.
+            let mut len = ctrl >> 5;
+            if len == 7 {
+                len += *data.get(i).ok_or_else(|| fail(&out, "DataCorrupted"))? as usize;
+                i += 1;
+            }
+            let low = *data.get(i).ok_or_else(|| fail(&out, "DataCorrupted"))? as usize;
+            i += 1;
+            let distance = ((ctrl & 0x1f) << 8) + low + 1;
+            if out.written + len + 2 > out.capacity {
+                return Err(fail(&out, "BufferTooSmall"));
+            }
+            if distance > out.written {
+                return Err(fail(&out, "DataCorrupted"));
+            }
+            out.back_reference(distance, len + 2);
         }
-        output.resize(self.points_to_read);
-
-        // Process fields (SoA in buffer: [Field1 All Points][Field2 All Points]...)
-        let mut offset = 0;
-
-        for field in &self.layout.fields {
-            let col = output
-                .get_column_mut(&field.name)
-                .ok_or(PcdError::InvalidDataFormat(format!(
-                    "Missing column {}",
-                    field.name
-                )))?;
-
-            let bytes_per_element = field.element_size; // e.g. 4 for f32
-            let elements_per_point = field.count; // e.g. 1
-            let bytes_per_field_block =
-                bytes_per_element * elements_per_point * self.points_to_read;
-
-            let end = offset + bytes_per_field_block;
-            let data_slice = &decompressed[offset..end];
-            offset = end;
-
-            match field.type_ {
-                ValueType::U8 => {
-                    let vec = col.as_u8_mut().unwrap();
-                    vec.copy_from_slice(data_slice);
-                }
-                ValueType::F32 => {
-                    let vec = col.as_f32_mut().unwrap();
-                    // Efficient copy using unsafe cast if alignment permits, or safely
-                    // Since standard lzf returns Vec<u8>, it might not be aligned to 4.
-                    // We iterate.
-                    // Optimizations: chunks_exact(4).
-
-                    // We must fill target vec. Target vec is flat for all points.
-                    // field.count > 1 means interleaved? No, SoA usually means:
-                    // If count=1: x1, x2, x3...
-                    // If count=3 (Normal): nx1, ny1, nz1, nx2, ny2... ?
-                    // OR: nx1..nxN, ny1..nyN ?
-                    // PCL spec: "The fields are stored sequentially... field_1_point_1, field_1_point_2... field_2_point_1..."
-                    // Wait. "binary_compressed format... The data is stored in a column-major order."
-                    // Does it mean field 1 for all points, then field 2?
-                    // Yes. "stored sequentially by field".
-                    // But if a field has count > 1 (e.g. viewpoint), how is it stored?
-                    // "dimensions corresponding to the same field are stored contiguously".
-                    // So if field is "normal" (count=3), is it nx1, ny1, nz1, nx2...?
-                    // OR nx1..nxN, ny1..nyN?
-                    // Usually PCL treats distinct Names as fields. "Normal" is usually split into "normal_x, normal_y, normal_z" in fields list.
-                    // If one field has count > 1 (e.g. FPFH signature 33 floats), it is stored as struct?
-                    // PCL generic: it is simply array of structs compressed? No.
-                    // It says "reorganized to column array".
-                    // I will assume for count > 1, it's (val_1_1, val_1_2... val_1_count), (val_2_1...)...
-                    // i.e. The unit being transposed is the whole field value (array).
-                    // So yes, loop over points, copy `count` elements.
-
-                    let mut i = 0;
-                    for chunk in data_slice.chunks_exact(4) {
-                        vec[i] = LittleEndian::read_f32(chunk);
-                        i += 1;
-                    }
-                }
-                ValueType::F64 => {
-                    let vec = col.as_f64_mut().unwrap();
-                    let mut i = 0;
-                    for chunk in data_slice.chunks_exact(8) {
-                        vec[i] = LittleEndian::read_f64(chunk);
-                        i += 1;
-                    }
-                }
-                ValueType::U16 => {
-                    let vec = col.as_u16_mut().unwrap();
-                    let mut i = 0;
-                    for chunk in data_slice.chunks_exact(2) {
-                        vec[i] = LittleEndian::read_u16(chunk);
-                        i += 1;
-                    }
-                }
-                ValueType::U32 => {
-                    let vec = col.as_u32_mut().unwrap();
-                    let mut i = 0;
-                    for chunk in data_slice.chunks_exact(4) {
-                        vec[i] = LittleEndian::read_u32(chunk);
-                        i += 1;
-                    }
-                }
-                ValueType::I8 => {
-                    let vec = col.as_i8_mut().unwrap();
-                    // Safe conversion from u8 to i8
-                    for (dest, &src) in vec.iter_mut().zip(data_slice.iter()) {
-                        *dest = src as i8;
-                    }
-                }
-                ValueType::I16 => {
-                    let vec = col.as_i16_mut().unwrap();
-                    let mut i = 0;
-                    for chunk in data_slice.chunks_exact(2) {
-                        vec[i] = LittleEndian::read_i16(chunk);
-                        i += 1;
-                    }
-                }
-                ValueType::I32 => {
-                    let vec = col.as_i32_mut().unwrap();
-                    let mut i = 0;
-                    for chunk in data_slice.chunks_exact(4) {
-                        vec[i] = LittleEndian::read_i32(chunk);
-                        i += 1;
-                    }
+    }
+    Ok(out.written)
+}
+
+/// Output cursor over consecutive byte segments.
+struct Segments<'s, 'b> {
+    segments: &'s mut [&'b mut [u8]],
+    // Current segment and position in it.
+    segment: usize,
+    at: usize,
+    written: usize,
+    capacity: usize,
+}
+
+impl<'s, 'b> Segments<'s, 'b> {
+    fn new(segments: &'s mut [&'b mut [u8]]) -> Self {
+        let capacity = segments.iter().map(|s| s.len()).sum();
+        Self {
+            segments,
+            segment: 0,
+            at: 0,
+            written: 0,
+            capacity,
+        }
+    }
+
+    /// Move past full segments; callers check capacity first.
+    fn next_free(&mut self) {
+        while self.at == self.segments[self.segment].len() {
+            self.segment += 1;
+            self.at = 0;
+        }
+    }
+
+    fn literal(&mut self, mut bytes: &[u8]) {
+        self.written += bytes.len();
+        while !bytes.is_empty() {
+            self.next_free();
+            let dst = &mut self.segments[self.segment][self.at..];
+            let n = dst.len().min(bytes.len());
+            dst[..n].copy_from_slice(&bytes[..n]);
+            self.at += n;
+            bytes = &bytes[n..];
+        }
+    }
+
+    /// Copy `len` bytes starting `distance` back, byte by byte as the
+    /// ranges may overlap.
+    fn back_reference(&mut self, distance: usize, len: usize) {
+        self.written += len;
+        self.next_free();
+        let segment = &mut self.segments[self.segment];
+        if self.at >= distance && self.at + len <= segment.len() {
+            let from = self.at - distance;
+            if distance >= len {
+                segment.copy_within(from..from + len, self.at);
+            } else {
+                for k in 0..len {
+                    segment[self.at + k] = segment[from + k];
                 }
             }
+            self.at += len;
+            return;
         }
 
-        Ok(())
+        // The source or destination crosses a segment boundary.
+        let (mut source, mut offset) = (self.segment, self.at);
+        let mut back = distance;
+        while back > offset {
+            back -= offset;
+            source -= 1;
+            offset = self.segments[source].len();
+        }
+        offset -= back;
+        for _ in 0..len {
+            while offset == self.segments[source].len() {
+                source += 1;
+                offset = 0;
+            }
+            self.next_free();
+            self.segments[self.segment][self.at] = self.segments[source][offset];
+            offset += 1;
+            self.at += 1;
+        }
     }
 }
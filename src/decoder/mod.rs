@@ -12,14 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::error::Result;
+use crate::error::{Operation, PcdError, Result};
+use crate::header::DataFormat;
+use crate::layout::{DEFAULT_MEMORY_LIMIT, PcdLayout};
 use crate::storage::PointBlock;
+use alloc::string::ToString;
+use binary::decode_binary;
+use compressed::{Codec, decode_compressed};
 
+#[cfg(feature = "std")]
 pub mod ascii;
+#[cfg(feature = "rayon")]
+pub mod ascii_par;
 pub mod binary;
 #[cfg(feature = "rayon")]
 pub mod binary_par;
 pub mod compressed;
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod simd;
 
 pub trait PcdDecoder {
     fn decode(&mut self, output: &mut PointBlock) -> Result<()>;
@@ -30,3 +40,148 @@ pub trait PcdDecoder {
 pub trait ParPcdDecoder {
     fn decode_par(&self, data: &[u8], output: &mut PointBlock) -> Result<()>;
 }
+
+/// Check that `block` has a column of the right type for every field of
+/// `layout`.
+pub(crate) fn check_columns(block: &PointBlock, layout: &PcdLayout) -> Result<()> {
+    for field in &layout.fields {
+        let column = block
+            .get_column(&field.name)
+            .ok_or_else(|| PcdError::MissingField {
+                field: field.name.clone(),
+                op: Operation::Read,
+            })?;
+        if column.value_type() != field.type_ {
+            return Err(PcdError::FieldType {
+                field: field.name.clone(),
+                expected: field.type_,
+                got: column.value_type(),
+                op: Operation::Read,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// A [`PcdError::Corrupt`] at byte `pos` of row-major records of `layout`,
+/// the first of which is point `first_point` at offset `base`.
+#[cfg(feature = "std")]
+pub(crate) fn corrupt_record(
+    layout: &PcdLayout,
+    base: u64,
+    first_point: usize,
+    pos: usize,
+    msg: &str,
+) -> PcdError {
+    let step = layout.total_size.max(1);
+    let within = pos % step;
+    PcdError::Corrupt {
+        offset: base + pos as u64,
+        point: first_point + pos / step,
+        field: layout
+            .fields
+            .iter()
+            .find(|f| within >= f.offset && within < f.offset + f.size)
+            .map(|f| f.name.clone()),
+        msg: msg.to_string(),
+    }
+}
+
+/// A [`PcdError::Corrupt`] at `offset` for byte `pos` of a column-major
+/// payload of `points` points, the first of which is point `first_point`.
+pub(crate) fn corrupt_column(
+    layout: &PcdLayout,
+    points: usize,
+    pos: usize,
+    offset: u64,
+    first_point: usize,
+    msg: &str,
+) -> PcdError {
+    let mut start = 0;
+    for field in &layout.fields {
+        let len = field.size * points;
+        if pos < start + len {
+            return PcdError::Corrupt {
+                offset,
+                point: first_point + (pos - start) / field.size,
+                field: Some(field.name.clone()),
+                msg: msg.to_string(),
+            };
+        }
+        start += len;
+    }
+    PcdError::Corrupt {
+        offset,
+        point: first_point + points,
+        field: None,
+        msg: msg.to_string(),
+    }
+}
+
+/// Shift a [`PcdError::Corrupt`] located relative to a data section to the
+/// section's file offset `base` and first point `first_point`.
+#[cfg(feature = "std")]
+pub(crate) fn relocate(err: PcdError, base: u64, first_point: usize) -> PcdError {
+    match err {
+        PcdError::Corrupt {
+            offset,
+            point,
+            field,
+            msg,
+        } => PcdError::Corrupt {
+            offset: offset + base,
+            point: point + first_point,
+            field,
+            msg,
+        },
+        other => other,
+    }
+}
+
+/// Size `block` for `points` points, with room for every value of fields
+/// with `COUNT > 1`.
+pub(crate) fn resize_block(block: &mut PointBlock, layout: &PcdLayout, points: usize) {
+    block.resize(points);
+    for field in &layout.fields {
+        if let Some(column) = block.get_column_mut(&field.name) {
+            column.resize(points * field.count);
+        }
+    }
+}
+
+/// Decode a complete data section held in memory. Binary and compressed data
+/// decode without `std`; ASCII data needs it. More points than fit in
+/// [`DEFAULT_MEMORY_LIMIT`] are refused with [`PcdError::TooLarge`].
+pub fn decode_slice(
+    data: &[u8],
+    format: DataFormat,
+    layout: &PcdLayout,
+    points: usize,
+) -> Result<PointBlock> {
+    layout.block_size(points, DEFAULT_MEMORY_LIMIT)?;
+    let mut block = PointBlock::new(layout.schema(), points);
+    match format {
+        DataFormat::Binary => decode_binary(data, layout, points, &mut block)?,
+        #[cfg(feature = "rayon")]
+        DataFormat::Ascii => {
+            ascii_par::AsciiParallelDecoder::new(layout, points).decode_par(data, &mut block)?
+        }
+        #[cfg(all(feature = "std", not(feature = "rayon")))]
+        DataFormat::Ascii => {
+            ascii::AsciiReader::new(&mut std::io::Cursor::new(data), layout, points)
+                .decode(&mut block)?
+        }
+        #[cfg(not(feature = "std"))]
+        DataFormat::Ascii => {
+            return Err(PcdError::UnsupportedDataFormat(
+                alloc::string::ToString::to_string("ascii data requires the std feature"),
+            ));
+        }
+        DataFormat::BinaryCompressed => {
+            decode_compressed(data, layout, points, Codec::Lzf, &mut block)?
+        }
+        #[cfg(feature = "zstd")]
+        DataFormat::BinaryZstd => decode_compressed(data, layout, points, Codec::Zstd, &mut block)?,
+    }
+    Ok(block)
+}
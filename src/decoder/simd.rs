@@ -0,0 +1,226 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! AVX2 kernels for gathering one field out of row-major records, picked at
+//! run time. Each returns how many values it wrote; the caller decodes the
+//! rest.
+
+use core::arch::x86_64::*;
+
+/// Gather the `width`-byte value at `offset` of each `stride`-byte record of
+/// `records` into `out`, as many as both hold, in native byte order.
+pub(super) fn gather(
+    width: usize,
+    records: &[u8],
+    stride: usize,
+    offset: usize,
+    out: &mut [u8],
+) -> usize {
+    let n = (out.len() / width).min(records.len() / stride);
+    // The index vectors hold eight record offsets as i32. The narrow kernels
+    // load four bytes of the record from the start of the field, or ending
+    // with it near the end of the record.
+    if n < 8 || stride < 4 || stride > (i32::MAX as usize) / 8 {
+        return 0;
+    }
+    let load = offset.min(stride - 4);
+    let skip = (offset - load) as i8;
+    if !std::is_x86_feature_detected!("avx2") {
+        return 0;
+    }
+    // Safety: AVX2 is available, and every load stays inside one of the
+    // first `n` records, so inside `records`.
+    unsafe {
+        match width {
+            1 => gather_8(records, stride, load, skip, out, n),
+            2 => gather_16(records, stride, load, skip, out, n),
+            4 => gather_32(records, stride, offset, out, n),
+            8 => gather_64(records, stride, offset, out, n),
+            _ => 0,
+        }
+    }
+}
+
+/// Offsets of eight consecutive records.
+#[target_feature(enable = "avx2")]
+fn record_offsets(stride: usize) -> __m256i {
+    let s = stride as i32;
+    _mm256_setr_epi32(0, s, 2 * s, 3 * s, 4 * s, 5 * s, 6 * s, 7 * s)
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn gather_32(
+    records: &[u8],
+    stride: usize,
+    offset: usize,
+    out: &mut [u8],
+    n: usize,
+) -> usize {
+    let index = record_offsets(stride);
+    let mut i = 0;
+    while i + 8 <= n {
+        unsafe {
+            let base = records.as_ptr().add(i * stride + offset) as *const i32;
+            let v = _mm256_i32gather_epi32::<1>(base, index);
+            _mm256_storeu_si256(out.as_mut_ptr().add(i * 4) as *mut __m256i, v);
+        }
+        i += 8;
+    }
+    i
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn gather_64(
+    records: &[u8],
+    stride: usize,
+    offset: usize,
+    out: &mut [u8],
+    n: usize,
+) -> usize {
+    let index = _mm256_castsi256_si128(record_offsets(stride));
+    let mut i = 0;
+    while i + 4 <= n {
+        unsafe {
+            let base = records.as_ptr().add(i * stride + offset) as *const i64;
+            let v = _mm256_i32gather_epi64::<1>(base, index);
+            _mm256_storeu_si256(out.as_mut_ptr().add(i * 8) as *mut __m256i, v);
+        }
+        i += 4;
+    }
+    i
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn gather_16(
+    records: &[u8],
+    stride: usize,
+    offset: usize,
+    skip: i8,
+    out: &mut [u8],
+    n: usize,
+) -> usize {
+    let index = record_offsets(stride);
+    // Bytes `skip..skip + 2` of each 32-bit lane, packed into the low half
+    // of each 128-bit lane.
+    let (a, b) = (skip, skip + 1);
+    let pick = _mm256_setr_epi8(
+        a,
+        b,
+        a + 4,
+        b + 4,
+        a + 8,
+        b + 8,
+        a + 12,
+        b + 12,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1, //
+        a,
+        b,
+        a + 4,
+        b + 4,
+        a + 8,
+        b + 8,
+        a + 12,
+        b + 12,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+    );
+    let mut i = 0;
+    while i + 8 <= n {
+        unsafe {
+            let base = records.as_ptr().add(i * stride + offset) as *const i32;
+            let v = _mm256_shuffle_epi8(_mm256_i32gather_epi32::<1>(base, index), pick);
+            let v = _mm256_permute4x64_epi64::<0b1000>(v);
+            _mm_storeu_si128(
+                out.as_mut_ptr().add(i * 2) as *mut __m128i,
+                _mm256_castsi256_si128(v),
+            );
+        }
+        i += 8;
+    }
+    i
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn gather_8(
+    records: &[u8],
+    stride: usize,
+    offset: usize,
+    skip: i8,
+    out: &mut [u8],
+    n: usize,
+) -> usize {
+    let index = record_offsets(stride);
+    // Byte `skip` of each 32-bit lane, packed into the low four bytes of
+    // each 128-bit lane.
+    let a = skip;
+    let pick = _mm256_setr_epi8(
+        a,
+        a + 4,
+        a + 8,
+        a + 12,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1, //
+        a,
+        a + 4,
+        a + 8,
+        a + 12,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+    );
+    let mut i = 0;
+    while i + 8 <= n {
+        unsafe {
+            let base = records.as_ptr().add(i * stride + offset) as *const i32;
+            let v = _mm256_shuffle_epi8(_mm256_i32gather_epi32::<1>(base, index), pick);
+            let v = _mm256_permutevar8x32_epi32(v, _mm256_setr_epi32(0, 4, 0, 0, 0, 0, 0, 0));
+            let low = _mm256_castsi256_si128(v);
+            core::ptr::write_unaligned(out.as_mut_ptr().add(i) as *mut i64, _mm_cvtsi128_si64(low));
+        }
+        i += 8;
+    }
+    i
+}
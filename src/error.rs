@@ -1,7 +1,28 @@
+use crate::header::ValueType;
+use alloc::string::String;
+use core::fmt;
 use thiserror::Error;
 
+/// Whether an error arose decoding points into a block or encoding them
+/// from one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Read,
+    Write,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Operation::Read => "reading",
+            Operation::Write => "writing",
+        })
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum PcdError {
+    #[cfg(feature = "std")]
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -23,11 +44,46 @@ pub enum PcdError {
     #[error("Layout mismatch: expected {expected}, got {got}")]
     LayoutMismatch { expected: usize, got: usize },
 
+    /// The point block has no column for a field of the header or layout.
+    #[error("Missing column {field} while {op}")]
+    MissingField { field: String, op: Operation },
+
+    /// A column's type differs from the one the header or layout gives.
+    #[error("Column {field} is {got:?}, expected {expected:?}, while {op}")]
+    FieldType {
+        field: String,
+        expected: ValueType,
+        got: ValueType,
+        op: Operation,
+    },
+
+    /// Undecodable data, located by absolute byte offset, point index and
+    /// the field whose bytes were being decoded, if any.
+    #[error(
+        "Corrupt data at byte {offset} (point {point}, field {}): {msg}",
+        .field.as_deref().unwrap_or("-")
+    )]
+    Corrupt {
+        offset: u64,
+        point: usize,
+        field: Option<String>,
+        msg: String,
+    },
+
     #[error("Buffer too small: expected {expected}, got {got}")]
     BufferTooSmall { expected: usize, got: usize },
 
+    /// Holding `points` points would take more memory than allowed, e.g.
+    /// because a header declares an absurd `POINTS`.
+    #[error("{points} points need more than the memory limit of {limit} bytes")]
+    TooLarge { points: usize, limit: u64 },
+
+    /// Decoding was stopped through a cancellation flag.
+    #[error("Cancelled")]
+    Cancelled,
+
     #[error("{0}")]
     Other(String),
 }
 
-pub type Result<T> = std::result::Result<T, PcdError>;
+pub type Result<T> = core::result::Result<T, PcdError>;
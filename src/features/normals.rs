@@ -0,0 +1,217 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Surface normals and curvature from the covariance of each point's
+//! neighbourhood, as PCL's `NormalEstimation`.
+
+use crate::error::{PcdError, Result};
+use crate::spatial::{KdTree, NeighborSearch};
+use crate::storage::{Column, PointBlock};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::f64::consts::PI;
+
+const COLUMNS: [&str; 4] = ["normal_x", "normal_y", "normal_z", "curvature"];
+
+/// The neighbours a normal is fitted to, the point itself included.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Neighborhood {
+    /// The `k` nearest points.
+    Knn(usize),
+    /// The points within a distance.
+    Radius(f64),
+}
+
+/// Normal estimation: the normal of a point is the direction of least
+/// variance of its neighbourhood, flipped towards the viewpoint, and its
+/// curvature the share of that variance, `λ0 / (λ0 + λ1 + λ2)`. Points with
+/// a NaN coordinate, fewer than three neighbours or collinear neighbours
+/// get NaN. Runs in parallel with the `rayon` feature.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NormalEstimation {
+    neighborhood: Neighborhood,
+    viewpoint: [f64; 3],
+}
+
+impl NormalEstimation {
+    pub fn new(neighborhood: Neighborhood) -> Self {
+        Self {
+            neighborhood,
+            viewpoint: [0.0; 3],
+        }
+    }
+
+    /// Orient normals towards `viewpoint`, by default the origin, where a
+    /// sweep's sensor is.
+    #[must_use]
+    pub fn viewpoint(mut self, viewpoint: [f64; 3]) -> Self {
+        self.viewpoint = viewpoint;
+        self
+    }
+
+    /// `[normal_x, normal_y, normal_z, curvature]` of each point of
+    /// `block`, from a [`KdTree`] over it. Fails if `x`, `y` or `z` is
+    /// missing.
+    pub fn normals(&self, block: &PointBlock) -> Result<Vec<[f64; 4]>> {
+        self.normals_with(block, &KdTree::from_block(block)?)
+    }
+
+    /// Like [`normals`](Self::normals), with neighbours from `index`, which
+    /// must hold the points of `block` by index, e.g. an [`Octree`] built
+    /// for other queries as well.
+    ///
+    /// [`Octree`]: crate::spatial::Octree
+    pub fn normals_with<S: NeighborSearch + Sync>(
+        &self,
+        block: &PointBlock,
+        index: &S,
+    ) -> Result<Vec<[f64; 4]>> {
+        let positions: Vec<[f64; 3]> = block
+            .iter_xyz()
+            .ok_or_else(|| PcdError::InvalidDataFormat("Missing column x, y or z".to_string()))?
+            .collect();
+        let normal = |p: &[f64; 3]| {
+            if !p.iter().all(|c| c.is_finite()) {
+                return [f64::NAN; 4];
+            }
+            let neighbors = match self.neighborhood {
+                Neighborhood::Knn(k) => index.nearest(*p, k),
+                Neighborhood::Radius(r) => index.radius_search(*p, r),
+            };
+            let normal = fit_normal(neighbors.iter().map(|n| positions[n.index]));
+            orient(normal, *p, self.viewpoint)
+        };
+        #[cfg(feature = "rayon")]
+        let normals = positions.par_iter().map(normal).collect();
+        #[cfg(not(feature = "rayon"))]
+        let normals = positions.iter().map(normal).collect();
+        Ok(normals)
+    }
+
+    /// Add (or replace) F32 columns `normal_x`, `normal_y`, `normal_z` and
+    /// `curvature`, as PCL's `PointNormal`.
+    pub fn apply(&self, block: &mut PointBlock) -> Result<()> {
+        let normals = self.normals(block)?;
+        for (k, name) in COLUMNS.iter().enumerate() {
+            let values = normals.iter().map(|n| n[k] as f32).collect();
+            block.remove_column(name);
+            block.add_column(name, Column::F32(values))?;
+        }
+        Ok(())
+    }
+}
+
+/// Add normals and curvature to `block` from `neighborhood`, see
+/// [`NormalEstimation`].
+pub fn estimate_normals(block: &mut PointBlock, neighborhood: Neighborhood) -> Result<()> {
+    NormalEstimation::new(neighborhood).apply(block)
+}
+
+/// Unit normal and curvature of the plane through `points`, unoriented.
+fn fit_normal(points: impl Iterator<Item = [f64; 3]> + Clone) -> [f64; 4] {
+    let n = points.clone().count();
+    if n < 3 {
+        return [f64::NAN; 4];
+    }
+    let mut mean = [0.0; 3];
+    for p in points.clone() {
+        for a in 0..3 {
+            mean[a] += p[a] / n as f64;
+        }
+    }
+    let mut cov = [[0.0; 3]; 3];
+    for p in points {
+        let d = [p[0] - mean[0], p[1] - mean[1], p[2] - mean[2]];
+        for r in 0..3 {
+            for c in 0..3 {
+                cov[r][c] += d[r] * d[c];
+            }
+        }
+    }
+    match smallest_eigen(cov) {
+        Some((normal, values)) => {
+            let total = values[0] + values[1] + values[2];
+            let curvature = if total > 0.0 { values[0] / total } else { 0.0 };
+            [normal[0], normal[1], normal[2], curvature]
+        }
+        None => [f64::NAN; 4],
+    }
+}
+
+/// Flip `normal` to face `viewpoint` from `p`.
+fn orient(normal: [f64; 4], p: [f64; 3], viewpoint: [f64; 3]) -> [f64; 4] {
+    let facing: f64 = (0..3).map(|a| normal[a] * (viewpoint[a] - p[a])).sum();
+    if facing < 0.0 {
+        [-normal[0], -normal[1], -normal[2], normal[3]]
+    } else {
+        normal
+    }
+}
+
+/// Unit eigenvector of the smallest eigenvalue of the symmetric `m` and
+/// the eigenvalues in ascending order, or `None` if that eigenvalue is not
+/// simple, as for collinear points.
+fn smallest_eigen(m: [[f64; 3]; 3]) -> Option<([f64; 3], [f64; 3])> {
+    // Scaled to the largest entry for conditioning.
+    let scale = m.iter().flatten().fold(0.0f64, |s, v| s.max(v.abs()));
+    if scale == 0.0 || !scale.is_finite() {
+        return None;
+    }
+    let a = m.map(|row| row.map(|v| v / scale));
+    let off = a[0][1] * a[0][1] + a[0][2] * a[0][2] + a[1][2] * a[1][2];
+    let values = if off == 0.0 {
+        let mut d = [a[0][0], a[1][1], a[2][2]];
+        d.sort_unstable_by(f64::total_cmp);
+        d
+    } else {
+        // Closed form for symmetric 3x3 matrices via the trigonometric
+        // solution of the characteristic cubic.
+        let q = (a[0][0] + a[1][1] + a[2][2]) / 3.0;
+        let p2 = (a[0][0] - q).powi(2) + (a[1][1] - q).powi(2) + (a[2][2] - q).powi(2) + 2.0 * off;
+        let p = (p2 / 6.0).sqrt();
+        let b = [0, 1, 2].map(|r| [0, 1, 2].map(|c| (a[r][c] - if r == c { q } else { 0.0 }) / p));
+        let det = b[0][0] * (b[1][1] * b[2][2] - b[1][2] * b[2][1])
+            - b[0][1] * (b[1][0] * b[2][2] - b[1][2] * b[2][0])
+            + b[0][2] * (b[1][0] * b[2][1] - b[1][1] * b[2][0]);
+        let phi = (det / 2.0).clamp(-1.0, 1.0).acos() / 3.0;
+        let largest = q + 2.0 * p * phi.cos();
+        let smallest = q + 2.0 * p * (phi + 2.0 * PI / 3.0).cos();
+        [smallest, 3.0 * q - largest - smallest, largest]
+    };
+    // The eigenvector is orthogonal to the rows of `a - λ0 I`; take the
+    // longest cross product of two of them.
+    let rows = [0, 1, 2].map(|r| [0, 1, 2].map(|c| a[r][c] - if r == c { values[0] } else { 0.0 }));
+    let cross = |u: [f64; 3], v: [f64; 3]| {
+        [
+            u[1] * v[2] - u[2] * v[1],
+            u[2] * v[0] - u[0] * v[2],
+            u[0] * v[1] - u[1] * v[0],
+        ]
+    };
+    let norm2 = |v: &[f64; 3]| v[0] * v[0] + v[1] * v[1] + v[2] * v[2];
+    let best = [
+        cross(rows[0], rows[1]),
+        cross(rows[0], rows[2]),
+        cross(rows[1], rows[2]),
+    ]
+    .into_iter()
+    .max_by(|u, v| norm2(u).total_cmp(&norm2(v)))
+    .unwrap();
+    let length = norm2(&best).sqrt();
+    // A repeated smallest eigenvalue leaves the rows spanning a line.
+    if length.is_nan() || length <= 1e-12 {
+        return None;
+    }
+    Some((best.map(|c| c / length), values.map(|v| v * scale)))
+}
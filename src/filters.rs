@@ -0,0 +1,25 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Filters that select or merge points of a block, gathered in one place.
+//!
+//! These are the [`storage`](crate::storage) filters under shorter paths:
+//! range and box crops, voxel-grid downsampling and farthest point sampling,
+//! e.g. `rs_pcd::filters::farthest_point_sample(&block, 1024)?`.
+
+pub use crate::storage::filter::{
+    BoundingBox, CompareOp, CropBox, PointFilter, Predicate, crop_box, pass_through,
+};
+pub use crate::storage::sampling::{FarthestPointSampler, farthest_point_sample};
+pub use crate::storage::voxel::{Aggregation, VoxelGrid, voxel_grid};
@@ -0,0 +1,67 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Axis conventions of sensor frames, such as a LiDAR's in ROS against a
+//! camera's in OpenCV.
+
+use super::Pose;
+use crate::error::Result;
+use crate::storage::PointBlock;
+
+/// Which way the `x`, `y` and `z` axes of a frame point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axes {
+    /// Forward, left, up: ROS bodies and LiDARs (REP 103).
+    Flu,
+    /// Right, down, forward: cameras in OpenCV and ROS optical frames.
+    Rdf,
+    /// Forward, right, down: aerospace bodies.
+    Frd,
+}
+
+impl Axes {
+    /// The rotation taking coordinates in the `from` convention to the `to`
+    /// convention, for the same physical point.
+    pub fn rotation(from: Axes, to: Axes) -> Pose {
+        let (from, to) = (from.to_flu(), to.to_flu());
+        // to⁻¹ * from, the inverse of a rotation being its transpose.
+        let mut matrix = [[0.0; 4]; 4];
+        for (r, row) in matrix.iter_mut().enumerate().take(3) {
+            for (c, value) in row.iter_mut().enumerate().take(3) {
+                *value = (0..3).map(|k| to[k][r] * from[k][c]).sum();
+            }
+        }
+        matrix[3][3] = 1.0;
+        Pose::from_matrix(&matrix)
+    }
+
+    /// The rotation from this convention to FLU, `flu = m * p`.
+    fn to_flu(self) -> [[f64; 3]; 3] {
+        match self {
+            Axes::Flu => [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            Axes::Rdf => [[0.0, 0.0, 1.0], [-1.0, 0.0, 0.0], [0.0, -1.0, 0.0]],
+            Axes::Frd => [[1.0, 0.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, -1.0]],
+        }
+    }
+}
+
+/// Re-express the points (and normals) of `block` from the `from` axis
+/// convention in the `to` one, e.g. a LiDAR scan in FLU for a camera
+/// pipeline in RDF. Fails like [`PointBlock::transform_f64`].
+pub fn convert_axes(block: &mut PointBlock, from: Axes, to: Axes) -> Result<()> {
+    if from == to {
+        return Ok(());
+    }
+    Axes::rotation(from, to).transform_block(block)
+}
@@ -0,0 +1,166 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Motion compensation of a sweep from per-point timestamps.
+//!
+//! A spinning LiDAR measures each point at a different time; if the sensor
+//! moves meanwhile, each point is in the sensor frame of its own instant.
+//! Deskewing moves every point into the frame of one reference instant,
+//! given the sensor pose over the sweep.
+
+use super::Pose;
+use crate::error::{PcdError, Result};
+use crate::header::ValueType;
+use crate::storage::{Column, PointBlock};
+
+const TIMESTAMP: &str = "timestamp";
+
+/// Deskew `block` given the sensor pose at its earliest `timestamp`,
+/// `start`, and at its latest, `end`, in any fixed frame such as
+/// odometry's. Poses in between are interpolated linearly in time (see
+/// [`Pose::interpolate`]), and the points moved into the sensor frame at
+/// the start. The timestamps may be in any unit. See [`deskew_with`].
+pub fn deskew(block: &mut PointBlock, start: &Pose, end: &Pose) -> Result<()> {
+    let times = timestamps(block)?;
+    let (first, last) = times
+        .iter()
+        .filter(|t| t.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &t| {
+            (lo.min(t), hi.max(t))
+        });
+    if first > last {
+        return Ok(());
+    }
+    let span = last - first;
+    move_points(block, &times, first, |t| {
+        let fraction = if span > 0.0 { (t - first) / span } else { 0.0 };
+        start.interpolate(end, fraction)
+    })
+}
+
+/// Deskew `block` with `pose_at`, the sensor pose at a given `timestamp`,
+/// e.g. interpolated from an odometry trajectory: each point is moved
+/// from the sensor frame at its timestamp into the one at `reference`.
+/// `normal_x`, `normal_y`, `normal_z`, if present, are rotated too.
+/// Points with a NaN timestamp are left as they are.
+///
+/// Fails, leaving the block unchanged, if there is no `timestamp` column
+/// or `x`, `y` and `z` are not all F32 or all F64.
+pub fn deskew_with(
+    block: &mut PointBlock,
+    reference: f64,
+    pose_at: impl Fn(f64) -> Pose,
+) -> Result<()> {
+    let times = timestamps(block)?;
+    move_points(block, &times, reference, pose_at)
+}
+
+/// The `timestamp` of each point.
+fn timestamps(block: &PointBlock) -> Result<Vec<f64>> {
+    let column = block
+        .get_column(TIMESTAMP)
+        .ok_or_else(|| PcdError::InvalidDataFormat(format!("Missing column {}", TIMESTAMP)))?;
+    if column.len() != block.len {
+        return Err(PcdError::InvalidDataFormat(format!(
+            "Column {} has more than one value per point",
+            TIMESTAMP
+        )));
+    }
+    Ok((0..block.len).map(|i| column.value_f64(i)).collect())
+}
+
+fn move_points(
+    block: &mut PointBlock,
+    times: &[f64],
+    reference: f64,
+    pose_at: impl Fn(f64) -> Pose,
+) -> Result<()> {
+    let to_reference = pose_at(reference).inverse();
+    let motions: Vec<Option<Pose>> = times
+        .iter()
+        .map(|&t| (!t.is_nan()).then(|| to_reference.compose(&pose_at(t))))
+        .collect();
+    let normals = ["normal_x", "normal_y", "normal_z"]
+        .iter()
+        .all(|n| block.get_column(n).is_some());
+    check_float_axes(block, ["x", "y", "z"])?;
+    if normals {
+        check_float_axes(block, ["normal_x", "normal_y", "normal_z"])?;
+    }
+    move_axes(block, ["x", "y", "z"], &motions, Pose::transform_point);
+    if normals {
+        move_axes(
+            block,
+            ["normal_x", "normal_y", "normal_z"],
+            &motions,
+            Pose::rotate,
+        );
+    }
+    Ok(())
+}
+
+/// Fail unless `names` are three columns of the same float type.
+fn check_float_axes(block: &PointBlock, names: [&str; 3]) -> Result<()> {
+    let types = names
+        .iter()
+        .map(|&name| {
+            block
+                .get_column(name)
+                .map(Column::value_type)
+                .ok_or_else(|| PcdError::InvalidDataFormat(format!("Missing column {}", name)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if types.iter().any(|t| *t != types[0]) || !matches!(types[0], ValueType::F32 | ValueType::F64)
+    {
+        return Err(PcdError::InvalidDataFormat(format!(
+            "Cannot deskew {} of types {:?}, not all F32 or all F64",
+            names.join("/"),
+            types
+        )));
+    }
+    Ok(())
+}
+
+/// Apply each point's motion to the vectors in columns `names` with `f`.
+fn move_axes(
+    block: &mut PointBlock,
+    names: [&str; 3],
+    motions: &[Option<Pose>],
+    f: fn(&Pose, [f64; 3]) -> [f64; 3],
+) {
+    let names = names.map(str::to_string);
+    let mut columns = block.get_columns_mut(&names).expect("checked columns");
+    let [x, y, z] = columns.as_mut_slice() else {
+        unreachable!()
+    };
+    match (&mut **x, &mut **y, &mut **z) {
+        (Column::F32(x), Column::F32(y), Column::F32(z)) => {
+            for (i, motion) in motions.iter().enumerate() {
+                if let Some(motion) = motion {
+                    let p = f(motion, [x[i] as f64, y[i] as f64, z[i] as f64]);
+                    (x[i], y[i], z[i]) = (p[0] as f32, p[1] as f32, p[2] as f32);
+                }
+            }
+        }
+        (Column::F64(x), Column::F64(y), Column::F64(z)) => {
+            for (i, motion) in motions.iter().enumerate() {
+                if let Some(motion) = motion {
+                    let p = f(motion, [x[i], y[i], z[i]]);
+                    (x[i], y[i], z[i]) = (p[0], p[1], p[2]);
+                }
+            }
+        }
+        _ => unreachable!("checked column types"),
+    }
+}
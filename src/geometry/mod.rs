@@ -0,0 +1,31 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rigid poses and the motion of the sensor during a sweep.
+//!
+//! A [`Pose`] maps points from a sensor's frame into a fixed frame, as a
+//! PCD `VIEWPOINT` does. [`deskew`] uses the poses at the start and end of a
+//! sweep to undo the distortion of a sensor moving while it scans, a
+//! [`Trajectory`] interpolates timestamped poses in between, and
+//! [`convert_axes`] switches a block between axis conventions such as ROS's
+//! forward-left-up and a camera's right-down-forward.
+
+pub mod axes;
+pub mod deskew;
+pub mod pose;
+pub mod trajectory;
+pub use axes::{Axes, convert_axes};
+pub use deskew::{deskew, deskew_with};
+pub use pose::Pose;
+pub use trajectory::Trajectory;
@@ -0,0 +1,218 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rigid transforms as a translation and a unit quaternion.
+
+use crate::error::Result;
+use crate::storage::PointBlock;
+
+/// A rigid transform: rotation by the unit quaternion `rotation`
+/// (`[w, x, y, z]`), then translation by `translation`. As a sensor pose it
+/// maps points from the sensor's frame into the fixed one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pose {
+    pub translation: [f64; 3],
+    pub rotation: [f64; 4],
+}
+
+impl Default for Pose {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Pose {
+    pub const IDENTITY: Pose = Pose {
+        translation: [0.0; 3],
+        rotation: [1.0, 0.0, 0.0, 0.0],
+    };
+
+    /// The pose with `translation` and the rotation of quaternion
+    /// `[w, x, y, z]`, normalized; a zero quaternion is the identity.
+    pub fn new(translation: [f64; 3], rotation: [f64; 4]) -> Self {
+        Self {
+            translation,
+            rotation: normalize(rotation),
+        }
+    }
+
+    /// The pose of a PCD `VIEWPOINT`, `tx ty tz qw qx qy qz`.
+    pub fn from_viewpoint(viewpoint: &[f64; 7]) -> Self {
+        let [tx, ty, tz, w, x, y, z] = *viewpoint;
+        Self::new([tx, ty, tz], [w, x, y, z])
+    }
+
+    /// The pose as a PCD `VIEWPOINT`, `tx ty tz qw qx qy qz`.
+    #[must_use]
+    pub fn to_viewpoint(&self) -> [f64; 7] {
+        let ([tx, ty, tz], [w, x, y, z]) = (self.translation, self.rotation);
+        [tx, ty, tz, w, x, y, z]
+    }
+
+    /// The pose of the homogeneous transform `matrix` (`matrix[row][col]`),
+    /// whose upper-left 3x3 block must be a rotation.
+    pub fn from_matrix(matrix: &[[f64; 4]; 4]) -> Self {
+        let r = |i: usize, j: usize| matrix[i][j];
+        let trace = r(0, 0) + r(1, 1) + r(2, 2);
+        let rotation = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            [
+                0.25 * s,
+                (r(2, 1) - r(1, 2)) / s,
+                (r(0, 2) - r(2, 0)) / s,
+                (r(1, 0) - r(0, 1)) / s,
+            ]
+        } else if r(0, 0) > r(1, 1) && r(0, 0) > r(2, 2) {
+            let s = (1.0 + r(0, 0) - r(1, 1) - r(2, 2)).sqrt() * 2.0;
+            [
+                (r(2, 1) - r(1, 2)) / s,
+                0.25 * s,
+                (r(0, 1) + r(1, 0)) / s,
+                (r(0, 2) + r(2, 0)) / s,
+            ]
+        } else if r(1, 1) > r(2, 2) {
+            let s = (1.0 + r(1, 1) - r(0, 0) - r(2, 2)).sqrt() * 2.0;
+            [
+                (r(0, 2) - r(2, 0)) / s,
+                (r(0, 1) + r(1, 0)) / s,
+                0.25 * s,
+                (r(1, 2) + r(2, 1)) / s,
+            ]
+        } else {
+            let s = (1.0 + r(2, 2) - r(0, 0) - r(1, 1)).sqrt() * 2.0;
+            [
+                (r(1, 0) - r(0, 1)) / s,
+                (r(0, 2) + r(2, 0)) / s,
+                (r(1, 2) + r(2, 1)) / s,
+                0.25 * s,
+            ]
+        };
+        Self::new([r(0, 3), r(1, 3), r(2, 3)], rotation)
+    }
+
+    /// The homogeneous transform (`matrix[row][col]`), as taken by
+    /// [`PointBlock::transform_f64`].
+    #[must_use]
+    pub fn to_matrix(&self) -> [[f64; 4]; 4] {
+        let columns = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]].map(|e| self.rotate(e));
+        let t = self.translation;
+        [
+            [columns[0][0], columns[1][0], columns[2][0], t[0]],
+            [columns[0][1], columns[1][1], columns[2][1], t[1]],
+            [columns[0][2], columns[1][2], columns[2][2], t[2]],
+            [0.0, 0.0, 0.0, 1.0],
+        ]
+    }
+
+    /// Move the points of `block` by this pose in place, and rotate its
+    /// normals; see [`PointBlock::transform_f64`] for the columns needed.
+    pub fn transform_block(&self, block: &mut PointBlock) -> Result<()> {
+        block.transform_f64(&self.to_matrix())
+    }
+
+    /// `v` rotated, without the translation, e.g. a normal.
+    #[must_use]
+    pub fn rotate(&self, v: [f64; 3]) -> [f64; 3] {
+        let [w, x, y, z] = self.rotation;
+        // v + 2w(q × v) + 2q × (q × v), with q the vector part.
+        let q = [x, y, z];
+        let t = cross(q, v).map(|c| 2.0 * c);
+        let u = cross(q, t);
+        [0, 1, 2].map(|i| v[i] + w * t[i] + u[i])
+    }
+
+    /// `p` rotated and translated.
+    #[must_use]
+    pub fn transform_point(&self, p: [f64; 3]) -> [f64; 3] {
+        let r = self.rotate(p);
+        [0, 1, 2].map(|i| r[i] + self.translation[i])
+    }
+
+    /// The transform undoing this one.
+    #[must_use]
+    pub fn inverse(&self) -> Pose {
+        let [w, x, y, z] = self.rotation;
+        let rotation = Pose {
+            translation: [0.0; 3],
+            rotation: [w, -x, -y, -z],
+        };
+        Pose {
+            translation: rotation.rotate(self.translation).map(|c| -c),
+            rotation: rotation.rotation,
+        }
+    }
+
+    /// `other` followed by this pose: the transform that applies `other`
+    /// first, as the product of their matrices `self * other`.
+    #[must_use]
+    pub fn compose(&self, other: &Pose) -> Pose {
+        let ([aw, ax, ay, az], [bw, bx, by, bz]) = (self.rotation, other.rotation);
+        Pose {
+            translation: self.transform_point(other.translation),
+            rotation: normalize([
+                aw * bw - ax * bx - ay * by - az * bz,
+                aw * bx + ax * bw + ay * bz - az * by,
+                aw * by - ax * bz + ay * bw + az * bx,
+                aw * bz + ax * by - ay * bx + az * bw,
+            ]),
+        }
+    }
+
+    /// The pose a fraction `t` of the way to `other`: translation
+    /// interpolated linearly, rotation spherically (SLERP) along the
+    /// shorter arc. `t` outside `0..=1` extrapolates.
+    #[must_use]
+    pub fn interpolate(&self, other: &Pose, t: f64) -> Pose {
+        let translation = [0, 1, 2]
+            .map(|i| self.translation[i] + t * (other.translation[i] - self.translation[i]));
+        let (a, mut b) = (self.rotation, other.rotation);
+        let mut dot: f64 = (0..4).map(|i| a[i] * b[i]).sum();
+        if dot < 0.0 {
+            b = b.map(|c| -c);
+            dot = -dot;
+        }
+        let rotation = if dot > 0.9995 {
+            // Nearly equal: linear interpolation avoids dividing by sin θ ≈ 0.
+            normalize([0, 1, 2, 3].map(|i| a[i] + t * (b[i] - a[i])))
+        } else {
+            let theta = dot.min(1.0).acos();
+            let (wa, wb) = (
+                ((1.0 - t) * theta).sin() / theta.sin(),
+                (t * theta).sin() / theta.sin(),
+            );
+            normalize([0, 1, 2, 3].map(|i| wa * a[i] + wb * b[i]))
+        };
+        Pose {
+            translation,
+            rotation,
+        }
+    }
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn normalize(q: [f64; 4]) -> [f64; 4] {
+    let norm = q.iter().map(|c| c * c).sum::<f64>().sqrt();
+    if norm > 0.0 && norm.is_finite() {
+        q.map(|c| c / norm)
+    } else {
+        Pose::IDENTITY.rotation
+    }
+}
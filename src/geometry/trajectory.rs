@@ -0,0 +1,85 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Timestamped poses, such as an odometry or GNSS/INS trajectory.
+
+use super::Pose;
+
+/// Poses ordered by time, interpolated in between.
+#[derive(Debug, Clone, Default)]
+pub struct Trajectory {
+    times: Vec<f64>,
+    poses: Vec<Pose>,
+}
+
+impl Trajectory {
+    /// Trajectory through `poses`, each with its time, in any order. Poses
+    /// with a NaN time are left out.
+    pub fn new(poses: impl IntoIterator<Item = (f64, Pose)>) -> Self {
+        let mut poses: Vec<(f64, Pose)> = poses.into_iter().filter(|(t, _)| !t.is_nan()).collect();
+        poses.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let (times, poses) = poses.into_iter().unzip();
+        Self { times, poses }
+    }
+
+    /// Number of poses.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.poses.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.poses.is_empty()
+    }
+
+    /// Time of the first and last pose, `None` if there are none.
+    #[must_use]
+    pub fn time_range(&self) -> Option<(f64, f64)> {
+        Some((*self.times.first()?, *self.times.last()?))
+    }
+
+    /// The poses with their times, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (f64, &Pose)> + '_ {
+        self.times.iter().copied().zip(&self.poses)
+    }
+
+    /// The pose at time `t`, interpolated between the poses around it (see
+    /// [`Pose::interpolate`]); before the first pose or after the last, that
+    /// pose. `None` if the trajectory is empty or `t` is NaN.
+    #[must_use]
+    pub fn pose_at(&self, t: f64) -> Option<Pose> {
+        if t.is_nan() {
+            return None;
+        }
+        // Index of the first pose after `t`.
+        let after = self.times.partition_point(|&time| time <= t);
+        if after == 0 {
+            return self.poses.first().copied();
+        }
+        if after == self.poses.len() {
+            return self.poses.last().copied();
+        }
+        let (t0, t1) = (self.times[after - 1], self.times[after]);
+        let fraction = (t - t0) / (t1 - t0);
+        Some(self.poses[after - 1].interpolate(&self.poses[after], fraction))
+    }
+
+    /// The motion from time `from` to time `to`: the pose at `to` relative
+    /// to the pose at `from`, e.g. a frame-to-frame odometry increment.
+    #[must_use]
+    pub fn relative(&self, from: f64, to: f64) -> Option<Pose> {
+        Some(self.pose_at(from)?.inverse().compose(&self.pose_at(to)?))
+    }
+}
@@ -33,18 +33,22 @@
 //!     .unwrap();
 //! ```
 
-use super::{DataFormat, PcdHeader, ValueType};
+use super::{DataFormat, PcdHeader, Schema, SchemaField, ValueType};
 use crate::error::{PcdError, Result};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 
 /// Builder for constructing PcdHeader with a fluent API.
 #[derive(Debug, Clone)]
 pub struct PcdHeaderBuilder {
-    fields: Vec<(String, ValueType)>,
+    fields: Schema,
     width: Option<u32>,
     height: u32,
     data: DataFormat,
     viewpoint: [f64; 7],
     version: String,
+    comments: Vec<String>,
 }
 
 impl Default for PcdHeaderBuilder {
@@ -57,24 +61,116 @@ impl PcdHeaderBuilder {
     /// Create a new builder with default values.
     pub fn new() -> Self {
         Self {
-            fields: Vec::new(),
+            fields: Schema::new(),
             width: None,
             height: 1,
             data: DataFormat::Binary,
             viewpoint: [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0],
             version: "0.7".to_string(),
+            comments: Vec::new(),
         }
     }
 
+    /// `x y z` as F32, the fields read by `PointBlock::xyz`.
+    pub fn xyz() -> Self {
+        Self::new()
+            .add_field("x", ValueType::F32)
+            .add_field("y", ValueType::F32)
+            .add_field("z", ValueType::F32)
+    }
+
+    /// [`xyz`](Self::xyz) + `intensity` (F32), as read by `PointBlock::xyzi`.
+    pub fn xyzi() -> Self {
+        Self::xyz().add_field("intensity", ValueType::F32)
+    }
+
+    /// [`xyzi`](Self::xyzi) + `ring` (U16), as read by `PointBlock::xyzir`.
+    pub fn xyzir() -> Self {
+        Self::xyzi().add_field("ring", ValueType::U16)
+    }
+
+    /// [`xyzir`](Self::xyzir) + `timestamp` (F64), as read by
+    /// `PointBlock::xyzirt`.
+    pub fn xyzirt() -> Self {
+        Self::xyzir().add_field("timestamp", ValueType::F64)
+    }
+
+    /// [`xyzirt`](Self::xyzirt) + `id` (U32), as read by
+    /// `PointBlock::xyzirt_id`.
+    pub fn xyzirt_id() -> Self {
+        Self::xyzirt().add_field("id", ValueType::U32)
+    }
+
+    /// [`xyz`](Self::xyz) + packed `rgb` (U32), as read by
+    /// `PointBlock::xyzrgb`.
+    pub fn xyzrgb() -> Self {
+        Self::xyz().add_rgb_packed()
+    }
+
+    /// [`xyz`](Self::xyz) + packed `rgba` (U32, 0xAARRGGBB), as read by
+    /// `PointBlock::xyzrgba`.
+    pub fn xyzrgba() -> Self {
+        Self::xyz().add_field("rgba", ValueType::U32)
+    }
+
+    /// [`xyz`](Self::xyz) + `label` (U32), as read by `PointBlock::xyzl`.
+    pub fn xyzl() -> Self {
+        Self::xyz().add_field("label", ValueType::U32)
+    }
+
+    /// [`xyz`](Self::xyz) + `normal_x`, `normal_y`, `normal_z` and
+    /// `curvature` (F32), as read by `PointBlock::xyz_normals`.
+    pub fn xyz_normals() -> Self {
+        Self::xyz()
+            .add_field("normal_x", ValueType::F32)
+            .add_field("normal_y", ValueType::F32)
+            .add_field("normal_z", ValueType::F32)
+            .add_field("curvature", ValueType::F32)
+    }
+
+    /// Start from the fields of `schema`.
+    pub fn from_schema(schema: Schema) -> Self {
+        Self {
+            fields: schema,
+            ..Self::new()
+        }
+    }
+
+    /// The fields added so far, for `PointBlock::new`.
+    pub fn schema(&self) -> Schema {
+        self.fields.clone()
+    }
+
     /// Add a field with the given name and type.
-    /// Fields are added in order and can only have count=1.
+    /// Fields are added in order and have count=1.
     /// For fields with count > 1, use `add_field_with_count`.
     #[must_use]
-    pub fn add_field(mut self, name: &str, value_type: ValueType) -> Self {
-        self.fields.push((name.to_string(), value_type));
+    pub fn add_field(self, name: &str, value_type: ValueType) -> Self {
+        self.add_field_with_count(name, value_type, 1)
+    }
+
+    /// Add a field holding `count` values per point, e.g. a histogram.
+    /// `build` rejects a count of 0.
+    #[must_use]
+    pub fn add_field_with_count(mut self, name: &str, value_type: ValueType, count: usize) -> Self {
+        self.fields = self.fields.with_field_count(name, value_type, count);
         self
     }
 
+    /// Add the PCL-style packed color field `rgb` (one U32 per point, 0x00RRGGBB),
+    /// as read by `PointBlock::xyzrgb`.
+    #[must_use]
+    pub fn add_rgb_packed(self) -> Self {
+        self.add_field("rgb", ValueType::U32)
+    }
+
+    /// Add a feature descriptor of `length` F32 values per point,
+    /// e.g. `add_descriptor("fpfh", 33)`.
+    #[must_use]
+    pub fn add_descriptor(self, name: &str, length: usize) -> Self {
+        self.add_field_with_count(name, ValueType::F32, length)
+    }
+
     /// Set the width (number of points per row).
     /// For unorganized point clouds, this equals the total number of points.
     #[must_use]
@@ -91,7 +187,8 @@ impl PcdHeaderBuilder {
         self
     }
 
-    /// Set the data format (Ascii, Binary, or BinaryCompressed).
+    /// Set the data format (Ascii, Binary, BinaryCompressed, or BinaryZstd
+    /// with the `zstd` feature).
     /// Default is Binary.
     #[must_use]
     pub fn data_format(mut self, fmt: DataFormat) -> Self {
@@ -115,8 +212,17 @@ impl PcdHeaderBuilder {
         self
     }
 
+    /// Add a `#` comment line, e.g. provenance information.
+    /// Comments are written before `VERSION`, in the order added.
+    #[must_use]
+    pub fn comment(mut self, text: &str) -> Self {
+        self.comments.push(text.to_string());
+        self
+    }
+
     /// Build the PcdHeader.
-    /// Returns an error if width is not set.
+    /// Returns an error if width is not set, no field was added, or a field
+    /// has an empty or repeated name or a count of 0.
     pub fn build(self) -> Result<PcdHeader> {
         let width = self.width.ok_or_else(|| {
             PcdError::InvalidHeader {
@@ -132,16 +238,35 @@ impl PcdHeaderBuilder {
             });
         }
 
+        if let Some(name) = self.fields.duplicate() {
+            return Err(PcdError::InvalidHeader {
+                line: 0,
+                msg: format!("Duplicate field {}", name),
+            });
+        }
+
         let mut field_names = Vec::with_capacity(self.fields.len());
         let mut sizes = Vec::with_capacity(self.fields.len());
         let mut types = Vec::with_capacity(self.fields.len());
         let mut counts = Vec::with_capacity(self.fields.len());
 
-        for (name, vtype) in &self.fields {
+        for SchemaField { name, type_, count } in &self.fields {
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                return Err(PcdError::InvalidHeader {
+                    line: 0,
+                    msg: format!("Invalid field name `{}`", name),
+                });
+            }
+            if *count == 0 {
+                return Err(PcdError::InvalidHeader {
+                    line: 0,
+                    msg: format!("Field {} must have a count of at least 1", name),
+                });
+            }
             field_names.push(name.clone());
-            sizes.push(vtype.size());
-            types.push(value_type_to_char(*vtype));
-            counts.push(1);
+            sizes.push(type_.size());
+            types.push(type_.type_char());
+            counts.push(*count);
         }
 
         let points = (width as usize) * (self.height as usize);
@@ -157,15 +282,8 @@ impl PcdHeaderBuilder {
             viewpoint: self.viewpoint,
             points,
             data: self.data,
+            comments: self.comments,
+            extras: Vec::new(),
         })
     }
 }
-
-/// Convert ValueType to PCD type character.
-fn value_type_to_char(vtype: ValueType) -> char {
-    match vtype {
-        ValueType::I8 | ValueType::I16 | ValueType::I32 => 'I',
-        ValueType::U8 | ValueType::U16 | ValueType::U32 => 'U',
-        ValueType::F32 | ValueType::F64 => 'F',
-    }
-}
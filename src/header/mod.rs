@@ -13,12 +13,24 @@
 // limitations under the License.
 
 use crate::error::{PcdError, Result};
-use std::str::FromStr;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
 
-mod parser;
 mod builder;
-pub use parser::parse_header;
+mod parser;
+mod schema;
 pub use builder::PcdHeaderBuilder;
+pub use parser::{DuplicateFields, HeaderOptions};
+#[cfg(feature = "std")]
+pub(crate) use parser::parse_header_counted;
+#[cfg(feature = "std")]
+pub use parser::{parse_header, parse_header_with};
+pub use parser::{parse_header_bytes, parse_header_bytes_with};
+pub use schema::{Schema, SchemaField};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum DataFormat {
@@ -26,6 +38,21 @@ pub enum DataFormat {
     Ascii,
     Binary,
     BinaryCompressed,
+    /// Non-standard extension (`DATA binary_zstd`): the `binary_compressed`
+    /// layout with zstd instead of LZF, for pipelines that control both
+    /// ends. Other PCD readers, PCL's included, reject it. Requires the
+    /// `zstd` feature.
+    #[cfg(feature = "zstd")]
+    BinaryZstd,
+}
+
+impl DataFormat {
+    /// Whether the data section is a single compressed block of all points,
+    /// which can only be read or written whole.
+    #[must_use]
+    pub fn is_compressed(self) -> bool {
+        !matches!(self, DataFormat::Ascii | DataFormat::Binary)
+    }
 }
 
 impl FromStr for DataFormat {
@@ -36,19 +63,40 @@ impl FromStr for DataFormat {
             "ascii" => Ok(DataFormat::Ascii),
             "binary" => Ok(DataFormat::Binary),
             "binary_compressed" => Ok(DataFormat::BinaryCompressed),
+            #[cfg(feature = "zstd")]
+            "binary_zstd" => Ok(DataFormat::BinaryZstd),
+            #[cfg(not(feature = "zstd"))]
+            "binary_zstd" => Err(PcdError::UnsupportedDataFormat(
+                "binary_zstd (requires the zstd feature)".to_string(),
+            )),
             _ => Err(PcdError::UnsupportedDataFormat(s.to_string())),
         }
     }
 }
 
+/// The `DATA` keyword.
+impl fmt::Display for DataFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DataFormat::Ascii => "ascii",
+            DataFormat::Binary => "binary",
+            DataFormat::BinaryCompressed => "binary_compressed",
+            #[cfg(feature = "zstd")]
+            DataFormat::BinaryZstd => "binary_zstd",
+        })
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ValueType {
     U8,
     U16,
     U32,
+    U64,
     I8,
     I16,
     I32,
+    I64,
     F32,
     F64,
 }
@@ -59,12 +107,12 @@ impl ValueType {
             ValueType::U8 | ValueType::I8 => 1,
             ValueType::U16 | ValueType::I16 => 2,
             ValueType::U32 | ValueType::I32 | ValueType::F32 => 4,
-            ValueType::F64 => 8,
+            ValueType::U64 | ValueType::I64 | ValueType::F64 => 8,
         }
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct PcdHeader {
     pub version: String,
     pub fields: Vec<String>,
@@ -76,6 +124,13 @@ pub struct PcdHeader {
     pub viewpoint: [f64; 7],
     pub points: usize,
     pub data: DataFormat,
+    /// Text of the `#` comment lines, without the `#`, in file order. They
+    /// are written back before `VERSION`.
+    pub comments: Vec<String>,
+    /// Unrecognized header lines as `(key, rest of line)`, e.g. custom vendor
+    /// keys. They are written back just before `DATA`; clear them to drop
+    /// them from the output.
+    pub extras: Vec<(String, String)>,
 }
 
 impl PcdHeader {
@@ -97,4 +152,118 @@ impl PcdHeader {
             .map(|(size, count)| size * count)
             .sum()
     }
+
+    /// Fix inconsistencies whose correct value can be derived, returning
+    /// whether anything changed:
+    ///
+    /// - a missing `COUNT` becomes 1 per field;
+    /// - `HEIGHT 0` becomes 1;
+    /// - `POINTS 0` becomes `WIDTH * HEIGHT`;
+    /// - otherwise, if `WIDTH * HEIGHT` differs from `POINTS`, the cloud is
+    ///   made unorganized with `WIDTH = POINTS`, since `POINTS` is what the
+    ///   data is read by.
+    pub fn normalize(&mut self) -> bool {
+        let before = (self.counts.len(), self.width, self.height, self.points);
+        if self.counts.is_empty() {
+            self.counts = vec![1; self.fields.len()];
+        }
+        if self.height == 0 {
+            self.height = 1;
+        }
+        let area = self.width as usize * self.height as usize;
+        if self.points == 0 {
+            self.points = area;
+        } else if area != self.points {
+            self.width = self.points as u32;
+            self.height = 1;
+        }
+        before != (self.counts.len(), self.width, self.height, self.points)
+    }
+
+    /// The fields with their types and counts; fails on a `TYPE`/`SIZE`
+    /// pair with no matching [`ValueType`].
+    pub fn schema(&self) -> Result<Schema> {
+        Schema::from_header(self)
+    }
+
+    /// The first field name that occurs more than once.
+    pub fn duplicate_field(&self) -> Option<&str> {
+        self.fields
+            .iter()
+            .enumerate()
+            .find(|(i, name)| self.fields[..*i].contains(name))
+            .map(|(_, name)| name.as_str())
+    }
+
+    /// Make field names unique: each occurrence of a repeated `name` becomes
+    /// `name_0`, `name_1`, ... in order (`_0`, `_1`, ... for the `_` padding
+    /// fields), skipping names already in use. Returns whether anything was
+    /// renamed.
+    pub fn rename_duplicate_fields(&mut self) -> bool {
+        let mut renamed = false;
+        while let Some(name) = self.duplicate_field() {
+            let name = name.to_string();
+            let stem = if name.ends_with('_') {
+                name.clone()
+            } else {
+                format!("{}_", name)
+            };
+            let mut suffix = 0;
+            for i in 0..self.fields.len() {
+                if self.fields[i] != name {
+                    continue;
+                }
+                let unique = loop {
+                    let candidate = format!("{}{}", stem, suffix);
+                    suffix += 1;
+                    if !self.fields.contains(&candidate) {
+                        break candidate;
+                    }
+                };
+                self.fields[i] = unique;
+            }
+            renamed = true;
+        }
+        renamed
+    }
+}
+
+/// The header text as written to a file: comments, the header lines in
+/// standard order, extra keys, and the `DATA` line, each ending in `\n`.
+impl fmt::Display for PcdHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for comment in &self.comments {
+            if comment.is_empty() {
+                writeln!(f, "#")?;
+            } else {
+                writeln!(f, "# {}", comment)?;
+            }
+        }
+        writeln!(f, "VERSION {}", self.version)?;
+        write_line(f, "FIELDS", &self.fields)?;
+        write_line(f, "SIZE", &self.sizes)?;
+        write_line(f, "TYPE", &self.types)?;
+        write_line(f, "COUNT", &self.counts)?;
+        writeln!(f, "WIDTH {}", self.width)?;
+        writeln!(f, "HEIGHT {}", self.height)?;
+        write_line(f, "VIEWPOINT", &self.viewpoint)?;
+        writeln!(f, "POINTS {}", self.points)?;
+        for (key, value) in &self.extras {
+            if value.is_empty() {
+                writeln!(f, "{}", key)?;
+            } else {
+                writeln!(f, "{} {}", key, value)?;
+            }
+        }
+        writeln!(f, "DATA {}", self.data)
+    }
+}
+
+/// `key v1 v2 ...`
+fn write_line<T: fmt::Display>(f: &mut fmt::Formatter<'_>, key: &str, values: &[T]) -> fmt::Result {
+    f.write_str(key)?;
+    for value in values {
+        write!(f, " {}", value)?;
+    }
+    writeln!(f)
 }
@@ -14,34 +14,162 @@
 
 use super::PcdHeader;
 use crate::error::{PcdError, Result};
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::str::FromStr;
+#[cfg(feature = "std")]
 use std::io::BufRead;
 
+#[cfg(feature = "std")]
 pub fn parse_header<R: BufRead>(reader: &mut R) -> Result<PcdHeader> {
-    let mut header = PcdHeader::default();
-    let mut line_num = 0;
+    parse_header_with(reader, &HeaderOptions::default())
+}
 
-    // Explicitly set viewpoint default to identity
-    header.viewpoint = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0];
+/// [`parse_header`] with explicit options.
+#[cfg(feature = "std")]
+pub fn parse_header_with<R: BufRead>(reader: &mut R, options: &HeaderOptions) -> Result<PcdHeader> {
+    Ok(parse_header_counted(reader, options)?.0)
+}
 
+/// [`parse_header_with`], also returning the length of the header in bytes.
+#[cfg(feature = "std")]
+pub(crate) fn parse_header_counted<R: BufRead>(
+    reader: &mut R,
+    options: &HeaderOptions,
+) -> Result<(PcdHeader, u64)> {
+    let mut parser = HeaderParser::new(*options);
+    let mut len = 0;
     loop {
         let mut line = String::new();
         let bytes_read = reader.read_line(&mut line)?;
         if bytes_read == 0 {
-            return Err(PcdError::InvalidHeader {
-                line: line_num,
-                msg: "Unexpected EOF before DATA section".to_string(),
-            });
+            return Err(parser.eof());
+        }
+        len += bytes_read as u64;
+        if let Some(header) = parser.line(&line)? {
+            return Ok((header, len));
+        }
+    }
+}
+
+/// Parse the header at the start of `bytes`, returning it with the offset of
+/// the data section. Available without `std`.
+pub fn parse_header_bytes(bytes: &[u8]) -> Result<(PcdHeader, usize)> {
+    parse_header_bytes_with(bytes, &HeaderOptions::default())
+}
+
+/// [`parse_header_bytes`] with explicit options.
+pub fn parse_header_bytes_with(
+    bytes: &[u8],
+    options: &HeaderOptions,
+) -> Result<(PcdHeader, usize)> {
+    let mut parser = HeaderParser::new(*options);
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let len = bytes[pos..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(bytes.len() - pos, |i| i + 1);
+        let line =
+            core::str::from_utf8(&bytes[pos..pos + len]).map_err(|_| PcdError::InvalidHeader {
+                line: parser.line_num + 1,
+                msg: "Header is not valid UTF-8".to_string(),
+            })?;
+        pos += len;
+        if let Some(header) = parser.line(line)? {
+            return Ok((header, pos));
+        }
+    }
+    Err(parser.eof())
+}
+
+/// Header parsing options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeaderOptions {
+    /// Accept headers missing `WIDTH`, `HEIGHT` or `POINTS`, as written by
+    /// some PCD 0.6 tools: `HEIGHT` defaults to 1, `POINTS` to
+    /// `WIDTH * HEIGHT` and `WIDTH` to `POINTS / HEIGHT`. Otherwise each of
+    /// them is required. A missing `VIEWPOINT` is the identity either way.
+    pub lenient: bool,
+    /// What to do with field names given more than once.
+    pub duplicates: DuplicateFields,
+}
+
+impl HeaderOptions {
+    /// Options with [`lenient`](Self::lenient) set.
+    pub fn lenient() -> Self {
+        Self {
+            lenient: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// Handling of repeated names in `FIELDS`, such as several `_` padding
+/// fields. Columns are looked up by name, so names must be unique.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateFields {
+    /// Rename them with [`PcdHeader::rename_duplicate_fields`].
+    #[default]
+    Rename,
+    /// Fail with `InvalidHeader`.
+    Error,
+}
+
+/// Line-by-line header state, shared by the reader and slice parsers.
+struct HeaderParser {
+    header: PcdHeader,
+    line_num: usize,
+    options: HeaderOptions,
+    // Which of the dimension lines were present.
+    seen_width: bool,
+    seen_height: bool,
+    seen_points: bool,
+}
+
+impl HeaderParser {
+    fn new(options: HeaderOptions) -> Self {
+        Self {
+            header: PcdHeader {
+                // Explicitly set viewpoint default to identity
+                viewpoint: [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0],
+                ..Default::default()
+            },
+            line_num: 0,
+            options,
+            seen_width: false,
+            seen_height: false,
+            seen_points: false,
+        }
+    }
+
+    fn eof(&self) -> PcdError {
+        PcdError::InvalidHeader {
+            line: self.line_num,
+            msg: "Unexpected EOF before DATA section".to_string(),
         }
-        line_num += 1;
+    }
+
+    /// Consume one line; returns the finished header at the `DATA` line.
+    fn line(&mut self, line: &str) -> Result<Option<PcdHeader>> {
+        self.line_num += 1;
+        let line_num = self.line_num;
+        let header = &mut self.header;
 
         let trimmed = line.trim();
-        if trimmed.is_empty() || trimmed.starts_with('#') {
-            continue;
+        if let Some(comment) = trimmed.strip_prefix('#') {
+            header.comments.push(comment.trim().to_string());
+            return Ok(None);
+        }
+        if trimmed.is_empty() {
+            return Ok(None);
         }
 
         let parts: Vec<&str> = trimmed.split_whitespace().collect();
         if parts.is_empty() {
-            continue;
+            return Ok(None);
         }
 
         match parts[0] {
@@ -74,9 +202,11 @@ pub fn parse_header<R: BufRead>(reader: &mut R) -> Result<PcdHeader> {
             }
             "WIDTH" => {
                 header.width = parse_single(parts.get(1), line_num, "WIDTH")?;
+                self.seen_width = true;
             }
             "HEIGHT" => {
                 header.height = parse_single(parts.get(1), line_num, "HEIGHT")?;
+                self.seen_height = true;
             }
             "VIEWPOINT" => {
                 let vp: Vec<f64> = parse_vec(&parts[1..], line_num, "VIEWPOINT")?;
@@ -91,6 +221,7 @@ pub fn parse_header<R: BufRead>(reader: &mut R) -> Result<PcdHeader> {
             }
             "POINTS" => {
                 header.points = parse_single(parts.get(1), line_num, "POINTS")?;
+                self.seen_points = true;
             }
             "DATA" => {
                 let fmt = parts.get(1).ok_or_else(|| PcdError::InvalidHeader {
@@ -103,21 +234,73 @@ pub fn parse_header<R: BufRead>(reader: &mut R) -> Result<PcdHeader> {
                 if header.counts.is_empty() {
                     header.counts = vec![1; header.fields.len()];
                 }
+                self.fill_dimensions(line_num)?;
+                let header = &mut self.header;
 
                 // Validate header consistency
-                validate_header(&header, line_num)?;
+                validate_header(header, line_num)?;
+                if let Some(name) = header.duplicate_field() {
+                    if self.options.duplicates == DuplicateFields::Error {
+                        return Err(PcdError::InvalidHeader {
+                            line: line_num,
+                            msg: format!("Duplicate field {}", name),
+                        });
+                    }
+                    header.rename_duplicate_fields();
+                }
 
-                return Ok(header);
+                return Ok(Some(core::mem::take(header)));
             }
-            _ => {
-                // Ignore unknown fields as per spec? Or warn?
-                // For valid PCD, usually we shouldn't see random stuff, but tolerance is good.
+            key => {
+                // Unknown key; keep it so it survives a rewrite.
+                let value = trimmed[key.len()..].trim();
+                header.extras.push((key.to_string(), value.to_string()));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl HeaderParser {
+    /// Check that `WIDTH`, `HEIGHT` and `POINTS` were given, deriving the
+    /// missing ones in lenient mode.
+    fn fill_dimensions(&mut self, line: usize) -> Result<()> {
+        let missing = |key: &str| PcdError::InvalidHeader {
+            line,
+            msg: format!("Missing {} (HeaderOptions::lenient derives it)", key),
+        };
+        let header = &mut self.header;
+        if !self.options.lenient {
+            for (seen, key) in [
+                (self.seen_width, "WIDTH"),
+                (self.seen_height, "HEIGHT"),
+                (self.seen_points, "POINTS"),
+            ] {
+                if !seen {
+                    return Err(missing(key));
+                }
+            }
+            return Ok(());
+        }
+        if !self.seen_height {
+            header.height = 1;
+        }
+        match (self.seen_width, self.seen_points) {
+            (true, true) => {}
+            (true, false) => header.points = header.width as usize * header.height as usize,
+            (false, true) => header.width = (header.points / header.height.max(1) as usize) as u32,
+            (false, false) => {
+                return Err(PcdError::InvalidHeader {
+                    line,
+                    msg: "Need WIDTH or POINTS".to_string(),
+                });
             }
         }
+        Ok(())
     }
 }
 
-fn parse_vec<T: std::str::FromStr>(parts: &[&str], line: usize, field: &str) -> Result<Vec<T>> {
+fn parse_vec<T: FromStr>(parts: &[&str], line: usize, field: &str) -> Result<Vec<T>> {
     parts
         .iter()
         .map(|s| {
@@ -129,7 +312,7 @@ fn parse_vec<T: std::str::FromStr>(parts: &[&str], line: usize, field: &str) ->
         .collect()
 }
 
-fn parse_single<T: std::str::FromStr>(part: Option<&&str>, line: usize, field: &str) -> Result<T> {
+fn parse_single<T: FromStr>(part: Option<&&str>, line: usize, field: &str) -> Result<T> {
     match part {
         Some(s) => s.parse::<T>().map_err(|_| PcdError::InvalidHeader {
             line,
@@ -173,7 +356,7 @@ fn validate_header(header: &PcdHeader, line: usize) -> Result<()> {
         // We should move this logic to the main parser loop before calling validate_header.
         return Err(PcdError::InvalidHeader {
             line,
-            msg: format!("Counts vector empty but fields present (logic error in parser)"),
+            msg: "Counts vector empty but fields present (logic error in parser)".to_string(),
         });
     } else if header.counts.len() != header.fields.len() {
         return Err(PcdError::InvalidHeader {
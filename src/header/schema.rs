@@ -0,0 +1,208 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The ordered fields of a cloud, shared by [`PcdHeader`], its builder,
+//! [`PcdLayout`](crate::layout::PcdLayout) and
+//! [`PointBlock`](crate::storage::PointBlock).
+
+use super::{PcdHeader, ValueType};
+use crate::error::{PcdError, Result};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// One field: `count` values of `type_` per point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaField {
+    pub name: String,
+    pub type_: ValueType,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Schema {
+    fields: Vec<SchemaField>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The fields of `header`, from its `FIELDS`, `SIZE`, `TYPE` and `COUNT`
+    /// lines; a missing `COUNT` means 1.
+    pub fn from_header(header: &PcdHeader) -> Result<Self> {
+        let mut fields = Vec::with_capacity(header.fields.len());
+        for (i, name) in header.fields.iter().enumerate() {
+            let missing = || PcdError::LayoutMismatch {
+                expected: header.fields.len(),
+                got: i,
+            };
+            let type_char = *header.types.get(i).ok_or_else(missing)?;
+            let size = *header.sizes.get(i).ok_or_else(missing)?;
+            fields.push(SchemaField {
+                name: name.clone(),
+                type_: ValueType::from_pcd(type_char, size)?,
+                count: *header.counts.get(i).unwrap_or(&1),
+            });
+        }
+        Ok(Self { fields })
+    }
+
+    /// Add a field with a count of 1.
+    #[must_use]
+    pub fn with_field(self, name: &str, type_: ValueType) -> Self {
+        self.with_field_count(name, type_, 1)
+    }
+
+    #[must_use]
+    pub fn with_field_count(mut self, name: &str, type_: ValueType, count: usize) -> Self {
+        self.push(SchemaField {
+            name: name.to_string(),
+            type_,
+            count,
+        });
+        self
+    }
+
+    pub fn push(&mut self, field: SchemaField) {
+        self.fields.push(field);
+    }
+
+    pub fn fields(&self) -> &[SchemaField] {
+        &self.fields
+    }
+
+    pub(crate) fn field_mut(&mut self, index: usize) -> &mut SchemaField {
+        &mut self.fields[index]
+    }
+
+    pub(crate) fn remove(&mut self, index: usize) -> SchemaField {
+        self.fields.remove(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SchemaField> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.fields.iter().map(|f| f.name.as_str())
+    }
+
+    /// The first name that appears more than once.
+    pub fn duplicate(&self) -> Option<&str> {
+        let names = &self.fields;
+        (0..names.len())
+            .find(|&i| names[..i].iter().any(|f| f.name == names[i].name))
+            .map(|i| names[i].name.as_str())
+    }
+
+    pub fn iter(&self) -> core::slice::Iter<'_, SchemaField> {
+        self.fields.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Schema {
+    type Item = &'a SchemaField;
+    type IntoIter = core::slice::Iter<'a, SchemaField>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.fields.iter()
+    }
+}
+
+impl FromIterator<SchemaField> for Schema {
+    fn from_iter<I: IntoIterator<Item = SchemaField>>(iter: I) -> Self {
+        Self {
+            fields: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl From<&Schema> for Schema {
+    fn from(schema: &Schema) -> Self {
+        schema.clone()
+    }
+}
+
+// `(name, type)` pairs, each with a count of 1.
+impl From<&[(String, ValueType)]> for Schema {
+    fn from(pairs: &[(String, ValueType)]) -> Self {
+        pairs
+            .iter()
+            .map(|(name, type_)| SchemaField {
+                name: name.clone(),
+                type_: *type_,
+                count: 1,
+            })
+            .collect()
+    }
+}
+
+impl From<Vec<(String, ValueType)>> for Schema {
+    fn from(pairs: Vec<(String, ValueType)>) -> Self {
+        Self::from(pairs.as_slice())
+    }
+}
+
+impl From<&Vec<(String, ValueType)>> for Schema {
+    fn from(pairs: &Vec<(String, ValueType)>) -> Self {
+        Self::from(pairs.as_slice())
+    }
+}
+
+impl<const N: usize> From<&[(String, ValueType); N]> for Schema {
+    fn from(pairs: &[(String, ValueType); N]) -> Self {
+        Self::from(pairs.as_slice())
+    }
+}
+
+impl ValueType {
+    /// The type of a PCD `TYPE` character and `SIZE`.
+    pub fn from_pcd(type_char: char, size: usize) -> Result<Self> {
+        Ok(match (type_char, size) {
+            ('I', 1) => ValueType::I8,
+            ('I', 2) => ValueType::I16,
+            ('I', 4) => ValueType::I32,
+            ('I', 8) => ValueType::I64,
+            ('U', 1) => ValueType::U8,
+            ('U', 2) => ValueType::U16,
+            ('U', 4) => ValueType::U32,
+            ('U', 8) => ValueType::U64,
+            ('F', 4) => ValueType::F32,
+            ('F', 8) => ValueType::F64,
+            ('I' | 'U' | 'F', _) => {
+                return Err(PcdError::UnsupportedType(format!("{}{}", type_char, size)));
+            }
+            _ => return Err(PcdError::UnsupportedType(type_char.to_string())),
+        })
+    }
+
+    /// The PCD `TYPE` character.
+    pub fn type_char(&self) -> char {
+        match self {
+            ValueType::I8 | ValueType::I16 | ValueType::I32 | ValueType::I64 => 'I',
+            ValueType::U8 | ValueType::U16 | ValueType::U32 | ValueType::U64 => 'U',
+            ValueType::F32 | ValueType::F64 => 'F',
+        }
+    }
+}
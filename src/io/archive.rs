@@ -0,0 +1,485 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reading PCD entries straight out of `.zip` and `.tar` archives.
+//!
+//! Entries whose name ends in `.pcd` are decoded in archive order; all other
+//! entries are skipped without being read. Zip entries may be stored or, with
+//! the `flate2` feature, deflated. Zip64 and encrypted entries are rejected.
+//! Tar archives may use ustar, GNU long names or pax `path` records.
+//!
+//! Entry sizes are checked against the archive's length and a memory limit
+//! before anything is allocated for them, so a corrupt or hostile header
+//! fails with [`PcdError::InvalidDataFormat`].
+
+use super::reader::PcdReader;
+use crate::error::{PcdError, Result};
+use crate::layout::DEFAULT_MEMORY_LIMIT;
+use crate::storage::PointBlock;
+use byteorder::{ByteOrder, LittleEndian};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+const ZIP_LOCAL_SIG: u32 = 0x0403_4b50;
+const ZIP_CENTRAL_SIG: u32 = 0x0201_4b50;
+const ZIP_END_SIG: u32 = 0x0605_4b50;
+// End of central directory record plus the longest possible comment.
+const ZIP_END_SEARCH: u64 = 22 + 0xffff;
+
+const TAR_BLOCK: usize = 512;
+
+/// Iterator over the `(name, PointBlock)` pairs of the PCD entries in an
+/// archive.
+///
+/// An entry that fails to decode yields an error and iteration continues
+/// with the next one; a malformed archive ends iteration after its error.
+pub struct ArchiveReader<R: Read + Seek> {
+    reader: R,
+    kind: ArchiveKind,
+    /// Length of the whole archive in bytes.
+    len: u64,
+    memory_limit: u64,
+    done: bool,
+}
+
+enum ArchiveKind {
+    Zip(VecDeque<ZipEntry>),
+    Tar,
+}
+
+struct ZipEntry {
+    name: String,
+    method: u16,
+    compressed_size: u64,
+    uncompressed_size: u64,
+    local_offset: u64,
+}
+
+impl ArchiveReader<BufReader<File>> {
+    /// Open a zip or tar file; the format is detected from its contents.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read + Seek> ArchiveReader<R> {
+    /// Wrap a zip or tar archive, detecting the format from its contents.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut magic = [0u8; 4];
+        let n = read_full(&mut reader, &mut magic)?;
+        reader.seek(SeekFrom::Start(0))?;
+
+        let kind = if n == 4 && LittleEndian::read_u32(&magic) == ZIP_LOCAL_SIG {
+            ArchiveKind::Zip(read_zip_directory(&mut reader)?)
+        } else if is_tar(&mut reader)? {
+            ArchiveKind::Tar
+        } else {
+            return Err(PcdError::UnsupportedDataFormat(
+                "Not a zip or tar archive".to_string(),
+            ));
+        };
+        let len = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(0))?;
+        Ok(Self {
+            reader,
+            kind,
+            len,
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            done: false,
+        })
+    }
+
+    /// Refuse entries larger than `bytes`, uncompressed, and decode entries
+    /// under the same limit. Defaults to [`DEFAULT_MEMORY_LIMIT`]; lower it
+    /// for untrusted input.
+    #[must_use]
+    pub fn memory_limit(mut self, bytes: u64) -> Self {
+        self.memory_limit = bytes;
+        self
+    }
+
+    /// Read the next PCD entry's name and raw bytes, or `None` at the end.
+    fn next_entry(&mut self) -> Result<Option<(String, Vec<u8>)>> {
+        let limits = Limits {
+            len: self.len,
+            memory: self.memory_limit,
+        };
+        match &mut self.kind {
+            ArchiveKind::Zip(entries) => match entries.pop_front() {
+                Some(entry) => {
+                    let data = read_zip_entry(&mut self.reader, &entry, limits)?;
+                    Ok(Some((entry.name, data)))
+                }
+                None => Ok(None),
+            },
+            ArchiveKind::Tar => next_tar_entry(&mut self.reader, limits),
+        }
+    }
+}
+
+impl<R: Read + Seek> Iterator for ArchiveReader<R> {
+    type Item = Result<(String, PointBlock)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.next_entry() {
+            Ok(Some((name, data))) => {
+                let block = PcdReader::from_bytes(&data)
+                    .and_then(|r| r.memory_limit(self.memory_limit).read_all());
+                Some(block.map(|b| (name, b)))
+            }
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Bounds on the size of an entry.
+#[derive(Clone, Copy)]
+struct Limits {
+    /// Length of the archive.
+    len: u64,
+    memory: u64,
+}
+
+impl Limits {
+    /// Check that `size` bytes, stored from `offset` on and `stored` bytes
+    /// long in the archive, fit in it and in the memory limit.
+    fn check(&self, name: &str, size: u64, offset: u64, stored: u64) -> Result<usize> {
+        if size > self.memory {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Archive entry {} of {} bytes exceeds the memory limit of {} bytes",
+                name, size, self.memory
+            )));
+        }
+        if offset.checked_add(stored).is_none_or(|end| end > self.len) {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Archive entry {} of {} bytes runs past the end of the archive",
+                name, stored
+            )));
+        }
+        usize::try_from(size).map_err(|_| {
+            PcdError::InvalidDataFormat(format!("Archive entry {} is too large", name))
+        })
+    }
+}
+
+fn is_pcd_name(name: &str) -> bool {
+    name.to_ascii_lowercase().ends_with(".pcd")
+}
+
+/// Read as much of `buf` as the stream holds, returning the byte count.
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+fn read_zip_directory<R: Read + Seek>(reader: &mut R) -> Result<VecDeque<ZipEntry>> {
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    let tail_len = file_len.min(ZIP_END_SEARCH);
+    reader.seek(SeekFrom::Start(file_len - tail_len))?;
+    let mut tail = vec![0u8; tail_len as usize];
+    reader.read_exact(&mut tail)?;
+
+    let end = (0..tail.len().saturating_sub(21))
+        .rev()
+        .find(|&i| LittleEndian::read_u32(&tail[i..]) == ZIP_END_SIG)
+        .ok_or_else(|| {
+            PcdError::InvalidDataFormat("Zip end of central directory not found".to_string())
+        })?;
+    let eocd = &tail[end..];
+    let count = LittleEndian::read_u16(&eocd[10..]) as usize;
+    let dir_size = LittleEndian::read_u32(&eocd[12..]);
+    let dir_offset = LittleEndian::read_u32(&eocd[16..]);
+    if count == 0xffff || dir_size == u32::MAX || dir_offset == u32::MAX {
+        return Err(PcdError::UnsupportedDataFormat("Zip64 archive".to_string()));
+    }
+    if dir_offset as u64 + dir_size as u64 > file_len {
+        return Err(PcdError::InvalidDataFormat(
+            "Zip central directory runs past the end of the archive".to_string(),
+        ));
+    }
+
+    reader.seek(SeekFrom::Start(dir_offset as u64))?;
+    let mut dir = vec![0u8; dir_size as usize];
+    reader.read_exact(&mut dir)?;
+
+    let mut entries = VecDeque::new();
+    let mut pos = 0;
+    for _ in 0..count {
+        let fixed = dir
+            .get(pos..pos + 46)
+            .filter(|h| LittleEndian::read_u32(h) == ZIP_CENTRAL_SIG)
+            .ok_or_else(|| {
+                PcdError::InvalidDataFormat("Malformed zip central directory".to_string())
+            })?;
+        let flags = LittleEndian::read_u16(&fixed[8..]);
+        let method = LittleEndian::read_u16(&fixed[10..]);
+        let compressed_size = LittleEndian::read_u32(&fixed[20..]);
+        let uncompressed_size = LittleEndian::read_u32(&fixed[24..]);
+        let name_len = LittleEndian::read_u16(&fixed[28..]) as usize;
+        let extra_len = LittleEndian::read_u16(&fixed[30..]) as usize;
+        let comment_len = LittleEndian::read_u16(&fixed[32..]) as usize;
+        let local_offset = LittleEndian::read_u32(&fixed[42..]);
+        let name = dir.get(pos + 46..pos + 46 + name_len).ok_or_else(|| {
+            PcdError::InvalidDataFormat("Malformed zip central directory".to_string())
+        })?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        pos += 46 + name_len + extra_len + comment_len;
+
+        if !is_pcd_name(&name) {
+            continue;
+        }
+        if flags & 1 != 0 {
+            return Err(PcdError::UnsupportedDataFormat(format!(
+                "Encrypted zip entry {}",
+                name
+            )));
+        }
+        if [compressed_size, uncompressed_size, local_offset].contains(&u32::MAX) {
+            return Err(PcdError::UnsupportedDataFormat(format!(
+                "Zip64 entry {}",
+                name
+            )));
+        }
+        entries.push_back(ZipEntry {
+            name,
+            method,
+            compressed_size: compressed_size as u64,
+            uncompressed_size: uncompressed_size as u64,
+            local_offset: local_offset as u64,
+        });
+    }
+    Ok(entries)
+}
+
+fn read_zip_entry<R: Read + Seek>(
+    reader: &mut R,
+    entry: &ZipEntry,
+    limits: Limits,
+) -> Result<Vec<u8>> {
+    reader.seek(SeekFrom::Start(entry.local_offset))?;
+    let mut local = [0u8; 30];
+    reader.read_exact(&mut local)?;
+    if LittleEndian::read_u32(&local) != ZIP_LOCAL_SIG {
+        return Err(PcdError::InvalidDataFormat(format!(
+            "Bad zip local header for {}",
+            entry.name
+        )));
+    }
+    let skip =
+        LittleEndian::read_u16(&local[26..]) as i64 + LittleEndian::read_u16(&local[28..]) as i64;
+    let offset = reader.seek(SeekFrom::Current(skip))?;
+    let size = limits.check(
+        &entry.name,
+        entry.uncompressed_size,
+        offset,
+        entry.compressed_size,
+    )?;
+
+    let mut raw = reader.take(entry.compressed_size);
+    let mut data = Vec::with_capacity(size);
+    match entry.method {
+        0 => {
+            raw.read_to_end(&mut data)?;
+        }
+        // One byte past the declared size is enough to tell it was wrong.
+        8 => inflate(&mut raw, entry.uncompressed_size + 1, &mut data)?,
+        m => {
+            return Err(PcdError::UnsupportedDataFormat(format!(
+                "Zip compression method {} for {}",
+                m, entry.name
+            )));
+        }
+    }
+    if data.len() as u64 != entry.uncompressed_size {
+        return Err(PcdError::BufferTooSmall {
+            expected: entry.uncompressed_size as usize,
+            got: data.len(),
+        });
+    }
+    Ok(data)
+}
+
+#[cfg(feature = "flate2")]
+fn inflate<R: Read>(raw: R, limit: u64, out: &mut Vec<u8>) -> Result<()> {
+    flate2::read::DeflateDecoder::new(raw)
+        .take(limit)
+        .read_to_end(out)
+        .map_err(|e| PcdError::Decompression(e.to_string()))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "flate2"))]
+fn inflate<R: Read>(_raw: R, _limit: u64, _out: &mut Vec<u8>) -> Result<()> {
+    Err(PcdError::UnsupportedDataFormat(
+        "Deflated zip entries require the flate2 feature".to_string(),
+    ))
+}
+
+/// A tar archive starts with a header block whose checksum matches.
+fn is_tar<R: Read + Seek>(reader: &mut R) -> Result<bool> {
+    let mut block = [0u8; TAR_BLOCK];
+    let n = read_full(reader, &mut block)?;
+    reader.seek(SeekFrom::Start(0))?;
+    if n < TAR_BLOCK {
+        return Ok(false);
+    }
+    let Some(stored) = parse_octal(&block[148..156]) else {
+        return Ok(false);
+    };
+    Ok(tar_checksum(&block) == stored)
+}
+
+fn tar_checksum(block: &[u8]) -> u64 {
+    block
+        .iter()
+        .enumerate()
+        .map(|(i, &b)| if (148..156).contains(&i) { b' ' } else { b } as u64)
+        .sum()
+}
+
+fn parse_octal(field: &[u8]) -> Option<u64> {
+    let text = std::str::from_utf8(field).ok()?;
+    let text = text.trim_matches(|c: char| c == '\0' || c == ' ');
+    if text.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(text, 8).ok()
+}
+
+/// Entry size: octal, or GNU base-256 when the high bit is set.
+fn tar_size(field: &[u8]) -> Result<u64> {
+    if field[0] & 0x80 != 0 {
+        return Ok(field[1..]
+            .iter()
+            .fold((field[0] & 0x7f) as u64, |acc, &b| (acc << 8) | b as u64));
+    }
+    parse_octal(field).ok_or_else(|| PcdError::InvalidDataFormat("Bad tar entry size".to_string()))
+}
+
+fn tar_name(block: &[u8]) -> String {
+    let field = |range: std::ops::Range<usize>| {
+        let raw = &block[range];
+        let end = raw.iter().position(|&b| b == 0).unwrap_or(raw.len());
+        String::from_utf8_lossy(&raw[..end]).into_owned()
+    };
+    let name = field(0..100);
+    if &block[257..262] == b"ustar" {
+        let prefix = field(345..500);
+        if !prefix.is_empty() {
+            return format!("{}/{}", prefix, name);
+        }
+    }
+    name
+}
+
+/// The `path` record of a pax extended header, if any.
+fn pax_path(data: &[u8]) -> Option<String> {
+    let mut rest = data;
+    while !rest.is_empty() {
+        let space = rest.iter().position(|&b| b == b' ')?;
+        let len: usize = std::str::from_utf8(&rest[..space]).ok()?.parse().ok()?;
+        let record = rest.get(space + 1..len)?;
+        if let Some(value) = record.strip_prefix(b"path=") {
+            let value = value.strip_suffix(b"\n").unwrap_or(value);
+            return Some(String::from_utf8_lossy(value).into_owned());
+        }
+        rest = &rest[len..];
+    }
+    None
+}
+
+/// The data of a tar entry of `size` bytes, and the padding after it.
+fn read_tar_data<R: Read + Seek>(
+    reader: &mut R,
+    name: &str,
+    size: u64,
+    padded: u64,
+    limits: Limits,
+) -> Result<Vec<u8>> {
+    let offset = reader.stream_position()?;
+    let len = limits.check(name, size, offset, padded)?;
+    let mut data = Vec::with_capacity(len);
+    reader.take(size).read_to_end(&mut data)?;
+    if data.len() != len {
+        return Err(PcdError::BufferTooSmall {
+            expected: len,
+            got: data.len(),
+        });
+    }
+    reader.seek(SeekFrom::Current((padded - size) as i64))?;
+    Ok(data)
+}
+
+fn next_tar_entry<R: Read + Seek>(
+    reader: &mut R,
+    limits: Limits,
+) -> Result<Option<(String, Vec<u8>)>> {
+    // Name carried over from a GNU long name or pax header.
+    let mut long_name: Option<String> = None;
+    loop {
+        let mut block = [0u8; TAR_BLOCK];
+        if read_full(reader, &mut block)? < TAR_BLOCK || block.iter().all(|&b| b == 0) {
+            return Ok(None);
+        }
+        let stored = parse_octal(&block[148..156]);
+        if stored != Some(tar_checksum(&block)) {
+            return Err(PcdError::InvalidDataFormat(
+                "Bad tar header checksum".to_string(),
+            ));
+        }
+        let size = tar_size(&block[124..136])?;
+        let padded = size
+            .checked_next_multiple_of(TAR_BLOCK as u64)
+            .filter(|&padded| padded <= i64::MAX as u64)
+            .ok_or_else(|| PcdError::InvalidDataFormat("Bad tar entry size".to_string()))?;
+        let name = long_name.take().unwrap_or_else(|| tar_name(&block));
+
+        match block[156] {
+            b'L' | b'x' => {
+                let data = read_tar_data(reader, &name, size, padded, limits)?;
+                long_name = if block[156] == b'L' {
+                    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+                    Some(String::from_utf8_lossy(&data[..end]).into_owned())
+                } else {
+                    pax_path(&data)
+                };
+            }
+            b'0' | 0 if is_pcd_name(&name) => {
+                let data = read_tar_data(reader, &name, size, padded, limits)?;
+                return Ok(Some((name, data)));
+            }
+            _ => {
+                reader.seek(SeekFrom::Current(padded as i64))?;
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Non-blocking PCD reading on tokio (feature `async`).
+//!
+//! Only I/O is asynchronous: the header lines and each chunk of the data
+//! section are read with `AsyncBufRead`, then handed to the regular decoders.
+//! Binary and ASCII data can be consumed in chunks of points to bound memory;
+//! compressed data is column-major and always arrives as a single chunk.
+
+use crate::decoder::decode_slice;
+use crate::error::Result;
+use crate::header::{DataFormat, PcdHeader, parse_header};
+use crate::layout::{DEFAULT_MEMORY_LIMIT, PcdLayout};
+use crate::storage::PointBlock;
+use std::io::Cursor;
+use std::path::Path;
+use tokio::fs::File;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, BufReader};
+
+pub struct AsyncPcdReader<R> {
+    reader: R,
+    header: PcdHeader,
+    layout: PcdLayout,
+    remaining: usize,
+    memory_limit: u64,
+}
+
+impl AsyncPcdReader<BufReader<File>> {
+    pub async fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new(BufReader::new(File::open(path).await?)).await
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> AsyncPcdReader<R> {
+    /// Read and parse the header, leaving `reader` at the data section.
+    pub async fn new(mut reader: R) -> Result<Self> {
+        let mut raw = Vec::new();
+        loop {
+            let n = reader.read_until(b'\n', &mut raw).await?;
+            // EOF is reported by the header parser.
+            if n == 0 {
+                break;
+            }
+            let line = String::from_utf8_lossy(&raw[raw.len() - n..]);
+            if line.trim_start().starts_with("DATA") {
+                break;
+            }
+        }
+        let header = parse_header(&mut Cursor::new(raw))?;
+        let layout = PcdLayout::from_header(&header)?;
+        Ok(Self {
+            reader,
+            remaining: header.points,
+            header,
+            layout,
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+        })
+    }
+
+    pub fn header(&self) -> &PcdHeader {
+        &self.header
+    }
+
+    /// See [`PcdReader::memory_limit`](crate::io::PcdReader::memory_limit).
+    #[must_use]
+    pub fn memory_limit(mut self, bytes: u64) -> Self {
+        self.memory_limit = bytes;
+        self
+    }
+
+    /// Points not yet returned by [`read_chunk`](Self::read_chunk).
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    pub async fn read_all(mut self) -> Result<PointBlock> {
+        match self.read_chunk(usize::MAX).await? {
+            Some(block) => Ok(block),
+            None => Ok(PointBlock::new(self.layout.schema(), 0)),
+        }
+    }
+
+    /// Read and decode up to `max_points` further points, or `None` once the
+    /// data section is exhausted. Compressed data is returned whole.
+    pub async fn read_chunk(&mut self, max_points: usize) -> Result<Option<PointBlock>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        let points = if self.header.data.is_compressed() {
+            self.remaining
+        } else {
+            self.remaining.min(max_points.max(1))
+        };
+        let bytes = self.layout.block_size(points, self.memory_limit)?;
+        let buf = match self.header.data {
+            DataFormat::Binary => {
+                let mut buf = vec![0u8; bytes];
+                self.reader.read_exact(&mut buf).await?;
+                buf
+            }
+            DataFormat::Ascii => {
+                let mut buf = Vec::new();
+                for _ in 0..points {
+                    // A short read is reported by the decoder.
+                    if self.reader.read_until(b'\n', &mut buf).await? == 0 {
+                        break;
+                    }
+                }
+                buf
+            }
+            // binary_compressed and binary_zstd.
+            _ => {
+                let mut buf = vec![0u8; 8];
+                self.reader.read_exact(&mut buf).await?;
+                let compressed_size = u32::from_le_bytes(buf[..4].try_into().unwrap()) as usize;
+                buf.resize(8 + compressed_size, 0);
+                self.reader.read_exact(&mut buf[8..]).await?;
+                buf
+            }
+        };
+        let block = decode_slice(&buf, self.header.data, &self.layout, points)?;
+
+        self.remaining -= points;
+        Ok(Some(block))
+    }
+
+}
@@ -0,0 +1,203 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Non-blocking PCD writing on tokio (feature `async`).
+//!
+//! Encoding is done by [`PcdWriter`] into a buffer that is then written
+//! asynchronously. Besides whole clouds, binary and ASCII output can be
+//! streamed chunk by chunk:
+//!
+//! - [`begin_stream`](AsyncPcdWriter::begin_stream) takes the point count
+//!   from the header, and [`finish`](AsyncPcdWriter::finish) checks it was
+//!   met. This works on any sink, including sockets.
+//! - [`begin_deferred`](AsyncPcdWriter::begin_deferred) reserves space for
+//!   `WIDTH` and `POINTS`, and
+//!   [`finish_deferred`](AsyncPcdWriter::finish_deferred) seeks back to fill
+//!   in the number of points written. The sink must be seekable.
+
+use super::writer::PcdWriter;
+use crate::error::{PcdError, Result};
+use crate::header::{DataFormat, PcdHeader};
+use crate::storage::PointBlock;
+use std::io::SeekFrom;
+use tokio::io::{AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+
+/// Characters reserved for a deferred `WIDTH` or `POINTS` value.
+const COUNT_WIDTH: usize = 20;
+
+pub struct AsyncPcdWriter<W> {
+    writer: W,
+    stream: Option<Stream>,
+}
+
+struct Stream {
+    header: PcdHeader,
+    points: usize,
+    // Bytes written since the start of the header.
+    written: u64,
+    // Offsets of the WIDTH and POINTS values in deferred mode.
+    deferred: Option<[usize; 2]>,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncPcdWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            stream: None,
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    pub async fn write_pcd(&mut self, header: &PcdHeader, data: &PointBlock) -> Result<()> {
+        let mut buf = Vec::new();
+        PcdWriter::new(&mut buf).write_pcd(header, data)?;
+        self.writer.write_all(&buf).await?;
+        Ok(())
+    }
+
+    /// Start streaming a cloud of `header.points` points.
+    pub async fn begin_stream(&mut self, header: &PcdHeader) -> Result<()> {
+        let mut buf = Vec::new();
+        PcdWriter::new(&mut buf).write_header(header)?;
+        self.begin(header.clone(), buf, None).await
+    }
+
+    /// Start streaming a cloud whose size is not known yet. The header is
+    /// written as unorganized (`HEIGHT 1`); `WIDTH` and `POINTS` are filled
+    /// in by [`finish_deferred`](Self::finish_deferred).
+    pub async fn begin_deferred(&mut self, header: &PcdHeader) -> Result<()> {
+        let mut header = header.clone();
+        header.width = 0;
+        header.height = 1;
+        header.points = 0;
+
+        let mut raw = Vec::new();
+        PcdWriter::new(&mut raw).write_header(&header)?;
+        let mut buf = Vec::with_capacity(raw.len() + 2 * COUNT_WIDTH);
+        let mut offsets = [0; 2];
+        for line in String::from_utf8_lossy(&raw).lines() {
+            if let Some(key) = ["WIDTH", "POINTS"]
+                .into_iter()
+                .find(|k| line.split_whitespace().next() == Some(*k))
+            {
+                let slot = if key == "WIDTH" { 0 } else { 1 };
+                offsets[slot] = buf.len() + key.len() + 1;
+                buf.extend(format!("{} {:<w$}\n", key, 0, w = COUNT_WIDTH).bytes());
+            } else {
+                buf.extend(line.bytes());
+                buf.push(b'\n');
+            }
+        }
+        self.begin(header, buf, Some(offsets)).await
+    }
+
+    async fn begin(
+        &mut self,
+        header: PcdHeader,
+        buf: Vec<u8>,
+        deferred: Option<[usize; 2]>,
+    ) -> Result<()> {
+        if self.stream.is_some() {
+            return Err(PcdError::Other(
+                "A stream is already in progress".to_string(),
+            ));
+        }
+        if !matches!(header.data, DataFormat::Binary | DataFormat::Ascii) {
+            return Err(PcdError::UnsupportedDataFormat(format!(
+                "Streaming {:?} data",
+                header.data
+            )));
+        }
+        self.writer.write_all(&buf).await?;
+        self.stream = Some(Stream {
+            header,
+            points: 0,
+            written: buf.len() as u64,
+            deferred,
+        });
+        Ok(())
+    }
+
+    /// Append the points of `data` to the stream.
+    pub async fn write_chunk(&mut self, data: &PointBlock) -> Result<()> {
+        let stream = self
+            .stream
+            .as_mut()
+            .ok_or_else(|| PcdError::Other("No stream in progress".to_string()))?;
+        let mut chunk = stream.header.clone();
+        chunk.width = data.len as u32;
+        chunk.height = 1;
+        chunk.points = data.len;
+
+        let mut buf = Vec::new();
+        PcdWriter::new(&mut buf).write_body(&chunk, data)?;
+        self.writer.write_all(&buf).await?;
+        stream.points += data.len;
+        stream.written += buf.len() as u64;
+        Ok(())
+    }
+
+    /// End a [`begin_stream`](Self::begin_stream) stream, checking that the
+    /// declared number of points was written, and flush.
+    pub async fn finish(&mut self) -> Result<()> {
+        let stream = self
+            .stream
+            .take()
+            .ok_or_else(|| PcdError::Other("No stream in progress".to_string()))?;
+        if stream.deferred.is_some() {
+            return Err(PcdError::Other(
+                "Deferred streams must end with finish_deferred".to_string(),
+            ));
+        }
+        if stream.points != stream.header.points {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Header declares {} points but {} were written",
+                stream.header.points, stream.points
+            )));
+        }
+        self.writer.flush().await?;
+        Ok(())
+    }
+}
+
+impl<W: AsyncWrite + AsyncSeek + Unpin> AsyncPcdWriter<W> {
+    /// End a [`begin_deferred`](Self::begin_deferred) stream: write the
+    /// final point count into the header and flush. The writer is left at
+    /// the end of the data.
+    pub async fn finish_deferred(&mut self) -> Result<()> {
+        let stream = self
+            .stream
+            .take()
+            .ok_or_else(|| PcdError::Other("No stream in progress".to_string()))?;
+        let offsets = stream.deferred.ok_or_else(|| {
+            PcdError::Other("Fixed-count streams must end with finish".to_string())
+        })?;
+
+        let end = self.writer.stream_position().await?;
+        let start = end - stream.written;
+        let value = format!("{:<w$}", stream.points, w = COUNT_WIDTH);
+        for offset in offsets {
+            self.writer
+                .seek(SeekFrom::Start(start + offset as u64))
+                .await?;
+            self.writer.write_all(value.as_bytes()).await?;
+        }
+        self.writer.seek(SeekFrom::Start(end)).await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+}
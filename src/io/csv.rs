@@ -0,0 +1,267 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Delimited text point files (`.csv`, `.xyz`, `.txt`).
+//!
+//! Columns are separated by commas, semicolons, tabs or runs of whitespace.
+//! Field names come from an optional header row, repeated names being made
+//! unique as in PCD headers; without one the first three columns are named
+//! `x`, `y`, `z` and the rest `field_<n>`. Columns holding only integers are
+//! read as `U32` or `I32`. Lines starting with `#` or `//` are skipped.
+//!
+//! A field with a `COUNT` above one spans that many consecutive columns.
+
+use super::writer::{FloatFormat, push_value};
+use crate::decoder::ascii::parse_token;
+use crate::error::{PcdError, Result};
+use crate::header::{PcdHeader, Schema, SchemaField, ValueType};
+use crate::storage::PointBlock;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Options for reading and writing delimited point files.
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    /// Column delimiter. `None` auto-detects on read and uses `,` on write.
+    /// `' '` means "any run of whitespace".
+    pub delimiter: Option<char>,
+    /// Whether the first data line is a header row. `None` auto-detects on
+    /// read (a row with any non-numeric token) and writes a header.
+    pub has_header: Option<bool>,
+    /// Explicit schema. Overrides names from a header row and the inferred types.
+    pub schema: Option<Schema>,
+    /// Type used, without an explicit schema, for `x`, `y`, `z` and columns
+    /// not holding only integers. Default is F32.
+    pub default_type: ValueType,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: None,
+            has_header: None,
+            schema: None,
+            default_type: ValueType::F32,
+        }
+    }
+}
+
+/// Read a delimited point file from disk.
+pub fn read_csv_file<P: AsRef<Path>>(path: P, options: &CsvOptions) -> Result<PointBlock> {
+    let file = File::open(path)?;
+    read_csv(BufReader::new(file), options)
+}
+
+/// Read delimited point data from any buffered reader.
+pub fn read_csv<R: BufRead>(reader: R, options: &CsvOptions) -> Result<PointBlock> {
+    let mut lines = Vec::new();
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with("//") {
+            continue;
+        }
+        lines.push((line_idx + 1, trimmed.to_string()));
+    }
+
+    let Some((_, first)) = lines.first() else {
+        let schema = options.schema.clone().unwrap_or_default();
+        return Ok(PointBlock::new(schema, 0));
+    };
+
+    let delimiter = options.delimiter.unwrap_or_else(|| detect_delimiter(first));
+    let first_tokens = split_line(first, delimiter);
+    let has_header = options
+        .has_header
+        .unwrap_or_else(|| first_tokens.iter().any(|t| t.parse::<f64>().is_err()));
+
+    let data_lines = if has_header { &lines[1..] } else { &lines[..] };
+    let rows: Vec<_> = data_lines
+        .iter()
+        .map(|(line_num, line)| (*line_num, split_line(line, delimiter)))
+        .collect();
+
+    let schema = match &options.schema {
+        Some(schema) => schema.clone(),
+        None => {
+            let names = if has_header {
+                let mut names = PcdHeader {
+                    fields: first_tokens.iter().map(|t| normalize_name(t)).collect(),
+                    ..Default::default()
+                };
+                names.rename_duplicate_fields();
+                names.fields
+            } else {
+                (0..first_tokens.len()).map(default_name).collect()
+            };
+            names
+                .into_iter()
+                .enumerate()
+                .map(|(i, name)| SchemaField {
+                    type_: infer_type(&name, &rows, i, options.default_type),
+                    name,
+                    count: 1,
+                })
+                .collect()
+        }
+    };
+    let width: usize = schema.iter().map(|f| f.count).sum();
+    let mut block = PointBlock::new(&schema, rows.len());
+
+    for (i, (line_num, tokens)) in rows.iter().enumerate() {
+        if tokens.len() < width {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Line {}: expected {} columns, got {}",
+                line_num,
+                width,
+                tokens.len()
+            )));
+        }
+        let mut tokens = tokens.iter();
+        for (col_idx, field) in schema.iter().enumerate() {
+            let col = block.get_column_mut_by_index(col_idx).unwrap();
+            for k in 0..field.count {
+                let token = tokens.next().unwrap();
+                parse_token(col, i * field.count + k, token).map_err(|e| {
+                    PcdError::InvalidDataFormat(format!("Line {}: {}", line_num, e))
+                })?;
+            }
+        }
+    }
+
+    Ok(block)
+}
+
+/// Write a `PointBlock` as a delimited text file.
+pub fn write_csv_file<P: AsRef<Path>>(
+    path: P,
+    block: &PointBlock,
+    options: &CsvOptions,
+) -> Result<()> {
+    let file = File::create(path)?;
+    let mut writer = BufWriter::new(file);
+    write_csv(&mut writer, block, options)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write delimited point data to any writer.
+///
+/// Columns are written in schema order, or restricted to (and ordered by)
+/// `options.schema` when given. The values of a field with a `COUNT` above
+/// one go into consecutive columns named `<name>_0`, `<name>_1`, ...
+pub fn write_csv<W: Write>(mut writer: W, block: &PointBlock, options: &CsvOptions) -> Result<()> {
+    let names: Vec<String> = match &options.schema {
+        Some(schema) => schema.names().map(String::from).collect(),
+        None => block.schema().names().map(String::from).collect(),
+    };
+
+    let mut columns = Vec::with_capacity(names.len());
+    let mut titles = Vec::with_capacity(names.len());
+    for name in &names {
+        let column = block
+            .get_column(name)
+            .ok_or_else(|| PcdError::InvalidDataFormat(format!("Missing column {}", name)))?;
+        let count = block.values_per_point(name);
+        if count == 1 {
+            titles.push(name.clone());
+        } else {
+            titles.extend((0..count).map(|k| format!("{}_{}", name, k)));
+        }
+        columns.push((column, count));
+    }
+
+    let sep = options.delimiter.unwrap_or(',').to_string();
+
+    if options.has_header.unwrap_or(true) {
+        writeln!(writer, "{}", titles.join(&sep))?;
+    }
+
+    let mut line = Vec::new();
+    for i in 0..block.len {
+        line.clear();
+        for (column, count) in &columns {
+            for k in 0..*count {
+                if !line.is_empty() {
+                    line.extend_from_slice(sep.as_bytes());
+                }
+                push_value(column, i * count + k, FloatFormat::default(), &mut line);
+            }
+        }
+        line.push(b'\n');
+        writer.write_all(&line)?;
+    }
+
+    Ok(())
+}
+
+/// The type of column `index`: `U32`, else `I32`, if every row holds an
+/// integer there that fits, except for the coordinates; else `default`.
+fn infer_type(
+    name: &str,
+    rows: &[(usize, Vec<&str>)],
+    index: usize,
+    default: ValueType,
+) -> ValueType {
+    if matches!(name, "x" | "y" | "z") || rows.is_empty() {
+        return default;
+    }
+    let (mut unsigned, mut signed) = (true, true);
+    for (_, tokens) in rows {
+        let Some(Ok(value)) = tokens.get(index).map(|t| t.parse::<i64>()) else {
+            return default;
+        };
+        unsigned &= u32::try_from(value).is_ok();
+        signed &= i32::try_from(value).is_ok();
+    }
+    if unsigned {
+        ValueType::U32
+    } else if signed {
+        ValueType::I32
+    } else {
+        default
+    }
+}
+
+fn detect_delimiter(line: &str) -> char {
+    [',', ';', '\t']
+        .into_iter()
+        .find(|c| line.contains(*c))
+        .unwrap_or(' ')
+}
+
+fn split_line(line: &str, delimiter: char) -> Vec<&str> {
+    if delimiter == ' ' {
+        line.split_whitespace().collect()
+    } else {
+        line.split(delimiter).map(str::trim).collect()
+    }
+}
+
+fn normalize_name(token: &str) -> String {
+    token
+        .trim_matches(|c| c == '"' || c == '\'')
+        .trim()
+        .to_lowercase()
+}
+
+fn default_name(index: usize) -> String {
+    match index {
+        0 => "x".to_string(),
+        1 => "y".to_string(),
+        2 => "z".to_string(),
+        n => format!("field_{}", n),
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Loading every PCD file of a directory.
+//!
+//! Patterns are a directory path, which matches the `*.pcd` files in it, or a
+//! path whose last component contains `*` and `?` wildcards, such as
+//! `scans/frame_*.pcd`. Subdirectories are not searched. Files are returned in
+//! lexicographic path order, so zero-padded frame numbers come out in sequence.
+//!
+//! With the `rayon` feature files are decoded in parallel.
+
+use super::reader::read_pcd_file;
+use crate::error::Result;
+use crate::storage::PointBlock;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Files matching `pattern`, in path order.
+pub fn find_files(pattern: &str) -> Result<Vec<PathBuf>> {
+    let path = Path::new(pattern);
+    let (dir, name_pattern) = if path.is_dir() {
+        (path, "*.pcd")
+    } else {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let dir = match path.parent() {
+            Some(p) if !p.as_os_str().is_empty() => p,
+            _ => Path::new("."),
+        };
+        (dir, name)
+    };
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        if wildcard_match(name_pattern.as_bytes(), name.as_bytes()) && entry.path().is_file() {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Load every file matching `pattern`, in path order.
+///
+/// All clouds are held in memory at once; use [`load_dir_with`] to process
+/// large datasets frame by frame.
+pub fn load_dir(pattern: &str) -> Result<Vec<(PathBuf, PointBlock)>> {
+    let files = find_files(pattern)?;
+    let blocks = load_files(&files)?;
+    Ok(files.into_iter().zip(blocks).collect())
+}
+
+/// Load the files matching `pattern` and hand them to `f` in path order.
+///
+/// Files are decoded in batches of one per rayon thread, so at most one batch
+/// is in memory. Stops at the first error from loading or from `f`.
+pub fn load_dir_with<F>(pattern: &str, mut f: F) -> Result<()>
+where
+    F: FnMut(PathBuf, PointBlock) -> Result<()>,
+{
+    let files = find_files(pattern)?;
+    for batch in files.chunks(batch_size()) {
+        for (path, block) in batch.iter().zip(load_files(batch)?) {
+            f(path.clone(), block)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "rayon")]
+fn load_files(files: &[PathBuf]) -> Result<Vec<PointBlock>> {
+    files.par_iter().map(read_pcd_file).collect()
+}
+
+#[cfg(not(feature = "rayon"))]
+fn load_files(files: &[PathBuf]) -> Result<Vec<PointBlock>> {
+    files.iter().map(read_pcd_file).collect()
+}
+
+#[cfg(feature = "rayon")]
+fn batch_size() -> usize {
+    rayon::current_num_threads()
+}
+
+#[cfg(not(feature = "rayon"))]
+fn batch_size() -> usize {
+    1
+}
+
+/// Glob-style match of `*` (any run) and `?` (any byte).
+fn wildcard_match(pattern: &[u8], name: &[u8]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // Position after the last `*` and the name position it is matched up to.
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p + 1, n));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((sp, sn)) => {
+                    p = sp;
+                    n = sn + 1;
+                    star = Some((sp, sn + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
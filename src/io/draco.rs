@@ -0,0 +1,276 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Draco point cloud encoding and decoding (feature `draco`).
+//!
+//! Draco attributes are typed rather than named, so only the columns with a
+//! Draco counterpart are carried:
+//!
+//! | PointBlock columns                      | Draco attribute        |
+//! |-----------------------------------------|------------------------|
+//! | `x y z` (F32, required)                 | POSITION, 3 x f32      |
+//! | `normal_x normal_y normal_z` (F32)      | NORMAL, 3 x f32        |
+//! | `rgb` (U32, packed `0x00RRGGBB`)        | COLOR, 3 x u8          |
+//! | `intensity` (F32)                       | CUSTOM, 1 x f32        |
+//!
+//! Other columns are dropped. The kd-tree point cloud method reorders points,
+//! so the decoded order differs from the encoded one.
+
+use crate::error::{PcdError, Result};
+use crate::header::{Schema, ValueType};
+use crate::storage::PointBlock;
+use crate::storage::rgb::pack_color;
+use draco_oxide::core::attribute::{
+    Attribute, AttributeDomain, AttributeId, AttributeType, ComponentDataType,
+};
+use draco_oxide::core::types::{ConfigType, NdVector, PointIdx, Vector};
+use draco_oxide::encode::{PointCloudConfig, Quantization, encode_point_cloud};
+use draco_oxide::{PointCloud, decode};
+
+/// Quantization and compression settings for Draco encoding.
+#[derive(Debug, Clone, Copy)]
+pub struct DracoOptions {
+    /// Quantization bits for positions. Default is 14.
+    pub position_bits: u8,
+    /// Quantization bits for normals. Default is 10.
+    pub normal_bits: u8,
+    /// Quantization bits for intensity. Default is 8.
+    pub intensity_bits: u8,
+    /// kd-tree compression level (0..=6). Default is 6.
+    pub compression_level: u8,
+}
+
+impl Default for DracoOptions {
+    fn default() -> Self {
+        Self {
+            position_bits: 14,
+            normal_bits: 10,
+            intensity_bits: 8,
+            compression_level: 6,
+        }
+    }
+}
+
+/// Encode a `PointBlock` into a Draco buffer.
+pub fn encode_draco(block: &PointBlock, options: &DracoOptions) -> Result<Vec<u8>> {
+    let (x, y, z) = block.xyz().ok_or_else(|| {
+        PcdError::InvalidDataFormat("Draco output requires F32 x, y, z columns".to_string())
+    })?;
+
+    let mut attributes = Vec::new();
+    let positions: Vec<NdVector<3, f32>> = (0..block.len)
+        .map(|i| NdVector::from([x[i], y[i], z[i]]))
+        .collect();
+    attributes.push(vec3_attribute(0, positions, AttributeType::Position));
+
+    let normals =
+        ["normal_x", "normal_y", "normal_z"].map(|n| block.get_column(n).and_then(|c| c.as_f32()));
+    if let [Some(nx), Some(ny), Some(nz)] = normals {
+        let values: Vec<NdVector<3, f32>> = (0..block.len)
+            .map(|i| NdVector::from([nx[i], ny[i], nz[i]]))
+            .collect();
+        attributes.push(vec3_attribute(
+            attributes.len(),
+            values,
+            AttributeType::Normal,
+        ));
+    }
+
+    if let Some(rgb) = block.get_column("rgb").and_then(|c| c.as_u32()) {
+        let values: Vec<NdVector<3, u8>> = rgb
+            .iter()
+            .map(|&c| NdVector::from([(c >> 16) as u8, (c >> 8) as u8, c as u8]))
+            .collect();
+        attributes.push(Attribute::from_without_removing_duplicates::<_, 3>(
+            AttributeId::new(attributes.len()),
+            values,
+            AttributeType::Color,
+            AttributeDomain::Position,
+            Vec::new(),
+        ));
+    }
+
+    if let Some(intensity) = block.get_column("intensity").and_then(|c| c.as_f32()) {
+        let values: Vec<NdVector<1, f32>> =
+            intensity.iter().map(|&v| NdVector::from([v])).collect();
+        attributes.push(Attribute::from_without_removing_duplicates::<_, 1>(
+            AttributeId::new(attributes.len()),
+            values,
+            AttributeType::Custom,
+            AttributeDomain::Position,
+            Vec::new(),
+        ));
+    }
+
+    let cloud = PointCloud::new(attributes).map_err(|e| PcdError::Other(e.to_string()))?;
+    let config = <PointCloudConfig as ConfigType>::default()
+        .with_compression_level(options.compression_level)
+        .with_quantization(Quantization::Bits(options.position_bits))
+        .with_attribute_quantization(
+            AttributeType::Normal,
+            Quantization::Bits(options.normal_bits),
+        )
+        .with_attribute_quantization(
+            AttributeType::Custom,
+            Quantization::Bits(options.intensity_bits),
+        );
+
+    let mut buffer = Vec::new();
+    encode_point_cloud(cloud, &mut buffer, config)
+        .map_err(|e| PcdError::Other(format!("Draco encoding failed: {}", e)))?;
+    Ok(buffer)
+}
+
+/// Decode a Draco point cloud buffer into a `PointBlock`.
+///
+/// Attributes map back onto the columns in the table above; a COLOR with
+/// four components becomes a packed `rgba` column. A further attribute of a
+/// type already seen gets its columns suffixed with its attribute index,
+/// e.g. `rgb_3`. Attributes of those types with other component types or
+/// counts are refused with [`PcdError::UnsupportedType`].
+pub fn decode_draco(data: &[u8]) -> Result<PointBlock> {
+    let cloud = decode::decode_point_cloud(data)
+        .map_err(|e| PcdError::Decompression(format!("Draco decoding failed: {}", e)))?;
+    let points = cloud.num_points();
+
+    let mut schema = Schema::new();
+    let mut columns = Vec::new();
+    for (index, att) in cloud.attributes().iter().enumerate() {
+        let Some((kind, names)) = column_names(att)? else {
+            continue;
+        };
+        let taken = schema.get(names[0]).is_some();
+        let names: Vec<String> = names
+            .into_iter()
+            .map(|name| {
+                if taken {
+                    format!("{}_{}", name, index)
+                } else {
+                    name.to_string()
+                }
+            })
+            .collect();
+        let type_ = match kind {
+            Kind::Color(_) => ValueType::U32,
+            _ => ValueType::F32,
+        };
+        for name in &names {
+            schema = schema.with_field(name, type_);
+        }
+        columns.push((att, kind, names));
+    }
+    let mut block = PointBlock::new(schema, points);
+
+    for (att, kind, names) in columns {
+        match kind {
+            Kind::Vec3 => fill_vec3(&mut block, att, &names),
+            Kind::Color(3) => {
+                let rgb = block
+                    .get_column_mut(&names[0])
+                    .unwrap()
+                    .as_u32_mut()
+                    .unwrap();
+                for (i, dst) in rgb.iter_mut().enumerate() {
+                    let c: NdVector<3, u8> = att.get(PointIdx::from(i));
+                    *dst = pack_color(*c.get(0), *c.get(1), *c.get(2), 0);
+                }
+            }
+            Kind::Color(_) => {
+                let rgba = block
+                    .get_column_mut(&names[0])
+                    .unwrap()
+                    .as_u32_mut()
+                    .unwrap();
+                for (i, dst) in rgba.iter_mut().enumerate() {
+                    let c: NdVector<4, u8> = att.get(PointIdx::from(i));
+                    *dst = pack_color(*c.get(0), *c.get(1), *c.get(2), *c.get(3));
+                }
+            }
+            Kind::Scalar => {
+                let col = block.get_column_mut(&names[0]).unwrap();
+                for (i, dst) in col.as_f32_mut().unwrap().iter_mut().enumerate() {
+                    let v: NdVector<1, f32> = att.get(PointIdx::from(i));
+                    *dst = *v.get(0);
+                }
+            }
+        }
+    }
+
+    Ok(block)
+}
+
+/// How an attribute's values are read.
+#[derive(Clone, Copy)]
+enum Kind {
+    /// Three f32 components, one column each.
+    Vec3,
+    /// Three or four u8 components, packed into one column.
+    Color(usize),
+    /// One f32 component.
+    Scalar,
+}
+
+/// The kind of `att` and the columns it becomes, or `None` for attributes
+/// without a column counterpart.
+fn column_names(att: &Attribute) -> Result<Option<(Kind, Vec<&'static str>)>> {
+    let components = att.get_num_components();
+    let component_type = att.get_component_type();
+    let (kind, names, expected) = match att.get_attribute_type() {
+        AttributeType::Position => (Kind::Vec3, vec!["x", "y", "z"], "3 x f32"),
+        AttributeType::Normal => (
+            Kind::Vec3,
+            vec!["normal_x", "normal_y", "normal_z"],
+            "3 x f32",
+        ),
+        AttributeType::Color if components == 4 => (Kind::Color(4), vec!["rgba"], "3 or 4 x u8"),
+        AttributeType::Color => (Kind::Color(3), vec!["rgb"], "3 or 4 x u8"),
+        AttributeType::Custom if components == 1 => (Kind::Scalar, vec!["intensity"], "1 x f32"),
+        _ => return Ok(None),
+    };
+    let supported = match kind {
+        Kind::Vec3 => components == 3 && component_type == ComponentDataType::F32,
+        Kind::Color(_) => components >= 3 && component_type == ComponentDataType::U8,
+        Kind::Scalar => component_type == ComponentDataType::F32,
+    };
+    if !supported {
+        return Err(PcdError::UnsupportedType(format!(
+            "Draco {:?} attribute of {} x {:?}, expected {}",
+            att.get_attribute_type(),
+            components,
+            component_type,
+            expected
+        )));
+    }
+    Ok(Some((kind, names)))
+}
+
+fn vec3_attribute(id: usize, values: Vec<NdVector<3, f32>>, ty: AttributeType) -> Attribute {
+    Attribute::from_without_removing_duplicates::<_, 3>(
+        AttributeId::new(id),
+        values,
+        ty,
+        AttributeDomain::Position,
+        Vec::new(),
+    )
+}
+
+fn fill_vec3(block: &mut PointBlock, att: &Attribute, names: &[String]) {
+    for (k, name) in names.iter().enumerate() {
+        let col = block.get_column_mut(name).unwrap().as_f32_mut().unwrap();
+        for (i, dst) in col.iter_mut().enumerate() {
+            let v: NdVector<3, f32> = att.get(PointIdx::from(i));
+            *dst = *v.get(k);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! KITTI velodyne `.bin` format.
+//!
+//! The file has no header: it is a flat sequence of little-endian `f32`
+//! quadruples `(x, y, z, intensity)`, one per point.
+
+use crate::error::{PcdError, Result};
+use crate::header::ValueType;
+use crate::storage::PointBlock;
+use byteorder::{ByteOrder, LittleEndian, WriteBytesExt};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// Bytes per KITTI point: four `f32` values.
+const KITTI_POINT_STEP: usize = 16;
+
+/// Read a KITTI `.bin` file into an XYZI `PointBlock` (all columns F32).
+pub fn read_kitti_bin<P: AsRef<Path>>(path: P) -> Result<PointBlock> {
+    let file = File::open(path)?;
+    read_kitti(BufReader::new(file))
+}
+
+/// Read KITTI point data from any reader.
+pub fn read_kitti<R: Read>(mut reader: R) -> Result<PointBlock> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+
+    if data.len() % KITTI_POINT_STEP != 0 {
+        return Err(PcdError::InvalidDataFormat(format!(
+            "KITTI data length {} is not a multiple of {}",
+            data.len(),
+            KITTI_POINT_STEP
+        )));
+    }
+
+    let points = data.len() / KITTI_POINT_STEP;
+    let schema = vec![
+        ("x".to_string(), ValueType::F32),
+        ("y".to_string(), ValueType::F32),
+        ("z".to_string(), ValueType::F32),
+        ("intensity".to_string(), ValueType::F32),
+    ];
+    let mut block = PointBlock::new(&schema, points);
+
+    for (field_idx, col) in block.columns_mut().iter_mut().enumerate() {
+        let vec = col.as_f32_mut().unwrap();
+        for (i, chunk) in data.chunks_exact(KITTI_POINT_STEP).enumerate() {
+            vec[i] = LittleEndian::read_f32(&chunk[field_idx * 4..field_idx * 4 + 4]);
+        }
+    }
+
+    Ok(block)
+}
+
+/// Write a `PointBlock` as a KITTI `.bin` file.
+///
+/// `x`, `y` and `z` must be F32 columns. `intensity` of any numeric type is
+/// converted to `f32`, and written as `0.0` when the block has none.
+pub fn write_kitti_bin<P: AsRef<Path>>(path: P, block: &PointBlock) -> Result<()> {
+    let file = File::create(path)?;
+    let mut writer = BufWriter::new(file);
+    write_kitti(&mut writer, block)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write KITTI point data to any writer.
+pub fn write_kitti<W: Write>(mut writer: W, block: &PointBlock) -> Result<()> {
+    let (x, y, z) = block.xyz().ok_or_else(|| {
+        PcdError::InvalidDataFormat("KITTI output requires F32 x, y, z columns".to_string())
+    })?;
+    let intensity = block.get_column("intensity");
+    if intensity.is_some() && block.values_per_point("intensity") != 1 {
+        return Err(PcdError::InvalidDataFormat(
+            "KITTI output requires one intensity value per point".to_string(),
+        ));
+    }
+
+    for i in 0..block.len {
+        writer.write_f32::<LittleEndian>(x[i])?;
+        writer.write_f32::<LittleEndian>(y[i])?;
+        writer.write_f32::<LittleEndian>(z[i])?;
+        writer.write_f32::<LittleEndian>(intensity.map_or(0.0, |c| c.value_f64(i) as f32))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Zero-copy view of a memory-mapped binary PCD (feature `memmap2`).
+//!
+//! [`LazyPcdView`] reads fields straight out of the mapped records instead
+//! of decoding them into a [`PointBlock`](crate::storage::PointBlock), which
+//! saves the copy for single passes such as bounds, counts or filters.
+
+use super::validate::check_header;
+use crate::error::{Operation, PcdError, Result};
+use crate::header::{DataFormat, PcdHeader, ValueType, parse_header_bytes};
+use crate::layout::PcdLayout;
+use crate::storage::{StridedColumnView, StridedValue};
+use memmap2::Mmap;
+use std::fs::File;
+use std::path::Path;
+
+pub struct LazyPcdView {
+    map: Mmap,
+    header: PcdHeader,
+    layout: PcdLayout,
+    data_start: usize,
+}
+
+impl LazyPcdView {
+    /// Map the file at `path`. Only `binary` data can be read in place;
+    /// other formats fail with [`PcdError::UnsupportedDataFormat`], and a data
+    /// section too short for `POINTS` fails as in
+    /// [`PcdReader`](super::PcdReader).
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        // Like `PcdReader::from_path_mmap`, this assumes the file is not
+        // truncated while mapped.
+        let map = unsafe { Mmap::map(&file)? };
+        let (header, data_start) = parse_header_bytes(&map)?;
+        if header.data != DataFormat::Binary {
+            return Err(PcdError::UnsupportedDataFormat(format!(
+                "Lazy view of {:?} data",
+                header.data
+            )));
+        }
+        let layout = PcdLayout::from_header(&header)?;
+        check_header(&header, &layout, Some((map.len() - data_start) as u64))?;
+        Ok(Self {
+            map,
+            header,
+            layout,
+            data_start,
+        })
+    }
+
+    pub fn header(&self) -> &PcdHeader {
+        &self.header
+    }
+
+    pub fn layout(&self) -> &PcdLayout {
+        &self.layout
+    }
+
+    pub fn len(&self) -> usize {
+        self.header.points
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The raw records, `len() * layout().total_size` bytes.
+    pub fn records(&self) -> &[u8] {
+        let bytes = self.len() * self.layout.total_size;
+        &self.map[self.data_start..self.data_start + bytes]
+    }
+
+    /// The raw record of point `i`.
+    pub fn record(&self, i: usize) -> Option<&[u8]> {
+        let step = self.layout.total_size;
+        self.records().get(i * step..(i + 1) * step)
+    }
+
+    /// The first element of field `name` for every point, as `f32`.
+    pub fn field(&self, name: &str) -> Option<StridedColumnView<'_, f32>> {
+        self.element(name, 0)
+    }
+
+    /// Element `index` of field `name` for every point, as `f32`; `None` if
+    /// there is no such field or `index` is not below its `COUNT`.
+    pub fn element(&self, name: &str, index: usize) -> Option<StridedColumnView<'_, f32>> {
+        let field = self.layout.get_field(name)?;
+        if index >= field.count {
+            return None;
+        }
+        let read: fn(&[u8]) -> f32 = match field.type_ {
+            ValueType::U8 => |b| b[0] as f32,
+            ValueType::I8 => |b| b[0] as i8 as f32,
+            ValueType::U16 => |b| u16::from_le_slice(b) as f32,
+            ValueType::I16 => |b| i16::from_le_slice(b) as f32,
+            ValueType::U32 => |b| u32::from_le_slice(b) as f32,
+            ValueType::I32 => |b| i32::from_le_slice(b) as f32,
+            ValueType::U64 => |b| u64::from_le_slice(b) as f32,
+            ValueType::I64 => |b| i64::from_le_slice(b) as f32,
+            ValueType::F32 => f32::from_le_slice,
+            ValueType::F64 => |b| f64::from_le_slice(b) as f32,
+        };
+        let offset = field.offset + index * field.element_size;
+        // In bounds: `open` checked the records against the layout.
+        StridedColumnView::with_reader(
+            self.records(),
+            offset,
+            self.layout.total_size,
+            self.len(),
+            field.element_size,
+            read,
+        )
+        .ok()
+    }
+
+    /// The first element of field `name` for every point, without
+    /// conversion; `T` must match the field's type.
+    pub fn column<T: StridedValue>(&self, name: &str) -> Result<StridedColumnView<'_, T>> {
+        let field = self
+            .layout
+            .get_field(name)
+            .ok_or_else(|| PcdError::MissingField {
+                field: name.to_string(),
+                op: Operation::Read,
+            })?;
+        if field.type_ != T::TYPE {
+            return Err(PcdError::FieldType {
+                field: name.to_string(),
+                expected: T::TYPE,
+                got: field.type_,
+                op: Operation::Read,
+            });
+        }
+        StridedColumnView::new(
+            self.records(),
+            field.offset,
+            self.layout.total_size,
+            self.len(),
+        )
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Streaming LZF compression (liblzf format). Back references reach at most
+//! 8 KiB, so input can arrive in pieces while only that much history is kept.
+
+/// Farthest back a reference can point.
+const WINDOW: usize = 1 << 13;
+/// Longest back reference.
+const MAX_MATCH: usize = 264;
+/// Longest literal run.
+const MAX_LITERAL: usize = 32;
+const HASH_BITS: u32 = 16;
+
+pub(crate) struct LzfEncoder {
+    // Recent input: at least the last `WINDOW` bytes, then the new data.
+    history: Vec<u8>,
+    // Position in the whole input of `history[0]`.
+    base: usize,
+    // Last position in the whole input of each 3-byte hash, plus 1. Sizes
+    // are u32 in the file format, so positions fit.
+    table: Vec<u32>,
+    out: Vec<u8>,
+}
+
+impl LzfEncoder {
+    pub(crate) fn new() -> Self {
+        Self {
+            history: Vec::new(),
+            base: 0,
+            table: vec![0; 1 << HASH_BITS],
+            out: Vec::new(),
+        }
+    }
+
+    /// Compress the next piece of input. Matches and literal runs do not
+    /// span pieces.
+    pub(crate) fn write(&mut self, data: &[u8]) {
+        let mut p = self.history.len();
+        self.history.extend_from_slice(data);
+        let end = self.history.len();
+        let mut literal = p;
+        while p + 3 <= end {
+            let (len, distance) = self.find_match(p, end);
+            if len == 0 {
+                p += 1;
+                continue;
+            }
+            self.emit_literal(literal, p);
+            self.emit_reference(len, distance);
+            for q in p + 1..(p + len).min(end - 2) {
+                self.insert(q);
+            }
+            p += len;
+            literal = p;
+        }
+        self.emit_literal(literal, end);
+
+        let excess = self.history.len().saturating_sub(WINDOW);
+        if excess > WINDOW {
+            self.history.drain(..excess);
+            self.base += excess;
+        }
+    }
+
+    /// Finish the stream and return the compressed bytes.
+    pub(crate) fn finish(self) -> Vec<u8> {
+        self.out
+    }
+
+    /// Longest earlier match for position `p` of `history`, as length and
+    /// distance; the length is 0 if there is none.
+    fn find_match(&mut self, p: usize, end: usize) -> (usize, usize) {
+        let candidate = self.insert(p);
+        let Some(c) = candidate.checked_sub(self.base + 1) else {
+            return (0, 0);
+        };
+        let distance = p - c;
+        let h = &self.history;
+        if distance > WINDOW || h[c..c + 3] != h[p..p + 3] {
+            return (0, 0);
+        }
+        let max = MAX_MATCH.min(end - p);
+        let mut len = 3;
+        while len < max && h[c + len] == h[p + len] {
+            len += 1;
+        }
+        (len, distance)
+    }
+
+    /// Record position `p` of `history` under its hash, returning the entry
+    /// it replaces.
+    fn insert(&mut self, p: usize) -> usize {
+        let h = &self.history;
+        let key = u32::from_le_bytes([h[p], h[p + 1], h[p + 2], 0]);
+        let slot = (key.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize;
+        core::mem::replace(&mut self.table[slot], (self.base + p + 1) as u32) as usize
+    }
+
+    fn emit_reference(&mut self, len: usize, distance: usize) {
+        let len = len - 2;
+        let offset = distance - 1;
+        if len < 7 {
+            self.out.push(((len << 5) | (offset >> 8)) as u8);
+        } else {
+            self.out.push(((7 << 5) | (offset >> 8)) as u8);
+            self.out.push((len - 7) as u8);
+        }
+        self.out.push(offset as u8);
+    }
+
+    /// Emit `history[from..to]` as literal runs.
+    fn emit_literal(&mut self, from: usize, to: usize) {
+        for run in self.history[from..to].chunks(MAX_LITERAL) {
+            self.out.push((run.len() - 1) as u8);
+            self.out.extend_from_slice(run);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Concatenation of PCD files with the same fields.
+
+use super::{PcdReader, PcdStreamWriter, read_pcd_header, write_pcd_file};
+use crate::error::{PcdError, Result};
+use crate::geometry::Pose;
+use crate::header::{DataFormat, PcdHeader};
+use crate::storage::{PointBlock, PointCloud};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+const IDENTITY: [f64; 7] = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0];
+
+/// Options of [`merge_pcd_files`] and [`read_merged_pcd_files`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MergeOptions {
+    /// `DATA` format of the output. Default is binary.
+    pub format: DataFormat,
+    /// Move `x`, `y`, `z` of each input from the frame of its `VIEWPOINT`
+    /// into the common frame and give the output the identity viewpoint.
+    /// Otherwise the first input's viewpoint is kept. Default is false.
+    pub apply_viewpoint: bool,
+    /// Points read and written at a time when streaming. Default is 65536.
+    pub chunk_points: usize,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        Self {
+            format: DataFormat::Binary,
+            apply_viewpoint: false,
+            chunk_points: 1 << 16,
+        }
+    }
+}
+
+/// Concatenate the PCD files `inputs`, whose fields (names, types, sizes
+/// and counts) must match in any order, into the PCD `output`, and return
+/// its header. The output is unorganized and has the first input's field
+/// order. Binary and ASCII output is streamed chunk by chunk; compressed
+/// or gzip output needs all points in memory.
+///
+/// Every header is checked before the output is created, so a mismatch
+/// leaves nothing behind.
+pub fn merge_pcd_files<P: AsRef<Path>, Q: AsRef<Path>>(
+    inputs: &[P],
+    output: Q,
+    options: &MergeOptions,
+) -> Result<PcdHeader> {
+    let output = output.as_ref();
+    let (header, headers) = merged_header(inputs, options)?;
+    let streamable = output.extension().is_none_or(|e| e != "gz")
+        && matches!(options.format, DataFormat::Ascii | DataFormat::Binary);
+    if !streamable {
+        let cloud = read_merged(inputs, header, &headers, options)?;
+        write_pcd_file(output, &cloud.header, &cloud.data)?;
+        return Ok(cloud.header);
+    }
+
+    let file = BufWriter::new(File::create(output)?);
+    let mut writer = PcdStreamWriter::new(file, &header)?;
+    for (input, input_header) in inputs.iter().zip(&headers) {
+        let mut reader = PcdReader::open(input)?;
+        while let Some(mut block) = reader.read_chunk(options.chunk_points.max(1))? {
+            if options.apply_viewpoint {
+                apply_viewpoint(&mut block, &input_header.viewpoint)?;
+            }
+            writer.write_chunk(&block)?;
+        }
+    }
+    writer.finish()?;
+    Ok(header)
+}
+
+/// The concatenation of the PCD files `inputs` in memory, with the header
+/// [`merge_pcd_files`] would write, e.g. to save it in another format.
+pub fn read_merged_pcd_files<P: AsRef<Path>>(
+    inputs: &[P],
+    options: &MergeOptions,
+) -> Result<PointCloud> {
+    let (header, headers) = merged_header(inputs, options)?;
+    read_merged(inputs, header, &headers, options)
+}
+
+/// The output header and the header of each input, checked to match.
+fn merged_header<P: AsRef<Path>>(
+    inputs: &[P],
+    options: &MergeOptions,
+) -> Result<(PcdHeader, Vec<PcdHeader>)> {
+    let mut headers: Vec<PcdHeader> = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let header = read_pcd_header(input)?;
+        if let Some(first) = headers.first() {
+            check_compatible(first, &header, input.as_ref())?;
+        }
+        headers.push(header);
+    }
+    let mut header = headers
+        .first()
+        .cloned()
+        .ok_or_else(|| PcdError::InvalidDataFormat("No files to merge".to_string()))?;
+    let total: usize = headers.iter().map(|h| h.points).sum();
+    header.width = total as u32;
+    header.height = 1;
+    header.points = total;
+    header.data = options.format;
+    if options.apply_viewpoint {
+        header.viewpoint = IDENTITY;
+    }
+    Ok((header, headers))
+}
+
+fn read_merged<P: AsRef<Path>>(
+    inputs: &[P],
+    header: PcdHeader,
+    headers: &[PcdHeader],
+    options: &MergeOptions,
+) -> Result<PointCloud> {
+    let mut merged: Option<PointBlock> = None;
+    for (input, input_header) in inputs.iter().zip(headers) {
+        let mut block = PcdReader::open(input)?.read_all()?;
+        if options.apply_viewpoint {
+            apply_viewpoint(&mut block, &input_header.viewpoint)?;
+        }
+        match &mut merged {
+            Some(merged) => merged.append(&block)?,
+            None => merged = Some(block),
+        }
+    }
+    Ok(PointCloud::new(header, merged.unwrap_or_default()))
+}
+
+/// Fail unless `header` has the same fields as `first`, in any order.
+fn check_compatible(first: &PcdHeader, header: &PcdHeader, path: &Path) -> Result<()> {
+    let fields = |h: &PcdHeader| {
+        let mut fields: Vec<_> = (0..h.fields.len())
+            .map(|i| (h.fields[i].clone(), h.types[i], h.sizes[i], h.counts[i]))
+            .collect();
+        fields.sort();
+        fields
+    };
+    let (expected, got) = (fields(first), fields(header));
+    if expected != got {
+        let describe = |f: &[(String, char, usize, usize)]| {
+            f.iter()
+                .map(|(name, t, size, count)| format!("{}:{}{}x{}", name, t, size, count))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        return Err(PcdError::InvalidDataFormat(format!(
+            "{} has fields [{}], expected [{}]",
+            path.display(),
+            describe(&got),
+            describe(&expected)
+        )));
+    }
+    Ok(())
+}
+
+/// Move x/y/z (and normals) from the sensor frame given by `viewpoint` into
+/// the common frame.
+fn apply_viewpoint(block: &mut PointBlock, viewpoint: &[f64; 7]) -> Result<()> {
+    if *viewpoint == IDENTITY {
+        return Ok(());
+    }
+    Pose::from_viewpoint(viewpoint).transform_block(block)
+}
@@ -12,9 +12,46 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod archive;
+#[cfg(feature = "async")]
+pub mod async_reader;
+#[cfg(feature = "async")]
+pub mod async_writer;
+pub mod csv;
+pub mod dataset;
+#[cfg(feature = "draco")]
+pub mod draco;
+pub mod kitti;
+#[cfg(feature = "memmap2")]
+pub mod lazy;
+mod lzf_stream;
+pub mod merge;
+pub mod pnts;
+#[cfg(unix)]
+pub mod positioned;
+pub mod pts;
+pub mod range;
 mod reader;
+#[cfg(feature = "object_store")]
+pub mod remote;
+pub mod sequence;
+pub mod validate;
 mod writer;
-pub use reader::{PcdReader, read_pcd_file};
-pub use writer::PcdWriter;
+pub use archive::ArchiveReader;
+#[cfg(feature = "async")]
+pub use async_reader::AsyncPcdReader;
+#[cfg(feature = "async")]
+pub use async_writer::AsyncPcdWriter;
+pub use crate::decoder::ascii::ShortLines;
+pub use csv::{CsvOptions, read_csv_file, write_csv_file};
+pub use kitti::{read_kitti_bin, write_kitti_bin};
+pub use merge::{MergeOptions, merge_pcd_files, read_merged_pcd_files};
+#[cfg(feature = "memmap2")]
+pub use lazy::LazyPcdView;
+pub use pts::{PtxOptions, PtxScan, read_pts_file, read_ptx_file, write_pts_file, write_ptx_file};
+pub use reader::{PcdReader, is_gzip, read_pcd_bytes, read_pcd_file, read_pcd_header};
+pub use sequence::PcdSequence;
+pub use validate::{Issue, Validation, repair_pcd, validate_pcd};
+pub use writer::{FloatFormat, PcdStreamWriter, PcdWriter, write_pcd_file};
 
 // Future: mmap support
@@ -0,0 +1,176 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cesium 3D Tiles point cloud export (`.pnts` tiles and `tileset.json`).
+//!
+//! Positions are written relative to `RTC_CENTER` (the bounding box center)
+//! so large georeferenced coordinates keep their precision. A packed U32
+//! `rgb` column becomes the `RGB` feature table property and an F32
+//! `intensity` column a per-point `INTENSITY` batch table property.
+
+use crate::error::{PcdError, Result};
+use crate::storage::PointBlock;
+use byteorder::{LittleEndian, WriteBytesExt};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+const PNTS_HEADER_SIZE: usize = 28;
+
+/// Axis-aligned bounds of the `x y z` columns as `(min, max)`.
+pub fn bounds(block: &PointBlock) -> Result<([f64; 3], [f64; 3])> {
+    let (x, y, z) = block.xyz().ok_or_else(|| {
+        PcdError::InvalidDataFormat("3D Tiles output requires F32 x, y, z columns".to_string())
+    })?;
+    if block.len == 0 {
+        return Ok(([0.0; 3], [0.0; 3]));
+    }
+    let mut min = [f64::INFINITY; 3];
+    let mut max = [f64::NEG_INFINITY; 3];
+    for i in 0..block.len {
+        for (k, v) in [x[i], y[i], z[i]].into_iter().enumerate() {
+            min[k] = min[k].min(v as f64);
+            max[k] = max[k].max(v as f64);
+        }
+    }
+    Ok((min, max))
+}
+
+/// Write a `PointBlock` as a `.pnts` tile file.
+pub fn write_pnts_file<P: AsRef<Path>>(path: P, block: &PointBlock) -> Result<()> {
+    let file = File::create(path)?;
+    let mut writer = BufWriter::new(file);
+    write_pnts(&mut writer, block)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write a `.pnts` tile to any writer.
+pub fn write_pnts<W: Write>(mut writer: W, block: &PointBlock) -> Result<()> {
+    let (x, y, z) = block.xyz().ok_or_else(|| {
+        PcdError::InvalidDataFormat("3D Tiles output requires F32 x, y, z columns".to_string())
+    })?;
+    let rgb = block.get_column("rgb").and_then(|c| c.as_u32());
+    let intensity = block.get_column("intensity").and_then(|c| c.as_f32());
+
+    let (min, max) = bounds(block)?;
+    let center = [0, 1, 2].map(|k| (min[k] + max[k]) / 2.0);
+
+    // Feature table binary: POSITION then RGB.
+    let mut feature_bin = Vec::with_capacity(block.len * 15);
+    for i in 0..block.len {
+        feature_bin.write_f32::<LittleEndian>((x[i] as f64 - center[0]) as f32)?;
+        feature_bin.write_f32::<LittleEndian>((y[i] as f64 - center[1]) as f32)?;
+        feature_bin.write_f32::<LittleEndian>((z[i] as f64 - center[2]) as f32)?;
+    }
+    let mut feature_json = format!(
+        "{{\"POINTS_LENGTH\":{},\"RTC_CENTER\":[{},{},{}],\"POSITION\":{{\"byteOffset\":0}}",
+        block.len, center[0], center[1], center[2]
+    );
+    if let Some(rgb) = rgb {
+        feature_json.push_str(&format!(
+            ",\"RGB\":{{\"byteOffset\":{}}}",
+            feature_bin.len()
+        ));
+        for &c in rgb {
+            feature_bin.extend_from_slice(&[(c >> 16) as u8, (c >> 8) as u8, c as u8]);
+        }
+    }
+    feature_json.push('}');
+
+    let mut batch_json = String::new();
+    let mut batch_bin = Vec::new();
+    if let Some(intensity) = intensity {
+        batch_json =
+            "{\"INTENSITY\":{\"byteOffset\":0,\"componentType\":\"FLOAT\",\"type\":\"SCALAR\"}}"
+                .to_string();
+        for &v in intensity {
+            batch_bin.write_f32::<LittleEndian>(v)?;
+        }
+    }
+
+    // Every section must end on an 8-byte boundary relative to the file start.
+    let mut feature_json = feature_json.into_bytes();
+    pad_to(&mut feature_json, PNTS_HEADER_SIZE, b' ');
+    pad_to(&mut feature_bin, 0, 0);
+    let mut batch_json = batch_json.into_bytes();
+    if !batch_json.is_empty() {
+        pad_to(&mut batch_json, 0, b' ');
+        pad_to(&mut batch_bin, 0, 0);
+    }
+
+    let total = PNTS_HEADER_SIZE
+        + feature_json.len()
+        + feature_bin.len()
+        + batch_json.len()
+        + batch_bin.len();
+    let total = u32::try_from(total)
+        .map_err(|_| PcdError::Other(format!("Tile too large: {} bytes", total)))?;
+
+    writer.write_all(b"pnts")?;
+    writer.write_u32::<LittleEndian>(1)?;
+    writer.write_u32::<LittleEndian>(total)?;
+    writer.write_u32::<LittleEndian>(feature_json.len() as u32)?;
+    writer.write_u32::<LittleEndian>(feature_bin.len() as u32)?;
+    writer.write_u32::<LittleEndian>(batch_json.len() as u32)?;
+    writer.write_u32::<LittleEndian>(batch_bin.len() as u32)?;
+    writer.write_all(&feature_json)?;
+    writer.write_all(&feature_bin)?;
+    writer.write_all(&batch_json)?;
+    writer.write_all(&batch_bin)?;
+    Ok(())
+}
+
+/// Build a single-tile `tileset.json` referencing `content_uri`.
+///
+/// The bounding volume is the box around `block`, and `geometric_error` is
+/// used for both the tileset and the root tile.
+pub fn tileset_json(block: &PointBlock, content_uri: &str, geometric_error: f64) -> Result<String> {
+    let (min, max) = bounds(block)?;
+    let c = [0, 1, 2].map(|k| (min[k] + max[k]) / 2.0);
+    let h = [0, 1, 2].map(|k| (max[k] - min[k]) / 2.0);
+    Ok(format!(
+        "{{\n  \"asset\": {{ \"version\": \"1.0\" }},\n  \"geometricError\": {ge},\n  \"root\": {{\n    \"boundingVolume\": {{ \"box\": [{}, {}, {}, {}, 0, 0, 0, {}, 0, 0, 0, {}] }},\n    \"geometricError\": {ge},\n    \"refine\": \"ADD\",\n    \"content\": {{ \"uri\": \"{}\" }}\n  }}\n}}\n",
+        c[0],
+        c[1],
+        c[2],
+        h[0],
+        h[1],
+        h[2],
+        content_uri.replace('\\', "\\\\").replace('"', "\\\""),
+        ge = geometric_error,
+    ))
+}
+
+/// Write a `.pnts` tile and a `tileset.json` next to it into `dir`.
+pub fn write_tileset<P: AsRef<Path>>(
+    dir: P,
+    block: &PointBlock,
+    geometric_error: f64,
+) -> Result<()> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+    write_pnts_file(dir.join("0.pnts"), block)?;
+    std::fs::write(
+        dir.join("tileset.json"),
+        tileset_json(block, "0.pnts", geometric_error)?,
+    )?;
+    Ok(())
+}
+
+fn pad_to(buf: &mut Vec<u8>, base: usize, fill: u8) {
+    while !(base + buf.len()).is_multiple_of(8) {
+        buf.push(fill);
+    }
+}
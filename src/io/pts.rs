@@ -0,0 +1,510 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Leica PTS and PTX plain-text scanner formats.
+//!
+//! PTS is a point count line followed by `x y z [intensity] [r g b]` lines,
+//! optionally repeated for several blocks. PTX stores one or more organized
+//! scans, each with a ten line header (grid size, scanner registration and a
+//! 4x4 row-vector transform) followed by `columns * rows` points in
+//! column-major order.
+//!
+//! Points map onto F32 `x y z`, an F32 `intensity` column and a packed U32
+//! `rgb` column (`0x00RRGGBB`), matching `PointBlock::xyzrgb`.
+
+use crate::decoder::ascii::parse_token;
+use crate::error::{PcdError, Result};
+use crate::header::{Schema, ValueType};
+use crate::storage::PointBlock;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Largest PTX scan read, in points and per side; `PtxScan` keeps its size
+/// as `u32`.
+const MAX_SCAN_POINTS: usize = u32::MAX as usize;
+
+/// Options for reading PTX files.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PtxOptions {
+    /// Transform points into the registered frame with the per-scan matrix.
+    /// When `false`, coordinates stay in the scanner frame and the transform
+    /// is only reported through `PtxScan::viewpoint`.
+    pub apply_transform: bool,
+}
+
+/// One organized scan read from (or written to) a PTX file.
+#[derive(Debug)]
+pub struct PtxScan {
+    /// Points in row-major order (`index = row * width + column`).
+    pub block: PointBlock,
+    /// Number of scan columns.
+    pub width: u32,
+    /// Number of scan rows.
+    pub height: u32,
+    /// Scan pose as a PCD viewpoint (tx, ty, tz, qw, qx, qy, qz).
+    pub viewpoint: [f64; 7],
+}
+
+// ========================
+// PTS
+// ========================
+
+/// Read a PTS file into a single unorganized `PointBlock`.
+pub fn read_pts_file<P: AsRef<Path>>(path: P) -> Result<PointBlock> {
+    let file = File::open(path)?;
+    read_pts(BufReader::new(file))
+}
+
+/// Read PTS data from any buffered reader.
+///
+/// All blocks in the file are concatenated.
+pub fn read_pts<R: BufRead>(reader: R) -> Result<PointBlock> {
+    let mut lines = TextLines::new(reader);
+    let mut rows: Vec<Vec<String>> = Vec::new();
+
+    while let Some((line_num, count_line)) = lines.next_line()? {
+        let count = parse_count(&count_line, line_num)?;
+        for _ in 0..count {
+            let (line_num, line) = lines.expect_line()?;
+            let tokens: Vec<String> = line.split_whitespace().map(str::to_string).collect();
+            if let Some(first) = rows.first() {
+                if first.len() != tokens.len() {
+                    return Err(PcdError::InvalidDataFormat(format!(
+                        "Line {}: expected {} columns, got {}",
+                        line_num,
+                        first.len(),
+                        tokens.len()
+                    )));
+                }
+            }
+            rows.push(tokens);
+        }
+    }
+
+    let columns = rows.first().map_or(3, Vec::len);
+    let mut block = PointBlock::new(point_schema(columns)?, rows.len());
+    for (i, tokens) in rows.iter().enumerate() {
+        fill_point(&mut block, i, tokens)?;
+    }
+    Ok(block)
+}
+
+/// Write a `PointBlock` as a single-block PTS file.
+pub fn write_pts_file<P: AsRef<Path>>(path: P, block: &PointBlock) -> Result<()> {
+    let file = File::create(path)?;
+    let mut writer = BufWriter::new(file);
+    write_pts(&mut writer, block)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write PTS data to any writer.
+///
+/// `intensity` (F32) and `rgb` (U32) are written when present.
+pub fn write_pts<W: Write>(mut writer: W, block: &PointBlock) -> Result<()> {
+    writeln!(writer, "{}", block.len)?;
+    write_points(&mut writer, block, 0..block.len, false)
+}
+
+// ========================
+// PTX
+// ========================
+
+/// Read every scan of a PTX file.
+pub fn read_ptx_file<P: AsRef<Path>>(path: P, options: &PtxOptions) -> Result<Vec<PtxScan>> {
+    let file = File::open(path)?;
+    read_ptx(BufReader::new(file), options)
+}
+
+/// Read PTX data from any buffered reader.
+pub fn read_ptx<R: BufRead>(reader: R, options: &PtxOptions) -> Result<Vec<PtxScan>> {
+    let mut lines = TextLines::new(reader);
+    let mut scans = Vec::new();
+
+    while let Some((line_num, cols_line)) = lines.next_line()? {
+        let width = parse_count(&cols_line, line_num)?;
+        let (line_num, rows_line) = lines.expect_line()?;
+        let height = parse_count(&rows_line, line_num)?;
+
+        // Scanner registration (position + 3 axes) duplicates the matrix below.
+        for _ in 0..4 {
+            lines.expect_line()?;
+        }
+        let mut matrix = [[0.0f64; 4]; 4];
+        for row in matrix.iter_mut() {
+            let (line_num, line) = lines.expect_line()?;
+            let values = parse_floats(&line, line_num, 4)?;
+            row.copy_from_slice(&values[..4]);
+        }
+
+        let total = width
+            .checked_mul(height)
+            .filter(|&total| width.max(height).max(total) <= MAX_SCAN_POINTS)
+            .ok_or_else(|| {
+                PcdError::InvalidDataFormat(format!(
+                    "Line {}: PTX scan of {}x{} points is too large",
+                    line_num, width, height
+                ))
+            })?;
+        // Points are stored column by column. Rows grow with the lines
+        // actually read, so a bogus size fails at the end of the input
+        // instead of allocating up front.
+        let mut by_column: Vec<Vec<String>> = Vec::new();
+        for _ in 0..total {
+            let (line_num, line) = lines.expect_line()?;
+            let tokens: Vec<String> = line.split_whitespace().map(str::to_string).collect();
+            if let Some(first) = by_column.first() {
+                if first.len() != tokens.len() {
+                    return Err(PcdError::InvalidDataFormat(format!(
+                        "Line {}: expected {} columns, got {}",
+                        line_num,
+                        first.len(),
+                        tokens.len()
+                    )));
+                }
+            }
+            by_column.push(tokens);
+        }
+        let rows: Vec<Vec<String>> = (0..total)
+            .map(|i| std::mem::take(&mut by_column[(i % width) * height + i / width]))
+            .collect();
+
+        let columns = rows.first().map_or(4, Vec::len);
+        let mut block = PointBlock::new(point_schema(columns)?, total);
+        for (i, tokens) in rows.iter().enumerate() {
+            fill_point(&mut block, i, tokens)?;
+        }
+
+        if options.apply_transform {
+            apply_row_transform(&mut block, &matrix);
+        }
+
+        scans.push(PtxScan {
+            block,
+            width: width as u32,
+            height: height as u32,
+            viewpoint: if options.apply_transform {
+                [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]
+            } else {
+                matrix_to_viewpoint(&matrix)
+            },
+        });
+    }
+
+    Ok(scans)
+}
+
+/// Write scans as a PTX file.
+pub fn write_ptx_file<P: AsRef<Path>>(path: P, scans: &[PtxScan]) -> Result<()> {
+    let file = File::create(path)?;
+    let mut writer = BufWriter::new(file);
+    write_ptx(&mut writer, scans)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write PTX data to any writer.
+///
+/// Each scan's viewpoint is written as the scanner registration and transform.
+/// PTX always carries an intensity value, so `0.5` is written when the block
+/// has no `intensity` column.
+pub fn write_ptx<W: Write>(mut writer: W, scans: &[PtxScan]) -> Result<()> {
+    for scan in scans {
+        let total = scan.width as usize * scan.height as usize;
+        if total != scan.block.len {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "PTX scan is {}x{} but block has {} points",
+                scan.width, scan.height, scan.block.len
+            )));
+        }
+
+        let m = viewpoint_to_matrix(&scan.viewpoint);
+        writeln!(writer, "{}", scan.width)?;
+        writeln!(writer, "{}", scan.height)?;
+        writeln!(writer, "{} {} {}", m[3][0], m[3][1], m[3][2])?;
+        for axis in m.iter().take(3) {
+            writeln!(writer, "{} {} {}", axis[0], axis[1], axis[2])?;
+        }
+        for row in &m {
+            writeln!(writer, "{} {} {} {}", row[0], row[1], row[2], row[3])?;
+        }
+
+        let width = scan.width as usize;
+        let height = scan.height as usize;
+        let order = (0..width).flat_map(|c| (0..height).map(move |r| r * width + c));
+        write_points(&mut writer, &scan.block, order, true)?;
+    }
+    Ok(())
+}
+
+// ========================
+// Shared helpers
+// ========================
+
+struct TextLines<R: BufRead> {
+    reader: R,
+    line_num: usize,
+}
+
+impl<R: BufRead> TextLines<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line_num: 0,
+        }
+    }
+
+    /// Next non-empty line, or `None` at EOF.
+    fn next_line(&mut self) -> Result<Option<(usize, String)>> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            self.line_num += 1;
+            let trimmed = line.trim();
+            if !trimmed.is_empty() {
+                return Ok(Some((self.line_num, trimmed.to_string())));
+            }
+        }
+    }
+
+    fn expect_line(&mut self) -> Result<(usize, String)> {
+        self.next_line()?.ok_or_else(|| {
+            PcdError::Io(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "Unexpected EOF in scanner data",
+            ))
+        })
+    }
+}
+
+fn parse_count(line: &str, line_num: usize) -> Result<usize> {
+    line.parse::<usize>().map_err(|_| {
+        PcdError::InvalidDataFormat(format!("Line {}: invalid count: {}", line_num, line))
+    })
+}
+
+fn parse_floats(line: &str, line_num: usize, expected: usize) -> Result<Vec<f64>> {
+    let values: Vec<f64> = line
+        .split_whitespace()
+        .map(|t| t.parse::<f64>())
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|_| PcdError::InvalidDataFormat(format!("Line {}: invalid number", line_num)))?;
+    if values.len() < expected {
+        return Err(PcdError::InvalidDataFormat(format!(
+            "Line {}: expected {} values, got {}",
+            line_num,
+            expected,
+            values.len()
+        )));
+    }
+    Ok(values)
+}
+
+/// Schema for a line with `columns` tokens: xyz, xyz+i, xyz+rgb or xyz+i+rgb.
+fn point_schema(columns: usize) -> Result<Schema> {
+    let schema = Schema::new()
+        .with_field("x", ValueType::F32)
+        .with_field("y", ValueType::F32)
+        .with_field("z", ValueType::F32);
+    Ok(match columns {
+        3 => schema,
+        4 => schema.with_field("intensity", ValueType::F32),
+        6 => schema.with_field("rgb", ValueType::U32),
+        7 => schema
+            .with_field("intensity", ValueType::F32)
+            .with_field("rgb", ValueType::U32),
+        n => {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Unsupported scanner point with {} values",
+                n
+            )));
+        }
+    })
+}
+
+/// Parse the values of point `i` into `block`, whose schema is
+/// [`point_schema`] of the number of `tokens`.
+fn fill_point(block: &mut PointBlock, i: usize, tokens: &[String]) -> Result<()> {
+    let missing = |name: &str| {
+        PcdError::InvalidDataFormat(format!(
+            "Point {} has {} values, which do not fit column {}",
+            i,
+            tokens.len(),
+            name
+        ))
+    };
+    for (name, token) in ["x", "y", "z"].into_iter().zip(tokens) {
+        let col = block.get_column_mut(name).ok_or_else(|| missing(name))?;
+        parse_token(col, i, token)?;
+    }
+    let mut next = 3;
+    if tokens.len() == 4 || tokens.len() == 7 {
+        let col = block
+            .get_column_mut("intensity")
+            .ok_or_else(|| missing("intensity"))?;
+        parse_token(col, i, &tokens[next])?;
+        next += 1;
+    }
+    if tokens.len() >= 6 {
+        let mut rgb = 0u32;
+        for token in tokens.get(next..next + 3).ok_or_else(|| missing("rgb"))? {
+            let c = token
+                .parse::<u8>()
+                .map_err(|_| PcdError::InvalidDataFormat(format!("Invalid color: {}", token)))?;
+            rgb = (rgb << 8) | c as u32;
+        }
+        let col = block
+            .get_column_mut("rgb")
+            .and_then(|c| c.as_u32_mut())
+            .ok_or_else(|| missing("rgb"))?;
+        col[i] = rgb;
+    }
+    Ok(())
+}
+
+fn write_points<W: Write>(
+    writer: &mut W,
+    block: &PointBlock,
+    order: impl Iterator<Item = usize>,
+    force_intensity: bool,
+) -> Result<()> {
+    let (x, y, z) = block.xyz().ok_or_else(|| {
+        PcdError::InvalidDataFormat("Scanner output requires F32 x, y, z columns".to_string())
+    })?;
+    let intensity = block.get_column("intensity").and_then(|c| c.as_f32());
+    let rgb = block.get_column("rgb").and_then(|c| c.as_u32());
+
+    for i in order {
+        write!(writer, "{:.6} {:.6} {:.6}", x[i], y[i], z[i])?;
+        match intensity {
+            Some(v) => write!(writer, " {:.6}", v[i])?,
+            None if force_intensity => write!(writer, " 0.5")?,
+            None => {}
+        }
+        if let Some(c) = rgb {
+            let c = c[i];
+            write!(
+                writer,
+                " {} {} {}",
+                (c >> 16) & 0xff,
+                (c >> 8) & 0xff,
+                c & 0xff
+            )?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// Apply a PTX row-vector transform (`p' = [x y z 1] * M`) in place.
+fn apply_row_transform(block: &mut PointBlock, m: &[[f64; 4]; 4]) {
+    let names = ["x".to_string(), "y".to_string(), "z".to_string()];
+    let mut cols = block.get_columns_mut(&names).unwrap();
+    let (xs, rest) = cols.split_first_mut().unwrap();
+    let (ys, rest) = rest.split_first_mut().unwrap();
+    let xs = xs.as_f32_mut().unwrap();
+    let ys = ys.as_f32_mut().unwrap();
+    let zs = rest[0].as_f32_mut().unwrap();
+
+    for ((x, y), z) in xs.iter_mut().zip(ys.iter_mut()).zip(zs.iter_mut()) {
+        let (px, py, pz) = (*x as f64, *y as f64, *z as f64);
+        // Missing returns are stored as the origin and must stay there.
+        if px == 0.0 && py == 0.0 && pz == 0.0 {
+            continue;
+        }
+        *x = (px * m[0][0] + py * m[1][0] + pz * m[2][0] + m[3][0]) as f32;
+        *y = (px * m[0][1] + py * m[1][1] + pz * m[2][1] + m[3][1]) as f32;
+        *z = (px * m[0][2] + py * m[1][2] + pz * m[2][2] + m[3][2]) as f32;
+    }
+}
+
+/// Convert a PTX row-vector matrix to a viewpoint (tx, ty, tz, qw, qx, qy, qz).
+fn matrix_to_viewpoint(m: &[[f64; 4]; 4]) -> [f64; 7] {
+    // Column-vector rotation R is the transpose of the upper-left block.
+    let r = |i: usize, j: usize| m[j][i];
+    let trace = r(0, 0) + r(1, 1) + r(2, 2);
+    let (qw, qx, qy, qz) = if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        (
+            0.25 * s,
+            (r(2, 1) - r(1, 2)) / s,
+            (r(0, 2) - r(2, 0)) / s,
+            (r(1, 0) - r(0, 1)) / s,
+        )
+    } else if r(0, 0) > r(1, 1) && r(0, 0) > r(2, 2) {
+        let s = (1.0 + r(0, 0) - r(1, 1) - r(2, 2)).sqrt() * 2.0;
+        (
+            (r(2, 1) - r(1, 2)) / s,
+            0.25 * s,
+            (r(0, 1) + r(1, 0)) / s,
+            (r(0, 2) + r(2, 0)) / s,
+        )
+    } else if r(1, 1) > r(2, 2) {
+        let s = (1.0 + r(1, 1) - r(0, 0) - r(2, 2)).sqrt() * 2.0;
+        (
+            (r(0, 2) - r(2, 0)) / s,
+            (r(0, 1) + r(1, 0)) / s,
+            0.25 * s,
+            (r(1, 2) + r(2, 1)) / s,
+        )
+    } else {
+        let s = (1.0 + r(2, 2) - r(0, 0) - r(1, 1)).sqrt() * 2.0;
+        (
+            (r(1, 0) - r(0, 1)) / s,
+            (r(0, 2) + r(2, 0)) / s,
+            (r(1, 2) + r(2, 1)) / s,
+            0.25 * s,
+        )
+    };
+    [m[3][0], m[3][1], m[3][2], qw, qx, qy, qz]
+}
+
+/// Convert a viewpoint to a PTX row-vector matrix.
+fn viewpoint_to_matrix(vp: &[f64; 7]) -> [[f64; 4]; 4] {
+    let [tx, ty, tz, w, x, y, z] = *vp;
+    let n = (w * w + x * x + y * y + z * z).sqrt();
+    let (w, x, y, z) = if n > 0.0 {
+        (w / n, x / n, y / n, z / n)
+    } else {
+        (1.0, 0.0, 0.0, 0.0)
+    };
+    // Column-vector rotation, stored transposed.
+    let r = [
+        [
+            1.0 - 2.0 * (y * y + z * z),
+            2.0 * (x * y - z * w),
+            2.0 * (x * z + y * w),
+        ],
+        [
+            2.0 * (x * y + z * w),
+            1.0 - 2.0 * (x * x + z * z),
+            2.0 * (y * z - x * w),
+        ],
+        [
+            2.0 * (x * z - y * w),
+            2.0 * (y * z + x * w),
+            1.0 - 2.0 * (x * x + y * y),
+        ],
+    ];
+    [
+        [r[0][0], r[1][0], r[2][0], 0.0],
+        [r[0][1], r[1][1], r[2][1], 0.0],
+        [r[0][2], r[1][2], r[2][2], 0.0],
+        [tx, ty, tz, 1.0],
+    ]
+}
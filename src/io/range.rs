@@ -0,0 +1,318 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Partial reads over byte-range sources such as HTTP servers.
+//!
+//! [`RangeReader`] fetches the header, then only what a read needs. For
+//! `binary` data a point range maps to one contiguous byte range, and column
+//! projection skips decoding the other fields. Other formats cannot be
+//! addressed by point, so their whole data section is fetched once.
+//!
+//! [`HttpRangeSource`] speaks plain `http://`; for HTTPS or authenticated
+//! hosts, implement [`RangeSource`] over the HTTP client of your choice.
+
+use super::reader::header_end;
+use super::validate::check_header;
+use crate::decoder::binary::BinaryReader;
+use crate::decoder::{decode_slice, relocate};
+use crate::error::{PcdError, Result};
+use crate::header::{DataFormat, PcdHeader, parse_header};
+use crate::layout::{DEFAULT_MEMORY_LIMIT, PcdLayout};
+use crate::storage::PointBlock;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor, Read, Seek, SeekFrom, Write};
+use std::net::TcpStream;
+use std::ops::Range;
+
+/// Bytes requested for the header; doubled until the `DATA` line is found.
+const HEADER_PROBE: u64 = 4096;
+
+/// Random access to the bytes of a remote or local object.
+pub trait RangeSource {
+    /// Total size in bytes.
+    fn size(&mut self) -> Result<u64>;
+
+    /// The bytes in `range`, which lies within the object.
+    fn read_range(&mut self, range: Range<u64>) -> Result<Vec<u8>>;
+}
+
+impl RangeSource for File {
+    fn size(&mut self) -> Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+
+    fn read_range(&mut self, range: Range<u64>) -> Result<Vec<u8>> {
+        self.seek(SeekFrom::Start(range.start))?;
+        let mut buf = vec![0u8; (range.end - range.start) as usize];
+        self.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+}
+
+/// Reads a PCD from a [`RangeSource`] in parts.
+pub struct RangeReader<S: RangeSource> {
+    source: S,
+    header: PcdHeader,
+    layout: PcdLayout,
+    data_start: u64,
+    size: u64,
+    memory_limit: u64,
+    // Whole data section of non-binary files, decoded on first use.
+    decoded: Option<PointBlock>,
+}
+
+impl<S: RangeSource> RangeReader<S> {
+    /// Fetch and parse the header, checking it against the object's size.
+    pub fn new(mut source: S) -> Result<Self> {
+        let size = source.size()?;
+        let mut probe = HEADER_PROBE.min(size);
+        let (header, data_start) = loop {
+            let bytes = source.read_range(0..probe)?;
+            if let Some(end) = header_end(&bytes) {
+                break (parse_header(&mut Cursor::new(&bytes[..end]))?, end as u64);
+            }
+            if probe == size {
+                // Truncated header, or a DATA line without a newline.
+                break (parse_header(&mut Cursor::new(&bytes[..]))?, size);
+            }
+            probe = (probe * 2).min(size);
+        };
+        let layout = PcdLayout::from_header(&header)?;
+        check_header(&header, &layout, Some(size - data_start))?;
+        Ok(Self {
+            source,
+            header,
+            layout,
+            data_start,
+            size,
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            decoded: None,
+        })
+    }
+
+    /// Refuse reads whose points, or for non-binary data the whole data
+    /// section, would take more than `bytes`, with [`PcdError::TooLarge`],
+    /// before fetching them. Defaults to [`DEFAULT_MEMORY_LIMIT`].
+    #[must_use]
+    pub fn memory_limit(mut self, bytes: u64) -> Self {
+        self.memory_limit = bytes;
+        self
+    }
+
+    pub fn header(&self) -> &PcdHeader {
+        &self.header
+    }
+
+    /// Decode the points in `points`, keeping only `columns` if given.
+    pub fn read(&mut self, points: Range<usize>, columns: Option<&[&str]>) -> Result<PointBlock> {
+        if points.start > points.end || points.end > self.header.points {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Point range {:?} outside 0..{}",
+                points, self.header.points
+            )));
+        }
+        let layout = self.project(columns)?;
+        let schema = layout.schema();
+        let n = points.end - points.start;
+
+        if self.header.data == DataFormat::Binary {
+            let bytes = layout.block_size(n, self.memory_limit)? as u64;
+            // Within the data section, which `new` checked holds every point.
+            let start = self.data_start + points.start as u64 * layout.total_size as u64;
+            let data = self.source.read_range(start..start + bytes)?;
+            let mut block = PointBlock::new(schema, n);
+            BinaryReader::new(&mut Cursor::new(data), &layout, n)
+                .at(start, points.start)
+                .decode(&mut block)?;
+            return Ok(block);
+        }
+
+        if self.decoded.is_none() {
+            let points = self.header.points;
+            self.layout.block_size(points, self.memory_limit)?;
+            if self.size - self.data_start > self.memory_limit {
+                return Err(PcdError::TooLarge {
+                    points,
+                    limit: self.memory_limit,
+                });
+            }
+            let data = self.source.read_range(self.data_start..self.size)?;
+            self.decoded = Some(
+                decode_slice(&data, self.header.data, &self.layout, self.header.points)
+                    .map_err(|e| relocate(e, self.data_start, 0))?,
+            );
+        }
+        let all = self.decoded.as_ref().unwrap();
+        let mut block = PointBlock::new(schema, n);
+        for (i, field) in layout.fields.iter().enumerate() {
+            let col = all.get_column(&field.name).unwrap();
+            block.columns_mut()[i] =
+                col.slice(points.start * field.count..points.end * field.count);
+        }
+        Ok(block)
+    }
+
+    /// Decode every point, keeping only `columns` if given.
+    pub fn read_all(&mut self, columns: Option<&[&str]>) -> Result<PointBlock> {
+        self.read(0..self.header.points, columns)
+    }
+
+    /// The layout restricted to `columns`, with record offsets unchanged.
+    fn project(&self, columns: Option<&[&str]>) -> Result<PcdLayout> {
+        let Some(columns) = columns else {
+            return Ok(self.layout.clone());
+        };
+        let mut fields = Vec::with_capacity(columns.len());
+        for name in columns {
+            let field = self
+                .layout
+                .get_field(name)
+                .ok_or_else(|| PcdError::InvalidDataFormat(format!("Missing column {}", name)))?;
+            fields.push(field.clone());
+        }
+        Ok(PcdLayout {
+            fields,
+            total_size: self.layout.total_size,
+        })
+    }
+}
+
+/// [`RangeSource`] over plain HTTP/1.1 `Range` requests.
+pub struct HttpRangeSource {
+    host: String,
+    port: u16,
+    path: String,
+    size: Option<u64>,
+}
+
+impl HttpRangeSource {
+    /// Source for an `http://host[:port]/path` URL.
+    pub fn new(url: &str) -> Result<Self> {
+        let rest = url.strip_prefix("http://").ok_or_else(|| {
+            PcdError::UnsupportedDataFormat(format!(
+                "Only http:// URLs are supported, got {}; implement RangeSource for others",
+                url
+            ))
+        })?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse()
+                    .map_err(|_| PcdError::Other(format!("Invalid port in {}", url)))?,
+            ),
+            None => (authority, 80),
+        };
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+            size: None,
+        })
+    }
+
+    /// Issue one range request, returning the body and the total size from
+    /// `Content-Range`.
+    fn request(&self, range: Range<u64>) -> Result<(Vec<u8>, Option<u64>)> {
+        let mut stream = TcpStream::connect((self.host.as_str(), self.port))?;
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: {}\r\nRange: bytes={}-{}\r\nConnection: close\r\n\r\n",
+            self.path,
+            self.host,
+            range.start,
+            range.end.saturating_sub(1)
+        )?;
+        let mut reader = BufReader::new(stream);
+
+        let mut status = String::new();
+        reader.read_line(&mut status)?;
+        let code = status.split_whitespace().nth(1).unwrap_or("");
+        let mut total = None;
+        let mut length = None;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "content-range" => total = value.rsplit('/').next().and_then(|t| t.parse().ok()),
+                "content-length" => length = value.parse::<u64>().ok(),
+                "transfer-encoding" if value.eq_ignore_ascii_case("chunked") => {
+                    return Err(PcdError::UnsupportedDataFormat(
+                        "Chunked HTTP responses".to_string(),
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        let mut body = Vec::new();
+        match length {
+            Some(len) => reader.take(len).read_to_end(&mut body)?,
+            None => reader.read_to_end(&mut body)?,
+        };
+        match code {
+            "206" => Ok((body, total)),
+            // The server ignored the Range header and sent everything.
+            "200" => {
+                let total = body.len() as u64;
+                let end = range.end.min(total) as usize;
+                let start = (range.start as usize).min(end);
+                Ok((body[start..end].to_vec(), Some(total)))
+            }
+            _ => Err(PcdError::Io(std::io::Error::other(format!(
+                "HTTP request for {} failed: {}",
+                self.path,
+                status.trim()
+            )))),
+        }
+    }
+}
+
+impl RangeSource for HttpRangeSource {
+    fn size(&mut self) -> Result<u64> {
+        if let Some(size) = self.size {
+            return Ok(size);
+        }
+        let (_, total) = self.request(0..1)?;
+        let size = total.ok_or_else(|| {
+            PcdError::Other("HTTP response without Content-Range size".to_string())
+        })?;
+        self.size = Some(size);
+        Ok(size)
+    }
+
+    fn read_range(&mut self, range: Range<u64>) -> Result<Vec<u8>> {
+        if range.start == range.end {
+            return Ok(Vec::new());
+        }
+        let expected = (range.end - range.start) as usize;
+        let (body, _) = self.request(range)?;
+        if body.len() != expected {
+            return Err(PcdError::BufferTooSmall {
+                expected,
+                got: body.len(),
+            });
+        }
+        Ok(body)
+    }
+}
@@ -12,22 +12,41 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::decoder::ascii::AsciiReader;
-use crate::decoder::binary::BinaryReader;
-#[cfg(feature = "rayon")]
+use super::validate::{Issue, check_header};
+use crate::decoder::ascii::{AsciiReader, ShortLines};
+use crate::decoder::binary::{BinaryReader, DEFAULT_BATCH_BYTES, decode_binary, read_full};
+#[cfg(all(feature = "rayon", feature = "memmap2"))]
+use crate::decoder::ascii_par::AsciiParallelDecoder;
+#[cfg(all(feature = "rayon", feature = "memmap2"))]
 use crate::decoder::binary_par::BinaryParallelDecoder;
-use crate::decoder::compressed::CompressedReader;
+#[cfg(feature = "memmap2")]
+use crate::decoder::compressed::decode_compressed;
+use crate::decoder::compressed::{Codec, CompressedReader};
+use crate::decoder::decode_slice;
+use crate::decoder::{corrupt_record, relocate};
+use crate::error::{Operation, PcdError};
 use crate::error::Result;
-use crate::header::{DataFormat, PcdHeader, parse_header};
-use crate::layout::PcdLayout;
-use crate::storage::PointBlock;
+use crate::header::{
+    DataFormat, HeaderOptions, PcdHeader, Schema, ValueType, parse_header_bytes,
+    parse_header_counted, parse_header_with,
+};
+use crate::layout::{DEFAULT_MEMORY_LIMIT, PcdLayout};
+use crate::storage::{Column, PointBlock, PointCloud};
 
+#[cfg(feature = "flate2")]
+use flate2::bufread::GzDecoder;
 #[cfg(feature = "memmap2")]
 use memmap2::Mmap;
 use std::fs::File;
 use std::io::Cursor;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
 use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Size of the chunks [`PcdReader::read_all`] decodes data of unknown
+/// extent in.
+const STREAM_CHUNK_BYTES: usize = 16 << 20;
 
 pub enum InputSource<R: BufRead> {
     Reader(R),
@@ -39,19 +58,69 @@ pub struct PcdReader<R: BufRead> {
     source: InputSource<R>,
     header: PcdHeader,
     layout: PcdLayout,
+    // Points not yet decoded.
+    remaining: usize,
+    // Non-fatal problems: header issues found on opening, then skipped
+    // ascii lines.
+    issues: Vec<Issue>,
+    position: DataPos,
+    // Most bytes one read may decode.
+    memory_limit: u64,
+    // Length of the data section, if known.
+    data_len: Option<u64>,
+    // Fields to return, see `target_schema`.
+    target: Option<Schema>,
+    // (name in the file, name returned)
+    renames: Vec<(String, String)>,
+    // Fill target fields the file lacks instead of failing.
+    fill_missing: bool,
     #[cfg(feature = "memmap2")]
     start_offset: usize, // Offset where data starts (after header)
 }
 
 impl<R: BufRead> PcdReader<R> {
-    pub fn new(mut reader: R) -> Result<Self> {
-        let header = parse_header(&mut reader)?;
-        let layout = PcdLayout::from_header(&header)?;
+    pub fn new(reader: R) -> Result<Self> {
+        Self::with_options(reader, &HeaderOptions::default())
+    }
+
+    /// Like [`new`](Self::new), parsing the header with `options`, e.g. to
+    /// accept PCD 0.6 files without `POINTS`.
+    pub fn with_options(mut reader: R, options: &HeaderOptions) -> Result<Self> {
+        let (header, data_start) = parse_header_counted(&mut reader, options)?;
+        Self::from_parts(InputSource::Reader(reader), header, data_start, None)
+    }
 
+    /// Check `header` against the data length, if known, and set up decoding
+    /// of the data at offset `data_start`.
+    fn from_parts(
+        source: InputSource<R>,
+        header: PcdHeader,
+        data_start: u64,
+        data_len: Option<u64>,
+    ) -> Result<Self> {
+        let layout = PcdLayout::from_header(&header)?;
+        let issues = check_header(&header, &layout, data_len)?;
         Ok(PcdReader {
-            source: InputSource::Reader(reader),
+            source,
+            remaining: header.points,
             header,
             layout,
+            issues,
+            position: DataPos {
+                offset: data_start,
+                point: 0,
+                line: 1,
+                skip_malformed: false,
+                short_lines: ShortLines::Error,
+                batch_bytes: DEFAULT_BATCH_BYTES,
+                progress: None,
+                cancel: None,
+            },
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            data_len,
+            target: None,
+            renames: Vec::new(),
+            fill_missing: false,
             #[cfg(feature = "memmap2")]
             start_offset: 0,
         })
@@ -62,17 +131,84 @@ impl<R: BufRead> PcdReader<R> {
 /// Useful for embedded resources, network data, or in-memory buffers.
 impl<'a> PcdReader<BufReader<Cursor<&'a [u8]>>> {
     pub fn from_bytes(data: &'a [u8]) -> Result<Self> {
-        let cursor = Cursor::new(data);
-        let reader = BufReader::new(cursor);
-        Self::new(reader)
+        let mut reader = BufReader::new(Cursor::new(data));
+        let header = parse_header_with(&mut reader, &HeaderOptions::default())?;
+        let data_start = reader.stream_position()?;
+        let data_len = data.len() as u64 - data_start;
+        Self::from_parts(
+            InputSource::Reader(reader),
+            header,
+            data_start,
+            Some(data_len),
+        )
     }
 }
 
+/// Parse the header of a plain file, returning it with the offset and length
+/// of the data.
+fn parse_file_header(
+    reader: &mut BufReader<File>,
+    options: &HeaderOptions,
+) -> Result<(PcdHeader, u64, u64)> {
+    let header = parse_header_with(reader, options)?;
+    let data_start = reader.stream_position()?;
+    let data_len = reader.get_ref().metadata()?.len() - data_start;
+    Ok((header, data_start, data_len))
+}
+
+impl PcdReader<Box<dyn BufRead>> {
+    /// Open a PCD file, transparently decompressing gzip (`.pcd.gz`) files
+    /// when the `flate2` feature is enabled. Detection is by magic bytes, not
+    /// by extension.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::open_with(path, &HeaderOptions::default())
+    }
+
+    /// Like [`open`](Self::open), parsing the header with `options`.
+    pub fn open_with<P: AsRef<Path>>(path: P, options: &HeaderOptions) -> Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        if is_gzip(&mut reader)? {
+            return Self::with_options(gzip_reader(reader)?, options);
+        }
+        let (header, data_start, data_len) = parse_file_header(&mut reader, options)?;
+        let source: Box<dyn BufRead> = Box::new(reader);
+        Self::from_parts(
+            InputSource::Reader(source),
+            header,
+            data_start,
+            Some(data_len),
+        )
+    }
+}
+
+/// Whether the stream starts with the gzip magic bytes. Nothing is consumed.
+pub fn is_gzip<R: BufRead>(reader: &mut R) -> Result<bool> {
+    Ok(reader.fill_buf()?.starts_with(&[0x1f, 0x8b]))
+}
+
+#[cfg(feature = "flate2")]
+fn gzip_reader<R: BufRead + 'static>(reader: R) -> Result<Box<dyn BufRead>> {
+    Ok(Box::new(BufReader::new(GzDecoder::new(reader))))
+}
+
+#[cfg(not(feature = "flate2"))]
+fn gzip_reader<R: BufRead + 'static>(_reader: R) -> Result<Box<dyn BufRead>> {
+    Err(PcdError::UnsupportedDataFormat(
+        "gzip input requires the flate2 feature".to_string(),
+    ))
+}
+
 impl PcdReader<BufReader<File>> {
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        Self::new(reader)
+        let mut reader = BufReader::new(File::open(path)?);
+        let (header, data_start, data_len) =
+            parse_file_header(&mut reader, &HeaderOptions::default())?;
+        Self::from_parts(
+            InputSource::Reader(reader),
+            header,
+            data_start,
+            Some(data_len),
+        )
     }
 
     #[cfg(feature = "memmap2")]
@@ -83,17 +219,14 @@ impl PcdReader<BufReader<File>> {
 
         // Parse header from mmap slice
         let mut cursor = Cursor::new(&mmap[..]);
-        let header = parse_header(&mut cursor)?;
+        let header = parse_header_with(&mut cursor, &HeaderOptions::default())?;
         let pos = cursor.position() as usize; // This is the data start offset
 
-        let layout = PcdLayout::from_header(&header)?;
-
-        Ok(PcdReader {
-            source: InputSource::Mmap(mmap),
-            header,
-            layout,
-            start_offset: pos,
-        })
+        let data_len = (mmap.len() - pos) as u64;
+        let mut reader =
+            Self::from_parts(InputSource::Mmap(mmap), header, pos as u64, Some(data_len))?;
+        reader.start_offset = pos;
+        Ok(reader)
     }
 }
 
@@ -102,75 +235,575 @@ impl<R: BufRead> PcdReader<R> {
         &self.header
     }
 
-    pub fn read_all(mut self) -> Result<PointBlock> {
-        let points = self.header.points;
-        let mut block = PointBlock::new(
-            &self
-                .layout
-                .fields
+    /// Header inconsistencies that do not prevent decoding, such as `POINTS`
+    /// differing from `WIDTH * HEIGHT` or bytes after the binary data. A
+    /// data section too short for `POINTS` fails when opening instead. Fields
+    /// filled by [`fill_missing`](Self::fill_missing) are listed here too.
+    pub fn issues(&self) -> &[Issue] {
+        &self.issues
+    }
+
+    /// For ascii data, skip lines that do not parse instead of failing, and
+    /// record each in [`issues`](Self::issues) as [`Issue::MalformedLine`].
+    /// If the data then ends before `POINTS` points, the last block is short.
+    #[must_use]
+    pub fn skip_malformed_lines(mut self, skip: bool) -> Self {
+        self.position.skip_malformed = skip;
+        self
+    }
+
+    /// For ascii data, set how lines with fewer values than the fields need
+    /// are handled, instead of failing on them.
+    #[must_use]
+    pub fn short_ascii_lines(mut self, short_lines: ShortLines) -> Self {
+        self.position.short_lines = short_lines;
+        self
+    }
+
+    /// For binary data, read about `bytes` of records at a time instead of
+    /// [`DEFAULT_BATCH_BYTES`]. Raise it for high-latency sources such as
+    /// network file systems, where many small reads are slow.
+    #[must_use]
+    pub fn read_batch_bytes(mut self, bytes: usize) -> Self {
+        self.position.batch_bytes = bytes;
+        self
+    }
+
+    /// Call `progress` with the points decoded so far and `POINTS` as data is
+    /// read: after each batch of binary data, every
+    /// [`PROGRESS_POINTS`](crate::decoder::ascii::PROGRESS_POINTS) ascii
+    /// points, and once per compressed block or memory-mapped read.
+    #[must_use]
+    pub fn on_progress(mut self, mut progress: impl FnMut(usize, usize) + Send + 'static) -> Self {
+        let total = self.header.points;
+        self.position.progress = Some(Box::new(move |points| progress(points, total)));
+        self
+    }
+
+    /// Fail with [`PcdError::Cancelled`] once `flag` is set, which is
+    /// checked before each read and wherever progress is reported. A
+    /// cancelled reader cannot continue.
+    #[must_use]
+    pub fn cancel_on(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.position.cancel = Some(flag);
+        self
+    }
+
+    /// Refuse reads whose points would take more than `bytes`, with
+    /// [`PcdError::TooLarge`], instead of allocating for them. Defaults to
+    /// [`DEFAULT_MEMORY_LIMIT`]; lower it for untrusted input.
+    #[must_use]
+    pub fn memory_limit(mut self, bytes: u64) -> Self {
+        self.memory_limit = bytes;
+        self
+    }
+
+    /// Return points with the fields of `schema`, in its order and cast to
+    /// its types as by [`PointBlock::into_schema`]. File fields it leaves
+    /// out are dropped, and binary data skips decoding them. A field missing
+    /// from the file fails reads with [`PcdError::MissingField`]; use
+    /// [`rename_field`](Self::rename_field) for fields named differently.
+    #[must_use]
+    pub fn target_schema(mut self, schema: Schema) -> Self {
+        self.target = Some(schema);
+        self.project();
+        self
+    }
+
+    /// Give target schema fields that the file lacks zeros, or NaN for
+    /// floats, instead of failing, and record each in
+    /// [`issues`](Self::issues) as [`Issue::FilledField`]. Useful for a fixed
+    /// point type across sensors that omit e.g. `ring` or `intensity`.
+    #[must_use]
+    pub fn fill_missing(mut self, fill: bool) -> Self {
+        self.fill_missing = fill;
+        self
+    }
+
+    /// Return file field `from` as `to`.
+    #[must_use]
+    pub fn rename_field(mut self, from: &str, to: &str) -> Self {
+        self.renames.push((from.to_string(), to.to_string()));
+        self.project();
+        self
+    }
+
+    /// Return fields under canonical names, e.g. `[("t", "timestamp"),
+    /// ("reflectivity", "intensity")]`. Unlike
+    /// [`rename_field`](Self::rename_field), an alias whose field the file
+    /// lacks is ignored, so one map can cover files from several vendors. An
+    /// alias is also skipped when its name is already taken, by a file field
+    /// or by an earlier alias.
+    #[must_use]
+    pub fn aliases<K, V>(mut self, map: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        for (from, to) in map {
+            let (from, to) = (from.as_ref(), to.as_ref());
+            let taken = |name: &str| {
+                self.renames.iter().any(|(_, to)| to == name)
+                    || (self.header.fields.iter().any(|f| f == name)
+                        && !self.renames.iter().any(|(from, _)| from == name))
+            };
+            let renamed = self.renames.iter().any(|(f, _)| f == from);
+            if self.header.fields.iter().any(|f| f == from) && !renamed && !taken(to) {
+                self.renames.push((from.to_string(), to.to_string()));
+            }
+        }
+        self.project();
+        self
+    }
+
+    /// For binary data with a target schema, decode only the fields it
+    /// keeps. Records keep their stride, so nothing else changes.
+    fn project(&mut self) {
+        let (Some(target), DataFormat::Binary) = (&self.target, self.header.data) else {
+            return;
+        };
+        // The header was checked on opening.
+        let Ok(mut layout) = PcdLayout::from_header(&self.header) else {
+            return;
+        };
+        layout.fields.retain(|f| {
+            let name = self
+                .renames
                 .iter()
-                .map(|f| (f.name.clone(), f.type_))
-                .collect(),
-            points,
-        );
+                .find(|(from, _)| *from == f.name)
+                .map_or(&f.name, |(_, to)| to);
+            target.get(name).is_some()
+        });
+        self.layout = layout;
+    }
+
+    /// Apply [`rename_field`](Self::rename_field),
+    /// [`aliases`](Self::aliases) and [`target_schema`](Self::target_schema) to decoded points.
+    fn conform(&mut self, mut block: PointBlock) -> Result<PointBlock> {
+        for (from, to) in &self.renames {
+            if !self.header.fields.contains(from) {
+                return Err(PcdError::MissingField {
+                    field: from.clone(),
+                    op: Operation::Read,
+                });
+            }
+            // Dropped by `project`.
+            if block.get_column_index(from).is_some() {
+                block.rename_column(from, to)?;
+            }
+        }
+        let Some(target) = &self.target else {
+            return Ok(block);
+        };
+        for field in target {
+            if block.get_column(&field.name).is_some() {
+                continue;
+            }
+            if !self.fill_missing {
+                return Err(PcdError::MissingField {
+                    field: field.name.clone(),
+                    op: Operation::Read,
+                });
+            }
+            let n = block.len * field.count;
+            let column = match field.type_ {
+                ValueType::F32 => Column::F32(vec![f32::NAN; n]),
+                ValueType::F64 => Column::F64(vec![f64::NAN; n]),
+                other => Column::new(other, n),
+            };
+            block.add_column(&field.name, column)?;
+            let issue = Issue::FilledField(field.name.clone());
+            if !self.issues.contains(&issue) {
+                self.issues.push(issue);
+            }
+        }
+        block.into_schema(target)
+    }
 
+    /// Drop the reader, keeping only its header.
+    pub fn into_header(self) -> PcdHeader {
+        self.header
+    }
+
+    /// Points not yet returned by [`read_chunk`](Self::read_chunk).
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Decode up to `max_points` further points, or `None` once the data
+    /// section is exhausted. Binary and ASCII data are read incrementally, so
+    /// memory is bounded by the chunk size; compressed data is column-major
+    /// and always arrives as a single chunk.
+    pub fn read_chunk(&mut self, max_points: usize) -> Result<Option<PointBlock>> {
+        match self.decode_chunk(max_points)? {
+            Some(block) => self.conform(block).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn decode_chunk(&mut self, max_points: usize) -> Result<Option<PointBlock>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        let points = if self.header.data.is_compressed() {
+            self.remaining
+        } else {
+            self.remaining.min(max_points.max(1))
+        };
+        self.layout.block_size(points, self.memory_limit)?;
+        let mut block = PointBlock::new(self.layout.schema(), points);
         match &mut self.source {
-            InputSource::Reader(reader) => match self.header.data {
-                DataFormat::Binary => {
-                    let mut decoder = BinaryReader::new(reader, &self.layout, points);
-                    decoder.decode(&mut block)?;
-                }
-                DataFormat::BinaryCompressed => {
-                    let mut decoder = CompressedReader::new(reader, &self.layout, points);
-                    decoder.decode(&mut block)?;
-                }
-                DataFormat::Ascii => {
-                    let mut decoder = AsciiReader::new(reader, &self.layout, points);
-                    decoder.decode(&mut block)?;
-                }
-            },
+            InputSource::Reader(reader) => decode_stream(
+                reader,
+                self.header.data,
+                &self.layout,
+                &mut self.position,
+                &mut self.issues,
+                &mut block,
+            )?,
+            #[cfg(feature = "memmap2")]
+            InputSource::Mmap(mmap) => {
+                let mut cursor = Cursor::new(&mmap[self.start_offset..]);
+                decode_stream(
+                    &mut cursor,
+                    self.header.data,
+                    &self.layout,
+                    &mut self.position,
+                    &mut self.issues,
+                    &mut block,
+                )?;
+                self.start_offset += cursor.position() as usize;
+            }
+        }
+        // Skipped ascii lines can leave the data short of `POINTS`.
+        self.remaining = if block.len < points {
+            0
+        } else {
+            self.remaining - points
+        };
+        Ok(Some(block))
+    }
+
+    /// Decode all remaining points.
+    pub fn read_all(mut self) -> Result<PointBlock> {
+        self.read_rest()
+    }
+
+    /// Like [`read_all`](Self::read_all), keeping the header with the
+    /// points.
+    pub fn read_cloud(mut self) -> Result<PointCloud> {
+        let data = self.read_rest()?;
+        Ok(PointCloud::new(self.header, data))
+    }
+
+    fn read_rest(&mut self) -> Result<PointBlock> {
+        let block = self.decode_rest()?;
+        self.conform(block)
+    }
+
+    fn decode_rest(&mut self) -> Result<PointBlock> {
+        let points = self.remaining;
+        self.layout.block_size(points, self.memory_limit)?;
+        // Only a known length of binary data vouches for `POINTS`; otherwise
+        // decode in chunks, so that memory grows with the data actually
+        // present and a bogus count fails at the end of the input.
+        let streamed = matches!(self.source, InputSource::Reader(_))
+            && !self.header.data.is_compressed()
+            && (self.data_len.is_none() || self.header.data == DataFormat::Ascii);
+        if streamed && points > 0 {
+            let chunk = (STREAM_CHUNK_BYTES / self.layout.total_size.max(1)).max(1);
+            let mut block = PointBlock::new(self.layout.schema(), 0);
+            while let Some(part) = self.decode_chunk(chunk)? {
+                block.append(&part)?;
+            }
+            return Ok(block);
+        }
+        let mut block = PointBlock::new(self.layout.schema(), points);
+        if points == 0 {
+            return Ok(block);
+        }
+
+        match &mut self.source {
+            InputSource::Reader(reader) => decode_stream(
+                reader,
+                self.header.data,
+                &self.layout,
+                &mut self.position,
+                &mut self.issues,
+                &mut block,
+            )?,
             #[cfg(feature = "memmap2")]
             InputSource::Mmap(mmap) => {
+                check_cancel(&self.position.cancel)?;
                 let data_slice = &mmap[self.start_offset..];
 
                 match self.header.data {
+                    #[cfg(feature = "rayon")]
                     DataFormat::Binary => {
-                        #[cfg(feature = "rayon")]
-                        {
-                            // Use parallel decoder if enabled
-                            let decoder = BinaryParallelDecoder::new(&self.layout, points);
-                            decoder.decode_par(data_slice, &mut block)?;
-                        }
-                        #[cfg(not(feature = "rayon"))]
-                        {
-                            // Fallback to sequential using Cursor
-                            let mut cursor = Cursor::new(data_slice);
-                            let mut decoder = BinaryReader::new(&mut cursor, &self.layout, points);
-                            decoder.decode(&mut block)?;
-                        }
+                        // Use parallel decoder if enabled
+                        let decoder = BinaryParallelDecoder::new(&self.layout, points);
+                        decoder.decode_par(data_slice, &mut block)?;
+                    }
+                    // Skipped lines shift the points after them, so only
+                    // strict ascii parses in parallel.
+                    #[cfg(feature = "rayon")]
+                    DataFormat::Ascii if !self.position.skip_malformed => {
+                        AsciiParallelDecoder::new(&self.layout, points)
+                            .at_line(self.position.line)
+                            .short_lines(self.position.short_lines)
+                            .decode_par(data_slice, &mut block)?;
                     }
-                    DataFormat::BinaryCompressed => {
-                        // Parallel Compressed not implemented yet (needs chunks processing)
-                        // Fallback to sequential
-                        let mut cursor = Cursor::new(data_slice);
-                        let mut decoder = CompressedReader::new(&mut cursor, &self.layout, points);
-                        decoder.decode(&mut block)?;
+                    // Decompressed straight from the mapping, with fields
+                    // converted in parallel under rayon.
+                    format if format.is_compressed() => {
+                        let codec = Codec::of(format).expect("a compressed format");
+                        decode_compressed(data_slice, &self.layout, points, codec, &mut block)
+                            .map_err(|e| {
+                                relocate(e, self.position.offset, self.position.point)
+                            })?;
                     }
-                    DataFormat::Ascii => {
-                        let mut cursor = Cursor::new(data_slice);
-                        let mut decoder = AsciiReader::new(&mut cursor, &self.layout, points);
-                        decoder.decode(&mut block)?;
+                    _ => {
+                        return decode_stream(
+                            &mut Cursor::new(data_slice),
+                            self.header.data,
+                            &self.layout,
+                            &mut self.position,
+                            &mut self.issues,
+                            &mut block,
+                        )
+                        .map(|()| block);
                     }
                 }
+                if let Some(progress) = self.position.progress.as_mut() {
+                    progress(self.position.point + points);
+                }
             }
         }
         Ok(block)
     }
+
+}
+
+impl<R: BufRead + Seek> PcdReader<R> {
+    /// Decode point `i` alone; see [`read_points`](Self::read_points).
+    pub fn read_point(&mut self, i: usize) -> Result<PointBlock> {
+        self.read_points(&[i])
+    }
+
+    /// Decode the points at `indices`, in that order, reading only their
+    /// records. Binary data only, as other formats cannot be addressed by
+    /// point. Sequential reads carry on where they were.
+    pub fn read_points(&mut self, indices: &[usize]) -> Result<PointBlock> {
+        if self.header.data != DataFormat::Binary {
+            return Err(PcdError::UnsupportedDataFormat(format!(
+                "Random access to {:?} data",
+                self.header.data
+            )));
+        }
+        if let Some(&i) = indices.iter().find(|&&i| i >= self.header.points) {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Point {} outside 0..{}",
+                i, self.header.points
+            )));
+        }
+        let bytes = self.layout.block_size(indices.len(), self.memory_limit)?;
+        let mut block = PointBlock::new(self.layout.schema(), indices.len());
+        let stride = self.layout.total_size;
+        if stride == 0 {
+            return Ok(block);
+        }
+
+        // Gather the records, then decode them as one run.
+        let data_start = self.position.offset - (self.position.point * stride) as u64;
+        // Saturating, as `POINTS` of unbounded streams is unchecked.
+        let record_offset =
+            |i: usize| data_start.saturating_add((i as u64).saturating_mul(stride as u64));
+        let mut rows = vec![0u8; bytes];
+        match &mut self.source {
+            InputSource::Reader(reader) => {
+                let resume = reader.stream_position()?;
+                let origin = resume - self.position.offset;
+                let gathered = (|| {
+                    for (row, &i) in rows.chunks_exact_mut(stride).zip(indices) {
+                        let offset = record_offset(i);
+                        reader.seek(SeekFrom::Start(origin.saturating_add(offset)))?;
+                        let got = read_full(reader, row)?;
+                        if got < stride {
+                            return Err(corrupt_record(
+                                &self.layout,
+                                offset,
+                                i,
+                                got,
+                                "unexpected end of data",
+                            ));
+                        }
+                    }
+                    Ok(())
+                })();
+                reader.seek(SeekFrom::Start(resume))?;
+                gathered?;
+            }
+            #[cfg(feature = "memmap2")]
+            InputSource::Mmap(mmap) => {
+                for (row, &i) in rows.chunks_exact_mut(stride).zip(indices) {
+                    let offset = record_offset(i);
+                    let record = mmap.get(offset as usize..).unwrap_or_default();
+                    if record.len() < stride {
+                        return Err(corrupt_record(
+                            &self.layout,
+                            offset,
+                            i,
+                            record.len(),
+                            "unexpected end of data",
+                        ));
+                    }
+                    row.copy_from_slice(&record[..stride]);
+                }
+            }
+        }
+        decode_binary(&rows, &self.layout, indices.len(), &mut block)?;
+        self.conform(block)
+    }
+}
+
+/// Where decoding stands in the data section, for locating errors and
+/// reporting progress, and how to treat malformed ascii lines.
+struct DataPos {
+    // File offset of the next binary record or compressed block.
+    offset: u64,
+    // Index of the next point.
+    point: usize,
+    // Next ascii data line, from 1.
+    line: usize,
+    skip_malformed: bool,
+    short_lines: ShortLines,
+    batch_bytes: usize,
+    // Called with the number of points decoded in all.
+    progress: Option<Box<dyn FnMut(usize) + Send>>,
+    // Set to stop decoding.
+    cancel: Option<Arc<AtomicBool>>,
+}
+
+fn check_cancel(cancel: &Option<Arc<AtomicBool>>) -> Result<()> {
+    match cancel {
+        Some(flag) if flag.load(Ordering::Relaxed) => Err(PcdError::Cancelled),
+        _ => Ok(()),
+    }
+}
+
+/// Decode `block.len` points from the current position of `reader`, which
+/// is at `pos`, and advance `pos`. Skipped ascii lines go to `issues`.
+fn decode_stream<R: BufRead>(
+    reader: &mut R,
+    format: DataFormat,
+    layout: &PcdLayout,
+    pos: &mut DataPos,
+    issues: &mut Vec<Issue>,
+    block: &mut PointBlock,
+) -> Result<()> {
+    check_cancel(&pos.cancel)?;
+    let points = block.len;
+    let first = pos.point;
+    let mut report = |n: usize| {
+        if let Some(progress) = pos.progress.as_mut() {
+            progress(first + n);
+        }
+        check_cancel(&pos.cancel)
+    };
+    match format {
+        DataFormat::Binary => {
+            BinaryReader::new(reader, layout, points)
+                .at(pos.offset, first)
+                .batch_bytes(pos.batch_bytes)
+                .progress(&mut report)
+                .decode(block)?;
+            pos.offset += (points * layout.total_size) as u64;
+        }
+        DataFormat::Ascii => {
+            let mut decoder = AsciiReader::new(reader, layout, points)
+                .at_line(pos.line)
+                .skip_malformed(pos.skip_malformed)
+                .short_lines(pos.short_lines)
+                .progress(&mut report);
+            decoder.decode(block)?;
+            pos.line += decoder.lines_read();
+            issues.extend(decoder.skipped_lines().iter().map(|&l| Issue::MalformedLine(l)));
+        }
+        // binary_compressed, and binary_zstd with the zstd feature.
+        compressed => {
+            let codec = Codec::of(compressed).expect("a compressed format");
+            CompressedReader::new(reader, layout, points)
+                .with_codec(codec)
+                .at(pos.offset, first)
+                .decode(block)?;
+            report(points)?;
+        }
+    }
+    pos.point += block.len;
+    Ok(())
 }
 
+/// Offset just past the `DATA` line, if `bytes` contains all of it.
+pub(crate) fn header_end(bytes: &[u8]) -> Option<usize> {
+    let mut pos = 0;
+    while let Some(len) = bytes[pos..].iter().position(|&b| b == b'\n') {
+        let line = String::from_utf8_lossy(&bytes[pos..pos + len]);
+        pos += len + 1;
+        if line.trim_start().starts_with("DATA") {
+            return Some(pos);
+        }
+    }
+    None
+}
+
+/// Parse only the header of a PCD file, gzip-compressed or not. No point
+/// data is read.
+pub fn read_pcd_header<P: AsRef<Path>>(path: P) -> Result<PcdHeader> {
+    Ok(PcdReader::open(path)?.into_header())
+}
+
+/// Read a whole PCD file, gzip-compressed or not.
 pub fn read_pcd_file<P: AsRef<Path>>(path: P) -> Result<PointBlock> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-    let pcd_reader = PcdReader::new(reader)?;
-    pcd_reader.read_all()
+    PcdReader::open(path)?.read_all()
+}
+
+/// Decode a whole PCD held in memory, gzip-compressed or not. Nothing touches
+/// the file system, so this also works on `wasm32-unknown-unknown`, e.g. for
+/// files dropped into a browser.
+pub fn read_pcd_bytes(bytes: &[u8]) -> Result<PointBlock> {
+    Ok(PointCloud::from_bytes(bytes)?.data)
+}
+
+impl PointCloud {
+    /// Like [`read_pcd_file`], keeping the header.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        PcdReader::open(path)?.read_cloud()
+    }
+
+    /// Like [`read_pcd_bytes`], keeping the header.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if is_gzip(&mut Cursor::new(bytes))? {
+            return Self::from_bytes(&gunzip(bytes)?);
+        }
+        let (header, end) = parse_header_bytes(bytes)?;
+        let layout = PcdLayout::from_header(&header)?;
+        let data = decode_slice(&bytes[end..], header.data, &layout, header.points)
+            .map_err(|e| relocate(e, end as u64, 0))?;
+        Ok(Self::new(header, data))
+    }
+}
+
+#[cfg(feature = "flate2")]
+fn gunzip(bytes: &[u8]) -> Result<Vec<u8>> {
+    use std::io::Read;
+    let mut out = Vec::new();
+    GzDecoder::new(bytes).read_to_end(&mut out)?;
+    Ok(out)
+}
+
+#[cfg(not(feature = "flate2"))]
+fn gunzip(_bytes: &[u8]) -> Result<Vec<u8>> {
+    Err(PcdError::UnsupportedDataFormat(
+        "gzip input requires the flate2 feature".to_string(),
+    ))
 }
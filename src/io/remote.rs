@@ -0,0 +1,135 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reading PCDs from object stores (feature `object_store`).
+//!
+//! Works with any [`ObjectStore`]: S3, GCS and Azure are enabled through the
+//! `aws`, `gcp` and `azure` features of the application's own `object_store`
+//! dependency. The header is fetched first with a small range request, then
+//! only the bytes of the data section are requested. Gzip objects are not
+//! supported.
+//!
+//! The header is checked against the object's size, and nothing larger than
+//! [`DEFAULT_MEMORY_LIMIT`] is requested or decoded.
+
+use super::reader::header_end;
+use super::validate::check_header;
+use crate::decoder::decode_slice;
+use crate::error::{PcdError, Result};
+use crate::header::{DataFormat, PcdHeader, parse_header};
+use crate::layout::{DEFAULT_MEMORY_LIMIT, PcdLayout};
+use crate::storage::PointBlock;
+use object_store::ObjectStore;
+use object_store::path::Path;
+use std::io::Cursor;
+
+/// Bytes requested for the header; doubled until the `DATA` line is found.
+const HEADER_PROBE: u64 = 4096;
+
+/// Fetch and parse the header of `location`.
+pub async fn read_object_header(store: &dyn ObjectStore, location: &Path) -> Result<PcdHeader> {
+    Ok(fetch_header(store, location).await?.0)
+}
+
+/// Fetch and decode `location`.
+pub async fn read_object(store: &dyn ObjectStore, location: &Path) -> Result<PointBlock> {
+    let (header, start, size) = fetch_header(store, location).await?;
+    let layout = PcdLayout::from_header(&header)?;
+    let points = header.points;
+    check_header(&header, &layout, Some(size - start))?;
+    let len = layout.block_size(points, DEFAULT_MEMORY_LIMIT)? as u64;
+    let too_large = || PcdError::TooLarge {
+        points,
+        limit: DEFAULT_MEMORY_LIMIT,
+    };
+
+    let data = match header.data {
+        // Within the object, as checked above.
+        DataFormat::Binary => get_range(store, location, start, start + len).await?,
+        DataFormat::Ascii => {
+            if size - start > DEFAULT_MEMORY_LIMIT {
+                return Err(too_large());
+            }
+            get_range(store, location, start, size).await?
+        }
+        // binary_compressed and binary_zstd.
+        _ => {
+            let mut data = get_range(store, location, start, start + 8).await?;
+            let compressed_size = u32::from_le_bytes(data[..4].try_into().unwrap()) as u64;
+            if compressed_size > (size - start).saturating_sub(8) {
+                return Err(PcdError::InvalidDataFormat(format!(
+                    "Compressed block of {} bytes runs past the end of the object",
+                    compressed_size
+                )));
+            }
+            if compressed_size > DEFAULT_MEMORY_LIMIT {
+                return Err(too_large());
+            }
+            let body = get_range(store, location, start + 8, start + 8 + compressed_size).await?;
+            data.extend(body);
+            data
+        }
+    };
+    decode_slice(&data, header.data, &layout, points)
+}
+
+/// Fetch and decode a PCD addressed by URL, e.g. `s3://bucket/scan.pcd`.
+/// Store options such as credentials are taken from the environment.
+pub async fn read_url(url: &str) -> Result<PointBlock> {
+    let url = url::Url::parse(url).map_err(|e| PcdError::Other(format!("Invalid URL: {}", e)))?;
+    let (store, location) = object_store::parse_url(&url).map_err(store_error)?;
+    read_object(store.as_ref(), &location).await
+}
+
+/// Header, data section offset and object size.
+async fn fetch_header(store: &dyn ObjectStore, location: &Path) -> Result<(PcdHeader, u64, u64)> {
+    let size = store.head(location).await.map_err(store_error)?.size;
+    let mut probe = HEADER_PROBE.min(size);
+    loop {
+        let bytes = get_range(store, location, 0, probe).await?;
+        if let Some(end) = header_end(&bytes) {
+            let header = parse_header(&mut Cursor::new(&bytes[..end]))?;
+            return Ok((header, end as u64, size));
+        }
+        if probe == size {
+            // Truncated header, or a DATA line without a newline.
+            let header = parse_header(&mut Cursor::new(&bytes[..]))?;
+            return Ok((header, size, size));
+        }
+        probe = (probe * 2).min(size);
+    }
+}
+
+async fn get_range(
+    store: &dyn ObjectStore,
+    location: &Path,
+    start: u64,
+    end: u64,
+) -> Result<Vec<u8>> {
+    let bytes = store
+        .get_range(location, start..end)
+        .await
+        .map_err(store_error)?;
+    if (bytes.len() as u64) < end - start {
+        return Err(PcdError::BufferTooSmall {
+            expected: (end - start) as usize,
+            got: bytes.len(),
+        });
+    }
+    Ok(bytes.to_vec())
+}
+
+fn store_error(e: object_store::Error) -> PcdError {
+    PcdError::Io(std::io::Error::other(e))
+}
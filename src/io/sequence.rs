@@ -0,0 +1,188 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ordered multi-frame sequences, such as LiDAR logs stored as numbered PCDs.
+//!
+//! File frames are only decoded when loaded. Timestamps are taken from file
+//! names by [`parse_timestamp`]; when every frame has one the sequence is
+//! ordered by time, otherwise by path.
+
+use super::dataset::find_files;
+use super::reader::read_pcd_file;
+use crate::error::Result;
+use crate::storage::PointBlock;
+use std::path::{Path, PathBuf};
+
+/// Where a frame's points come from.
+#[derive(Debug, Clone)]
+pub enum FrameSource {
+    /// A PCD file, read on every load.
+    File(PathBuf),
+    /// A block already in memory, e.g. from a bag or a network stream.
+    Memory(PointBlock),
+}
+
+/// One frame of a [`PcdSequence`].
+#[derive(Debug, Clone)]
+pub struct SequenceFrame {
+    /// Seconds, if known.
+    pub timestamp: Option<f64>,
+    pub source: FrameSource,
+}
+
+impl SequenceFrame {
+    /// Decode the frame. Memory frames are cloned.
+    pub fn load(&self) -> Result<PointBlock> {
+        match &self.source {
+            FrameSource::File(path) => read_pcd_file(path),
+            FrameSource::Memory(block) => Ok(block.clone()),
+        }
+    }
+}
+
+/// An ordered set of frames.
+#[derive(Debug, Clone, Default)]
+pub struct PcdSequence {
+    frames: Vec<SequenceFrame>,
+}
+
+impl PcdSequence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Frames from the files matching `pattern` (see [`crate::io::dataset`]).
+    pub fn from_dir(pattern: &str) -> Result<Self> {
+        Ok(Self::from_paths(find_files(pattern)?))
+    }
+
+    /// Frames from the given files, with timestamps parsed from their names.
+    pub fn from_paths<I, P>(paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut seq = Self::new();
+        for path in paths {
+            let path = path.as_ref();
+            let timestamp = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(parse_timestamp);
+            seq.frames.push(SequenceFrame {
+                timestamp,
+                source: FrameSource::File(path.to_path_buf()),
+            });
+        }
+        seq.sort();
+        seq
+    }
+
+    /// Append an in-memory frame, e.g. one received from a stream.
+    pub fn push(&mut self, timestamp: Option<f64>, block: PointBlock) {
+        self.frames.push(SequenceFrame {
+            timestamp,
+            source: FrameSource::Memory(block),
+        });
+    }
+
+    /// Order by timestamp when every frame has one. The sort is stable, so
+    /// frames without timestamps keep their insertion order.
+    pub fn sort(&mut self) {
+        if self.frames.iter().all(|f| f.timestamp.is_some()) {
+            self.frames
+                .sort_by(|a, b| a.timestamp.unwrap().total_cmp(&b.timestamp.unwrap()));
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn frames(&self) -> &[SequenceFrame] {
+        &self.frames
+    }
+
+    pub fn get(&self, index: usize) -> Option<&SequenceFrame> {
+        self.frames.get(index)
+    }
+
+    /// Decode frame `index`, or `None` if out of range.
+    pub fn load(&self, index: usize) -> Option<Result<PointBlock>> {
+        self.frames.get(index).map(SequenceFrame::load)
+    }
+
+    /// Time from the first to the last frame, if both are timestamped.
+    pub fn duration(&self) -> Option<f64> {
+        Some(self.frames.last()?.timestamp? - self.frames.first()?.timestamp?)
+    }
+
+    /// Index of the frame closest in time to `t`. Frames without timestamps
+    /// are never returned.
+    pub fn nearest(&self, t: f64) -> Option<usize> {
+        self.frames
+            .iter()
+            .enumerate()
+            .filter_map(|(i, f)| f.timestamp.map(|ts| (i, (ts - t).abs())))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    /// Decode the frames one at a time, in order.
+    pub fn iter(&self) -> impl Iterator<Item = Result<(Option<f64>, PointBlock)>> + '_ {
+        self.frames
+            .iter()
+            .map(|f| f.load().map(|block| (f.timestamp, block)))
+    }
+}
+
+/// Timestamp in seconds from a file name such as `1541234567.123456.pcd`,
+/// `lidar_1541234567123.pcd` or `1541234567123456789.pcd`.
+///
+/// The last run of digits (with an optional fraction) is used. Integers of
+/// 10, 13, 16 or 19 digits are read as seconds, milliseconds, microseconds or
+/// nanoseconds; other integers are frame numbers and give `None`.
+pub fn parse_timestamp(name: &str) -> Option<f64> {
+    let base = name.strip_suffix(".gz").unwrap_or(name);
+    let base = base.strip_suffix(".pcd").unwrap_or(base);
+
+    let bytes = base.as_bytes();
+    let end = bytes.iter().rposition(|b| b.is_ascii_digit())? + 1;
+    let mut start = end;
+    while start > 0 && (bytes[start - 1].is_ascii_digit() || bytes[start - 1] == b'.') {
+        start -= 1;
+    }
+    let number = base[start..end].trim_start_matches('.');
+
+    match number.split_once('.') {
+        Some((int, frac)) if int.len() == 10 && !frac.contains('.') => number.parse().ok(),
+        Some(_) => None,
+        None if matches!(number.len(), 10 | 13 | 16 | 19) => {
+            // Split off whole seconds so nanosecond stamps keep their precision.
+            let secs: f64 = number[..10].parse().ok()?;
+            let frac = &number[10..];
+            let sub: f64 = if frac.is_empty() {
+                0.0
+            } else {
+                frac.parse().ok()?
+            };
+            Some(secs + sub / 10f64.powi(frac.len() as i32))
+        }
+        None => None,
+    }
+}
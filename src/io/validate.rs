@@ -0,0 +1,420 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Header/data consistency checks and repair of PCD files (not gzipped).
+//!
+//! [`validate_pcd`] compares `POINTS` with `WIDTH * HEIGHT` and with what
+//! the data section holds: the byte length for `binary`, well-formed lines
+//! for `ascii`, and the block sizes for compressed data. The data is not
+//! decoded, so this is fast on large files.
+//!
+//! [`repair_pcd`] writes a copy with the header fixed to the points actually
+//! present and any trailing garbage and malformed ascii lines dropped. When
+//! `POINTS` and `WIDTH * HEIGHT` disagree, the one matching the data wins.
+
+use super::reader::is_gzip;
+use super::writer::PcdWriter;
+use crate::error::{PcdError, Result};
+use crate::header::{DataFormat, PcdHeader, parse_header};
+use crate::layout::PcdLayout;
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// A problem found by [`validate_pcd`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// `POINTS` differs from `WIDTH * HEIGHT`.
+    PointsMismatch {
+        points: usize,
+        width: u32,
+        height: u32,
+    },
+    /// The data section holds a different number of points than `POINTS`.
+    PointCount { declared: usize, available: usize },
+    /// Bytes after the last point.
+    TrailingBytes(u64),
+    /// An ascii data line (1-based) without one number per field element,
+    /// which a repair drops. Blank lines at the end are ignored.
+    MalformedLine(usize),
+    /// The sizes of a compressed block do not fit the layout or the file.
+    CompressedSize {
+        compressed: usize,
+        uncompressed: usize,
+        available: u64,
+    },
+    /// A field of the reader's target schema that the file lacks, returned
+    /// with default values; see [`PcdReader::fill_missing`](super::PcdReader::fill_missing).
+    FilledField(String),
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::PointsMismatch {
+                points,
+                width,
+                height,
+            } => write!(
+                f,
+                "POINTS {} differs from WIDTH x HEIGHT = {} x {}",
+                points, width, height
+            ),
+            Issue::PointCount {
+                declared,
+                available,
+            } => write!(
+                f,
+                "data holds {} points, POINTS says {}",
+                available, declared
+            ),
+            Issue::TrailingBytes(n) => write!(f, "{} bytes after the last point", n),
+            Issue::MalformedLine(line) => write!(f, "malformed ascii data line {}", line),
+            Issue::CompressedSize {
+                compressed,
+                uncompressed,
+                available,
+            } => write!(
+                f,
+                "compressed block of {} bytes ({} uncompressed) does not fit the layout or \
+                 the {} data bytes",
+                compressed, uncompressed, available
+            ),
+            Issue::FilledField(name) => {
+                write!(f, "field {} is missing, filled with defaults", name)
+            }
+        }
+    }
+}
+
+/// Result of [`validate_pcd`].
+#[derive(Debug, Clone)]
+pub struct Validation {
+    pub header: PcdHeader,
+    pub issues: Vec<Issue>,
+    /// Points the data section holds, if it could be determined.
+    pub available: Option<usize>,
+    data_start: u64,
+    // Data bytes kept by a repair.
+    keep_bytes: u64,
+}
+
+impl Validation {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// The header a repair writes. Fails if the number of points in the data
+    /// is unknown, or too large for `WIDTH`.
+    pub fn repaired_header(&self) -> Result<PcdHeader> {
+        let available = self.available.ok_or_else(|| {
+            PcdError::InvalidDataFormat(format!(
+                "Cannot repair: {}",
+                self.issues
+                    .iter()
+                    .map(|i| i.to_string())
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ))
+        })?;
+        let mut header = self.header.clone();
+        // The compressed block always decodes to all of its points.
+        header.points = if header.data.is_compressed() {
+            available
+        } else {
+            choose_points(&self.header, available)
+        };
+        if header.width as usize * header.height as usize != header.points {
+            header.width = u32::try_from(header.points).map_err(|_| {
+                PcdError::InvalidDataFormat(format!(
+                    "Cannot repair: {} points do not fit in WIDTH",
+                    header.points
+                ))
+            })?;
+            header.height = 1;
+        }
+        Ok(header)
+    }
+}
+
+/// `POINTS` or `WIDTH * HEIGHT`, preferring an exact match with the data,
+/// then the larger that fits, then whatever the data holds.
+fn choose_points(header: &PcdHeader, available: usize) -> usize {
+    let area = header.width as usize * header.height as usize;
+    let candidates = [header.points, area];
+    if let Some(&exact) = candidates.iter().find(|&&c| c == available) {
+        return exact;
+    }
+    candidates
+        .into_iter()
+        .filter(|&c| c <= available && c > 0)
+        .max()
+        .unwrap_or(available)
+}
+
+/// Problems visible from the header and, when known, the data length,
+/// without reading the data. A `binary` or compressed data section too short
+/// for `POINTS` is an error; the rest are returned.
+pub(crate) fn check_header(
+    header: &PcdHeader,
+    layout: &PcdLayout,
+    data_len: Option<u64>,
+) -> Result<Vec<Issue>> {
+    let mut issues = Vec::new();
+    if header.width as usize * header.height as usize != header.points {
+        issues.push(Issue::PointsMismatch {
+            points: header.points,
+            width: header.width,
+            height: header.height,
+        });
+    }
+    let Some(data_len) = data_len else {
+        return Ok(issues);
+    };
+    match header.data {
+        DataFormat::Binary => {
+            let step = layout.total_size as u64;
+            let needed = (header.points as u64).saturating_mul(step);
+            if data_len < needed {
+                return Err(PcdError::InvalidDataFormat(format!(
+                    "Data section holds {} points, POINTS says {}",
+                    data_len.checked_div(step).unwrap_or(0),
+                    header.points
+                )));
+            }
+            if data_len > needed {
+                issues.push(Issue::TrailingBytes(data_len - needed));
+            }
+        }
+        // Lines are only counted by `validate_pcd`.
+        DataFormat::Ascii => {}
+        // binary_compressed and binary_zstd.
+        _ => {
+            if data_len < 8 && header.points > 0 {
+                return Err(PcdError::InvalidDataFormat(format!(
+                    "Data section of {} bytes has no compressed block",
+                    data_len
+                )));
+            }
+        }
+    }
+    Ok(issues)
+}
+
+/// Check the header of the PCD at `path` against its data section.
+pub fn validate_pcd<P: AsRef<Path>>(path: P) -> Result<Validation> {
+    let mut reader = BufReader::new(File::open(path)?);
+    if is_gzip(&mut reader)? {
+        return Err(PcdError::UnsupportedDataFormat(
+            "Validating gzip-compressed PCDs; decompress first".to_string(),
+        ));
+    }
+    let header = parse_header(&mut reader)?;
+    let layout = PcdLayout::from_header(&header)?;
+    let data_start = reader.stream_position()?;
+    let data_len = reader.get_ref().metadata()?.len() - data_start;
+    let step = layout.total_size as u64;
+
+    let mut issues = Vec::new();
+    if header.width as usize * header.height as usize != header.points {
+        issues.push(Issue::PointsMismatch {
+            points: header.points,
+            width: header.width,
+            height: header.height,
+        });
+    }
+
+    let (available, payload) = match header.data {
+        DataFormat::Binary => {
+            let available = data_len.checked_div(step).unwrap_or(0) as usize;
+            (Some(available), None)
+        }
+        DataFormat::Ascii => {
+            let values = header.counts.iter().sum::<usize>();
+            let lines = scan_ascii(&mut reader, values, None, None)?;
+            issues.extend(lines.malformed.into_iter().map(Issue::MalformedLine));
+            (Some(lines.points), Some(lines.end))
+        }
+        // binary_compressed and binary_zstd.
+        _ => {
+            if data_len < 8 {
+                issues.push(Issue::PointCount {
+                    declared: header.points,
+                    available: 0,
+                });
+                (None, None)
+            } else {
+                let compressed = reader.read_u32::<LittleEndian>()? as usize;
+                let uncompressed = reader.read_u32::<LittleEndian>()? as usize;
+                let block = 8 + compressed as u64;
+                if block > data_len || step == 0 || !(uncompressed as u64).is_multiple_of(step) {
+                    issues.push(Issue::CompressedSize {
+                        compressed,
+                        uncompressed,
+                        available: data_len,
+                    });
+                    (None, None)
+                } else {
+                    (Some(uncompressed / step as usize), Some(block))
+                }
+            }
+        }
+    };
+
+    let mut validation = Validation {
+        header,
+        issues,
+        available,
+        data_start,
+        keep_bytes: data_len,
+    };
+    let Some(available) = available else {
+        return Ok(validation);
+    };
+    let header = &validation.header;
+    if available != header.points {
+        validation.issues.push(Issue::PointCount {
+            declared: header.points,
+            available,
+        });
+    }
+
+    // Bytes a repair keeps, and whatever data follows them. Ascii data ends
+    // with its last non-blank line.
+    let points = choose_points(header, available);
+    let (keep_bytes, data_end) = match header.data {
+        DataFormat::Binary => (points as u64 * step, data_len),
+        DataFormat::Ascii => {
+            let end = payload.unwrap_or(0);
+            if points < available {
+                reader.seek(SeekFrom::Start(data_start))?;
+                let values = header.counts.iter().sum::<usize>();
+                let kept = scan_ascii(&mut reader, values, Some(points), None)?;
+                (kept.end, end)
+            } else {
+                (end, end)
+            }
+        }
+        // Only sizes of the single block are known; a changed point count
+        // cannot be cut out of it.
+        _ => (payload.unwrap_or(data_len), data_len),
+    };
+    let trailing = data_end - keep_bytes;
+    if trailing > 0 {
+        validation.issues.push(Issue::TrailingBytes(trailing));
+    }
+    validation.keep_bytes = keep_bytes;
+    Ok(validation)
+}
+
+/// Lines of an ascii data section, from [`scan_ascii`].
+struct AsciiLines {
+    /// Lines of exactly the expected number of values.
+    points: usize,
+    /// Numbers (1-based) of the other lines, but for blank lines at the end.
+    malformed: Vec<usize>,
+    /// Bytes up to the end of the last non-blank line read.
+    end: u64,
+}
+
+/// Scan ascii lines from the current position, stopping after `limit` lines
+/// of exactly `values` numbers. Those lines are copied to `out` if given.
+fn scan_ascii<R: BufRead>(
+    reader: &mut R,
+    values: usize,
+    limit: Option<usize>,
+    mut out: Option<&mut dyn Write>,
+) -> Result<AsciiLines> {
+    let mut lines = AsciiLines {
+        points: 0,
+        malformed: Vec::new(),
+        end: 0,
+    };
+    let mut line = Vec::new();
+    let (mut number, mut read) = (0, 0);
+    // Blank lines since the last other one, malformed if more data follows.
+    let mut blank = Vec::new();
+    while limit.is_none_or(|limit| lines.points < limit) {
+        line.clear();
+        let n = reader.read_until(b'\n', &mut line)?;
+        if n == 0 {
+            break;
+        }
+        number += 1;
+        read += n as u64;
+        let text = std::str::from_utf8(&line).ok();
+        if text.is_some_and(|text| text.trim().is_empty()) {
+            blank.push(number);
+            continue;
+        }
+        lines.malformed.append(&mut blank);
+        lines.end = read;
+        let numeric = text.and_then(|text| {
+            text.split_whitespace()
+                .take(values + 1)
+                .try_fold(0, |n, t| t.parse::<f64>().is_ok().then_some(n + 1))
+        });
+        match (text, numeric == Some(values)) {
+            (Some(text), true) => {
+                lines.points += 1;
+                if let Some(out) = out.as_mut() {
+                    writeln!(out, "{}", text.trim_end())?;
+                }
+            }
+            _ => lines.malformed.push(number),
+        }
+    }
+    Ok(lines)
+}
+
+/// Write a repaired copy of the PCD at `src` to `dst`, which must be a
+/// different file. Returns the validation of `src`; valid files are copied
+/// with a rewritten header. Fails before writing anything if `dst` resolves
+/// to `src`, which would truncate it; repair in place through a temporary
+/// file instead.
+pub fn repair_pcd<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> Result<Validation> {
+    if is_same_file(src.as_ref(), dst.as_ref()) {
+        return Err(PcdError::InvalidDataFormat(format!(
+            "Cannot repair {} onto itself",
+            src.as_ref().display()
+        )));
+    }
+    let validation = validate_pcd(&src)?;
+    let header = validation.repaired_header()?;
+
+    let mut input = BufReader::new(File::open(src)?);
+    input.seek(SeekFrom::Start(validation.data_start))?;
+    let mut output = BufWriter::new(File::create(dst)?);
+    PcdWriter::new(&mut output).write_header(&header)?;
+    if header.data == DataFormat::Ascii {
+        // Only the well-formed lines, up to the repaired point count.
+        let values = header.counts.iter().sum::<usize>();
+        scan_ascii(&mut input, values, Some(header.points), Some(&mut output))?;
+    } else {
+        std::io::copy(&mut input.take(validation.keep_bytes), &mut output)?;
+    }
+    output.flush()?;
+    Ok(validation)
+}
+
+/// Whether `a` and `b` name the same existing file, through links and
+/// relative components.
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
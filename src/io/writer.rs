@@ -12,391 +12,499 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::lzf_stream::LzfEncoder;
+use crate::decoder::ascii::PROGRESS_POINTS;
+use crate::decoder::compressed::Codec;
 use crate::error::Result;
 use crate::header::DataFormat;
 use crate::header::PcdHeader;
 // use crate::header::ValueType;
-use crate::error::PcdError;
-use crate::storage::PointBlock;
+use crate::error::{Operation, PcdError};
+use crate::layout::{FieldLayout, PcdLayout};
+use crate::storage::rgb::is_packed_color;
+use crate::storage::{Column, PointBlock, PointCloud};
 use byteorder::{LittleEndian, WriteBytesExt};
-use lzf;
-use std::io::Write;
+#[cfg(feature = "flate2")]
+use flate2::{Compression, write::GzEncoder};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Bytes of binary records assembled before each write.
+const WRITE_BUFFER: usize = 4 << 20;
+/// Bytes of records each rayon task encodes.
+#[cfg(feature = "rayon")]
+const ENCODE_TASK: usize = 256 << 10;
+
+/// zstd level of `binary_zstd` output, the library's default.
+#[cfg(feature = "zstd")]
+const ZSTD_LEVEL: i32 = 3;
+
+/// How the ASCII writer prints `F` fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloatFormat {
+    /// Fixed-point with this many decimals, e.g. `1.250000`.
+    Fixed(usize),
+    /// Scientific with this many decimals, e.g. `1.25e-7`.
+    Scientific(usize),
+    /// The fewest digits that read back to the same value, e.g. `1.25`.
+    Shortest,
+}
+
+impl Default for FloatFormat {
+    fn default() -> Self {
+        FloatFormat::Fixed(6)
+    }
+}
 
 pub struct PcdWriter<W: Write> {
     writer: W,
+    float_format: FloatFormat,
+    field_formats: HashMap<String, FloatFormat>,
+    progress: Option<Box<dyn FnMut(usize, usize) + Send>>,
 }
 
 impl<W: Write> PcdWriter<W> {
     pub fn new(writer: W) -> Self {
-        Self { writer }
-    }
-
-    pub fn write_pcd(&mut self, header: &PcdHeader, data: &PointBlock) -> Result<()> {
-        self.write_header(header)?;
-        match header.data {
-            DataFormat::Binary => self.write_binary(header, data)?,
-            DataFormat::Ascii => self.write_ascii(header, data)?,
-            DataFormat::BinaryCompressed => self.write_compressed_binary(header, data)?,
+        Self {
+            writer,
+            float_format: FloatFormat::default(),
+            field_formats: HashMap::new(),
+            progress: None,
         }
-        Ok(())
     }
 
-    fn write_header(&mut self, header: &PcdHeader) -> Result<()> {
-        writeln!(self.writer, "VERSION {}", header.version)?;
-        writeln!(self.writer, "FIELDS {}", header.fields.join(" "))?;
-
-        let sizes_str: Vec<String> = header.sizes.iter().map(|s| s.to_string()).collect();
-        writeln!(self.writer, "SIZE {}", sizes_str.join(" "))?;
+    /// Set how ASCII output prints float fields without their own format.
+    /// Defaults to six fixed decimals.
+    #[must_use]
+    pub fn float_format(mut self, format: FloatFormat) -> Self {
+        self.float_format = format;
+        self
+    }
 
-        let types_str: Vec<String> = header.types.iter().map(|t| t.to_string()).collect();
-        writeln!(self.writer, "TYPE {}", types_str.join(" "))?;
+    /// Set how ASCII output prints the float field `field`, e.g.
+    /// [`FloatFormat::Shortest`] to keep full precision in timestamps.
+    #[must_use]
+    pub fn field_format(mut self, field: &str, format: FloatFormat) -> Self {
+        self.field_formats.insert(field.to_string(), format);
+        self
+    }
 
-        let counts_str: Vec<String> = header.counts.iter().map(|c| c.to_string()).collect();
-        writeln!(self.writer, "COUNT {}", counts_str.join(" "))?;
+    /// Call `progress` with the points written so far and the points of the
+    /// data being written: after each buffer of binary records, every
+    /// [`PROGRESS_POINTS`] ascii lines, and once for compressed data.
+    #[must_use]
+    pub fn on_progress(mut self, progress: impl FnMut(usize, usize) + Send + 'static) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
 
-        writeln!(self.writer, "WIDTH {}", header.width)?;
-        writeln!(self.writer, "HEIGHT {}", header.height)?;
+    fn report(&mut self, points: usize, total: usize) {
+        if let Some(progress) = self.progress.as_mut() {
+            progress(points, total);
+        }
+    }
 
-        // VIEWPOINT
-        writeln!(
-            self.writer,
-            "VIEWPOINT {} {} {} {} {} {} {}",
-            header.viewpoint[0],
-            header.viewpoint[1],
-            header.viewpoint[2],
-            header.viewpoint[3],
-            header.viewpoint[4],
-            header.viewpoint[5],
-            header.viewpoint[6]
-        )?;
+    /// Recover the underlying writer, e.g. to finish a gzip stream.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
 
-        writeln!(self.writer, "POINTS {}", header.points)?;
+    pub fn write_pcd(&mut self, header: &PcdHeader, data: &PointBlock) -> Result<()> {
+        // Refuse oversized compressed data before writing anything.
+        if header.data.is_compressed() {
+            compressed_input_size(header, &PcdLayout::from_header(header)?)?;
+            header_columns(header, data)?;
+        }
+        self.write_header(header)?;
+        self.write_body(header, data)
+    }
 
+    /// Write the data section for the `header.points` points of `data`.
+    pub(crate) fn write_body(&mut self, header: &PcdHeader, data: &PointBlock) -> Result<()> {
         match header.data {
-            DataFormat::Ascii => writeln!(self.writer, "DATA ascii")?,
-            DataFormat::Binary => writeln!(self.writer, "DATA binary")?,
-            DataFormat::BinaryCompressed => writeln!(self.writer, "DATA binary_compressed")?,
+            DataFormat::Binary => self.write_binary(header, data)?,
+            DataFormat::Ascii => self.write_ascii(header, data)?,
+            DataFormat::BinaryCompressed => {
+                self.write_compressed_binary(header, data, Codec::Lzf)?
+            }
+            #[cfg(feature = "zstd")]
+            DataFormat::BinaryZstd => self.write_compressed_binary(header, data, Codec::Zstd)?,
         }
+        Ok(())
+    }
 
+    pub(crate) fn write_header(&mut self, header: &PcdHeader) -> Result<()> {
+        write!(self.writer, "{}", header)?;
         Ok(())
     }
 
     fn write_binary(&mut self, header: &PcdHeader, data: &PointBlock) -> Result<()> {
-        // Optimization: Collect column references once
-        let mut columns = Vec::with_capacity(header.fields.len());
-        for name in &header.fields {
-            columns.push(
-                data.get_column(name).ok_or_else(|| {
-                    PcdError::InvalidDataFormat(format!("Missing column {}", name))
-                })?,
-            );
+        let layout = PcdLayout::from_header(header)?;
+        let columns = header_columns(header, data)?;
+        let stride = layout.total_size;
+        if stride == 0 {
+            return Ok(());
         }
 
-        // Loop points, then fields (AoS)
-        for i in 0..header.points {
-            for (field_idx, _name) in header.fields.iter().enumerate() {
-                let col = columns[field_idx];
-                let count = header.counts[field_idx];
-                let start = i * count;
-
-                match header.types[field_idx] {
-                    'F' => {
-                        // Check sizes: 4 bytes -> F32, 8 bytes -> F64
-                        match header.sizes[field_idx] {
-                            4 => {
-                                let vec = col.as_f32().ok_or_else(|| PcdError::LayoutMismatch {
-                                    expected: 0,
-                                    got: 0,
-                                })?; // Todo better error
-                                for k in 0..count {
-                                    self.writer.write_f32::<LittleEndian>(vec[start + k])?;
-                                }
-                            }
-                            8 => {
-                                let vec = col.as_f64().ok_or_else(|| PcdError::LayoutMismatch {
-                                    expected: 0,
-                                    got: 0,
-                                })?;
-                                for k in 0..count {
-                                    self.writer.write_f64::<LittleEndian>(vec[start + k])?;
-                                }
-                            }
-                            _ => {
-                                return Err(PcdError::UnsupportedType(format!(
-                                    "F{}",
-                                    header.sizes[field_idx]
-                                )));
-                            }
-                        }
-                    }
-                    'U' => match header.sizes[field_idx] {
-                        1 => {
-                            let vec = col.as_u8().ok_or(PcdError::LayoutMismatch {
-                                expected: 0,
-                                got: 0,
-                            })?;
-                            for k in 0..count {
-                                self.writer.write_u8(vec[start + k])?;
-                            }
-                        }
-                        2 => {
-                            let vec = col.as_u16().ok_or(PcdError::LayoutMismatch {
-                                expected: 0,
-                                got: 0,
-                            })?;
-                            for k in 0..count {
-                                self.writer.write_u16::<LittleEndian>(vec[start + k])?;
-                            }
-                        }
-                        4 => {
-                            let vec = col.as_u32().ok_or(PcdError::LayoutMismatch {
-                                expected: 0,
-                                got: 0,
-                            })?;
-                            for k in 0..count {
-                                self.writer.write_u32::<LittleEndian>(vec[start + k])?;
-                            }
-                        }
-                        _ => {
-                            return Err(PcdError::UnsupportedType(format!(
-                                "U{}",
-                                header.sizes[field_idx]
-                            )));
-                        }
-                    },
-                    'I' => match header.sizes[field_idx] {
-                        1 => {
-                            let vec = col.as_i8().ok_or(PcdError::LayoutMismatch {
-                                expected: 0,
-                                got: 0,
-                            })?;
-                            for k in 0..count {
-                                self.writer.write_i8(vec[start + k])?;
-                            }
-                        }
-                        2 => {
-                            let vec = col.as_i16().ok_or(PcdError::LayoutMismatch {
-                                expected: 0,
-                                got: 0,
-                            })?;
-                            for k in 0..count {
-                                self.writer.write_i16::<LittleEndian>(vec[start + k])?;
-                            }
-                        }
-                        4 => {
-                            let vec = col.as_i32().ok_or(PcdError::LayoutMismatch {
-                                expected: 0,
-                                got: 0,
-                            })?;
-                            for k in 0..count {
-                                self.writer.write_i32::<LittleEndian>(vec[start + k])?;
-                            }
-                        }
-                        _ => {
-                            return Err(PcdError::UnsupportedType(format!(
-                                "I{}",
-                                header.sizes[field_idx]
-                            )));
-                        }
-                    },
-                    _ => {
-                        return Err(PcdError::UnsupportedType(
-                            header.types[field_idx].to_string(),
-                        ));
-                    }
-                }
-            }
+        // Rows are assembled column by column into one buffer, then written
+        // in a single call. With rayon, parts of the buffer are encoded in
+        // parallel.
+        let rows = (WRITE_BUFFER / stride).max(1);
+        let mut buffer = vec![0u8; rows.min(header.points) * stride];
+        let mut first = 0;
+        while first < header.points {
+            let n = rows.min(header.points - first);
+            let chunk = &mut buffer[..n * stride];
+            #[cfg(feature = "rayon")]
+            encode_records_par(&columns, &layout, first, chunk);
+            #[cfg(not(feature = "rayon"))]
+            encode_records(&columns, &layout, first, chunk);
+            self.writer.write_all(chunk)?;
+            first += n;
+            self.report(first, header.points);
         }
         Ok(())
     }
 
     fn write_ascii(&mut self, header: &PcdHeader, data: &PointBlock) -> Result<()> {
-        // Optimization: Collect column references once
-        let mut columns = Vec::with_capacity(header.fields.len());
-        for name in &header.fields {
-            columns.push(
-                data.get_column(name).ok_or_else(|| {
-                    PcdError::InvalidDataFormat(format!("Missing column {}", name))
-                })?,
-            );
-        }
+        let columns = header_columns(header, data)?;
+        let formats: Vec<FloatFormat> = header
+            .fields
+            .iter()
+            .map(|f| *self.field_formats.get(f).unwrap_or(&self.float_format))
+            .collect();
+        // Packed colors are written as the integer of their bits, as PCL does.
+        let packed: Vec<bool> = header.fields.iter().map(|f| is_packed_color(f)).collect();
 
+        // Each line is formatted into one reused buffer and written whole.
+        let mut line = Vec::new();
+        let mut ints = itoa::Buffer::new();
         for i in 0..header.points {
-            let mut line_tokens = Vec::with_capacity(header.fields.len());
-            for (field_idx, _name) in header.fields.iter().enumerate() {
-                let col = columns[field_idx];
-                let count = header.counts[field_idx];
-                let start = i * count;
-
-                match header.types[field_idx] {
-                    'F' => match header.sizes[field_idx] {
-                        4 => {
-                            let vec = col.as_f32().ok_or(PcdError::LayoutMismatch {
-                                expected: 0,
-                                got: 0,
-                            })?;
-                            for k in 0..count {
-                                line_tokens.push(format!("{:.6}", vec[start + k]));
-                            }
-                        }
-                        8 => {
-                            let vec = col.as_f64().ok_or(PcdError::LayoutMismatch {
-                                expected: 0,
-                                got: 0,
-                            })?;
-                            for k in 0..count {
-                                line_tokens.push(format!("{:.6}", vec[start + k]));
-                            }
-                        }
-                        _ => {}
-                    },
-                    'U' => match header.sizes[field_idx] {
-                        1 => {
-                            let vec = col.as_u8().ok_or(PcdError::LayoutMismatch {
-                                expected: 0,
-                                got: 0,
-                            })?;
-                            for k in 0..count {
-                                line_tokens.push(format!("{}", vec[start + k]));
-                            }
-                        }
-                        2 => {
-                            let vec = col.as_u16().ok_or(PcdError::LayoutMismatch {
-                                expected: 0,
-                                got: 0,
-                            })?;
-                            for k in 0..count {
-                                line_tokens.push(format!("{}", vec[start + k]));
-                            }
-                        }
-                        4 => {
-                            let vec = col.as_u32().ok_or(PcdError::LayoutMismatch {
-                                expected: 0,
-                                got: 0,
-                            })?;
-                            for k in 0..count {
-                                line_tokens.push(format!("{}", vec[start + k]));
-                            }
-                        }
-                        _ => {}
-                    },
-                    'I' => match header.sizes[field_idx] {
-                        1 => {
-                            let vec = col.as_i8().ok_or(PcdError::LayoutMismatch {
-                                expected: 0,
-                                got: 0,
-                            })?;
-                            for k in 0..count {
-                                line_tokens.push(format!("{}", vec[start + k]));
-                            }
-                        }
-                        2 => {
-                            let vec = col.as_i16().ok_or(PcdError::LayoutMismatch {
-                                expected: 0,
-                                got: 0,
-                            })?;
-                            for k in 0..count {
-                                line_tokens.push(format!("{}", vec[start + k]));
-                            }
-                        }
-                        4 => {
-                            let vec = col.as_i32().ok_or(PcdError::LayoutMismatch {
-                                expected: 0,
-                                got: 0,
-                            })?;
-                            for k in 0..count {
-                                line_tokens.push(format!("{}", vec[start + k]));
-                            }
+            line.clear();
+            for (((col, &count), &format), &packed) in
+                columns.iter().zip(&header.counts).zip(&formats).zip(&packed)
+            {
+                for idx in i * count..(i + 1) * count {
+                    if !line.is_empty() {
+                        line.push(b' ');
+                    }
+                    match col {
+                        Column::F32(v) if packed => {
+                            line.extend_from_slice(ints.format(v[idx].to_bits()).as_bytes())
                         }
-                        _ => {}
-                    },
-                    _ => {}
+                        _ => push_value(col, idx, format, &mut line),
+                    }
                 }
             }
-            writeln!(self.writer, "{}", line_tokens.join(" "))?;
+            line.push(b'\n');
+            self.writer.write_all(&line)?;
+            if (i + 1).is_multiple_of(PROGRESS_POINTS) {
+                self.report(i + 1, header.points);
+            }
+        }
+        if !header.points.is_multiple_of(PROGRESS_POINTS) {
+            self.report(header.points, header.points);
         }
         Ok(())
     }
-    fn write_compressed_binary(&mut self, header: &PcdHeader, data: &PointBlock) -> Result<()> {
-        let mut uncompressed_data = Vec::new();
-
-        // Binary Compressed is SoA in the buffer
-        for (field_idx, name) in header.fields.iter().enumerate() {
-            let col = data
-                .get_column(name)
-                .ok_or_else(|| PcdError::InvalidDataFormat(format!("Missing column {}", name)))?;
-            let _count = header.counts[field_idx];
-
-            match header.types[field_idx] {
-                'F' => {
-                    if header.sizes[field_idx] == 4 {
-                        let vec = col.as_f32().unwrap();
-                        for val in vec {
-                            uncompressed_data.write_f32::<LittleEndian>(*val)?;
-                        }
-                    } else {
-                        let vec = col.as_f64().unwrap();
-                        for val in vec {
-                            uncompressed_data.write_f64::<LittleEndian>(*val)?;
-                        }
-                    }
+    fn write_compressed_binary(
+        &mut self,
+        header: &PcdHeader,
+        data: &PointBlock,
+        codec: Codec,
+    ) -> Result<()> {
+        let layout = PcdLayout::from_header(header)?;
+        let uncompressed_size = compressed_input_size(header, &layout)?;
+        let columns = header_columns(header, data)?;
+
+        // Binary Compressed is SoA: each field is transposed into one reused
+        // buffer and fed to the compressor, so only one field is held
+        // uncompressed at a time.
+        let mut field_data = Vec::new();
+        let compressed = match codec {
+            Codec::Lzf => {
+                let mut encoder = LzfEncoder::new();
+                for (col, field) in columns.iter().zip(&layout.fields) {
+                    encode_field(col, field, header.points, &mut field_data);
+                    encoder.write(&field_data);
                 }
-                'U' => match header.sizes[field_idx] {
-                    1 => uncompressed_data.write_all(col.as_u8().unwrap())?,
-                    2 => {
-                        let vec = col.as_u16().unwrap();
-                        for val in vec {
-                            uncompressed_data.write_u16::<LittleEndian>(*val)?;
-                        }
-                    }
-                    4 => {
-                        let vec = col.as_u32().unwrap();
-                        for val in vec {
-                            uncompressed_data.write_u32::<LittleEndian>(*val)?;
-                        }
-                    }
-                    _ => {}
-                },
-                'I' => match header.sizes[field_idx] {
-                    1 => {
-                        let vec = col.as_i8().unwrap();
-                        for val in vec {
-                            uncompressed_data.write_i8(*val)?;
-                        }
-                    }
-                    2 => {
-                        let vec = col.as_i16().unwrap();
-                        for val in vec {
-                            uncompressed_data.write_i16::<LittleEndian>(*val)?;
-                        }
-                    }
-                    4 => {
-                        let vec = col.as_i32().unwrap();
-                        for val in vec {
-                            uncompressed_data.write_i32::<LittleEndian>(*val)?;
-                        }
-                    }
-                    _ => {}
-                },
-                _ => {}
+                encoder.finish()
             }
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => {
+                let mut encoder = zstd::stream::Encoder::new(Vec::new(), ZSTD_LEVEL)?;
+                for (col, field) in columns.iter().zip(&layout.fields) {
+                    encode_field(col, field, header.points, &mut field_data);
+                    encoder.write_all(&field_data)?;
+                }
+                encoder.finish()?
+            }
+        };
+
+        // Incompressible data is stored as is, which readers recognize by
+        // equal sizes.
+        if compressed.len() >= uncompressed_size {
+            self.writer
+                .write_u32::<LittleEndian>(uncompressed_size as u32)?;
+            self.writer
+                .write_u32::<LittleEndian>(uncompressed_size as u32)?;
+            for (col, field) in columns.iter().zip(&layout.fields) {
+                encode_field(col, field, header.points, &mut field_data);
+                self.writer.write_all(&field_data)?;
+            }
+        } else {
+            self.writer
+                .write_u32::<LittleEndian>(compressed.len() as u32)?;
+            self.writer
+                .write_u32::<LittleEndian>(uncompressed_size as u32)?;
+            self.writer.write_all(&compressed)?;
+        }
+        self.report(header.points, header.points);
+        Ok(())
+    }
+}
+
+/// Writes a binary or ASCII PCD chunk by chunk, so clouds larger than memory
+/// can be produced. The header, including its point count, is written up
+/// front; [`finish`](Self::finish) checks that exactly that many points
+/// followed. `binary_compressed` is column-major and cannot be streamed.
+pub struct PcdStreamWriter<W: Write> {
+    writer: PcdWriter<W>,
+    header: PcdHeader,
+    points: usize,
+    progress: Option<Box<dyn FnMut(usize, usize) + Send>>,
+}
+
+impl<W: Write> PcdStreamWriter<W> {
+    /// Write `header` and start the data section.
+    pub fn new(writer: W, header: &PcdHeader) -> Result<Self> {
+        if !matches!(header.data, DataFormat::Binary | DataFormat::Ascii) {
+            return Err(PcdError::UnsupportedDataFormat(format!(
+                "Streaming {:?} data",
+                header.data
+            )));
         }
+        let mut writer = PcdWriter::new(writer);
+        writer.write_header(header)?;
+        Ok(Self {
+            writer,
+            header: header.clone(),
+            points: 0,
+            progress: None,
+        })
+    }
 
-        let uncompressed_size = uncompressed_data.len();
-        let compressed_result = lzf::compress(&uncompressed_data);
+    /// See [`PcdWriter::float_format`].
+    #[must_use]
+    pub fn float_format(mut self, format: FloatFormat) -> Self {
+        self.writer = self.writer.float_format(format);
+        self
+    }
 
-        let (final_compressed_size, final_data) = match compressed_result {
-            Ok(data) => (data.len(), data),
-            Err(lzf::LzfError::NoCompressionPossible) => (uncompressed_size, uncompressed_data),
-            Err(e) => return Err(PcdError::Other(format!("Compression failed: {:?}", e))),
-        };
+    /// See [`PcdWriter::field_format`].
+    #[must_use]
+    pub fn field_format(mut self, field: &str, format: FloatFormat) -> Self {
+        self.writer = self.writer.field_format(field, format);
+        self
+    }
 
-        self.writer
-            .write_u32::<LittleEndian>(final_compressed_size as u32)?;
-        self.writer
-            .write_u32::<LittleEndian>(uncompressed_size as u32)?;
-        self.writer.write_all(&final_data)?;
+    /// Call `progress` with the points written so far and the points the
+    /// header declares after each chunk.
+    #[must_use]
+    pub fn on_progress(mut self, progress: impl FnMut(usize, usize) + Send + 'static) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
 
+    /// Append the points of `data`.
+    pub fn write_chunk(&mut self, data: &PointBlock) -> Result<()> {
+        if self.points + data.len > self.header.points {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Header declares {} points but {} were written",
+                self.header.points,
+                self.points + data.len
+            )));
+        }
+        let mut chunk = self.header.clone();
+        chunk.width = data.len as u32;
+        chunk.height = 1;
+        chunk.points = data.len;
+        self.writer.write_body(&chunk, data)?;
+        self.points += data.len;
+        if let Some(progress) = self.progress.as_mut() {
+            progress(self.points, self.header.points);
+        }
         Ok(())
     }
+
+    /// Points written so far.
+    pub fn points_written(&self) -> usize {
+        self.points
+    }
+
+    /// Check the declared number of points was written, flush, and return
+    /// the underlying writer.
+    pub fn finish(self) -> Result<W> {
+        if self.points != self.header.points {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Header declares {} points but {} were written",
+                self.header.points, self.points
+            )));
+        }
+        let mut writer = self.writer.into_inner();
+        writer.flush()?;
+        Ok(writer)
+    }
+}
+
+#[cfg(feature = "flate2")]
+impl<W: Write> PcdWriter<GzEncoder<W>> {
+    /// Writer producing gzip-compressed output (`.pcd.gz`) at `level` (0-9).
+    /// Call `into_inner().finish()` after the last write.
+    pub fn gzip(writer: W, level: u32) -> Self {
+        Self::new(GzEncoder::new(writer, Compression::new(level)))
+    }
+}
+
+/// Write a PCD file. Paths ending in `.gz` are gzip-compressed, which
+/// requires the `flate2` feature.
+pub fn write_pcd_file<P: AsRef<Path>>(
+    path: P,
+    header: &PcdHeader,
+    data: &PointBlock,
+) -> Result<()> {
+    let path = path.as_ref();
+    let file = BufWriter::new(File::create(path)?);
+    if path.extension().is_some_and(|e| e == "gz") {
+        #[cfg(feature = "flate2")]
+        {
+            let mut writer = PcdWriter::gzip(file, 6);
+            writer.write_pcd(header, data)?;
+            writer.into_inner().finish()?.flush()?;
+            return Ok(());
+        }
+        #[cfg(not(feature = "flate2"))]
+        return Err(PcdError::UnsupportedDataFormat(
+            "gzip output requires the flate2 feature".to_string(),
+        ));
+    }
+    let mut writer = PcdWriter::new(file);
+    writer.write_pcd(header, data)?;
+    writer.into_inner().flush()?;
+    Ok(())
+}
+
+impl PointCloud {
+    /// Write the cloud with its header, see [`write_pcd_file`].
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        write_pcd_file(path, &self.header, &self.data)
+    }
+}
+
+/// Append one element of a column the way the ASCII writer formats it
+/// (floats in `format`, integers verbatim).
+pub(crate) fn push_value(col: &Column, idx: usize, format: FloatFormat, out: &mut Vec<u8>) {
+    let mut ints = itoa::Buffer::new();
+    match col {
+        Column::U8(v) => out.extend_from_slice(ints.format(v[idx]).as_bytes()),
+        Column::U16(v) => out.extend_from_slice(ints.format(v[idx]).as_bytes()),
+        Column::U32(v) => out.extend_from_slice(ints.format(v[idx]).as_bytes()),
+        Column::U64(v) => out.extend_from_slice(ints.format(v[idx]).as_bytes()),
+        Column::I8(v) => out.extend_from_slice(ints.format(v[idx]).as_bytes()),
+        Column::I16(v) => out.extend_from_slice(ints.format(v[idx]).as_bytes()),
+        Column::I32(v) => out.extend_from_slice(ints.format(v[idx]).as_bytes()),
+        Column::I64(v) => out.extend_from_slice(ints.format(v[idx]).as_bytes()),
+        Column::F32(v) => push_float(v[idx], format, out),
+        Column::F64(v) => push_float(v[idx], format, out),
+    }
+}
+
+fn push_float<T: std::fmt::Display + std::fmt::LowerExp>(
+    value: T,
+    format: FloatFormat,
+    out: &mut Vec<u8>,
+) {
+    // Writing to a Vec cannot fail.
+    let _ = match format {
+        FloatFormat::Fixed(decimals) => write!(out, "{:.*}", decimals, value),
+        FloatFormat::Scientific(decimals) => write!(out, "{:.*e}", decimals, value),
+        FloatFormat::Shortest => write!(out, "{}", value),
+    };
+}
+
+/// Encode points `first..` of `columns`, one per field of `layout`, as the
+/// binary records filling `out`, splitting `out` across rayon threads.
+#[cfg(feature = "rayon")]
+fn encode_records_par(columns: &[&Column], layout: &PcdLayout, first: usize, out: &mut [u8]) {
+    let task_rows = (ENCODE_TASK / layout.total_size).max(1);
+    out.par_chunks_mut(task_rows * layout.total_size)
+        .enumerate()
+        .for_each(|(t, task)| encode_records(columns, layout, first + t * task_rows, task));
+}
+
+/// Encode points `first..` of `columns`, one per field of `layout`, as the
+/// binary records filling `out`.
+fn encode_records(columns: &[&Column], layout: &PcdLayout, first: usize, out: &mut [u8]) {
+    for (col, field) in columns.iter().zip(&layout.fields) {
+        col.write_le_strided(first, field.count, out, layout.total_size, field.offset);
+    }
+}
+
+/// Bytes of SoA data a compressed section holds, checked to fit the u32
+/// size fields before it.
+fn compressed_input_size(header: &PcdHeader, layout: &PcdLayout) -> Result<usize> {
+    let size = header.points as u64 * layout.packed_size() as u64;
+    if size > u64::from(u32::MAX) {
+        return Err(PcdError::UnsupportedDataFormat(format!(
+            "binary_compressed data of {} bytes exceeds the 4 GiB limit of its size fields",
+            size
+        )));
+    }
+    Ok(size as usize)
+}
+
+/// The first `points` points of `col`, one `field`, as little-endian bytes
+/// in `out`.
+fn encode_field(col: &Column, field: &FieldLayout, points: usize, out: &mut Vec<u8>) {
+    out.clear();
+    out.resize(points * field.size, 0);
+    col.write_le_strided(0, field.count, out, field.size, 0);
+}
+
+/// The columns of `data` for the fields of `header`, in header order, each
+/// checked to have the type the header gives.
+fn header_columns<'b>(header: &PcdHeader, data: &'b PointBlock) -> Result<Vec<&'b Column>> {
+    let layout = PcdLayout::from_header(header)?;
+    let mut columns = Vec::with_capacity(layout.fields.len());
+    for field in &layout.fields {
+        let column = data
+            .get_column(&field.name)
+            .ok_or_else(|| PcdError::MissingField {
+                field: field.name.clone(),
+                op: Operation::Write,
+            })?;
+        if column.value_type() != field.type_ {
+            return Err(PcdError::FieldType {
+                field: field.name.clone(),
+                expected: field.type_,
+                got: column.value_type(),
+                op: Operation::Write,
+            });
+        }
+        if column.len() < header.points * field.count {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Column {} has {} values but {} points need {}",
+                field.name,
+                column.len(),
+                header.points,
+                header.points * field.count
+            )));
+        }
+        columns.push(column);
+    }
+    Ok(columns)
 }
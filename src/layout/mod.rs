@@ -13,7 +13,14 @@
 // limitations under the License.
 
 use crate::error::{PcdError, Result};
-use crate::header::{PcdHeader, ValueType};
+use crate::header::{PcdHeader, Schema, SchemaField, ValueType};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Default memory limit for the points of one read, 4 GiB; see
+/// [`PcdLayout::block_size`].
+pub const DEFAULT_MEMORY_LIMIT: u64 = 1 << 32;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct FieldLayout {
@@ -32,74 +39,42 @@ pub struct PcdLayout {
 }
 
 impl PcdLayout {
+    /// Field names must be unique, see
+    /// [`PcdHeader::rename_duplicate_fields`].
     pub fn from_header(header: &PcdHeader) -> Result<Self> {
-        let mut fields = Vec::with_capacity(header.fields.len());
-        let mut offset = 0;
-
-        for (i, name) in header.fields.iter().enumerate() {
-            let type_char = header
-                .types
-                .get(i)
-                .ok_or_else(|| PcdError::LayoutMismatch {
-                    expected: header.fields.len(),
-                    got: i,
-                })?;
-
-            let size_in_header = *header
-                .sizes
-                .get(i)
-                .ok_or_else(|| PcdError::LayoutMismatch {
-                    expected: header.fields.len(),
-                    got: i,
-                })?;
-
-            let count = *header.counts.get(i).unwrap_or(&1);
-
-            let value_type = match type_char {
-                'I' => match size_in_header {
-                    1 => ValueType::I8,
-                    2 => ValueType::I16,
-                    4 => ValueType::I32,
-                    _ => return Err(PcdError::UnsupportedType(format!("I{}", size_in_header))),
-                },
-                'U' => match size_in_header {
-                    1 => ValueType::U8,
-                    2 => ValueType::U16,
-                    4 => ValueType::U32,
-                    _ => return Err(PcdError::UnsupportedType(format!("U{}", size_in_header))),
-                },
-                'F' => match size_in_header {
-                    4 => ValueType::F32,
-                    8 => ValueType::F64,
-                    _ => return Err(PcdError::UnsupportedType(format!("F{}", size_in_header))),
-                },
-                _ => return Err(PcdError::UnsupportedType(type_char.to_string())),
-            };
-
-            // Check if size * count matches expected logic if strict?
-            // PCD Header SIZE is size of *one* element typically (like '4' for float), even if count > 1.
-            // But let's verify. Yes, SIZE is per-element bytes.
+        Self::from_schema(&header.schema()?)
+    }
 
-            let element_size = value_type.size();
-            if element_size != size_in_header {
-                return Err(PcdError::LayoutMismatch {
-                    expected: element_size,
-                    got: size_in_header,
-                });
-            }
+    /// Tightly packed records of the fields of `schema`, in order. Field
+    /// names must be unique.
+    pub fn from_schema(schema: &Schema) -> Result<Self> {
+        if let Some(name) = schema.duplicate() {
+            return Err(PcdError::InvalidHeader {
+                line: 0,
+                msg: format!("Duplicate field {}", name),
+            });
+        }
+        let mut fields = Vec::with_capacity(schema.len());
+        let mut offset = 0usize;
 
-            let field_size = element_size * count;
+        for field in schema {
+            let too_large = || PcdError::InvalidHeader {
+                line: 0,
+                msg: format!("Field {} is too large", field.name),
+            };
+            let element_size = field.type_.size();
+            let field_size = element_size.checked_mul(field.count).ok_or_else(too_large)?;
 
             fields.push(FieldLayout {
-                name: name.clone(),
+                name: field.name.clone(),
                 offset,
                 size: field_size,
                 element_size,
-                count,
-                type_: value_type,
+                count: field.count,
+                type_: field.type_,
             });
 
-            offset += field_size;
+            offset = offset.checked_add(field_size).ok_or_else(too_large)?;
         }
 
         Ok(PcdLayout {
@@ -108,6 +83,81 @@ impl PcdLayout {
         })
     }
 
+    /// The fields with their types and counts, e.g. for `PointBlock::new`.
+    pub fn schema(&self) -> Schema {
+        self.fields
+            .iter()
+            .map(|f| SchemaField {
+                name: f.name.clone(),
+                type_: f.type_,
+                count: f.count,
+            })
+            .collect()
+    }
+
+    /// Bytes taken by `points` points of this layout, as records or as
+    /// columns. Fails with [`PcdError::TooLarge`] above `limit`, so that a
+    /// crafted header is refused before anything is allocated for it.
+    pub fn block_size(&self, points: usize, limit: u64) -> Result<usize> {
+        (points as u64)
+            .checked_mul(self.total_size as u64)
+            .filter(|&bytes| bytes <= limit)
+            .and_then(|bytes| usize::try_from(bytes).ok())
+            .ok_or(PcdError::TooLarge { points, limit })
+    }
+
+    /// A layout with explicit field offsets and record size, e.g. for
+    /// PointCloud2 records or records with padding. Fields keep the given
+    /// order; they must not overlap and must fit in `total_size`, which may
+    /// exceed [`packed_size`](Self::packed_size).
+    pub fn new(fields: Vec<FieldLayout>, total_size: usize) -> Result<Self> {
+        for (i, f) in fields.iter().enumerate() {
+            if f.size != f.element_size * f.count || f.element_size != f.type_.size() {
+                return Err(PcdError::LayoutMismatch {
+                    expected: f.type_.size() * f.count,
+                    got: f.size,
+                });
+            }
+            if f.offset + f.size > total_size {
+                return Err(PcdError::LayoutMismatch {
+                    expected: total_size,
+                    got: f.offset + f.size,
+                });
+            }
+            if let Some(other) = fields[..i]
+                .iter()
+                .find(|o| o.offset < f.offset + f.size && f.offset < o.offset + o.size)
+            {
+                return Err(PcdError::Other(format!(
+                    "Fields {} and {} overlap",
+                    other.name, f.name
+                )));
+            }
+        }
+        Ok(Self { fields, total_size })
+    }
+
+    /// Set the record size, keeping field offsets; the extra bytes are
+    /// padding that decoders skip.
+    pub fn with_stride(self, total_size: usize) -> Result<Self> {
+        Self::new(self.fields, total_size)
+    }
+
+    /// Drop the `_` padding fields of PCL headers. Offsets and the record
+    /// size are kept, so `binary` decoding skips the padding bytes.
+    #[must_use]
+    pub fn without_padding(mut self) -> Self {
+        self.fields.retain(|f| f.name != "_");
+        self
+    }
+
+    /// Bytes of field data per point, without padding. This is the size per
+    /// point of the column-major `binary_compressed` payload, which has no
+    /// padding.
+    pub fn packed_size(&self) -> usize {
+        self.fields.iter().map(|f| f.size).sum()
+    }
+
     pub fn get_field(&self, name: &str) -> Option<&FieldLayout> {
         self.fields.iter().find(|f| f.name == name)
     }
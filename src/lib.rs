@@ -1,9 +1,38 @@
+//! Without the default `std` feature only the `alloc`-based core is built:
+//! [`header`], [`layout`], [`storage`] and the binary and compressed
+//! decoders, which work on byte slices (see [`decoder::decode_slice`] and
+//! [`header::parse_header_bytes`]).
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "pcap")]
+pub mod capture;
 pub mod decoder;
 pub mod error;
+#[cfg(feature = "std")]
+pub mod features;
+pub mod filters;
 pub mod header;
+#[cfg(feature = "std")]
+pub mod geometry;
+#[cfg(feature = "std")]
 pub mod io;
 pub mod layout;
+#[cfg(feature = "std")]
+pub mod projection;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "ros")]
+pub mod ros;
+#[cfg(feature = "std")]
+pub mod spatial;
 pub mod storage;
+#[cfg(feature = "std")]
+pub mod tiling;
+#[cfg(feature = "std")]
+pub mod viz;
 
-pub use error::{PcdError, Result};
+pub use error::{Operation, PcdError, Result};
 pub use header::{DataFormat, PcdHeader, ValueType};
@@ -0,0 +1,57 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conversions between point clouds and 2D images.
+//!
+//! Images are row-major buffers of `width * height` pixels, row 0 at the
+//! top; pixels without a point hold NaN, or black in color images.
+
+pub mod organized;
+pub mod pinhole;
+pub mod range_image;
+pub use organized::{color_image, depth_image};
+pub use pinhole::{CameraIntrinsics, PinholeProjection, project_pinhole};
+pub use range_image::{RangeImage, RangeProjection, RowMapping, to_range_image};
+
+/// A `width` by `height` image, its pixels row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image<T> {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<T>,
+}
+
+impl<T> Image<T> {
+    /// # Panics
+    ///
+    /// If there are not `width * height` pixels.
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<T>) -> Self {
+        assert_eq!(pixels.len(), width * height, "image size mismatch");
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// The pixel at `row`, `column`, or `None` outside the image.
+    #[must_use]
+    pub fn get(&self, row: usize, column: usize) -> Option<&T> {
+        if row < self.height && column < self.width {
+            self.pixels.get(row * self.width + column)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Images of organized clouds, such as those of RGB-D cameras, whose points
+//! are already laid out as the pixels of a `WIDTH` by `HEIGHT` image.
+
+use super::Image;
+use crate::error::{PcdError, Result};
+use crate::storage::rgb::{packed_colors, unpack_color};
+use crate::storage::{PointBlock, PointCloud};
+
+/// The depth image of an organized cloud: each point's `z`, as RGB-D
+/// cameras measure it along their optical axis, in the cloud's units.
+/// Points with a NaN or infinite coordinate, PCL's invalid measurements,
+/// give NaN pixels. Fails if the cloud is not organized or `x`, `y` or `z` is
+/// missing.
+pub fn depth_image(cloud: &PointCloud) -> Result<Image<f32>> {
+    let (width, height) = organized_size(cloud)?;
+    let block = &cloud.data;
+    let mut depth = vec![f32::NAN; block.len];
+    let points = block
+        .iter_xyz_finite()
+        .ok_or_else(|| PcdError::InvalidDataFormat("Missing column x, y or z".to_string()))?;
+    for (i, [_, _, z]) in points {
+        depth[i] = z as f32;
+    }
+    Ok(Image::from_pixels(width, height, depth))
+}
+
+/// The color image of an organized cloud, `[r, g, b]` per pixel, from a
+/// packed `rgb` or `rgba` column or else `U8` columns `r`, `g`, `b`.
+/// Pixels of points with a NaN coordinate are black; the
+/// [`depth_image`]'s NaN pixels tell them apart. Fails if the cloud is not
+/// organized or has no colors.
+pub fn color_image(cloud: &PointCloud) -> Result<Image<[u8; 3]>> {
+    let (width, height) = organized_size(cloud)?;
+    let block = &cloud.data;
+    let mut colors = colors(block)?;
+    let points = block
+        .iter_xyz()
+        .ok_or_else(|| PcdError::InvalidDataFormat("Missing column x, y or z".to_string()))?;
+    for (p, color) in points.zip(&mut colors) {
+        if p.iter().any(|c| c.is_nan()) {
+            *color = [0; 3];
+        }
+    }
+    Ok(Image::from_pixels(width, height, colors))
+}
+
+fn colors(block: &PointBlock) -> Result<Vec<[u8; 3]>> {
+    if let Some(column) = block.get_column("rgba").or_else(|| block.get_column("rgb")) {
+        let packed = packed_colors(column).ok_or_else(|| {
+            PcdError::InvalidDataFormat(format!(
+                "Packed colors held as {:?}, not U32 or F32",
+                column.value_type()
+            ))
+        })?;
+        return Ok(packed
+            .into_iter()
+            .map(|c| {
+                let [r, g, b, _] = unpack_color(c);
+                [r, g, b]
+            })
+            .collect());
+    }
+    let channel = |name: &str| {
+        block
+            .get_column(name)
+            .and_then(|c| c.as_u8())
+            .ok_or_else(|| PcdError::InvalidDataFormat(format!("Missing U8 column {}", name)))
+    };
+    let (r, g, b) = (channel("r")?, channel("g")?, channel("b")?);
+    Ok((0..block.len).map(|i| [r[i], g[i], b[i]]).collect())
+}
+
+/// `WIDTH` and `HEIGHT` of an organized cloud, checked against its points.
+fn organized_size(cloud: &PointCloud) -> Result<(usize, usize)> {
+    let (width, height) = (cloud.header.width as usize, cloud.header.height as usize);
+    if !cloud.header.is_organized() {
+        return Err(PcdError::InvalidDataFormat(format!(
+            "Cloud of height {} is not organized",
+            height
+        )));
+    }
+    if width * height != cloud.len() {
+        return Err(PcdError::InvalidDataFormat(format!(
+            "Organized cloud of {} x {} holds {} points",
+            width,
+            height,
+            cloud.len()
+        )));
+    }
+    Ok((width, height))
+}
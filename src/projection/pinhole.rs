@@ -0,0 +1,131 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Projection of a cloud into a pinhole camera, for LiDAR-camera fusion.
+//!
+//! The camera frame is OpenCV's: `x` right, `y` down, `z` forward. A point
+//! at `(x, y, z)` in it lands on pixel coordinates
+//! `(fx * x / z + cx, fy * y / z + cy)`, pixel `(u, v)` covering
+//! `u..u + 1` and `v..v + 1`.
+
+use super::Image;
+use crate::error::{PcdError, Result};
+use crate::storage::PointBlock;
+
+/// Intrinsics of a pinhole camera without distortion, in pixels, with the
+/// size of its images.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraIntrinsics {
+    pub fx: f64,
+    pub fy: f64,
+    pub cx: f64,
+    pub cy: f64,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl CameraIntrinsics {
+    pub fn new(fx: f64, fy: f64, cx: f64, cy: f64, width: usize, height: usize) -> Self {
+        Self {
+            fx,
+            fy,
+            cx,
+            cy,
+            width,
+            height,
+        }
+    }
+
+    /// Pixel coordinates of `p` in the camera frame, `None` if it is not in
+    /// front of the camera or falls outside the image.
+    #[must_use]
+    pub fn project(&self, p: [f64; 3]) -> Option<[f64; 2]> {
+        let [x, y, z] = p;
+        if z.is_nan() || z <= 0.0 {
+            return None;
+        }
+        let (u, v) = (self.fx * x / z + self.cx, self.fy * y / z + self.cy);
+        let inside =
+            (0.0..self.width as f64).contains(&u) && (0.0..self.height as f64).contains(&v);
+        inside.then_some([u, v])
+    }
+}
+
+/// Where the points of a block fall in a camera image, see
+/// [`project_pinhole`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PinholeProjection {
+    /// Pixel coordinates `[u, v]` of each point of the block, `None` for
+    /// points behind the camera, outside the image or not finite.
+    pub pixels: Vec<Option<[f64; 2]>>,
+    /// Depth along the optical axis of the nearest point per pixel, NaN
+    /// where none falls.
+    pub depth: Image<f32>,
+    /// Index of the nearest point per pixel, the one the depth is of.
+    pub indices: Image<Option<usize>>,
+}
+
+impl PinholeProjection {
+    /// The pixel of `image` under each point, `None` for points outside
+    /// it, e.g. to paint the points with the colors of a camera image of
+    /// the same size. Points hidden behind nearer ones take the color of
+    /// those too; compare [`indices`](Self::indices) to leave them out.
+    #[must_use]
+    pub fn sample<T: Copy>(&self, image: &Image<T>) -> Vec<Option<T>> {
+        self.pixels
+            .iter()
+            .map(|pixel| {
+                let [u, v] = (*pixel)?;
+                image.get(v as usize, u as usize).copied()
+            })
+            .collect()
+    }
+}
+
+/// Project the positions of `block` into a camera with `intrinsics`, after
+/// moving them into its frame by `extrinsics`, the homogeneous transform
+/// from the cloud's frame to the camera's (`extrinsics[row][col]`, as for
+/// [`PointBlock::transform`]). The depth map keeps the nearest point of
+/// each pixel. Fails if `x`, `y` or `z` is missing.
+pub fn project_pinhole(
+    block: &PointBlock,
+    intrinsics: &CameraIntrinsics,
+    extrinsics: &[[f64; 4]; 4],
+) -> Result<PinholeProjection> {
+    let points = block
+        .iter_xyz_finite()
+        .ok_or_else(|| PcdError::InvalidDataFormat("Missing column x, y or z".to_string()))?;
+    let (width, height) = (intrinsics.width, intrinsics.height);
+    let mut projection = PinholeProjection {
+        pixels: vec![None; block.len],
+        depth: Image::from_pixels(width, height, vec![f32::NAN; width * height]),
+        indices: Image::from_pixels(width, height, vec![None; width * height]),
+    };
+    let m = extrinsics;
+    for (i, p) in points {
+        let camera: [f64; 3] =
+            core::array::from_fn(|r| m[r][0] * p[0] + m[r][1] * p[1] + m[r][2] * p[2] + m[r][3]);
+        let Some([u, v]) = intrinsics.project(camera) else {
+            continue;
+        };
+        projection.pixels[i] = Some([u, v]);
+        let pixel = v as usize * width + u as usize;
+        let (depth, kept) = (camera[2] as f32, projection.depth.pixels[pixel]);
+        if kept.is_nan() || depth < kept {
+            projection.depth.pixels[pixel] = depth;
+            projection.indices.pixels[pixel] = Some(i);
+        }
+    }
+    Ok(projection)
+}
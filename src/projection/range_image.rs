@@ -0,0 +1,222 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Spherical projection of a LiDAR sweep to a range image, as used by
+//! range-image segmentation networks such as RangeNet++, and back.
+//!
+//! Columns span the azimuth from behind the sensor on the left through
+//! straight ahead (`+x`) in the middle, turning clockwise seen from above;
+//! rows span the elevation or the sensor's rings.
+
+use crate::error::{PcdError, Result};
+use crate::storage::{Column, PointBlock};
+use std::f64::consts::PI;
+
+/// How [`RangeProjection`] picks a point's row.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RowMapping {
+    /// Rows split the elevations from `up` (top of row 0) to `down`
+    /// (bottom of the last row) evenly, in radians, e.g. 3° and -25° for
+    /// an HDL-64E. Points outside are clamped to the first or last row.
+    Fov { up: f64, down: f64 },
+    /// The row is the point's `ring`; points of rings beyond the image are
+    /// dropped.
+    Ring,
+}
+
+/// Projection of a sweep in the sensor frame to a `width` by `height`
+/// [`RangeImage`]. When several points fall on a pixel, the nearest is
+/// kept. Points with a NaN coordinate or at the origin are dropped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RangeProjection {
+    pub width: usize,
+    pub height: usize,
+    pub rows: RowMapping,
+}
+
+impl RangeProjection {
+    /// Rows by elevation over the field of view `up..down`, in radians.
+    pub fn fov(width: usize, height: usize, up: f64, down: f64) -> Self {
+        Self {
+            width,
+            height,
+            rows: RowMapping::Fov { up, down },
+        }
+    }
+
+    /// One row per ring, for sweeps with a `ring` column.
+    pub fn rings(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            rows: RowMapping::Ring,
+        }
+    }
+
+    /// The range image of `block`, with intensities if it has an
+    /// `intensity` column. Fails if `x`, `y` or `z` is missing, the image
+    /// is empty, the field of view is empty or [`RowMapping::Ring`] finds
+    /// no `ring` column.
+    pub fn project(&self, block: &PointBlock) -> Result<RangeImage> {
+        let (width, height) = (self.width, self.height);
+        if width == 0 || height == 0 {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Range image of {} x {} pixels is empty",
+                width, height
+            )));
+        }
+        let rings =
+            match self.rows {
+                RowMapping::Fov { up, down } if up.is_nan() || down.is_nan() || up <= down => {
+                    return Err(PcdError::InvalidDataFormat(format!(
+                        "Field of view from {} up to {} is empty",
+                        down, up
+                    )));
+                }
+                RowMapping::Fov { .. } => None,
+                RowMapping::Ring => Some(block.get_column("ring").ok_or_else(|| {
+                    PcdError::InvalidDataFormat("Missing column ring".to_string())
+                })?),
+            };
+        let points = block
+            .iter_xyz_finite()
+            .ok_or_else(|| PcdError::InvalidDataFormat("Missing column x, y or z".to_string()))?;
+        let intensity = block.get_column("intensity");
+
+        let mut image = RangeImage::empty(width, height, intensity.is_some());
+        let mut elevation_sums = vec![(0.0, 0usize); height];
+        for (i, [x, y, z]) in points {
+            let range = (x * x + y * y + z * z).sqrt();
+            if range == 0.0 {
+                continue;
+            }
+            let elevation = (z / range).asin();
+            let azimuth = y.atan2(x);
+            let column = ((0.5 * (1.0 - azimuth / PI) * width as f64) as usize).min(width - 1);
+            let row = match (self.rows, rings) {
+                (RowMapping::Fov { up, down }, _) => {
+                    let v = (up - elevation) / (up - down) * height as f64;
+                    v.clamp(0.0, (height - 1) as f64) as usize
+                }
+                (_, Some(rings)) => {
+                    let ring = rings.value_f64(i);
+                    if !(ring >= 0.0 && ring < height as f64) {
+                        continue;
+                    }
+                    ring as usize
+                }
+                (RowMapping::Ring, None) => unreachable!(),
+            };
+            elevation_sums[row].0 += elevation;
+            elevation_sums[row].1 += 1;
+            let pixel = row * width + column;
+            let kept = image.range[pixel];
+            if kept.is_nan() || (range as f32) < kept {
+                image.range[pixel] = range as f32;
+                image.indices[pixel] = Some(i);
+                if let (Some(out), Some(column)) = (&mut image.intensity, intensity) {
+                    out[pixel] = column.value_f64(i) as f32;
+                }
+            }
+        }
+        image.row_elevations = match self.rows {
+            RowMapping::Fov { up, down } => (0..height)
+                .map(|row| up - (row as f64 + 0.5) / height as f64 * (up - down))
+                .collect(),
+            RowMapping::Ring => elevation_sums
+                .iter()
+                .map(|&(sum, n)| if n == 0 { f64::NAN } else { sum / n as f64 })
+                .collect(),
+        };
+        Ok(image)
+    }
+}
+
+/// The range image of `block`, see [`RangeProjection`].
+pub fn to_range_image(block: &PointBlock, projection: &RangeProjection) -> Result<RangeImage> {
+    projection.project(block)
+}
+
+/// A range image: per pixel the distance from the sensor to the point
+/// kept, its intensity and its index in the block, row-major.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeImage {
+    pub width: usize,
+    pub height: usize,
+    /// Euclidean distance, NaN where there is no point.
+    pub range: Vec<f32>,
+    /// Intensity of the point kept, if the block had an `intensity` column.
+    pub intensity: Option<Vec<f32>>,
+    /// Index of the point kept in the projected block.
+    pub indices: Vec<Option<usize>>,
+    /// Elevation of each row in radians: its center for
+    /// [`RowMapping::Fov`], the mean over its points (NaN without any) for
+    /// [`RowMapping::Ring`].
+    pub row_elevations: Vec<f64>,
+}
+
+impl RangeImage {
+    fn empty(width: usize, height: usize, intensity: bool) -> Self {
+        let pixels = width * height;
+        Self {
+            width,
+            height,
+            range: vec![f32::NAN; pixels],
+            intensity: intensity.then(|| vec![f32::NAN; pixels]),
+            indices: vec![None; pixels],
+            row_elevations: vec![f64::NAN; height],
+        }
+    }
+
+    /// Azimuth of the center of `column` in radians, from `-π` to `π`.
+    #[must_use]
+    pub fn column_azimuth(&self, column: usize) -> f64 {
+        PI * (1.0 - 2.0 * (column as f64 + 0.5) / self.width as f64)
+    }
+
+    /// The points of the image, one per pixel with a range, row by row:
+    /// F32 `x`, `y`, `z` at the pixel's azimuth and row elevation, and
+    /// `intensity` if the image has one. Positions are exact up to the
+    /// pixel's angular size.
+    #[must_use]
+    pub fn to_block(&self) -> PointBlock {
+        let (mut x, mut y, mut z, mut intensity) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        for (pixel, &range) in self.range.iter().enumerate() {
+            let elevation = self.row_elevations[pixel / self.width];
+            if range.is_nan() || elevation.is_nan() {
+                continue;
+            }
+            let azimuth = self.column_azimuth(pixel % self.width);
+            let (range, flat) = (range as f64, elevation.cos());
+            x.push((range * flat * azimuth.cos()) as f32);
+            y.push((range * flat * azimuth.sin()) as f32);
+            z.push((range * elevation.sin()) as f32);
+            if let Some(values) = &self.intensity {
+                intensity.push(values[pixel]);
+            }
+        }
+        let mut block = PointBlock::default();
+        block.len = x.len();
+        let mut columns = vec![("x", x), ("y", y), ("z", z)];
+        if self.intensity.is_some() {
+            columns.push(("intensity", intensity));
+        }
+        for (name, values) in columns {
+            block
+                .add_column(name, Column::F32(values))
+                .expect("fresh block has no columns");
+        }
+        block
+    }
+}
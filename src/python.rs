@@ -0,0 +1,165 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Python bindings (feature `python`).
+//!
+//! Builds the `rs_pcd` extension module, e.g. with
+//! `maturin build --features python,pyo3/extension-module`:
+//!
+//! ```python
+//! import rs_pcd
+//! cloud = rs_pcd.read_pcd("scan.pcd")   # {"x": ndarray, "y": ndarray, ...}
+//! rs_pcd.write_pcd("out.pcd", cloud, "binary_compressed")
+//! ```
+//!
+//! Decoded columns are handed to numpy without copying. Fields with
+//! `COUNT > 1` come back as `(points, count)` arrays. Written columns must be
+//! one-dimensional and of equal length.
+
+use crate::error::PcdError;
+use crate::header::{DataFormat, PcdHeaderBuilder, Schema};
+use crate::io::{PcdReader, write_pcd_file};
+use crate::storage::{Column, PointBlock};
+use numpy::{PyArray1, PyArrayMethods};
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::path::PathBuf;
+
+impl From<PcdError> for PyErr {
+    fn from(e: PcdError) -> Self {
+        match e {
+            PcdError::Io(e) => PyIOError::new_err(e.to_string()),
+            e => PyValueError::new_err(e.to_string()),
+        }
+    }
+}
+
+/// Read a PCD file into a dict of numpy arrays keyed by field name.
+#[pyfunction]
+fn read_pcd(py: Python<'_>, path: PathBuf) -> PyResult<Bound<'_, PyDict>> {
+    let (header, mut block) = py
+        .allow_threads(|| PcdReader::from_path(&path)?.read_cloud())?
+        .into_parts();
+    let len = block.len;
+    let names: Vec<String> = block.schema().names().map(String::from).collect();
+    // Move the buffers out of the block so numpy can own them.
+    let columns: Vec<Column> = block
+        .columns_mut()
+        .iter_mut()
+        .map(|c| std::mem::replace(c, Column::U8(Vec::new())))
+        .collect();
+
+    let dict = PyDict::new(py);
+    for ((name, column), count) in names.into_iter().zip(columns).zip(header.counts) {
+        let array = column_to_numpy(py, column)?;
+        let array = if count > 1 && array.len()? == len * count {
+            array.call_method1("reshape", ((len, count),))?
+        } else {
+            array
+        };
+        dict.set_item(name, array)?;
+    }
+    Ok(dict)
+}
+
+/// Write a dict of one-dimensional numpy arrays as a PCD file. `format` is
+/// `"ascii"`, `"binary"` or `"binary_compressed"`.
+#[pyfunction]
+#[pyo3(signature = (path, data, format = "binary"))]
+fn write_pcd(
+    py: Python<'_>,
+    path: PathBuf,
+    data: &Bound<'_, PyDict>,
+    format: &str,
+) -> PyResult<()> {
+    let format: DataFormat = format.parse()?;
+    let mut names = Vec::with_capacity(data.len());
+    let mut columns = Vec::with_capacity(data.len());
+    for (key, value) in data.iter() {
+        names.push(key.extract::<String>()?);
+        columns.push(numpy_to_column(&value)?);
+    }
+    let len = columns.first().map_or(0, Column::len);
+    if let Some((name, column)) = names.iter().zip(&columns).find(|(_, c)| c.len() != len) {
+        return Err(PyValueError::new_err(format!(
+            "Column {} has {} values, expected {}",
+            name,
+            column.len(),
+            len
+        )));
+    }
+
+    let mut schema = Schema::new();
+    for (name, column) in names.iter().zip(&columns) {
+        schema = schema.with_field(name, column.value_type());
+    }
+    let header = PcdHeaderBuilder::from_schema(schema.clone())
+        .width(len as u32)
+        .data_format(format)
+        .build()?;
+    let mut block = PointBlock::new(schema, 0);
+    block.len = len;
+    for (slot, column) in block.columns_mut().iter_mut().zip(columns) {
+        *slot = column;
+    }
+    py.allow_threads(|| write_pcd_file(&path, &header, &block))?;
+    Ok(())
+}
+
+fn column_to_numpy(py: Python<'_>, column: Column) -> PyResult<Bound<'_, PyAny>> {
+    // `from_vec` adopts the Vec's allocation instead of copying it.
+    Ok(match column {
+        Column::U8(v) => PyArray1::from_vec(py, v).into_any(),
+        Column::U16(v) => PyArray1::from_vec(py, v).into_any(),
+        Column::U32(v) => PyArray1::from_vec(py, v).into_any(),
+        Column::U64(v) => PyArray1::from_vec(py, v).into_any(),
+        Column::I8(v) => PyArray1::from_vec(py, v).into_any(),
+        Column::I16(v) => PyArray1::from_vec(py, v).into_any(),
+        Column::I32(v) => PyArray1::from_vec(py, v).into_any(),
+        Column::I64(v) => PyArray1::from_vec(py, v).into_any(),
+        Column::F32(v) => PyArray1::from_vec(py, v).into_any(),
+        Column::F64(v) => PyArray1::from_vec(py, v).into_any(),
+    })
+}
+
+fn numpy_to_column(value: &Bound<'_, PyAny>) -> PyResult<Column> {
+    macro_rules! try_type {
+        ($t:ty, $variant:ident) => {
+            if let Ok(array) = value.downcast::<PyArray1<$t>>() {
+                return Ok(Column::$variant(array.readonly().as_array().to_vec()));
+            }
+        };
+    }
+    try_type!(u8, U8);
+    try_type!(u16, U16);
+    try_type!(u32, U32);
+    try_type!(u64, U64);
+    try_type!(i8, I8);
+    try_type!(i16, I16);
+    try_type!(i32, I32);
+    try_type!(i64, I64);
+    try_type!(f32, F32);
+    try_type!(f64, F64);
+    Err(PyValueError::new_err(
+        "Columns must be 1-D numpy arrays of uint8/16/32/64, int8/16/32/64, float32 or float64",
+    ))
+}
+
+#[pymodule]
+fn rs_pcd(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(read_pcd, m)?)?;
+    m.add_function(wrap_pyfunction!(write_pcd, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,290 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ROS1 bag (format 2.0) PointCloud2 extraction (feature `rosbag`).
+//!
+//! The bag is read front to back; index records are skipped. Chunks must be
+//! stored uncompressed (`compression=none`), bz2 and lz4 chunks are rejected.
+
+use super::{BagFrame, Header, PointCloud2, PointField};
+use crate::error::{PcdError, Result};
+use crate::storage::PointBlock;
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read};
+use std::path::Path;
+
+const BAG_MAGIC: &[u8] = b"#ROSBAG V2.0\n";
+const POINTCLOUD2_TYPE: &str = "sensor_msgs/PointCloud2";
+
+/// Record header fields by name.
+type Fields = HashMap<String, Vec<u8>>;
+
+const OP_MESSAGE_DATA: u8 = 0x02;
+const OP_CHUNK: u8 = 0x05;
+const OP_CONNECTION: u8 = 0x07;
+
+/// Iterator over the PointCloud2 messages of a bag, in file order.
+pub struct RosbagReader<R: Read> {
+    reader: R,
+    topic: Option<String>,
+    // conn id -> topic, for PointCloud2 connections only
+    connections: HashMap<u32, String>,
+    pending: VecDeque<(u32, Vec<u8>)>,
+    done: bool,
+}
+
+impl RosbagReader<BufReader<File>> {
+    /// Open a bag file.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> RosbagReader<R> {
+    /// Wrap a reader positioned at the start of a bag.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut magic = [0u8; 13];
+        reader.read_exact(&mut magic)?;
+        if magic != BAG_MAGIC {
+            return Err(PcdError::InvalidDataFormat(
+                "Not a ROS bag v2.0 file".to_string(),
+            ));
+        }
+        Ok(Self {
+            reader,
+            topic: None,
+            connections: HashMap::new(),
+            pending: VecDeque::new(),
+            done: false,
+        })
+    }
+
+    /// Only yield messages published on `topic`.
+    #[must_use]
+    pub fn with_topic(mut self, topic: &str) -> Self {
+        self.topic = Some(topic.to_string());
+        self
+    }
+
+    /// Read the next top-level record, or `None` at end of file.
+    fn read_record(&mut self) -> Result<Option<(Fields, Vec<u8>)>> {
+        let header_len = match self.reader.read_u32::<LittleEndian>() {
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let header = read_record_part(&mut self.reader, header_len)?;
+        let data_len = self.reader.read_u32::<LittleEndian>()?;
+        let data = read_record_part(&mut self.reader, data_len)?;
+        Ok(Some((parse_fields(&header)?, data)))
+    }
+
+    /// Handle a record from the file or a chunk, queueing PointCloud2 data.
+    fn handle(&mut self, fields: &Fields, data: Vec<u8>) -> Result<()> {
+        match field_u8(fields, "op")? {
+            OP_CONNECTION => {
+                let conn = field_u32(fields, "conn")?;
+                let conn_header = parse_fields(&data)?;
+                let is_cloud = conn_header
+                    .get("type")
+                    .is_some_and(|t| t == POINTCLOUD2_TYPE.as_bytes());
+                let topic = String::from_utf8_lossy(
+                    fields.get("topic").map(Vec::as_slice).unwrap_or_default(),
+                )
+                .into_owned();
+                let wanted = self.topic.as_ref().is_none_or(|t| *t == topic);
+                if is_cloud && wanted {
+                    self.connections.insert(conn, topic);
+                }
+            }
+            OP_MESSAGE_DATA => {
+                let conn = field_u32(fields, "conn")?;
+                if self.connections.contains_key(&conn) {
+                    self.pending.push_back((conn, data));
+                }
+            }
+            OP_CHUNK => {
+                let compression = fields.get("compression").map(Vec::as_slice);
+                if compression != Some(b"none") {
+                    return Err(PcdError::UnsupportedDataFormat(format!(
+                        "bag chunk compression {}",
+                        String::from_utf8_lossy(compression.unwrap_or_default())
+                    )));
+                }
+                let mut cursor = Cursor::new(&data[..]);
+                while (cursor.position() as usize) < data.len() {
+                    let header = read_bytes(&mut cursor)?;
+                    let inner = read_bytes(&mut cursor)?;
+                    self.handle(&parse_fields(&header)?, inner)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for RosbagReader<R> {
+    type Item = Result<BagFrame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((conn, data)) = self.pending.pop_front() {
+                let topic = self.connections[&conn].clone();
+                return Some(decode_frame(topic, &data));
+            }
+            if self.done {
+                return None;
+            }
+            match self.read_record() {
+                Ok(Some((fields, data))) => {
+                    if let Err(e) = self.handle(&fields, data) {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                }
+                Ok(None) => self.done = true,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+fn decode_frame(topic: String, data: &[u8]) -> Result<BagFrame> {
+    let msg = deserialize_pointcloud2(data)?;
+    let block = PointBlock::from_pointcloud2(&msg)?;
+    Ok(BagFrame {
+        topic,
+        stamp_sec: msg.header.stamp_sec as i64,
+        stamp_nsec: msg.header.stamp_nsec,
+        frame_id: msg.header.frame_id,
+        width: msg.width,
+        height: msg.height,
+        block,
+    })
+}
+
+/// Deserialize a ROS1-serialized `sensor_msgs/PointCloud2`.
+pub fn deserialize_pointcloud2(data: &[u8]) -> Result<PointCloud2> {
+    let mut r = Cursor::new(data);
+    let header = Header {
+        seq: r.read_u32::<LittleEndian>()?,
+        stamp_sec: r.read_u32::<LittleEndian>()?,
+        stamp_nsec: r.read_u32::<LittleEndian>()?,
+        frame_id: read_string(&mut r)?,
+    };
+    let height = r.read_u32::<LittleEndian>()?;
+    let width = r.read_u32::<LittleEndian>()?;
+    let num_fields = r.read_u32::<LittleEndian>()?;
+    let mut fields = Vec::new();
+    for _ in 0..num_fields {
+        fields.push(PointField {
+            name: read_string(&mut r)?,
+            offset: r.read_u32::<LittleEndian>()?,
+            datatype: r.read_u8()?,
+            count: r.read_u32::<LittleEndian>()?,
+        });
+    }
+    let is_bigendian = r.read_u8()? != 0;
+    let point_step = r.read_u32::<LittleEndian>()?;
+    let row_step = r.read_u32::<LittleEndian>()?;
+    let data = read_bytes(&mut r)?;
+    let is_dense = r.read_u8()? != 0;
+
+    Ok(PointCloud2 {
+        header,
+        height,
+        width,
+        fields,
+        is_bigendian,
+        point_step,
+        row_step,
+        data,
+        is_dense,
+    })
+}
+
+/// The `len` bytes of a top-level record's header or data, read without
+/// trusting `len` for the allocation.
+fn read_record_part<R: Read>(reader: &mut R, len: u32) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    reader.take(len as u64).read_to_end(&mut buf)?;
+    if buf.len() != len as usize {
+        return Err(PcdError::BufferTooSmall {
+            expected: len as usize,
+            got: buf.len(),
+        });
+    }
+    Ok(buf)
+}
+
+fn read_bytes(r: &mut Cursor<&[u8]>) -> Result<Vec<u8>> {
+    let len = r.read_u32::<LittleEndian>()? as usize;
+    let remaining = r.get_ref().len() - r.position() as usize;
+    if len > remaining {
+        return Err(PcdError::BufferTooSmall {
+            expected: len,
+            got: remaining,
+        });
+    }
+    let mut buf = vec![0u8; len];
+    r.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn read_string(r: &mut Cursor<&[u8]>) -> Result<String> {
+    Ok(String::from_utf8_lossy(&read_bytes(r)?).into_owned())
+}
+
+/// Parse a record header: a sequence of `u32 len` + `name=value`.
+fn parse_fields(buf: &[u8]) -> Result<Fields> {
+    let mut fields = HashMap::new();
+    let mut pos = 0;
+    while pos + 4 <= buf.len() {
+        let len = u32::from_le_bytes(buf[pos..pos + 4].try_into().unwrap()) as usize;
+        pos += 4;
+        let entry = buf.get(pos..pos + len).ok_or_else(|| {
+            PcdError::InvalidDataFormat("Truncated bag record header".to_string())
+        })?;
+        let eq = entry.iter().position(|&b| b == b'=').ok_or_else(|| {
+            PcdError::InvalidDataFormat("Bag header field without '='".to_string())
+        })?;
+        fields.insert(
+            String::from_utf8_lossy(&entry[..eq]).into_owned(),
+            entry[eq + 1..].to_vec(),
+        );
+        pos += len;
+    }
+    Ok(fields)
+}
+
+fn field_u8(fields: &Fields, name: &str) -> Result<u8> {
+    fields
+        .get(name)
+        .and_then(|v| v.first().copied())
+        .ok_or_else(|| PcdError::InvalidDataFormat(format!("Bag record without {}", name)))
+}
+
+fn field_u32(fields: &Fields, name: &str) -> Result<u32> {
+    fields
+        .get(name)
+        .and_then(|v| v.get(..4))
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| PcdError::InvalidDataFormat(format!("Bag record without {}", name)))
+}
@@ -0,0 +1,325 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! MCAP (ROS2 bag) PointCloud2 extraction (feature `mcap`).
+//!
+//! Only CDR-encoded `sensor_msgs/msg/PointCloud2` channels are decoded.
+//! Chunks must be stored uncompressed; summary and index records are skipped.
+
+use super::BagFrame;
+use super::PointField;
+use super::ros2::{Header, PointCloud2, Time};
+use crate::error::{PcdError, Result};
+use crate::storage::PointBlock;
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read};
+use std::path::Path;
+
+const MCAP_MAGIC: &[u8] = b"\x89MCAP0\r\n";
+const POINTCLOUD2_SCHEMA: &str = "sensor_msgs/msg/PointCloud2";
+
+const OP_FOOTER: u8 = 0x02;
+const OP_SCHEMA: u8 = 0x03;
+const OP_CHANNEL: u8 = 0x04;
+const OP_MESSAGE: u8 = 0x05;
+const OP_CHUNK: u8 = 0x06;
+
+/// Iterator over the PointCloud2 messages of an MCAP file, in file order.
+pub struct McapReader<R: Read> {
+    reader: R,
+    topic: Option<String>,
+    // schema id -> is PointCloud2
+    schemas: HashMap<u16, bool>,
+    // channel id -> topic, for PointCloud2 channels only
+    channels: HashMap<u16, String>,
+    pending: VecDeque<(u16, Vec<u8>)>,
+    done: bool,
+}
+
+impl McapReader<BufReader<File>> {
+    /// Open an MCAP file.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> McapReader<R> {
+    /// Wrap a reader positioned at the start of an MCAP file.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if magic != MCAP_MAGIC {
+            return Err(PcdError::InvalidDataFormat("Not an MCAP file".to_string()));
+        }
+        Ok(Self {
+            reader,
+            topic: None,
+            schemas: HashMap::new(),
+            channels: HashMap::new(),
+            pending: VecDeque::new(),
+            done: false,
+        })
+    }
+
+    /// Only yield messages published on `topic`.
+    #[must_use]
+    pub fn with_topic(mut self, topic: &str) -> Self {
+        self.topic = Some(topic.to_string());
+        self
+    }
+
+    /// Read the next top-level record, or `None` after the footer.
+    fn read_record(&mut self) -> Result<Option<(u8, Vec<u8>)>> {
+        let op = match self.reader.read_u8() {
+            Ok(op) => op,
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        if op == OP_FOOTER {
+            return Ok(None);
+        }
+        let len = self.reader.read_u64::<LittleEndian>()?;
+        let mut body = Vec::new();
+        (&mut self.reader).take(len).read_to_end(&mut body)?;
+        if body.len() as u64 != len {
+            return Err(PcdError::BufferTooSmall {
+                expected: len as usize,
+                got: body.len(),
+            });
+        }
+        Ok(Some((op, body)))
+    }
+
+    fn handle(&mut self, op: u8, body: Vec<u8>) -> Result<()> {
+        let mut r = Cursor::new(&body[..]);
+        match op {
+            OP_SCHEMA => {
+                let id = r.read_u16::<LittleEndian>()?;
+                let name = read_string(&mut r)?;
+                self.schemas.insert(id, name == POINTCLOUD2_SCHEMA);
+            }
+            OP_CHANNEL => {
+                let id = r.read_u16::<LittleEndian>()?;
+                let schema_id = r.read_u16::<LittleEndian>()?;
+                let topic = read_string(&mut r)?;
+                let encoding = read_string(&mut r)?;
+                let is_cloud = self.schemas.get(&schema_id).copied().unwrap_or(false);
+                let wanted = self.topic.as_ref().is_none_or(|t| *t == topic);
+                if is_cloud && encoding == "cdr" && wanted {
+                    self.channels.insert(id, topic);
+                }
+            }
+            OP_MESSAGE => {
+                let channel = r.read_u16::<LittleEndian>()?;
+                if self.channels.contains_key(&channel) {
+                    // sequence (4) + log_time (8) + publish_time (8)
+                    let data = body.get(22..).unwrap_or_default().to_vec();
+                    self.pending.push_back((channel, data));
+                }
+            }
+            OP_CHUNK => {
+                // start/end time (16) + uncompressed size (8) + crc (4)
+                r.set_position(28);
+                let compression = read_string(&mut r)?;
+                if !compression.is_empty() {
+                    return Err(PcdError::UnsupportedDataFormat(format!(
+                        "MCAP chunk compression {}",
+                        compression
+                    )));
+                }
+                let len = r.read_u64::<LittleEndian>()?;
+                let records = take_slice(&mut r, len).ok_or_else(|| {
+                    PcdError::InvalidDataFormat("Truncated MCAP chunk".to_string())
+                })?;
+                let mut inner = Cursor::new(records);
+                while (inner.position() as usize) < records.len() {
+                    let op = inner.read_u8()?;
+                    let len = inner.read_u64::<LittleEndian>()?;
+                    let record = take_slice(&mut inner, len).ok_or_else(|| {
+                        PcdError::InvalidDataFormat("Truncated MCAP record".to_string())
+                    })?;
+                    self.handle(op, record.to_vec())?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for McapReader<R> {
+    type Item = Result<BagFrame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((channel, data)) = self.pending.pop_front() {
+                let topic = self.channels[&channel].clone();
+                return Some(decode_frame(topic, &data));
+            }
+            if self.done {
+                return None;
+            }
+            match self.read_record() {
+                Ok(Some((op, body))) => {
+                    if let Err(e) = self.handle(op, body) {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                }
+                Ok(None) => self.done = true,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+fn decode_frame(topic: String, data: &[u8]) -> Result<BagFrame> {
+    let msg = deserialize_pointcloud2_cdr(data)?;
+    let block = PointBlock::from_ros2_pointcloud2(&msg)?;
+    Ok(BagFrame {
+        topic,
+        stamp_sec: msg.header.stamp.sec as i64,
+        stamp_nsec: msg.header.stamp.nanosec,
+        frame_id: msg.header.frame_id,
+        width: msg.width,
+        height: msg.height,
+        block,
+    })
+}
+
+/// Deserialize a CDR-encoded `sensor_msgs/msg/PointCloud2`.
+pub fn deserialize_pointcloud2_cdr(data: &[u8]) -> Result<PointCloud2> {
+    let mut r = CdrReader::new(data)?;
+    let header = Header {
+        stamp: Time {
+            sec: r.u32()? as i32,
+            nanosec: r.u32()?,
+        },
+        frame_id: r.string()?,
+    };
+    let height = r.u32()?;
+    let width = r.u32()?;
+    let num_fields = r.u32()?;
+    let mut fields = Vec::new();
+    for _ in 0..num_fields {
+        fields.push(PointField {
+            name: r.string()?,
+            offset: r.u32()?,
+            datatype: r.u8()?,
+            count: r.u32()?,
+        });
+    }
+    let is_bigendian = r.u8()? != 0;
+    let point_step = r.u32()?;
+    let row_step = r.u32()?;
+    let len = r.u32()? as usize;
+    let data = r.bytes(len)?.to_vec();
+    let is_dense = r.u8()? != 0;
+
+    Ok(PointCloud2 {
+        header,
+        height,
+        width,
+        fields,
+        is_bigendian,
+        point_step,
+        row_step,
+        data,
+        is_dense,
+    })
+}
+
+/// Minimal CDR reader: primitives are aligned to their size, relative to
+/// the end of the 4-byte encapsulation header.
+struct CdrReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    big_endian: bool,
+}
+
+impl<'a> CdrReader<'a> {
+    fn new(data: &'a [u8]) -> Result<Self> {
+        let big_endian = match data.get(..2) {
+            Some([0x00, 0x00]) => true,
+            Some([0x00, 0x01]) => false,
+            _ => {
+                return Err(PcdError::UnsupportedDataFormat(
+                    "CDR encapsulation".to_string(),
+                ));
+            }
+        };
+        Ok(Self {
+            buf: &data[4.min(data.len())..],
+            pos: 0,
+            big_endian,
+        })
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.pos.saturating_add(len);
+        let out = self
+            .buf
+            .get(self.pos..end)
+            .ok_or(PcdError::BufferTooSmall {
+                expected: end,
+                got: self.buf.len(),
+            })?;
+        self.pos = end;
+        Ok(out)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        self.pos = self.pos.next_multiple_of(4);
+        let b: [u8; 4] = self.bytes(4)?.try_into().unwrap();
+        Ok(if self.big_endian {
+            u32::from_be_bytes(b)
+        } else {
+            u32::from_le_bytes(b)
+        })
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = self.u32()? as usize;
+        let raw = self.bytes(len)?;
+        let raw = raw.strip_suffix(&[0]).unwrap_or(raw);
+        Ok(String::from_utf8_lossy(raw).into_owned())
+    }
+}
+
+fn read_string(r: &mut Cursor<&[u8]>) -> Result<String> {
+    let len = r.read_u32::<LittleEndian>()?;
+    let raw = take_slice(r, len as u64)
+        .ok_or_else(|| PcdError::InvalidDataFormat("Truncated MCAP string".to_string()))?;
+    Ok(String::from_utf8_lossy(raw).into_owned())
+}
+
+/// The next `len` bytes of `r`, advancing past them, or `None` if fewer
+/// remain.
+fn take_slice<'a>(r: &mut Cursor<&'a [u8]>, len: u64) -> Option<&'a [u8]> {
+    let buf: &'a [u8] = r.get_ref();
+    let start = usize::try_from(r.position()).ok()?;
+    let end = start.checked_add(usize::try_from(len).ok()?)?;
+    let out = buf.get(start..end)?;
+    r.set_position(end as u64);
+    Some(out)
+}
@@ -0,0 +1,445 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ROS `sensor_msgs/PointCloud2` interop (feature `ros`).
+//!
+//! The message types mirror the ROS1 definitions field for field, so they can
+//! be filled from any client library (`rosrust`, `roslibrust`, a bag reader)
+//! without this crate depending on one. PointFields are mapped onto a
+//! [`PcdLayout`] whose offsets and stride come from the message, and decoding
+//! goes through the regular binary decoder.
+
+use crate::decoder::binary::BinaryReader;
+use crate::error::{Operation, PcdError, Result};
+use crate::header::{DataFormat, PcdHeader, PcdHeaderBuilder, ValueType};
+use crate::layout::{FieldLayout, PcdLayout};
+use crate::storage::{PointBlock, StridedColumnView, StridedValue};
+use std::io::Cursor;
+
+#[cfg(feature = "rosbag")]
+pub mod bag;
+#[cfg(feature = "mcap")]
+pub mod mcap;
+#[cfg(feature = "ros2")]
+pub mod ros2;
+
+#[cfg(feature = "rosbag")]
+pub use bag::RosbagReader;
+#[cfg(feature = "mcap")]
+pub use mcap::McapReader;
+
+/// `sensor_msgs/PointField`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PointField {
+    pub name: String,
+    pub offset: u32,
+    pub datatype: u8,
+    pub count: u32,
+}
+
+impl PointField {
+    pub const INT8: u8 = 1;
+    pub const UINT8: u8 = 2;
+    pub const INT16: u8 = 3;
+    pub const UINT16: u8 = 4;
+    pub const INT32: u8 = 5;
+    pub const UINT32: u8 = 6;
+    pub const FLOAT32: u8 = 7;
+    pub const FLOAT64: u8 = 8;
+}
+
+/// `std_msgs/Header` (ROS1).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Header {
+    pub seq: u32,
+    pub stamp_sec: u32,
+    pub stamp_nsec: u32,
+    pub frame_id: String,
+}
+
+/// `sensor_msgs/PointCloud2`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PointCloud2 {
+    pub header: Header,
+    pub height: u32,
+    pub width: u32,
+    pub fields: Vec<PointField>,
+    pub is_bigendian: bool,
+    pub point_step: u32,
+    pub row_step: u32,
+    pub data: Vec<u8>,
+    pub is_dense: bool,
+}
+
+/// One PointCloud2 message extracted from a bag.
+#[cfg(any(feature = "rosbag", feature = "mcap"))]
+#[derive(Debug)]
+pub struct BagFrame {
+    pub topic: String,
+    pub stamp_sec: i64,
+    pub stamp_nsec: u32,
+    pub frame_id: String,
+    pub width: u32,
+    pub height: u32,
+    pub block: PointBlock,
+}
+
+/// Write each frame to `dir/000000.pcd`, `dir/000001.pcd`, ... and return
+/// the number of frames written. Stops at the first error.
+#[cfg(any(feature = "rosbag", feature = "mcap"))]
+pub fn write_frames<I, P>(frames: I, dir: P, data: DataFormat) -> Result<usize>
+where
+    I: IntoIterator<Item = Result<BagFrame>>,
+    P: AsRef<std::path::Path>,
+{
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+    let mut written = 0;
+    for frame in frames {
+        let frame = frame?;
+        let header = PcdHeaderBuilder::from_schema(frame.block.to_schema())
+            .width(frame.width)
+            .height(frame.height)
+            .data_format(data)
+            .build()?;
+        let file = std::fs::File::create(dir.join(format!("{:06}.pcd", written)))?;
+        let mut writer = std::io::BufWriter::new(file);
+        crate::io::PcdWriter::new(&mut writer).write_pcd(&header, &frame.block)?;
+        std::io::Write::flush(&mut writer)?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+impl PointCloud2 {
+    /// PCD header matching this message's fields and dimensions, for saving
+    /// a converted frame with [`PcdWriter`](crate::io::PcdWriter).
+    pub fn pcd_header(&self, data: DataFormat) -> Result<PcdHeader> {
+        cloud_header(&self.fields, self.point_step, self.width, self.height, data)
+    }
+
+    /// Borrowed view of field `name` over all points, honouring
+    /// `is_bigendian`; `T` must match the field's datatype. Rows padded
+    /// beyond `width * point_step` have no single stride and fail with
+    /// [`PcdError::UnsupportedDataFormat`]; convert those with
+    /// [`PointBlock::from_pointcloud2`].
+    pub fn column<T: StridedValue>(&self, name: &str) -> Result<StridedColumnView<'_, T>> {
+        let field = self
+            .fields
+            .iter()
+            .find(|f| f.name == name)
+            .ok_or_else(|| PcdError::MissingField {
+                field: name.to_string(),
+                op: Operation::Read,
+            })?;
+        let type_ = value_type_of(field.datatype)?;
+        if type_ != T::TYPE {
+            return Err(PcdError::FieldType {
+                field: name.to_string(),
+                expected: T::TYPE,
+                got: type_,
+                op: Operation::Read,
+            });
+        }
+        if field.count != 1 {
+            return Err(PcdError::UnsupportedDataFormat(format!(
+                "Column view of PointField {} with count {}",
+                name, field.count
+            )));
+        }
+        let width = self.width as usize;
+        let step = self.point_step as usize;
+        if self.height > 1 && width.checked_mul(step) != Some(self.row_step as usize) {
+            return Err(PcdError::UnsupportedDataFormat(format!(
+                "Column view of rows padded to {} bytes",
+                self.row_step
+            )));
+        }
+        let points = width
+            .checked_mul(self.height as usize)
+            .ok_or_else(|| cloud_too_large(self.width, self.height))?;
+        let view = StridedColumnView::new(&self.data, field.offset as usize, step, points)?;
+        Ok(if self.is_bigendian {
+            view.big_endian()
+        } else {
+            view
+        })
+    }
+}
+
+impl PointBlock {
+    /// Convert a `PointCloud2` message into a `PointBlock`.
+    ///
+    /// Each PointField becomes a column of the matching type. Gaps between
+    /// fields and padding at the end of rows are skipped, and big-endian
+    /// data is byte-swapped. Points of non-dense clouds are kept as is, so
+    /// organized clouds keep their `width * height` grid.
+    pub fn from_pointcloud2(msg: &PointCloud2) -> Result<Self> {
+        decode_cloud(
+            &msg.fields,
+            msg.width,
+            msg.height,
+            msg.point_step,
+            msg.row_step,
+            msg.is_bigendian,
+            &msg.data,
+        )
+    }
+
+    /// Convert the block into an unorganized (`height = 1`), tightly packed
+    /// little-endian `PointCloud2`.
+    ///
+    /// Columns holding several values per point become PointFields with
+    /// that `count`. `is_dense` is set when no `x`, `y` or `z` value is NaN.
+    /// The header is left at its default for the caller to fill in. Fails if
+    /// the cloud's row does not fit the message's 32-bit sizes.
+    pub fn to_pointcloud2(&self) -> Result<PointCloud2> {
+        let encoded = encode_cloud(self)?;
+        let (width, row_step) = row_size(encoded.point_step, self.len)?;
+        Ok(PointCloud2 {
+            header: Header::default(),
+            height: 1,
+            width,
+            fields: encoded.fields,
+            is_bigendian: false,
+            point_step: encoded.point_step,
+            row_step,
+            data: encoded.data,
+            is_dense: encoded.is_dense,
+        })
+    }
+}
+
+/// Message-independent part of a PointCloud2 produced by [`encode_cloud`].
+pub(crate) struct EncodedCloud {
+    pub fields: Vec<PointField>,
+    pub point_step: u32,
+    pub data: Vec<u8>,
+    pub is_dense: bool,
+}
+
+pub(crate) fn decode_cloud(
+    fields: &[PointField],
+    width: u32,
+    height: u32,
+    point_step: u32,
+    row_step: u32,
+    is_bigendian: bool,
+    data: &[u8],
+) -> Result<PointBlock> {
+    let layout = pointfield_layout(fields, point_step as usize)?;
+    let too_large = || cloud_too_large(width, height);
+    let (width, height) = (width as usize, height as usize);
+    let points = width.checked_mul(height).ok_or_else(too_large)?;
+    let row_bytes = width.checked_mul(layout.total_size).ok_or_else(too_large)?;
+    let row_step = row_step as usize;
+    let expected = row_step
+        .checked_mul(height.saturating_sub(1))
+        .and_then(|bytes| bytes.checked_add(row_bytes))
+        .ok_or_else(too_large)?;
+
+    if row_step < row_bytes || data.len() < expected {
+        return Err(PcdError::BufferTooSmall {
+            expected,
+            got: data.len(),
+        });
+    }
+
+    // Rows padded beyond `width * point_step` are compacted first.
+    let mut owned = None;
+    if row_step != row_bytes {
+        owned = Some(
+            (0..height)
+                .flat_map(|r| &data[r * row_step..r * row_step + row_bytes])
+                .copied()
+                .collect::<Vec<u8>>(),
+        );
+    }
+    if is_bigendian {
+        let buf = owned.get_or_insert_with(|| data[..points * layout.total_size].to_vec());
+        for record in buf.chunks_exact_mut(layout.total_size) {
+            for f in &layout.fields {
+                for value in record[f.offset..f.offset + f.size].chunks_exact_mut(f.element_size) {
+                    value.reverse();
+                }
+            }
+        }
+    }
+    let data = match &owned {
+        Some(buf) => &buf[..],
+        None => &data[..points * layout.total_size],
+    };
+
+    let mut block = PointBlock::new(layout.schema(), points);
+    let mut cursor = Cursor::new(data);
+    BinaryReader::new(&mut cursor, &layout, points).decode(&mut block)?;
+    Ok(block)
+}
+
+pub(crate) fn encode_cloud(block: &PointBlock) -> Result<EncodedCloud> {
+    let mut fields = Vec::with_capacity(block.num_columns());
+    let mut counts = Vec::with_capacity(block.num_columns());
+    let mut offset = 0u32;
+    for (field, col) in block.schema().iter().zip(block.columns()) {
+        let (name, vtype, count) = (&field.name, col.value_type(), field.count);
+        let size = u32::try_from(count)
+            .ok()
+            .and_then(|count| count.checked_mul(vtype.size() as u32));
+        let next = size.and_then(|size| offset.checked_add(size));
+        let (Ok(count_u32), Some(next)) = (u32::try_from(count), next) else {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Column {} with {} values per point does not fit a PointCloud2",
+                name, count
+            )));
+        };
+        fields.push(PointField {
+            name: name.clone(),
+            offset,
+            datatype: datatype_of(vtype)?,
+            count: count_u32,
+        });
+        counts.push(count);
+        offset = next;
+    }
+
+    let mut data = Vec::with_capacity((offset as usize).saturating_mul(block.len));
+    for i in 0..block.len {
+        for (col, &count) in block.columns().iter().zip(&counts) {
+            for k in i * count..(i + 1) * count {
+                col.push_le_bytes(k, &mut data);
+            }
+        }
+    }
+
+    // Integer coordinates cannot be NaN.
+    let is_dense = ["x", "y", "z"].iter().all(|n| {
+        block
+            .get_column(n)
+            .is_none_or(|c| (0..c.len()).all(|k| !c.value_f64(k).is_nan()))
+    });
+
+    Ok(EncodedCloud {
+        fields,
+        point_step: offset,
+        data,
+        is_dense,
+    })
+}
+
+/// `width` and `row_step` of an unorganized cloud of `len` points.
+pub(crate) fn row_size(point_step: u32, len: usize) -> Result<(u32, u32)> {
+    u32::try_from(len)
+        .ok()
+        .and_then(|width| Some((width, point_step.checked_mul(width)?)))
+        .ok_or_else(|| {
+            PcdError::InvalidDataFormat(format!(
+                "{} points of {} bytes exceed a PointCloud2 row",
+                len, point_step
+            ))
+        })
+}
+
+fn cloud_too_large(width: u32, height: u32) -> PcdError {
+    PcdError::InvalidDataFormat(format!(
+        "PointCloud2 of {}x{} points is too large",
+        width, height
+    ))
+}
+
+pub(crate) fn cloud_header(
+    fields: &[PointField],
+    point_step: u32,
+    width: u32,
+    height: u32,
+    data: DataFormat,
+) -> Result<PcdHeader> {
+    let layout = pointfield_layout(fields, point_step as usize)?;
+    let mut builder = PcdHeaderBuilder::new()
+        .width(width)
+        .height(height)
+        .data_format(data);
+    for f in &layout.fields {
+        builder = builder.add_field_with_count(&f.name, f.type_, f.count);
+    }
+    builder.build()
+}
+
+/// Build a layout from PointFields, ordered by offset, with `point_step` as
+/// the record size.
+pub fn pointfield_layout(fields: &[PointField], point_step: usize) -> Result<PcdLayout> {
+    let mut sorted: Vec<&PointField> = fields.iter().collect();
+    sorted.sort_by_key(|f| f.offset);
+
+    let mut layout = Vec::with_capacity(sorted.len());
+    for f in sorted {
+        let type_ = value_type_of(f.datatype)?;
+        let count = f.count as usize;
+        let size = type_.size().checked_mul(count).filter(|_| count > 0);
+        let Some(size) = size.filter(|&size| size <= point_step) else {
+            return Err(PcdError::UnsupportedType(format!(
+                "PointField {} with count {}",
+                f.name, f.count
+            )));
+        };
+        layout.push(FieldLayout {
+            name: f.name.clone(),
+            offset: f.offset as usize,
+            size,
+            element_size: type_.size(),
+            count,
+            type_,
+        });
+    }
+
+    PcdLayout::new(layout, point_step)
+}
+
+fn value_type_of(datatype: u8) -> Result<ValueType> {
+    Ok(match datatype {
+        PointField::INT8 => ValueType::I8,
+        PointField::UINT8 => ValueType::U8,
+        PointField::INT16 => ValueType::I16,
+        PointField::UINT16 => ValueType::U16,
+        PointField::INT32 => ValueType::I32,
+        PointField::UINT32 => ValueType::U32,
+        PointField::FLOAT32 => ValueType::F32,
+        PointField::FLOAT64 => ValueType::F64,
+        _ => {
+            return Err(PcdError::UnsupportedType(format!(
+                "PointField datatype {}",
+                datatype
+            )));
+        }
+    })
+}
+
+/// The `PointField` datatype of `vtype`; 64-bit integers have none.
+fn datatype_of(vtype: ValueType) -> Result<u8> {
+    Ok(match vtype {
+        ValueType::I8 => PointField::INT8,
+        ValueType::U8 => PointField::UINT8,
+        ValueType::I16 => PointField::INT16,
+        ValueType::U16 => PointField::UINT16,
+        ValueType::I32 => PointField::INT32,
+        ValueType::U32 => PointField::UINT32,
+        ValueType::F32 => PointField::FLOAT32,
+        ValueType::F64 => PointField::FLOAT64,
+        ValueType::U64 | ValueType::I64 => {
+            return Err(PcdError::UnsupportedType(format!(
+                "{:?} in a PointField",
+                vtype
+            )));
+        }
+    })
+}
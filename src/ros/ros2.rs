@@ -0,0 +1,96 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ROS2 `sensor_msgs/msg/PointCloud2` interop (feature `ros2`).
+//!
+//! ROS2 only changes the header (no `seq`, signed seconds); the point data
+//! layout is the same as ROS1, so both share one conversion path. Copy
+//! messages from `r2r` or `rclrs` into these types field by field.
+
+use super::{PointField, cloud_header, decode_cloud, encode_cloud, row_size};
+use crate::error::Result;
+use crate::header::{DataFormat, PcdHeader};
+use crate::storage::PointBlock;
+
+/// `builtin_interfaces/msg/Time`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Time {
+    pub sec: i32,
+    pub nanosec: u32,
+}
+
+/// `std_msgs/msg/Header`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Header {
+    pub stamp: Time,
+    pub frame_id: String,
+}
+
+/// `sensor_msgs/msg/PointCloud2`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PointCloud2 {
+    pub header: Header,
+    pub height: u32,
+    pub width: u32,
+    pub fields: Vec<PointField>,
+    pub is_bigendian: bool,
+    pub point_step: u32,
+    pub row_step: u32,
+    pub data: Vec<u8>,
+    pub is_dense: bool,
+}
+
+impl PointCloud2 {
+    /// PCD header matching this message's fields and dimensions.
+    pub fn pcd_header(&self, data: DataFormat) -> Result<PcdHeader> {
+        cloud_header(&self.fields, self.point_step, self.width, self.height, data)
+    }
+}
+
+impl PointBlock {
+    /// Convert a ROS2 `PointCloud2` message into a `PointBlock`.
+    ///
+    /// Same rules as [`PointBlock::from_pointcloud2`]: field gaps and row
+    /// padding are skipped, big-endian data is swapped, NaN points are kept.
+    pub fn from_ros2_pointcloud2(msg: &PointCloud2) -> Result<Self> {
+        decode_cloud(
+            &msg.fields,
+            msg.width,
+            msg.height,
+            msg.point_step,
+            msg.row_step,
+            msg.is_bigendian,
+            &msg.data,
+        )
+    }
+
+    /// Convert the block into an unorganized, tightly packed little-endian
+    /// ROS2 `PointCloud2` with a default header, as
+    /// [`PointBlock::to_pointcloud2`] does.
+    pub fn to_ros2_pointcloud2(&self) -> Result<PointCloud2> {
+        let encoded = encode_cloud(self)?;
+        let (width, row_step) = row_size(encoded.point_step, self.len)?;
+        Ok(PointCloud2 {
+            header: Header::default(),
+            height: 1,
+            width,
+            fields: encoded.fields,
+            is_bigendian: false,
+            point_step: encoded.point_step,
+            row_step,
+            data: encoded.data,
+            is_dense: encoded.is_dense,
+        })
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A static 3D KD-tree, implicit in the order of its points.
+
+use super::{Nearest, Neighbor, distance_squared};
+use crate::error::{PcdError, Result};
+use crate::storage::PointBlock;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+// Ranges of at most this many points are scanned rather than split.
+const LEAF_SIZE: usize = 8;
+
+/// KD-tree over 3D points, e.g. the positions of a [`PointBlock`]. The
+/// points are copied into one array, reordered so that each range's median
+/// splits it along the axis of largest spread; there are no node
+/// allocations. Results are nearest first and report the index given for
+/// each point.
+#[derive(Debug, Clone, Default)]
+pub struct KdTree {
+    points: Vec<[f64; 3]>,
+    indices: Vec<usize>,
+    // Split axis of the range whose median is at each position.
+    axes: Vec<u8>,
+}
+
+impl KdTree {
+    /// Tree over `points`, each with the index results report for it.
+    pub fn new(points: impl IntoIterator<Item = (usize, [f64; 3])>) -> Self {
+        let mut entries: Vec<(usize, [f64; 3])> = points.into_iter().collect();
+        let mut axes = vec![0; entries.len()];
+        build(&mut entries, &mut axes);
+        let (indices, points) = entries.into_iter().unzip();
+        Self {
+            points,
+            indices,
+            axes,
+        }
+    }
+
+    /// Tree over the positions of `block`, in f64 whatever their type,
+    /// indexed by point. Points with a NaN or infinite coordinate are left
+    /// out. Fails if `x`, `y` or `z` is missing.
+    pub fn from_block(block: &PointBlock) -> Result<Self> {
+        let points = block
+            .iter_xyz_finite()
+            .ok_or_else(|| PcdError::InvalidDataFormat("Missing column x, y or z".to_string()))?;
+        Ok(Self::new(points))
+    }
+
+    /// Number of points in the tree.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// The `k` points nearest to `query`, fewer if the tree is smaller.
+    #[must_use]
+    pub fn nearest(&self, query: [f64; 3], k: usize) -> Vec<Neighbor> {
+        let mut nearest = Nearest::new(k);
+        if k > 0 {
+            self.knn(query, 0, self.points.len(), &mut nearest);
+        }
+        nearest.into_neighbors(|i| self.indices[i])
+    }
+
+    /// The points within `radius` of `query`, bounds included.
+    #[must_use]
+    pub fn radius_search(&self, query: [f64; 3], radius: f64) -> Vec<Neighbor> {
+        let mut found = Vec::new();
+        self.within(query, radius * radius, 0, self.points.len(), &mut found);
+        found.sort_unstable_by(|a, b| a.distance.total_cmp(&b.distance));
+        found
+    }
+
+    /// [`nearest`](Self::nearest) for each of `queries`, in parallel with
+    /// the `rayon` feature.
+    #[must_use]
+    pub fn nearest_batch(&self, queries: &[[f64; 3]], k: usize) -> Vec<Vec<Neighbor>> {
+        batch(queries, |&q| self.nearest(q, k))
+    }
+
+    /// [`radius_search`](Self::radius_search) for each of `queries`, in
+    /// parallel with the `rayon` feature.
+    #[must_use]
+    pub fn radius_search_batch(&self, queries: &[[f64; 3]], radius: f64) -> Vec<Vec<Neighbor>> {
+        batch(queries, |&q| self.radius_search(q, radius))
+    }
+
+    fn knn(&self, query: [f64; 3], lo: usize, hi: usize, nearest: &mut Nearest) {
+        if hi - lo <= LEAF_SIZE {
+            for i in lo..hi {
+                nearest.offer(distance_squared(query, self.points[i]), i);
+            }
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        nearest.offer(distance_squared(query, self.points[mid]), mid);
+        let axis = self.axes[mid] as usize;
+        let diff = query[axis] - self.points[mid][axis];
+        let (near, far) = if diff < 0.0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.knn(query, near.0, near.1, nearest);
+        if diff * diff < nearest.worst() {
+            self.knn(query, far.0, far.1, nearest);
+        }
+    }
+
+    fn within(
+        &self,
+        query: [f64; 3],
+        radius_squared: f64,
+        lo: usize,
+        hi: usize,
+        found: &mut Vec<Neighbor>,
+    ) {
+        let mut offer = |i: usize| {
+            let d = distance_squared(query, self.points[i]);
+            if d <= radius_squared {
+                found.push(Neighbor {
+                    index: self.indices[i],
+                    distance: d.sqrt(),
+                });
+            }
+        };
+        if hi - lo <= LEAF_SIZE {
+            (lo..hi).for_each(offer);
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        offer(mid);
+        let axis = self.axes[mid] as usize;
+        let diff = query[axis] - self.points[mid][axis];
+        if diff <= 0.0 || diff * diff <= radius_squared {
+            self.within(query, radius_squared, lo, mid, found);
+        }
+        if diff >= 0.0 || diff * diff <= radius_squared {
+            self.within(query, radius_squared, mid + 1, hi, found);
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+fn batch<T: Send>(queries: &[[f64; 3]], f: impl Fn(&[f64; 3]) -> T + Send + Sync) -> Vec<T> {
+    queries.par_iter().map(f).collect()
+}
+
+#[cfg(not(feature = "rayon"))]
+fn batch<T>(queries: &[[f64; 3]], f: impl Fn(&[f64; 3]) -> T) -> Vec<T> {
+    queries.iter().map(f).collect()
+}
+
+/// Reorder `entries` into tree order, recording split axes in `axes`.
+fn build(entries: &mut [(usize, [f64; 3])], axes: &mut [u8]) {
+    if entries.len() <= LEAF_SIZE {
+        return;
+    }
+    let mut min = [f64::INFINITY; 3];
+    let mut max = [f64::NEG_INFINITY; 3];
+    for (_, p) in entries.iter() {
+        for a in 0..3 {
+            min[a] = min[a].min(p[a]);
+            max[a] = max[a].max(p[a]);
+        }
+    }
+    let axis = (0..3)
+        .max_by(|&a, &b| (max[a] - min[a]).total_cmp(&(max[b] - min[b])))
+        .unwrap();
+    let mid = entries.len() / 2;
+    entries.select_nth_unstable_by(mid, |a, b| a.1[axis].total_cmp(&b.1[axis]));
+    axes[mid] = axis as u8;
+    let (left, rest) = entries.split_at_mut(mid);
+    let (left_axes, rest_axes) = axes.split_at_mut(mid);
+    build(left, left_axes);
+    build(&mut rest[1..], &mut rest_axes[1..]);
+}
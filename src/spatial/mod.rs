@@ -0,0 +1,165 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Spatial indexes over point positions.
+//!
+//! [`KdTree`] answers nearest-neighbour and radius queries over a fixed set
+//! of points; [`Octree`] also takes points added later, lists its leaves
+//! and can be saved to disk; [`VoxelHashMap`] buckets points of many frames
+//! into voxels, for odometry maps. All three implement [`NeighborSearch`].
+//! With a KD-tree:
+//!
+//! ```ignore
+//! let tree = KdTree::from_block(&block)?;
+//! for n in tree.nearest([1.0, 2.0, 0.0], 8) {
+//!     println!("point {} at {:.2}", n.index, n.distance);
+//! }
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+pub mod kdtree;
+pub mod octree;
+pub mod voxel_map;
+pub use kdtree::KdTree;
+pub use octree::{Octree, OctreeLeaf};
+pub use voxel_map::{VoxelHashMap, VoxelKey};
+
+/// A point found by a search: its index in the block and its Euclidean
+/// distance from the query.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Neighbor {
+    pub index: usize,
+    pub distance: f64,
+}
+
+/// Nearest-neighbour and radius queries, implemented by [`KdTree`],
+/// [`Octree`] and [`VoxelHashMap`] so that filters and features can take
+/// any of them. Results are nearest first and carry the index each point
+/// was inserted with.
+pub trait NeighborSearch {
+    /// The `k` points nearest to `query`, fewer if there are not as many.
+    fn nearest(&self, query: [f64; 3], k: usize) -> Vec<Neighbor>;
+
+    /// The points within `radius` of `query`, bounds included.
+    fn radius_search(&self, query: [f64; 3], radius: f64) -> Vec<Neighbor>;
+}
+
+impl NeighborSearch for KdTree {
+    fn nearest(&self, query: [f64; 3], k: usize) -> Vec<Neighbor> {
+        KdTree::nearest(self, query, k)
+    }
+
+    fn radius_search(&self, query: [f64; 3], radius: f64) -> Vec<Neighbor> {
+        KdTree::radius_search(self, query, radius)
+    }
+}
+
+impl NeighborSearch for Octree {
+    fn nearest(&self, query: [f64; 3], k: usize) -> Vec<Neighbor> {
+        Octree::nearest(self, query, k)
+    }
+
+    fn radius_search(&self, query: [f64; 3], radius: f64) -> Vec<Neighbor> {
+        Octree::radius_search(self, query, radius)
+    }
+}
+
+impl NeighborSearch for VoxelHashMap {
+    fn nearest(&self, query: [f64; 3], k: usize) -> Vec<Neighbor> {
+        VoxelHashMap::nearest(self, query, k)
+    }
+
+    fn radius_search(&self, query: [f64; 3], radius: f64) -> Vec<Neighbor> {
+        VoxelHashMap::radius_search(self, query, radius)
+    }
+}
+
+pub(crate) fn distance_squared(a: [f64; 3], b: [f64; 3]) -> f64 {
+    (0..3).map(|i| (a[i] - b[i]) * (a[i] - b[i])).sum()
+}
+
+/// The `k` smallest of the squared distances offered, with an id each.
+pub(crate) struct Nearest {
+    k: usize,
+    heap: BinaryHeap<Candidate>,
+}
+
+impl Nearest {
+    pub(crate) fn new(k: usize) -> Self {
+        Self {
+            k,
+            heap: BinaryHeap::with_capacity(k.saturating_add(1).min(1024)),
+        }
+    }
+
+    pub(crate) fn offer(&mut self, distance_squared: f64, id: usize) {
+        if self.heap.len() < self.k {
+            self.heap.push(Candidate(distance_squared, id));
+        } else if self
+            .heap
+            .peek()
+            .is_some_and(|worst| distance_squared < worst.0)
+        {
+            self.heap.pop();
+            self.heap.push(Candidate(distance_squared, id));
+        }
+    }
+
+    /// The squared distance a point must beat to be kept: infinite until
+    /// `k` are held.
+    pub(crate) fn worst(&self) -> f64 {
+        match self.heap.peek() {
+            Some(worst) if self.heap.len() >= self.k => worst.0,
+            _ if self.k == 0 => f64::NEG_INFINITY,
+            _ => f64::INFINITY,
+        }
+    }
+
+    /// The kept points nearest first, with ids turned into point indices.
+    pub(crate) fn into_neighbors(self, index: impl Fn(usize) -> usize) -> Vec<Neighbor> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Candidate(d, id)| Neighbor {
+                index: index(id),
+                distance: d.sqrt(),
+            })
+            .collect()
+    }
+}
+
+/// Squared distance and id, ordered by distance for the k-NN heap.
+struct Candidate(f64, usize);
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0).then(self.1.cmp(&other.1))
+    }
+}
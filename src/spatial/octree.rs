@@ -0,0 +1,438 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A point octree with a compact binary form, so an index can be cached
+//! next to the PCD file it was built from.
+//!
+//! The binary form is little-endian: the magic `PCDOCT01`, the root's
+//! minimum corner and edge (4 f64), the leaf capacity and maximum depth
+//! (2 u32) and the node count (u64), then the nodes, root first. A branch
+//! is the byte 1 and its eight child node numbers (u32), each larger than
+//! its own; a leaf is the byte 0, its point count (u32) and per point its
+//! index (u64) and position (3 f64).
+
+use super::{Nearest, Neighbor, distance_squared};
+use crate::error::{PcdError, Result};
+use crate::storage::PointBlock;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"PCDOCT01";
+
+/// Depth beyond which leaves are never split, so that piles of duplicate
+/// points end.
+pub const MAX_DEPTH: u32 = 21;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Leaf(Vec<(usize, [f64; 3])>),
+    Branch([u32; 8]),
+}
+
+/// A leaf of an [`Octree`]: its cube and its points with their indices.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OctreeLeaf<'a> {
+    pub depth: u32,
+    pub min: [f64; 3],
+    pub size: f64,
+    pub points: &'a [(usize, [f64; 3])],
+}
+
+/// Octree over a cube, holding points with their indices. A leaf with more
+/// than `leaf_capacity` points splits into eight octants, down to
+/// [`MAX_DEPTH`]. Points can be added at any time within the cube.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Octree {
+    min: [f64; 3],
+    size: f64,
+    leaf_capacity: usize,
+    nodes: Vec<Node>,
+}
+
+impl Octree {
+    /// Empty tree over the cube at `min` enclosing the box `min..=max`.
+    pub fn new(min: [f64; 3], max: [f64; 3], leaf_capacity: usize) -> Self {
+        let mut size = (0..3).map(|a| max[a] - min[a]).fold(0.0, f64::max);
+        // Rounding must not leave the box's far corner outside the cube.
+        while (0..3).any(|a| min[a] + size < max[a]) {
+            size = size.next_up();
+        }
+        Self {
+            min,
+            size,
+            leaf_capacity: leaf_capacity.max(1),
+            nodes: vec![Node::Leaf(Vec::new())],
+        }
+    }
+
+    /// Tree over the bounds of the finite positions of `block`, holding
+    /// them indexed by point. Fails if `x`, `y` or `z` is missing.
+    pub fn from_block(block: &PointBlock, leaf_capacity: usize) -> Result<Self> {
+        let points: Vec<(usize, [f64; 3])> = block
+            .iter_xyz_finite()
+            .ok_or_else(|| PcdError::InvalidDataFormat("Missing column x, y or z".to_string()))?
+            .collect();
+        let mut min = [f64::INFINITY; 3];
+        let mut max = [f64::NEG_INFINITY; 3];
+        for (_, p) in &points {
+            for a in 0..3 {
+                min[a] = min[a].min(p[a]);
+                max[a] = max[a].max(p[a]);
+            }
+        }
+        if points.is_empty() {
+            (min, max) = ([0.0; 3], [0.0; 3]);
+        }
+        let mut tree = Self::new(min, max, leaf_capacity);
+        for (i, p) in points {
+            tree.insert(i, p);
+        }
+        Ok(tree)
+    }
+
+    /// Add the finite points of `block`, numbered from `first_index`, e.g.
+    /// to index several frames together. Points outside the cube are
+    /// skipped; returns how many were added.
+    pub fn insert_block(&mut self, block: &PointBlock, first_index: usize) -> Result<usize> {
+        let points = block
+            .iter_xyz_finite()
+            .ok_or_else(|| PcdError::InvalidDataFormat("Missing column x, y or z".to_string()))?;
+        Ok(points
+            .filter(|&(i, p)| self.insert(first_index + i, p))
+            .count())
+    }
+
+    /// Add point `index` at `p`; false, leaving the tree unchanged, if `p`
+    /// is outside the cube.
+    pub fn insert(&mut self, index: usize, p: [f64; 3]) -> bool {
+        if !(0..3).all(|a| p[a] >= self.min[a] && p[a] <= self.min[a] + self.size) {
+            return false;
+        }
+        let (mut node, mut min, mut size, mut depth) = (0, self.min, self.size, 0);
+        while let Node::Branch(children) = &self.nodes[node] {
+            size /= 2.0;
+            let octant = octant(p, min, size);
+            node = children[octant] as usize;
+            min = child_min(min, size, octant);
+            depth += 1;
+        }
+        let Node::Leaf(points) = &mut self.nodes[node] else {
+            unreachable!()
+        };
+        points.push((index, p));
+        if points.len() > self.leaf_capacity && depth < MAX_DEPTH {
+            self.split(node, min, size);
+        }
+        true
+    }
+
+    /// Number of points in the tree.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.leaves().map(|leaf| leaf.points.len()).sum()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Minimum corner and edge of the root cube.
+    #[must_use]
+    pub fn bounds(&self) -> ([f64; 3], f64) {
+        (self.min, self.size)
+    }
+
+    /// The leaves holding points, depth first.
+    pub fn leaves(&self) -> impl Iterator<Item = OctreeLeaf<'_>> + '_ {
+        let mut stack = vec![(0usize, self.min, self.size, 0u32)];
+        std::iter::from_fn(move || {
+            while let Some((node, min, size, depth)) = stack.pop() {
+                match &self.nodes[node] {
+                    Node::Branch(children) => {
+                        for (octant, &child) in children.iter().enumerate().rev() {
+                            let half = size / 2.0;
+                            stack.push((
+                                child as usize,
+                                child_min(min, half, octant),
+                                half,
+                                depth + 1,
+                            ));
+                        }
+                    }
+                    Node::Leaf(points) if !points.is_empty() => {
+                        return Some(OctreeLeaf {
+                            depth,
+                            min,
+                            size,
+                            points,
+                        });
+                    }
+                    Node::Leaf(_) => {}
+                }
+            }
+            None
+        })
+    }
+
+    /// Indices of the points inside the box `min..=max`, ascending.
+    #[must_use]
+    pub fn box_search(&self, min: [f64; 3], max: [f64; 3]) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.visit(
+            |lo, size| (0..3).all(|a| lo[a] <= max[a] && lo[a] + size >= min[a]),
+            |index, p| {
+                if (0..3).all(|a| p[a] >= min[a] && p[a] <= max[a]) {
+                    found.push(index);
+                }
+            },
+        );
+        found.sort_unstable();
+        found
+    }
+
+    /// The `k` points nearest to `query`, nearest first, fewer if the tree
+    /// is smaller. Nodes are visited closest cube first.
+    #[must_use]
+    pub fn nearest(&self, query: [f64; 3], k: usize) -> Vec<Neighbor> {
+        let mut nearest = Nearest::new(k);
+        // Cubes still to visit, by squared distance; the `u64` is the bits
+        // of that distance, which order as the non-negative distances do.
+        let mut cubes = vec![(0usize, self.min, self.size)];
+        let mut queue = BinaryHeap::new();
+        queue.push(Reverse((
+            cube_distance_squared(query, self.min, self.size).to_bits(),
+            0,
+        )));
+        while let Some(Reverse((bits, cube))) = queue.pop() {
+            if f64::from_bits(bits) >= nearest.worst() {
+                break;
+            }
+            let (node, min, size) = cubes[cube];
+            match &self.nodes[node] {
+                Node::Branch(children) => {
+                    let half = size / 2.0;
+                    for (octant, &child) in children.iter().enumerate() {
+                        let child_min = child_min(min, half, octant);
+                        let d = cube_distance_squared(query, child_min, half);
+                        queue.push(Reverse((d.to_bits(), cubes.len())));
+                        cubes.push((child as usize, child_min, half));
+                    }
+                }
+                Node::Leaf(points) => {
+                    for &(index, p) in points {
+                        nearest.offer(distance_squared(query, p), index);
+                    }
+                }
+            }
+        }
+        nearest.into_neighbors(|index| index)
+    }
+
+    /// The points within `radius` of `query`, bounds included, nearest
+    /// first.
+    #[must_use]
+    pub fn radius_search(&self, query: [f64; 3], radius: f64) -> Vec<Neighbor> {
+        let mut found = Vec::new();
+        self.visit(
+            |lo, size| cube_distance_squared(query, lo, size) <= radius * radius,
+            |index, p| {
+                let distance = distance_squared(query, p).sqrt();
+                if distance <= radius {
+                    found.push(Neighbor { index, distance });
+                }
+            },
+        );
+        found.sort_unstable_by(|a, b| a.distance.total_cmp(&b.distance));
+        found
+    }
+
+    /// Write the tree in the binary form described in the module docs.
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        writer.write_all(MAGIC)?;
+        for v in self.min {
+            writer.write_f64::<LittleEndian>(v)?;
+        }
+        writer.write_f64::<LittleEndian>(self.size)?;
+        writer.write_u32::<LittleEndian>(u32::try_from(self.leaf_capacity).unwrap_or(u32::MAX))?;
+        writer.write_u32::<LittleEndian>(MAX_DEPTH)?;
+        writer.write_u64::<LittleEndian>(self.nodes.len() as u64)?;
+        for node in &self.nodes {
+            match node {
+                Node::Branch(children) => {
+                    writer.write_u8(1)?;
+                    for &child in children {
+                        writer.write_u32::<LittleEndian>(child)?;
+                    }
+                }
+                Node::Leaf(points) => {
+                    writer.write_u8(0)?;
+                    writer.write_u32::<LittleEndian>(points.len() as u32)?;
+                    for &(index, p) in points {
+                        writer.write_u64::<LittleEndian>(index as u64)?;
+                        for v in p {
+                            writer.write_f64::<LittleEndian>(v)?;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a tree written by [`write`](Self::write). Fails on a bad magic,
+    /// truncated data or nodes that do not form a tree.
+    pub fn read<R: Read>(mut reader: R) -> Result<Self> {
+        let invalid = |msg: &str| PcdError::InvalidDataFormat(format!("Invalid octree: {}", msg));
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("bad magic"));
+        }
+        let mut min = [0.0; 3];
+        for v in &mut min {
+            *v = reader.read_f64::<LittleEndian>()?;
+        }
+        let size = reader.read_f64::<LittleEndian>()?;
+        let leaf_capacity = reader.read_u32::<LittleEndian>()? as usize;
+        let max_depth = reader.read_u32::<LittleEndian>()?;
+        let count = reader.read_u64::<LittleEndian>()?;
+        if !(size >= 0.0 && size.is_finite()) || max_depth > MAX_DEPTH || count == 0 {
+            return Err(invalid("bad bounds, depth or node count"));
+        }
+        let mut nodes = Vec::new();
+        for id in 0..count {
+            nodes.push(match reader.read_u8()? {
+                1 => {
+                    let mut children = [0u32; 8];
+                    for child in &mut children {
+                        *child = reader.read_u32::<LittleEndian>()?;
+                        if u64::from(*child) <= id || u64::from(*child) >= count {
+                            return Err(invalid("child out of order"));
+                        }
+                    }
+                    Node::Branch(children)
+                }
+                0 => {
+                    let n = reader.read_u32::<LittleEndian>()?;
+                    let mut points = Vec::new();
+                    for _ in 0..n {
+                        let index = reader.read_u64::<LittleEndian>()? as usize;
+                        let mut p = [0.0; 3];
+                        for v in &mut p {
+                            *v = reader.read_f64::<LittleEndian>()?;
+                        }
+                        points.push((index, p));
+                    }
+                    Node::Leaf(points)
+                }
+                _ => return Err(invalid("bad node tag")),
+            });
+        }
+        // Every node but the root has exactly one parent, within the depth.
+        let mut seen = vec![false; nodes.len()];
+        let mut stack = vec![(0usize, 0u32)];
+        while let Some((node, depth)) = stack.pop() {
+            if seen[node] || depth > max_depth {
+                return Err(invalid("nodes do not form a tree"));
+            }
+            seen[node] = true;
+            if let Node::Branch(children) = &nodes[node] {
+                stack.extend(children.iter().map(|&c| (c as usize, depth + 1)));
+            }
+        }
+        if seen.contains(&false) {
+            return Err(invalid("unreachable nodes"));
+        }
+        Ok(Self {
+            min,
+            size,
+            leaf_capacity: leaf_capacity.max(1),
+            nodes,
+        })
+    }
+
+    /// Write the tree to a file, see [`write`](Self::write).
+    pub fn write_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Read a tree from a file, see [`read`](Self::read).
+    pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    /// Turn leaf `node`, of cube `min`/`size`, into a branch.
+    fn split(&mut self, node: usize, min: [f64; 3], size: f64) {
+        let Node::Leaf(points) = std::mem::replace(&mut self.nodes[node], Node::Leaf(Vec::new()))
+        else {
+            unreachable!()
+        };
+        let first = self.nodes.len() as u32;
+        let half = size / 2.0;
+        let mut octants: [Vec<(usize, [f64; 3])>; 8] = Default::default();
+        for (index, p) in points {
+            octants[octant(p, min, half)].push((index, p));
+        }
+        self.nodes.extend(octants.into_iter().map(Node::Leaf));
+        self.nodes[node] = Node::Branch(std::array::from_fn(|i| first + i as u32));
+    }
+
+    /// Call `point` for every point in the leaves whose cube passes
+    /// `enter`, given its minimum corner and edge.
+    fn visit(&self, enter: impl Fn([f64; 3], f64) -> bool, mut point: impl FnMut(usize, [f64; 3])) {
+        let mut stack = vec![(0usize, self.min, self.size)];
+        while let Some((node, min, size)) = stack.pop() {
+            if !enter(min, size) {
+                continue;
+            }
+            match &self.nodes[node] {
+                Node::Branch(children) => {
+                    let half = size / 2.0;
+                    for (octant, &child) in children.iter().enumerate() {
+                        stack.push((child as usize, child_min(min, half, octant), half));
+                    }
+                }
+                Node::Leaf(points) => points.iter().for_each(|&(i, p)| point(i, p)),
+            }
+        }
+    }
+}
+
+/// Octant of `p` in the cube at `min` whose children have edge `half`:
+/// bit `a` set for the upper half along axis `a`.
+fn octant(p: [f64; 3], min: [f64; 3], half: f64) -> usize {
+    (0..3).fold(0, |o, a| o | (usize::from(p[a] >= min[a] + half) << a))
+}
+
+fn child_min(min: [f64; 3], half: f64, octant: usize) -> [f64; 3] {
+    [0, 1, 2].map(|a| min[a] + if octant >> a & 1 == 1 { half } else { 0.0 })
+}
+
+/// Squared distance from `p` to the cube at `min` with edge `size`.
+fn cube_distance_squared(p: [f64; 3], min: [f64; 3], size: f64) -> f64 {
+    (0..3)
+        .map(|a| {
+            let d = (min[a] - p[a]).max(p[a] - (min[a] + size)).max(0.0);
+            d * d
+        })
+        .sum()
+}
@@ -0,0 +1,248 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A sparse voxel grid keyed by integer voxel coordinates, as the local maps
+//! of LiDAR odometry pipelines such as KISS-ICP.
+
+use super::{Nearest, Neighbor, distance_squared};
+use crate::error::{PcdError, Result};
+use crate::storage::PointBlock;
+use std::collections::HashMap;
+
+/// Integer coordinates of a voxel: the position divided by the leaf size,
+/// floored.
+pub type VoxelKey = [i64; 3];
+
+/// Points bucketed into cubic voxels of edge `leaf_size` aligned to the
+/// origin, so that frames inserted one after another share voxels. Each
+/// voxel keeps at most `max_points_per_voxel` points, the first inserted;
+/// later ones are dropped, bounding the map's density.
+#[derive(Debug, Clone)]
+pub struct VoxelHashMap {
+    leaf_size: f64,
+    max_points_per_voxel: usize,
+    voxels: HashMap<VoxelKey, Vec<(usize, [f64; 3])>>,
+}
+
+impl VoxelHashMap {
+    /// Empty map of voxels of edge `leaf_size`, without a limit on points
+    /// per voxel.
+    ///
+    /// # Panics
+    ///
+    /// If `leaf_size` is not positive and finite.
+    pub fn new(leaf_size: f64) -> Self {
+        assert!(
+            leaf_size > 0.0 && leaf_size.is_finite(),
+            "voxel leaf size must be positive"
+        );
+        Self {
+            leaf_size,
+            max_points_per_voxel: usize::MAX,
+            voxels: HashMap::new(),
+        }
+    }
+
+    /// Keep at most `max` points per voxel, e.g. 20 for an odometry map.
+    #[must_use]
+    pub fn max_points_per_voxel(mut self, max: usize) -> Self {
+        self.max_points_per_voxel = max;
+        self
+    }
+
+    #[must_use]
+    pub fn leaf_size(&self) -> f64 {
+        self.leaf_size
+    }
+
+    /// The voxel containing `p`.
+    #[must_use]
+    pub fn key(&self, p: [f64; 3]) -> VoxelKey {
+        p.map(|c| (c / self.leaf_size).floor() as i64)
+    }
+
+    /// Add point `index` at `p`; false if `p` is not finite or its voxel is
+    /// full.
+    pub fn insert(&mut self, index: usize, p: [f64; 3]) -> bool {
+        if !p.iter().all(|c| c.is_finite()) || self.max_points_per_voxel == 0 {
+            return false;
+        }
+        let points = self.voxels.entry(self.key(p)).or_default();
+        if points.len() >= self.max_points_per_voxel {
+            return false;
+        }
+        points.push((index, p));
+        true
+    }
+
+    /// Add the finite points of `block`, numbered from `first_index`, e.g.
+    /// a new frame already in map coordinates. Returns how many were added.
+    /// Fails if `x`, `y` or `z` is missing.
+    pub fn insert_block(&mut self, block: &PointBlock, first_index: usize) -> Result<usize> {
+        let points = block
+            .iter_xyz_finite()
+            .ok_or_else(|| PcdError::InvalidDataFormat("Missing column x, y or z".to_string()))?;
+        Ok(points
+            .filter(|&(i, p)| self.insert(first_index + i, p))
+            .count())
+    }
+
+    /// Whether the voxel containing `p` holds points.
+    #[must_use]
+    pub fn is_occupied(&self, p: [f64; 3]) -> bool {
+        self.voxels.contains_key(&self.key(p))
+    }
+
+    /// The points of voxel `key` with their indices, empty if there are
+    /// none.
+    #[must_use]
+    pub fn points(&self, key: VoxelKey) -> &[(usize, [f64; 3])] {
+        self.voxels.get(&key).map_or(&[], Vec::as_slice)
+    }
+
+    /// The occupied voxels with their points, in no particular order.
+    pub fn voxels(&self) -> impl Iterator<Item = (VoxelKey, &[(usize, [f64; 3])])> + '_ {
+        self.voxels
+            .iter()
+            .map(|(&key, points)| (key, points.as_slice()))
+    }
+
+    /// The `k` points nearest to `query`, nearest first, fewer if the map
+    /// holds fewer. Searches shells of voxels around the query's voxel,
+    /// scanning the whole map once the shells outgrow it.
+    #[must_use]
+    pub fn nearest(&self, query: [f64; 3], k: usize) -> Vec<Neighbor> {
+        let mut nearest = Nearest::new(k);
+        if k == 0 {
+            return Vec::new();
+        }
+        let center = self.key(query);
+        for r in 0i64.. {
+            if self.shell_outgrows_map(r) {
+                nearest = Nearest::new(k);
+                for (_, points) in self.voxels() {
+                    offer_all(&mut nearest, query, points);
+                }
+                break;
+            }
+            for key in shell(center, r) {
+                offer_all(&mut nearest, query, self.points(key));
+            }
+            // Voxels beyond shell `r` are at least `r` edges away.
+            let reach = r as f64 * self.leaf_size;
+            if nearest.worst() <= reach * reach {
+                break;
+            }
+        }
+        nearest.into_neighbors(|index| index)
+    }
+
+    /// The points within `radius` of `query`, bounds included, nearest
+    /// first.
+    #[must_use]
+    pub fn radius_search(&self, query: [f64; 3], radius: f64) -> Vec<Neighbor> {
+        let radius_squared = radius * radius;
+        let mut found = Vec::new();
+        let mut offer = |points: &[(usize, [f64; 3])]| {
+            for &(index, p) in points {
+                let d = distance_squared(query, p);
+                if d <= radius_squared {
+                    found.push(Neighbor {
+                        index,
+                        distance: d.sqrt(),
+                    });
+                }
+            }
+        };
+        let reach = (radius / self.leaf_size).ceil();
+        if reach.is_nan() || reach < 0.0 {
+            return found;
+        }
+        if self.shell_outgrows_map(reach as i64) {
+            self.voxels().for_each(|(_, points)| offer(points));
+        } else {
+            let (lo, hi) = (
+                self.key(query.map(|c| c - radius)),
+                self.key(query.map(|c| c + radius)),
+            );
+            for x in lo[0]..=hi[0] {
+                for y in lo[1]..=hi[1] {
+                    for z in lo[2]..=hi[2] {
+                        offer(self.points([x, y, z]));
+                    }
+                }
+            }
+        }
+        found.sort_unstable_by(|a, b| a.distance.total_cmp(&b.distance));
+        found
+    }
+
+    /// Whether the cube of voxels out to shell `r` has more voxels than the
+    /// map, so that scanning the map is cheaper.
+    fn shell_outgrows_map(&self, r: i64) -> bool {
+        let side = r.saturating_mul(2).saturating_add(1) as u128;
+        side * side * side > self.voxels.len() as u128
+    }
+
+    /// Drop the voxels whose first point is farther than `max_distance`
+    /// from `origin`, e.g. the sensor position, to keep a local map.
+    pub fn remove_far(&mut self, origin: [f64; 3], max_distance: f64) {
+        let max_squared = max_distance * max_distance;
+        self.voxels.retain(|_, points| {
+            let p = points[0].1;
+            (0..3)
+                .map(|a| (p[a] - origin[a]) * (p[a] - origin[a]))
+                .sum::<f64>()
+                <= max_squared
+        });
+    }
+
+    /// Number of occupied voxels.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.voxels.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.voxels.is_empty()
+    }
+
+    /// Number of points in all voxels.
+    #[must_use]
+    pub fn num_points(&self) -> usize {
+        self.voxels.values().map(Vec::len).sum()
+    }
+
+    pub fn clear(&mut self) {
+        self.voxels.clear();
+    }
+}
+
+fn offer_all(nearest: &mut Nearest, query: [f64; 3], points: &[(usize, [f64; 3])]) {
+    for &(index, p) in points {
+        nearest.offer(distance_squared(query, p), index);
+    }
+}
+
+/// The keys at Chebyshev distance `r` from `center`.
+fn shell(center: VoxelKey, r: i64) -> impl Iterator<Item = VoxelKey> {
+    (-r..=r).flat_map(move |x| {
+        (-r..=r).flat_map(move |y| {
+            (-r..=r)
+                .filter(move |&z| x.abs().max(y.abs()).max(z.abs()) == r)
+                .map(move |z| [center[0] + x, center[1] + y, center[2] + z])
+        })
+    })
+}
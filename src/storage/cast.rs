@@ -0,0 +1,189 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+//! Numeric conversion of columns between value types, and of blocks to
+//! another schema.
+
+use super::{Column, PointBlock};
+use crate::error::{PcdError, Result};
+use crate::header::{Schema, ValueType};
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// How [`Column::cast_with`] turns floats into integers. Integer results
+/// saturate at the bounds of the target type and NaN becomes 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// Drop the fraction, as Rust's `as` does.
+    #[default]
+    Truncate,
+    /// Round half away from zero.
+    Nearest,
+    Floor,
+    Ceil,
+}
+
+impl Rounding {
+    /// `v` rounded to an integer, saturating at the `i128` range.
+    fn apply(self, v: f64) -> i128 {
+        let t = v as i128;
+        let frac = v - t as f64;
+        match self {
+            Rounding::Truncate => t,
+            Rounding::Nearest if frac >= 0.5 => t.saturating_add(1),
+            Rounding::Nearest if frac <= -0.5 => t.saturating_sub(1),
+            Rounding::Floor if frac < 0.0 => t.saturating_sub(1),
+            Rounding::Ceil if frac > 0.0 => t.saturating_add(1),
+            _ => t,
+        }
+    }
+}
+
+impl Column {
+    /// Convert every value to `to`, truncating floats; see
+    /// [`cast_with`](Self::cast_with).
+    #[must_use]
+    pub fn cast(&self, to: ValueType) -> Column {
+        self.cast_with(to, Rounding::Truncate)
+    }
+
+    /// Convert every value to `to`. Floats become integers by `rounding`,
+    /// then integers saturate at the bounds of `to`, so `-1.0` and `300` cast
+    /// to `U8` give 0 and 255. Integers convert exactly when they fit, e.g.
+    /// `U64` nanosecond timestamps to `I64`. `F64` to `F32` and 64-bit
+    /// integers to floats round to the nearest value.
+    #[must_use]
+    pub fn cast_with(&self, to: ValueType, rounding: Rounding) -> Column {
+        if self.value_type() == to {
+            return self.clone();
+        }
+        macro_rules! int {
+            ($variant:ident, $t:ty) => {
+                Column::$variant(self.map_ints(rounding, |v| {
+                    v.clamp(<$t>::MIN as i128, <$t>::MAX as i128) as $t
+                }))
+            };
+        }
+        match to {
+            ValueType::U8 => int!(U8, u8),
+            ValueType::U16 => int!(U16, u16),
+            ValueType::U32 => int!(U32, u32),
+            ValueType::U64 => int!(U64, u64),
+            ValueType::I8 => int!(I8, i8),
+            ValueType::I16 => int!(I16, i16),
+            ValueType::I32 => int!(I32, i32),
+            ValueType::I64 => int!(I64, i64),
+            ValueType::F32 => Column::F32(self.map_values(|v| v as f32)),
+            ValueType::F64 => Column::F64(self.map_values(|v| v)),
+        }
+    }
+
+    /// Every value as `f64`, mapped by `f`. Only 64-bit integers beyond
+    /// 2^53 are rounded.
+    fn map_values<T>(&self, f: impl Fn(f64) -> T) -> Vec<T> {
+        match self {
+            Column::U8(v) => v.iter().map(|&x| f(x as f64)).collect(),
+            Column::U16(v) => v.iter().map(|&x| f(x as f64)).collect(),
+            Column::U32(v) => v.iter().map(|&x| f(x as f64)).collect(),
+            Column::U64(v) => v.iter().map(|&x| f(x as f64)).collect(),
+            Column::I8(v) => v.iter().map(|&x| f(x as f64)).collect(),
+            Column::I16(v) => v.iter().map(|&x| f(x as f64)).collect(),
+            Column::I32(v) => v.iter().map(|&x| f(x as f64)).collect(),
+            Column::I64(v) => v.iter().map(|&x| f(x as f64)).collect(),
+            Column::F32(v) => v.iter().map(|&x| f(x as f64)).collect(),
+            Column::F64(v) => v.iter().map(|&x| f(x)).collect(),
+        }
+    }
+
+    /// Every value as `i128`, mapped by `f`: integers exactly, floats
+    /// rounded by `rounding`.
+    fn map_ints<T>(&self, rounding: Rounding, f: impl Fn(i128) -> T) -> Vec<T> {
+        match self {
+            Column::U8(v) => v.iter().map(|&x| f(x as i128)).collect(),
+            Column::U16(v) => v.iter().map(|&x| f(x as i128)).collect(),
+            Column::U32(v) => v.iter().map(|&x| f(x as i128)).collect(),
+            Column::U64(v) => v.iter().map(|&x| f(x as i128)).collect(),
+            Column::I8(v) => v.iter().map(|&x| f(x as i128)).collect(),
+            Column::I16(v) => v.iter().map(|&x| f(x as i128)).collect(),
+            Column::I32(v) => v.iter().map(|&x| f(x as i128)).collect(),
+            Column::I64(v) => v.iter().map(|&x| f(x as i128)).collect(),
+            Column::F32(v) => v.iter().map(|&x| f(rounding.apply(x as f64))).collect(),
+            Column::F64(v) => v.iter().map(|&x| f(rounding.apply(x))).collect(),
+        }
+    }
+}
+
+impl PointBlock {
+    /// Replace column `name` by its values cast to `to`, see
+    /// [`Column::cast_with`].
+    pub fn cast_column(&mut self, name: &str, to: ValueType, rounding: Rounding) -> Result<()> {
+        let index = self
+            .get_column_index(name)
+            .ok_or_else(|| PcdError::InvalidDataFormat(format!("Missing column {}", name)))?;
+        self.columns[index] = self.columns[index].cast_with(to, rounding);
+        self.schema.field_mut(index).type_ = to;
+        Ok(())
+    }
+
+    /// Rename column `from` to `to`; fails if `from` is missing or `to` is
+    /// taken by another column.
+    pub fn rename_column(&mut self, from: &str, to: &str) -> Result<()> {
+        let index = self
+            .get_column_index(from)
+            .ok_or_else(|| PcdError::InvalidDataFormat(format!("Missing column {}", from)))?;
+        if from == to {
+            return Ok(());
+        }
+        if self.name_to_index.contains_key(to) {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Column {} already exists",
+                to
+            )));
+        }
+        self.name_to_index.remove(from);
+        self.name_to_index.insert(to.to_string(), index);
+        self.schema.field_mut(index).name = to.to_string();
+        Ok(())
+    }
+
+    /// The columns of `schema`, in its order and cast to its types with
+    /// [`Rounding::Truncate`]; other columns are dropped. Columns already of
+    /// the right type are moved, not copied. Fails if a field is missing or
+    /// holds a different number of values per point.
+    pub fn into_schema(mut self, schema: &Schema) -> Result<PointBlock> {
+        let mut out = PointBlock::new(schema, 0);
+        out.len = self.len;
+        for (i, field) in schema.iter().enumerate() {
+            let len = self.len;
+            let column = self.get_column_mut(&field.name).ok_or_else(|| {
+                PcdError::InvalidDataFormat(format!("Missing column {}", field.name))
+            })?;
+            if column.len() != len * field.count {
+                return Err(PcdError::LayoutMismatch {
+                    expected: len * field.count,
+                    got: column.len(),
+                });
+            }
+            let column = core::mem::replace(column, Column::U8(Vec::new()));
+            out.columns[i] = if column.value_type() == field.type_ {
+                column
+            } else {
+                column.cast(field.type_)
+            };
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,42 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::PointBlock;
+use crate::header::PcdHeader;
+
+/// A decoded cloud together with the header it was read with, so metadata
+/// such as `VIEWPOINT`, `WIDTH`/`HEIGHT` and `COUNT`s travels with the data.
+#[derive(Debug, Clone)]
+pub struct PointCloud {
+    pub header: PcdHeader,
+    pub data: PointBlock,
+}
+
+impl PointCloud {
+    pub fn new(header: PcdHeader, data: PointBlock) -> Self {
+        Self { header, data }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.len == 0
+    }
+
+    pub fn into_parts(self) -> (PcdHeader, PointBlock) {
+        (self.header, self.data)
+    }
+}
@@ -0,0 +1,264 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Colors from a scalar field such as intensity, height or label, so that
+//! exported clouds show something useful in viewers that only display
+//! `rgb`.
+
+use super::rgb::{pack_color, unpack_color};
+use super::{Column, ColumnStats, PointBlock};
+use crate::error::{PcdError, Result};
+use alloc::format;
+use alloc::vec::Vec;
+
+/// Category10's colors, for [`Colormap::Palette`].
+const PALETTE: [[u8; 3]; 10] = [
+    [0x1f, 0x77, 0xb4],
+    [0xff, 0x7f, 0x0e],
+    [0x2c, 0xa0, 0x2c],
+    [0xd6, 0x27, 0x28],
+    [0x94, 0x67, 0xbd],
+    [0x8c, 0x56, 0x4b],
+    [0xe3, 0x77, 0xc2],
+    [0x7f, 0x7f, 0x7f],
+    [0xbc, 0xbd, 0x22],
+    [0x17, 0xbe, 0xcf],
+];
+
+/// Polynomial approximations of the colormaps, within a few levels of the
+/// originals except near the ends of turbo; per channel, coefficients of
+/// `t⁰` first.
+const VIRIDIS: [[f64; 7]; 3] = [
+    [
+        0.277_727_327_223_417_7,
+        0.105_093_043_108_577_4,
+        -0.330_861_828_725_556_3,
+        -4.634_230_498_983_486,
+        6.228_269_936_347_081,
+        4.776_384_997_670_288,
+        -5.435_455_855_934_631,
+    ],
+    [
+        0.005_407_344_544_966_578,
+        1.404_613_529_898_575,
+        0.214_847_559_468_213,
+        -5.799_100_973_351_585,
+        14.179_933_366_805_09,
+        -13.745_145_377_746_01,
+        4.645_852_612_178_535,
+    ],
+    [
+        0.334_099_805_335_306_1,
+        1.384_590_162_594_685,
+        0.095_095_163_028_236_59,
+        -19.332_440_956_279_87,
+        56.690_552_600_681_05,
+        -65.353_032_633_372_34,
+        26.312_435_249_583_2,
+    ],
+];
+const TURBO: [[f64; 7]; 3] = [
+    [
+        0.135_721_38,
+        4.615_392_6,
+        -42.660_322_58,
+        132.131_082_34,
+        -152.942_393_96,
+        59.286_379_43,
+        0.0,
+    ],
+    [
+        0.091_402_61,
+        2.194_188_39,
+        4.842_966_58,
+        -14.185_033_33,
+        4.277_298_57,
+        2.829_566_04,
+        0.0,
+    ],
+    [
+        0.106_673_3,
+        12.641_946_08,
+        -60.582_048_36,
+        110.362_767_71,
+        -89.903_109_12,
+        27.348_249_73,
+        0.0,
+    ],
+];
+
+/// How [`PointBlock::colorize_by`] turns values into colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Colormap {
+    /// Dark purple through green to yellow, perceptually uniform and
+    /// readable in grayscale.
+    Viridis,
+    /// Dark blue through green to dark red, a higher-contrast rainbow.
+    Turbo,
+    /// Black to white.
+    Grayscale,
+    /// A distinct color per integer value, such as a class label, cycling
+    /// through ten colors.
+    Palette,
+}
+
+impl Colormap {
+    /// The color at `t`, clamped to `0..=1`; black for NaN. For
+    /// [`Palette`](Self::Palette), `t` is the class, truncated.
+    #[must_use]
+    pub fn color(self, t: f64) -> [u8; 3] {
+        if t.is_nan() {
+            return [0; 3];
+        }
+        let polynomial = match self {
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Turbo => &TURBO,
+            Colormap::Grayscale => {
+                let v = channel(t);
+                return [v; 3];
+            }
+            Colormap::Palette => {
+                let class = (t as i64).rem_euclid(PALETTE.len() as i64);
+                return PALETTE[class as usize];
+            }
+        };
+        let t = t.clamp(0.0, 1.0);
+        polynomial.map(|c| channel(c.iter().rev().fold(0.0, |acc, &k| acc * t + k)))
+    }
+}
+
+/// `v` in `0..=1` as an 8-bit channel.
+fn channel(v: f64) -> u8 {
+    (v.clamp(0.0, 1.0) * 255.0 + 0.5) as u8
+}
+
+impl PointBlock {
+    /// Color every point by field `field` through `colormap`, the range of
+    /// its values spanning the whole map; see
+    /// [`colorize_by_range`](Self::colorize_by_range).
+    pub fn colorize_by(&mut self, field: &str, colormap: Colormap) -> Result<()> {
+        let column = self.scalar(field)?;
+        let stats = ColumnStats::from_column(column);
+        let (min, max) = if stats.has_range() {
+            (stats.min, stats.max)
+        } else {
+            (0.0, 0.0)
+        };
+        self.colorize_by_range(field, colormap, min, max)
+    }
+
+    /// Color every point by field `field` through `colormap`, `min` mapping
+    /// to its start and `max` to its end, e.g. a fixed height range so that
+    /// tiles colored separately match. [`Colormap::Palette`] ignores the
+    /// range and colors by the value itself; NaN values are black.
+    ///
+    /// The colors go into `U8` columns `r`, `g`, `b` if the block has them,
+    /// else into a packed `rgb` column (`U32` or `F32`, whose type is kept)
+    /// or `rgba` (whose alpha is kept), else into a new `U32` `rgb` column.
+    /// Fails, leaving the block unchanged, if `field` is missing or holds
+    /// several values per point, or the color columns have other types.
+    pub fn colorize_by_range(
+        &mut self,
+        field: &str,
+        colormap: Colormap,
+        min: f64,
+        max: f64,
+    ) -> Result<()> {
+        let column = self.scalar(field)?;
+        let colors: Vec<[u8; 3]> = (0..self.len)
+            .map(|i| {
+                let v = column.value_f64(i);
+                let t = match colormap {
+                    Colormap::Palette => v,
+                    _ if max > min => (v - min) / (max - min),
+                    _ if v.is_nan() => v,
+                    _ => 0.0,
+                };
+                colormap.color(t)
+            })
+            .collect();
+        self.write_colors(&colors)
+    }
+
+    /// Column `name`, checked to hold one value per point.
+    fn scalar(&self, name: &str) -> Result<&Column> {
+        let column = self
+            .get_column(name)
+            .ok_or_else(|| PcdError::InvalidDataFormat(format!("Missing column {}", name)))?;
+        if self.values_per_point(name) != 1 {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Column {} holds several values per point",
+                name
+            )));
+        }
+        Ok(column)
+    }
+
+    fn write_colors(&mut self, colors: &[[u8; 3]]) -> Result<()> {
+        let wrong_type = |name: &str, column: &Column| {
+            PcdError::InvalidDataFormat(format!(
+                "Column {} holds {:?}, not colors",
+                name,
+                column.value_type()
+            ))
+        };
+        if ["r", "g", "b"].iter().all(|c| self.get_column(c).is_some()) {
+            for c in ["r", "g", "b"] {
+                let column = self.get_column(c).unwrap();
+                if column.as_u8().is_none() {
+                    return Err(wrong_type(c, column));
+                }
+            }
+            for (channel, c) in ["r", "g", "b"].into_iter().enumerate() {
+                let values = self.get_column_mut(c).unwrap().as_u8_mut().unwrap();
+                for (value, color) in values.iter_mut().zip(colors) {
+                    *value = color[channel];
+                }
+            }
+            return Ok(());
+        }
+        let name = if self.get_column("rgb").is_none() && self.get_column("rgba").is_some() {
+            "rgba"
+        } else {
+            "rgb"
+        };
+        let Some(column) = self.get_column_mut(name) else {
+            let packed = colors.iter().map(|&[r, g, b]| pack_color(r, g, b, 0));
+            return self.add_column("rgb", Column::U32(packed.collect()));
+        };
+        // Keep the alpha byte of `rgba`; `rgb` has none.
+        let pack = |old: u32, [r, g, b]: [u8; 3]| {
+            let a = if name == "rgba" {
+                unpack_color(old)[3]
+            } else {
+                0
+            };
+            pack_color(r, g, b, a)
+        };
+        match column {
+            Column::U32(values) => {
+                for (value, &color) in values.iter_mut().zip(colors) {
+                    *value = pack(*value, color);
+                }
+            }
+            Column::F32(values) => {
+                for (value, &color) in values.iter_mut().zip(colors) {
+                    *value = f32::from_bits(pack(value.to_bits(), color));
+                }
+            }
+            column => return Err(wrong_type(name, column)),
+        }
+        Ok(())
+    }
+}
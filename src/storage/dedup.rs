@@ -0,0 +1,98 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Removal of points that repeat others, as merged maps accumulate where
+//! scans overlap.
+
+use super::PointBlock;
+use super::voxel::{Aggregation, aggregate};
+use crate::error::{PcdError, Result};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+
+impl PointBlock {
+    /// The block with points at exactly the same position merged into one,
+    /// their fields combined with `aggregation`: [`Aggregation::First`]
+    /// keeps the first point, [`Aggregation::Mean`] averages the others.
+    /// `0.0` and `-0.0` are the same position. Merged points take the place
+    /// of the first of them; points with a NaN or infinite coordinate are
+    /// kept as they are. Fails if `x`, `y` or `z` is missing.
+    pub fn dedup_exact(&self, aggregation: Aggregation) -> Result<PointBlock> {
+        // Adding 0.0 turns -0.0 into 0.0.
+        self.dedup_by(aggregation, |p| p.map(|c| (c + 0.0).to_bits() as i64))
+    }
+
+    /// The block with the points in each cube of edge `leaf_size` merged
+    /// into one, as [`dedup_exact`](Self::dedup_exact) does for equal
+    /// positions. The cubes are aligned to the origin, so that blocks
+    /// deduplicated apart agree; with [`Aggregation::First`] the points
+    /// kept are original ones, unlike those of a
+    /// [`VoxelGrid`](super::VoxelGrid). Fails if `leaf_size` is not
+    /// positive or `x`, `y` or `z` is missing.
+    pub fn dedup_voxel(&self, leaf_size: f64, aggregation: Aggregation) -> Result<PointBlock> {
+        if !(leaf_size > 0.0 && leaf_size.is_finite()) {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Voxel leaf size {} is not positive",
+                leaf_size
+            )));
+        }
+        self.dedup_by(aggregation, |p| p.map(|c| floor(c / leaf_size)))
+    }
+
+    /// Merge the finite points sharing a `key`, in order of first point.
+    fn dedup_by(
+        &self,
+        aggregation: Aggregation,
+        key: impl Fn([f64; 3]) -> [i64; 3],
+    ) -> Result<PointBlock> {
+        let points = self
+            .iter_xyz()
+            .ok_or_else(|| PcdError::InvalidDataFormat("Missing column x, y or z".to_string()))?;
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        let mut first: BTreeMap<[i64; 3], usize> = BTreeMap::new();
+        for (i, p) in points.enumerate() {
+            if !p.iter().all(|c| c.is_finite()) {
+                groups.push(vec![i]);
+                continue;
+            }
+            let group = *first.entry(key(p)).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[group].push(i);
+        }
+        if groups.len() == self.len {
+            return Ok(self.clone());
+        }
+        let mut out = PointBlock::new(self.to_schema(), 0);
+        out.len = groups.len();
+        for (i, (field, column)) in self.schema.iter().zip(&self.columns).enumerate() {
+            out.columns[i] = aggregate(self, &field.name, column, &groups, aggregation);
+        }
+        Ok(out)
+    }
+}
+
+/// `x` rounded down, without `std`'s `f64::floor`.
+fn floor(x: f64) -> i64 {
+    let truncated = x as i64;
+    if (truncated as f64) > x {
+        truncated - 1
+    } else {
+        truncated
+    }
+}
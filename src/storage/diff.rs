@@ -0,0 +1,193 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Comparison of two clouds: schema, point counts and per-field errors.
+//!
+//! [`diff_blocks`] compares two decoded blocks. For files larger than memory,
+//! create a [`CloudDiff`] from the two schemas and feed it aligned chunks
+//! with [`CloudDiff::compare`].
+
+use super::{Column, PointBlock};
+use crate::header::{Schema, ValueType};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// When two values count as equal: their difference is at most `abs`, or at
+/// most `rel` times the larger magnitude. The default accepts only exact
+/// equality. NaN equals NaN.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Tolerance {
+    pub abs: f64,
+    pub rel: f64,
+}
+
+impl Tolerance {
+    pub fn new(abs: f64, rel: f64) -> Self {
+        Self { abs, rel }
+    }
+
+    pub fn accepts(&self, a: f64, b: f64) -> bool {
+        if a.is_nan() || b.is_nan() {
+            return a.is_nan() && b.is_nan();
+        }
+        let d = (a - b).abs();
+        d <= self.abs || d <= self.rel * a.abs().max(b.abs())
+    }
+}
+
+/// Errors of one field present in both clouds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDiff {
+    pub name: String,
+    /// Values compared.
+    pub compared: usize,
+    /// Largest absolute difference, ignoring NaNs.
+    pub max_abs: f64,
+    /// Largest difference relative to the larger magnitude, ignoring NaNs.
+    pub max_rel: f64,
+    /// Values outside the tolerance.
+    pub mismatches: usize,
+    /// Index of the first point with a value outside the tolerance.
+    pub first_mismatch: Option<usize>,
+}
+
+impl FieldDiff {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            compared: 0,
+            max_abs: 0.0,
+            max_rel: 0.0,
+            mismatches: 0,
+            first_mismatch: None,
+        }
+    }
+}
+
+/// Differences between cloud `a` and cloud `b`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CloudDiff {
+    pub tolerance: Tolerance,
+    /// Fields only `a` has.
+    pub only_in_a: Vec<String>,
+    /// Fields only `b` has.
+    pub only_in_b: Vec<String>,
+    /// Common fields stored with different types, as `(name, in a, in b)`.
+    /// Their values are still compared.
+    pub type_changes: Vec<(String, ValueType, ValueType)>,
+    /// Points compared so far, or set by the caller to the cloud sizes.
+    pub points_a: usize,
+    pub points_b: usize,
+    /// One entry per common field, in the field order of `a`.
+    pub fields: Vec<FieldDiff>,
+}
+
+impl CloudDiff {
+    /// An empty comparison of clouds with the given schemas.
+    pub fn new(a: &Schema, b: &Schema, tolerance: Tolerance) -> Self {
+        let mut diff = Self {
+            tolerance,
+            only_in_a: Vec::new(),
+            only_in_b: Vec::new(),
+            type_changes: Vec::new(),
+            points_a: 0,
+            points_b: 0,
+            fields: Vec::new(),
+        };
+        for field in a {
+            match b.get(&field.name) {
+                Some(other) => {
+                    if other.type_ != field.type_ {
+                        diff.type_changes
+                            .push((field.name.clone(), field.type_, other.type_));
+                    }
+                    diff.fields.push(FieldDiff::new(field.name.clone()));
+                }
+                None => diff.only_in_a.push(field.name.clone()),
+            }
+        }
+        for field in b {
+            if a.get(&field.name).is_none() {
+                diff.only_in_b.push(field.name.clone());
+            }
+        }
+        diff
+    }
+
+    /// Compare the next points of both clouds. The blocks hold the same
+    /// points of `a` and `b`, following those already compared; extra
+    /// points in the longer block are counted but not compared.
+    pub fn compare(&mut self, a: &PointBlock, b: &PointBlock) {
+        let offset = self.points_a.min(self.points_b);
+        let n = a.len.min(b.len);
+        for field in &mut self.fields {
+            let (Some(col_a), Some(col_b)) = (a.get_column(&field.name), b.get_column(&field.name))
+            else {
+                continue;
+            };
+            let count = a.values_per_point(&field.name);
+            let (va, vb) = (values(col_a), values(col_b));
+            let len = (n * count).min(va.len()).min(vb.len());
+            for (i, (&x, &y)) in va[..len].iter().zip(&vb[..len]).enumerate() {
+                if !x.is_nan() && !y.is_nan() {
+                    let d = (x - y).abs();
+                    field.max_abs = field.max_abs.max(d);
+                    let scale = x.abs().max(y.abs());
+                    if scale > 0.0 {
+                        field.max_rel = field.max_rel.max(d / scale);
+                    }
+                }
+                if !self.tolerance.accepts(x, y) {
+                    field.mismatches += 1;
+                    field.first_mismatch.get_or_insert(offset + i / count);
+                }
+            }
+            field.compared += len;
+        }
+        self.points_a += a.len;
+        self.points_b += b.len;
+    }
+
+    /// Whether the clouds have the same fields, types and point count, and
+    /// every value is within the tolerance.
+    pub fn is_match(&self) -> bool {
+        self.only_in_a.is_empty()
+            && self.only_in_b.is_empty()
+            && self.type_changes.is_empty()
+            && self.points_a == self.points_b
+            && self.fields.iter().all(|f| f.mismatches == 0)
+    }
+}
+
+/// Compare two whole clouds.
+pub fn diff_blocks(a: &PointBlock, b: &PointBlock, tolerance: Tolerance) -> CloudDiff {
+    let mut diff = CloudDiff::new(&a.to_schema(), &b.to_schema(), tolerance);
+    diff.compare(a, b);
+    diff
+}
+
+fn values(column: &Column) -> Vec<f64> {
+    match column {
+        Column::U8(v) => v.iter().map(|&x| x as f64).collect(),
+        Column::U16(v) => v.iter().map(|&x| x as f64).collect(),
+        Column::U32(v) => v.iter().map(|&x| x as f64).collect(),
+        Column::U64(v) => v.iter().map(|&x| x as f64).collect(),
+        Column::I8(v) => v.iter().map(|&x| x as f64).collect(),
+        Column::I16(v) => v.iter().map(|&x| x as f64).collect(),
+        Column::I32(v) => v.iter().map(|&x| x as f64).collect(),
+        Column::I64(v) => v.iter().map(|&x| x as f64).collect(),
+        Column::F32(v) => v.iter().map(|&x| x as f64).collect(),
+        Column::F64(v) => v.clone(),
+    }
+}
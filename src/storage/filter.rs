@@ -0,0 +1,411 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Point selection: field predicates, bounding-box crops and NaN removal.
+//!
+//! A [`PointFilter`] turns a block into a keep-mask, which
+//! [`PointBlock::filter`] applies. Because masks are computed per block, a
+//! filter can be run chunk by chunk over a streamed file.
+
+use super::{Column, PointBlock};
+use crate::error::{PcdError, Result};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+/// Comparison operator of a [`Predicate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+/// `field op value`, e.g. `intensity > 0.1`. Fields of any numeric type are
+/// compared as `f64`; NaN never matches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Predicate {
+    pub field: String,
+    pub op: CompareOp,
+    pub value: f64,
+}
+
+impl Predicate {
+    pub fn new(field: impl Into<String>, op: CompareOp, value: f64) -> Self {
+        Self {
+            field: field.into(),
+            op,
+            value,
+        }
+    }
+
+    pub fn matches(&self, v: f64) -> bool {
+        match self.op {
+            CompareOp::Lt => v < self.value,
+            CompareOp::Le => v <= self.value,
+            CompareOp::Gt => v > self.value,
+            CompareOp::Ge => v >= self.value,
+            CompareOp::Eq => v == self.value,
+            CompareOp::Ne => !v.is_nan() && v != self.value,
+        }
+    }
+}
+
+impl FromStr for Predicate {
+    type Err = PcdError;
+
+    /// Parse `<field><op><value>` with `op` one of `<`, `<=`, `>`, `>=`,
+    /// `=`/`==` and `!=`. Whitespace around the parts is ignored.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || PcdError::Other(format!("Invalid predicate `{}`", s));
+        let start = s.find(['<', '>', '=', '!']).ok_or_else(invalid)?;
+        let rest = &s[start..];
+        let (op, len) = match rest.get(..2) {
+            Some("<=") => (CompareOp::Le, 2),
+            Some(">=") => (CompareOp::Ge, 2),
+            Some("==") => (CompareOp::Eq, 2),
+            Some("!=") => (CompareOp::Ne, 2),
+            _ => match rest.as_bytes()[0] {
+                b'<' => (CompareOp::Lt, 1),
+                b'>' => (CompareOp::Gt, 1),
+                b'=' => (CompareOp::Eq, 1),
+                _ => return Err(invalid()),
+            },
+        };
+        let field = s[..start].trim();
+        let value = rest[len..].trim().parse().map_err(|_| invalid())?;
+        if field.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self::new(field, op, value))
+    }
+}
+
+/// Axis-aligned box over the `x`, `y` and `z` fields, bounds included.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min: [f64; 3],
+    pub max: [f64; 3],
+}
+
+impl BoundingBox {
+    pub fn new(min: [f64; 3], max: [f64; 3]) -> Self {
+        Self { min, max }
+    }
+
+    pub fn contains(&self, p: [f64; 3]) -> bool {
+        (0..3).all(|i| p[i] >= self.min[i] && p[i] <= self.max[i])
+    }
+}
+
+/// Conditions a point must meet to be kept. All of them must hold.
+#[derive(Debug, Clone, Default)]
+pub struct PointFilter {
+    predicates: Vec<Predicate>,
+    bbox: Option<BoundingBox>,
+    drop_nan: bool,
+}
+
+impl PointFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep points matching `predicate`. Only single-value fields
+    /// (`COUNT 1`) can be tested.
+    pub fn predicate(mut self, predicate: Predicate) -> Self {
+        self.predicates.push(predicate);
+        self
+    }
+
+    /// Keep points whose `field` is within `min..=max`, as PCL's
+    /// `PassThrough` filter; NaN values are dropped.
+    pub fn range(self, field: &str, min: f64, max: f64) -> Self {
+        self.predicate(Predicate::new(field, CompareOp::Ge, min))
+            .predicate(Predicate::new(field, CompareOp::Le, max))
+    }
+
+    /// Keep points inside `bbox`.
+    pub fn crop(mut self, bbox: BoundingBox) -> Self {
+        self.bbox = Some(bbox);
+        self
+    }
+
+    /// Drop points with a NaN in any floating-point field.
+    pub fn drop_nan(mut self, drop_nan: bool) -> Self {
+        self.drop_nan = drop_nan;
+        self
+    }
+
+    /// Whether each point of `block` passes.
+    pub fn mask(&self, block: &PointBlock) -> Result<Vec<bool>> {
+        let mut keep = vec![true; block.len];
+        for predicate in &self.predicates {
+            let column = scalar_column(block, &predicate.field)?;
+            update_mask(column, &mut keep, |v| predicate.matches(v));
+        }
+        if let Some(bbox) = &self.bbox {
+            let x = scalar_column(block, "x")?;
+            let y = scalar_column(block, "y")?;
+            let z = scalar_column(block, "z")?;
+            // Narrow one axis at a time.
+            for (axis, column) in [x, y, z].into_iter().enumerate() {
+                let (min, max) = (bbox.min[axis], bbox.max[axis]);
+                update_mask(column, &mut keep, |v| v >= min && v <= max);
+            }
+        }
+        if self.drop_nan {
+            for (field, column) in block.schema().iter().zip(block.columns()) {
+                let count = field.count;
+                match column {
+                    Column::F32(v) => mark_nan(v, count, &mut keep, |x| x.is_nan()),
+                    Column::F64(v) => mark_nan(v, count, &mut keep, |x| x.is_nan()),
+                    _ => {}
+                }
+            }
+        }
+        Ok(keep)
+    }
+
+    /// The points of `block` that pass, in order.
+    pub fn apply(&self, block: &PointBlock) -> Result<PointBlock> {
+        Ok(block.filter(&self.mask(block)?))
+    }
+}
+
+/// A box of [`BoundingBox`] extent in its own frame, placed in the cloud by
+/// a rigid `pose`, as PCL's `CropBox`. Keeps the points inside, or with
+/// [`negative`](Self::negative) those outside, e.g. to remove the vehicle's
+/// own footprint from a LiDAR sweep. Points with a NaN or infinite
+/// coordinate are always dropped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CropBox {
+    pub bbox: BoundingBox,
+    // Box-to-cloud pose, [row][col].
+    pose: Option<[[f64; 4]; 4]>,
+    negative: bool,
+}
+
+impl CropBox {
+    pub fn new(min: [f64; 3], max: [f64; 3]) -> Self {
+        Self {
+            bbox: BoundingBox::new(min, max),
+            pose: None,
+            negative: false,
+        }
+    }
+
+    /// Place the box by the rigid transform `pose` (`pose[row][col]`, as for
+    /// [`PointBlock::transform`]) from box to cloud coordinates.
+    #[must_use]
+    pub fn pose(mut self, pose: &[[f32; 4]; 4]) -> Self {
+        self.pose = Some(pose.map(|row| row.map(f64::from)));
+        self
+    }
+
+    /// Keep the points outside the box instead.
+    #[must_use]
+    pub fn negative(mut self, negative: bool) -> Self {
+        self.negative = negative;
+        self
+    }
+
+    /// Whether each point of `block` is kept. Fails if `x`, `y` or `z` is
+    /// missing.
+    pub fn mask(&self, block: &PointBlock) -> Result<Vec<bool>> {
+        let points = block
+            .iter_xyz()
+            .ok_or_else(|| PcdError::InvalidDataFormat("Missing column x, y or z".to_string()))?;
+        Ok(points
+            .map(|p| {
+                if !p.iter().all(|c| c.is_finite()) {
+                    return false;
+                }
+                let local = match &self.pose {
+                    // The inverse of a rigid pose: R^T (p - t).
+                    Some(m) => {
+                        let d = [p[0] - m[0][3], p[1] - m[1][3], p[2] - m[2][3]];
+                        [0, 1, 2].map(|c| m[0][c] * d[0] + m[1][c] * d[1] + m[2][c] * d[2])
+                    }
+                    None => p,
+                };
+                self.bbox.contains(local) != self.negative
+            })
+            .collect())
+    }
+
+    /// The points of `block` that are kept, in order.
+    pub fn apply(&self, block: &PointBlock) -> Result<PointBlock> {
+        Ok(block.filter(&self.mask(block)?))
+    }
+}
+
+/// The points of `block` inside the box `min..=max` placed by `pose`, see
+/// [`CropBox`].
+pub fn crop_box(
+    block: &PointBlock,
+    min: [f64; 3],
+    max: [f64; 3],
+    pose: Option<&[[f32; 4]; 4]>,
+) -> Result<PointBlock> {
+    let crop = CropBox::new(min, max);
+    match pose {
+        Some(pose) => crop.pose(pose),
+        None => crop,
+    }
+    .apply(block)
+}
+
+/// The points of `block` whose `field`, of any numeric type, is within
+/// `min..=max`, e.g. `pass_through(&block, "z", -1.0, 3.0)`; see
+/// [`PointFilter::range`].
+pub fn pass_through(block: &PointBlock, field: &str, min: f64, max: f64) -> Result<PointBlock> {
+    PointFilter::new().range(field, min, max).apply(block)
+}
+
+impl PointBlock {
+    /// A new block with the points whose entry in `keep` is true.
+    ///
+    /// # Panics
+    ///
+    /// If `keep.len()` differs from `self.len`.
+    #[must_use]
+    pub fn filter(&self, keep: &[bool]) -> PointBlock {
+        assert_eq!(keep.len(), self.len, "mask length must equal point count");
+        let mut out = self.clone();
+        for (column, field) in out.columns.iter_mut().zip(&self.schema) {
+            gather(column, keep, field.count);
+        }
+        out.len = keep.iter().filter(|&&k| k).count();
+        out
+    }
+
+    /// A new block with the points at `indices`, in that order; an index
+    /// may repeat.
+    ///
+    /// # Panics
+    ///
+    /// If an index is not below `self.len`.
+    #[must_use]
+    pub fn take(&self, indices: &[usize]) -> PointBlock {
+        let mut out = PointBlock::new(self.to_schema(), 0);
+        for ((to, column), field) in out.columns.iter_mut().zip(&self.columns).zip(&self.schema) {
+            let count = field.count;
+            macro_rules! take {
+                ($v:expr, $variant:ident) => {
+                    Column::$variant(
+                        indices
+                            .iter()
+                            .flat_map(|&i| &$v[i * count..(i + 1) * count])
+                            .copied()
+                            .collect(),
+                    )
+                };
+            }
+            *to = match column {
+                Column::U8(v) => take!(v, U8),
+                Column::U16(v) => take!(v, U16),
+                Column::U32(v) => take!(v, U32),
+                Column::U64(v) => take!(v, U64),
+                Column::I8(v) => take!(v, I8),
+                Column::I16(v) => take!(v, I16),
+                Column::I32(v) => take!(v, I32),
+                Column::I64(v) => take!(v, I64),
+                Column::F32(v) => take!(v, F32),
+                Column::F64(v) => take!(v, F64),
+            };
+        }
+        out.len = indices.len();
+        out
+    }
+}
+
+fn scalar_column<'a>(block: &'a PointBlock, name: &str) -> Result<&'a Column> {
+    let column = block
+        .get_column(name)
+        .ok_or_else(|| PcdError::InvalidDataFormat(format!("Missing column {}", name)))?;
+    if block.values_per_point(name) != 1 {
+        return Err(PcdError::InvalidDataFormat(format!(
+            "Cannot filter on {}, which has more than one value per point",
+            name
+        )));
+    }
+    Ok(column)
+}
+
+/// Clear `keep[i]` where value `i` fails `test`.
+fn update_mask(column: &Column, keep: &mut [bool], test: impl Fn(f64) -> bool) {
+    macro_rules! apply {
+        ($v:expr) => {
+            for (k, &x) in keep.iter_mut().zip($v.iter()) {
+                *k = *k && test(x as f64);
+            }
+        };
+    }
+    match column {
+        Column::U8(v) => apply!(v),
+        Column::U16(v) => apply!(v),
+        Column::U32(v) => apply!(v),
+        Column::U64(v) => apply!(v),
+        Column::I8(v) => apply!(v),
+        Column::I16(v) => apply!(v),
+        Column::I32(v) => apply!(v),
+        Column::I64(v) => apply!(v),
+        Column::F32(v) => apply!(v),
+        Column::F64(v) => {
+            for (k, &x) in keep.iter_mut().zip(v.iter()) {
+                *k = *k && test(x);
+            }
+        }
+    }
+}
+
+fn mark_nan<T: Copy>(values: &[T], count: usize, keep: &mut [bool], is_nan: impl Fn(T) -> bool) {
+    for (k, point) in keep.iter_mut().zip(values.chunks_exact(count)) {
+        *k = *k && !point.iter().any(|&x| is_nan(x));
+    }
+}
+
+/// Keep the `count`-value groups of `column` whose entry in `keep` is true.
+fn gather(column: &mut Column, keep: &[bool], count: usize) {
+    macro_rules! retain {
+        ($v:expr) => {{
+            let mut write = 0;
+            for (read, &k) in keep.iter().enumerate() {
+                if k {
+                    $v.copy_within(read * count..(read + 1) * count, write * count);
+                    write += 1;
+                }
+            }
+            $v.truncate(write * count);
+        }};
+    }
+    match column {
+        Column::U8(v) => retain!(v),
+        Column::U16(v) => retain!(v),
+        Column::U32(v) => retain!(v),
+        Column::U64(v) => retain!(v),
+        Column::I8(v) => retain!(v),
+        Column::I16(v) => retain!(v),
+        Column::I32(v) => retain!(v),
+        Column::I64(v) => retain!(v),
+        Column::F32(v) => retain!(v),
+        Column::F64(v) => retain!(v),
+    }
+}
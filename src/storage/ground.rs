@@ -0,0 +1,357 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ground extraction for LiDAR sweeps, labelling each point as ground or
+//! not: a progressive morphological filter over a height grid, as PCL's
+//! `ProgressiveMorphologicalFilter`, or for sweeps with a `ring` column the
+//! angle between points of neighbouring rings, as LeGO-LOAM does.
+
+use super::{Column, PointBlock};
+use crate::error::{PcdError, Result};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f64::consts::PI;
+
+/// Name of the column [`GroundSegmentation::label`] writes.
+pub const GROUND: &str = "ground";
+
+// Grids larger than this are refused rather than allocated.
+const MAX_CELLS: usize = 1 << 26;
+
+/// How [`GroundSegmentation`] tells ground points apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroundMethod {
+    /// [`Ring`](Self::Ring) if the block has an integer `ring` column, else
+    /// [`Grid`](Self::Grid).
+    #[default]
+    Auto,
+    /// Progressive morphological filtering of the lowest point per grid
+    /// cell: openings with growing windows remove objects up to the window
+    /// size, and points higher than a slope-dependent threshold above the
+    /// opened surface are not ground.
+    Grid,
+    /// Within each azimuth sector, two points of neighbouring rings are
+    /// ground if the line between them is flatter than the maximum angle;
+    /// other points of the sector and ring follow the one compared. Needs
+    /// the sweep in the sensor frame, with `z` up.
+    Ring,
+}
+
+/// Ground/non-ground segmentation of a LiDAR sweep, see [`GroundMethod`].
+/// Points with a NaN or infinite coordinate are never ground. Distances are
+/// in the units of the coordinates; the defaults suit sweeps in meters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroundSegmentation {
+    method: GroundMethod,
+    cell_size: f64,
+    max_window: f64,
+    slope: f64,
+    initial_distance: f64,
+    max_distance: f64,
+    sectors: usize,
+    max_angle: f64,
+}
+
+impl Default for GroundSegmentation {
+    fn default() -> Self {
+        Self {
+            method: GroundMethod::Auto,
+            cell_size: 0.5,
+            max_window: 16.0,
+            slope: 0.3,
+            initial_distance: 0.15,
+            max_distance: 2.5,
+            sectors: 1800,
+            max_angle: 10f64.to_radians(),
+        }
+    }
+}
+
+impl GroundSegmentation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn method(mut self, method: GroundMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Edge of the grid cells. Default is 0.5.
+    #[must_use]
+    pub fn cell_size(mut self, cell_size: f64) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
+    /// Largest opening window, about the size of the largest object to
+    /// remove, e.g. a building. Default is 16.
+    #[must_use]
+    pub fn max_window(mut self, max_window: f64) -> Self {
+        self.max_window = max_window;
+        self
+    }
+
+    /// Terrain slope, rise over run, by which the height threshold grows
+    /// with the window. Default is 0.3.
+    #[must_use]
+    pub fn slope(mut self, slope: f64) -> Self {
+        self.slope = slope;
+        self
+    }
+
+    /// Height above the ground surface up to which points are ground at the
+    /// smallest window, and in [`GroundMethod::Ring`] the height above the
+    /// compared point. Default is 0.15.
+    #[must_use]
+    pub fn initial_distance(mut self, initial_distance: f64) -> Self {
+        self.initial_distance = initial_distance;
+        self
+    }
+
+    /// Cap on the height threshold as windows grow. Default is 2.5.
+    #[must_use]
+    pub fn max_distance(mut self, max_distance: f64) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    /// Number of azimuth sectors in [`GroundMethod::Ring`], about the
+    /// points per ring of the sensor. Default is 1800, 0.2° each.
+    #[must_use]
+    pub fn sectors(mut self, sectors: usize) -> Self {
+        self.sectors = sectors;
+        self
+    }
+
+    /// Steepest angle from the horizontal, in radians, between ground
+    /// points of neighbouring rings. Default is 10°.
+    #[must_use]
+    pub fn max_angle(mut self, max_angle: f64) -> Self {
+        self.max_angle = max_angle;
+        self
+    }
+
+    /// Whether each point of `block` is ground. Fails if `x`, `y` or `z` is
+    /// missing, the grid for [`GroundMethod::Grid`] would be too large or
+    /// [`GroundMethod::Ring`] finds no integer `ring` column.
+    pub fn mask(&self, block: &PointBlock) -> Result<Vec<bool>> {
+        let points: Vec<(usize, [f64; 3])> = block
+            .iter_xyz_finite()
+            .ok_or_else(|| PcdError::InvalidDataFormat("Missing column x, y or z".to_string()))?
+            .collect();
+        let ring = match self.method {
+            GroundMethod::Auto => block
+                .get_column("ring")
+                .is_some_and(|c| !matches!(c, Column::F32(_) | Column::F64(_))),
+            GroundMethod::Grid => false,
+            GroundMethod::Ring => true,
+        };
+        let mut ground = vec![false; block.len];
+        if ring {
+            self.ring_ground(block, &points, &mut ground)?;
+        } else {
+            self.grid_ground(&points, &mut ground)?;
+        }
+        Ok(ground)
+    }
+
+    /// Add (or replace) a U8 column [`GROUND`], 1 for ground points and 0
+    /// for the others.
+    pub fn label(&self, block: &mut PointBlock) -> Result<()> {
+        let ground = self.mask(block)?;
+        block.remove_column(GROUND);
+        block.add_column(
+            GROUND,
+            Column::U8(ground.into_iter().map(u8::from).collect()),
+        )
+    }
+
+    /// The ground points of `block` and the others, each in order.
+    pub fn split(&self, block: &PointBlock) -> Result<(PointBlock, PointBlock)> {
+        let ground = self.mask(block)?;
+        let rest: Vec<bool> = ground.iter().map(|&g| !g).collect();
+        Ok((block.filter(&ground), block.filter(&rest)))
+    }
+
+    fn grid_ground(&self, points: &[(usize, [f64; 3])], ground: &mut [bool]) -> Result<()> {
+        if !(self.cell_size > 0.0 && self.cell_size.is_finite()) {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Ground cell size {} is not positive",
+                self.cell_size
+            )));
+        }
+        if points.is_empty() {
+            return Ok(());
+        }
+        let mut min = [f64::INFINITY; 2];
+        let mut max = [f64::NEG_INFINITY; 2];
+        for (_, p) in points {
+            for axis in 0..2 {
+                min[axis] = min[axis].min(p[axis]);
+                max[axis] = max[axis].max(p[axis]);
+            }
+        }
+        let [nx, ny] = [0, 1].map(|axis| ((max[axis] - min[axis]) / self.cell_size) as usize + 1);
+        if nx.checked_mul(ny).is_none_or(|cells| cells > MAX_CELLS) {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Ground grid of {} x {} cells is too large for cell size {}",
+                nx, ny, self.cell_size
+            )));
+        }
+        // Offsets from `min` are not negative, so truncating floors them.
+        let cells: Vec<usize> = points
+            .iter()
+            .map(|(_, p)| {
+                let [cx, cy] = [0, 1].map(|axis| ((p[axis] - min[axis]) / self.cell_size) as usize);
+                cy * nx + cx
+            })
+            .collect();
+        let mut surface = vec![f64::INFINITY; nx * ny];
+        for (&cell, (i, p)) in cells.iter().zip(points) {
+            surface[cell] = surface[cell].min(p[2]);
+            ground[*i] = true;
+        }
+
+        // Windows of 2^k + 1 cells, as PCL's exponential window growth.
+        let mut previous = 1;
+        for k in 1.. {
+            let window = (1usize << k) + 1;
+            if window as f64 * self.cell_size > self.max_window.max(self.cell_size * 3.0) {
+                break;
+            }
+            let threshold = if k == 1 {
+                self.initial_distance
+            } else {
+                (self.slope * (window - previous) as f64 * self.cell_size + self.initial_distance)
+                    .min(self.max_distance)
+            };
+            let eroded = sliding(&surface, nx, ny, window, f64::min, f64::INFINITY);
+            let opened = sliding(&eroded, nx, ny, window, max_finite, f64::NEG_INFINITY);
+            for (&cell, (i, p)) in cells.iter().zip(points) {
+                if p[2] - opened[cell] > threshold {
+                    ground[*i] = false;
+                }
+            }
+            surface = opened;
+            previous = window;
+        }
+        Ok(())
+    }
+
+    fn ring_ground(
+        &self,
+        block: &PointBlock,
+        points: &[(usize, [f64; 3])],
+        ground: &mut [bool],
+    ) -> Result<()> {
+        let column = block
+            .get_column("ring")
+            .ok_or_else(|| PcdError::InvalidDataFormat("Missing column ring".to_string()))?;
+        if matches!(column, Column::F32(_) | Column::F64(_)) || block.values_per_point("ring") != 1
+        {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Column ring holds {:?}, not one integer per point",
+                column.value_type()
+            )));
+        }
+        let sectors = self.sectors.max(1);
+        let sector = |p: &[f64; 3]| {
+            let turn = (p[1].atan2(p[0]) + PI) / (2.0 * PI);
+            ((turn * sectors as f64) as usize).min(sectors - 1)
+        };
+        // The first point of each (sector, ring), compared with the others.
+        let mut samples: BTreeMap<(usize, i64), (usize, [f64; 3], bool)> = BTreeMap::new();
+        for &(i, p) in points {
+            let ring = column.value_f64(i) as i64;
+            samples.entry((sector(&p), ring)).or_insert((i, p, false));
+        }
+        let keys: Vec<(usize, i64)> = samples.keys().copied().collect();
+        for pair in keys.windows(2) {
+            let ((s0, r0), (s1, r1)) = (pair[0], pair[1]);
+            if s0 != s1 || r1 != r0 + 1 {
+                continue;
+            }
+            let (a, b) = (samples[&pair[0]].1, samples[&pair[1]].1);
+            let run = (b[0] - a[0]).hypot(b[1] - a[1]);
+            if (b[2] - a[2]).abs().atan2(run) <= self.max_angle {
+                samples.get_mut(&pair[0]).unwrap().2 = true;
+                samples.get_mut(&pair[1]).unwrap().2 = true;
+            }
+        }
+        for &(i, p) in points {
+            let ring = column.value_f64(i) as i64;
+            let (_, q, sample_ground) = samples[&(sector(&p), ring)];
+            ground[i] = sample_ground && p[2] - q[2] <= self.initial_distance;
+        }
+        Ok(())
+    }
+}
+
+/// [`GroundSegmentation`] with its defaults: whether each point of `block`
+/// is ground.
+pub fn segment_ground(block: &PointBlock) -> Result<Vec<bool>> {
+    GroundSegmentation::new().mask(block)
+}
+
+/// The larger of two values, ignoring infinite ones, which mark empty cells.
+fn max_finite(a: f64, b: f64) -> f64 {
+    match (a.is_finite(), b.is_finite()) {
+        (true, true) => a.max(b),
+        (true, false) => a,
+        _ => b,
+    }
+}
+
+/// `grid` (`nx` by `ny`, row-major) with each cell replaced by `combine`
+/// over the `window` by `window` cells centred on it, as two passes.
+fn sliding(
+    grid: &[f64],
+    nx: usize,
+    ny: usize,
+    window: usize,
+    combine: fn(f64, f64) -> f64,
+    empty: f64,
+) -> Vec<f64> {
+    let half = window / 2;
+    let mut rows = vec![empty; grid.len()];
+    for y in 0..ny {
+        for x in 0..nx {
+            let row = &grid[y * nx..(y + 1) * nx];
+            rows[y * nx + x] = row[x.saturating_sub(half)..(x + half + 1).min(nx)]
+                .iter()
+                .copied()
+                .fold(empty, combine);
+        }
+    }
+    let mut out = vec![empty; grid.len()];
+    for y in 0..ny {
+        for x in 0..nx {
+            out[y * nx + x] = (y.saturating_sub(half)..(y + half + 1).min(ny))
+                .map(|yy| rows[yy * nx + x])
+                .fold(empty, combine);
+        }
+    }
+    // Cells emptied by the window stay empty rather than taking `empty`.
+    for (o, &g) in out.iter_mut().zip(grid) {
+        if !o.is_finite() {
+            *o = g;
+        }
+    }
+    out
+}
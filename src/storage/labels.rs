@@ -0,0 +1,129 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Semantic label utilities for the `label` column, as in PCL's
+//! `PointXYZL`: remapping class ids, counting points per class and
+//! splitting a block by class. The column may hold any integer type; values
+//! are handled as `u32`.
+
+use super::{Column, PointBlock};
+use crate::error::{PcdError, Result};
+use crate::header::ValueType;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::vec::Vec;
+
+const LABEL: &str = "label";
+
+impl PointBlock {
+    /// Replace each label found in `map` with its value, e.g. Semantic
+    /// KITTI's raw ids with training ids; other labels are kept. Takes a
+    /// `&HashMap<u32, u32>` or `&BTreeMap<u32, u32>`. Fails if there is no
+    /// integer `label` column or a new label does not fit its type, leaving
+    /// the block unchanged.
+    pub fn remap_labels<'a>(
+        &mut self,
+        map: impl IntoIterator<Item = (&'a u32, &'a u32)>,
+    ) -> Result<()> {
+        let map: BTreeMap<u32, u32> = map.into_iter().map(|(&k, &v)| (k, v)).collect();
+        let column = self
+            .get_column_mut(LABEL)
+            .ok_or_else(|| PcdError::InvalidDataFormat(format!("Missing column {}", LABEL)))?;
+        let value_type = column.value_type();
+        macro_rules! remap {
+            ($v:expr, $t:ty) => {{
+                let mapped = $v
+                    .iter()
+                    .map(|&l| match map.get(&(l as u32)) {
+                        Some(&to) => <$t>::try_from(to).map_err(|_| {
+                            PcdError::InvalidDataFormat(format!(
+                                "Label {} does not fit column {} of type {:?}",
+                                to, LABEL, value_type
+                            ))
+                        }),
+                        None => Ok(l),
+                    })
+                    .collect::<Result<Vec<$t>>>()?;
+                *$v = mapped;
+            }};
+        }
+        match column {
+            Column::U8(v) => remap!(v, u8),
+            Column::U16(v) => remap!(v, u16),
+            Column::U32(v) => remap!(v, u32),
+            Column::U64(v) => remap!(v, u64),
+            Column::I8(v) => remap!(v, i8),
+            Column::I16(v) => remap!(v, i16),
+            Column::I32(v) => remap!(v, i32),
+            Column::I64(v) => remap!(v, i64),
+            Column::F32(_) | Column::F64(_) => return Err(not_integer(value_type)),
+        }
+        Ok(())
+    }
+
+    /// Number of points per label.
+    pub fn label_histogram(&self) -> Result<BTreeMap<u32, usize>> {
+        let mut histogram = BTreeMap::new();
+        for label in self.labels()? {
+            *histogram.entry(label).or_insert(0) += 1;
+        }
+        Ok(histogram)
+    }
+
+    /// One block per label with its points, in order, keyed by label.
+    pub fn split_by_label(&self) -> Result<BTreeMap<u32, PointBlock>> {
+        let labels = self.labels()?;
+        let mut classes: Vec<u32> = labels.clone();
+        classes.sort_unstable();
+        classes.dedup();
+        Ok(classes
+            .into_iter()
+            .map(|class| {
+                let keep: Vec<bool> = labels.iter().map(|&l| l == class).collect();
+                (class, self.filter(&keep))
+            })
+            .collect())
+    }
+
+    /// The `label` of each point as `u32`.
+    fn labels(&self) -> Result<Vec<u32>> {
+        let column = self
+            .get_column(LABEL)
+            .ok_or_else(|| PcdError::InvalidDataFormat(format!("Missing column {}", LABEL)))?;
+        if self.values_per_point(LABEL) != 1 {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Column {} has more than one value per point",
+                LABEL
+            )));
+        }
+        Ok(match column {
+            Column::U8(v) => v.iter().map(|&l| l as u32).collect(),
+            Column::U16(v) => v.iter().map(|&l| l as u32).collect(),
+            Column::U32(v) => v.clone(),
+            Column::U64(v) => v.iter().map(|&l| l as u32).collect(),
+            Column::I8(v) => v.iter().map(|&l| l as u32).collect(),
+            Column::I16(v) => v.iter().map(|&l| l as u32).collect(),
+            Column::I32(v) => v.iter().map(|&l| l as u32).collect(),
+            Column::I64(v) => v.iter().map(|&l| l as u32).collect(),
+            Column::F32(_) | Column::F64(_) => return Err(not_integer(column.value_type())),
+        })
+    }
+}
+
+fn not_integer(value_type: ValueType) -> PcdError {
+    PcdError::InvalidDataFormat(format!(
+        "Column {} holds {:?}, not integer labels",
+        LABEL, value_type
+    ))
+}
@@ -12,20 +12,64 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::header::ValueType;
-use std::collections::HashMap;
-
+use crate::error::{PcdError, Result};
+use crate::header::{Schema, SchemaField, ValueType};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+pub mod cast;
+pub mod cloud;
+pub mod colormap;
+pub mod dedup;
+pub mod diff;
+pub mod filter;
+#[cfg(feature = "std")]
+pub mod ground;
+pub mod labels;
+pub mod normalize;
+pub mod rgb;
+pub mod rings;
+pub mod sampling;
+pub mod stats;
+pub mod strided;
+pub mod transform;
+pub mod typed;
+pub mod units;
+#[cfg(feature = "std")]
 pub mod view;
+pub mod voxel;
+pub use cast::Rounding;
+pub use cloud::PointCloud;
+pub use colormap::Colormap;
+pub use diff::{CloudDiff, FieldDiff, Tolerance, diff_blocks};
+pub use filter::{
+    BoundingBox, CompareOp, CropBox, PointFilter, Predicate, crop_box, pass_through,
+};
+#[cfg(feature = "std")]
+pub use ground::{GroundMethod, GroundSegmentation, segment_ground};
+pub use normalize::Normalization;
+pub use sampling::{FarthestPointSampler, farthest_point_sample};
+pub use stats::{ColumnStats, Histogram};
+pub use strided::{StridedColumnView, StridedIter, StridedValue};
+pub use typed::ColumnValue;
+pub use units::LengthUnit;
+#[cfg(feature = "std")]
 pub use view::{ColumnView, PointView};
+pub use voxel::{Aggregation, VoxelGrid, voxel_grid};
 
 #[derive(Debug, Clone)]
 pub enum Column {
     U8(Vec<u8>),
     U16(Vec<u16>),
     U32(Vec<u32>),
+    U64(Vec<u64>),
     I8(Vec<i8>),
     I16(Vec<i16>),
     I32(Vec<i32>),
+    I64(Vec<i64>),
     F32(Vec<f32>),
     F64(Vec<f64>),
 }
@@ -36,9 +80,11 @@ impl Column {
             ValueType::U8 => Column::U8(vec![0; capacity]),
             ValueType::U16 => Column::U16(vec![0; capacity]),
             ValueType::U32 => Column::U32(vec![0; capacity]),
+            ValueType::U64 => Column::U64(vec![0; capacity]),
             ValueType::I8 => Column::I8(vec![0; capacity]),
             ValueType::I16 => Column::I16(vec![0; capacity]),
             ValueType::I32 => Column::I32(vec![0; capacity]),
+            ValueType::I64 => Column::I64(vec![0; capacity]),
             ValueType::F32 => Column::F32(vec![0.0; capacity]),
             ValueType::F64 => Column::F64(vec![0.0; capacity]),
         }
@@ -49,23 +95,106 @@ impl Column {
             Column::U8(v) => v.resize(new_len, 0),
             Column::U16(v) => v.resize(new_len, 0),
             Column::U32(v) => v.resize(new_len, 0),
+            Column::U64(v) => v.resize(new_len, 0),
             Column::I8(v) => v.resize(new_len, 0),
             Column::I16(v) => v.resize(new_len, 0),
             Column::I32(v) => v.resize(new_len, 0),
+            Column::I64(v) => v.resize(new_len, 0),
             Column::F32(v) => v.resize(new_len, 0.0),
             Column::F64(v) => v.resize(new_len, 0.0),
         }
     }
 
+    /// Copy of the elements in `range`.
+    #[must_use]
+    pub fn slice(&self, range: core::ops::Range<usize>) -> Column {
+        match self {
+            Column::U8(v) => Column::U8(v[range].to_vec()),
+            Column::U16(v) => Column::U16(v[range].to_vec()),
+            Column::U32(v) => Column::U32(v[range].to_vec()),
+            Column::U64(v) => Column::U64(v[range].to_vec()),
+            Column::I8(v) => Column::I8(v[range].to_vec()),
+            Column::I16(v) => Column::I16(v[range].to_vec()),
+            Column::I32(v) => Column::I32(v[range].to_vec()),
+            Column::I64(v) => Column::I64(v[range].to_vec()),
+            Column::F32(v) => Column::F32(v[range].to_vec()),
+            Column::F64(v) => Column::F64(v[range].to_vec()),
+        }
+    }
+
+    /// Append the elements of `other`, which must have the same type.
+    pub fn extend_from(&mut self, other: &Column) -> Result<()> {
+        match (self, other) {
+            (Column::U8(a), Column::U8(b)) => a.extend_from_slice(b),
+            (Column::U16(a), Column::U16(b)) => a.extend_from_slice(b),
+            (Column::U32(a), Column::U32(b)) => a.extend_from_slice(b),
+            (Column::U64(a), Column::U64(b)) => a.extend_from_slice(b),
+            (Column::I8(a), Column::I8(b)) => a.extend_from_slice(b),
+            (Column::I16(a), Column::I16(b)) => a.extend_from_slice(b),
+            (Column::I32(a), Column::I32(b)) => a.extend_from_slice(b),
+            (Column::I64(a), Column::I64(b)) => a.extend_from_slice(b),
+            (Column::F32(a), Column::F32(b)) => a.extend_from_slice(b),
+            (Column::F64(a), Column::F64(b)) => a.extend_from_slice(b),
+            (a, b) => {
+                return Err(PcdError::InvalidDataFormat(format!(
+                    "Cannot append {:?} values to a {:?} column",
+                    b.value_type(),
+                    a.value_type()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The element type stored in this column.
+    #[must_use]
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Column::U8(_) => ValueType::U8,
+            Column::U16(_) => ValueType::U16,
+            Column::U32(_) => ValueType::U32,
+            Column::U64(_) => ValueType::U64,
+            Column::I8(_) => ValueType::I8,
+            Column::I16(_) => ValueType::I16,
+            Column::I32(_) => ValueType::I32,
+            Column::I64(_) => ValueType::I64,
+            Column::F32(_) => ValueType::F32,
+            Column::F64(_) => ValueType::F64,
+        }
+    }
+
+    /// Value `idx` as f64, whatever the column's type.
+    ///
+    /// # Panics
+    ///
+    /// If `idx` is not below [`len`](Self::len).
+    #[must_use]
+    pub fn value_f64(&self, idx: usize) -> f64 {
+        match self {
+            Column::U8(v) => v[idx] as f64,
+            Column::U16(v) => v[idx] as f64,
+            Column::U32(v) => v[idx] as f64,
+            Column::U64(v) => v[idx] as f64,
+            Column::I8(v) => v[idx] as f64,
+            Column::I16(v) => v[idx] as f64,
+            Column::I32(v) => v[idx] as f64,
+            Column::I64(v) => v[idx] as f64,
+            Column::F32(v) => v[idx] as f64,
+            Column::F64(v) => v[idx],
+        }
+    }
+
     #[must_use]
     pub fn len(&self) -> usize {
         match self {
             Column::U8(v) => v.len(),
             Column::U16(v) => v.len(),
             Column::U32(v) => v.len(),
+            Column::U64(v) => v.len(),
             Column::I8(v) => v.len(),
             Column::I16(v) => v.len(),
             Column::I32(v) => v.len(),
+            Column::I64(v) => v.len(),
             Column::F32(v) => v.len(),
             Column::F64(v) => v.len(),
         }
@@ -107,6 +236,13 @@ impl Column {
             None
         }
     }
+    pub fn as_u64_mut(&mut self) -> Option<&mut Vec<u64>> {
+        if let Column::U64(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
     pub fn as_i8_mut(&mut self) -> Option<&mut Vec<i8>> {
         if let Column::I8(v) = self {
             Some(v)
@@ -128,6 +264,13 @@ impl Column {
             None
         }
     }
+    pub fn as_i64_mut(&mut self) -> Option<&mut Vec<i64>> {
+        if let Column::I64(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
     pub fn as_f32_mut(&mut self) -> Option<&mut Vec<f32>> {
         if let Column::F32(v) = self {
             Some(v)
@@ -165,6 +308,13 @@ impl Column {
             None
         }
     }
+    pub fn as_u64(&self) -> Option<&[u64]> {
+        if let Column::U64(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
     pub fn as_i8(&self) -> Option<&[i8]> {
         if let Column::I8(v) = self {
             Some(v)
@@ -186,6 +336,13 @@ impl Column {
             None
         }
     }
+    pub fn as_i64(&self) -> Option<&[i64]> {
+        if let Column::I64(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
     pub fn as_f32(&self) -> Option<&[f32]> {
         if let Column::F32(v) = self {
             Some(v)
@@ -201,16 +358,88 @@ impl Column {
         }
     }
 
-    // Unsafe methods to get mutable slice for parallel writing.
-    // Safety: Caller must ensure exclusive access to the slice regions if writing in parallel.
+    /// Append element `idx` to `out` as little-endian bytes.
+    #[cfg(feature = "std")]
+    pub(crate) fn push_le_bytes(&self, idx: usize, out: &mut Vec<u8>) {
+        match self {
+            Column::U8(v) => out.push(v[idx]),
+            Column::U16(v) => out.extend_from_slice(&v[idx].to_le_bytes()),
+            Column::U32(v) => out.extend_from_slice(&v[idx].to_le_bytes()),
+            Column::U64(v) => out.extend_from_slice(&v[idx].to_le_bytes()),
+            Column::I8(v) => out.extend_from_slice(&v[idx].to_le_bytes()),
+            Column::I16(v) => out.extend_from_slice(&v[idx].to_le_bytes()),
+            Column::I32(v) => out.extend_from_slice(&v[idx].to_le_bytes()),
+            Column::I64(v) => out.extend_from_slice(&v[idx].to_le_bytes()),
+            Column::F32(v) => out.extend_from_slice(&v[idx].to_le_bytes()),
+            Column::F64(v) => out.extend_from_slice(&v[idx].to_le_bytes()),
+        }
+    }
+
+    /// Scatter elements as little-endian bytes into the records of `out`,
+    /// each `stride` bytes long: record `r` gets elements
+    /// `(first + r) * count..` at byte `offset`.
+    #[cfg(feature = "std")]
+    pub(crate) fn write_le_strided(
+        &self,
+        first: usize,
+        count: usize,
+        out: &mut [u8],
+        stride: usize,
+        offset: usize,
+    ) {
+        macro_rules! scatter {
+            ($v:expr, $n:expr) => {{
+                let values = $v[first * count..].chunks_exact(count);
+                for (record, values) in out.chunks_exact_mut(stride).zip(values) {
+                    let bytes = &mut record[offset..offset + count * $n];
+                    for (dst, x) in bytes.chunks_exact_mut($n).zip(values) {
+                        dst.copy_from_slice(&x.to_le_bytes());
+                    }
+                }
+            }};
+        }
+        match self {
+            Column::U8(v) => scatter!(v, 1),
+            Column::U16(v) => scatter!(v, 2),
+            Column::U32(v) => scatter!(v, 4),
+            Column::U64(v) => scatter!(v, 8),
+            Column::I8(v) => scatter!(v, 1),
+            Column::I16(v) => scatter!(v, 2),
+            Column::I32(v) => scatter!(v, 4),
+            Column::I64(v) => scatter!(v, 8),
+            Column::F32(v) => scatter!(v, 4),
+            Column::F64(v) => scatter!(v, 8),
+        }
+    }
+
+    /// The column's memory as bytes, which on little-endian targets are the
+    /// little-endian encoding of its values.
+    #[cfg(target_endian = "little")]
+    pub(crate) fn as_le_bytes_mut(&mut self) -> &mut [u8] {
+        // Safety: the bytes are borrowed exclusively for the slice's
+        // lifetime, and every bit pattern is a valid value of each element
+        // type.
+        unsafe {
+            let (ptr, len) = self.as_ptr_mut();
+            core::slice::from_raw_parts_mut(ptr, len)
+        }
+    }
+
+    /// Raw byte pointer and byte length of the column buffer, for parallel writing.
+    ///
+    /// # Safety
+    ///
+    /// Caller must ensure exclusive access to the slice regions if writing in parallel.
     pub unsafe fn as_ptr_mut(&mut self) -> (*mut u8, usize) {
         match self {
-            Column::U8(v) => (v.as_mut_ptr() as *mut u8, v.len() * 1),
+            Column::U8(v) => (v.as_mut_ptr(), v.len()),
             Column::U16(v) => (v.as_mut_ptr() as *mut u8, v.len() * 2),
             Column::U32(v) => (v.as_mut_ptr() as *mut u8, v.len() * 4),
-            Column::I8(v) => (v.as_mut_ptr() as *mut u8, v.len() * 1),
+            Column::U64(v) => (v.as_mut_ptr() as *mut u8, v.len() * 8),
+            Column::I8(v) => (v.as_mut_ptr() as *mut u8, v.len()),
             Column::I16(v) => (v.as_mut_ptr() as *mut u8, v.len() * 2),
             Column::I32(v) => (v.as_mut_ptr() as *mut u8, v.len() * 4),
+            Column::I64(v) => (v.as_mut_ptr() as *mut u8, v.len() * 8),
             Column::F32(v) => (v.as_mut_ptr() as *mut u8, v.len() * 4),
             Column::F64(v) => (v.as_mut_ptr() as *mut u8, v.len() * 8),
         }
@@ -219,55 +448,52 @@ impl Column {
 
 /// SoA (Structure of Arrays) storage for point cloud data.
 /// 
-/// Internally uses Vec<Column> for O(1) index-based access, with a HashMap
+/// Internally uses Vec<Column> for O(1) index-based access, with a BTreeMap
 /// for name-based lookups. This provides efficient iteration while maintaining
 /// backwards-compatible named access.
-#[derive(Debug)]
+#[derive(Debug, Clone, Default)]
 pub struct PointBlock {
     /// Column data stored in schema order for O(1) indexed access
     columns: Vec<Column>,
-    /// Field names in schema order
-    schema: Vec<String>,
+    /// Names, types and counts of the columns, in order
+    schema: Schema,
     /// Name to index mapping for backwards-compatible get_column(name) API
-    name_to_index: HashMap<String, usize>,
+    name_to_index: BTreeMap<String, usize>,
     /// Number of points
     pub len: usize,
 }
 
-impl Default for PointBlock {
-    fn default() -> Self {
-        Self {
-            columns: Vec::new(),
-            schema: Vec::new(),
-            name_to_index: HashMap::new(),
-            len: 0,
-        }
-    }
-}
-
 impl PointBlock {
-    pub fn new(schema: &Vec<(String, ValueType)>, capacity: usize) -> Self {
+    /// A block of `capacity` zeroed points, with `capacity * count` values
+    /// per column. `schema` can also be a slice of `(name, type)` pairs.
+    ///
+    /// # Panics
+    ///
+    /// If `capacity * count` overflows, like `Vec::with_capacity`.
+    pub fn new(schema: impl Into<Schema>, capacity: usize) -> Self {
+        let schema = schema.into();
         let mut columns = Vec::with_capacity(schema.len());
-        let mut names = Vec::with_capacity(schema.len());
-        let mut name_to_index = HashMap::with_capacity(schema.len());
-
-        for (i, (name, dtype)) in schema.iter().enumerate() {
-            columns.push(Column::new(*dtype, capacity));
-            names.push(name.clone());
-            name_to_index.insert(name.clone(), i);
+        let mut name_to_index = BTreeMap::new();
+
+        for (i, field) in schema.iter().enumerate() {
+            let values = capacity
+                .checked_mul(field.count)
+                .expect("capacity overflow");
+            columns.push(Column::new(field.type_, values));
+            name_to_index.insert(field.name.clone(), i);
         }
 
         PointBlock {
             columns,
-            schema: names,
+            schema,
             name_to_index,
             len: capacity,
         }
     }
 
     pub fn resize(&mut self, new_len: usize) {
-        for col in &mut self.columns {
-            col.resize(new_len);
+        for (col, field) in self.columns.iter_mut().zip(&self.schema) {
+            col.resize(new_len * field.count);
         }
         self.len = new_len;
     }
@@ -309,12 +535,27 @@ impl PointBlock {
         self.name_to_index.get(name).copied()
     }
 
-    /// Get the schema (field names in order).
+    /// Names, types and counts of the columns, in order.
     #[must_use]
-    pub fn schema(&self) -> &[String] {
+    pub fn schema(&self) -> &Schema {
         &self.schema
     }
 
+    /// Owned copy of the schema, for a block of the same shape. Types come
+    /// from the columns, so a column replaced through
+    /// [`columns_mut`](Self::columns_mut) is reported as it now is.
+    #[must_use]
+    pub fn to_schema(&self) -> Schema {
+        self.schema
+            .iter()
+            .zip(&self.columns)
+            .map(|(field, column)| SchemaField {
+                type_: column.value_type(),
+                ..field.clone()
+            })
+            .collect()
+    }
+
     /// Number of columns.
     #[must_use]
     pub fn num_columns(&self) -> usize {
@@ -356,6 +597,109 @@ impl PointBlock {
         Some(results)
     }
 
+    /// Append the points of `other`, matching columns by name. Both blocks
+    /// must have the same fields with the same types and counts.
+    pub fn append(&mut self, other: &PointBlock) -> Result<()> {
+        if other.num_columns() != self.num_columns() {
+            return Err(PcdError::LayoutMismatch {
+                expected: self.num_columns(),
+                got: other.num_columns(),
+            });
+        }
+        let mut sources = Vec::with_capacity(self.columns.len());
+        for (field, column) in self.schema.iter().zip(&self.columns) {
+            let name = &field.name;
+            let source = other.get_column(name).ok_or_else(|| {
+                PcdError::InvalidDataFormat(format!("Missing column {}", name))
+            })?;
+            if source.value_type() != column.value_type() {
+                return Err(PcdError::InvalidDataFormat(format!(
+                    "Column {} is {:?}, expected {:?}",
+                    name,
+                    source.value_type(),
+                    column.value_type()
+                )));
+            }
+            let count = other.values_per_point(name);
+            if count != field.count {
+                return Err(PcdError::InvalidDataFormat(format!(
+                    "Column {} has count {}, expected {}",
+                    name, count, field.count
+                )));
+            }
+            sources.push(source);
+        }
+        for (column, source) in self.columns.iter_mut().zip(sources) {
+            column.extend_from(source)?;
+        }
+        self.len += other.len;
+        Ok(())
+    }
+
+    /// Add `column` after the existing ones; fails if `name` is taken.
+    /// Its count is `column.len() / self.len`, or 1 for an empty block.
+    pub fn add_column(&mut self, name: &str, column: Column) -> Result<()> {
+        if self.name_to_index.contains_key(name) {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Column {} already exists",
+                name
+            )));
+        }
+        self.name_to_index.insert(name.to_string(), self.columns.len());
+        self.schema.push(SchemaField {
+            name: name.to_string(),
+            type_: column.value_type(),
+            count: column.len().checked_div(self.len).unwrap_or(1).max(1),
+        });
+        self.columns.push(column);
+        Ok(())
+    }
+
+    /// Remove column `name` and return it, or `None` if there is none.
+    pub fn remove_column(&mut self, name: &str) -> Option<Column> {
+        let index = self.name_to_index.remove(name)?;
+        self.schema.remove(index);
+        for i in self.name_to_index.values_mut() {
+            if *i > index {
+                *i -= 1;
+            }
+        }
+        Some(self.columns.remove(index))
+    }
+
+    /// A new block with the points in `range`.
+    ///
+    /// # Panics
+    ///
+    /// If `range` is not within `0..self.len`.
+    #[must_use]
+    pub fn slice(&self, range: core::ops::Range<usize>) -> PointBlock {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "point range out of bounds"
+        );
+        PointBlock {
+            columns: self
+                .columns
+                .iter()
+                .zip(&self.schema)
+                .map(|(column, field)| {
+                    column.slice(range.start * field.count..range.end * field.count)
+                })
+                .collect(),
+            schema: self.schema.clone(),
+            name_to_index: self.name_to_index.clone(),
+            len: range.end - range.start,
+        }
+    }
+
+    /// Values stored per point in column `name`, i.e. its field's `COUNT`;
+    /// 1 if there is no such column.
+    pub(crate) fn values_per_point(&self, name: &str) -> usize {
+        self.schema.get(name).map_or(1, |field| field.count)
+    }
+
+
     /// Access underlying columns slice (for iteration).
     #[must_use]
     pub fn columns(&self) -> &[Column] {
@@ -372,7 +716,8 @@ impl PointBlock {
     // ========================
 
     /// Get XYZ coordinates as f32 slices.
-    /// Returns None if any of x, y, z columns are missing or not F32.
+    /// Returns None if any of x, y, z columns are missing or not F32; see
+    /// [`xyz_f64`](Self::xyz_f64) and [`xyz_as`](Self::xyz_as) for others.
     #[must_use]
     pub fn xyz(&self) -> Option<(&[f32], &[f32], &[f32])> {
         let x = self.get_column("x")?.as_f32()?;
@@ -381,9 +726,21 @@ impl PointBlock {
         Some((x, y, z))
     }
 
+    /// Get XYZ coordinates as f64 slices, as georeferenced clouds store them.
+    /// Returns None if any of x, y, z columns are missing or not F64; see
+    /// [`xyz_as`](Self::xyz_as) to convert.
+    #[must_use]
+    pub fn xyz_f64(&self) -> Option<(&[f64], &[f64], &[f64])> {
+        let x = self.get_column("x")?.as_f64()?;
+        let y = self.get_column("y")?.as_f64()?;
+        let z = self.get_column("z")?.as_f64()?;
+        Some((x, y, z))
+    }
+
     /// Get XYZ + intensity as f32 slices.
     /// Returns None if any column is missing or has wrong type.
     #[must_use]
+    #[allow(clippy::type_complexity)]
     pub fn xyzi(&self) -> Option<(&[f32], &[f32], &[f32], &[f32])> {
         let x = self.get_column("x")?.as_f32()?;
         let y = self.get_column("y")?.as_f32()?;
@@ -393,8 +750,10 @@ impl PointBlock {
     }
 
     /// Get XYZ + RGB (packed as u32) slices.
-    /// Returns None if any column is missing or has wrong type.
+    /// Returns None if any column is missing or has wrong type; for PCL's
+    /// `F32` packing use [`xyzrgb_unpacked`](Self::xyzrgb_unpacked).
     #[must_use]
+    #[allow(clippy::type_complexity)]
     pub fn xyzrgb(&self) -> Option<(&[f32], &[f32], &[f32], &[u32])> {
         let x = self.get_column("x")?.as_f32()?;
         let y = self.get_column("y")?.as_f32()?;
@@ -408,6 +767,7 @@ impl PointBlock {
     /// - intensity: F32
     /// - ring: U16
     #[must_use]
+    #[allow(clippy::type_complexity)]
     pub fn xyzir(&self) -> Option<(&[f32], &[f32], &[f32], &[f32], &[u16])> {
         let x = self.get_column("x")?.as_f32()?;
         let y = self.get_column("y")?.as_f32()?;
@@ -423,6 +783,7 @@ impl PointBlock {
     /// - ring: U16
     /// - timestamp: F64
     #[must_use]
+    #[allow(clippy::type_complexity)]
     pub fn xyzirt(&self) -> Option<(&[f32], &[f32], &[f32], &[f32], &[u16], &[f64])> {
         let x = self.get_column("x")?.as_f32()?;
         let y = self.get_column("y")?.as_f32()?;
@@ -440,6 +801,7 @@ impl PointBlock {
     /// - timestamp: F64
     /// - id: U32
     #[must_use]
+    #[allow(clippy::type_complexity)]
     pub fn xyzirt_id(&self) -> Option<(&[f32], &[f32], &[f32], &[f32], &[u16], &[f64], &[u32])> {
         let x = self.get_column("x")?.as_f32()?;
         let y = self.get_column("y")?.as_f32()?;
@@ -450,4 +812,37 @@ impl PointBlock {
         let id = self.get_column("id")?.as_u32()?;
         Some((x, y, z, intensity, ring, timestamp, id))
     }
+
+    /// Get XYZ + RGBA (packed as u32, `0xAARRGGBB`) slices.
+    /// Returns None if any column is missing or has wrong type.
+    #[must_use]
+    #[allow(clippy::type_complexity)]
+    pub fn xyzrgba(&self) -> Option<(&[f32], &[f32], &[f32], &[u32])> {
+        let (x, y, z) = self.xyz()?;
+        let rgba = self.get_column("rgba")?.as_u32()?;
+        Some((x, y, z, rgba))
+    }
+
+    /// Get XYZ + label (u32, as in PCL's `PointXYZL`), e.g. for segmentation.
+    /// Returns None if any column is missing or has wrong type.
+    #[must_use]
+    #[allow(clippy::type_complexity)]
+    pub fn xyzl(&self) -> Option<(&[f32], &[f32], &[f32], &[u32])> {
+        let (x, y, z) = self.xyz()?;
+        let label = self.get_column("label")?.as_u32()?;
+        Some((x, y, z, label))
+    }
+
+    /// Get XYZ + normal_x/y/z + curvature, all f32 (PCL's `PointNormal`).
+    /// Returns None if any column is missing or has wrong type.
+    #[must_use]
+    #[allow(clippy::type_complexity)]
+    pub fn xyz_normals(&self) -> Option<(&[f32], &[f32], &[f32], &[f32], &[f32], &[f32], &[f32])> {
+        let (x, y, z) = self.xyz()?;
+        let normal_x = self.get_column("normal_x")?.as_f32()?;
+        let normal_y = self.get_column("normal_y")?.as_f32()?;
+        let normal_z = self.get_column("normal_z")?.as_f32()?;
+        let curvature = self.get_column("curvature")?.as_f32()?;
+        Some((x, y, z, normal_x, normal_y, normal_z, curvature))
+    }
 }
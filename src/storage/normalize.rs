@@ -0,0 +1,105 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rescaling of a column to `0..=1`, e.g. intensities of sensors calibrated
+//! differently before feeding them to a learning pipeline.
+
+use super::{Column, ColumnStats, PointBlock};
+use crate::error::{PcdError, Result};
+use crate::header::ValueType;
+use alloc::format;
+use alloc::vec::Vec;
+
+/// How [`PointBlock::normalize_column`] picks the range mapped to `0..=1`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Normalization {
+    /// The minimum and maximum of the values.
+    MinMax,
+    /// The `low` and `high` percentiles (in `0..=100`) of the values, with
+    /// values beyond clipped, which keeps a few outliers such as
+    /// retroreflectors from squeezing everything else near 0.
+    Percentile { low: f64, high: f64 },
+}
+
+impl PointBlock {
+    /// Rescale column `name` so that the range chosen by `method` maps to
+    /// `0..=1`, and return that range. All values of multi-value fields are
+    /// rescaled together; NaN stays NaN, and a column of a single value
+    /// becomes 0. F64 columns keep their type; others, such as `U8` or `U16`
+    /// intensities, become F32.
+    ///
+    /// Fails, leaving the block unchanged, if the column is missing or the
+    /// percentiles are not ordered within `0..=100`.
+    pub fn normalize_column(&mut self, name: &str, method: Normalization) -> Result<(f64, f64)> {
+        let index = self
+            .get_column_index(name)
+            .ok_or_else(|| PcdError::InvalidDataFormat(format!("Missing column {}", name)))?;
+        let column = &mut self.columns[index];
+        let (low, high) = match method {
+            Normalization::MinMax => {
+                let stats = ColumnStats::from_column(column);
+                if !stats.has_range() {
+                    (0.0, 0.0)
+                } else {
+                    (stats.min, stats.max)
+                }
+            }
+            Normalization::Percentile { low, high } => {
+                if !(0.0 <= low && low <= high && high <= 100.0) {
+                    return Err(PcdError::InvalidDataFormat(format!(
+                        "Invalid percentiles {}..{}, expected 0 <= low <= high <= 100",
+                        low, high
+                    )));
+                }
+                let mut values: Vec<f64> = (0..column.len())
+                    .map(|i| column.value_f64(i))
+                    .filter(|v| !v.is_nan())
+                    .collect();
+                values.sort_unstable_by(f64::total_cmp);
+                (percentile(&values, low), percentile(&values, high))
+            }
+        };
+        let scale = |v: f64| {
+            if high > low {
+                ((v - low) / (high - low)).clamp(0.0, 1.0)
+            } else if v.is_nan() {
+                v
+            } else {
+                0.0
+            }
+        };
+        if column.value_type() != ValueType::F64 {
+            *column = column.cast(ValueType::F32);
+            self.schema.field_mut(index).type_ = ValueType::F32;
+        }
+        match column {
+            Column::F32(values) => values.iter_mut().for_each(|v| *v = scale(*v as f64) as f32),
+            Column::F64(values) => values.iter_mut().for_each(|v| *v = scale(*v)),
+            _ => unreachable!(),
+        }
+        Ok((low, high))
+    }
+}
+
+/// The `p`th percentile of the sorted `values`, interpolated linearly
+/// between ranks; 0 if there are none.
+fn percentile(values: &[f64], p: f64) -> f64 {
+    let Some(last) = values.len().checked_sub(1) else {
+        return 0.0;
+    };
+    let rank = p / 100.0 * last as f64;
+    let below = rank as usize;
+    let above = (below + 1).min(last);
+    values[below] + (rank - below as f64) * (values[above] - values[below])
+}
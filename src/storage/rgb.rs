@@ -0,0 +1,162 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! PCL's packed colors. PCL keeps a point's color in one 32-bit `rgb` field,
+//! `0x00RRGGBB`, or `rgba`, `0xAARRGGBB`, and usually declares it `F32`: the
+//! float holds the color's bits, it is not a number. Casting such a column
+//! (e.g. with [`Column::cast`]) or formatting it as a number destroys the
+//! color; the helpers here reinterpret the bits instead.
+
+use super::{Column, PointBlock};
+use crate::error::{PcdError, Result};
+use crate::header::ValueType;
+use alloc::format;
+use alloc::vec::Vec;
+
+/// The packed colors in `column`: `U32` values as they are, `F32` values
+/// reinterpreted bit for bit. `None` for other types.
+#[must_use]
+pub fn packed_colors(column: &Column) -> Option<Vec<u32>> {
+    match column {
+        Column::U32(v) => Some(v.clone()),
+        Column::F32(v) => Some(v.iter().map(|f| f.to_bits()).collect()),
+        _ => None,
+    }
+}
+
+/// Whether an `F32` field `name` holds packed color bits. PCL writes such
+/// fields in ASCII as the integer of their bits, since opaque `rgba` colors
+/// are NaN as floats.
+#[cfg(feature = "std")]
+pub(crate) fn is_packed_color(name: &str) -> bool {
+    matches!(name, "rgb" | "rgba")
+}
+
+/// `[r, g, b, a]` of a packed color; `a` is the top byte, 0 for `rgb`.
+#[must_use]
+pub fn unpack_color(packed: u32) -> [u8; 4] {
+    let [a, r, g, b] = packed.to_be_bytes();
+    [r, g, b, a]
+}
+
+/// The packed color `0xAARRGGBB`.
+#[must_use]
+pub fn pack_color(r: u8, g: u8, b: u8, a: u8) -> u32 {
+    u32::from_be_bytes([a, r, g, b])
+}
+
+impl PointBlock {
+    /// Replace the packed `rgba` column, or else `rgb`, with `U8` columns
+    /// `r`, `g`, `b`, and `a` for `rgba`, added after the others. The packed
+    /// column may be `U32` or `F32`.
+    pub fn unpack_rgb(&mut self) -> Result<()> {
+        let name = if self.get_column("rgba").is_some() {
+            "rgba"
+        } else {
+            "rgb"
+        };
+        let column = self
+            .get_column(name)
+            .ok_or_else(|| PcdError::InvalidDataFormat(format!("Missing column {}", name)))?;
+        let packed = packed_colors(column).ok_or_else(|| {
+            PcdError::InvalidDataFormat(format!(
+                "Column {} holds {:?}, not packed colors",
+                name,
+                column.value_type()
+            ))
+        })?;
+        let channels: &[&str] = if name == "rgba" {
+            &["r", "g", "b", "a"]
+        } else {
+            &["r", "g", "b"]
+        };
+        if let Some(taken) = channels.iter().find(|c| self.get_column(c).is_some()) {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Column {} already exists",
+                taken
+            )));
+        }
+        let colors: Vec<[u8; 4]> = packed.into_iter().map(unpack_color).collect();
+        for (i, channel) in channels.iter().enumerate() {
+            self.add_column(channel, Column::U8(colors.iter().map(|c| c[i]).collect()))?;
+        }
+        self.remove_column(name);
+        Ok(())
+    }
+
+    /// Replace `U8` columns `r`, `g`, `b` with one packed `rgb` column of
+    /// type `type_`, `U32` or `F32` (PCL's usual choice, the bits of the
+    /// color). With an `a` column too, it is packed as well, into `rgba`;
+    /// otherwise the alpha byte is 0.
+    pub fn pack_rgb(&mut self, type_: ValueType) -> Result<()> {
+        if !matches!(type_, ValueType::U32 | ValueType::F32) {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Packed colors as {:?}, not U32 or F32",
+                type_
+            )));
+        }
+        let channel = |name: &str| -> Result<&[u8]> {
+            let column = self
+                .get_column(name)
+                .ok_or_else(|| PcdError::InvalidDataFormat(format!("Missing column {}", name)))?;
+            column.as_u8().ok_or_else(|| {
+                PcdError::InvalidDataFormat(format!(
+                    "Column {} holds {:?}, not U8",
+                    name,
+                    column.value_type()
+                ))
+            })
+        };
+        let (r, g, b) = (channel("r")?, channel("g")?, channel("b")?);
+        let a = match self.get_column("a") {
+            Some(_) => Some(channel("a")?),
+            None => None,
+        };
+        let name = if a.is_some() { "rgba" } else { "rgb" };
+        if self.get_column(name).is_some() {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Column {} already exists",
+                name
+            )));
+        }
+        let packed: Vec<u32> = (0..r.len())
+            .map(|i| pack_color(r[i], g[i], b[i], a.map_or(0, |a| a[i])))
+            .collect();
+        let column = match type_ {
+            ValueType::F32 => Column::F32(packed.into_iter().map(f32::from_bits).collect()),
+            _ => Column::U32(packed),
+        };
+        self.add_column(name, column)?;
+        for channel in ["r", "g", "b", "a"] {
+            self.remove_column(channel);
+        }
+        Ok(())
+    }
+
+    /// XYZ with `[r, g, b]` from the packed `rgb` column, `U32` or `F32`.
+    /// Returns None if any column is missing or has wrong type.
+    #[must_use]
+    #[allow(clippy::type_complexity)]
+    pub fn xyzrgb_unpacked(&self) -> Option<(&[f32], &[f32], &[f32], Vec<[u8; 3]>)> {
+        let (x, y, z) = self.xyz()?;
+        let rgb = packed_colors(self.get_column("rgb")?)?
+            .into_iter()
+            .map(|c| {
+                let [r, g, b, _] = unpack_color(c);
+                [r, g, b]
+            })
+            .collect();
+        Some((x, y, z, rgb))
+    }
+}
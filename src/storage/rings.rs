@@ -0,0 +1,120 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Scan-line structure of LiDAR sweeps from the `ring` column, the laser
+//! that measured each point, as in XYZIR and XYZIRT clouds: counting and
+//! splitting points per ring, and ordering each ring by azimuth for
+//! scan-line algorithms such as LOAM's feature extraction.
+
+use super::{Column, PointBlock};
+use crate::error::{PcdError, Result};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+const RING: &str = "ring";
+
+impl PointBlock {
+    /// Number of points per ring.
+    pub fn ring_counts(&self) -> Result<BTreeMap<u16, usize>> {
+        let mut counts = BTreeMap::new();
+        for ring in self.rings()? {
+            *counts.entry(ring).or_insert(0) += 1;
+        }
+        Ok(counts)
+    }
+
+    /// One block per ring with its points, in order, keyed by ring.
+    pub fn split_by_ring(&self) -> Result<BTreeMap<u16, PointBlock>> {
+        Ok(self
+            .ring_indices(false)?
+            .into_iter()
+            .map(|(ring, indices)| (ring, self.take(&indices)))
+            .collect())
+    }
+
+    /// The indices of the points of each ring ordered by azimuth,
+    /// counterclockwise seen from above from the `+x` axis, the scan lines
+    /// of the sweep. Points with a NaN `x` or `y` come last in their ring.
+    /// Fails if there is no integer `ring` column or `x`, `y` or `z` is
+    /// missing.
+    pub fn scan_lines(&self) -> Result<BTreeMap<u16, Vec<usize>>> {
+        self.ring_indices(true)
+    }
+
+    /// The block ordered ring by ring, each by azimuth as in
+    /// [`scan_lines`](Self::scan_lines).
+    pub fn sort_by_ring(&self) -> Result<PointBlock> {
+        let order: Vec<usize> = self.scan_lines()?.into_values().flatten().collect();
+        Ok(self.take(&order))
+    }
+
+    fn ring_indices(&self, by_azimuth: bool) -> Result<BTreeMap<u16, Vec<usize>>> {
+        let mut rings: BTreeMap<u16, Vec<usize>> = BTreeMap::new();
+        for (i, ring) in self.rings()?.into_iter().enumerate() {
+            rings.entry(ring).or_default().push(i);
+        }
+        if by_azimuth {
+            let azimuths: Vec<f64> = self
+                .iter_xyz()
+                .ok_or_else(|| PcdError::InvalidDataFormat("Missing column x, y or z".to_string()))?
+                .map(|[x, y, _]| pseudo_azimuth(x, y))
+                .collect();
+            for indices in rings.values_mut() {
+                indices.sort_by(|&a, &b| azimuths[a].total_cmp(&azimuths[b]));
+            }
+        }
+        Ok(rings)
+    }
+
+    /// The `ring` of each point.
+    fn rings(&self) -> Result<Vec<u16>> {
+        let column = self
+            .get_column(RING)
+            .ok_or_else(|| PcdError::InvalidDataFormat(format!("Missing column {}", RING)))?;
+        if self.values_per_point(RING) != 1 || matches!(column, Column::F32(_) | Column::F64(_)) {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Column {} holds {:?}, not one integer per point",
+                RING,
+                column.value_type()
+            )));
+        }
+        (0..self.len)
+            .map(|i| {
+                let ring = column.value_f64(i);
+                u16::try_from(ring as i64).map_err(|_| {
+                    PcdError::InvalidDataFormat(format!("Ring {} is out of range", ring))
+                })
+            })
+            .collect()
+    }
+}
+
+/// A value in `0..4` increasing with the angle of `(x, y)` from the `+x`
+/// axis over `0..2π`, without trigonometry; NaN if either is NaN.
+fn pseudo_azimuth(x: f64, y: f64) -> f64 {
+    let sum = x.abs() + y.abs();
+    if sum == 0.0 {
+        return 0.0;
+    }
+    let p = y / sum;
+    if x < 0.0 {
+        2.0 - p
+    } else if y < 0.0 {
+        4.0 + p
+    } else {
+        p
+    }
+}
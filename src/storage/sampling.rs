@@ -0,0 +1,138 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Farthest point sampling: picking points that each lie farthest from
+//! those already picked, for an even spread over the cloud, as
+//! deep-learning pipelines use before grouping points.
+
+use super::PointBlock;
+use crate::error::{PcdError, Result};
+use alloc::collections::BTreeMap;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Farthest point sampling of `n` points, starting from the first point.
+/// Points with a NaN or infinite coordinate are never picked; a block with
+/// fewer than `n` other points gives all of them.
+///
+/// Exact sampling takes time proportional to `n` times the number of
+/// points. [`bucketed`](Self::bucketed) sampling picks from one point per
+/// voxel instead, with voxels sized so that there are a few times `n` of
+/// them: much faster on dense clouds, with picks off by at most a voxel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FarthestPointSampler {
+    n: usize,
+    bucketed: bool,
+}
+
+impl FarthestPointSampler {
+    pub fn new(n: usize) -> Self {
+        Self { n, bucketed: false }
+    }
+
+    /// Sample one point per voxel bucket, see above.
+    #[must_use]
+    pub fn bucketed(mut self, bucketed: bool) -> Self {
+        self.bucketed = bucketed;
+        self
+    }
+
+    /// Indices of the sampled points of `block`, in the order picked. Fails
+    /// if `x`, `y` or `z` is missing.
+    pub fn indices(&self, block: &PointBlock) -> Result<Vec<usize>> {
+        let points: Vec<(usize, [f64; 3])> = block
+            .iter_xyz_finite()
+            .ok_or_else(|| PcdError::InvalidDataFormat("Missing column x, y or z".to_string()))?
+            .collect();
+        let candidates = if self.bucketed {
+            buckets(&points, self.n)
+        } else {
+            points
+        };
+        Ok(farthest(&candidates, self.n))
+    }
+
+    /// The sampled points of `block`, in the order picked.
+    pub fn apply(&self, block: &PointBlock) -> Result<PointBlock> {
+        Ok(block.take(&self.indices(block)?))
+    }
+}
+
+/// Exact farthest point sampling of `n` points of `block`, see
+/// [`FarthestPointSampler`].
+pub fn farthest_point_sample(block: &PointBlock, n: usize) -> Result<PointBlock> {
+    FarthestPointSampler::new(n).apply(block)
+}
+
+/// Exact sampling of `n` of `points`, giving their indices.
+fn farthest(points: &[(usize, [f64; 3])], n: usize) -> Vec<usize> {
+    let n = n.min(points.len());
+    let mut picked = Vec::with_capacity(n);
+    // Squared distance of each point to the nearest picked one.
+    let mut nearest = vec![f64::INFINITY; points.len()];
+    let mut next = 0;
+    while picked.len() < n {
+        let (index, p) = points[next];
+        picked.push(index);
+        // Never picked again, even with duplicates at distance 0.
+        nearest[next] = f64::NEG_INFINITY;
+        let mut farthest = -1.0;
+        for (i, (d, (_, q))) in nearest.iter_mut().zip(points).enumerate() {
+            let dist: f64 = (0..3).map(|a| (p[a] - q[a]) * (p[a] - q[a])).sum();
+            *d = d.min(dist);
+            if *d > farthest {
+                farthest = *d;
+                next = i;
+            }
+        }
+    }
+    picked
+}
+
+/// The first of `points` in each occupied voxel, with voxels shrunk until
+/// there are at least four times `n` of them, or one per point.
+fn buckets(points: &[(usize, [f64; 3])], n: usize) -> Vec<(usize, [f64; 3])> {
+    let target = n.saturating_mul(4).min(points.len());
+    let mut min = [f64::INFINITY; 3];
+    let mut max = [f64::NEG_INFINITY; 3];
+    for (_, p) in points {
+        for a in 0..3 {
+            min[a] = min[a].min(p[a]);
+            max[a] = max[a].max(p[a]);
+        }
+    }
+    let extent = (0..3).map(|a| max[a] - min[a]).fold(0.0, f64::max);
+    let mut leaf = extent / 2.0;
+    // Halving 64 times takes any finite extent below what a voxel can tell.
+    for _ in 0..64 {
+        if leaf <= 0.0 {
+            break;
+        }
+        let mut voxels = BTreeMap::new();
+        for &(i, p) in points {
+            // Offsets from `min` are not negative, so truncating floors them.
+            let key = [0, 1, 2].map(|a| ((p[a] - min[a]) / leaf) as u64);
+            voxels.entry(key).or_insert((i, p));
+        }
+        if voxels.len() >= target {
+            let mut picked: Vec<_> = voxels.into_values().collect();
+            // Back in block order, so sampling starts from the first point.
+            picked.sort_unstable_by_key(|&(i, _)| i);
+            return picked;
+        }
+        leaf /= 2.0;
+    }
+    points.to_vec()
+}
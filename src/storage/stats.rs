@@ -0,0 +1,196 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-column summary statistics and histograms that can be accumulated
+//! chunk by chunk.
+
+use super::{Column, PointBlock};
+use crate::error::{PcdError, Result};
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Value count, NaN count and range of a column. NaNs are excluded from
+/// `min` and `max`, which stay at `+inf`/`-inf` until a value is seen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColumnStats {
+    pub count: usize,
+    pub nan_count: usize,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Default for ColumnStats {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            nan_count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl ColumnStats {
+    pub fn from_column(column: &Column) -> Self {
+        let mut stats = Self::default();
+        stats.update(column);
+        stats
+    }
+
+    /// Add the values of `column`.
+    pub fn update(&mut self, column: &Column) {
+        match column {
+            Column::U8(v) => self.extend(v.iter().map(|&x| x as f64)),
+            Column::U16(v) => self.extend(v.iter().map(|&x| x as f64)),
+            Column::U32(v) => self.extend(v.iter().map(|&x| x as f64)),
+            Column::U64(v) => self.extend(v.iter().map(|&x| x as f64)),
+            Column::I8(v) => self.extend(v.iter().map(|&x| x as f64)),
+            Column::I16(v) => self.extend(v.iter().map(|&x| x as f64)),
+            Column::I32(v) => self.extend(v.iter().map(|&x| x as f64)),
+            Column::I64(v) => self.extend(v.iter().map(|&x| x as f64)),
+            Column::F32(v) => self.extend(v.iter().map(|&x| x as f64)),
+            Column::F64(v) => self.extend(v.iter().copied()),
+        }
+    }
+
+    /// Combine with statistics gathered over other values.
+    pub fn merge(&mut self, other: &ColumnStats) {
+        self.count += other.count;
+        self.nan_count += other.nan_count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    /// Whether any non-NaN value was seen, i.e. `min` and `max` are valid.
+    pub fn has_range(&self) -> bool {
+        self.count > self.nan_count
+    }
+
+    fn extend(&mut self, values: impl Iterator<Item = f64>) {
+        for v in values {
+            self.count += 1;
+            if v.is_nan() {
+                self.nan_count += 1;
+            } else {
+                self.min = self.min.min(v);
+                self.max = self.max.max(v);
+            }
+        }
+    }
+}
+
+/// Counts of values in equal-width bins over `min..=max`. NaNs are not
+/// counted; values outside the range are counted in `outside` only.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    pub min: f64,
+    pub max: f64,
+    pub counts: Vec<usize>,
+    pub outside: usize,
+}
+
+impl Histogram {
+    /// An empty histogram of `bins` bins over `min..=max`, e.g. a fixed
+    /// intensity range to compare sensors. Fails if `bins` is 0 or the range
+    /// is not finite and ordered.
+    pub fn new(min: f64, max: f64, bins: usize) -> Result<Self> {
+        if bins == 0 {
+            return Err(PcdError::InvalidDataFormat(
+                "A histogram needs at least one bin".to_string(),
+            ));
+        }
+        if !(min.is_finite() && max.is_finite() && min <= max) {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Invalid histogram range {}..={}",
+                min, max
+            )));
+        }
+        Ok(Self {
+            min,
+            max,
+            counts: vec![0; bins],
+            outside: 0,
+        })
+    }
+
+    /// Width of each bin.
+    #[must_use]
+    pub fn bin_width(&self) -> f64 {
+        (self.max - self.min) / self.counts.len() as f64
+    }
+
+    /// The values bin `bin` counts, `start..end`; the last bin also
+    /// includes `max`.
+    #[must_use]
+    pub fn bin_range(&self, bin: usize) -> core::ops::Range<f64> {
+        let width = self.bin_width();
+        self.min + bin as f64 * width..self.min + (bin + 1) as f64 * width
+    }
+
+    /// Add the values of `column`.
+    pub fn update(&mut self, column: &Column) {
+        for i in 0..column.len() {
+            self.add(column.value_f64(i));
+        }
+    }
+
+    fn add(&mut self, v: f64) {
+        if v.is_nan() {
+            return;
+        }
+        if v < self.min || v > self.max {
+            self.outside += 1;
+            return;
+        }
+        let bins = self.counts.len();
+        let bin = if self.max > self.min {
+            ((v - self.min) / (self.max - self.min) * bins as f64) as usize
+        } else {
+            0
+        };
+        self.counts[bin.min(bins - 1)] += 1;
+    }
+}
+
+impl PointBlock {
+    /// Histogram of column `name` in `bins` bins over the range of its
+    /// values, all values of multi-value fields included. Without any
+    /// non-NaN value the range is `0..=0` and every count 0. Fails if the
+    /// column is missing or `bins` is 0.
+    pub fn histogram(&self, name: &str, bins: usize) -> Result<Histogram> {
+        let column = self
+            .get_column(name)
+            .ok_or_else(|| PcdError::InvalidDataFormat(format!("Missing column {}", name)))?;
+        let stats = ColumnStats::from_column(column);
+        let (min, max) = if stats.has_range() {
+            (stats.min, stats.max)
+        } else {
+            (0.0, 0.0)
+        };
+        let mut histogram = Histogram::new(min, max, bins)?;
+        histogram.update(column);
+        Ok(histogram)
+    }
+
+    /// Statistics of every column, in schema order.
+    #[must_use]
+    pub fn column_stats(&self) -> Vec<ColumnStats> {
+        self.columns()
+            .iter()
+            .map(ColumnStats::from_column)
+            .collect()
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Borrowed access to one field of array-of-structs records, as found in
+//! binary PCD data and `PointCloud2` messages: value `i` lives at
+//! `offset + i * stride` of a byte slice.
+
+use crate::error::{PcdError, Result};
+use crate::header::ValueType;
+use core::fmt;
+use core::iter::FusedIterator;
+use core::mem::size_of;
+
+/// A scalar stored in PCD records.
+pub trait StridedValue: Copy {
+    const TYPE: ValueType;
+
+    fn from_le_slice(bytes: &[u8]) -> Self;
+
+    fn from_be_slice(bytes: &[u8]) -> Self;
+}
+
+macro_rules! strided_value {
+    ($($t:ty => $v:ident),*) => {
+        $(impl StridedValue for $t {
+            const TYPE: ValueType = ValueType::$v;
+
+            fn from_le_slice(bytes: &[u8]) -> Self {
+                let mut buf = [0; size_of::<$t>()];
+                buf.copy_from_slice(&bytes[..size_of::<$t>()]);
+                <$t>::from_le_bytes(buf)
+            }
+
+            fn from_be_slice(bytes: &[u8]) -> Self {
+                let mut buf = [0; size_of::<$t>()];
+                buf.copy_from_slice(&bytes[..size_of::<$t>()]);
+                <$t>::from_be_bytes(buf)
+            }
+        })*
+    };
+}
+
+strided_value!(u8 => U8, u16 => U16, u32 => U32, u64 => U64, i8 => I8, i16 => I16,
+    i32 => I32, i64 => I64, f32 => F32, f64 => F64);
+
+/// `len` values of `T`, the `i`th read from the `width` bytes at
+/// `offset + i * stride` of `data`. Bounds are checked once on creation, so
+/// [`get`](Self::get) and iteration never fail.
+pub struct StridedColumnView<'a, T> {
+    data: &'a [u8],
+    offset: usize,
+    stride: usize,
+    len: usize,
+    width: usize,
+    read: fn(&[u8]) -> T,
+}
+
+// Derives would require `T: Clone` / `T: Debug`.
+impl<T> Clone for StridedColumnView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for StridedColumnView<'_, T> {}
+
+impl<T> fmt::Debug for StridedColumnView<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StridedColumnView")
+            .field("offset", &self.offset)
+            .field("stride", &self.stride)
+            .field("len", &self.len)
+            .field("width", &self.width)
+            .finish()
+    }
+}
+
+impl<'a, T: StridedValue> StridedColumnView<'a, T> {
+    /// Little-endian values; fails with [`PcdError::BufferTooSmall`] if
+    /// `data` ends before the last one.
+    pub fn new(data: &'a [u8], offset: usize, stride: usize, len: usize) -> Result<Self> {
+        Self::with_reader(data, offset, stride, len, size_of::<T>(), T::from_le_slice)
+    }
+
+    /// Read the values as big-endian instead.
+    #[must_use]
+    pub fn big_endian(mut self) -> Self {
+        self.read = T::from_be_slice;
+        self
+    }
+}
+
+impl<'a, T> StridedColumnView<'a, T> {
+    /// Values decoded by `read` from `width` bytes each, e.g. to widen
+    /// them to a common type.
+    pub fn with_reader(
+        data: &'a [u8],
+        offset: usize,
+        stride: usize,
+        len: usize,
+        width: usize,
+        read: fn(&[u8]) -> T,
+    ) -> Result<Self> {
+        let end = len
+            .checked_sub(1)
+            .map(|last| {
+                last.checked_mul(stride)
+                    .and_then(|n| n.checked_add(offset))
+                    .and_then(|n| n.checked_add(width))
+                    .unwrap_or(usize::MAX)
+            })
+            .unwrap_or(0);
+        if end > data.len() {
+            return Err(PcdError::BufferTooSmall {
+                expected: end,
+                got: data.len(),
+            });
+        }
+        Ok(Self {
+            data,
+            offset,
+            stride,
+            len,
+            width,
+            read,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    pub fn get(&self, i: usize) -> Option<T> {
+        (i < self.len).then(|| self.read_at(i))
+    }
+
+    pub fn iter(&self) -> StridedIter<'a, T> {
+        StridedIter {
+            view: *self,
+            front: 0,
+            back: self.len,
+        }
+    }
+
+    fn read_at(&self, i: usize) -> T {
+        let start = self.offset + i * self.stride;
+        (self.read)(&self.data[start..start + self.width])
+    }
+}
+
+impl<'a, T> IntoIterator for StridedColumnView<'a, T> {
+    type Item = T;
+    type IntoIter = StridedIter<'a, T>;
+
+    fn into_iter(self) -> StridedIter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &StridedColumnView<'a, T> {
+    type Item = T;
+    type IntoIter = StridedIter<'a, T>;
+
+    fn into_iter(self) -> StridedIter<'a, T> {
+        self.iter()
+    }
+}
+
+/// Iterator over the values of a [`StridedColumnView`].
+#[derive(Debug)]
+pub struct StridedIter<'a, T> {
+    view: StridedColumnView<'a, T>,
+    front: usize,
+    back: usize,
+}
+
+impl<T> Clone for StridedIter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            view: self.view,
+            front: self.front,
+            back: self.back,
+        }
+    }
+}
+
+impl<T> Iterator for StridedIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        Some(self.view.read_at(self.front - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.back - self.front;
+        (n, Some(n))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<T> {
+        self.front += n.min(self.back - self.front);
+        self.next()
+    }
+}
+
+impl<T> DoubleEndedIterator for StridedIter<'_, T> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.view.read_at(self.back))
+    }
+}
+
+impl<T> ExactSizeIterator for StridedIter<'_, T> {}
+
+impl<T> FusedIterator for StridedIter<'_, T> {}
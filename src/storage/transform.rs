@@ -0,0 +1,149 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rigid (and affine) transforms of point positions and normals, and
+//! scaling of positions.
+
+use super::{Column, PointBlock};
+use crate::error::{PcdError, Result};
+use crate::header::ValueType;
+use alloc::format;
+use alloc::string::String;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+const XYZ: [&str; 3] = ["x", "y", "z"];
+const NORMALS: [&str; 3] = ["normal_x", "normal_y", "normal_z"];
+
+impl PointBlock {
+    /// Apply the homogeneous transform `matrix` (`matrix[row][col]`, as
+    /// PCL's `Eigen::Matrix4f`) to `x`, `y`, `z` in place: the upper-left
+    /// 3x3 block is the rotation and the last column the translation; the
+    /// last row is ignored. `normal_x`, `normal_y`, `normal_z`, if present,
+    /// are rotated only. F64 coordinates are transformed in f64. Runs in
+    /// parallel with the `rayon` feature.
+    ///
+    /// Fails, leaving the block unchanged, if `x`, `y` or `z` is missing or
+    /// the coordinates are not all F32 or all F64.
+    pub fn transform(&mut self, matrix: &[[f32; 4]; 4]) -> Result<()> {
+        self.transform_f64(&matrix.map(|row| row.map(f64::from)))
+    }
+
+    /// [`transform`](Self::transform) with an f64 matrix, which keeps the
+    /// precision of large translations such as georeferenced offsets.
+    pub fn transform_f64(&mut self, matrix: &[[f64; 4]; 4]) -> Result<()> {
+        let m = *matrix;
+        let normals = NORMALS.iter().all(|n| self.get_column(n).is_some());
+        float_axes(self, XYZ)?;
+        if normals {
+            float_axes(self, NORMALS)?;
+        }
+        apply(self, XYZ, &m, true);
+        if normals {
+            apply(self, NORMALS, &m, false);
+        }
+        Ok(())
+    }
+
+    /// Multiply `x`, `y`, `z` by `factor` in place, about the origin;
+    /// normals are left as they are. See
+    /// [`convert_units`](Self::convert_units) to also rescale ranges.
+    ///
+    /// Fails, leaving the block unchanged, if `x`, `y` or `z` is missing or
+    /// the coordinates are not all F32 or all F64.
+    pub fn scale_xyz(&mut self, factor: f64) -> Result<()> {
+        float_axes(self, XYZ)?;
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            row[i] = factor;
+        }
+        apply(self, XYZ, &m, false);
+        Ok(())
+    }
+}
+
+/// Fail unless `names` are three columns of the same float type.
+pub(super) fn float_axes(block: &PointBlock, names: [&str; 3]) -> Result<()> {
+    let mut types = names.iter().map(|&name| {
+        block
+            .get_column(name)
+            .map(Column::value_type)
+            .ok_or_else(|| PcdError::InvalidDataFormat(format!("Missing column {}", name)))
+    });
+    let first = types.next().unwrap()?;
+    for t in types {
+        let t = t?;
+        if t != first || !matches!(t, ValueType::F32 | ValueType::F64) {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Cannot transform {} of types {:?} and {:?}, not all F32 or all F64",
+                names.join("/"),
+                first,
+                t
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Multiply the vectors in columns `names` by `m`, adding the translation
+/// if `translate`.
+fn apply(block: &mut PointBlock, names: [&str; 3], m: &[[f64; 4]; 4], translate: bool) {
+    let t = if translate {
+        [m[0][3], m[1][3], m[2][3]]
+    } else {
+        [0.0; 3]
+    };
+    let rotate = move |[x, y, z]: [f64; 3]| {
+        [
+            m[0][0] * x + m[0][1] * y + m[0][2] * z + t[0],
+            m[1][0] * x + m[1][1] * y + m[1][2] * z + t[1],
+            m[2][0] * x + m[2][1] * y + m[2][2] * z + t[2],
+        ]
+    };
+    let names = names.map(String::from);
+    let mut columns = block.get_columns_mut(&names).unwrap().into_iter();
+    let (cx, cy, cz) = (
+        columns.next().unwrap(),
+        columns.next().unwrap(),
+        columns.next().unwrap(),
+    );
+    match (cx, cy, cz) {
+        (Column::F32(x), Column::F32(y), Column::F32(z)) => {
+            map_points(x, y, z, |p| rotate(p.map(f64::from)).map(|c| c as f32))
+        }
+        (Column::F64(x), Column::F64(y), Column::F64(z)) => map_points(x, y, z, rotate),
+        // Checked by `float_axes`.
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(feature = "rayon")]
+fn map_points<T: Copy + Send + Sync>(
+    x: &mut [T],
+    y: &mut [T],
+    z: &mut [T],
+    f: impl Fn([T; 3]) -> [T; 3] + Sync,
+) {
+    x.par_iter_mut()
+        .zip(y.par_iter_mut())
+        .zip(z.par_iter_mut())
+        .for_each(|((x, y), z)| [*x, *y, *z] = f([*x, *y, *z]));
+}
+
+#[cfg(not(feature = "rayon"))]
+fn map_points<T: Copy>(x: &mut [T], y: &mut [T], z: &mut [T], f: impl Fn([T; 3]) -> [T; 3]) {
+    for ((x, y), z) in x.iter_mut().zip(y.iter_mut()).zip(z.iter_mut()) {
+        [*x, *y, *z] = f([*x, *y, *z]);
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+//! Access to columns by Rust value type, for combinations of fields the
+//! `xyz*` accessors of [`PointBlock`] do not cover; see [`columns!`].
+//!
+//! [`columns!`]: crate::columns
+
+use super::{Column, PointBlock};
+use crate::header::ValueType;
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+/// A Rust type a [`Column`] can hold.
+pub trait ColumnValue: Copy {
+    const TYPE: ValueType;
+
+    /// The values of `column`, or `None` if it holds another type.
+    fn slice(column: &Column) -> Option<&[Self]>;
+
+    /// The values of `column`, moved out, or `None` if it holds another type.
+    fn into_vec(column: Column) -> Option<Vec<Self>>;
+}
+
+macro_rules! column_value {
+    ($($t:ty => $v:ident),*) => {
+        $(impl ColumnValue for $t {
+            const TYPE: ValueType = ValueType::$v;
+
+            fn slice(column: &Column) -> Option<&[Self]> {
+                match column {
+                    Column::$v(v) => Some(v),
+                    _ => None,
+                }
+            }
+
+            fn into_vec(column: Column) -> Option<Vec<Self>> {
+                match column {
+                    Column::$v(v) => Some(v),
+                    _ => None,
+                }
+            }
+        })*
+    };
+}
+
+column_value!(u8 => U8, u16 => U16, u32 => U32, u64 => U64, i8 => I8, i16 => I16,
+    i32 => I32, i64 => I64, f32 => F32, f64 => F64);
+
+impl Column {
+    /// The values as `&[T]`, or `None` if the column holds another type.
+    #[must_use]
+    pub fn as_slice<T: ColumnValue>(&self) -> Option<&[T]> {
+        T::slice(self)
+    }
+}
+
+impl PointBlock {
+    /// Column `name` as `&[T]`; `None` if it is missing or holds another
+    /// type.
+    #[must_use]
+    pub fn column_as<T: ColumnValue>(&self, name: &str) -> Option<&[T]> {
+        self.get_column(name)?.as_slice()
+    }
+
+    /// Column `name` as `T` values: borrowed if it holds `T`, else cast as
+    /// by [`Column::cast`]. `None` if it is missing.
+    #[must_use]
+    pub fn column_cast<T: ColumnValue>(&self, name: &str) -> Option<Cow<'_, [T]>> {
+        let column = self.get_column(name)?;
+        match column.as_slice() {
+            Some(values) => Some(Cow::Borrowed(values)),
+            None => T::into_vec(column.cast(T::TYPE)).map(Cow::Owned),
+        }
+    }
+
+    /// XYZ as `T` whatever type the file stores them in, e.g.
+    /// `xyz_as::<f64>()` for georeferenced clouds or `xyz_as::<f32>()` to
+    /// narrow them. Columns already of type `T` are borrowed.
+    /// Returns None if any of x, y, z is missing.
+    #[must_use]
+    #[allow(clippy::type_complexity)]
+    pub fn xyz_as<T: ColumnValue>(&self) -> Option<(Cow<'_, [T]>, Cow<'_, [T]>, Cow<'_, [T]>)> {
+        Some((self.column_cast("x")?, self.column_cast("y")?, self.column_cast("z")?))
+    }
+
+    /// Point positions as `[x, y, z]` in f64, converted on the fly from
+    /// whatever type the coordinates are stored in, so geometry code need not
+    /// care about the file's precision. Points with NaN coordinates are
+    /// included, keeping positions aligned with point indices; see
+    /// [`iter_xyz_finite`](Self::iter_xyz_finite) to skip them.
+    /// Returns None if any of x, y, z is missing.
+    pub fn iter_xyz(&self) -> Option<impl ExactSizeIterator<Item = [f64; 3]> + '_> {
+        let x = self.get_column("x")?;
+        let y = self.get_column("y")?;
+        let z = self.get_column("z")?;
+        Some((0..self.len).map(move |i| [x.value_f64(i), y.value_f64(i), z.value_f64(i)]))
+    }
+
+    /// Like [`iter_xyz`](Self::iter_xyz), but with the point index and
+    /// skipping points with a NaN or infinite coordinate, as PCL marks
+    /// invalid points in organized clouds.
+    pub fn iter_xyz_finite(&self) -> Option<impl Iterator<Item = (usize, [f64; 3])> + '_> {
+        Some(
+            self.iter_xyz()?
+                .enumerate()
+                .filter(|(_, p)| p.iter().all(|c| c.is_finite())),
+        )
+    }
+}
+
+/// A tuple of typed column slices of a [`PointBlock`], like the `xyz*`
+/// accessors but for any fields, or `None` if one is missing or of another
+/// type.
+///
+/// ```
+/// use rs_pcd::header::PcdHeaderBuilder;
+/// use rs_pcd::storage::PointBlock;
+/// use rs_pcd::ValueType;
+///
+/// let builder = PcdHeaderBuilder::xyz().add_field("ring", ValueType::U16);
+/// let block = PointBlock::new(builder.schema(), 4);
+/// let (x, ring) = rs_pcd::columns!(block, x: f32, ring: u16).unwrap();
+/// assert_eq!((x.len(), ring.len()), (4, 4));
+/// assert!(rs_pcd::columns!(block, x: f64).is_none());
+/// ```
+#[macro_export]
+macro_rules! columns {
+    ($block:expr, $($name:ident : $t:ty),+ $(,)?) => {{
+        let block: &$crate::storage::PointBlock = &$block;
+        match ($(block.column_as::<$t>(stringify!($name)),)+) {
+            ($(Some($name),)+) => Some(($($name,)+)),
+            _ => None,
+        }
+    }};
+}
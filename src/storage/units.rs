@@ -0,0 +1,86 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Length units of a cloud, e.g. millimeters from industrial scanners
+//! against meters from LiDARs.
+
+use super::PointBlock;
+use super::transform::float_axes;
+use crate::error::Result;
+use crate::header::ValueType;
+
+/// Columns holding a distance from the sensor, rescaled with the positions.
+const RANGES: [&str; 2] = ["range", "distance"];
+
+/// A unit of length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LengthUnit {
+    Millimeters,
+    Centimeters,
+    Meters,
+}
+
+impl LengthUnit {
+    /// What a length in this unit is multiplied by to express it in `to`,
+    /// e.g. 0.001 from millimeters to meters.
+    #[must_use]
+    pub fn factor_to(self, to: LengthUnit) -> f64 {
+        self.millimeters() / to.millimeters()
+    }
+
+    fn millimeters(self) -> f64 {
+        match self {
+            LengthUnit::Millimeters => 1.0,
+            LengthUnit::Centimeters => 10.0,
+            LengthUnit::Meters => 1000.0,
+        }
+    }
+}
+
+impl PointBlock {
+    /// Re-express the block from unit `from` in unit `to`: `x`, `y`, `z`
+    /// are scaled as by [`scale_xyz`](Self::scale_xyz), and so are the
+    /// `range` and `distance` columns if present. Float ranges keep their
+    /// type; integer ones, such as Ouster's millimeter `range`, become F32
+    /// since the result is generally not a whole number.
+    ///
+    /// Fails, leaving the block unchanged, if `x`, `y` or `z` is missing or
+    /// the coordinates are not all F32 or all F64.
+    pub fn convert_units(&mut self, from: LengthUnit, to: LengthUnit) -> Result<()> {
+        if from == to {
+            return Ok(());
+        }
+        float_axes(self, ["x", "y", "z"])?;
+        let factor = from.factor_to(to);
+        self.scale_xyz(factor)?;
+        for name in RANGES {
+            let Some(index) = self.get_column_index(name) else {
+                continue;
+            };
+            let column = &mut self.columns[index];
+            if column.value_type() != ValueType::F64 {
+                *column = column.cast(ValueType::F32);
+                self.schema.field_mut(index).type_ = ValueType::F32;
+            }
+            if let Some(values) = column.as_f32_mut() {
+                values
+                    .iter_mut()
+                    .for_each(|v| *v = (*v as f64 * factor) as f32);
+            } else if let Some(values) = column.as_f64_mut() {
+                values.iter_mut().for_each(|v| *v *= factor);
+            }
+        }
+        Ok(())
+    }
+}
@@ -39,6 +39,10 @@ impl<'a> ColumnView<'a> {
             ColumnView::F64(v) => v.len(),
         }
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 pub struct PointView<'a> {
@@ -46,6 +50,12 @@ pub struct PointView<'a> {
     pub len: usize,
 }
 
+impl<'a> Default for PointView<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a> PointView<'a> {
     pub fn new() -> Self {
         Self {
@@ -0,0 +1,190 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Voxel grid downsampling: one point per occupied cube of a fixed edge
+//! length, as PCL's `VoxelGrid` filter.
+
+use super::rgb::{pack_color, packed_colors, unpack_color};
+use super::{Column, PointBlock, Rounding};
+use crate::error::{PcdError, Result};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// How [`VoxelGrid`] combines the values of a field within a voxel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Aggregation {
+    /// The average, rounded to the nearest value for integer fields.
+    /// Packed `rgb`/`rgba` colors are averaged per channel.
+    #[default]
+    Mean,
+    /// The value of the voxel's first point, in block order.
+    First,
+    Min,
+    Max,
+    /// The most frequent value, the smallest on ties; for labels.
+    Mode,
+}
+
+/// Downsampling to one point per voxel, a cube of edge `leaf_size` aligned
+/// to the smallest coordinates of the block. Every field is combined over
+/// the points of a voxel with [`Aggregation::Mean`] unless set otherwise by
+/// [`aggregate`](Self::aggregate). Points with a NaN or infinite coordinate
+/// are dropped. Output points are ordered by voxel.
+#[derive(Debug, Clone)]
+pub struct VoxelGrid {
+    leaf_size: f64,
+    aggregations: BTreeMap<String, Aggregation>,
+}
+
+impl VoxelGrid {
+    pub fn new(leaf_size: f64) -> Self {
+        Self {
+            leaf_size,
+            aggregations: BTreeMap::new(),
+        }
+    }
+
+    /// Combine field `name` with `aggregation`, e.g. `Aggregation::Mode` for
+    /// `label`.
+    #[must_use]
+    pub fn aggregate(mut self, name: &str, aggregation: Aggregation) -> Self {
+        self.aggregations.insert(name.to_string(), aggregation);
+        self
+    }
+
+    /// The downsampled points of `block`. Fails if `leaf_size` is not
+    /// positive or `x`, `y` or `z` is missing.
+    pub fn apply(&self, block: &PointBlock) -> Result<PointBlock> {
+        if !(self.leaf_size > 0.0 && self.leaf_size.is_finite()) {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Voxel leaf size {} is not positive",
+                self.leaf_size
+            )));
+        }
+        let voxels = self.voxels(block)?;
+        let mut out = PointBlock::new(block.to_schema(), 0);
+        out.len = voxels.len();
+        for (i, (field, column)) in block.schema.iter().zip(&block.columns).enumerate() {
+            let name = &field.name;
+            let aggregation = self.aggregations.get(name).copied().unwrap_or_default();
+            out.columns[i] = aggregate(block, name, column, &voxels, aggregation);
+        }
+        Ok(out)
+    }
+
+    /// Indices of the points in each occupied voxel, ordered by voxel.
+    fn voxels(&self, block: &PointBlock) -> Result<Vec<Vec<usize>>> {
+        let points: Vec<(usize, [f64; 3])> = block
+            .iter_xyz_finite()
+            .ok_or_else(|| PcdError::InvalidDataFormat("Missing column x, y or z".to_string()))?
+            .collect();
+        let mut min = [f64::INFINITY; 3];
+        for (_, p) in &points {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(p[axis]);
+            }
+        }
+        let mut voxels: BTreeMap<[i64; 3], Vec<usize>> = BTreeMap::new();
+        for (i, p) in points {
+            // Offsets from `min` are not negative, so truncating floors them.
+            let key = [0, 1, 2].map(|axis| ((p[axis] - min[axis]) / self.leaf_size) as i64);
+            voxels.entry(key).or_default().push(i);
+        }
+        Ok(voxels.into_values().collect())
+    }
+}
+
+/// [`VoxelGrid`] with `leaf_size` and [`Aggregation::Mean`] for every field.
+pub fn voxel_grid(block: &PointBlock, leaf_size: f64) -> Result<PointBlock> {
+    VoxelGrid::new(leaf_size).apply(block)
+}
+
+/// Column `name` of `block` with the values of each group of points
+/// combined by `aggregation`; packed colors are averaged per channel.
+pub(super) fn aggregate(
+    block: &PointBlock,
+    name: &str,
+    column: &Column,
+    groups: &[Vec<usize>],
+    aggregation: Aggregation,
+) -> Column {
+    let count = block.values_per_point(name);
+    if aggregation == Aggregation::Mean && matches!(name, "rgb" | "rgba") && count == 1 {
+        if let Some(packed) = packed_colors(column) {
+            return mean_colors(column, &packed, groups);
+        }
+    }
+    combine(column, count, groups, aggregation)
+}
+
+/// `column` with each voxel's `count` values per point combined, computed
+/// in f64, which holds every value type exactly.
+fn combine(
+    column: &Column,
+    count: usize,
+    voxels: &[Vec<usize>],
+    aggregation: Aggregation,
+) -> Column {
+    let mut out = Vec::with_capacity(voxels.len() * count);
+    let mut values = Vec::new();
+    for voxel in voxels {
+        for k in 0..count {
+            values.clear();
+            values.extend(voxel.iter().map(|&i| column.value_f64(i * count + k)));
+            out.push(match aggregation {
+                Aggregation::Mean => values.iter().sum::<f64>() / values.len() as f64,
+                Aggregation::First => values[0],
+                Aggregation::Min => values.iter().copied().fold(f64::INFINITY, f64::min),
+                Aggregation::Max => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+                Aggregation::Mode => mode(&mut values),
+            });
+        }
+    }
+    Column::F64(out).cast_with(column.value_type(), Rounding::Nearest)
+}
+
+fn mode(values: &mut [f64]) -> f64 {
+    values.sort_unstable_by(f64::total_cmp);
+    let mut best = (values[0], 0);
+    for run in values.chunk_by(|a, b| a.total_cmp(b).is_eq()) {
+        if run.len() > best.1 {
+            best = (run[0], run.len());
+        }
+    }
+    best.0
+}
+
+/// Packed colors averaged per channel, in the type of `column`.
+fn mean_colors(column: &Column, packed: &[u32], voxels: &[Vec<usize>]) -> Column {
+    let colors: Vec<u32> = voxels
+        .iter()
+        .map(|voxel| {
+            let mut sum = [0u64; 4];
+            for &i in voxel {
+                for (s, c) in sum.iter_mut().zip(unpack_color(packed[i])) {
+                    *s += u64::from(c);
+                }
+            }
+            let n = voxel.len() as u64;
+            let [r, g, b, a] = sum.map(|s| ((s + n / 2) / n) as u8);
+            pack_color(r, g, b, a)
+        })
+        .collect();
+    match column {
+        Column::F32(_) => Column::F32(colors.into_iter().map(f32::from_bits).collect()),
+        _ => Column::U32(colors),
+    }
+}
@@ -0,0 +1,223 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Entwine Point Tile (EPT) output with the `binary` data type.
+//!
+//! Layout on disk:
+//!
+//! ```text
+//! <dir>/ept.json
+//! <dir>/ept-data/<D>-<X>-<Y>-<Z>.bin
+//! <dir>/ept-hierarchy/0-0-0-0.json
+//! ```
+//!
+//! `X`, `Y`, `Z` are stored as 8-byte floats; every other column keeps its
+//! PCD type. Node files are appended after each `add`, so memory use is
+//! bounded by the octree occupancy rather than the point data.
+
+use super::{NodeKey, OctreeBuilder, TilingOptions, cubic_bounds};
+use crate::error::{PcdError, Result};
+use crate::header::{Schema, SchemaField, ValueType};
+use crate::storage::PointBlock;
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Incremental EPT writer for clouds delivered in chunks.
+#[derive(Debug)]
+pub struct EptWriter {
+    dir: PathBuf,
+    octree: OctreeBuilder,
+    cube: ([f64; 3], [f64; 3]),
+    attributes: Schema,
+    options: TilingOptions,
+    counts: BTreeMap<NodeKey, u64>,
+    conforming: ([f64; 3], [f64; 3]),
+    points: u64,
+}
+
+impl EptWriter {
+    /// Create a writer for points within `bounds` (min, max).
+    ///
+    /// `schema` lists every column that added blocks provide; it must contain
+    /// F32 `x`, `y`, `z`. Points outside `bounds` are clamped into the edge nodes.
+    /// Fails if `options` are out of range; see [`TilingOptions::validate`].
+    pub fn new<P: AsRef<Path>>(
+        dir: P,
+        schema: impl Into<Schema>,
+        bounds: ([f64; 3], [f64; 3]),
+        options: TilingOptions,
+    ) -> Result<Self> {
+        options.validate()?;
+        let schema = schema.into();
+        for axis in ["x", "y", "z"] {
+            if schema.get(axis).is_none_or(|f| f.type_ != ValueType::F32) {
+                return Err(PcdError::InvalidDataFormat(format!(
+                    "EPT schema requires F32 column {}",
+                    axis
+                )));
+            }
+        }
+
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(dir.join("ept-data"))?;
+        fs::create_dir_all(dir.join("ept-hierarchy"))?;
+
+        let cube = cubic_bounds(bounds.0, bounds.1);
+        Ok(Self {
+            dir,
+            octree: OctreeBuilder::new(cube, options),
+            cube,
+            attributes: schema
+                .iter()
+                .filter(|f| !matches!(f.name.as_str(), "x" | "y" | "z"))
+                .cloned()
+                .collect(),
+            options,
+            counts: BTreeMap::new(),
+            conforming: ([f64::INFINITY; 3], [f64::NEG_INFINITY; 3]),
+            points: 0,
+        })
+    }
+
+    /// Insert a chunk of points and append them to their node files.
+    pub fn add(&mut self, block: &PointBlock) -> Result<()> {
+        let (x, y, z) = block.xyz().ok_or_else(|| {
+            PcdError::InvalidDataFormat("EPT input requires F32 x, y, z columns".to_string())
+        })?;
+        let mut columns = Vec::with_capacity(self.attributes.len());
+        for SchemaField { name, .. } in &self.attributes {
+            columns.push(
+                block.get_column(name).ok_or_else(|| {
+                    PcdError::InvalidDataFormat(format!("Missing column {}", name))
+                })?,
+            );
+        }
+
+        let mut pending: BTreeMap<NodeKey, Vec<u8>> = BTreeMap::new();
+        for i in 0..block.len {
+            let p = [x[i] as f64, y[i] as f64, z[i] as f64];
+            for (k, v) in p.into_iter().enumerate() {
+                self.conforming.0[k] = self.conforming.0[k].min(v);
+                self.conforming.1[k] = self.conforming.1[k].max(v);
+            }
+
+            let key = self.octree.place(p);
+            let buf = pending.entry(key).or_default();
+            for v in p {
+                buf.extend_from_slice(&v.to_le_bytes());
+            }
+            for col in &columns {
+                col.push_le_bytes(i, buf);
+            }
+            *self.counts.entry(key).or_default() += 1;
+        }
+        self.points += block.len as u64;
+
+        for (key, data) in pending {
+            let mut file = OpenOptions::new().create(true).append(true).open(
+                self.dir
+                    .join("ept-data")
+                    .join(format!("{}.bin", key_name(&key))),
+            )?;
+            file.write_all(&data)?;
+        }
+        Ok(())
+    }
+
+    /// Write `ept.json` and the hierarchy.
+    pub fn finish(self) -> Result<()> {
+        let mut hierarchy = String::from("{");
+        for (i, (key, count)) in self.counts.iter().enumerate() {
+            if i > 0 {
+                hierarchy.push(',');
+            }
+            hierarchy.push_str(&format!("\n  \"{}\": {}", key_name(key), count));
+        }
+        hierarchy.push_str("\n}\n");
+        fs::write(
+            self.dir.join("ept-hierarchy").join("0-0-0-0.json"),
+            hierarchy,
+        )?;
+
+        let mut schema = vec![
+            "{ \"name\": \"X\", \"type\": \"float\", \"size\": 8 }".to_string(),
+            "{ \"name\": \"Y\", \"type\": \"float\", \"size\": 8 }".to_string(),
+            "{ \"name\": \"Z\", \"type\": \"float\", \"size\": 8 }".to_string(),
+        ];
+        for SchemaField { name, type_, .. } in &self.attributes {
+            let kind = match type_ {
+                ValueType::U8 | ValueType::U16 | ValueType::U32 | ValueType::U64 => "unsigned",
+                ValueType::I8 | ValueType::I16 | ValueType::I32 | ValueType::I64 => "signed",
+                ValueType::F32 | ValueType::F64 => "float",
+            };
+            schema.push(format!(
+                "{{ \"name\": \"{}\", \"type\": \"{}\", \"size\": {} }}",
+                name,
+                kind,
+                type_.size()
+            ));
+        }
+
+        let conforming = if self.points == 0 {
+            ([0.0; 3], [0.0; 3])
+        } else {
+            self.conforming
+        };
+        let json = format!(
+            "{{\n  \"version\": \"1.0.0\",\n  \"dataType\": \"binary\",\n  \"hierarchyType\": \"json\",\n  \"points\": {},\n  \"span\": {},\n  \"bounds\": {},\n  \"boundsConforming\": {},\n  \"schema\": [\n    {}\n  ],\n  \"srs\": {{}}\n}}\n",
+            self.points,
+            self.options.span,
+            bounds_json(&self.cube),
+            bounds_json(&conforming),
+            schema.join(",\n    ")
+        );
+        fs::write(self.dir.join("ept.json"), json)?;
+        Ok(())
+    }
+}
+
+/// Tile a whole cloud into an EPT dataset at `dir`.
+pub fn write_ept<P: AsRef<Path>>(dir: P, block: &PointBlock, options: TilingOptions) -> Result<()> {
+    let (x, y, z) = block.xyz().ok_or_else(|| {
+        PcdError::InvalidDataFormat("EPT input requires F32 x, y, z columns".to_string())
+    })?;
+    let mut min = [f64::INFINITY; 3];
+    let mut max = [f64::NEG_INFINITY; 3];
+    for i in 0..block.len {
+        for (k, v) in [x[i], y[i], z[i]].into_iter().enumerate() {
+            min[k] = min[k].min(v as f64);
+            max[k] = max[k].max(v as f64);
+        }
+    }
+    if block.len == 0 {
+        (min, max) = ([0.0; 3], [0.0; 3]);
+    }
+
+    let mut writer = EptWriter::new(dir, block.to_schema(), (min, max), options)?;
+    writer.add(block)?;
+    writer.finish()
+}
+
+fn key_name(key: &NodeKey) -> String {
+    format!("{}-{}-{}-{}", key.depth, key.x, key.y, key.z)
+}
+
+fn bounds_json(b: &([f64; 3], [f64; 3])) -> String {
+    format!(
+        "[{}, {}, {}, {}, {}, {}]",
+        b.0[0], b.0[1], b.0[2], b.1[0], b.1[1], b.1[2]
+    )
+}
@@ -0,0 +1,183 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Flat splitting of large clouds into square tiles on the `x`/`y` plane,
+//! e.g. to break a city-scale map into pieces that fit in memory.
+//!
+//! Tile `[i, j]` covers `i * tile_size <= x < (i + 1) * tile_size` and the
+//! same for `j` and `y`, whatever the height. [`GridTileWriter`] writes one
+//! binary PCD per tile, `tile_<i>_<j>.pcd`, from chunks of any size.
+
+use crate::error::{PcdError, Result};
+use crate::header::{DataFormat, PcdHeader};
+use crate::io::PcdWriter;
+use crate::storage::PointBlock;
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Integer coordinates of a tile: `x` and `y` divided by the tile size,
+/// floored.
+pub type TileKey = [i64; 2];
+
+/// The points of `block` in each tile of edge `tile_size`, as indices in
+/// order. Points with a NaN or infinite coordinate are left out. Fails if
+/// `x`, `y` or `z` is missing or `tile_size` is not positive.
+pub fn grid_indices(block: &PointBlock, tile_size: f64) -> Result<BTreeMap<TileKey, Vec<usize>>> {
+    check_tile_size(tile_size)?;
+    let points = block
+        .iter_xyz_finite()
+        .ok_or_else(|| PcdError::InvalidDataFormat("Missing column x, y or z".to_string()))?;
+    let mut tiles: BTreeMap<TileKey, Vec<usize>> = BTreeMap::new();
+    for (i, p) in points {
+        tiles.entry(tile_key(p, tile_size)).or_default().push(i);
+    }
+    Ok(tiles)
+}
+
+/// `block` split into one block per tile of edge `tile_size`, with all its
+/// columns; see [`grid_indices`].
+pub fn split_grid(block: &PointBlock, tile_size: f64) -> Result<BTreeMap<TileKey, PointBlock>> {
+    Ok(grid_indices(block, tile_size)?
+        .into_iter()
+        .map(|(key, indices)| (key, block.take(&indices)))
+        .collect())
+}
+
+/// A tile written by [`GridTileWriter`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileFile {
+    pub key: TileKey,
+    pub path: PathBuf,
+    pub points: usize,
+}
+
+/// Writes the chunks of a cloud, e.g. as read with
+/// [`PcdReader::read_chunk`](crate::io::PcdReader::read_chunk), into one
+/// PCD per tile.
+/// Points are appended to a `.part` file per tile as they come, so memory
+/// use stays that of one chunk; [`finish`](Self::finish) puts the headers
+/// in front once the counts are known.
+#[derive(Debug)]
+pub struct GridTileWriter {
+    dir: PathBuf,
+    header: PcdHeader,
+    tile_size: f64,
+    counts: BTreeMap<TileKey, usize>,
+}
+
+impl GridTileWriter {
+    /// Writer of tiles of edge `tile_size` into `dir`, created if needed.
+    /// `header` gives the fields, which every chunk must have, and the
+    /// viewpoint of the tiles; they are written as binary. Fails if
+    /// `tile_size` is not positive.
+    pub fn new<P: AsRef<Path>>(dir: P, header: &PcdHeader, tile_size: f64) -> Result<Self> {
+        check_tile_size(tile_size)?;
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let mut header = header.clone();
+        header.data = DataFormat::Binary;
+        Ok(Self {
+            dir,
+            header,
+            tile_size,
+            counts: BTreeMap::new(),
+        })
+    }
+
+    /// Append the points of `block` to their tiles.
+    pub fn add(&mut self, block: &PointBlock) -> Result<()> {
+        for (key, indices) in grid_indices(block, self.tile_size)? {
+            let tile = block.take(&indices);
+            let mut chunk = self.header.clone();
+            chunk.width = tile.len as u32;
+            chunk.height = 1;
+            chunk.points = tile.len;
+            let part = part_path(&self.tile_path(key));
+            // A tile's first chunk replaces any `.part` file left over.
+            let file = if self.counts.contains_key(&key) {
+                OpenOptions::new().append(true).open(part)?
+            } else {
+                File::create(part)?
+            };
+            let mut writer = PcdWriter::new(BufWriter::new(file));
+            writer.write_body(&chunk, &tile)?;
+            writer.into_inner().flush()?;
+            *self.counts.entry(key).or_default() += tile.len;
+        }
+        Ok(())
+    }
+
+    /// Write the header of each tile followed by its points, and list the
+    /// tiles written.
+    pub fn finish(self) -> Result<Vec<TileFile>> {
+        let mut tiles = Vec::with_capacity(self.counts.len());
+        for (&key, &points) in &self.counts {
+            let path = self.tile_path(key);
+            let part = part_path(&path);
+            let mut header = self.header.clone();
+            header.width = points as u32;
+            header.height = 1;
+            header.points = points;
+            let mut writer = PcdWriter::new(BufWriter::new(File::create(&path)?));
+            writer.write_header(&header)?;
+            let mut out = writer.into_inner();
+            io::copy(&mut BufReader::new(File::open(&part)?), &mut out)?;
+            out.flush()?;
+            fs::remove_file(part)?;
+            tiles.push(TileFile { key, path, points });
+        }
+        Ok(tiles)
+    }
+
+    fn tile_path(&self, key: TileKey) -> PathBuf {
+        self.dir.join(format!("tile_{}_{}.pcd", key[0], key[1]))
+    }
+}
+
+/// Split `block` into tiles of edge `tile_size` written into `dir` with
+/// `header`'s fields, see [`GridTileWriter`].
+pub fn write_grid_tiles<P: AsRef<Path>>(
+    dir: P,
+    header: &PcdHeader,
+    block: &PointBlock,
+    tile_size: f64,
+) -> Result<Vec<TileFile>> {
+    let mut writer = GridTileWriter::new(dir, header, tile_size)?;
+    writer.add(block)?;
+    writer.finish()
+}
+
+fn tile_key(p: [f64; 3], tile_size: f64) -> TileKey {
+    [
+        (p[0] / tile_size).floor() as i64,
+        (p[1] / tile_size).floor() as i64,
+    ]
+}
+
+fn check_tile_size(tile_size: f64) -> Result<()> {
+    if tile_size > 0.0 && tile_size.is_finite() {
+        Ok(())
+    } else {
+        Err(PcdError::InvalidDataFormat(format!(
+            "Tile size must be positive, got {}",
+            tile_size
+        )))
+    }
+}
+
+fn part_path(path: &Path) -> PathBuf {
+    path.with_extension("pcd.part")
+}
@@ -0,0 +1,171 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Hierarchical (octree) tiling of large clouds for web streaming.
+//!
+//! Points are inserted into an octree over a cubic root bounds. Each node
+//! keeps at most one point per cell of a `span^3` grid; a point whose cell is
+//! already taken moves down to the child octant, so shallow nodes hold an
+//! evenly thinned overview and deeper nodes add detail. Nodes at `max_depth`
+//! accept every point that reaches them.
+//!
+//! [`split_grid`] and [`GridTileWriter`] instead cut a cloud into a flat grid
+//! of square tiles.
+//!
+//! The octree is written as EPT only; there is no writer for Potree's own
+//! format. The Potree viewer (1.7 and later) loads an EPT directory through
+//! its `ept.json`.
+
+use crate::error::{PcdError, Result};
+use std::collections::{HashMap, HashSet};
+
+pub mod ept;
+pub mod grid;
+pub use ept::{EptWriter, write_ept};
+pub use grid::{GridTileWriter, TileFile, TileKey, grid_indices, split_grid, write_grid_tiles};
+
+/// Octree node key: depth and integer position at that depth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeKey {
+    pub depth: u32,
+    pub x: u64,
+    pub y: u64,
+    pub z: u64,
+}
+
+impl NodeKey {
+    pub const ROOT: NodeKey = NodeKey {
+        depth: 0,
+        x: 0,
+        y: 0,
+        z: 0,
+    };
+
+    /// Child key for the octant selected by `(bx, by, bz)`, each 0 or 1.
+    #[must_use]
+    pub fn child(&self, bx: u64, by: u64, bz: u64) -> NodeKey {
+        NodeKey {
+            depth: self.depth + 1,
+            x: self.x * 2 + bx,
+            y: self.y * 2 + by,
+            z: self.z * 2 + bz,
+        }
+    }
+}
+
+/// Options controlling the octree shape.
+#[derive(Debug, Clone, Copy)]
+pub struct TilingOptions {
+    /// Grid resolution per node axis, at most [`MAX_SPAN`](Self::MAX_SPAN).
+    /// Default is 128.
+    pub span: u32,
+    /// Deepest level, at most [`MAX_DEPTH`](Self::MAX_DEPTH); nodes here are
+    /// never split. Default is 12.
+    pub max_depth: u32,
+}
+
+impl TilingOptions {
+    /// Deepest supported level; node positions stay exact in an `f64`.
+    pub const MAX_DEPTH: u32 = 21;
+    /// Largest supported span; cell ids of a node fit in a `u64`.
+    pub const MAX_SPAN: u32 = 1 << 21;
+
+    /// Check that the options describe an octree the writers can build.
+    pub fn validate(&self) -> Result<()> {
+        if self.max_depth > Self::MAX_DEPTH {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Octree max_depth {} exceeds {}",
+                self.max_depth,
+                Self::MAX_DEPTH
+            )));
+        }
+        if self.span > Self::MAX_SPAN {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Octree span {} exceeds {}",
+                self.span,
+                Self::MAX_SPAN
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for TilingOptions {
+    fn default() -> Self {
+        Self {
+            span: 128,
+            max_depth: 12,
+        }
+    }
+}
+
+/// Cube enclosing the axis-aligned bounds `(min, max)`.
+#[must_use]
+pub fn cubic_bounds(min: [f64; 3], max: [f64; 3]) -> ([f64; 3], [f64; 3]) {
+    let extent = (0..3)
+        .map(|k| max[k] - min[k])
+        .fold(0.0f64, f64::max)
+        .max(f64::EPSILON);
+    let half = extent / 2.0;
+    let center = [0, 1, 2].map(|k| (min[k] + max[k]) / 2.0);
+    (center.map(|c| c - half), center.map(|c| c + half))
+}
+
+/// Octree point placement shared by the tiling writers.
+#[derive(Debug)]
+pub(crate) struct OctreeBuilder {
+    origin: [f64; 3],
+    size: f64,
+    options: TilingOptions,
+    occupied: HashMap<NodeKey, HashSet<u64>>,
+}
+
+impl OctreeBuilder {
+    pub(crate) fn new(cube: ([f64; 3], [f64; 3]), options: TilingOptions) -> Self {
+        Self {
+            origin: cube.0,
+            size: cube.1[0] - cube.0[0],
+            options,
+            occupied: HashMap::new(),
+        }
+    }
+
+    /// Find the node that stores a point at `p`, claiming its grid cell.
+    pub(crate) fn place(&mut self, p: [f64; 3]) -> NodeKey {
+        let span = self.options.span.max(1) as f64;
+        let mut key = NodeKey::ROOT;
+        loop {
+            let node_size = self.size / (1u64 << key.depth) as f64;
+            let node_origin = [
+                self.origin[0] + key.x as f64 * node_size,
+                self.origin[1] + key.y as f64 * node_size,
+                self.origin[2] + key.z as f64 * node_size,
+            ];
+            let rel = [0, 1, 2].map(|k| ((p[k] - node_origin[k]) / node_size).clamp(0.0, 1.0));
+
+            if key.depth >= self.options.max_depth {
+                return key;
+            }
+
+            let cell = rel.map(|r| ((r * span) as u64).min(span as u64 - 1));
+            let cell_id = (cell[0] * span as u64 + cell[1]) * span as u64 + cell[2];
+            if self.occupied.entry(key).or_default().insert(cell_id) {
+                return key;
+            }
+
+            let [bx, by, bz] = rel.map(|r| u64::from(r >= 0.5));
+            key = key.child(bx, by, bz);
+        }
+    }
+}
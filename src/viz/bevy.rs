@@ -0,0 +1,63 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bevy meshes (feature `bevy`).
+
+use super::MeshAttributes;
+use crate::error::{PcdError, Result};
+use crate::storage::PointBlock;
+use bevy_asset::RenderAssetUsages;
+use bevy_mesh::{Mesh, MeshVertexAttribute, PrimitiveTopology, VertexFormat};
+
+impl PointBlock {
+    /// A point-list [`Mesh`] of the block: positions, colors as in
+    /// [`mesh_attributes`](Self::mesh_attributes), and each column of
+    /// `custom` as the vertex attribute paired with it, which must be
+    /// `Float32`. Render it with a material whose shader reads the custom
+    /// attributes.
+    ///
+    /// ```ignore
+    /// const INTENSITY: MeshVertexAttribute =
+    ///     MeshVertexAttribute::new("Intensity", 988_540_917, VertexFormat::Float32);
+    /// let mesh = block.to_bevy_mesh(&[("intensity", INTENSITY)])?;
+    /// commands.spawn((Mesh3d(meshes.add(mesh)), MeshMaterial3d(material)));
+    /// ```
+    pub fn to_bevy_mesh(&self, custom: &[(&str, MeshVertexAttribute)]) -> Result<Mesh> {
+        if let Some((name, attribute)) = custom
+            .iter()
+            .find(|(_, attribute)| attribute.format != VertexFormat::Float32)
+        {
+            return Err(PcdError::InvalidDataFormat(format!(
+                "Vertex attribute {} for column {} is {:?}, not Float32",
+                attribute.name, name, attribute.format
+            )));
+        }
+        let names: Vec<&str> = custom.iter().map(|(name, _)| *name).collect();
+        let MeshAttributes {
+            positions,
+            colors,
+            custom: values,
+        } = self.mesh_attributes(&names)?;
+
+        let mut mesh = Mesh::new(PrimitiveTopology::PointList, RenderAssetUsages::default());
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        if let Some(colors) = colors {
+            mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        }
+        for ((_, attribute), (_, values)) in custom.iter().zip(values) {
+            mesh.insert_attribute(*attribute, values);
+        }
+        Ok(mesh)
+    }
+}
@@ -0,0 +1,146 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Viewer-ready point data.
+//!
+//! [`RenderPoints`] holds positions and 8-bit RGBA colors in the shapes that
+//! visualization SDKs accept directly, and [`MeshAttributes`] the vertex
+//! attributes of a point-list mesh for game engines. With the `bevy` feature,
+//! [`PointBlock::to_bevy_mesh`] builds the mesh itself, and with the `rerun`
+//! feature [`PointBlock::log_to_rerun`] logs the points.
+//!
+//! Colors come from a packed `rgb`/`rgba` column (U32, or F32 holding the
+//! packed bits as written by PCL). Without one, an `intensity` column is
+//! mapped to grayscale over its min..max range.
+
+#[cfg(feature = "bevy")]
+mod bevy;
+#[cfg(feature = "rerun")]
+mod rerun;
+
+use crate::error::{PcdError, Result};
+use crate::storage::PointBlock;
+use crate::storage::rgb::{packed_colors, unpack_color};
+
+/// Positions and optional per-point colors.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenderPoints {
+    pub positions: Vec<[f32; 3]>,
+    pub colors: Option<Vec<[u8; 4]>>,
+}
+
+impl PointBlock {
+    /// Collect positions and colors for a viewer.
+    pub fn render_points(&self) -> Result<RenderPoints> {
+        let (x, y, z) = self.xyz().ok_or_else(|| {
+            PcdError::InvalidDataFormat("Rendering requires F32 x, y, z columns".to_string())
+        })?;
+        let positions = (0..self.len).map(|i| [x[i], y[i], z[i]]).collect();
+        Ok(RenderPoints {
+            positions,
+            colors: point_colors(self),
+        })
+    }
+}
+
+/// Vertex attributes for a point-list mesh, for engines other than bevy,
+/// whose mesh [`PointBlock::to_bevy_mesh`] builds with the `bevy` feature.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshAttributes {
+    pub positions: Vec<[f32; 3]>,
+    /// Linear RGBA, converted from the sRGB colors of [`point_colors`].
+    pub colors: Option<Vec<[f32; 4]>>,
+    /// Requested extra columns, converted to f32, in request order.
+    pub custom: Vec<(String, Vec<f32>)>,
+}
+
+impl PointBlock {
+    /// Collect mesh vertex attributes, including the `custom` columns.
+    pub fn mesh_attributes(&self, custom: &[&str]) -> Result<MeshAttributes> {
+        let RenderPoints { positions, colors } = self.render_points()?;
+        let colors = colors.map(|c| {
+            c.into_iter()
+                .map(|[r, g, b, a]| {
+                    [
+                        srgb_to_linear(r),
+                        srgb_to_linear(g),
+                        srgb_to_linear(b),
+                        a as f32 / 255.0,
+                    ]
+                })
+                .collect()
+        });
+
+        let mut extra = Vec::with_capacity(custom.len());
+        for name in custom {
+            let col = self
+                .get_column(name)
+                .ok_or_else(|| PcdError::InvalidDataFormat(format!("Missing column {}", name)))?;
+            let values = (0..self.len).map(|i| col.value_f64(i) as f32).collect();
+            extra.push((name.to_string(), values));
+        }
+
+        Ok(MeshAttributes {
+            positions,
+            colors,
+            custom: extra,
+        })
+    }
+}
+
+fn srgb_to_linear(c: u8) -> f32 {
+    let c = c as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Per-point RGBA colors from `rgba`, `rgb` or `intensity`, in that order.
+pub fn point_colors(block: &PointBlock) -> Option<Vec<[u8; 4]>> {
+    for (name, has_alpha) in [("rgba", true), ("rgb", false)] {
+        if let Some(packed) = block.get_column(name).and_then(packed_colors) {
+            return Some(
+                packed
+                    .into_iter()
+                    .map(|c| {
+                        let [r, g, b, a] = unpack_color(c);
+                        [r, g, b, if has_alpha { a } else { 255 }]
+                    })
+                    .collect(),
+            );
+        }
+    }
+
+    let intensity = block.get_column("intensity")?;
+    let values: Vec<f64> = (0..intensity.len())
+        .map(|i| intensity.value_f64(i))
+        .collect();
+    let (min, max) = values
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        });
+    let span = if max > min { max - min } else { 1.0 };
+    Some(
+        values
+            .into_iter()
+            .map(|v| {
+                let g = (((v - min) / span) * 255.0).round() as u8;
+                [g, g, g, 255]
+            })
+            .collect(),
+    )
+}
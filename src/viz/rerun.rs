@@ -0,0 +1,43 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Logging to rerun (feature `rerun`).
+
+use super::RenderPoints;
+use crate::error::{PcdError, Result};
+use crate::storage::PointBlock;
+use ::rerun::{EntityPath, Points3D, RecordingStream};
+
+impl PointBlock {
+    /// Log the block to `rec` as `Points3D` at `entity_path`, with the
+    /// colors of [`render_points`](Self::render_points) if it has any.
+    ///
+    /// ```ignore
+    /// let rec = rerun::RecordingStreamBuilder::new("lidar").spawn()?;
+    /// block.log_to_rerun(&rec, "lidar/points")?;
+    /// ```
+    pub fn log_to_rerun(
+        &self,
+        rec: &RecordingStream,
+        entity_path: impl Into<EntityPath>,
+    ) -> Result<()> {
+        let RenderPoints { positions, colors } = self.render_points()?;
+        let mut points = Points3D::new(positions);
+        if let Some(colors) = colors {
+            points = points.with_colors(colors);
+        }
+        rec.log(entity_path, &points)
+            .map_err(|e| PcdError::Other(format!("Rerun logging failed: {}", e)))
+    }
+}
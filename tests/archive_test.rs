@@ -0,0 +1,174 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rs_pcd::io::ArchiveReader;
+use std::io::Cursor;
+
+fn pcd_bytes(value: f32) -> Vec<u8> {
+    format!(
+        "VERSION .7\nFIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nCOUNT 1 1 1\nWIDTH 2\nHEIGHT 1\n\
+         VIEWPOINT 0 0 0 1 0 0 0\nPOINTS 2\nDATA ascii\n{v} 0 0\n{v} 1 2\n",
+        v = value
+    )
+    .into_bytes()
+}
+
+fn tar_header(name: &str, size: usize, typeflag: u8) -> Vec<u8> {
+    let mut block = vec![0u8; 512];
+    block[..name.len()].copy_from_slice(name.as_bytes());
+    block[100..107].copy_from_slice(b"0000644");
+    block[124..135].copy_from_slice(format!("{:011o}", size).as_bytes());
+    block[156] = typeflag;
+    block[257..263].copy_from_slice(b"ustar\0");
+    block[263..265].copy_from_slice(b"00");
+    block[148..156].fill(b' ');
+    let sum: u32 = block.iter().map(|&b| b as u32).sum();
+    block[148..155].copy_from_slice(format!("{:06o}\0", sum).as_bytes());
+    block
+}
+
+fn tar_append(tar: &mut Vec<u8>, name: &str, data: &[u8], typeflag: u8) {
+    tar.extend(tar_header(name, data.len(), typeflag));
+    tar.extend_from_slice(data);
+    tar.resize(tar.len().next_multiple_of(512), 0);
+}
+
+fn build_zip(entries: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let mut zip = Vec::new();
+    let mut central = Vec::new();
+    for (name, data) in entries {
+        let offset = zip.len() as u32;
+        // Stored entries; CRC is not checked by the reader.
+        let mut common = Vec::new();
+        common.extend(20u16.to_le_bytes()); // version needed
+        common.extend(0u16.to_le_bytes()); // flags
+        common.extend(0u16.to_le_bytes()); // method
+        common.extend([0u8; 4]); // time, date
+        common.extend(0u32.to_le_bytes()); // crc
+        common.extend((data.len() as u32).to_le_bytes());
+        common.extend((data.len() as u32).to_le_bytes());
+        common.extend((name.len() as u16).to_le_bytes());
+        common.extend(0u16.to_le_bytes()); // extra
+
+        zip.extend(0x0403_4b50u32.to_le_bytes());
+        zip.extend(&common);
+        zip.extend(name.as_bytes());
+        zip.extend(data);
+
+        central.extend(0x0201_4b50u32.to_le_bytes());
+        central.extend(20u16.to_le_bytes()); // version made by
+        central.extend(&common);
+        central.extend([0u8; 6]); // comment, disk, internal attributes
+        central.extend([0u8; 4]); // external attributes
+        central.extend(offset.to_le_bytes());
+        central.extend(name.as_bytes());
+    }
+    let dir_offset = zip.len() as u32;
+    zip.extend(&central);
+    zip.extend(0x0605_4b50u32.to_le_bytes());
+    zip.extend([0u8; 4]);
+    zip.extend((entries.len() as u16).to_le_bytes());
+    zip.extend((entries.len() as u16).to_le_bytes());
+    zip.extend((central.len() as u32).to_le_bytes());
+    zip.extend(dir_offset.to_le_bytes());
+    zip.extend(0u16.to_le_bytes());
+    zip
+}
+
+#[test]
+fn test_tar_entries() {
+    let long_name = format!("{}/frame_0001.pcd", "d".repeat(120));
+    let mut tar = Vec::new();
+    tar_append(&mut tar, "scans/", &[], b'5');
+    tar_append(&mut tar, "scans/000000.pcd", &pcd_bytes(1.0), b'0');
+    tar_append(&mut tar, "README.txt", b"not a cloud", b'0');
+    let mut long = long_name.clone().into_bytes();
+    long.push(0);
+    tar_append(&mut tar, "././@LongLink", &long, b'L');
+    tar_append(&mut tar, "placeholder", &pcd_bytes(2.0), b'0');
+    tar.extend([0u8; 1024]);
+
+    let entries: Vec<_> = ArchiveReader::new(Cursor::new(tar))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].0, "scans/000000.pcd");
+    assert_eq!(entries[1].0, long_name);
+    let (x, _, z) = entries[1].1.xyz().unwrap();
+    assert_eq!(x, &[2.0, 2.0]);
+    assert_eq!(z[1], 2.0);
+}
+
+#[test]
+fn test_zip_entries() {
+    let zip = build_zip(&[
+        ("a.pcd", pcd_bytes(1.0)),
+        ("notes.txt", b"skip me".to_vec()),
+        ("b.PCD", b"VERSION .7\nFIELDS x\n".to_vec()),
+        ("c.pcd", pcd_bytes(3.0)),
+    ]);
+
+    let entries: Vec<_> = ArchiveReader::new(Cursor::new(zip)).unwrap().collect();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].as_ref().unwrap().0, "a.pcd");
+    // A broken entry does not stop iteration.
+    assert!(entries[1].is_err());
+    let (name, block) = entries[2].as_ref().unwrap();
+    assert_eq!(name, "c.pcd");
+    assert_eq!(block.xyz().unwrap().0, &[3.0, 3.0]);
+}
+
+#[test]
+fn test_archive_rejects_other_data() {
+    assert!(ArchiveReader::new(Cursor::new(pcd_bytes(1.0))).is_err());
+}
+
+#[test]
+fn test_archive_rejects_oversized_entries() {
+    use rs_pcd::PcdError;
+
+    // GNU base-256 size of 2^88 - 1 bytes.
+    let mut header = tar_header("huge.pcd", 0, b'0');
+    header[124] = 0x80;
+    header[125..136].fill(0xff);
+    header[148..156].fill(b' ');
+    let sum: u32 = header.iter().map(|&b| b as u32).sum();
+    header[148..155].copy_from_slice(format!("{:06o}\0", sum).as_bytes());
+    let mut entries = ArchiveReader::new(Cursor::new(header)).unwrap();
+    assert!(matches!(
+        entries.next(),
+        Some(Err(PcdError::InvalidDataFormat(_)))
+    ));
+    assert!(entries.next().is_none());
+
+    // An octal size past the end of the archive.
+    let mut tar = tar_header("big.pcd", 0o77_777_777_777, b'0');
+    tar.extend([0u8; 1024]);
+    let mut entries = ArchiveReader::new(Cursor::new(tar)).unwrap();
+    assert!(matches!(
+        entries.next(),
+        Some(Err(PcdError::InvalidDataFormat(_)))
+    ));
+
+    // A zip entry over the memory limit.
+    let zip = build_zip(&[("a.pcd", pcd_bytes(1.0))]);
+    let mut entries = ArchiveReader::new(Cursor::new(zip))
+        .unwrap()
+        .memory_limit(16);
+    assert!(matches!(
+        entries.next(),
+        Some(Err(PcdError::InvalidDataFormat(_)))
+    ));
+}
@@ -0,0 +1,170 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "async")]
+
+use rs_pcd::header::{DataFormat, PcdHeader, PcdHeaderBuilder, ValueType};
+use rs_pcd::io::{AsyncPcdReader, AsyncPcdWriter, PcdWriter, read_pcd_file};
+use rs_pcd::storage::PointBlock;
+use std::io::Cursor;
+
+fn header(n: usize, format: DataFormat) -> PcdHeader {
+    PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .add_field("ring", ValueType::U16)
+        .width(n as u32)
+        .data_format(format)
+        .build()
+        .unwrap()
+}
+
+fn block(n: usize) -> PointBlock {
+    let schema = [
+        ("x".to_string(), ValueType::F32),
+        ("ring".to_string(), ValueType::U16),
+    ];
+    let mut block = PointBlock::new(&schema, n);
+    for i in 0..n {
+        block.get_column_mut("x").unwrap().as_f32_mut().unwrap()[i] = i as f32 * 0.5;
+        block.get_column_mut("ring").unwrap().as_u16_mut().unwrap()[i] = i as u16;
+    }
+    block
+}
+
+fn encode(n: usize, format: DataFormat) -> Vec<u8> {
+    let mut out = Vec::new();
+    PcdWriter::new(&mut out)
+        .write_pcd(&header(n, format), &block(n))
+        .unwrap();
+    out
+}
+
+#[tokio::test]
+async fn test_async_read_all() {
+    for format in [
+        DataFormat::Ascii,
+        DataFormat::Binary,
+        DataFormat::BinaryCompressed,
+    ] {
+        let data = encode(10, format);
+        let reader = AsyncPcdReader::new(&data[..]).await.unwrap();
+        assert_eq!(reader.header().points, 10);
+        let block = reader.read_all().await.unwrap();
+        assert_eq!(block.len, 10);
+        assert_eq!(block.get_column("x").unwrap().as_f32().unwrap()[7], 3.5);
+        assert_eq!(block.get_column("ring").unwrap().as_u16().unwrap()[9], 9);
+    }
+}
+
+#[tokio::test]
+async fn test_async_read_chunks() {
+    for format in [DataFormat::Ascii, DataFormat::Binary] {
+        let data = encode(10, format);
+        let mut reader = AsyncPcdReader::new(&data[..]).await.unwrap();
+        let mut lens = Vec::new();
+        let mut last = 0.0;
+        while let Some(chunk) = reader.read_chunk(4).await.unwrap() {
+            lens.push(chunk.len);
+            last = *chunk
+                .get_column("x")
+                .unwrap()
+                .as_f32()
+                .unwrap()
+                .last()
+                .unwrap();
+        }
+        assert_eq!(lens, [4, 4, 2]);
+        assert_eq!(last, 4.5);
+        assert_eq!(reader.remaining(), 0);
+    }
+}
+
+#[tokio::test]
+async fn test_async_truncated_data() {
+    let data = encode(10, DataFormat::Binary);
+    let reader = AsyncPcdReader::new(&data[..data.len() - 3]).await.unwrap();
+    assert!(reader.read_all().await.is_err());
+}
+
+#[tokio::test]
+async fn test_async_write_pcd() {
+    let mut writer = AsyncPcdWriter::new(Vec::new());
+    writer
+        .write_pcd(&header(10, DataFormat::BinaryCompressed), &block(10))
+        .await
+        .unwrap();
+    assert_eq!(
+        writer.into_inner(),
+        encode(10, DataFormat::BinaryCompressed)
+    );
+}
+
+#[tokio::test]
+async fn test_async_stream_fixed_count() {
+    for format in [DataFormat::Ascii, DataFormat::Binary] {
+        let mut writer = AsyncPcdWriter::new(Vec::new());
+        writer.begin_stream(&header(10, format)).await.unwrap();
+        writer.write_chunk(&block(6)).await.unwrap();
+        writer.write_chunk(&block(4)).await.unwrap();
+        writer.finish().await.unwrap();
+
+        let data = writer.into_inner();
+        let read = AsyncPcdReader::new(&data[..])
+            .await
+            .unwrap()
+            .read_all()
+            .await
+            .unwrap();
+        assert_eq!(read.len, 10);
+        assert_eq!(read.get_column("ring").unwrap().as_u16().unwrap()[7], 1);
+    }
+
+    let mut writer = AsyncPcdWriter::new(Vec::new());
+    writer
+        .begin_stream(&header(10, DataFormat::Binary))
+        .await
+        .unwrap();
+    writer.write_chunk(&block(3)).await.unwrap();
+    assert!(writer.finish().await.is_err());
+}
+
+#[tokio::test]
+async fn test_async_stream_deferred() {
+    let mut writer = AsyncPcdWriter::new(Cursor::new(Vec::new()));
+    writer
+        .begin_deferred(&header(0, DataFormat::Binary))
+        .await
+        .unwrap();
+    for n in [5, 2, 4] {
+        writer.write_chunk(&block(n)).await.unwrap();
+    }
+    writer.finish_deferred().await.unwrap();
+
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), writer.into_inner().into_inner()).unwrap();
+    let read = read_pcd_file(file.path()).unwrap();
+    assert_eq!(read.len, 11);
+    assert_eq!(read.get_column("x").unwrap().as_f32().unwrap()[10], 1.5);
+}
+
+#[tokio::test]
+async fn test_async_stream_rejects_compressed() {
+    let mut writer = AsyncPcdWriter::new(Vec::new());
+    assert!(
+        writer
+            .begin_stream(&header(1, DataFormat::BinaryCompressed))
+            .await
+            .is_err()
+    );
+}
@@ -0,0 +1,221 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "pcap")]
+
+use rs_pcd::capture::{CaptureOptions, OusterIntrinsics, SensorModel, SweepReader};
+
+fn pcap_header() -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&0xa1b2c3d4u32.to_le_bytes());
+    out.extend_from_slice(&2u16.to_le_bytes());
+    out.extend_from_slice(&4u16.to_le_bytes());
+    out.extend_from_slice(&[0; 8]);
+    out.extend_from_slice(&65535u32.to_le_bytes());
+    out.extend_from_slice(&1u32.to_le_bytes());
+    out
+}
+
+/// Append one Ethernet/IPv4 frame per fragment of a UDP datagram.
+fn push_udp(pcap: &mut Vec<u8>, time: f64, port: u16, payload: &[u8], mtu_payload: usize) {
+    let mut udp = Vec::new();
+    udp.extend_from_slice(&5000u16.to_be_bytes());
+    udp.extend_from_slice(&port.to_be_bytes());
+    udp.extend_from_slice(&((payload.len() + 8) as u16).to_be_bytes());
+    udp.extend_from_slice(&[0, 0]);
+    udp.extend_from_slice(payload);
+
+    for (i, part) in udp.chunks(mtu_payload).enumerate() {
+        let offset = i * mtu_payload / 8;
+        let more = (i + 1) * mtu_payload < udp.len();
+        let mut frame = vec![0u8; 12];
+        frame.extend_from_slice(&0x0800u16.to_be_bytes());
+        frame.push(0x45);
+        frame.push(0);
+        frame.extend_from_slice(&((20 + part.len()) as u16).to_be_bytes());
+        frame.extend_from_slice(&42u16.to_be_bytes());
+        frame.extend_from_slice(&((offset as u16) | if more { 0x2000 } else { 0 }).to_be_bytes());
+        frame.extend_from_slice(&[64, 17, 0, 0, 192, 168, 1, 201, 192, 168, 1, 100]);
+        frame.extend_from_slice(part);
+
+        pcap.extend_from_slice(&(time.trunc() as u32).to_le_bytes());
+        pcap.extend_from_slice(&((time.fract() * 1e6).round() as u32).to_le_bytes());
+        pcap.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        pcap.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        pcap.extend_from_slice(&frame);
+    }
+}
+
+fn vlp16_packet(first_azimuth: u32, toh_us: u32) -> Vec<u8> {
+    let mut data = Vec::new();
+    for b in 0..12 {
+        data.extend_from_slice(&[0xFF, 0xEE]);
+        let az = ((first_azimuth + b * 20) % 36000) as u16;
+        data.extend_from_slice(&az.to_le_bytes());
+        for ch in 0..32 {
+            let distance: u16 = if ch == 1 { 0 } else { 500 };
+            data.extend_from_slice(&distance.to_le_bytes());
+            data.push(10);
+        }
+    }
+    data.extend_from_slice(&toh_us.to_le_bytes());
+    data.extend_from_slice(&[0x37, 0x22]);
+    data
+}
+
+#[test]
+fn test_velodyne_sweeps() {
+    let mut pcap = pcap_header();
+    push_udp(&mut pcap, 7200.001, 2368, &vlp16_packet(35800, 1000), 1500);
+    // Position packets are ignored by size.
+    push_udp(&mut pcap, 7200.0015, 8308, &[0; 512], 1500);
+    push_udp(&mut pcap, 7200.0023, 2368, &vlp16_packet(40, 2328), 1500);
+
+    let options = CaptureOptions {
+        model: SensorModel::Velodyne,
+        ..Default::default()
+    };
+    let sweeps: Vec<_> = SweepReader::new(&pcap[..], options)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(sweeps.len(), 2);
+    assert_eq!(sweeps[0].len, 10 * 31);
+    assert_eq!(sweeps[1].len, 14 * 31);
+
+    let (x, y, z, intensity, ring, t) = sweeps[0].xyzirt().unwrap();
+    let el = (-15f32).to_radians();
+    let az = 358f32.to_radians();
+    assert!((x[0] - el.cos() * az.sin()).abs() < 1e-5);
+    assert!((y[0] - el.cos() * az.cos()).abs() < 1e-5);
+    assert!((z[0] - el.sin()).abs() < 1e-5);
+    assert_eq!(intensity[0], 10.0);
+    assert_eq!(ring[0], 0);
+    // Channel 15 (laser 15, +15 degrees) is the top ring.
+    assert_eq!(ring[14], 15);
+    assert!((t[0] - 7200.001).abs() < 1e-9);
+    assert!(t.windows(2).all(|w| w[0] <= w[1]));
+}
+
+fn ouster_packet(frame_id: u16, first_column: u16, channels: usize) -> Vec<u8> {
+    let mut data = Vec::new();
+    for c in 0..16u16 {
+        data.extend_from_slice(&(1_000_000_000u64 + c as u64 * 1000).to_le_bytes());
+        data.extend_from_slice(&(first_column + c).to_le_bytes());
+        data.extend_from_slice(&frame_id.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        for ch in 0..channels {
+            let range: u32 = if ch == 0 { 2000 } else { 0 };
+            data.extend_from_slice(&range.to_le_bytes());
+            data.extend_from_slice(&0u16.to_le_bytes());
+            data.extend_from_slice(&77u16.to_le_bytes());
+            data.extend_from_slice(&[0; 4]);
+        }
+        let status: u32 = if c == 15 { 0 } else { 0xFFFF_FFFF };
+        data.extend_from_slice(&status.to_le_bytes());
+    }
+    data
+}
+
+#[test]
+fn test_ouster_fragmented_packets() {
+    let mut pcap = pcap_header();
+    push_udp(&mut pcap, 1.0, 7502, &ouster_packet(3, 0, 16), 1480);
+    push_udp(&mut pcap, 1.1, 7502, &ouster_packet(4, 0, 16), 1480);
+
+    let intrinsics = OusterIntrinsics::uniform(16, 33.2, 1024);
+    let options = CaptureOptions {
+        model: SensorModel::Ouster(intrinsics),
+        port: Some(7502),
+        min_range: 0.5,
+    };
+    let sweeps: Vec<_> = SweepReader::new(&pcap[..], options)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(sweeps.len(), 2);
+    assert_eq!(sweeps[0].len, 15);
+
+    let (x, y, z, intensity, ring, t) = sweeps[0].xyzirt().unwrap();
+    let phi = 16.6f32.to_radians();
+    assert!((x[0] - 2.0 * phi.cos()).abs() < 1e-4);
+    assert!(y[0].abs() < 1e-4);
+    assert!((z[0] - 2.0 * phi.sin()).abs() < 1e-4);
+    assert_eq!(intensity[0], 77.0);
+    assert_eq!(ring[0], 0);
+    assert!((t[1] - 1.000001).abs() < 1e-9);
+}
+
+/// Append an Ethernet/IPv4 frame holding `part` of datagram `id` at byte
+/// `offset`.
+fn push_fragment(pcap: &mut Vec<u8>, id: u16, offset: usize, more: bool, part: &[u8]) {
+    let mut frame = vec![0u8; 12];
+    frame.extend_from_slice(&0x0800u16.to_be_bytes());
+    frame.extend_from_slice(&[0x45, 0]);
+    frame.extend_from_slice(&((20 + part.len()) as u16).to_be_bytes());
+    frame.extend_from_slice(&id.to_be_bytes());
+    frame.extend_from_slice(&((offset / 8) as u16 | if more { 0x2000 } else { 0 }).to_be_bytes());
+    frame.extend_from_slice(&[64, 17, 0, 0, 192, 168, 1, 201, 192, 168, 1, 100]);
+    frame.extend_from_slice(part);
+    pcap.extend_from_slice(&[0; 8]);
+    pcap.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+    pcap.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+    pcap.extend_from_slice(&frame);
+}
+
+#[test]
+fn test_pcap_bounds() {
+    use rs_pcd::capture::PcapReader;
+    use rs_pcd::capture::pcap::MAX_PENDING_DATAGRAMS;
+
+    // A record longer than the snapshot length.
+    let mut pcap = pcap_header();
+    pcap.extend_from_slice(&[0; 8]);
+    pcap.extend_from_slice(&u32::MAX.to_le_bytes());
+    pcap.extend_from_slice(&u32::MAX.to_le_bytes());
+    let mut packets = PcapReader::new(&pcap[..]).unwrap();
+    assert!(packets.next().unwrap().is_err());
+
+    let mut udp = 5000u16.to_be_bytes().to_vec();
+    udp.extend_from_slice(&7502u16.to_be_bytes());
+    udp.extend_from_slice(&24u16.to_be_bytes());
+    udp.extend_from_slice(&[0; 2]);
+    udp.extend_from_slice(&[1; 16]);
+
+    // Repeated fragments are ignored.
+    let mut pcap = pcap_header();
+    push_fragment(&mut pcap, 1, 0, true, &udp[..16]);
+    push_fragment(&mut pcap, 1, 0, true, &udp[..16]);
+    push_fragment(&mut pcap, 1, 16, false, &udp[16..]);
+    let packets: Vec<_> = PcapReader::new(&pcap[..])
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(packets.len(), 1);
+    assert_eq!(packets[0].payload, [1; 16]);
+
+    // The oldest incomplete datagram is dropped to make room.
+    let mut pcap = pcap_header();
+    push_fragment(&mut pcap, 1, 0, true, &udp[..16]);
+    for id in 2..2 + MAX_PENDING_DATAGRAMS as u16 {
+        push_fragment(&mut pcap, id, 0, true, &udp[..16]);
+    }
+    push_fragment(&mut pcap, 2, 16, false, &udp[16..]);
+    push_fragment(&mut pcap, 1, 16, false, &udp[16..]);
+    let packets: Vec<_> = PcapReader::new(&pcap[..])
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(packets.len(), 1);
+}
@@ -0,0 +1,391 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "cli")]
+
+use rs_pcd::header::{DataFormat, PcdHeaderBuilder, ValueType};
+use rs_pcd::io::write_pcd_file;
+use rs_pcd::storage::PointBlock;
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn pcd(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_pcd"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+/// `n` points with x = i, intensity = i % 7 and a NaN x every 10th point.
+fn write_cloud(path: &Path, n: usize, format: DataFormat) {
+    let header = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .add_field("intensity", ValueType::U8)
+        .width(n as u32)
+        .data_format(format)
+        .build()
+        .unwrap();
+    let schema = [
+        ("x".to_string(), ValueType::F32),
+        ("intensity".to_string(), ValueType::U8),
+    ];
+    let mut block = PointBlock::new(&schema, n);
+    for i in 0..n {
+        block.get_column_mut("x").unwrap().as_f32_mut().unwrap()[i] =
+            if i % 10 == 9 { f32::NAN } else { i as f32 };
+        block
+            .get_column_mut("intensity")
+            .unwrap()
+            .as_u8_mut()
+            .unwrap()[i] = (i % 7) as u8;
+    }
+    write_pcd_file(path, &header, &block).unwrap();
+}
+
+#[test]
+fn test_info() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cloud.pcd");
+    write_cloud(&path, 1000, DataFormat::Binary);
+
+    let out = stdout(&pcd(&["info", "--chunk", "64", path.to_str().unwrap()]));
+    assert!(out.contains("points      1000"), "{}", out);
+    assert!(out.contains("data        binary"), "{}", out);
+    let x = out
+        .lines()
+        .find(|l| l.trim_start().starts_with("x "))
+        .unwrap();
+    assert_eq!(
+        x.split_whitespace().collect::<Vec<_>>(),
+        ["x", "F4", "1", "0", "998", "100"]
+    );
+    let intensity = out
+        .lines()
+        .find(|l| l.trim_start().starts_with("intensity"))
+        .unwrap();
+    assert_eq!(
+        intensity.split_whitespace().collect::<Vec<_>>(),
+        ["intensity", "U1", "1", "0", "6", "0"]
+    );
+}
+
+#[test]
+fn test_usage_errors() {
+    let out = pcd(&["frobnicate"]);
+    assert_eq!(out.status.code(), Some(2));
+
+    let out = pcd(&["info"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("no input files"));
+
+    let out = pcd(&["info", "--bogus", "x.pcd"]);
+    assert!(!out.status.success());
+}
+
+fn read_x(path: &Path) -> Vec<f32> {
+    let block = rs_pcd::io::read_pcd_file(path).unwrap();
+    block.get_column("x").unwrap().as_f32().unwrap().to_vec()
+}
+
+#[test]
+fn test_convert_between_pcd_formats() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src.pcd");
+    write_cloud(&src, 500, DataFormat::Binary);
+    let expected = read_x(&src);
+
+    let mut prev = src;
+    for (i, format) in ["ascii", "binary_compressed", "binary"].iter().enumerate() {
+        let dst = dir.path().join(format!("step{}.pcd", i));
+        stdout(&pcd(&[
+            "convert",
+            "--chunk",
+            "33",
+            "--format",
+            format,
+            prev.to_str().unwrap(),
+            dst.to_str().unwrap(),
+        ]));
+        let header = rs_pcd::io::PcdReader::open(&dst).unwrap().header().clone();
+        assert_eq!(header.data, format.parse().unwrap());
+        let x = read_x(&dst);
+        assert_eq!(x.len(), expected.len());
+        for (a, b) in x.iter().zip(&expected) {
+            assert!(a == b || (a.is_nan() && b.is_nan()));
+        }
+        prev = dst;
+    }
+}
+
+#[test]
+fn test_convert_other_formats() {
+    let dir = tempfile::tempdir().unwrap();
+    let csv = dir.path().join("cloud.csv");
+    std::fs::write(&csv, "x,y,z\n1,2,3\n4,5,6\n").unwrap();
+    let out = dir.path().join("cloud.pcd");
+    stdout(&pcd(&[
+        "convert",
+        csv.to_str().unwrap(),
+        out.to_str().unwrap(),
+    ]));
+    assert_eq!(read_x(&out), [1.0, 4.0]);
+
+    let back = dir.path().join("back.csv");
+    stdout(&pcd(&[
+        "convert",
+        out.to_str().unwrap(),
+        back.to_str().unwrap(),
+    ]));
+    assert!(std::fs::read_to_string(&back).unwrap().starts_with("x,y,z"));
+
+    let bad = pcd(&["convert", out.to_str().unwrap(), "cloud.unknown"]);
+    assert!(!bad.status.success());
+}
+
+fn write_xyz(path: &Path, points: &[[f32; 3]], viewpoint: [f64; 7]) {
+    let header = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .add_field("y", ValueType::F32)
+        .add_field("z", ValueType::F32)
+        .width(points.len() as u32)
+        .viewpoint(viewpoint)
+        .build()
+        .unwrap();
+    let schema = ["x", "y", "z"].map(|n| (n.to_string(), ValueType::F32));
+    let mut block = PointBlock::new(&schema, points.len());
+    for (axis, name) in ["x", "y", "z"].iter().enumerate() {
+        let column = block.get_column_mut(name).unwrap().as_f32_mut().unwrap();
+        for (value, point) in column.iter_mut().zip(points) {
+            *value = point[axis];
+        }
+    }
+    write_pcd_file(path, &header, &block).unwrap();
+}
+
+#[test]
+fn test_merge() {
+    let dir = tempfile::tempdir().unwrap();
+    let a = dir.path().join("a.pcd");
+    let b = dir.path().join("b.pcd");
+    write_cloud(&a, 300, DataFormat::Binary);
+    write_cloud(&b, 200, DataFormat::Ascii);
+    let mut expected = read_x(&a);
+    expected.extend(read_x(&b));
+
+    for format in ["binary", "binary_compressed"] {
+        let out = dir.path().join(format!("merged_{}.pcd", format));
+        stdout(&pcd(&[
+            "merge",
+            "--chunk",
+            "64",
+            "--format",
+            format,
+            "--output",
+            out.to_str().unwrap(),
+            a.to_str().unwrap(),
+            b.to_str().unwrap(),
+        ]));
+        let header = rs_pcd::io::PcdReader::open(&out).unwrap().header().clone();
+        assert_eq!((header.points, header.width, header.height), (500, 500, 1));
+        let x = read_x(&out);
+        assert_eq!(x.len(), expected.len());
+        for (a, b) in x.iter().zip(&expected) {
+            assert!(a == b || (a.is_nan() && b.is_nan()));
+        }
+    }
+
+    let other = dir.path().join("xyz.pcd");
+    write_xyz(&other, &[[0.0; 3]], [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+    let out = dir.path().join("bad.pcd");
+    let bad = pcd(&[
+        "merge",
+        "--output",
+        out.to_str().unwrap(),
+        a.to_str().unwrap(),
+        other.to_str().unwrap(),
+    ]);
+    assert!(!bad.status.success());
+    assert!(!out.exists());
+}
+
+#[test]
+fn test_merge_apply_viewpoint() {
+    let dir = tempfile::tempdir().unwrap();
+    let a = dir.path().join("a.pcd");
+    let b = dir.path().join("b.pcd");
+    write_xyz(&a, &[[1.0, 2.0, 3.0]], [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+    // 90 degrees about z, then shifted by (10, 0, 0).
+    let half = std::f64::consts::FRAC_1_SQRT_2;
+    write_xyz(
+        &b,
+        &[[1.0, 0.0, 5.0]],
+        [10.0, 0.0, 0.0, half, 0.0, 0.0, half],
+    );
+
+    let out = dir.path().join("merged.pcd");
+    stdout(&pcd(&[
+        "merge",
+        "--apply-viewpoint",
+        "--output",
+        out.to_str().unwrap(),
+        a.to_str().unwrap(),
+        b.to_str().unwrap(),
+    ]));
+    let reader = rs_pcd::io::PcdReader::open(&out).unwrap();
+    assert_eq!(
+        reader.header().viewpoint,
+        [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]
+    );
+    let block = reader.read_all().unwrap();
+    let column = |name| block.get_column(name).unwrap().as_f32().unwrap().to_vec();
+    let (x, y, z) = (column("x"), column("y"), column("z"));
+    assert_eq!((x[0], y[0], z[0]), (1.0, 2.0, 3.0));
+    assert!((x[1] - 10.0).abs() < 1e-5 && (y[1] - 1.0).abs() < 1e-5 && z[1] == 5.0);
+}
+
+#[test]
+fn test_filter() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src.pcd");
+    write_cloud(&src, 100, DataFormat::Binary);
+
+    for out in ["kept.pcd", "kept.csv"] {
+        let out = dir.path().join(out);
+        stdout(&pcd(&[
+            "filter",
+            "--chunk",
+            "16",
+            "--remove-nan",
+            "--where",
+            "intensity>=5",
+            "--where=x<50",
+            src.to_str().unwrap(),
+            out.to_str().unwrap(),
+        ]));
+        if out.extension().unwrap() == "pcd" {
+            let header = rs_pcd::io::PcdReader::open(&out).unwrap().header().clone();
+            assert_eq!((header.width, header.height), (header.points as u32, 1));
+            let expected: Vec<f32> = (0..50)
+                .filter(|i| i % 10 != 9 && i % 7 >= 5)
+                .map(|i| i as f32)
+                .collect();
+            assert_eq!(read_x(&out), expected);
+        }
+    }
+
+    let out = dir.path().join("crop.pcd");
+    let bad = pcd(&[
+        "filter",
+        "--crop",
+        "0,0,0,1,1",
+        src.to_str().unwrap(),
+        out.to_str().unwrap(),
+    ]);
+    assert!(!bad.status.success());
+    // write_cloud has no y/z columns.
+    let bad = pcd(&[
+        "filter",
+        "--crop",
+        "0,0,0,1,1,1",
+        src.to_str().unwrap(),
+        out.to_str().unwrap(),
+    ]);
+    assert!(!bad.status.success());
+}
+
+#[test]
+fn test_diff() {
+    let dir = tempfile::tempdir().unwrap();
+    let a = dir.path().join("a.pcd");
+    let b = dir.path().join("b.pcd");
+    write_cloud(&a, 300, DataFormat::Binary);
+    write_cloud(&b, 300, DataFormat::BinaryCompressed);
+
+    let out = stdout(&pcd(&[
+        "diff",
+        "--chunk",
+        "7",
+        a.to_str().unwrap(),
+        b.to_str().unwrap(),
+    ]));
+    assert!(out.contains("points      300 300"), "{}", out);
+
+    let c = dir.path().join("c.pcd");
+    write_xyz(&c, &[[0.0; 3]], [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+    let out = pcd(&["diff", a.to_str().unwrap(), c.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(1));
+    let text = String::from_utf8(out.stdout).unwrap();
+    assert!(text.contains("only in a   intensity"), "{}", text);
+    assert!(text.contains("only in b   y"), "{}", text);
+}
+
+#[test]
+fn test_validate_repair() {
+    let dir = tempfile::tempdir().unwrap();
+    let good = dir.path().join("good.pcd");
+    write_cloud(&good, 50, DataFormat::Binary);
+    let bad = dir.path().join("bad.pcd");
+    let text = std::fs::read(&good).unwrap();
+    let split = text.windows(5).position(|w| w == b"DATA ").unwrap();
+    let mut bytes = String::from_utf8(text[..split].to_vec())
+        .unwrap()
+        .replace("POINTS 50", "POINTS 500")
+        .into_bytes();
+    bytes.extend_from_slice(&text[split..]);
+    std::fs::write(&bad, bytes).unwrap();
+
+    let out = stdout(&pcd(&["validate", good.to_str().unwrap()]));
+    assert!(out.contains(": ok"), "{}", out);
+    let out = pcd(&["validate", good.to_str().unwrap(), bad.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(
+        String::from_utf8(out.stdout)
+            .unwrap()
+            .contains("POINTS says 500")
+    );
+
+    let fixed = dir.path().join("fixed.pcd");
+    stdout(&pcd(&[
+        "validate",
+        "--repair",
+        "--output",
+        fixed.to_str().unwrap(),
+        bad.to_str().unwrap(),
+    ]));
+    assert_eq!(read_x(&fixed).len(), 50);
+
+    // In place, also when --output names the input.
+    let copy = dir.path().join("copy.pcd");
+    std::fs::copy(&bad, &copy).unwrap();
+    stdout(&pcd(&["validate", "--repair", bad.to_str().unwrap()]));
+    stdout(&pcd(&["validate", bad.to_str().unwrap()]));
+    assert!(!dir.path().join("bad.pcd.repair").exists());
+    stdout(&pcd(&[
+        "validate",
+        "--repair",
+        "--output",
+        copy.to_str().unwrap(),
+        copy.to_str().unwrap(),
+    ]));
+    assert_eq!(read_x(&copy).len(), 50);
+}
@@ -0,0 +1,116 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rs_pcd::header::{DataFormat, PcdHeaderBuilder, ValueType};
+use rs_pcd::io::dataset::{find_files, load_dir, load_dir_with};
+use rs_pcd::io::sequence::parse_timestamp;
+use rs_pcd::io::{PcdSequence, write_pcd_file};
+use rs_pcd::storage::PointBlock;
+use std::path::Path;
+
+fn write_frame(path: &Path, n: usize) {
+    let header = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .width(n as u32)
+        .data_format(DataFormat::Binary)
+        .build()
+        .unwrap();
+    let mut block = PointBlock::new(&[("x".to_string(), ValueType::F32)], n);
+    block
+        .get_column_mut("x")
+        .unwrap()
+        .as_f32_mut()
+        .unwrap()
+        .fill(n as f32);
+    write_pcd_file(path, &header, &block).unwrap();
+}
+
+#[test]
+fn test_load_dir_order_and_pattern() {
+    let dir = tempfile::tempdir().unwrap();
+    for n in [3, 1, 2] {
+        write_frame(&dir.path().join(format!("frame_{:03}.pcd", n)), n);
+    }
+    write_frame(&dir.path().join("other_009.pcd"), 9);
+    std::fs::write(dir.path().join("notes.txt"), "x").unwrap();
+
+    let all = load_dir(dir.path().to_str().unwrap()).unwrap();
+    let lens: Vec<usize> = all.iter().map(|(_, b)| b.len).collect();
+    assert_eq!(lens, [1, 2, 3, 9]);
+
+    let pattern = dir.path().join("frame_???.pcd");
+    let files = find_files(pattern.to_str().unwrap()).unwrap();
+    assert_eq!(files.len(), 3);
+    assert!(files[0].ends_with("frame_001.pcd"));
+
+    let mut seen = Vec::new();
+    load_dir_with(dir.path().join("*_00*").to_str().unwrap(), |path, block| {
+        seen.push((path.file_name().unwrap().to_owned(), block.len));
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(seen.len(), 4);
+    assert_eq!(seen[3], ("other_009.pcd".into(), 9));
+}
+
+#[test]
+fn test_parse_timestamp() {
+    assert_eq!(
+        parse_timestamp("1541234567.250000.pcd"),
+        Some(1541234567.25)
+    );
+    assert_eq!(
+        parse_timestamp("lidar_1541234567250.pcd"),
+        Some(1541234567.25)
+    );
+    assert_eq!(
+        parse_timestamp("1541234567250000000.pcd.gz"),
+        Some(1541234567.25)
+    );
+    assert_eq!(parse_timestamp("frame_000123.pcd"), None);
+    assert_eq!(parse_timestamp("cloud.pcd"), None);
+}
+
+#[test]
+fn test_sequence_time_order() {
+    let dir = tempfile::tempdir().unwrap();
+    write_frame(&dir.path().join("scan_1700000002.500.pcd"), 3);
+    write_frame(&dir.path().join("scan_1700000001.000.pcd"), 1);
+    write_frame(&dir.path().join("scan_1700000002.000.pcd"), 2);
+
+    let seq = PcdSequence::from_dir(dir.path().to_str().unwrap()).unwrap();
+    assert_eq!(seq.len(), 3);
+    assert_eq!(seq.duration(), Some(1.5));
+    assert_eq!(seq.nearest(1700000002.1), Some(1));
+    assert_eq!(seq.load(2).unwrap().unwrap().len, 3);
+
+    let lens: Vec<usize> = seq.iter().map(|f| f.unwrap().1.len).collect();
+    assert_eq!(lens, [1, 2, 3]);
+}
+
+#[test]
+fn test_sequence_memory_frames() {
+    let mut seq = PcdSequence::new();
+    seq.push(
+        Some(2.0),
+        PointBlock::new(&[("x".to_string(), ValueType::F32)], 2),
+    );
+    seq.push(
+        Some(1.0),
+        PointBlock::new(&[("x".to_string(), ValueType::F32)], 1),
+    );
+    seq.sort();
+    let (t, block) = seq.iter().next().unwrap().unwrap();
+    assert_eq!((t, block.len), (Some(1.0), 1));
+}
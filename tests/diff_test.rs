@@ -0,0 +1,81 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rs_pcd::header::ValueType;
+use rs_pcd::storage::{PointBlock, Tolerance, diff_blocks};
+
+fn block(fields: &[(&str, ValueType)], x: &[f32]) -> PointBlock {
+    let schema: Vec<_> = fields.iter().map(|(n, t)| (n.to_string(), *t)).collect();
+    let mut block = PointBlock::new(&schema, x.len());
+    block
+        .get_column_mut("x")
+        .unwrap()
+        .as_f32_mut()
+        .unwrap()
+        .copy_from_slice(x);
+    block
+}
+
+#[test]
+fn test_diff_values() {
+    let fields = [("x", ValueType::F32), ("label", ValueType::U16)];
+    let a = block(&fields, &[1.0, f32::NAN, 100.0, 4.0]);
+    let b = block(&fields, &[1.0, f32::NAN, 101.0, 4.5]);
+
+    let exact = diff_blocks(&a, &a, Tolerance::default());
+    assert!(exact.is_match());
+
+    let diff = diff_blocks(&a, &b, Tolerance::default());
+    assert!(!diff.is_match());
+    let x = &diff.fields[0];
+    assert_eq!(
+        (x.compared, x.mismatches, x.first_mismatch),
+        (4, 2, Some(2))
+    );
+    assert_eq!(x.max_abs, 1.0);
+    assert!((x.max_rel - 0.5 / 4.5).abs() < 1e-12);
+    assert_eq!(diff.fields[1].mismatches, 0);
+
+    // 1.0 off at 100 is within 1%, 0.5 off at 4.5 is within the absolute bound.
+    assert!(diff_blocks(&a, &b, Tolerance::new(0.5, 0.0)).fields[0].mismatches == 1);
+    assert!(diff_blocks(&a, &b, Tolerance::new(0.5, 0.01)).is_match());
+
+    let c = block(&fields, &[1.0, 2.0, 100.0, 4.0]);
+    let diff = diff_blocks(&a, &c, Tolerance::new(1.0, 1.0));
+    assert_eq!(diff.fields[0].first_mismatch, Some(1));
+}
+
+#[test]
+fn test_diff_schema_and_points() {
+    let a = block(&[("x", ValueType::F32), ("y", ValueType::F32)], &[1.0, 2.0]);
+    let b = block(
+        &[
+            ("x", ValueType::F32),
+            ("y", ValueType::F64),
+            ("z", ValueType::F32),
+        ],
+        &[1.0, 2.0, 3.0],
+    );
+    let diff = diff_blocks(&a, &b, Tolerance::default());
+    assert!(diff.only_in_a.is_empty());
+    assert_eq!(diff.only_in_b, ["z"]);
+    assert_eq!(
+        diff.type_changes,
+        [("y".to_string(), ValueType::F32, ValueType::F64)]
+    );
+    assert_eq!((diff.points_a, diff.points_b), (2, 3));
+    assert_eq!(diff.fields.len(), 2);
+    assert!(diff.fields.iter().all(|f| f.mismatches == 0));
+    assert!(!diff.is_match());
+}
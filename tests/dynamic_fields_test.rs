@@ -14,7 +14,7 @@
 
 use rs_pcd::header::{DataFormat, PcdHeader, ValueType};
 use rs_pcd::io::{PcdReader, PcdWriter};
-use rs_pcd::storage::PointBlock;
+use rs_pcd::storage::{Colormap, LengthUnit, PointBlock};
 use std::io::Cursor;
 
 #[test]
@@ -91,8 +91,10 @@ fn test_dynamic_fields_binary() {
         width: num_points as u32,
         height: 1,
         viewpoint: [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0],
-        points: num_points as usize,
+        points: num_points,
         data: DataFormat::Binary,
+        comments: vec![],
+        extras: vec![],
     };
 
     // Write to buffer
@@ -163,8 +165,10 @@ fn test_dynamic_fields_ascii() {
         width: num_points as u32,
         height: 1,
         viewpoint: [0.0; 7],
-        points: num_points as usize,
+        points: num_points,
         data: DataFormat::Ascii,
+        comments: vec![],
+        extras: vec![],
     };
 
     let mut buffer = Vec::new();
@@ -181,8 +185,8 @@ fn test_dynamic_fields_ascii() {
     let read_block = reader.read_all().expect("Read failed");
 
     let id_col = read_block.get_column("id").unwrap().as_i32().unwrap();
-    for i in 0..num_points {
-        assert_eq!(id_col[i], -(i as i32));
+    for (i, &id) in id_col.iter().enumerate().take(num_points) {
+        assert_eq!(id, -(i as i32));
     }
 }
 
@@ -225,8 +229,10 @@ fn test_dynamic_fields_compressed() {
         width: num_points as u32,
         height: 1,
         viewpoint: [0.0; 7],
-        points: num_points as usize,
+        points: num_points,
         data: DataFormat::BinaryCompressed,
+        comments: vec![],
+        extras: vec![],
     };
 
     let mut buffer = Vec::new();
@@ -247,3 +253,484 @@ fn test_dynamic_fields_compressed() {
         assert_eq!(id_col[i], i as u32 + 500);
     }
 }
+
+#[test]
+fn test_builder_multi_count_fields() {
+    use rs_pcd::header::PcdHeaderBuilder;
+
+    let header = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .add_rgb_packed()
+        .add_descriptor("fpfh", 3)
+        .add_field_with_count("hist", ValueType::U16, 2)
+        .width(2)
+        .data_format(DataFormat::Ascii)
+        .build()
+        .unwrap();
+    assert_eq!(header.fields, ["x", "rgb", "fpfh", "hist"]);
+    assert_eq!(header.counts, [1, 1, 3, 2]);
+    assert_eq!(header.types, ['F', 'U', 'F', 'U']);
+    assert_eq!(header.sizes, [4, 4, 4, 2]);
+
+    let mut buffer = header.to_string().into_bytes();
+    buffer.extend_from_slice(b"1 255 0.1 0.2 0.3 4 5\n2 65280 1.1 1.2 1.3 6 7\n");
+    let block = PcdReader::new(Cursor::new(buffer))
+        .unwrap()
+        .read_all()
+        .unwrap();
+    assert_eq!(block.len, 2);
+    let fpfh = block.get_column("fpfh").unwrap().as_f32().unwrap();
+    assert_eq!(fpfh, [0.1, 0.2, 0.3, 1.1, 1.2, 1.3]);
+    let hist = block.get_column("hist").unwrap().as_u16().unwrap();
+    assert_eq!(hist, [4, 5, 6, 7]);
+    assert_eq!(
+        block.get_column("rgb").unwrap().as_u32().unwrap(),
+        [255, 65280]
+    );
+
+    for format in [DataFormat::Binary, DataFormat::BinaryCompressed] {
+        let mut header = header.clone();
+        header.data = format;
+        let mut buffer = Vec::new();
+        PcdWriter::new(&mut buffer)
+            .write_pcd(&header, &block)
+            .unwrap();
+        let back = PcdReader::new(Cursor::new(buffer))
+            .unwrap()
+            .read_all()
+            .unwrap();
+        let fpfh = back.get_column("fpfh").unwrap().as_f32().unwrap();
+        assert_eq!(fpfh, [0.1, 0.2, 0.3, 1.1, 1.2, 1.3], "{:?}", format);
+        let hist = back.get_column("hist").unwrap().as_u16().unwrap();
+        assert_eq!(hist, [4, 5, 6, 7], "{:?}", format);
+    }
+
+    let zero = PcdHeaderBuilder::new()
+        .add_field_with_count("hist", ValueType::U16, 0)
+        .width(1)
+        .build();
+    assert!(zero.is_err());
+    let spaced = PcdHeaderBuilder::new()
+        .add_field("bad name", ValueType::F32)
+        .width(1)
+        .build();
+    assert!(spaced.is_err());
+}
+
+#[test]
+fn test_64bit_integer_fields() {
+    use rs_pcd::header::PcdHeaderBuilder;
+
+    let header = PcdHeaderBuilder::new()
+        .add_field("t", ValueType::U64)
+        .add_field("id", ValueType::I64)
+        .width(2)
+        .data_format(DataFormat::Ascii)
+        .build()
+        .unwrap();
+    assert_eq!(header.types, ['U', 'I']);
+    assert_eq!(header.sizes, [8, 8]);
+
+    let mut buffer = header.to_string().into_bytes();
+    buffer
+        .extend_from_slice(b"18446744073709551615 -9223372036854775808\n1700000000123456789 42\n");
+    let block = PcdReader::new(Cursor::new(buffer))
+        .unwrap()
+        .read_all()
+        .unwrap();
+    let t = [u64::MAX, 1_700_000_000_123_456_789];
+    let id = [i64::MIN, 42];
+    assert_eq!(block.get_column("t").unwrap().as_u64().unwrap(), t);
+    assert_eq!(block.get_column("id").unwrap().as_i64().unwrap(), id);
+
+    for format in [
+        DataFormat::Ascii,
+        DataFormat::Binary,
+        DataFormat::BinaryCompressed,
+    ] {
+        let mut header = header.clone();
+        header.data = format;
+        let mut buffer = Vec::new();
+        PcdWriter::new(&mut buffer)
+            .write_pcd(&header, &block)
+            .unwrap();
+        let back = PcdReader::new(Cursor::new(buffer))
+            .unwrap()
+            .read_all()
+            .unwrap();
+        assert_eq!(
+            back.get_column("t").unwrap().as_u64().unwrap(),
+            t,
+            "{:?}",
+            format
+        );
+        assert_eq!(
+            back.get_column("id").unwrap().as_i64().unwrap(),
+            id,
+            "{:?}",
+            format
+        );
+    }
+}
+
+#[test]
+fn test_schema_mismatch_errors() {
+    use rs_pcd::decoder::binary::BinaryReader;
+    use rs_pcd::header::PcdHeaderBuilder;
+    use rs_pcd::layout::PcdLayout;
+    use rs_pcd::{Operation, PcdError};
+
+    let header = PcdHeaderBuilder::xyzi().width(1).build().unwrap();
+    let wrong = PointBlock::new(
+        &[
+            ("x".to_string(), ValueType::F32),
+            ("y".to_string(), ValueType::F32),
+            ("z".to_string(), ValueType::F32),
+            ("intensity".to_string(), ValueType::U16),
+        ],
+        1,
+    );
+    let err = PcdWriter::new(Vec::new())
+        .write_pcd(&header, &wrong)
+        .unwrap_err();
+    assert!(matches!(
+        &err,
+        PcdError::FieldType { field, expected: ValueType::F32, got: ValueType::U16, op: Operation::Write }
+            if field == "intensity"
+    ));
+    assert_eq!(
+        err.to_string(),
+        "Column intensity is U16, expected F32, while writing"
+    );
+
+    let layout = PcdLayout::from_header(&header).unwrap();
+    let data = [0u8; 16];
+    let mut wrong = wrong;
+    let err = BinaryReader::new(&mut Cursor::new(&data[..]), &layout, 1)
+        .decode(&mut wrong)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        PcdError::FieldType {
+            op: Operation::Read,
+            ..
+        }
+    ));
+
+    let mut missing = PointBlock::new(&[("x".to_string(), ValueType::F32)], 1);
+    let err = BinaryReader::new(&mut Cursor::new(&data[..]), &layout, 1)
+        .decode(&mut missing)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        PcdError::MissingField { field, op: Operation::Read } if field == "y"
+    ));
+}
+
+#[test]
+fn test_cast_columns() {
+    use rs_pcd::storage::{Column, Rounding};
+
+    let intensity = Column::U8(vec![0, 7, 255]);
+    assert_eq!(
+        intensity.cast(ValueType::F32).as_f32().unwrap(),
+        &[0.0, 7.0, 255.0]
+    );
+    assert_eq!(
+        Column::I32(vec![-5, 300, 70000])
+            .cast(ValueType::U8)
+            .as_u8(),
+        Some(&[0, 255, 255][..])
+    );
+    assert_eq!(
+        Column::U32(vec![u32::MAX]).cast(ValueType::I32).as_i32(),
+        Some(&[i32::MAX][..])
+    );
+
+    let t = Column::F64(vec![1.5, -1.5, 2.4, -2.6, f64::NAN, 1e12]);
+    let cast = |rounding| t.cast_with(ValueType::I32, rounding);
+    assert_eq!(
+        cast(Rounding::Truncate).as_i32().unwrap(),
+        &[1, -1, 2, -2, 0, i32::MAX]
+    );
+    assert_eq!(
+        cast(Rounding::Nearest).as_i32().unwrap(),
+        &[2, -2, 2, -3, 0, i32::MAX]
+    );
+    assert_eq!(
+        cast(Rounding::Floor).as_i32().unwrap(),
+        &[1, -2, 2, -3, 0, i32::MAX]
+    );
+    assert_eq!(
+        cast(Rounding::Ceil).as_i32().unwrap(),
+        &[2, -1, 3, -2, 0, i32::MAX]
+    );
+    assert_eq!(
+        Column::F64(vec![0.1]).cast(ValueType::F32).as_f32(),
+        Some(&[0.1f32][..])
+    );
+
+    let stamps = Column::F64(vec![1.7e18, 1.8e19, 2e19, -1.0]);
+    assert_eq!(
+        stamps.cast(ValueType::U64).as_u64().unwrap(),
+        &[
+            1_700_000_000_000_000_000,
+            18_000_000_000_000_000_000,
+            u64::MAX,
+            0
+        ]
+    );
+    assert_eq!(
+        Column::U64(vec![u64::MAX, 1 << 60])
+            .cast(ValueType::I64)
+            .as_i64(),
+        Some(&[i64::MAX, 1 << 60][..])
+    );
+    assert_eq!(
+        Column::I64(vec![-3, i64::MAX])
+            .cast(ValueType::U64)
+            .as_u64(),
+        Some(&[0, i64::MAX as u64][..])
+    );
+
+    let schema = [
+        ("x".to_string(), ValueType::F32),
+        ("intensity".to_string(), ValueType::U8),
+    ];
+    let mut block = PointBlock::new(&schema, 2);
+    block
+        .get_column_mut("intensity")
+        .unwrap()
+        .as_u8_mut()
+        .unwrap()[1] = 9;
+    block
+        .cast_column("intensity", ValueType::F32, Rounding::Truncate)
+        .unwrap();
+    assert_eq!(
+        block.get_column("intensity").unwrap().as_f32().unwrap(),
+        &[0.0, 9.0]
+    );
+    block
+        .cast_column("x", ValueType::U16, Rounding::Nearest)
+        .unwrap();
+    assert!(block.get_column("x").unwrap().as_u16().is_some());
+    assert!(
+        block
+            .cast_column("y", ValueType::F32, Rounding::Truncate)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_packed_rgb() {
+    use rs_pcd::header::PcdHeaderBuilder;
+    use rs_pcd::storage::rgb::{pack_color, unpack_color};
+
+    assert_eq!(pack_color(0xff, 0x80, 0x01, 0), 0x00ff_8001);
+    assert_eq!(unpack_color(0x40ff_8001), [0xff, 0x80, 0x01, 0x40]);
+
+    // PCL's F32 `rgb`: the float's bits are the color, NaN with alpha set.
+    let builder = PcdHeaderBuilder::xyz()
+        .add_field("rgb", ValueType::F32)
+        .width(2);
+    let mut block = PointBlock::new(builder.schema(), 2);
+    block.get_column_mut("rgb").unwrap().as_f32_mut().unwrap()[1] = f32::from_bits(0xffff_8001);
+    for format in [DataFormat::Ascii, DataFormat::Binary] {
+        let header = builder.clone().data_format(format).build().unwrap();
+        let mut buf = Vec::new();
+        PcdWriter::new(&mut buf).write_pcd(&header, &block).unwrap();
+        let mut back = PcdReader::new(Cursor::new(buf))
+            .unwrap()
+            .read_all()
+            .unwrap();
+
+        let (_, _, _, rgb) = back.xyzrgb_unpacked().unwrap();
+        assert_eq!(rgb, [[0, 0, 0], [0xff, 0x80, 0x01]], "{:?}", format);
+        back.unpack_rgb().unwrap();
+        assert_eq!(
+            back.schema().names().collect::<Vec<_>>(),
+            &["x", "y", "z", "r", "g", "b"]
+        );
+        assert_eq!(back.get_column("g").unwrap().as_u8().unwrap(), &[0, 0x80]);
+        // Packing `rgb` leaves the alpha byte 0.
+        back.pack_rgb(ValueType::F32).unwrap();
+        assert_eq!(
+            back.schema().names().collect::<Vec<_>>(),
+            &["x", "y", "z", "rgb"]
+        );
+        assert_eq!(
+            back.get_column("rgb").unwrap().as_f32().unwrap()[1].to_bits(),
+            0x00ff_8001
+        );
+    }
+
+    // With an alpha channel, to and from `rgba`.
+    let mut block = PointBlock::new(
+        PcdHeaderBuilder::xyz()
+            .add_field("rgba", ValueType::U32)
+            .schema(),
+        1,
+    );
+    block.get_column_mut("rgba").unwrap().as_u32_mut().unwrap()[0] = 0x8001_0203;
+    block.unpack_rgb().unwrap();
+    assert_eq!(block.get_column("a").unwrap().as_u8().unwrap(), &[0x80]);
+    block.pack_rgb(ValueType::U32).unwrap();
+    assert_eq!(
+        block.get_column("rgba").unwrap().as_u32().unwrap(),
+        &[0x8001_0203]
+    );
+
+    assert!(block.unpack_rgb().is_ok());
+    assert!(block.pack_rgb(ValueType::F64).is_err());
+    assert!(
+        PointBlock::new(PcdHeaderBuilder::xyz().schema(), 1)
+            .unpack_rgb()
+            .is_err()
+    );
+}
+
+#[test]
+fn test_convert_units() {
+    let schema = rs_pcd::header::PcdHeaderBuilder::xyz()
+        .add_field("range", ValueType::U32)
+        .add_field("distance", ValueType::F64)
+        .add_field("normal_x", ValueType::F32)
+        .schema();
+    let mut block = PointBlock::new(schema, 2);
+    block.get_column_mut("x").unwrap().as_f32_mut().unwrap()[..].copy_from_slice(&[1500.0, -20.0]);
+    block.get_column_mut("range").unwrap().as_u32_mut().unwrap()[0] = 1500;
+    block
+        .get_column_mut("distance")
+        .unwrap()
+        .as_f64_mut()
+        .unwrap()[1] = 20.0;
+    block
+        .get_column_mut("normal_x")
+        .unwrap()
+        .as_f32_mut()
+        .unwrap()[0] = 1.0;
+
+    block
+        .convert_units(LengthUnit::Millimeters, LengthUnit::Meters)
+        .unwrap();
+    let x = block.get_column("x").unwrap().as_f32().unwrap();
+    assert_eq!(x, &[1.5, -0.02]);
+    // Integer ranges become F32, float ones keep their type.
+    assert_eq!(
+        block.get_column("range").unwrap().as_f32().unwrap(),
+        &[1.5, 0.0]
+    );
+    assert_eq!(
+        block.get_column("distance").unwrap().as_f64().unwrap(),
+        &[0.0, 0.02]
+    );
+    assert_eq!(
+        block.get_column("normal_x").unwrap().as_f32().unwrap()[0],
+        1.0
+    );
+
+    block
+        .convert_units(LengthUnit::Meters, LengthUnit::Centimeters)
+        .unwrap();
+    assert_eq!(
+        block.get_column("x").unwrap().as_f32().unwrap(),
+        &[150.0, -2.0]
+    );
+    assert_eq!(
+        LengthUnit::Centimeters.factor_to(LengthUnit::Millimeters),
+        10.0
+    );
+
+    block.scale_xyz(2.0).unwrap();
+    assert_eq!(
+        block.get_column("x").unwrap().as_f32().unwrap(),
+        &[300.0, -4.0]
+    );
+    assert_eq!(
+        block.get_column("range").unwrap().as_f32().unwrap(),
+        &[150.0, 0.0]
+    );
+
+    let mut ints = PointBlock::new(
+        rs_pcd::header::PcdHeaderBuilder::new()
+            .add_field("x", ValueType::I32)
+            .add_field("y", ValueType::I32)
+            .add_field("z", ValueType::I32)
+            .add_field("range", ValueType::U32)
+            .schema(),
+        1,
+    );
+    assert!(
+        ints.convert_units(LengthUnit::Millimeters, LengthUnit::Meters)
+            .is_err()
+    );
+    assert_eq!(
+        ints.get_column("range").unwrap().value_type(),
+        ValueType::U32
+    );
+}
+
+#[test]
+fn test_colorize_by() {
+    use rs_pcd::header::PcdHeaderBuilder;
+    use rs_pcd::storage::rgb::unpack_color;
+
+    let near = |a: [u8; 4], b: [u8; 3]| (0..3).all(|i| a[i].abs_diff(b[i]) <= 4);
+    let builder = PcdHeaderBuilder::xyz().add_field("label", ValueType::U32);
+    let mut block = PointBlock::new(builder.schema(), 3);
+    block.get_column_mut("z").unwrap().as_f32_mut().unwrap()[..].copy_from_slice(&[0.0, 5.0, 10.0]);
+    block.get_column_mut("label").unwrap().as_u32_mut().unwrap()[..].copy_from_slice(&[0, 1, 11]);
+
+    // A new U32 `rgb` column, the range of z spanning the map.
+    block.colorize_by("z", Colormap::Viridis).unwrap();
+    let rgb: Vec<[u8; 4]> = block
+        .get_column("rgb")
+        .unwrap()
+        .as_u32()
+        .unwrap()
+        .iter()
+        .map(|&c| unpack_color(c))
+        .collect();
+    assert!(near(rgb[0], [68, 1, 84]) && near(rgb[2], [253, 231, 37]));
+    assert_eq!(rgb[0][3], 0);
+
+    // Classes cycle through the palette.
+    block.colorize_by("label", Colormap::Palette).unwrap();
+    let rgb = block.get_column("rgb").unwrap().as_u32().unwrap();
+    assert_eq!(rgb[1], rgb[2]);
+    assert_ne!(rgb[0], rgb[1]);
+
+    // Existing r/g/b columns are filled instead, with a fixed range.
+    block.remove_column("rgb");
+    for c in ["r", "g", "b"] {
+        block
+            .add_column(c, rs_pcd::storage::Column::U8(vec![0; 3]))
+            .unwrap();
+    }
+    block
+        .colorize_by_range("z", Colormap::Grayscale, 0.0, 5.0)
+        .unwrap();
+    assert_eq!(
+        block.get_column("g").unwrap().as_u8().unwrap(),
+        &[0, 255, 255]
+    );
+    assert!(block.get_column("rgb").is_none());
+
+    // PCL's F32 `rgb` keeps its type.
+    let mut pcl = PointBlock::new(builder.add_field("rgb", ValueType::F32).schema(), 3);
+    pcl.get_column_mut("z").unwrap().as_f32_mut().unwrap()[2] = 1.0;
+    pcl.colorize_by("z", Colormap::Turbo).unwrap();
+    let bits = pcl.get_column("rgb").unwrap().as_f32().unwrap()[2].to_bits();
+    let [r, g, b, _] = unpack_color(bits);
+    assert!(r > 100 && g < 32 && b < 32);
+
+    let normals = PcdHeaderBuilder::xyz().add_field_with_count("normal", ValueType::F32, 3);
+    let mut multi = PointBlock::new(normals.schema(), 2);
+    assert!(multi.colorize_by("normal", Colormap::Viridis).is_err());
+    assert!(multi.colorize_by("missing", Colormap::Viridis).is_err());
+    multi
+        .add_column("rgb", rs_pcd::storage::Column::U8(vec![0; 2]))
+        .unwrap();
+    assert!(multi.colorize_by("z", Colormap::Viridis).is_err());
+    assert_eq!(multi.get_column("rgb").unwrap().as_u8().unwrap(), &[0, 0]);
+}
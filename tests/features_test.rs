@@ -0,0 +1,113 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rs_pcd::features::{Neighborhood, NormalEstimation, estimate_normals};
+use rs_pcd::header::ValueType;
+use rs_pcd::storage::PointBlock;
+
+/// Points with the given positions in F32 `x`, `y`, `z`.
+fn cloud(points: &[[f32; 3]]) -> PointBlock {
+    let schema = [
+        ("x".to_string(), ValueType::F32),
+        ("y".to_string(), ValueType::F32),
+        ("z".to_string(), ValueType::F32),
+    ];
+    let mut block = PointBlock::new(&schema, points.len());
+    for (axis, name) in ["x", "y", "z"].iter().enumerate() {
+        let column = block.get_column_mut(name).unwrap().as_f32_mut().unwrap();
+        for (v, p) in column.iter_mut().zip(points) {
+            *v = p[axis];
+        }
+    }
+    block
+}
+
+#[test]
+fn test_estimate_normals() {
+    // The plane z = x / 2 + 5, above the origin, and a NaN point.
+    let mut points: Vec<[f32; 3]> = (0..100)
+        .map(|i| {
+            let (x, y) = ((i % 10) as f32 * 0.1, (i / 10) as f32 * 0.1);
+            [x, y, x / 2.0 + 5.0]
+        })
+        .collect();
+    points.push([f32::NAN, 0.0, 0.0]);
+    let mut block = cloud(&points);
+    estimate_normals(&mut block, Neighborhood::Knn(8)).unwrap();
+    let (_, _, _, nx, ny, nz, curvature) = block.xyz_normals().unwrap();
+    let expected = [0.5 / 1.25f32.sqrt(), 0.0, -1.0 / 1.25f32.sqrt()];
+    for i in 0..100 {
+        let n = [nx[i], ny[i], nz[i]];
+        for a in 0..3 {
+            assert!((n[a] - expected[a]).abs() < 1e-4, "{}: {:?}", i, n);
+        }
+        assert!(curvature[i].abs() < 1e-6);
+    }
+    assert!(nx[100].is_nan() && curvature[100].is_nan());
+
+    // Towards a viewpoint above the plane, by radius.
+    let normals = NormalEstimation::new(Neighborhood::Radius(0.15))
+        .viewpoint([0.0, 0.0, 100.0])
+        .normals(&block)
+        .unwrap();
+    assert!((normals[55][2] - 1.0 / 1.25f64.sqrt()).abs() < 1e-6);
+
+    // Corner of a box: three planes, so the curvature is not zero.
+    let corner: Vec<[f32; 3]> = (0..3)
+        .flat_map(|plane| {
+            (0..9).map(move |i| {
+                let (u, v) = ((i % 3) as f32 * 0.1, (i / 3) as f32 * 0.1);
+                [[0.0, u, v], [u, 0.0, v], [u, v, 0.0]][plane]
+            })
+        })
+        .collect();
+    let normals = NormalEstimation::new(Neighborhood::Knn(27))
+        .normals(&cloud(&corner))
+        .unwrap();
+    assert!(normals[0][3] > 0.1);
+
+    // Collinear points and too few neighbours have no normal.
+    let line = cloud(&[
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [2.0, 0.0, 0.0],
+        [9.0, 9.0, 9.0],
+    ]);
+    let normals = NormalEstimation::new(Neighborhood::Radius(2.5))
+        .normals(&line)
+        .unwrap();
+    assert!(normals.iter().all(|n| n[0].is_nan()));
+}
+
+#[test]
+fn test_normals_with_octree() {
+    use rs_pcd::spatial::Octree;
+
+    let points: Vec<[f32; 3]> = (0..100)
+        .map(|i| [(i % 10) as f32 * 0.1, (i / 10) as f32 * 0.1, 1.0])
+        .collect();
+    let block = cloud(&points);
+    let octree = Octree::from_block(&block, 4).unwrap();
+    let estimation = NormalEstimation::new(Neighborhood::Knn(6));
+    let with_octree = estimation.normals_with(&block, &octree).unwrap();
+    // Ties on the grid may pick other neighbours, on the same plane.
+    let with_kdtree = estimation.normals(&block).unwrap();
+    for normals in [with_octree, with_kdtree] {
+        assert!(
+            normals
+                .iter()
+                .all(|n| (n[2] + 1.0).abs() < 1e-9 && n[3].abs() < 1e-9)
+        );
+    }
+}
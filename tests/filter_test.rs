@@ -0,0 +1,490 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rs_pcd::header::{Schema, ValueType};
+use rs_pcd::storage::{
+    BoundingBox, CompareOp, Histogram, Normalization, PointBlock, PointFilter, Predicate,
+};
+
+/// Points (i, i, 0) with intensity i / 10 and a NaN x at point 3.
+fn cloud(n: usize) -> PointBlock {
+    let schema = [
+        ("x".to_string(), ValueType::F32),
+        ("y".to_string(), ValueType::F32),
+        ("z".to_string(), ValueType::F32),
+        ("intensity".to_string(), ValueType::F64),
+        ("ring".to_string(), ValueType::U16),
+    ];
+    let mut block = PointBlock::new(&schema, n);
+    for i in 0..n {
+        block.get_column_mut("x").unwrap().as_f32_mut().unwrap()[i] =
+            if i == 3 { f32::NAN } else { i as f32 };
+        block.get_column_mut("y").unwrap().as_f32_mut().unwrap()[i] = i as f32;
+        block
+            .get_column_mut("intensity")
+            .unwrap()
+            .as_f64_mut()
+            .unwrap()[i] = i as f64 / 10.0;
+        block.get_column_mut("ring").unwrap().as_u16_mut().unwrap()[i] = (i % 2) as u16;
+    }
+    block
+}
+
+fn ys(block: &PointBlock) -> Vec<f32> {
+    block.get_column("y").unwrap().as_f32().unwrap().to_vec()
+}
+
+#[test]
+fn test_parse_predicate() {
+    let p: Predicate = "intensity>0.1".parse().unwrap();
+    assert_eq!(p, Predicate::new("intensity", CompareOp::Gt, 0.1));
+    let p: Predicate = " ring != 3 ".parse().unwrap();
+    assert_eq!(p, Predicate::new("ring", CompareOp::Ne, 3.0));
+    assert_eq!("x<=-1".parse::<Predicate>().unwrap().op, CompareOp::Le);
+    assert_eq!("x==2".parse::<Predicate>().unwrap().op, CompareOp::Eq);
+    for bad in ["intensity", ">1", "x>", "x>abc", "x!1"] {
+        assert!(bad.parse::<Predicate>().is_err(), "{}", bad);
+    }
+}
+
+#[test]
+fn test_point_filter() {
+    let block = cloud(10);
+
+    let filter = PointFilter::new()
+        .predicate("intensity>=0.2".parse().unwrap())
+        .predicate("ring=0".parse().unwrap());
+    assert_eq!(ys(&filter.apply(&block).unwrap()), [2.0, 4.0, 6.0, 8.0]);
+
+    let filter = PointFilter::new().drop_nan(true);
+    let kept = filter.apply(&block).unwrap();
+    assert_eq!(kept.len, 9);
+    assert!(!ys(&kept).contains(&3.0));
+
+    // NaN x falls outside every box.
+    let filter = PointFilter::new().crop(BoundingBox::new([1.0, 0.0, 0.0], [5.0, 5.0, 0.0]));
+    assert_eq!(ys(&filter.apply(&block).unwrap()), [1.0, 2.0, 4.0, 5.0]);
+
+    let filter = PointFilter::new().predicate("missing>0".parse().unwrap());
+    assert!(filter.apply(&block).is_err());
+}
+
+#[test]
+fn test_filter_multi_value_column() {
+    let mut block = PointBlock::new(Schema::new().with_field_count("h", ValueType::F32, 2), 3);
+    *block.get_column_mut("h").unwrap().as_f32_mut().unwrap() =
+        vec![0.0, 1.0, 2.0, f32::NAN, 4.0, 5.0];
+    let kept = PointFilter::new().drop_nan(true).apply(&block).unwrap();
+    assert_eq!(kept.len, 2);
+    assert_eq!(
+        kept.get_column("h").unwrap().as_f32().unwrap(),
+        [0.0, 1.0, 4.0, 5.0]
+    );
+    let filter = PointFilter::new().predicate("h>0".parse().unwrap());
+    assert!(filter.apply(&block).is_err());
+}
+
+#[test]
+fn test_voxel_grid() {
+    use rs_pcd::filters::{Aggregation, VoxelGrid, voxel_grid};
+
+    // Voxels of edge 2 hold points {0, 1}, {2}, {4, 5} and {6, 7}; point 3
+    // has a NaN coordinate.
+    let block = cloud(8);
+    let down = voxel_grid(&block, 2.0).unwrap();
+    assert_eq!(down.len, 4);
+    assert_eq!(ys(&down), [0.5, 2.0, 4.5, 6.5]);
+    assert_eq!(
+        down.get_column("ring").unwrap().as_u16().unwrap(),
+        &[1, 0, 1, 1]
+    );
+    let intensity = down.get_column("intensity").unwrap().as_f64().unwrap();
+    assert!((intensity[2] - 0.45).abs() < 1e-12);
+
+    let down = VoxelGrid::new(2.0)
+        .aggregate("ring", Aggregation::Mode)
+        .aggregate("y", Aggregation::Max)
+        .aggregate("intensity", Aggregation::First)
+        .apply(&block)
+        .unwrap();
+    assert_eq!(
+        down.get_column("ring").unwrap().as_u16().unwrap(),
+        &[0, 0, 0, 0]
+    );
+    assert_eq!(ys(&down), [1.0, 2.0, 5.0, 7.0]);
+    assert_eq!(
+        down.get_column("intensity").unwrap().as_f64().unwrap()[3],
+        0.6
+    );
+
+    assert!(voxel_grid(&block, 0.0).is_err());
+    assert!(voxel_grid(&block, f64::NAN).is_err());
+}
+
+#[test]
+fn test_voxel_grid_colors() {
+    use rs_pcd::storage::voxel_grid;
+
+    // PCL's F32 packing, averaged per channel rather than as floats.
+    let schema = [
+        ("x".to_string(), ValueType::F32),
+        ("y".to_string(), ValueType::F32),
+        ("z".to_string(), ValueType::F32),
+        ("rgb".to_string(), ValueType::F32),
+    ];
+    let mut block = PointBlock::new(&schema, 2);
+    block.get_column_mut("x").unwrap().as_f32_mut().unwrap()[1] = 0.5;
+    let rgb = block.get_column_mut("rgb").unwrap().as_f32_mut().unwrap();
+    rgb[0] = f32::from_bits(0xff_ff0000);
+    rgb[1] = f32::from_bits(0xff_0000ff);
+    let down = voxel_grid(&block, 1.0).unwrap();
+    let (x, _, _, colors) = down.xyzrgb_unpacked().unwrap();
+    assert_eq!(x, &[0.25]);
+    assert_eq!(colors, [[128, 0, 128]]);
+}
+
+#[test]
+fn test_pass_through() {
+    use rs_pcd::filters::pass_through;
+
+    let block = cloud(8);
+    let kept = pass_through(&block, "intensity", 0.2, 0.5).unwrap();
+    assert_eq!(ys(&kept), [2.0, 3.0, 4.0, 5.0]);
+    // Integer fields, and NaN never in range.
+    let kept = pass_through(&block, "ring", 1.0, 1.0).unwrap();
+    assert_eq!(ys(&kept), [1.0, 3.0, 5.0, 7.0]);
+    let kept = pass_through(&block, "x", 0.0, 100.0).unwrap();
+    assert_eq!(kept.len, 7);
+    let filter = PointFilter::new()
+        .range("y", 1.0, 6.0)
+        .range("ring", 0.0, 0.0);
+    assert_eq!(ys(&filter.apply(&block).unwrap()), [2.0, 4.0, 6.0]);
+    assert!(pass_through(&block, "h", 0.0, 1.0).is_err());
+}
+
+#[test]
+fn test_crop_box() {
+    use rs_pcd::storage::{CropBox, crop_box};
+
+    // Points (i, i, 0), NaN x at 3.
+    let block = cloud(8);
+    let kept = crop_box(&block, [1.0, 1.0, -1.0], [4.0, 4.0, 1.0], None).unwrap();
+    assert_eq!(ys(&kept), [1.0, 2.0, 4.0]);
+    let outside = CropBox::new([1.0, 1.0, -1.0], [4.0, 4.0, 1.0])
+        .negative(true)
+        .apply(&block)
+        .unwrap();
+    assert_eq!(ys(&outside), [0.0, 5.0, 6.0, 7.0]);
+
+    // A thin box 3 long along its x, turned 45 degrees about z and centred
+    // on (5, 5, 0), holds only points on the diagonal near (5, 5).
+    let (s, c) = std::f32::consts::FRAC_PI_4.sin_cos();
+    let pose = [
+        [c, -s, 0.0, 5.0],
+        [s, c, 0.0, 5.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ];
+    let kept = crop_box(&block, [-1.5, -0.1, -1.0], [1.5, 0.1, 1.0], Some(&pose)).unwrap();
+    assert_eq!(ys(&kept), [4.0, 5.0, 6.0]);
+    let mut moved = block.clone();
+    moved.get_column_mut("y").unwrap().as_f32_mut().unwrap()[5] = 4.0;
+    let kept = crop_box(&moved, [-1.5, -0.1, -1.0], [1.5, 0.1, 1.0], Some(&pose)).unwrap();
+    assert_eq!(ys(&kept), [4.0, 6.0]);
+}
+
+#[test]
+fn test_farthest_point_sample() {
+    use rs_pcd::filters::{FarthestPointSampler, farthest_point_sample};
+
+    // Points (i, i, 0), NaN x at 3.
+    let block = cloud(8);
+    let sampled = farthest_point_sample(&block, 3).unwrap();
+    assert_eq!(ys(&sampled), [0.0, 7.0, 4.0]);
+    assert_eq!(
+        sampled.get_column("ring").unwrap().as_u16().unwrap(),
+        &[0, 1, 0]
+    );
+    let all = FarthestPointSampler::new(100).indices(&block).unwrap();
+    assert_eq!(all.len(), 7);
+    assert!(!all.contains(&3));
+    assert_eq!(farthest_point_sample(&block, 0).unwrap().len, 0);
+
+    // A 20 x 20 grid: both variants find its corners.
+    let mut grid = PointBlock::new(
+        &[
+            ("x".to_string(), ValueType::F32),
+            ("y".to_string(), ValueType::F32),
+            ("z".to_string(), ValueType::F32),
+        ],
+        400,
+    );
+    for i in 0..400 {
+        grid.get_column_mut("x").unwrap().as_f32_mut().unwrap()[i] = (i % 20) as f32;
+        grid.get_column_mut("y").unwrap().as_f32_mut().unwrap()[i] = (i / 20) as f32;
+    }
+    for bucketed in [false, true] {
+        let picks = FarthestPointSampler::new(4)
+            .bucketed(bucketed)
+            .indices(&grid)
+            .unwrap();
+        let mut corners = picks.clone();
+        corners.sort_unstable();
+        assert_eq!(corners, [0, 19, 380, 399], "bucketed {}", bucketed);
+        assert_eq!(picks[..2], [0, 399]);
+    }
+}
+
+#[test]
+fn test_take() {
+    let block = cloud(6);
+    let taken = block.take(&[5, 0, 5]);
+    assert_eq!(taken.len, 3);
+    assert_eq!(ys(&taken), [5.0, 0.0, 5.0]);
+    assert_eq!(
+        taken.get_column("intensity").unwrap().as_f64().unwrap(),
+        &[0.5, 0.0, 0.5]
+    );
+}
+
+#[test]
+fn test_ground_segmentation() {
+    use rs_pcd::storage::{Column, GroundMethod, GroundSegmentation, segment_ground};
+
+    let xyz = [
+        ("x".to_string(), ValueType::F32),
+        ("y".to_string(), ValueType::F32),
+        ("z".to_string(), ValueType::F32),
+    ];
+    let set = |block: &mut PointBlock, i: usize, p: [f32; 3]| {
+        for (name, v) in ["x", "y", "z"].iter().zip(p) {
+            block.get_column_mut(name).unwrap().as_f32_mut().unwrap()[i] = v;
+        }
+    };
+
+    // Flat ground every 0.5 m over 20 x 20 m, a 2 x 2 m box 1.5 m high on
+    // it and a NaN point.
+    let mut block = PointBlock::new(&xyz, 41 * 41 + 25 + 1);
+    for i in 0..41 * 41 {
+        set(
+            &mut block,
+            i,
+            [(i % 41) as f32 * 0.5, (i / 41) as f32 * 0.5, 0.0],
+        );
+    }
+    for i in 0..25 {
+        let (x, y) = (8.0 + (i % 5) as f32 * 0.5, 8.0 + (i / 5) as f32 * 0.5);
+        set(&mut block, 41 * 41 + i, [x, y, 1.5]);
+    }
+    set(&mut block, 41 * 41 + 25, [f32::NAN, 0.0, 0.0]);
+    let ground = segment_ground(&block).unwrap();
+    assert!(ground[..41 * 41].iter().all(|&g| g));
+    assert!(!ground[41 * 41..].iter().any(|&g| g));
+
+    // One azimuth sector: rings 0 and 1 on flat ground, 2 and 3 on a wall.
+    let schema = [xyz.to_vec(), vec![("ring".to_string(), ValueType::U16)]].concat();
+    let mut sweep = PointBlock::new(&schema[..], 6);
+    let points = [
+        ([5.0, 0.0, -1.7], 0),
+        ([6.0, 0.0, -1.7], 1),
+        ([6.05, 0.0, -1.0], 2),
+        ([6.05, 0.0, -0.3], 3),
+        ([6.01, 0.0, -1.65], 1),
+        ([0.0, 6.0, -1.7], 3),
+    ];
+    for (i, (p, ring)) in points.into_iter().enumerate() {
+        set(&mut sweep, i, p);
+        sweep.get_column_mut("ring").unwrap().as_u16_mut().unwrap()[i] = ring;
+    }
+    assert_eq!(
+        segment_ground(&sweep).unwrap(),
+        [true, true, false, false, true, false]
+    );
+    let grid = GroundSegmentation::new().method(GroundMethod::Grid);
+    assert_eq!(grid.mask(&sweep).unwrap()[..2], [true, true]);
+
+    GroundSegmentation::new().label(&mut sweep).unwrap();
+    assert!(matches!(
+        sweep.get_column("ground"),
+        Some(Column::U8(v)) if v == &[1, 1, 0, 0, 1, 0]
+    ));
+    let (on, off) = GroundSegmentation::new().split(&block).unwrap();
+    assert_eq!((on.len, off.len), (41 * 41, 26));
+    assert!(
+        GroundSegmentation::new()
+            .method(GroundMethod::Ring)
+            .mask(&block)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_dedup() {
+    use rs_pcd::storage::Aggregation;
+
+    let schema = [
+        ("x".to_string(), ValueType::F32),
+        ("y".to_string(), ValueType::F32),
+        ("z".to_string(), ValueType::F32),
+        ("intensity".to_string(), ValueType::F32),
+    ];
+    let points = [
+        [1.0, 2.0, 3.0, 10.0],
+        [0.0, 0.0, 0.0, 1.0],
+        [1.0, 2.0, 3.0, 20.0],
+        [f32::NAN, 0.0, 0.0, 2.0],
+        [-0.0, 0.0, 0.0, 3.0],
+        [1.1, 2.1, 3.1, 30.0],
+        [f32::NAN, 0.0, 0.0, 4.0],
+        [-0.5, 0.0, 0.0, 5.0],
+    ];
+    let mut block = PointBlock::new(&schema, points.len());
+    for (c, name) in ["x", "y", "z", "intensity"].iter().enumerate() {
+        let column = block.get_column_mut(name).unwrap().as_f32_mut().unwrap();
+        for (i, p) in points.iter().enumerate() {
+            column[i] = p[c];
+        }
+    }
+    let intensity = |b: &PointBlock| b.get_column("intensity").unwrap().as_f32().unwrap().to_vec();
+
+    // Points 2 and 4 repeat 0 and 1; NaN points are never merged.
+    let exact = block.dedup_exact(Aggregation::First).unwrap();
+    assert_eq!(intensity(&exact), [10.0, 1.0, 2.0, 30.0, 4.0, 5.0]);
+    let mean = block.dedup_exact(Aggregation::Mean).unwrap();
+    assert_eq!(intensity(&mean), [15.0, 2.0, 2.0, 30.0, 4.0, 5.0]);
+
+    // Unit voxels from the origin also merge point 5 into 0, but not -0.5
+    // into the origin's voxel.
+    let voxel = block.dedup_voxel(1.0, Aggregation::First).unwrap();
+    assert_eq!(intensity(&voxel), [10.0, 1.0, 2.0, 4.0, 5.0]);
+    let voxel = block.dedup_voxel(1.0, Aggregation::Mean).unwrap();
+    assert_eq!(intensity(&voxel)[0], 20.0);
+    let x = voxel.get_column("x").unwrap().as_f32().unwrap();
+    assert!((x[0] - 1.0333).abs() < 1e-4);
+
+    // Nothing to merge gives the block back.
+    let unique = block.dedup_voxel(0.01, Aggregation::First).unwrap();
+    assert_eq!(unique.len, 6);
+    assert_eq!(unique.dedup_exact(Aggregation::First).unwrap().len, 6);
+    assert!(block.dedup_voxel(-1.0, Aggregation::First).is_err());
+}
+
+#[test]
+fn test_rings() {
+    use rs_pcd::header::PcdHeaderBuilder;
+
+    // Two rings of points around the sensor, out of order.
+    let points = [
+        (1, [0.0, -1.0]),
+        (0, [-1.0, 0.0]),
+        (1, [1.0, 0.0]),
+        (0, [0.0, 1.0]),
+        (0, [f32::NAN, 0.0]),
+        (0, [1.0, -0.1]),
+        (1, [-1.0, -1.0]),
+        (0, [1.0, 0.1]),
+    ];
+    let mut block = PointBlock::new(PcdHeaderBuilder::xyzir().schema(), points.len());
+    for (i, (ring, [x, y])) in points.into_iter().enumerate() {
+        block.get_column_mut("x").unwrap().as_f32_mut().unwrap()[i] = x;
+        block.get_column_mut("y").unwrap().as_f32_mut().unwrap()[i] = y;
+        block.get_column_mut("ring").unwrap().as_u16_mut().unwrap()[i] = ring;
+    }
+
+    let counts = block.ring_counts().unwrap();
+    assert_eq!(counts.into_iter().collect::<Vec<_>>(), [(0, 5), (1, 3)]);
+    let rings = block.split_by_ring().unwrap();
+    assert_eq!(
+        rings[&1].get_column("y").unwrap().as_f32().unwrap(),
+        &[-1.0, 0.0, -1.0]
+    );
+
+    // Counterclockwise from +x, NaN last.
+    let lines = block.scan_lines().unwrap();
+    assert_eq!(lines[&0], [7, 3, 1, 5, 4]);
+    assert_eq!(lines[&1], [2, 6, 0]);
+    let sorted = block.sort_by_ring().unwrap();
+    assert_eq!(
+        sorted.get_column("ring").unwrap().as_u16().unwrap(),
+        &[0, 0, 0, 0, 0, 1, 1, 1]
+    );
+    assert_eq!(sorted.get_column("x").unwrap().as_f32().unwrap()[..2], [1.0, 0.0]);
+
+    block.remove_column("ring");
+    assert!(block.ring_counts().is_err());
+}
+
+#[test]
+fn test_histogram() {
+    let block = cloud(11);
+    let histogram = block.histogram("y", 5).unwrap();
+    assert_eq!((histogram.min, histogram.max), (0.0, 10.0));
+    // The last bin holds the maximum too.
+    assert_eq!(histogram.counts, vec![2, 2, 2, 2, 3]);
+    assert_eq!(histogram.bin_range(1), 2.0..4.0);
+    // NaN x at point 3 is not counted.
+    assert_eq!(block.histogram("x", 1).unwrap().counts, vec![10]);
+
+    let mut fixed = Histogram::new(0.0, 4.0, 2).unwrap();
+    fixed.update(block.get_column("y").unwrap());
+    fixed.update(block.get_column("y").unwrap());
+    assert_eq!((fixed.counts, fixed.outside), (vec![4, 6], 12));
+
+    assert!(block.histogram("y", 0).is_err());
+    assert!(block.histogram("missing", 4).is_err());
+    assert!(Histogram::new(1.0, 0.0, 4).is_err());
+    let empty = cloud(0).histogram("y", 3).unwrap();
+    assert_eq!(empty.counts, vec![0, 0, 0]);
+}
+
+#[test]
+fn test_normalize_column() {
+    let mut block = cloud(11);
+    let range = block.normalize_column("y", Normalization::MinMax).unwrap();
+    assert_eq!(range, (0.0, 10.0));
+    let expected: Vec<f32> = (0..11).map(|i| (i as f64 / 10.0) as f32).collect();
+    assert_eq!(ys(&block), expected);
+
+    // Integer columns become F32.
+    block.normalize_column("ring", Normalization::MinMax).unwrap();
+    let ring = block.get_column("ring").unwrap().as_f32().unwrap();
+    assert_eq!(&ring[..4], &[0.0, 1.0, 0.0, 1.0]);
+
+    // Percentiles of the 10 finite x: 0.9 and 9.1, values beyond clipped.
+    let method = Normalization::Percentile {
+        low: 10.0,
+        high: 90.0,
+    };
+    let (low, high) = block.normalize_column("x", method).unwrap();
+    assert!((low - 0.9).abs() < 1e-9 && (high - 9.1).abs() < 1e-9);
+    let x = block.get_column("x").unwrap().as_f32().unwrap();
+    assert_eq!((x[0], x[10]), (0.0, 1.0));
+    assert!((x[5] - (5.0 - 0.9) / 8.2).abs() < 1e-6);
+    assert!(x[3].is_nan());
+
+    // A constant column becomes 0.
+    block.normalize_column("z", Normalization::MinMax).unwrap();
+    assert!(block.get_column("z").unwrap().as_f32().unwrap().iter().all(|&v| v == 0.0));
+
+    let invalid = Normalization::Percentile {
+        low: 60.0,
+        high: 40.0,
+    };
+    assert!(block.normalize_column("intensity", invalid).is_err());
+    assert_eq!(
+        block.get_column("intensity").unwrap().value_type(),
+        ValueType::F64
+    );
+    assert!(block.normalize_column("missing", Normalization::MinMax).is_err());
+}
@@ -0,0 +1,535 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rs_pcd::header::{Schema, ValueType};
+use rs_pcd::io::csv::{read_csv, write_csv};
+use rs_pcd::io::pnts::{tileset_json, write_pnts};
+use rs_pcd::io::pts::{read_pts, read_ptx, write_ptx};
+use rs_pcd::header::{DataFormat, PcdHeaderBuilder};
+use rs_pcd::io::{
+    CsvOptions, MergeOptions, PtxOptions, PtxScan, merge_pcd_files, read_csv_file,
+    read_kitti_bin, read_merged_pcd_files, read_pcd_file, read_pcd_header, write_csv_file,
+    write_kitti_bin, write_pcd_file,
+};
+use rs_pcd::storage::PointBlock;
+use tempfile::NamedTempFile;
+
+fn make_xyzi(n: usize) -> PointBlock {
+    let schema = vec![
+        ("x".to_string(), ValueType::F32),
+        ("y".to_string(), ValueType::F32),
+        ("z".to_string(), ValueType::F32),
+        ("intensity".to_string(), ValueType::F32),
+    ];
+    let mut block = PointBlock::new(&schema, n);
+    for (c, col) in block.columns_mut().iter_mut().enumerate() {
+        let v = col.as_f32_mut().unwrap();
+        for (i, val) in v.iter_mut().enumerate() {
+            *val = i as f32 + c as f32 * 0.25;
+        }
+    }
+    block
+}
+
+#[test]
+fn test_kitti_roundtrip() {
+    let block = make_xyzi(5);
+    let file = NamedTempFile::new().unwrap();
+    write_kitti_bin(file.path(), &block).unwrap();
+
+    assert_eq!(std::fs::metadata(file.path()).unwrap().len(), 5 * 16);
+
+    let read = read_kitti_bin(file.path()).unwrap();
+    assert_eq!(read.len, 5);
+    let (x, y, z, i) = read.xyzi().unwrap();
+    assert_eq!(x[3], 3.0);
+    assert_eq!(y[3], 3.25);
+    assert_eq!(z[3], 3.5);
+    assert_eq!(i[3], 3.75);
+}
+
+#[test]
+fn test_kitti_converts_intensity() {
+    // Livox-style U8 intensity is converted, not dropped.
+    let mut block = make_xyzi(3);
+    block.cast_column("intensity", ValueType::U8, Default::default()).unwrap();
+    let file = NamedTempFile::new().unwrap();
+    write_kitti_bin(file.path(), &block).unwrap();
+    let read = read_kitti_bin(file.path()).unwrap();
+    assert_eq!(read.xyzi().unwrap().3, &[0.0, 1.0, 2.0]);
+
+    let schema = PcdHeaderBuilder::xyz().add_field_with_count("intensity", ValueType::F32, 2);
+    let multi = PointBlock::new(schema.schema(), 1);
+    assert!(write_kitti_bin(file.path(), &multi).is_err());
+}
+
+#[test]
+fn test_kitti_rejects_truncated() {
+    let file = NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), [0u8; 20]).unwrap();
+    assert!(read_kitti_bin(file.path()).is_err());
+}
+
+#[test]
+fn test_csv_header_inference() {
+    let data = "X,Y,Z,Intensity\n1.0,2.0,3.0,0.5\n4.0,5.0,6.0,0.7\n";
+    let block = read_csv(data.as_bytes(), &CsvOptions::default()).unwrap();
+    assert_eq!(block.schema().names().collect::<Vec<_>>(), ["x", "y", "z", "intensity"]);
+    let (x, _, z, i) = block.xyzi().unwrap();
+    assert_eq!(x[1], 4.0);
+    assert_eq!(z[0], 3.0);
+    assert_eq!(i[1], 0.7);
+}
+
+#[test]
+fn test_xyz_whitespace_with_schema() {
+    let data = "# survey export\n10.5 20.5 1.25 7\n11.5 21.5 1.5 9\n";
+    let options = CsvOptions {
+        schema: Some(
+            Schema::new()
+                .with_field("x", ValueType::F64)
+                .with_field("y", ValueType::F64)
+                .with_field("z", ValueType::F64)
+                .with_field("label", ValueType::U8),
+        ),
+        ..Default::default()
+    };
+    let block = read_csv(data.as_bytes(), &options).unwrap();
+    assert_eq!(block.len, 2);
+    assert_eq!(block.get_column("y").unwrap().as_f64().unwrap()[1], 21.5);
+    assert_eq!(block.get_column("label").unwrap().as_u8().unwrap(), &[7, 9]);
+}
+
+#[test]
+fn test_csv_roundtrip() {
+    let block = make_xyzi(4);
+    let file = NamedTempFile::new().unwrap();
+    write_csv_file(file.path(), &block, &CsvOptions::default()).unwrap();
+    let read = read_csv_file(file.path(), &CsvOptions::default()).unwrap();
+    assert_eq!(read.schema(), block.schema());
+    assert_eq!(read.xyzi().unwrap().3, block.xyzi().unwrap().3);
+}
+
+#[test]
+fn test_csv_types_counts_and_names() {
+    let data = "x,y,z,label,offset,Label,t\n1,2,3,7,-1,4,0.5\n4,5,6,9,2,4,1\n";
+    let block = read_csv(data.as_bytes(), &CsvOptions::default()).unwrap();
+    assert_eq!(
+        block.schema().names().collect::<Vec<_>>(),
+        ["x", "y", "z", "label_0", "offset", "label_1", "t"]
+    );
+    assert_eq!(block.xyz().unwrap().0, &[1.0, 4.0]);
+    assert_eq!(
+        block.get_column("label_0").unwrap().as_u32().unwrap(),
+        &[7, 9]
+    );
+    assert_eq!(
+        block.get_column("offset").unwrap().as_i32().unwrap(),
+        &[-1, 2]
+    );
+    assert_eq!(
+        block.get_column("t").unwrap().as_f32().unwrap(),
+        &[0.5, 1.0]
+    );
+
+    let schema = Schema::new()
+        .with_field("x", ValueType::F32)
+        .with_field_count("normal", ValueType::F32, 3)
+        .with_field("label", ValueType::U8);
+    let options = CsvOptions {
+        schema: Some(schema),
+        ..Default::default()
+    };
+    let data = "1 0.1 0.2 0.3 5\n2 0.4 0.5 0.6 6\n";
+    let block = read_csv(data.as_bytes(), &options).unwrap();
+    assert_eq!(
+        block.get_column("normal").unwrap().as_f32().unwrap(),
+        &[0.1, 0.2, 0.3, 0.4, 0.5, 0.6]
+    );
+    assert_eq!(block.get_column("label").unwrap().as_u8().unwrap(), &[5, 6]);
+    assert!(read_csv("1 0.1 0.2 5\n".as_bytes(), &options).is_err());
+
+    let mut out = Vec::new();
+    write_csv(&mut out, &block, &CsvOptions::default()).unwrap();
+    let text = String::from_utf8(out.clone()).unwrap();
+    assert!(text.starts_with("x,normal_0,normal_1,normal_2,label\n1.000000,0.100000,"));
+    let read = read_csv(&out[..], &options).unwrap();
+    assert_eq!(
+        read.get_column("normal").unwrap().as_f32().unwrap(),
+        block.get_column("normal").unwrap().as_f32().unwrap()
+    );
+}
+
+#[test]
+fn test_pts_intensity_rgb() {
+    let data = "2\n1.0 2.0 3.0 -100 255 128 0\n4.0 5.0 6.0 200 0 0 255\n1\n7.0 8.0 9.0 5 1 2 3\n";
+    let block = read_pts(data.as_bytes()).unwrap();
+    assert_eq!(block.len, 3);
+    let (x, _, _, rgb) = block.xyzrgb().unwrap();
+    assert_eq!(x[2], 7.0);
+    assert_eq!(rgb[0], 0xff8000);
+    assert_eq!(
+        block.get_column("intensity").unwrap().as_f32().unwrap()[0],
+        -100.0
+    );
+}
+
+#[test]
+fn test_ptx_transform_and_viewpoint() {
+    // 2 columns x 1 row, rotated 90 degrees about Z and translated by (10, 0, 0).
+    let data = "2\n1\n10 0 0\n0 1 0\n-1 0 0\n0 0 1\n\
+                0 1 0 0\n-1 0 0 0\n0 0 1 0\n10 0 0 1\n\
+                1 0 0 0.5\n0 0 0 0.5\n";
+
+    let kept = read_ptx(data.as_bytes(), &PtxOptions::default()).unwrap();
+    assert_eq!(kept.len(), 1);
+    let vp = kept[0].viewpoint;
+    assert!((vp[0] - 10.0).abs() < 1e-9);
+    let half = std::f64::consts::FRAC_1_SQRT_2;
+    assert!((vp[3] - half).abs() < 1e-9 && (vp[6] - half).abs() < 1e-9);
+    assert_eq!(kept[0].block.xyz().unwrap().0[0], 1.0);
+
+    let applied = read_ptx(
+        data.as_bytes(),
+        &PtxOptions {
+            apply_transform: true,
+        },
+    )
+    .unwrap();
+    let (x, y, _) = applied[0].block.xyz().unwrap();
+    assert_eq!((x[0], y[0]), (10.0, 1.0));
+    // Missing returns stay at the origin.
+    assert_eq!((x[1], y[1]), (0.0, 0.0));
+
+    let mut out = Vec::new();
+    write_ptx(&mut out, &kept).unwrap();
+    let reread = read_ptx(
+        out.as_slice(),
+        &PtxOptions {
+            apply_transform: true,
+        },
+    )
+    .unwrap();
+    let (x, y, _) = reread[0].block.xyz().unwrap();
+    assert!((x[0] - 10.0).abs() < 1e-5 && (y[0] - 1.0).abs() < 1e-5);
+    let _: &PtxScan = &reread[0];
+}
+
+#[test]
+fn test_ptx_rejects_malformed_scans() {
+    let header = "0 0 0\n1 0 0\n0 1 0\n0 0 1\n\
+                  1 0 0 0\n0 1 0 0\n0 0 1 0\n0 0 0 1\n";
+
+    // A colored point after intensity-only ones.
+    let ragged = format!("3\n1\n{}1 2 3 0.5\n4 5 6 0.5\n7 8 9 0.5 255 0 0\n", header);
+    let err = read_ptx(ragged.as_bytes(), &PtxOptions::default()).unwrap_err();
+    assert!(err.to_string().contains("expected 4 columns, got 7"), "{}", err);
+
+    // The grid size overflows, or promises far more points than there are.
+    for size in [
+        format!("{}\n2\n", usize::MAX),
+        "4294967296\n1\n".to_string(),
+    ] {
+        let data = format!("{}{}1 2 3\n", size, header);
+        assert!(read_ptx(data.as_bytes(), &PtxOptions::default()).is_err());
+    }
+    let truncated = format!("100000\n100000\n{}1 2 3\n", header);
+    assert!(read_ptx(truncated.as_bytes(), &PtxOptions::default()).is_err());
+}
+
+#[cfg(feature = "draco")]
+#[test]
+fn test_draco_roundtrip() {
+    use rs_pcd::io::draco::{DracoOptions, decode_draco, encode_draco};
+
+    let mut block = make_xyzi(200);
+    let schema = block.to_schema().with_field("rgb", ValueType::U32);
+    let mut with_rgb = PointBlock::new(schema, 200);
+    for (dst, src) in with_rgb.columns_mut().iter_mut().zip(block.columns_mut()) {
+        *dst = src.clone();
+    }
+    for (k, c) in with_rgb
+        .get_column_mut("rgb")
+        .unwrap()
+        .as_u32_mut()
+        .unwrap()
+        .iter_mut()
+        .enumerate()
+    {
+        *c = (k as u32) << 8;
+    }
+    block = with_rgb;
+
+    let options = DracoOptions {
+        position_bits: 16,
+        intensity_bits: 12,
+        ..Default::default()
+    };
+    let encoded = encode_draco(&block, &options).unwrap();
+    assert!(encoded.starts_with(b"DRACO"));
+
+    let decoded = decode_draco(&encoded).unwrap();
+    assert_eq!(decoded.len, 200);
+    // Points may be reordered; compare sorted x and y - x offsets.
+    let (x, y, _, i) = decoded.xyzi().unwrap();
+    let mut xs: Vec<f32> = x.to_vec();
+    xs.sort_by(f32::total_cmp);
+    assert!((xs[199] - 199.0).abs() < 0.01);
+    for k in 0..200 {
+        assert!((y[k] - x[k] - 0.25).abs() < 0.01);
+        assert!((i[k] - x[k] - 0.75).abs() < 0.1);
+    }
+    let rgb = decoded.get_column("rgb").unwrap().as_u32().unwrap();
+    for k in 0..200 {
+        assert_eq!(rgb[k], (x[k].round() as u32) << 8);
+    }
+}
+
+#[cfg(feature = "draco")]
+#[test]
+fn test_draco_attribute_types() {
+    use draco_oxide::core::attribute::{Attribute, AttributeDomain, AttributeId, AttributeType};
+    use draco_oxide::encode::{PointCloudConfig, encode_point_cloud};
+    use draco_oxide::{ConfigType, NdVector, PointCloud};
+    use rs_pcd::io::draco::decode_draco;
+
+    fn attribute<T, const N: usize>(id: usize, values: Vec<T>, ty: AttributeType) -> Attribute
+    where
+        T: draco_oxide::core::types::Vector<N>,
+        T::Component: draco_oxide::core::types::DataValue,
+    {
+        Attribute::from_without_removing_duplicates::<_, N>(
+            AttributeId::new(id),
+            values,
+            ty,
+            AttributeDomain::Position,
+            Vec::new(),
+        )
+    }
+    let encode = |attributes: Vec<Attribute>| {
+        let mut buffer = Vec::new();
+        let config = <PointCloudConfig as ConfigType>::default();
+        encode_point_cloud(PointCloud::new(attributes).unwrap(), &mut buffer, config).unwrap();
+        buffer
+    };
+    let positions = || {
+        let values = (0..4)
+            .map(|i| NdVector::from([i as f32, 0.0, 0.0]))
+            .collect();
+        attribute::<_, 3>(0, values, AttributeType::Position)
+    };
+    let colors = |id: usize, c: u8| {
+        let values = vec![NdVector::from([c, c, c]); 4];
+        attribute::<_, 3>(id, values, AttributeType::Color)
+    };
+
+    // A second color attribute gets its own column.
+    let decoded = decode_draco(&encode(vec![positions(), colors(1, 1), colors(2, 2)])).unwrap();
+    assert_eq!(decoded.schema().names().collect::<Vec<_>>(), &["x", "y", "z", "rgb", "rgb_2"]);
+    assert!(
+        decoded
+            .get_column("rgb")
+            .unwrap()
+            .as_u32()
+            .unwrap()
+            .iter()
+            .all(|&c| c == 0x010101)
+    );
+    assert!(
+        decoded
+            .get_column("rgb_2")
+            .unwrap()
+            .as_u32()
+            .unwrap()
+            .iter()
+            .all(|&c| c == 0x020202)
+    );
+
+    // Colors as floats are refused rather than misread.
+    let float_colors = vec![NdVector::from([0.5f32, 0.5, 0.5]); 4];
+    let buffer = encode(vec![
+        positions(),
+        attribute::<_, 3>(1, float_colors, AttributeType::Color),
+    ]);
+    assert!(matches!(
+        decode_draco(&buffer),
+        Err(rs_pcd::PcdError::UnsupportedType(_))
+    ));
+}
+
+#[test]
+fn test_pnts_layout() {
+    let block = make_xyzi(10);
+    let mut out = Vec::new();
+    write_pnts(&mut out, &block).unwrap();
+
+    let u32_at = |o: usize| u32::from_le_bytes(out[o..o + 4].try_into().unwrap()) as usize;
+    assert_eq!(&out[..4], b"pnts");
+    assert_eq!(u32_at(8), out.len());
+    let feature_json_len = u32_at(12);
+    let feature_bin_len = u32_at(16);
+    assert_eq!((28 + feature_json_len) % 8, 0);
+    assert_eq!(feature_bin_len, 10 * 12);
+    let json = std::str::from_utf8(&out[28..28 + feature_json_len]).unwrap();
+    assert!(json.contains("\"POINTS_LENGTH\":10"));
+    assert!(json.contains("\"RTC_CENTER\":[4.5,4.75,5]"));
+    let batch_json_len = u32_at(20);
+    let batch_bin_len = u32_at(24);
+    assert_eq!(batch_bin_len, 40);
+    let batch_start = 28 + feature_json_len + feature_bin_len;
+    let batch = std::str::from_utf8(&out[batch_start..batch_start + batch_json_len]).unwrap();
+    assert!(batch.contains("INTENSITY"));
+
+    let tileset = tileset_json(&block, "0.pnts", 2.0).unwrap();
+    assert!(tileset.contains("\"uri\": \"0.pnts\""));
+    assert!(tileset.contains("\"box\": [4.5, 4.75, 5, 4.5, 0, 0, 0, 4.5, 0, 0, 0, 4.5]"));
+}
+
+#[test]
+fn test_render_points_colors() {
+    let mut block = make_xyzi(3);
+    let points = block.render_points().unwrap();
+    assert_eq!(points.positions[1], [1.0, 1.25, 1.5]);
+    // Intensity 0.75, 1.75, 2.75 spread over the gray range.
+    let colors = points.colors.unwrap();
+    assert_eq!(colors[0], [0, 0, 0, 255]);
+    assert_eq!(colors[1], [128, 128, 128, 255]);
+    assert_eq!(colors[2], [255, 255, 255, 255]);
+
+    let schema = vec![
+        ("x".to_string(), ValueType::F32),
+        ("y".to_string(), ValueType::F32),
+        ("z".to_string(), ValueType::F32),
+        ("rgb".to_string(), ValueType::F32),
+    ];
+    block = PointBlock::new(&schema, 1);
+    block.get_column_mut("rgb").unwrap().as_f32_mut().unwrap()[0] = f32::from_bits(0x00FF8000);
+    let colors = block.render_points().unwrap().colors.unwrap();
+    assert_eq!(colors[0], [255, 128, 0, 255]);
+}
+
+#[test]
+fn test_mesh_attributes() {
+    let block = make_xyzi(2);
+    let attrs = block.mesh_attributes(&["intensity"]).unwrap();
+    assert_eq!(attrs.positions.len(), 2);
+    let colors = attrs.colors.unwrap();
+    assert_eq!(colors[0], [0.0, 0.0, 0.0, 1.0]);
+    assert_eq!(colors[1], [1.0, 1.0, 1.0, 1.0]);
+    assert_eq!(attrs.custom[0].0, "intensity");
+    assert_eq!(attrs.custom[0].1, vec![0.75, 1.75]);
+    assert!(block.mesh_attributes(&["normal_x"]).is_err());
+}
+
+#[cfg(feature = "rerun")]
+#[test]
+fn test_log_to_rerun() {
+    let (rec, storage) = rerun::RecordingStreamBuilder::new("rs_pcd_test")
+        .memory()
+        .unwrap();
+    let before = storage.num_msgs();
+    make_xyzi(3).log_to_rerun(&rec, "lidar/points").unwrap();
+    assert!(storage.num_msgs() > before);
+    assert!(
+        PointBlock::new(&[("x".to_string(), ValueType::F32)], 1)
+            .log_to_rerun(&rec, "lidar/points")
+            .is_err()
+    );
+}
+
+#[cfg(feature = "bevy")]
+#[test]
+fn test_bevy_mesh() {
+    use bevy_mesh::{
+        Mesh, MeshVertexAttribute, PrimitiveTopology, VertexAttributeValues, VertexFormat,
+    };
+
+    const INTENSITY: MeshVertexAttribute =
+        MeshVertexAttribute::new("Intensity", 988_540_917, VertexFormat::Float32);
+    let block = make_xyzi(2);
+    let mesh = block.to_bevy_mesh(&[("intensity", INTENSITY)]).unwrap();
+    assert_eq!(mesh.primitive_topology(), PrimitiveTopology::PointList);
+    assert_eq!(mesh.count_vertices(), 2);
+    assert!(mesh.contains_attribute(Mesh::ATTRIBUTE_COLOR));
+    assert!(matches!(
+        mesh.attribute(INTENSITY),
+        Some(VertexAttributeValues::Float32(v)) if v == &[0.75, 1.75]
+    ));
+
+    let wide = MeshVertexAttribute::new("Wide", 988_540_918, VertexFormat::Float32x2);
+    assert!(block.to_bevy_mesh(&[("intensity", wide)]).is_err());
+    assert!(block.to_bevy_mesh(&[("normal_x", INTENSITY)]).is_err());
+}
+
+#[test]
+fn test_merge_pcd_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let block = make_xyzi(100);
+    let header = |n: usize, viewpoint| {
+        PcdHeaderBuilder::xyzi()
+            .width(n as u32)
+            .height(1)
+            .viewpoint(viewpoint)
+            .build()
+            .unwrap()
+    };
+    let identity = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0];
+    let (a, b) = (dir.path().join("a.pcd"), dir.path().join("b.pcd"));
+    write_pcd_file(&a, &header(100, identity), &block).unwrap();
+    // Shifted by (10, 0, 0), its fields in another order.
+    let mut shifted = PointBlock::new(
+        &[
+            ("intensity".to_string(), ValueType::F32),
+            ("x".to_string(), ValueType::F32),
+            ("y".to_string(), ValueType::F32),
+            ("z".to_string(), ValueType::F32),
+        ],
+        0,
+    );
+    shifted.append(&block.take(&[0, 1])).unwrap();
+    let mut reordered = header(2, [10.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+    reordered.fields = ["intensity", "x", "y", "z"].map(String::from).to_vec();
+    write_pcd_file(&b, &reordered, &shifted).unwrap();
+
+    for format in [DataFormat::Ascii, DataFormat::Binary, DataFormat::BinaryCompressed] {
+        let out = dir.path().join("merged.pcd");
+        let options = MergeOptions {
+            format,
+            apply_viewpoint: true,
+            chunk_points: 7,
+        };
+        let written = merge_pcd_files(&[&a, &b], &out, &options).unwrap();
+        assert_eq!(read_pcd_header(&out).unwrap(), written);
+        assert_eq!((written.points, written.viewpoint), (102, identity));
+        let merged = read_pcd_file(&out).unwrap();
+        let x = merged.get_column("x").unwrap().as_f32().unwrap();
+        assert_eq!((x[99], x[100], x[101]), (99.0, 10.0, 11.0));
+    }
+
+    let cloud = read_merged_pcd_files(&[&b, &a], &MergeOptions::default()).unwrap();
+    assert_eq!(cloud.header.fields[0], "intensity");
+    assert_eq!(cloud.header.viewpoint[0], 10.0);
+    assert_eq!(cloud.data.get_column("x").unwrap().as_f32().unwrap()[2], 0.0);
+
+    // A file with other fields is rejected before anything is written.
+    let c = dir.path().join("c.pcd");
+    let xyz = PcdHeaderBuilder::xyz().width(100).height(1).build().unwrap();
+    let mut only_xyz = block.clone();
+    only_xyz.remove_column("intensity");
+    write_pcd_file(&c, &xyz, &only_xyz).unwrap();
+    let out = dir.path().join("bad.pcd");
+    assert!(merge_pcd_files(&[&a, &c], &out, &MergeOptions::default()).is_err());
+    assert!(!out.exists());
+    assert!(merge_pcd_files::<&std::path::Path, _>(&[], &out, &MergeOptions::default()).is_err());
+}
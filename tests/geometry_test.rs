@@ -0,0 +1,183 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rs_pcd::geometry::{Axes, Pose, Trajectory, convert_axes, deskew, deskew_with};
+use rs_pcd::header::PcdHeaderBuilder;
+use rs_pcd::storage::PointBlock;
+use std::f64::consts::FRAC_PI_2;
+
+fn close(a: [f64; 3], b: [f64; 3]) -> bool {
+    (0..3).all(|i| (a[i] - b[i]).abs() < 1e-9)
+}
+
+/// Rotation about `z` by `angle`.
+fn yaw(angle: f64) -> [f64; 4] {
+    [(angle / 2.0).cos(), 0.0, 0.0, (angle / 2.0).sin()]
+}
+
+#[test]
+fn test_pose() {
+    let pose = Pose::new([1.0, 2.0, 3.0], yaw(FRAC_PI_2));
+    assert!(close(pose.rotate([1.0, 0.0, 0.0]), [0.0, 1.0, 0.0]));
+    assert!(close(
+        pose.transform_point([1.0, 0.0, 0.0]),
+        [1.0, 3.0, 3.0]
+    ));
+    let back = pose
+        .inverse()
+        .transform_point(pose.transform_point([4.0, 5.0, 6.0]));
+    assert!(close(back, [4.0, 5.0, 6.0]));
+
+    // `compose` applies its argument first.
+    let shift = Pose::new([1.0, 0.0, 0.0], [1.0, 0.0, 0.0, 0.0]);
+    assert!(close(
+        pose.compose(&shift).transform_point([0.0; 3]),
+        [1.0, 3.0, 3.0]
+    ));
+    assert!(close(
+        shift.compose(&pose).transform_point([0.0; 3]),
+        [2.0, 2.0, 3.0]
+    ));
+
+    // Halfway: half the translation and half the turn.
+    let half = Pose::IDENTITY.interpolate(&pose, 0.5);
+    assert!(close(half.translation, [0.5, 1.0, 1.5]));
+    let s = std::f64::consts::FRAC_1_SQRT_2;
+    assert!(close(half.rotate([1.0, 0.0, 0.0]), [s, s, 0.0]));
+    // The shorter arc, even with the quaternion's sign flipped.
+    let flipped = Pose::new([0.0; 3], yaw(FRAC_PI_2).map(|c| -c));
+    let half = Pose::IDENTITY.interpolate(&flipped, 0.5);
+    assert!(close(half.rotate([1.0, 0.0, 0.0]), [s, s, 0.0]));
+
+    let viewpoint = [1.0, 2.0, 3.0, 0.5, 0.5, 0.5, 0.5];
+    assert_eq!(Pose::from_viewpoint(&viewpoint).to_viewpoint(), viewpoint);
+}
+
+#[test]
+fn test_deskew() {
+    // A wall at x = 10 scanned while driving 1 m along x and turning 90°.
+    let mut block = PointBlock::new(PcdHeaderBuilder::xyzirt().schema(), 3);
+    let start = Pose::IDENTITY;
+    let end = Pose::new([1.0, 0.0, 0.0], yaw(FRAC_PI_2));
+    let world = [[10.0, 0.0, 0.0], [10.0, 1.0, 0.0], [10.0, 2.0, 0.0]];
+    let times = [100.0, 100.05, 100.1];
+    for i in 0..3 {
+        // Where the sensor saw the point at its own instant.
+        let pose = start.interpolate(&end, i as f64 / 2.0);
+        let seen = pose.inverse().transform_point(world[i]);
+        for (axis, name) in ["x", "y", "z"].iter().enumerate() {
+            block.get_column_mut(name).unwrap().as_f32_mut().unwrap()[i] = seen[axis] as f32;
+        }
+        block
+            .get_column_mut("timestamp")
+            .unwrap()
+            .as_f64_mut()
+            .unwrap()[i] = times[i];
+    }
+
+    let mut deskewed = block.clone();
+    deskew(&mut deskewed, &start, &end).unwrap();
+    let positions: Vec<[f64; 3]> = deskewed.iter_xyz().unwrap().collect();
+    for i in 0..3 {
+        assert!((0..3).all(|a| (positions[i][a] - world[i][a]).abs() < 1e-4));
+    }
+
+    // Into the frame at the end of the sweep instead.
+    let mut at_end = block.clone();
+    deskew_with(&mut at_end, 100.1, |t| {
+        start.interpolate(&end, (t - 100.0) / 0.1)
+    })
+    .unwrap();
+    let last: Vec<[f64; 3]> = at_end.iter_xyz().unwrap().collect();
+    let expected = end.inverse().transform_point(world[0]);
+    assert!((0..3).all(|a| (last[0][a] - expected[a]).abs() < 1e-4));
+
+    block.remove_column("timestamp");
+    assert!(deskew(&mut block, &start, &end).is_err());
+}
+
+#[test]
+fn test_pose_matrix() {
+    let pose = Pose::new([1.0, 2.0, 3.0], [0.3, -0.5, 0.7, 0.1]);
+    let back = Pose::from_matrix(&pose.to_matrix());
+    assert!(close(back.translation, pose.translation));
+    let p = [0.5, -4.0, 2.0];
+    assert!(close(back.transform_point(p), pose.transform_point(p)));
+    // A half turn, where the trace is negative.
+    let flip = Pose::new([0.0; 3], [0.0, 0.0, 0.0, 1.0]);
+    let back = Pose::from_matrix(&flip.to_matrix());
+    assert!(close(back.transform_point(p), [-0.5, 4.0, 2.0]));
+
+    let mut block = PointBlock::new(PcdHeaderBuilder::xyz().schema(), 1);
+    for (name, v) in ["x", "y", "z"].iter().zip(p) {
+        block.get_column_mut(name).unwrap().as_f32_mut().unwrap()[0] = v as f32;
+    }
+    pose.transform_block(&mut block).unwrap();
+    let moved = block.iter_xyz().unwrap().next().unwrap();
+    assert!((0..3).all(|a| (moved[a] - pose.transform_point(p)[a]).abs() < 1e-5));
+}
+
+#[test]
+fn test_trajectory() {
+    let trajectory = Trajectory::new([
+        (2.0, Pose::new([2.0, 0.0, 0.0], yaw(FRAC_PI_2))),
+        (0.0, Pose::IDENTITY),
+        (f64::NAN, Pose::new([9.0, 9.0, 9.0], [1.0, 0.0, 0.0, 0.0])),
+    ]);
+    assert_eq!(trajectory.len(), 2);
+    assert_eq!(trajectory.time_range(), Some((0.0, 2.0)));
+
+    let middle = trajectory.pose_at(1.0).unwrap();
+    assert!(close(middle.translation, [1.0, 0.0, 0.0]));
+    let half = Pose::new([0.0; 3], yaw(FRAC_PI_2 / 2.0));
+    assert!(close(
+        middle.rotate([1.0, 0.0, 0.0]),
+        half.rotate([1.0, 0.0, 0.0])
+    ));
+    // Clamped outside the range.
+    assert_eq!(trajectory.pose_at(-1.0), Some(Pose::IDENTITY));
+    assert!(close(
+        trajectory.pose_at(5.0).unwrap().translation,
+        [2.0, 0.0, 0.0]
+    ));
+    assert!(trajectory.pose_at(f64::NAN).is_none());
+    assert!(Trajectory::new([]).pose_at(0.0).is_none());
+
+    // From t = 0 to t = 2: 2 m forward, then a quarter turn left.
+    let relative = trajectory.relative(0.0, 2.0).unwrap();
+    assert!(close(
+        relative.transform_point([1.0, 0.0, 0.0]),
+        [2.0, 1.0, 0.0]
+    ));
+}
+
+#[test]
+fn test_convert_axes() {
+    // 1 m ahead, 2 m left and 3 m up of a ROS body.
+    let flu = [1.0, 2.0, 3.0];
+    let rdf = Axes::rotation(Axes::Flu, Axes::Rdf).transform_point(flu);
+    assert!(close(rdf, [-2.0, -3.0, 1.0]));
+    let frd = Axes::rotation(Axes::Rdf, Axes::Frd).transform_point(rdf);
+    assert!(close(frd, [1.0, -2.0, -3.0]));
+
+    let mut block = PointBlock::new(PcdHeaderBuilder::xyz().schema(), 1);
+    for (name, v) in ["x", "y", "z"].iter().zip(flu) {
+        block.get_column_mut(name).unwrap().as_f32_mut().unwrap()[0] = v as f32;
+    }
+    convert_axes(&mut block, Axes::Flu, Axes::Rdf).unwrap();
+    let p = block.iter_xyz().unwrap().next().unwrap();
+    assert!(close(p, [-2.0, -3.0, 1.0]));
+    convert_axes(&mut block, Axes::Rdf, Axes::Flu).unwrap();
+    assert!(close(block.iter_xyz().unwrap().next().unwrap(), flu));
+}
@@ -52,6 +52,8 @@ DATA ascii
         viewpoint: [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0],
         points: 2,
         data: DataFormat::Ascii,
+        comments: vec![".PCD v.7 - Point Cloud Data file format".to_string()],
+        extras: vec![],
     };
 
     (file, header)
@@ -92,3 +94,1353 @@ fn test_parse_binary() {
     assert_eq!(x_col[0], 42.0);
     assert_eq!(x_col[1], 123.0);
 }
+
+#[cfg(feature = "flate2")]
+#[test]
+fn test_gzip_roundtrip() {
+    let (file, mut header) = create_dummy_pcd_ascii();
+    let block = read_pcd_file(file.path()).unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cloud.pcd.gz");
+    header.data = DataFormat::Binary;
+    rs_pcd::io::write_pcd_file(&path, &header, &block).unwrap();
+
+    let raw = std::fs::read(&path).unwrap();
+    assert_eq!(&raw[..2], &[0x1f, 0x8b]);
+
+    let back = read_pcd_file(&path).unwrap();
+    let z = back.get_column("z").unwrap().as_f32_slice().unwrap();
+    assert_eq!(z, &[0.3, 1.3]);
+}
+
+#[cfg(not(feature = "flate2"))]
+#[test]
+fn test_gzip_requires_feature() {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(&[0x1f, 0x8b, 0x08, 0x00]).unwrap();
+    assert!(matches!(
+        read_pcd_file(file.path()),
+        Err(rs_pcd::PcdError::UnsupportedDataFormat(_))
+    ));
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_binary_zstd_roundtrip() {
+    use rs_pcd::header::{PcdHeaderBuilder, ValueType};
+    use rs_pcd::io::{PcdReader, PcdWriter};
+    use rs_pcd::storage::PointBlock;
+    use std::io::Cursor;
+
+    assert_eq!(
+        "binary_zstd".parse::<DataFormat>().unwrap(),
+        DataFormat::BinaryZstd
+    );
+    let builder = PcdHeaderBuilder::xyzi()
+        .add_field_with_count("label", ValueType::U16, 2)
+        .data_format(DataFormat::BinaryZstd);
+    // Compressible, and 2 points that are stored as is.
+    for n in [1000, 2] {
+        let header = builder.clone().width(n as u32).build().unwrap();
+        let mut block = PointBlock::new(builder.schema(), n);
+        for (c, column) in block.columns_mut().iter_mut().enumerate() {
+            if let Some(values) = column.as_f32_mut() {
+                for (i, v) in values.iter_mut().enumerate() {
+                    *v = (i % 17) as f32 * 0.5 + c as f32;
+                }
+            } else if let Some(values) = column.as_u16_mut() {
+                for (i, v) in values.iter_mut().enumerate() {
+                    *v = i as u16;
+                }
+            }
+        }
+        let mut out = Vec::new();
+        PcdWriter::new(&mut out).write_pcd(&header, &block).unwrap();
+        assert!(String::from_utf8_lossy(&out).contains("DATA binary_zstd\n"));
+
+        let back = PcdReader::new(Cursor::new(&out)).unwrap().read_all().unwrap();
+        assert_eq!(back.len, n);
+        assert_eq!(back.xyzi(), block.xyzi());
+        assert_eq!(
+            back.get_column("label").unwrap().as_u16(),
+            block.get_column("label").unwrap().as_u16()
+        );
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(&out).unwrap();
+        assert_eq!(read_pcd_file(file.path()).unwrap().len, n);
+
+        // A damaged payload is an error, not a panic.
+        let last = out.len() - 1;
+        out[last] ^= 0xff;
+        out.truncate(last - 3);
+        assert!(PcdReader::new(Cursor::new(&out)).unwrap().read_all().is_err());
+    }
+
+    // Missing columns are refused before any header bytes are written.
+    let header = builder.width(2).build().unwrap();
+    let mut out = Vec::new();
+    let block = PointBlock::new(PcdHeaderBuilder::xyz().schema(), 2);
+    assert!(PcdWriter::new(&mut out).write_pcd(&header, &block).is_err());
+    assert!(out.is_empty());
+}
+
+#[cfg(not(feature = "zstd"))]
+#[test]
+fn test_binary_zstd_needs_feature() {
+    assert!(matches!(
+        "binary_zstd".parse::<DataFormat>(),
+        Err(rs_pcd::PcdError::UnsupportedDataFormat(_))
+    ));
+}
+
+#[test]
+fn test_read_pcd_bytes() {
+    let (file, mut header) = create_dummy_pcd_ascii();
+    let block = read_pcd_file(file.path()).unwrap();
+
+    for format in [
+        DataFormat::Ascii,
+        DataFormat::Binary,
+        DataFormat::BinaryCompressed,
+    ] {
+        header.data = format;
+        let mut buf = Vec::new();
+        rs_pcd::io::PcdWriter::new(&mut buf)
+            .write_pcd(&header, &block)
+            .unwrap();
+
+        let back = rs_pcd::io::read_pcd_bytes(&buf).unwrap();
+        assert_eq!(back.len, 2);
+        let intensity = back
+            .get_column("intensity")
+            .unwrap()
+            .as_f32_slice()
+            .unwrap();
+        assert_eq!(intensity, &[0.5, 0.8], "{:?}", format);
+    }
+}
+
+#[test]
+fn test_point_cloud() {
+    use rs_pcd::storage::PointCloud;
+
+    let (file, header) = create_dummy_pcd_ascii();
+    let cloud = PointCloud::open(file.path()).unwrap();
+    assert_eq!(cloud.header, header);
+    assert_eq!(cloud.len(), 2);
+
+    let mut cloud = rs_pcd::io::PcdReader::from_path(file.path())
+        .unwrap()
+        .read_cloud()
+        .unwrap();
+    assert_eq!(cloud.header, header);
+    assert_eq!(
+        cloud.data.get_column("x").unwrap().as_f32().unwrap(),
+        &[0.1, 1.1]
+    );
+
+    cloud.header.viewpoint[0] = 5.0;
+    cloud.header.data = DataFormat::Binary;
+    let out = NamedTempFile::new().unwrap();
+    cloud.write(out.path()).unwrap();
+    let bytes = std::fs::read(out.path()).unwrap();
+    let back = PointCloud::from_bytes(&bytes).unwrap();
+    assert_eq!(back.header.viewpoint[0], 5.0);
+    assert_eq!(back.header.data, DataFormat::Binary);
+    let (_, data) = back.into_parts();
+    assert_eq!(
+        data.get_column("intensity").unwrap().as_f32().unwrap(),
+        &[0.5, 0.8]
+    );
+}
+
+#[test]
+fn test_read_chunk() {
+    let (file, mut header) = create_dummy_pcd_ascii();
+    let block = read_pcd_file(file.path()).unwrap();
+
+    for format in [DataFormat::Ascii, DataFormat::Binary] {
+        header.data = format;
+        let mut buf = Vec::new();
+        rs_pcd::io::PcdWriter::new(&mut buf)
+            .write_pcd(&header, &block)
+            .unwrap();
+
+        let mut reader = rs_pcd::io::PcdReader::from_bytes(&buf).unwrap();
+        let first = reader.read_chunk(1).unwrap().unwrap();
+        assert_eq!(first.len, 1);
+        assert_eq!(reader.remaining(), 1);
+        let second = reader.read_chunk(1).unwrap().unwrap();
+        assert_eq!(second.get_column("x").unwrap().as_f32().unwrap(), &[1.1]);
+        assert!(reader.read_chunk(1).unwrap().is_none());
+    }
+}
+
+#[test]
+fn test_column_stats() {
+    use rs_pcd::storage::{Column, ColumnStats};
+
+    let mut stats = ColumnStats::from_column(&Column::F32(vec![1.0, f32::NAN, -2.0]));
+    stats.update(&Column::F32(vec![5.0]));
+    assert_eq!(stats.count, 4);
+    assert_eq!(stats.nan_count, 1);
+    assert_eq!((stats.min, stats.max), (-2.0, 5.0));
+
+    let empty = ColumnStats::from_column(&Column::U8(vec![]));
+    assert!(!empty.has_range());
+}
+
+#[test]
+fn test_stream_writer() {
+    let (file, mut header) = create_dummy_pcd_ascii();
+    let block = read_pcd_file(file.path()).unwrap();
+    header.data = DataFormat::Binary;
+
+    let mut writer = rs_pcd::io::PcdStreamWriter::new(Vec::new(), &header).unwrap();
+    let mut reader = rs_pcd::io::PcdReader::open(file.path()).unwrap();
+    while let Some(chunk) = reader.read_chunk(1).unwrap() {
+        writer.write_chunk(&chunk).unwrap();
+    }
+    let buf = writer.finish().unwrap();
+    let back = rs_pcd::io::read_pcd_bytes(&buf).unwrap();
+    assert_eq!(
+        back.get_column("z").unwrap().as_f32().unwrap(),
+        block.get_column("z").unwrap().as_f32().unwrap()
+    );
+
+    // Too few points.
+    let writer = rs_pcd::io::PcdStreamWriter::new(Vec::new(), &header).unwrap();
+    assert!(writer.finish().is_err());
+}
+
+#[test]
+fn test_point_block_append() {
+    use rs_pcd::header::ValueType;
+    use rs_pcd::storage::PointBlock;
+
+    let schema = [
+        ("x".to_string(), ValueType::F32),
+        ("label".to_string(), ValueType::U16),
+    ];
+    let mut a = PointBlock::new(&schema, 2);
+    a.get_column_mut("x").unwrap().as_f32_mut().unwrap()[1] = 1.0;
+    // Same fields in another order.
+    let mut b = PointBlock::new(&[schema[1].clone(), schema[0].clone()], 3);
+    b.get_column_mut("x").unwrap().as_f32_mut().unwrap()[2] = 4.0;
+    b.get_column_mut("label").unwrap().as_u16_mut().unwrap()[0] = 7;
+
+    a.append(&b).unwrap();
+    assert_eq!(a.len, 5);
+    assert_eq!(
+        a.get_column("x").unwrap().as_f32().unwrap(),
+        [0.0, 1.0, 0.0, 0.0, 4.0]
+    );
+    assert_eq!(
+        a.get_column("label").unwrap().as_u16().unwrap(),
+        [0, 0, 7, 0, 0]
+    );
+
+    let other = PointBlock::new(&[("x".to_string(), ValueType::F64)], 1);
+    assert!(a.append(&other).is_err());
+    let other = PointBlock::new(
+        &[
+            ("x".to_string(), ValueType::F64),
+            ("label".to_string(), ValueType::U16),
+        ],
+        1,
+    );
+    assert!(a.append(&other).is_err());
+    assert_eq!(a.len, 5);
+}
+
+#[test]
+fn test_read_pcd_header() {
+    let (file, header) = create_dummy_pcd_ascii();
+    assert_eq!(rs_pcd::io::read_pcd_header(file.path()).unwrap(), header);
+
+    let reader = rs_pcd::io::PcdReader::open(file.path()).unwrap();
+    assert_eq!(reader.into_header().points, 2);
+
+    // Only the header needs to be well-formed.
+    let mut truncated = NamedTempFile::new().unwrap();
+    let text = std::fs::read_to_string(file.path()).unwrap();
+    write!(
+        truncated,
+        "{}",
+        &text[..text.find("DATA ascii").unwrap() + 11]
+    )
+    .unwrap();
+    assert_eq!(
+        rs_pcd::io::read_pcd_header(truncated.path()).unwrap(),
+        header
+    );
+}
+
+#[test]
+fn test_header_comments_round_trip() {
+    let (file, _) = create_dummy_pcd_ascii();
+    let text = std::fs::read_to_string(file.path())
+        .unwrap()
+        .replace("WIDTH 2", "#generated by scanner v3\n#\nWIDTH 2");
+    let reader = rs_pcd::io::PcdReader::from_bytes(text.as_bytes()).unwrap();
+    let mut header = reader.header().clone();
+    assert_eq!(
+        header.comments,
+        [
+            ".PCD v.7 - Point Cloud Data file format",
+            "generated by scanner v3",
+            ""
+        ]
+    );
+
+    header.comments.push("converted by rs-pcd".to_string());
+    let block = reader.read_all().unwrap();
+    let mut buf = Vec::new();
+    rs_pcd::io::PcdWriter::new(&mut buf)
+        .write_pcd(&header, &block)
+        .unwrap();
+    assert!(String::from_utf8_lossy(&buf).starts_with("# .PCD v.7"));
+    let back = rs_pcd::io::PcdReader::from_bytes(&buf).unwrap();
+    assert_eq!(back.header(), &header);
+
+    let built = rs_pcd::header::PcdHeaderBuilder::new()
+        .add_field("x", rs_pcd::ValueType::F32)
+        .width(1)
+        .comment("made here")
+        .build()
+        .unwrap();
+    assert_eq!(built.comments, ["made here"]);
+}
+
+#[test]
+fn test_header_extras_round_trip() {
+    let (file, _) = create_dummy_pcd_ascii();
+    let text = std::fs::read_to_string(file.path())
+        .unwrap()
+        .replace("WIDTH 2", "SENSOR_ID  vlp-16 rev2\nCALIBRATED\nWIDTH 2");
+    let reader = rs_pcd::io::PcdReader::from_bytes(text.as_bytes()).unwrap();
+    let mut header = reader.header().clone();
+    assert_eq!(
+        header.extras,
+        [
+            ("SENSOR_ID".to_string(), "vlp-16 rev2".to_string()),
+            ("CALIBRATED".to_string(), String::new()),
+        ]
+    );
+
+    let block = reader.read_all().unwrap();
+    let mut buf = Vec::new();
+    rs_pcd::io::PcdWriter::new(&mut buf)
+        .write_pcd(&header, &block)
+        .unwrap();
+    let written = String::from_utf8_lossy(&buf).to_string();
+    assert!(written.contains("POINTS 2\nSENSOR_ID vlp-16 rev2\nCALIBRATED\nDATA ascii"));
+    let back = rs_pcd::io::PcdReader::from_bytes(&buf).unwrap();
+    assert_eq!(back.header(), &header);
+
+    header.extras.clear();
+    let mut buf = Vec::new();
+    rs_pcd::io::PcdWriter::new(&mut buf)
+        .write_pcd(&header, &block)
+        .unwrap();
+    assert!(!String::from_utf8_lossy(&buf).contains("SENSOR_ID"));
+}
+
+#[test]
+fn test_lenient_header() {
+    use rs_pcd::header::{HeaderOptions, parse_header_bytes, parse_header_bytes_with};
+
+    // PCD 0.6 style: no VIEWPOINT and no POINTS.
+    let v06 = b"VERSION .6\nFIELDS x\nSIZE 4\nTYPE F\nCOUNT 1\nWIDTH 2\nHEIGHT 3\nDATA ascii\n";
+    assert!(matches!(
+        parse_header_bytes(v06),
+        Err(rs_pcd::PcdError::InvalidHeader { line: 8, msg }) if msg.contains("POINTS")
+    ));
+    let (header, _) = parse_header_bytes_with(v06, &HeaderOptions::lenient()).unwrap();
+    assert_eq!(header.points, 6);
+    assert_eq!(header.viewpoint, [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+
+    // Only POINTS.
+    let points_only = b"FIELDS x\nSIZE 4\nTYPE F\nPOINTS 4\nDATA ascii\n";
+    let (header, _) = parse_header_bytes_with(points_only, &HeaderOptions::lenient()).unwrap();
+    assert_eq!((header.width, header.height, header.points), (4, 1, 4));
+
+    let neither = b"FIELDS x\nSIZE 4\nTYPE F\nDATA ascii\n";
+    assert!(parse_header_bytes_with(neither, &HeaderOptions::lenient()).is_err());
+
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(v06).unwrap();
+    file.write_all(b"1\n2\n3\n4\n5\n6\n").unwrap();
+    assert!(rs_pcd::io::PcdReader::open(file.path()).is_err());
+    let reader = rs_pcd::io::PcdReader::open_with(file.path(), &HeaderOptions::lenient()).unwrap();
+    assert_eq!(reader.read_all().unwrap().len, 6);
+}
+
+#[test]
+fn test_header_to_string() {
+    let (file, header) = create_dummy_pcd_ascii();
+    let text = std::fs::read_to_string(file.path()).unwrap();
+    let header_text = header.to_string();
+    assert!(header_text.ends_with("DATA ascii\n"));
+    assert_eq!(header_text, text[..header_text.len()]);
+    assert_eq!(
+        DataFormat::BinaryCompressed.to_string(),
+        "binary_compressed"
+    );
+
+    let (parsed, _) = rs_pcd::header::parse_header_bytes(header_text.as_bytes()).unwrap();
+    assert_eq!(parsed, header);
+}
+
+#[test]
+fn test_builder_presets() {
+    use rs_pcd::header::PcdHeaderBuilder;
+    use rs_pcd::storage::PointBlock;
+
+    let presets = [
+        PcdHeaderBuilder::xyz(),
+        PcdHeaderBuilder::xyzi(),
+        PcdHeaderBuilder::xyzir(),
+        PcdHeaderBuilder::xyzirt(),
+        PcdHeaderBuilder::xyzirt_id(),
+        PcdHeaderBuilder::xyzrgb(),
+        PcdHeaderBuilder::xyzrgba(),
+        PcdHeaderBuilder::xyzl(),
+        PcdHeaderBuilder::xyz_normals(),
+    ];
+    let mut blocks = Vec::new();
+    for builder in presets {
+        let block = PointBlock::new(builder.schema(), 3);
+        let header = builder.width(3).build().unwrap();
+        let mut buf = Vec::new();
+        rs_pcd::io::PcdWriter::new(&mut buf)
+            .write_pcd(&header, &block)
+            .unwrap();
+        blocks.push(rs_pcd::io::read_pcd_bytes(&buf).unwrap());
+    }
+    assert!(blocks[0].xyz().is_some() && blocks[0].xyzi().is_none());
+    assert!(blocks[1].xyzi().is_some());
+    assert!(blocks[2].xyzir().is_some());
+    assert!(blocks[3].xyzirt().is_some());
+    assert!(blocks[4].xyzirt_id().is_some());
+    assert!(blocks[5].xyzrgb().is_some());
+    assert!(blocks[6].xyzrgba().is_some() && blocks[6].xyzrgb().is_none());
+    assert!(blocks[7].xyzl().is_some());
+    assert!(blocks[8].xyz_normals().is_some());
+
+    let (x, ring, t) = rs_pcd::columns!(blocks[3], x: f32, ring: u16, timestamp: f64).unwrap();
+    assert_eq!((x.len(), ring.len(), t.len()), (3, 3, 3));
+    assert!(rs_pcd::columns!(blocks[3], ring: u32).is_none());
+    assert!(rs_pcd::columns!(&blocks[3], x: f32, id: u32).is_none());
+    assert_eq!(blocks[8].column_as::<f32>("curvature").unwrap().len(), 3);
+}
+
+#[test]
+fn test_xyz_f64() {
+    use rs_pcd::ValueType;
+    use rs_pcd::header::PcdHeaderBuilder;
+    use rs_pcd::storage::PointBlock;
+    use std::borrow::Cow;
+
+    let builder = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F64)
+        .add_field("y", ValueType::F64)
+        .add_field("z", ValueType::F64)
+        .width(2);
+    let mut block = PointBlock::new(builder.schema(), 2);
+    block.get_column_mut("x").unwrap().as_f64_mut().unwrap()[1] = 4_500_000.25;
+    let mut buf = Vec::new();
+    rs_pcd::io::PcdWriter::new(&mut buf)
+        .write_pcd(&builder.build().unwrap(), &block)
+        .unwrap();
+    let block = rs_pcd::io::read_pcd_bytes(&buf).unwrap();
+
+    assert!(block.xyz().is_none());
+    let (x, _, _) = block.xyz_f64().unwrap();
+    assert_eq!(x, &[0.0, 4_500_000.25]);
+    let (x, y, _) = block.xyz_as::<f64>().unwrap();
+    assert!(matches!(x, Cow::Borrowed(_)));
+    assert_eq!(y.len(), 2);
+    let (x, _, _) = block.xyz_as::<f32>().unwrap();
+    assert!(matches!(x, Cow::Owned(_)));
+    assert_eq!(x[1], 4_500_000.25_f64 as f32);
+    assert!(
+        PointBlock::new(PcdHeaderBuilder::xyzl().schema(), 1)
+            .xyz_f64()
+            .is_none()
+    );
+}
+
+#[test]
+fn test_iter_xyz() {
+    use rs_pcd::ValueType;
+    use rs_pcd::header::PcdHeaderBuilder;
+    use rs_pcd::storage::PointBlock;
+
+    let mut block = PointBlock::new(PcdHeaderBuilder::xyz().schema(), 3);
+    block.get_column_mut("y").unwrap().as_f32_mut().unwrap()[0] = 0.5;
+    block.get_column_mut("z").unwrap().as_f32_mut().unwrap()[1] = f32::NAN;
+    let points: Vec<_> = block.iter_xyz().unwrap().collect();
+    assert_eq!(points.len(), 3);
+    assert_eq!(points[0], [0.0, 0.5, 0.0]);
+    assert!(points[1][2].is_nan());
+    let finite: Vec<_> = block.iter_xyz_finite().unwrap().map(|(i, _)| i).collect();
+    assert_eq!(finite, [0, 2]);
+
+    let schema = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F64)
+        .add_field("y", ValueType::F64)
+        .add_field("z", ValueType::I32)
+        .schema();
+    let mut block = PointBlock::new(schema, 1);
+    block.get_column_mut("x").unwrap().as_f64_mut().unwrap()[0] = 4_500_000.125;
+    block.get_column_mut("z").unwrap().as_i32_mut().unwrap()[0] = -3;
+    assert_eq!(
+        block.iter_xyz().unwrap().next(),
+        Some([4_500_000.125, 0.0, -3.0])
+    );
+    assert!(
+        PointBlock::new(PcdHeaderBuilder::new().schema(), 0)
+            .iter_xyz()
+            .is_none()
+    );
+}
+
+#[test]
+fn test_schema() {
+    use rs_pcd::ValueType;
+    use rs_pcd::header::{PcdHeaderBuilder, Schema};
+    use rs_pcd::layout::PcdLayout;
+    use rs_pcd::storage::PointBlock;
+
+    let schema = Schema::new()
+        .with_field("x", ValueType::F32)
+        .with_field_count("fpfh", ValueType::F32, 33)
+        .with_field("label", ValueType::U16);
+    let header = PcdHeaderBuilder::from_schema(schema.clone())
+        .width(4)
+        .build()
+        .unwrap();
+    assert_eq!(header.counts, [1, 33, 1]);
+    assert_eq!(header.schema().unwrap(), schema);
+
+    let layout = PcdLayout::from_schema(&schema).unwrap();
+    assert_eq!(layout, PcdLayout::from_header(&header).unwrap());
+    assert_eq!(layout.total_size, 4 + 33 * 4 + 2);
+    assert_eq!(layout.schema(), schema);
+
+    // Columns hold `count` values per point.
+    let block = PointBlock::new(&schema, 4);
+    assert_eq!(block.get_column("fpfh").unwrap().len(), 4 * 33);
+    assert_eq!(block.to_schema(), schema);
+    // Counts come from the schema, so blocks without points keep them.
+    assert_eq!(PointBlock::new(&schema, 0).to_schema(), schema);
+    assert_eq!(block.slice(0..0).to_schema(), schema);
+    assert_eq!(block.filter(&[false; 4]).to_schema(), schema);
+    let reshaped = Schema::new()
+        .with_field("x", ValueType::F32)
+        .with_field_count("fpfh", ValueType::F32, 11)
+        .with_field("label", ValueType::U16);
+    let mut appended = block.clone();
+    assert!(appended.append(&PointBlock::new(&reshaped, 12)).is_err());
+    let mut buf = Vec::new();
+    rs_pcd::io::PcdWriter::new(&mut buf)
+        .write_pcd(&header, &block)
+        .unwrap();
+    assert_eq!(
+        rs_pcd::io::read_pcd_bytes(&buf).unwrap().to_schema(),
+        schema
+    );
+
+    let repeated = schema.with_field("x", ValueType::F64);
+    assert_eq!(repeated.duplicate(), Some("x"));
+    assert!(PcdLayout::from_schema(&repeated).is_err());
+    assert!(ValueType::from_pcd('F', 2).is_err());
+    assert_eq!(ValueType::from_pcd('U', 2).unwrap(), ValueType::U16);
+}
+
+#[test]
+fn test_target_schema() {
+    use rs_pcd::header::{PcdHeaderBuilder, Schema};
+    use rs_pcd::io::{PcdReader, PcdWriter};
+    use rs_pcd::{Operation, PcdError, ValueType};
+
+    let builder = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .add_field("t", ValueType::F64)
+        .add_field("intensity", ValueType::U8)
+        .add_field("ring", ValueType::U16)
+        .width(3);
+    let mut block = rs_pcd::storage::PointBlock::new(builder.schema(), 3);
+    for i in 0..3 {
+        block.get_column_mut("x").unwrap().as_f32_mut().unwrap()[i] = i as f32 + 0.5;
+        block.get_column_mut("t").unwrap().as_f64_mut().unwrap()[i] = 10.0 + i as f64;
+        block
+            .get_column_mut("intensity")
+            .unwrap()
+            .as_u8_mut()
+            .unwrap()[i] = 100 + i as u8;
+    }
+    let target = Schema::new()
+        .with_field("intensity", ValueType::F32)
+        .with_field("x", ValueType::F64)
+        .with_field("timestamp", ValueType::F64);
+
+    for format in [
+        DataFormat::Ascii,
+        DataFormat::Binary,
+        DataFormat::BinaryCompressed,
+    ] {
+        let header = builder.clone().data_format(format).build().unwrap();
+        let mut buf = Vec::new();
+        PcdWriter::new(&mut buf).write_pcd(&header, &block).unwrap();
+        let open = || {
+            PcdReader::from_bytes(&buf)
+                .unwrap()
+                .target_schema(target.clone())
+                .rename_field("t", "timestamp")
+        };
+
+        let back = open().read_all().unwrap();
+        assert_eq!(back.to_schema(), target, "{:?}", format);
+        assert_eq!(
+            back.get_column("intensity").unwrap().as_f32().unwrap(),
+            &[100.0, 101.0, 102.0]
+        );
+        assert_eq!(
+            back.get_column("x").unwrap().as_f64().unwrap(),
+            &[0.5, 1.5, 2.5]
+        );
+        assert_eq!(
+            back.get_column("timestamp").unwrap().as_f64().unwrap()[2],
+            12.0
+        );
+        let mut reader = open();
+        let chunk = reader.read_chunk(2).unwrap().unwrap();
+        assert_eq!(chunk.to_schema(), target);
+    }
+
+    let header = builder.clone().build().unwrap();
+    let mut buf = Vec::new();
+    PcdWriter::new(&mut buf).write_pcd(&header, &block).unwrap();
+    let mut reader = PcdReader::from_bytes(&buf)
+        .unwrap()
+        .target_schema(target.clone())
+        .rename_field("t", "timestamp");
+    let point = reader.read_point(1).unwrap();
+    assert_eq!(point.get_column("x").unwrap().as_f64().unwrap(), &[1.5]);
+
+    // Renames alone keep every field.
+    let renamed = PcdReader::from_bytes(&buf)
+        .unwrap()
+        .rename_field("t", "timestamp")
+        .read_all()
+        .unwrap();
+    assert_eq!(
+        renamed.schema().names().collect::<Vec<_>>(),
+        &["x", "timestamp", "intensity", "ring"]
+    );
+
+    let err = PcdReader::from_bytes(&buf)
+        .unwrap()
+        .target_schema(target.clone())
+        .read_all()
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        PcdError::MissingField { field, op: Operation::Read } if field == "timestamp"
+    ));
+    let err = PcdReader::from_bytes(&buf)
+        .unwrap()
+        .rename_field("time", "timestamp")
+        .read_all()
+        .unwrap_err();
+    assert!(matches!(err, PcdError::MissingField { field, .. } if field == "time"));
+}
+
+#[test]
+fn test_fill_missing() {
+    use rs_pcd::ValueType;
+    use rs_pcd::header::{PcdHeaderBuilder, Schema};
+    use rs_pcd::io::{Issue, PcdReader, PcdWriter};
+
+    let builder = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .width(3);
+    let mut block = rs_pcd::storage::PointBlock::new(builder.schema(), 3);
+    block.get_column_mut("x").unwrap().as_f32_mut().unwrap()[1] = 2.0;
+    let header = builder.build().unwrap();
+    let mut buf = Vec::new();
+    PcdWriter::new(&mut buf).write_pcd(&header, &block).unwrap();
+
+    let target = Schema::new()
+        .with_field("x", ValueType::F32)
+        .with_field("intensity", ValueType::F32)
+        .with_field("ring", ValueType::U16);
+    let mut reader = PcdReader::from_bytes(&buf)
+        .unwrap()
+        .target_schema(target.clone())
+        .fill_missing(true);
+    let first = reader.read_chunk(2).unwrap().unwrap();
+    let rest = reader.read_chunk(2).unwrap().unwrap();
+    assert_eq!(first.to_schema(), target);
+    assert_eq!(
+        first.get_column("x").unwrap().as_f32().unwrap(),
+        &[0.0, 2.0]
+    );
+    assert!(rest.get_column("intensity").unwrap().as_f32().unwrap()[0].is_nan());
+    assert_eq!(rest.get_column("ring").unwrap().as_u16().unwrap(), &[0]);
+    assert_eq!(
+        reader.issues(),
+        &[
+            Issue::FilledField("intensity".into()),
+            Issue::FilledField("ring".into())
+        ]
+    );
+}
+
+#[test]
+fn test_duplicate_fields() {
+    use rs_pcd::header::{DuplicateFields, HeaderOptions, parse_header_bytes_with};
+
+    let text = "FIELDS x _ _ x\nSIZE 4 1 1 4\nTYPE F U U F\nWIDTH 1\nHEIGHT 1\nPOINTS 1\n\
+                DATA ascii\n1.5 7 8 2.5\n";
+    let reader = rs_pcd::io::PcdReader::from_bytes(text.as_bytes()).unwrap();
+    assert_eq!(reader.header().fields, ["x_0", "_0", "_1", "x_1"]);
+    let block = reader.read_all().unwrap();
+    assert_eq!(block.get_column("x_0").unwrap().as_f32().unwrap(), [1.5]);
+    assert_eq!(block.get_column("x_1").unwrap().as_f32().unwrap(), [2.5]);
+    assert_eq!(block.get_column("_1").unwrap().as_u8().unwrap(), [8]);
+
+    let strict = HeaderOptions {
+        duplicates: DuplicateFields::Error,
+        ..HeaderOptions::default()
+    };
+    assert!(matches!(
+        parse_header_bytes_with(text.as_bytes(), &strict),
+        Err(rs_pcd::PcdError::InvalidHeader { line: 7, msg }) if msg.contains("Duplicate field _")
+    ));
+
+    // Renaming skips names already in use.
+    let mut header = PcdHeader {
+        fields: vec!["y".into(), "y_0".into(), "y".into()],
+        sizes: vec![4; 3],
+        types: vec!['F'; 3],
+        counts: vec![1; 3],
+        ..PcdHeader::default()
+    };
+    assert!(rs_pcd::layout::PcdLayout::from_header(&header).is_err());
+    assert!(header.rename_duplicate_fields());
+    assert_eq!(header.fields, ["y_1", "y_0", "y_2"]);
+    assert!(!header.rename_duplicate_fields());
+    assert!(rs_pcd::layout::PcdLayout::from_header(&header).is_ok());
+
+    let built = rs_pcd::header::PcdHeaderBuilder::xyz()
+        .add_field("x", rs_pcd::ValueType::F32)
+        .width(1)
+        .build();
+    assert!(built.is_err());
+}
+
+#[test]
+fn test_ascii_malformed_lines() {
+    use rs_pcd::io::{Issue, PcdReader};
+
+    let text = "FIELDS x y\nSIZE 4 4\nTYPE F F\nWIDTH 3\nHEIGHT 1\nPOINTS 3\nDATA ascii\n\
+                1 2\n3 oops\n5\n7 8\n";
+    let err = PcdReader::from_bytes(text.as_bytes())
+        .unwrap()
+        .read_all()
+        .unwrap_err();
+    assert!(err.to_string().contains("ASCII data line 2:"), "{}", err);
+
+    let mut reader = PcdReader::from_bytes(text.as_bytes())
+        .unwrap()
+        .skip_malformed_lines(true);
+    let first = reader.read_chunk(1).unwrap().unwrap();
+    assert_eq!(first.get_column("y").unwrap().as_f32().unwrap(), [2.0]);
+    let rest = reader.read_chunk(2).unwrap().unwrap();
+    assert_eq!(rest.len, 1);
+    assert_eq!(rest.get_column("x").unwrap().as_f32().unwrap(), [7.0]);
+    assert!(reader.read_chunk(1).unwrap().is_none());
+    assert_eq!(
+        reader.issues(),
+        [Issue::MalformedLine(2), Issue::MalformedLine(3)]
+    );
+}
+
+#[test]
+fn test_ascii_short_lines() {
+    use rs_pcd::io::{PcdReader, ShortLines};
+
+    let text = "FIELDS x y rgb\nSIZE 4 8 4\nTYPE F F U\nCOUNT 1 1 1\nWIDTH 2\nHEIGHT 1\n\
+                POINTS 2\nDATA ascii\n1 2 3\n4\n";
+    let err = PcdReader::from_bytes(text.as_bytes())
+        .unwrap()
+        .read_all()
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("line 2: Not enough tokens for field y"),
+        "{}",
+        err
+    );
+
+    let block = PcdReader::from_bytes(text.as_bytes())
+        .unwrap()
+        .short_ascii_lines(ShortLines::Zero)
+        .read_all()
+        .unwrap();
+    assert_eq!(block.get_column("y").unwrap().as_f64().unwrap(), [2.0, 0.0]);
+
+    let block = PcdReader::from_bytes(text.as_bytes())
+        .unwrap()
+        .short_ascii_lines(ShortLines::Nan)
+        .read_all()
+        .unwrap();
+    assert!(block.get_column("y").unwrap().as_f64().unwrap()[1].is_nan());
+    assert_eq!(block.get_column("rgb").unwrap().as_u32().unwrap(), [3, 0]);
+}
+
+#[test]
+fn test_ascii_lines_across_buffer_fills() {
+    use std::io::BufReader;
+
+    let mut text = String::from(
+        "FIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nWIDTH 50\nHEIGHT 1\nPOINTS 50\nDATA ascii\n",
+    );
+    for i in 0..50 {
+        text.push_str(&format!("{}.25 -{} 1e-3\r\n", i, i * 7));
+    }
+    for capacity in [5, 16, 8192] {
+        let reader = BufReader::with_capacity(capacity, text.as_bytes());
+        let mut reader = rs_pcd::io::PcdReader::new(reader).unwrap();
+        let head = reader.read_chunk(17).unwrap().unwrap();
+        let tail = reader.read_chunk(50).unwrap().unwrap();
+        assert_eq!(head.get_column("x").unwrap().as_f32().unwrap()[16], 16.25);
+        assert_eq!(tail.len, 33);
+        assert_eq!(tail.get_column("y").unwrap().as_f32().unwrap()[32], -343.0);
+        assert_eq!(tail.get_column("z").unwrap().as_f32().unwrap()[0], 1e-3);
+    }
+}
+
+#[test]
+fn test_ascii_output_text() {
+    use rs_pcd::ValueType;
+    use rs_pcd::header::PcdHeaderBuilder;
+    use rs_pcd::storage::{Column, PointBlock};
+
+    let header = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F64)
+        .add_field_with_count("n", ValueType::I16, 2)
+        .width(2)
+        .data_format(DataFormat::Ascii)
+        .build()
+        .unwrap();
+    let mut block = PointBlock::new(
+        &[("x".into(), ValueType::F64), ("n".into(), ValueType::I16)],
+        2,
+    );
+    *block.get_column_mut("x").unwrap() = Column::F64(vec![-1.25, 3e-7]);
+    *block.get_column_mut("n").unwrap() = Column::I16(vec![-32768, 0, 7, 32767]);
+
+    let mut buf = Vec::new();
+    rs_pcd::io::PcdWriter::new(&mut buf)
+        .write_pcd(&header, &block)
+        .unwrap();
+    let text = String::from_utf8(buf).unwrap();
+    assert!(
+        text.ends_with("DATA ascii\n-1.250000 -32768 0\n0.000000 7 32767\n"),
+        "{}",
+        text
+    );
+}
+
+#[test]
+fn test_ascii_float_formats() {
+    use rs_pcd::ValueType;
+    use rs_pcd::header::PcdHeaderBuilder;
+    use rs_pcd::io::{FloatFormat, PcdReader, PcdWriter};
+    use rs_pcd::storage::{Column, PointBlock};
+
+    let header = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .add_field("y", ValueType::F32)
+        .add_field("t", ValueType::F64)
+        .width(1)
+        .data_format(DataFormat::Ascii)
+        .build()
+        .unwrap();
+    let schema = [
+        ("x".into(), ValueType::F32),
+        ("y".into(), ValueType::F32),
+        ("t".into(), ValueType::F64),
+    ];
+    let mut block = PointBlock::new(&schema, 1);
+    *block.get_column_mut("x").unwrap() = Column::F32(vec![1.5]);
+    *block.get_column_mut("y").unwrap() = Column::F32(vec![0.000125]);
+    *block.get_column_mut("t").unwrap() = Column::F64(vec![1_700_000_000.123_456_7]);
+
+    let mut buf = Vec::new();
+    PcdWriter::new(&mut buf)
+        .float_format(FloatFormat::Fixed(2))
+        .field_format("y", FloatFormat::Scientific(1))
+        .field_format("t", FloatFormat::Shortest)
+        .write_pcd(&header, &block)
+        .unwrap();
+    let text = String::from_utf8(buf.clone()).unwrap();
+    assert!(
+        text.ends_with("DATA ascii\n1.50 1.3e-4 1700000000.1234567\n"),
+        "{}",
+        text
+    );
+
+    let back = PcdReader::from_bytes(&buf).unwrap().read_all().unwrap();
+    assert_eq!(
+        back.get_column("t").unwrap().as_f64().unwrap(),
+        [1_700_000_000.123_456_7]
+    );
+}
+
+#[test]
+fn test_compressed_write() {
+    use rand::Rng;
+    use rs_pcd::ValueType;
+    use rs_pcd::header::PcdHeaderBuilder;
+    use rs_pcd::io::{PcdReader, PcdWriter};
+    use rs_pcd::storage::{Column, PointBlock};
+
+    let points = 50_000;
+    let builder = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .add_field_with_count("n", ValueType::U8, 3)
+        .add_field("t", ValueType::F64)
+        .width(points as u32)
+        .data_format(DataFormat::BinaryCompressed);
+    let schema = builder.schema();
+    let header = builder.build().unwrap();
+    let mut rng = rand::rng();
+    let mut block = PointBlock::new(&schema, points);
+    *block.get_column_mut("x").unwrap() =
+        Column::F32((0..points).map(|i| (i / 100) as f32).collect());
+    *block.get_column_mut("n").unwrap() =
+        Column::U8((0..3 * points).map(|_| rng.random()).collect());
+    *block.get_column_mut("t").unwrap() = Column::F64((0..points).map(|i| i as f64).collect());
+
+    let mut buf = Vec::new();
+    PcdWriter::new(&mut buf).write_pcd(&header, &block).unwrap();
+    let marker = b"DATA binary_compressed\n";
+    let start = buf.windows(marker.len()).position(|w| w == marker).unwrap() + marker.len();
+    let compressed = u32::from_le_bytes(buf[start..start + 4].try_into().unwrap()) as usize;
+    let uncompressed = u32::from_le_bytes(buf[start + 4..start + 8].try_into().unwrap()) as usize;
+    assert_eq!(uncompressed, points * 15);
+    assert!(compressed < uncompressed);
+    let payload = lzf::decompress(&buf[start + 8..], uncompressed).unwrap();
+    assert_eq!(&payload[4 * 2_500..4 * 2_501], &25f32.to_le_bytes());
+
+    let back = PcdReader::from_bytes(&buf).unwrap().read_all().unwrap();
+    let x = |b: &PointBlock| b.get_column("x").unwrap().as_f32().unwrap().to_vec();
+    let n = |b: &PointBlock| b.get_column("n").unwrap().as_u8().unwrap().to_vec();
+    let t = |b: &PointBlock| b.get_column("t").unwrap().as_f64().unwrap().to_vec();
+    assert_eq!(
+        (x(&back), n(&back), t(&back)),
+        (x(&block), n(&block), t(&block))
+    );
+
+    // Incompressible data is stored as is.
+    let noise = PcdHeaderBuilder::new()
+        .add_field("n", ValueType::U8)
+        .width(64)
+        .data_format(DataFormat::BinaryCompressed);
+    let mut small = PointBlock::new(noise.schema(), 64);
+    *small.get_column_mut("n").unwrap() = Column::U8((0..64).map(|_| rng.random()).collect());
+    let mut buf = Vec::new();
+    PcdWriter::new(&mut buf)
+        .write_pcd(&noise.build().unwrap(), &small)
+        .unwrap();
+    assert!(buf.ends_with(small.get_column("n").unwrap().as_u8().unwrap()));
+    let back = rs_pcd::io::read_pcd_bytes(&buf).unwrap();
+    assert_eq!(
+        back.get_column("n").unwrap().as_u8(),
+        small.get_column("n").unwrap().as_u8()
+    );
+}
+
+#[test]
+fn test_compressed_size_limit() {
+    use rs_pcd::ValueType;
+    use rs_pcd::header::PcdHeaderBuilder;
+    use rs_pcd::storage::PointBlock;
+
+    // 8 GiB of data; refused before the columns are looked at.
+    let header = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .add_field("y", ValueType::F32)
+        .width(1 << 30)
+        .data_format(DataFormat::BinaryCompressed)
+        .build()
+        .unwrap();
+    let mut buf = Vec::new();
+    let err = rs_pcd::io::PcdWriter::new(&mut buf)
+        .write_pcd(&header, &PointBlock::default())
+        .unwrap_err();
+    assert!(buf.is_empty());
+    assert!(
+        matches!(&err, rs_pcd::PcdError::UnsupportedDataFormat(msg) if msg.contains("8589934592 bytes")),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_read_batch_bytes() {
+    use rs_pcd::ValueType;
+    use rs_pcd::decoder::binary::BinaryReader;
+    use rs_pcd::header::{PcdHeaderBuilder, parse_header_bytes};
+    use rs_pcd::io::{PcdReader, PcdWriter};
+    use rs_pcd::layout::PcdLayout;
+    use rs_pcd::storage::PointBlock;
+    use std::io::Read;
+
+    // Counts the reads that reach the source.
+    struct Counted<'a>(&'a [u8], usize);
+    impl Read for Counted<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.1 += 1;
+            self.0.read(buf)
+        }
+    }
+
+    let builder = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .add_field("label", ValueType::U16)
+        .width(1000);
+    let header = builder.clone().build().unwrap();
+    let mut block = PointBlock::new(builder.schema(), 1000);
+    for (i, x) in block
+        .get_column_mut("x")
+        .unwrap()
+        .as_f32_mut()
+        .unwrap()
+        .iter_mut()
+        .enumerate()
+    {
+        *x = i as f32;
+    }
+    let mut buf = Vec::new();
+    PcdWriter::new(&mut buf).write_pcd(&header, &block).unwrap();
+    let (_, start) = parse_header_bytes(&buf).unwrap();
+
+    // Batches are whole 6-byte points: 100 bytes is 16 points, so 63 reads.
+    let layout = PcdLayout::from_header(&header).unwrap();
+    for (bytes, reads) in [(100, 63), (1, 1000), (1 << 20, 1)] {
+        let mut source = Counted(&buf[start..], 0);
+        let mut out = PointBlock::new(builder.schema(), 0);
+        BinaryReader::new(&mut source, &layout, 1000)
+            .batch_bytes(bytes)
+            .decode(&mut out)
+            .unwrap();
+        assert_eq!(source.1, reads, "{} bytes", bytes);
+        assert_eq!(out.get_column("x").unwrap().as_f32().unwrap()[999], 999.0);
+    }
+
+    let back = PcdReader::from_bytes(&buf)
+        .unwrap()
+        .read_batch_bytes(7)
+        .read_all()
+        .unwrap();
+    assert_eq!(back.get_column("x").unwrap().as_f32().unwrap()[500], 500.0);
+}
+
+#[test]
+fn test_progress_callbacks() {
+    use rs_pcd::ValueType;
+    use rs_pcd::header::PcdHeaderBuilder;
+    use rs_pcd::io::{PcdReader, PcdStreamWriter, PcdWriter};
+    use rs_pcd::storage::PointBlock;
+    use std::sync::{Arc, Mutex};
+
+    type Reports = Arc<Mutex<Vec<(usize, usize)>>>;
+    let recorder = || {
+        let reports: Reports = Arc::default();
+        let sink = reports.clone();
+        (reports, move |done, total| {
+            sink.lock().unwrap().push((done, total))
+        })
+    };
+
+    let builder = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .add_field("n", ValueType::U8)
+        .width(70_000);
+    let block = PointBlock::new(builder.schema(), 70_000);
+    for (format, written, read) in [
+        (DataFormat::Binary, vec![70_000], vec![52_428, 70_000]),
+        (
+            DataFormat::Ascii,
+            vec![65_536, 70_000],
+            vec![65_536, 70_000],
+        ),
+        (DataFormat::BinaryCompressed, vec![70_000], vec![70_000]),
+    ] {
+        let header = builder.clone().data_format(format).build().unwrap();
+        let (reports, progress) = recorder();
+        let mut buf = Vec::new();
+        PcdWriter::new(&mut buf)
+            .on_progress(progress)
+            .write_pcd(&header, &block)
+            .unwrap();
+        let done: Vec<usize> = reports.lock().unwrap().iter().map(|r| r.0).collect();
+        assert_eq!(done, written, "{:?}", format);
+        assert!(reports.lock().unwrap().iter().all(|r| r.1 == 70_000));
+
+        let (reports, progress) = recorder();
+        let back = PcdReader::from_bytes(&buf)
+            .unwrap()
+            .on_progress(progress)
+            .read_all()
+            .unwrap();
+        assert_eq!(back.len, 70_000);
+        let done: Vec<usize> = reports.lock().unwrap().iter().map(|r| r.0).collect();
+        assert_eq!(done, read, "{:?}", format);
+    }
+
+    // Chunked reads report points in the whole file.
+    let header = builder.clone().build().unwrap();
+    let mut buf = Vec::new();
+    PcdWriter::new(&mut buf).write_pcd(&header, &block).unwrap();
+    let (reports, progress) = recorder();
+    let mut reader = PcdReader::from_bytes(&buf).unwrap().on_progress(progress);
+    while reader.read_chunk(30_000).unwrap().is_some() {}
+    assert_eq!(
+        *reports.lock().unwrap(),
+        [(30_000, 70_000), (60_000, 70_000), (70_000, 70_000)]
+    );
+
+    let (reports, progress) = recorder();
+    let mut stream = PcdStreamWriter::new(Vec::new(), &header)
+        .unwrap()
+        .on_progress(progress);
+    let half = PointBlock::new(builder.schema(), 35_000);
+    stream.write_chunk(&half).unwrap();
+    stream.write_chunk(&half).unwrap();
+    stream.finish().unwrap();
+    assert_eq!(
+        *reports.lock().unwrap(),
+        [(35_000, 70_000), (70_000, 70_000)]
+    );
+}
+
+#[test]
+fn test_cancel_read() {
+    use rs_pcd::PcdError;
+    use rs_pcd::ValueType;
+    use rs_pcd::header::PcdHeaderBuilder;
+    use rs_pcd::io::{PcdReader, PcdWriter};
+    use rs_pcd::storage::PointBlock;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    let builder = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .width(100_000);
+    let block = PointBlock::new(builder.schema(), 100_000);
+    for format in [DataFormat::Binary, DataFormat::Ascii] {
+        let header = builder.clone().data_format(format).build().unwrap();
+        let mut buf = Vec::new();
+        PcdWriter::new(&mut buf).write_pcd(&header, &block).unwrap();
+
+        // Cancelled from the progress callback after the first batch.
+        let flag = Arc::new(AtomicBool::new(false));
+        let (set, reports) = (flag.clone(), Arc::new(AtomicUsize::new(0)));
+        let seen = reports.clone();
+        let err = PcdReader::from_bytes(&buf)
+            .unwrap()
+            .read_batch_bytes(4 << 10)
+            .cancel_on(flag)
+            .on_progress(move |_, _| {
+                seen.fetch_add(1, Ordering::Relaxed);
+                set.store(true, Ordering::Relaxed);
+            })
+            .read_all()
+            .unwrap_err();
+        assert!(matches!(err, PcdError::Cancelled), "{:?}", format);
+        assert_eq!(reports.load(Ordering::Relaxed), 1);
+        assert_eq!(err.to_string(), "Cancelled");
+
+        // Already set: nothing is read.
+        let mut reader = PcdReader::from_bytes(&buf)
+            .unwrap()
+            .cancel_on(Arc::new(AtomicBool::new(true)));
+        assert!(matches!(reader.read_chunk(10), Err(PcdError::Cancelled)));
+    }
+}
+
+#[test]
+fn test_aliases() {
+    use rs_pcd::ValueType;
+    use rs_pcd::header::{PcdHeaderBuilder, Schema};
+    use rs_pcd::io::{PcdReader, PcdWriter};
+
+    let aliases = [
+        ("t", "timestamp"),
+        ("time", "timestamp"),
+        ("reflectivity", "intensity"),
+        ("signal", "intensity"),
+    ];
+    let encode = |fields: &[&str]| {
+        let mut builder = PcdHeaderBuilder::new().width(2);
+        for name in fields {
+            builder = builder.add_field(name, ValueType::F32);
+        }
+        let mut block = rs_pcd::storage::PointBlock::new(builder.schema(), 2);
+        for (i, name) in fields.iter().enumerate() {
+            block.get_column_mut(name).unwrap().as_f32_mut().unwrap()[1] = i as f32;
+        }
+        let mut buf = Vec::new();
+        PcdWriter::new(&mut buf)
+            .write_pcd(&builder.build().unwrap(), &block)
+            .unwrap();
+        buf
+    };
+
+    let buf = encode(&["x", "time", "signal", "reflectivity"]);
+    let block = PcdReader::from_bytes(&buf)
+        .unwrap()
+        .aliases(aliases)
+        .read_all()
+        .unwrap();
+    // `signal` comes after `reflectivity` in the map, so keeps its name.
+    assert_eq!(
+        block.schema().names().collect::<Vec<_>>(),
+        &["x", "timestamp", "signal", "intensity"]
+    );
+    assert_eq!(
+        block.get_column("intensity").unwrap().as_f32().unwrap()[1],
+        3.0
+    );
+
+    // A field already named canonically wins over its alias.
+    let buf = encode(&["x", "reflectivity", "intensity"]);
+    let target = Schema::new()
+        .with_field("x", ValueType::F32)
+        .with_field("intensity", ValueType::F32);
+    let block = PcdReader::from_bytes(&buf)
+        .unwrap()
+        .aliases(aliases)
+        .target_schema(target.clone())
+        .read_all()
+        .unwrap();
+    assert_eq!(block.to_schema(), target);
+    assert_eq!(
+        block.get_column("intensity").unwrap().as_f32().unwrap()[1],
+        2.0
+    );
+}
+
+#[test]
+fn test_labels() {
+    use rs_pcd::ValueType;
+    use rs_pcd::header::PcdHeaderBuilder;
+    use rs_pcd::storage::PointBlock;
+    use std::collections::HashMap;
+
+    let mut block = PointBlock::new(PcdHeaderBuilder::xyzl().schema(), 5);
+    block
+        .get_column_mut("label")
+        .unwrap()
+        .as_u32_mut()
+        .unwrap()
+        .copy_from_slice(&[10, 40, 10, 44, 0]);
+    block.get_column_mut("x").unwrap().as_f32_mut().unwrap()[2] = 2.0;
+
+    let map = HashMap::from([(10, 1), (40, 9), (44, 9)]);
+    block.remap_labels(&map).unwrap();
+    assert_eq!(
+        block.get_column("label").unwrap().as_u32().unwrap(),
+        &[1, 9, 1, 9, 0]
+    );
+    let histogram = block.label_histogram().unwrap();
+    assert_eq!(
+        histogram.into_iter().collect::<Vec<_>>(),
+        [(0, 1), (1, 2), (9, 2)]
+    );
+
+    let classes = block.split_by_label().unwrap();
+    assert_eq!(classes.keys().copied().collect::<Vec<_>>(), [0, 1, 9]);
+    assert_eq!(classes[&1].len, 2);
+    assert_eq!(
+        classes[&1].get_column("x").unwrap().as_f32().unwrap(),
+        &[0.0, 2.0]
+    );
+
+    // Narrow label types reject labels they cannot hold.
+    let schema = PcdHeaderBuilder::xyz()
+        .add_field("label", ValueType::U8)
+        .schema();
+    let mut block = PointBlock::new(schema, 2);
+    assert!(block.remap_labels(&HashMap::from([(0, 300)])).is_err());
+    assert_eq!(block.get_column("label").unwrap().as_u8().unwrap(), &[0, 0]);
+    assert_eq!(block.label_histogram().unwrap()[&0], 2);
+    assert!(
+        PointBlock::new(PcdHeaderBuilder::xyz().schema(), 1)
+            .label_histogram()
+            .is_err()
+    );
+}
+
+#[test]
+fn test_transform() {
+    use rs_pcd::ValueType;
+    use rs_pcd::header::PcdHeaderBuilder;
+    use rs_pcd::storage::PointBlock;
+
+    // 90 degrees about z, then (10, 0, 1).
+    let m = [
+        [0.0, -1.0, 0.0, 10.0],
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 1.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ];
+    let mut block = PointBlock::new(PcdHeaderBuilder::xyz_normals().schema(), 2);
+    block.get_column_mut("x").unwrap().as_f32_mut().unwrap()[1] = 2.0;
+    block
+        .get_column_mut("normal_x")
+        .unwrap()
+        .as_f32_mut()
+        .unwrap()[1] = 1.0;
+    block.transform(&m).unwrap();
+    let (x, y, z, nx, ny, nz, _) = block.xyz_normals().unwrap();
+    assert_eq!(
+        (x, y, z),
+        (&[10.0, 10.0][..], &[0.0, 2.0][..], &[1.0, 1.0][..])
+    );
+    assert_eq!((nx[1], ny[1], nz[1]), (0.0, 1.0, 0.0));
+
+    let schema = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F64)
+        .add_field("y", ValueType::F64)
+        .add_field("z", ValueType::F64)
+        .schema();
+    let mut block = PointBlock::new(schema, 1);
+    block.get_column_mut("y").unwrap().as_f64_mut().unwrap()[0] = 4_500_000.125;
+    block.transform(&m).unwrap();
+    assert_eq!(block.xyz_f64().unwrap().0, &[10.0 - 4_500_000.125]);
+
+    let schema = PcdHeaderBuilder::xyz().add_field("normal_x", ValueType::F32);
+    let mut block = PointBlock::new(schema.schema(), 1);
+    // Partial normals are left alone.
+    block.transform(&m).unwrap();
+    assert_eq!(
+        block.get_column("normal_x").unwrap().as_f32().unwrap(),
+        &[0.0]
+    );
+    let schema = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .add_field("y", ValueType::F32)
+        .add_field("z", ValueType::I32)
+        .schema();
+    assert!(PointBlock::new(schema, 1).transform(&m).is_err());
+}
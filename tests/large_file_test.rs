@@ -12,12 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-#[cfg(feature = "memmap2")]
-use rs_pcd::header::{DataFormat, PcdHeader};
 #[cfg(feature = "memmap2")]
 use rs_pcd::io::PcdReader;
-#[cfg(feature = "memmap2")]
-use rs_pcd::io::read_pcd_file;
 use std::io::Write;
 use tempfile::NamedTempFile;
 
@@ -60,3 +56,105 @@ fn test_large_file_binary_mmap_rayon() {
         assert_eq!(x_col[points - 1], (points - 1) as f32);
     }
 }
+
+#[cfg(unix)]
+#[test]
+fn test_positioned_reads() {
+    use rs_pcd::io::PcdReader;
+    use rs_pcd::io::positioned::PositionedReader;
+
+    let mut file = NamedTempFile::new().unwrap();
+    let points = 50_000;
+    write!(
+        file,
+        "VERSION .7\nFIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nCOUNT 1 1 1\nWIDTH {p}\nHEIGHT 1\nPOINTS {p}\nDATA binary\n",
+        p = points
+    )
+    .unwrap();
+    for i in 0..points {
+        let val = i as f32;
+        for v in [val, val * 2.0, val * 3.0] {
+            file.write_all(&v.to_le_bytes()).unwrap();
+        }
+    }
+    file.flush().unwrap();
+
+    let block = PcdReader::from_path_positioned(file.path())
+        .unwrap()
+        .read_all()
+        .unwrap();
+    assert_eq!(block.len, points);
+    assert_eq!(
+        block.get_column("z").unwrap().as_f32().unwrap()[points - 1],
+        (points - 1) as f32 * 3.0
+    );
+
+    // Small blocks make records straddle block boundaries.
+    let reader = PositionedReader::with_block_size(std::fs::File::open(file.path()).unwrap(), 1000);
+    let block = PcdReader::new(reader).unwrap().read_all().unwrap();
+    let y = block.get_column("y").unwrap().as_f32().unwrap();
+    assert_eq!(y[12_345], 24_690.0);
+}
+
+#[test]
+fn test_large_binary_write() {
+    use rs_pcd::ValueType;
+    use rs_pcd::header::{DataFormat, PcdHeaderBuilder};
+    use rs_pcd::io::PcdWriter;
+    use rs_pcd::storage::{Column, PointBlock};
+
+    // 32-byte records, so the points span several write buffers.
+    let points = 300_000;
+    let builder = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F64)
+        .add_field_with_count("n", ValueType::U16, 3)
+        .add_field("label", ValueType::I8)
+        .add_field("t", ValueType::U32)
+        .add_field("w", ValueType::F32)
+        .add_field("p", ValueType::I16)
+        .add_field("q", ValueType::I32)
+        .width(points as u32)
+        .data_format(DataFormat::Binary);
+    let schema = builder.schema();
+    let header = builder.build().unwrap();
+    let mut block = PointBlock::new(&schema, points);
+    let idx: Vec<usize> = (0..points).collect();
+    *block.get_column_mut("x").unwrap() =
+        Column::F64(idx.iter().map(|&i| i as f64 * 0.5).collect());
+    *block.get_column_mut("n").unwrap() = Column::U16((0..3 * points).map(|i| i as u16).collect());
+    *block.get_column_mut("label").unwrap() = Column::I8(idx.iter().map(|&i| i as i8).collect());
+    *block.get_column_mut("t").unwrap() = Column::U32(idx.iter().map(|&i| i as u32 * 3).collect());
+    *block.get_column_mut("w").unwrap() = Column::F32(idx.iter().map(|&i| -(i as f32)).collect());
+    *block.get_column_mut("p").unwrap() =
+        Column::I16(idx.iter().map(|&i| (i as i16).wrapping_neg()).collect());
+    *block.get_column_mut("q").unwrap() = Column::I32(idx.iter().map(|&i| -(i as i32)).collect());
+
+    let mut out = Vec::new();
+    PcdWriter::new(&mut out).write_pcd(&header, &block).unwrap();
+
+    let mut expected = Vec::with_capacity(points * 32);
+    for i in 0..points {
+        expected.extend_from_slice(&(i as f64 * 0.5).to_le_bytes());
+        for k in 0..3 {
+            expected.extend_from_slice(&((3 * i + k) as u16).to_le_bytes());
+        }
+        expected.push(i as i8 as u8);
+        expected.extend_from_slice(&(i as u32 * 3).to_le_bytes());
+        expected.extend_from_slice(&(-(i as f32)).to_le_bytes());
+        expected.extend_from_slice(&((i as i16).wrapping_neg()).to_le_bytes());
+        expected.extend_from_slice(&(-(i as i32)).to_le_bytes());
+    }
+    assert!(out.ends_with(&expected));
+    assert!(out[..out.len() - expected.len()].ends_with(b"DATA binary\n"));
+
+    // Too few values for the header's points.
+    block.get_column_mut("q").unwrap().resize(points - 1);
+    let err = PcdWriter::new(Vec::new())
+        .write_pcd(&header, &block)
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("Column q has 299999 values"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,245 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rs_pcd::header::{PcdHeaderBuilder, ValueType};
+use rs_pcd::projection::{
+    CameraIntrinsics, Image, RangeProjection, color_image, depth_image, project_pinhole,
+    to_range_image,
+};
+use rs_pcd::storage::rgb::pack_color;
+use rs_pcd::storage::{PointBlock, PointCloud};
+
+/// A sweep of `rings` rings from 2° up to -13° down, `per_ring` points each
+/// at 10 m, with F32 `x`, `y`, `z`, `intensity` (the ring) and U16 `ring`.
+fn sweep(rings: usize, per_ring: usize) -> PointBlock {
+    let schema = [
+        ("x".to_string(), ValueType::F32),
+        ("y".to_string(), ValueType::F32),
+        ("z".to_string(), ValueType::F32),
+        ("intensity".to_string(), ValueType::F32),
+        ("ring".to_string(), ValueType::U16),
+    ];
+    let n = rings * per_ring;
+    let mut block = PointBlock::new(&schema, n);
+    let (mut x, mut y, mut z, mut intensity, mut ring) = (vec![], vec![], vec![], vec![], vec![]);
+    for r in 0..rings {
+        let elevation = (2.0 - r as f64).to_radians();
+        for c in 0..per_ring {
+            let azimuth = std::f64::consts::PI * (1.0 - 2.0 * (c as f64 + 0.5) / per_ring as f64);
+            x.push((10.0 * elevation.cos() * azimuth.cos()) as f32);
+            y.push((10.0 * elevation.cos() * azimuth.sin()) as f32);
+            z.push((10.0 * elevation.sin()) as f32);
+            intensity.push(r as f32);
+            ring.push(r as u16);
+        }
+    }
+    block
+        .get_column_mut("x")
+        .unwrap()
+        .as_f32_mut()
+        .unwrap()
+        .copy_from_slice(&x);
+    block
+        .get_column_mut("y")
+        .unwrap()
+        .as_f32_mut()
+        .unwrap()
+        .copy_from_slice(&y);
+    block
+        .get_column_mut("z")
+        .unwrap()
+        .as_f32_mut()
+        .unwrap()
+        .copy_from_slice(&z);
+    block
+        .get_column_mut("intensity")
+        .unwrap()
+        .as_f32_mut()
+        .unwrap()
+        .copy_from_slice(&intensity);
+    block
+        .get_column_mut("ring")
+        .unwrap()
+        .as_u16_mut()
+        .unwrap()
+        .copy_from_slice(&ring);
+    block
+}
+
+#[test]
+fn test_range_image() {
+    let block = sweep(16, 360);
+
+    // One row per ring, one column per point: every pixel is filled.
+    let image = to_range_image(&block, &RangeProjection::rings(360, 16)).unwrap();
+    assert_eq!((image.width, image.height), (360, 16));
+    assert!(image.range.iter().all(|&r| (r - 10.0).abs() < 1e-4));
+    let intensity = image.intensity.as_ref().unwrap();
+    for row in 0..16 {
+        assert_eq!(intensity[row * 360 + 17], row as f32);
+        assert_eq!(image.indices[row * 360 + 17], Some(row * 360 + 17));
+        assert!((image.row_elevations[row] - (2.0 - row as f64).to_radians()).abs() < 1e-6);
+    }
+
+    // The same rows by elevation, 1° each centred on the rings.
+    let fov = RangeProjection::fov(360, 16, 2.5f64.to_radians(), (-13.5f64).to_radians());
+    let by_fov = fov.project(&block).unwrap();
+    assert_eq!(by_fov.indices, image.indices);
+
+    // Back to points, which land where they came from.
+    let points = image.to_block();
+    assert_eq!(points.len, 16 * 360);
+    let (xs, ys, zs) = (
+        points.get_column("x").unwrap().as_f32().unwrap(),
+        points.get_column("y").unwrap().as_f32().unwrap(),
+        points.get_column("z").unwrap().as_f32().unwrap(),
+    );
+    let original = block.get_column("x").unwrap().as_f32().unwrap();
+    for i in 0..points.len {
+        assert!((xs[i] - original[i]).abs() < 1e-3);
+        assert!((xs[i].powi(2) + ys[i].powi(2) + zs[i].powi(2)).sqrt() - 10.0 < 1e-3);
+    }
+    assert_eq!(
+        points.get_column("intensity").unwrap().as_f32().unwrap()[360],
+        1.0
+    );
+
+    // A coarser image keeps the nearest point of each pixel and leaves
+    // rings beyond its height out.
+    let mut closer = block.clone();
+    for name in ["x", "y", "z"] {
+        closer.get_column_mut(name).unwrap().as_f32_mut().unwrap()[1] *= 0.5;
+    }
+    let small = to_range_image(&closer, &RangeProjection::rings(180, 8)).unwrap();
+    assert!(small.range[0] < 10.0 && small.indices[0] == Some(1));
+    assert_eq!(small.indices.iter().flatten().count(), 8 * 180);
+
+    // Without rings or with an empty field of view.
+    closer.remove_column("ring");
+    assert!(to_range_image(&closer, &RangeProjection::rings(180, 8)).is_err());
+    assert!(to_range_image(&block, &RangeProjection::fov(180, 8, 0.0, 0.1)).is_err());
+    assert!(to_range_image(&block, &RangeProjection::fov(0, 8, 0.1, 0.0)).is_err());
+}
+
+#[test]
+fn test_depth_and_color_images() {
+    // A 4 x 3 RGB-D frame at depth 1 + row, with a hole at row 1, column 2.
+    let header = PcdHeaderBuilder::xyzrgb()
+        .width(4)
+        .height(3)
+        .build()
+        .unwrap();
+    let mut block = PointBlock::new(header.schema().unwrap(), 12);
+    for i in 0..12 {
+        let (row, column) = (i / 4, i % 4);
+        block.get_column_mut("x").unwrap().as_f32_mut().unwrap()[i] = column as f32;
+        block.get_column_mut("y").unwrap().as_f32_mut().unwrap()[i] = row as f32;
+        block.get_column_mut("z").unwrap().as_f32_mut().unwrap()[i] = 1.0 + row as f32;
+        block.get_column_mut("rgb").unwrap().as_u32_mut().unwrap()[i] =
+            pack_color(10 * i as u8, 1, 2, 0);
+    }
+    block.get_column_mut("x").unwrap().as_f32_mut().unwrap()[6] = f32::NAN;
+    let cloud = PointCloud::new(header, block);
+
+    let depth = depth_image(&cloud).unwrap();
+    assert_eq!((depth.width, depth.height), (4, 3));
+    assert_eq!(depth.get(2, 3), Some(&3.0));
+    assert!(depth.get(1, 2).unwrap().is_nan());
+    assert_eq!(depth.pixels.iter().filter(|d| d.is_nan()).count(), 1);
+    assert_eq!(depth.get(3, 0), None);
+
+    let color = color_image(&cloud).unwrap();
+    assert_eq!(color.get(2, 1), Some(&[90, 1, 2]));
+    assert_eq!(color.get(1, 2), Some(&[0, 0, 0]));
+
+    // The same colors from U8 channels.
+    let mut unpacked = cloud.clone();
+    unpacked.data.unpack_rgb().unwrap();
+    assert_eq!(color_image(&unpacked).unwrap(), color);
+
+    // Unorganized clouds, mismatched sizes and missing colors fail.
+    let mut flat = cloud.clone();
+    flat.header.width = 12;
+    flat.header.height = 1;
+    assert!(depth_image(&flat).is_err());
+    let mut short = cloud.clone();
+    short.data.resize(11);
+    assert!(depth_image(&short).is_err());
+    unpacked.data.remove_column("g");
+    assert!(color_image(&unpacked).is_err());
+}
+
+#[test]
+fn test_project_pinhole() {
+    // LiDAR points in front (+x), behind and off to the side of a camera
+    // looking along the LiDAR's x axis: camera x = -y, y = -z, z = x.
+    let extrinsics = [
+        [0.0, -1.0, 0.0, 0.0],
+        [0.0, 0.0, -1.0, 0.0],
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ];
+    let intrinsics = CameraIntrinsics::new(100.0, 100.0, 320.0, 240.0, 640, 480);
+    let points = [
+        [10.0, 0.0, 0.0],
+        [5.0, 0.0, 0.0],
+        [10.0, -1.0, 1.0],
+        [-10.0, 0.0, 0.0],
+        [1.0, 100.0, 0.0],
+        [f32::NAN, 0.0, 0.0],
+    ];
+    let schema = [
+        ("x".to_string(), ValueType::F32),
+        ("y".to_string(), ValueType::F32),
+        ("z".to_string(), ValueType::F32),
+    ];
+    let mut block = PointBlock::new(&schema, points.len());
+    for (axis, name) in ["x", "y", "z"].iter().enumerate() {
+        let column = block.get_column_mut(name).unwrap().as_f32_mut().unwrap();
+        for (i, p) in points.iter().enumerate() {
+            column[i] = p[axis];
+        }
+    }
+
+    let projection = project_pinhole(&block, &intrinsics, &extrinsics).unwrap();
+    assert_eq!(projection.pixels[0], Some([320.0, 240.0]));
+    assert_eq!(projection.pixels[1], Some([320.0, 240.0]));
+    assert_eq!(projection.pixels[2], Some([330.0, 230.0]));
+    assert_eq!(projection.pixels[3..], [None, None, None]);
+
+    // The nearer of the two points on the centre pixel wins.
+    assert_eq!(projection.depth.get(240, 320), Some(&5.0));
+    assert_eq!(projection.indices.get(240, 320), Some(&Some(1)));
+    assert_eq!(projection.depth.get(230, 330), Some(&10.0));
+    assert_eq!(
+        projection
+            .depth
+            .pixels
+            .iter()
+            .filter(|d| !d.is_nan())
+            .count(),
+        2
+    );
+
+    // Painting from a camera image.
+    let mut image = Image::from_pixels(640, 480, vec![[0u8; 3]; 640 * 480]);
+    image.pixels[230 * 640 + 330] = [255, 0, 0];
+    let colors = projection.sample(&image);
+    assert_eq!(colors[2], Some([255, 0, 0]));
+    assert_eq!(colors[0], Some([0, 0, 0]));
+    assert_eq!(colors[3], None);
+
+    block.remove_column("z");
+    assert!(project_pinhole(&block, &intrinsics, &extrinsics).is_err());
+}
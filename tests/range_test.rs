@@ -0,0 +1,167 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rs_pcd::header::{DataFormat, PcdHeaderBuilder, ValueType};
+use rs_pcd::io::range::{HttpRangeSource, RangeReader};
+use rs_pcd::io::{PcdWriter, write_pcd_file};
+use rs_pcd::storage::PointBlock;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+fn cloud(n: usize, format: DataFormat) -> (rs_pcd::PcdHeader, PointBlock) {
+    let header = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .add_field("y", ValueType::F32)
+        .add_field("ring", ValueType::U16)
+        .width(n as u32)
+        .data_format(format)
+        .build()
+        .unwrap();
+    let schema = [
+        ("x".to_string(), ValueType::F32),
+        ("y".to_string(), ValueType::F32),
+        ("ring".to_string(), ValueType::U16),
+    ];
+    let mut block = PointBlock::new(&schema, n);
+    for i in 0..n {
+        block.get_column_mut("x").unwrap().as_f32_mut().unwrap()[i] = i as f32;
+        block.get_column_mut("y").unwrap().as_f32_mut().unwrap()[i] = -(i as f32);
+        block.get_column_mut("ring").unwrap().as_u16_mut().unwrap()[i] = (i % 16) as u16;
+    }
+    (header, block)
+}
+
+/// Serve `body` with Range support, recording the byte count of each reply.
+fn serve(body: Vec<u8>) -> (String, Arc<Mutex<Vec<usize>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/cloud.pcd", listener.local_addr().unwrap());
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let log = sent.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut range = None;
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some(spec) = line.strip_prefix("Range: bytes=") {
+                    let (a, b) = spec.trim().split_once('-').unwrap();
+                    range = Some((a.parse::<usize>().unwrap(), b.parse::<usize>().unwrap()));
+                }
+            }
+            let (a, b) = range.unwrap();
+            let b = b.min(body.len() - 1);
+            let part = &body[a..=b];
+            log.lock().unwrap().push(part.len());
+            write!(
+                stream,
+                "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}-{}/{}\r\nContent-Length: {}\r\n\r\n",
+                a,
+                b,
+                body.len(),
+                part.len()
+            )
+            .unwrap();
+            stream.write_all(part).unwrap();
+        }
+    });
+    (url, sent)
+}
+
+#[test]
+fn test_http_point_range_projection() {
+    let (header, block) = cloud(100_000, DataFormat::Binary);
+    let mut body = Vec::new();
+    PcdWriter::new(&mut body)
+        .write_pcd(&header, &block)
+        .unwrap();
+    let total = body.len();
+    let (url, sent) = serve(body);
+
+    let mut reader = RangeReader::new(HttpRangeSource::new(&url).unwrap()).unwrap();
+    assert_eq!(reader.header().points, 100_000);
+    let part = reader.read(50_000..50_010, Some(&["ring", "x"])).unwrap();
+    assert_eq!(part.len, 10);
+    assert_eq!(part.schema().names().collect::<Vec<_>>(), ["ring", "x"]);
+    assert_eq!(part.get_column("x").unwrap().as_f32().unwrap()[3], 50_003.0);
+    assert_eq!(
+        part.get_column("ring").unwrap().as_u16().unwrap()[0],
+        50_000 % 16
+    );
+
+    let fetched: usize = sent.lock().unwrap().iter().sum();
+    assert!(fetched < 10_000, "fetched {} of {} bytes", fetched, total);
+    assert_eq!(*sent.lock().unwrap().last().unwrap(), 10 * 10);
+}
+
+#[test]
+fn test_file_range_compressed() {
+    let (header, block) = cloud(1000, DataFormat::BinaryCompressed);
+    let file = tempfile::NamedTempFile::new().unwrap();
+    write_pcd_file(file.path(), &header, &block).unwrap();
+
+    let mut reader = RangeReader::new(std::fs::File::open(file.path()).unwrap()).unwrap();
+    let part = reader.read(990..1000, Some(&["y"])).unwrap();
+    assert_eq!(part.num_columns(), 1);
+    assert_eq!(part.get_column("y").unwrap().as_f32().unwrap()[9], -999.0);
+    assert!(reader.read(990..1001, None).is_err());
+    assert!(reader.read_all(Some(&["z"])).is_err());
+}
+
+#[test]
+fn test_http_rejects_https() {
+    assert!(HttpRangeSource::new("https://example.com/a.pcd").is_err());
+}
+
+#[test]
+fn test_range_reader_checks_sizes() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let header = |points: u64, format: &str| {
+        format!(
+            "VERSION .7\nFIELDS x y z w\nSIZE 4 4 4 4\nTYPE F F F F\nCOUNT 1 1 1 1\n\
+             WIDTH {points}\nHEIGHT 1\nVIEWPOINT 0 0 0 1 0 0 0\nPOINTS {points}\nDATA {format}\n"
+        )
+    };
+
+    // POINTS far beyond the data section.
+    std::fs::write(file.path(), header(1 << 60, "binary")).unwrap();
+    assert!(RangeReader::new(std::fs::File::open(file.path()).unwrap()).is_err());
+
+    let mut body = header(2, "ascii").into_bytes();
+    body.extend(b"1 2 3 4\n5 6 7 8\n");
+    std::fs::write(file.path(), &body).unwrap();
+    let mut reader = RangeReader::new(std::fs::File::open(file.path()).unwrap())
+        .unwrap()
+        .memory_limit(16);
+    assert!(matches!(
+        reader.read_all(None),
+        Err(rs_pcd::PcdError::TooLarge { .. })
+    ));
+
+    let (header, block) = cloud(100, DataFormat::Binary);
+    write_pcd_file(file.path(), &header, &block).unwrap();
+    let mut reader = RangeReader::new(std::fs::File::open(file.path()).unwrap())
+        .unwrap()
+        .memory_limit(100);
+    assert_eq!(reader.read(0..10, None).unwrap().len, 10);
+    assert!(matches!(
+        reader.read(0..11, None),
+        Err(rs_pcd::PcdError::TooLarge { .. })
+    ));
+}
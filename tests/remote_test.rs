@@ -0,0 +1,114 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "object_store")]
+
+use object_store::memory::InMemory;
+use object_store::path::Path;
+use object_store::{ObjectStore, PutPayload};
+use rs_pcd::header::{DataFormat, PcdHeaderBuilder, ValueType};
+use rs_pcd::io::PcdWriter;
+use rs_pcd::io::remote::{read_object, read_object_header};
+use rs_pcd::storage::PointBlock;
+
+async fn put_cloud(store: &InMemory, name: &str, n: usize, format: DataFormat) -> Path {
+    let header = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .add_field("label", ValueType::U32)
+        .width(n as u32)
+        .data_format(format)
+        .build()
+        .unwrap();
+    let schema = [
+        ("x".to_string(), ValueType::F32),
+        ("label".to_string(), ValueType::U32),
+    ];
+    let mut block = PointBlock::new(&schema, n);
+    for i in 0..n {
+        block.get_column_mut("x").unwrap().as_f32_mut().unwrap()[i] = i as f32;
+        block.get_column_mut("label").unwrap().as_u32_mut().unwrap()[i] = 7;
+    }
+    let mut data = Vec::new();
+    PcdWriter::new(&mut data)
+        .write_pcd(&header, &block)
+        .unwrap();
+    // Trailing bytes must not be requested or decoded.
+    data.extend_from_slice(b"trailer");
+
+    let path = Path::from(name);
+    store.put(&path, PutPayload::from(data)).await.unwrap();
+    path
+}
+
+#[tokio::test]
+async fn test_read_object_formats() {
+    let store = InMemory::new();
+    for (i, format) in [
+        DataFormat::Binary,
+        DataFormat::BinaryCompressed,
+        DataFormat::Ascii,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let path = put_cloud(&store, &format!("scans/{}.pcd", i), 2000, format).await;
+        let header = read_object_header(&store, &path).await.unwrap();
+        assert_eq!(header.points, 2000);
+        let block = read_object(&store, &path).await.unwrap();
+        assert_eq!(block.len, 2000);
+        assert_eq!(
+            block.get_column("x").unwrap().as_f32().unwrap()[1999],
+            1999.0
+        );
+        assert_eq!(block.get_column("label").unwrap().as_u32().unwrap()[5], 7);
+    }
+}
+
+#[tokio::test]
+async fn test_read_object_missing() {
+    let store = InMemory::new();
+    assert!(read_object(&store, &Path::from("nope.pcd")).await.is_err());
+}
+
+#[tokio::test]
+async fn test_read_object_checks_sizes() {
+    let store = InMemory::new();
+    let header = |points: u64, format: &str| {
+        format!(
+            "VERSION .7\nFIELDS x\nSIZE 4\nTYPE F\nCOUNT 1\nWIDTH {points}\nHEIGHT 1\n\
+             VIEWPOINT 0 0 0 1 0 0 0\nPOINTS {points}\nDATA {format}\n"
+        )
+        .into_bytes()
+    };
+
+    // POINTS whose byte count overflows, and POINTS beyond the object.
+    for points in [1 << 62, 1000] {
+        let path = Path::from(format!("binary_{}.pcd", points));
+        let mut data = header(points, "binary");
+        data.extend([0; 16]);
+        store.put(&path, PutPayload::from(data)).await.unwrap();
+        assert!(read_object(&store, &path).await.is_err());
+    }
+
+    // A compressed size beyond the object.
+    let path = Path::from("compressed.pcd");
+    let mut data = header(4, "binary_compressed");
+    data.extend(u32::MAX.to_le_bytes());
+    data.extend(16u32.to_le_bytes());
+    store.put(&path, PutPayload::from(data)).await.unwrap();
+    assert!(matches!(
+        read_object(&store, &path).await,
+        Err(rs_pcd::PcdError::InvalidDataFormat(_))
+    ));
+}
@@ -0,0 +1,584 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "ros")]
+
+use rs_pcd::PcdError;
+use rs_pcd::header::{Schema, ValueType};
+use rs_pcd::ros::{PointCloud2, PointField};
+use rs_pcd::storage::PointBlock;
+
+fn field(name: &str, offset: u32, datatype: u8) -> PointField {
+    PointField {
+        name: name.to_string(),
+        offset,
+        datatype,
+        count: 1,
+    }
+}
+
+#[test]
+fn test_pointcloud2_padded_layout() {
+    // x y z at 0/4/8, 4 bytes of padding, intensity at 16, ring at 20,
+    // point_step 32, and 8 bytes of row padding.
+    let mut data = Vec::new();
+    for row in 0..2u16 {
+        for col in 0..3u16 {
+            let mut rec = [0u8; 32];
+            let i = (row * 3 + col) as f32;
+            rec[0..4].copy_from_slice(&i.to_le_bytes());
+            rec[4..8].copy_from_slice(&(i * 2.0).to_le_bytes());
+            rec[8..12].copy_from_slice(&(-i).to_le_bytes());
+            rec[16..20].copy_from_slice(&(i * 10.0).to_le_bytes());
+            rec[20..22].copy_from_slice(&row.to_le_bytes());
+            data.extend_from_slice(&rec);
+        }
+        data.extend_from_slice(&[0xAA; 8]);
+    }
+    let msg = PointCloud2 {
+        height: 2,
+        width: 3,
+        fields: vec![
+            field("ring", 20, PointField::UINT16),
+            field("x", 0, PointField::FLOAT32),
+            field("y", 4, PointField::FLOAT32),
+            field("z", 8, PointField::FLOAT32),
+            field("intensity", 16, PointField::FLOAT32),
+        ],
+        point_step: 32,
+        row_step: 3 * 32 + 8,
+        data,
+        is_dense: true,
+        ..Default::default()
+    };
+
+    let block = PointBlock::from_pointcloud2(&msg).unwrap();
+    assert_eq!(block.len, 6);
+    assert_eq!(
+        block.schema().names().collect::<Vec<_>>(),
+        &["x", "y", "z", "intensity", "ring"]
+    );
+    let (x, y, z, i, ring) = block.xyzir().unwrap();
+    assert_eq!(x, &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+    assert_eq!(y[5], 10.0);
+    assert_eq!(z[4], -4.0);
+    assert_eq!(i[3], 30.0);
+    assert_eq!(ring, &[0, 0, 0, 1, 1, 1]);
+}
+
+#[test]
+fn test_pointcloud2_roundtrip() {
+    let schema = vec![
+        ("x".to_string(), ValueType::F32),
+        ("y".to_string(), ValueType::F32),
+        ("z".to_string(), ValueType::F32),
+        ("t".to_string(), ValueType::F64),
+    ];
+    let mut block = PointBlock::new(&schema, 4);
+    for i in 0..4 {
+        block.get_column_mut("x").unwrap().as_f32_mut().unwrap()[i] = i as f32;
+        block.get_column_mut("t").unwrap().as_f64_mut().unwrap()[i] = i as f64 * 0.1;
+    }
+    block.get_column_mut("z").unwrap().as_f32_mut().unwrap()[2] = f32::NAN;
+
+    let msg = block.to_pointcloud2().unwrap();
+    assert_eq!(msg.point_step, 20);
+    assert_eq!(msg.row_step, 80);
+    assert_eq!(msg.fields[3].datatype, PointField::FLOAT64);
+    assert!(!msg.is_dense);
+
+    let back = PointBlock::from_pointcloud2(&msg).unwrap();
+    assert_eq!(
+        back.get_column("x").unwrap().as_f32().unwrap(),
+        &[0.0, 1.0, 2.0, 3.0]
+    );
+    assert_eq!(
+        back.get_column("t").unwrap().as_f64().unwrap()[3],
+        3.0 * 0.1
+    );
+    assert!(back.get_column("z").unwrap().as_f32().unwrap()[2].is_nan());
+}
+
+#[test]
+fn test_pointcloud2_multi_count_fields() {
+    let schema = Schema::new()
+        .with_field("x", ValueType::F64)
+        .with_field("y", ValueType::F64)
+        .with_field("z", ValueType::F64)
+        .with_field_count("hist", ValueType::U16, 3);
+    let mut block = PointBlock::new(schema, 2);
+    let hist = block.get_column_mut("hist").unwrap().as_u16_mut().unwrap();
+    hist.copy_from_slice(&[1, 2, 3, 4, 5, 6]);
+    block.get_column_mut("x").unwrap().as_f64_mut().unwrap()[1] = f64::NAN;
+
+    let msg = block.to_pointcloud2().unwrap();
+    assert_eq!(msg.fields[3].count, 3);
+    assert_eq!((msg.point_step, msg.row_step), (30, 60));
+    assert!(!msg.is_dense);
+    let back = PointBlock::from_pointcloud2(&msg).unwrap();
+    assert_eq!(back.to_schema(), block.to_schema());
+    assert_eq!(
+        back.get_column("hist").unwrap().as_u16().unwrap(),
+        &[1, 2, 3, 4, 5, 6]
+    );
+    assert!(matches!(
+        msg.column::<u16>("hist"),
+        Err(PcdError::UnsupportedDataFormat(_))
+    ));
+
+    // Big-endian elements are swapped one by one.
+    let mut data = Vec::new();
+    for v in [7u16, 8] {
+        data.extend_from_slice(&v.to_be_bytes());
+    }
+    let msg = PointCloud2 {
+        height: 1,
+        width: 1,
+        fields: vec![PointField {
+            count: 2,
+            ..field("hist", 0, PointField::UINT16)
+        }],
+        is_bigendian: true,
+        point_step: 4,
+        row_step: 4,
+        data,
+        ..Default::default()
+    };
+    let back = PointBlock::from_pointcloud2(&msg).unwrap();
+    assert_eq!(back.get_column("hist").unwrap().as_u16().unwrap(), &[7, 8]);
+}
+
+#[test]
+fn test_pointcloud2_column_view() {
+    let mut data = Vec::new();
+    for i in 0..4u16 {
+        data.extend_from_slice(&(i as f32).to_be_bytes());
+        data.extend_from_slice(&(i * 100).to_be_bytes());
+        data.extend_from_slice(&[0; 2]);
+    }
+    let mut msg = PointCloud2 {
+        height: 2,
+        width: 2,
+        fields: vec![
+            field("x", 0, PointField::FLOAT32),
+            field("label", 4, PointField::UINT16),
+        ],
+        is_bigendian: true,
+        point_step: 8,
+        row_step: 16,
+        data,
+        ..Default::default()
+    };
+
+    let x = msg.column::<f32>("x").unwrap();
+    assert_eq!(x.len(), 4);
+    assert_eq!(x.iter().collect::<Vec<_>>(), [0.0, 1.0, 2.0, 3.0]);
+    let label = msg.column::<u16>("label").unwrap();
+    assert_eq!(label.get(3), Some(300));
+    assert_eq!(label.get(4), None);
+    assert_eq!(label.iter().next_back(), Some(300));
+
+    assert!(matches!(
+        msg.column::<f64>("x"),
+        Err(PcdError::FieldType { .. })
+    ));
+    assert!(matches!(
+        msg.column::<f32>("y"),
+        Err(PcdError::MissingField { .. })
+    ));
+
+    msg.row_step = 20;
+    assert!(matches!(
+        msg.column::<f32>("x"),
+        Err(PcdError::UnsupportedDataFormat(_))
+    ));
+    msg.row_step = 16;
+    msg.data.truncate(26);
+    assert!(matches!(
+        msg.column::<f32>("x"),
+        Err(PcdError::BufferTooSmall { .. })
+    ));
+}
+
+#[test]
+fn test_pointcloud2_short_data() {
+    let msg = PointCloud2 {
+        height: 1,
+        width: 4,
+        fields: vec![field("x", 0, PointField::FLOAT32)],
+        point_step: 4,
+        row_step: 16,
+        data: vec![0; 12],
+        ..Default::default()
+    };
+    assert!(PointBlock::from_pointcloud2(&msg).is_err());
+
+    // Sizes whose products overflow fail instead of panicking.
+    let msg = PointCloud2 {
+        height: u32::MAX,
+        width: u32::MAX,
+        row_step: u32::MAX,
+        point_step: u32::MAX,
+        ..msg
+    };
+    assert!(PointBlock::from_pointcloud2(&msg).is_err());
+    assert!(msg.column::<f32>("x").is_err());
+}
+
+#[cfg(feature = "ros2")]
+#[test]
+fn test_ros2_bigendian_to_pcd() {
+    use rs_pcd::header::DataFormat;
+    use rs_pcd::io::{PcdReader, PcdWriter};
+    use rs_pcd::ros::ros2;
+
+    // Big-endian x y z with a 4-byte gap before a u16 ring, point_step 20.
+    let mut data = Vec::new();
+    for i in 0..3u16 {
+        data.extend_from_slice(&(i as f32).to_be_bytes());
+        data.extend_from_slice(&(i as f32 + 0.5).to_be_bytes());
+        data.extend_from_slice(&f32::NAN.to_be_bytes());
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&(i + 7).to_be_bytes());
+        data.extend_from_slice(&[0; 2]);
+    }
+    let msg = ros2::PointCloud2 {
+        height: 1,
+        width: 3,
+        fields: vec![
+            field("x", 0, PointField::FLOAT32),
+            field("y", 4, PointField::FLOAT32),
+            field("z", 8, PointField::FLOAT32),
+            field("ring", 16, PointField::UINT16),
+        ],
+        is_bigendian: true,
+        point_step: 20,
+        row_step: 60,
+        data,
+        is_dense: false,
+        ..Default::default()
+    };
+
+    let block = PointBlock::from_ros2_pointcloud2(&msg).unwrap();
+    assert_eq!(
+        block.get_column("y").unwrap().as_f32().unwrap(),
+        &[0.5, 1.5, 2.5]
+    );
+    assert_eq!(
+        block.get_column("ring").unwrap().as_u16().unwrap(),
+        &[7, 8, 9]
+    );
+    assert!(block.get_column("z").unwrap().as_f32().unwrap()[1].is_nan());
+    assert!(!block.to_ros2_pointcloud2().unwrap().is_dense);
+
+    let header = msg.pcd_header(DataFormat::BinaryCompressed).unwrap();
+    let mut buf = Vec::new();
+    PcdWriter::new(&mut buf).write_pcd(&header, &block).unwrap();
+    let back = PcdReader::from_bytes(&buf).unwrap().read_all().unwrap();
+    assert_eq!(
+        back.get_column("ring").unwrap().as_u16().unwrap(),
+        &[7, 8, 9]
+    );
+}
+
+#[cfg(any(feature = "rosbag", feature = "mcap"))]
+fn cloud_with_x(xs: &[f32]) -> PointBlock {
+    let schema = vec![
+        ("x".to_string(), ValueType::F32),
+        ("y".to_string(), ValueType::F32),
+        ("z".to_string(), ValueType::F32),
+    ];
+    let mut block = PointBlock::new(&schema, xs.len());
+    block
+        .get_column_mut("x")
+        .unwrap()
+        .as_f32_mut()
+        .unwrap()
+        .copy_from_slice(xs);
+    block
+}
+
+#[cfg(feature = "rosbag")]
+fn bag_record(fields: &[(&str, &[u8])], data: &[u8]) -> Vec<u8> {
+    let mut header = Vec::new();
+    for (name, value) in fields {
+        header.extend_from_slice(&((name.len() + 1 + value.len()) as u32).to_le_bytes());
+        header.extend_from_slice(name.as_bytes());
+        header.push(b'=');
+        header.extend_from_slice(value);
+    }
+    let mut out = Vec::new();
+    out.extend_from_slice(&(header.len() as u32).to_le_bytes());
+    out.extend_from_slice(&header);
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(data);
+    out
+}
+
+#[cfg(feature = "rosbag")]
+fn ros1_serialize(msg: &PointCloud2) -> Vec<u8> {
+    fn string(out: &mut Vec<u8>, s: &str) {
+        out.extend_from_slice(&(s.len() as u32).to_le_bytes());
+        out.extend_from_slice(s.as_bytes());
+    }
+    let mut out = Vec::new();
+    out.extend_from_slice(&msg.header.seq.to_le_bytes());
+    out.extend_from_slice(&msg.header.stamp_sec.to_le_bytes());
+    out.extend_from_slice(&msg.header.stamp_nsec.to_le_bytes());
+    string(&mut out, &msg.header.frame_id);
+    out.extend_from_slice(&msg.height.to_le_bytes());
+    out.extend_from_slice(&msg.width.to_le_bytes());
+    out.extend_from_slice(&(msg.fields.len() as u32).to_le_bytes());
+    for f in &msg.fields {
+        string(&mut out, &f.name);
+        out.extend_from_slice(&f.offset.to_le_bytes());
+        out.push(f.datatype);
+        out.extend_from_slice(&f.count.to_le_bytes());
+    }
+    out.push(msg.is_bigendian as u8);
+    out.extend_from_slice(&msg.point_step.to_le_bytes());
+    out.extend_from_slice(&msg.row_step.to_le_bytes());
+    out.extend_from_slice(&(msg.data.len() as u32).to_le_bytes());
+    out.extend_from_slice(&msg.data);
+    out.push(msg.is_dense as u8);
+    out
+}
+
+#[cfg(feature = "rosbag")]
+#[test]
+fn test_rosbag_extract_frames() {
+    use rs_pcd::header::DataFormat;
+    use rs_pcd::ros::{RosbagReader, write_frames};
+
+    let conn = |id: u32, topic: &str, ty: &str| {
+        let conn_header = bag_record(&[("topic", topic.as_bytes()), ("type", ty.as_bytes())], &[]);
+        // The connection header is the record header part only.
+        let conn_header = &conn_header[4..conn_header.len() - 4];
+        bag_record(
+            &[
+                ("op", &[0x07]),
+                ("conn", &id.to_le_bytes()),
+                ("topic", topic.as_bytes()),
+            ],
+            conn_header,
+        )
+    };
+    let message =
+        |id: u32, data: &[u8]| bag_record(&[("op", &[0x02]), ("conn", &id.to_le_bytes())], data);
+
+    let mut chunk = Vec::new();
+    chunk.extend(conn(0, "/points", "sensor_msgs/PointCloud2"));
+    chunk.extend(conn(1, "/imu", "sensor_msgs/Imu"));
+    chunk.extend(conn(2, "/other_points", "sensor_msgs/PointCloud2"));
+    for (i, id) in [0u32, 1, 2, 0].into_iter().enumerate() {
+        let mut msg = cloud_with_x(&[i as f32; 2]).to_pointcloud2().unwrap();
+        msg.header.stamp_sec = 100 + i as u32;
+        chunk.extend(message(id, &ros1_serialize(&msg)));
+    }
+
+    let mut bag = b"#ROSBAG V2.0\n".to_vec();
+    bag.extend(bag_record(&[("op", &[0x03])], &[0; 8]));
+    bag.extend(bag_record(
+        &[("op", &[0x05]), ("compression", b"none")],
+        &chunk,
+    ));
+
+    let frames: Vec<_> = RosbagReader::new(&bag[..])
+        .unwrap()
+        .with_topic("/points")
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[1].stamp_sec, 103);
+    assert_eq!(
+        frames[1].block.get_column("x").unwrap().as_f32().unwrap(),
+        &[3.0; 2]
+    );
+
+    let dir = tempfile::tempdir().unwrap();
+    let reader = RosbagReader::new(&bag[..]).unwrap();
+    assert_eq!(
+        write_frames(reader, dir.path(), DataFormat::Binary).unwrap(),
+        3
+    );
+    let block = rs_pcd::io::read_pcd_file(dir.path().join("000001.pcd")).unwrap();
+    assert_eq!(block.get_column("x").unwrap().as_f32().unwrap(), &[2.0; 2]);
+}
+
+#[cfg(feature = "mcap")]
+#[test]
+fn test_mcap_extract_frames() {
+    use rs_pcd::ros::McapReader;
+
+    fn record(op: u8, body: &[u8]) -> Vec<u8> {
+        let mut out = vec![op];
+        out.extend_from_slice(&(body.len() as u64).to_le_bytes());
+        out.extend_from_slice(body);
+        out
+    }
+    fn string(out: &mut Vec<u8>, s: &str) {
+        out.extend_from_slice(&(s.len() as u32).to_le_bytes());
+        out.extend_from_slice(s.as_bytes());
+    }
+    // CDR little endian; alignment is relative to the byte after the header.
+    fn cdr(msg: &rs_pcd::ros::ros2::PointCloud2) -> Vec<u8> {
+        let mut b = Vec::new();
+        let align = |b: &mut Vec<u8>| {
+            while !b.len().is_multiple_of(4) {
+                b.push(0);
+            }
+        };
+        let u32_ = |b: &mut Vec<u8>, v: u32| {
+            align(b);
+            b.extend_from_slice(&v.to_le_bytes());
+        };
+        let string_ = |b: &mut Vec<u8>, s: &str| {
+            align(b);
+            b.extend_from_slice(&(s.len() as u32 + 1).to_le_bytes());
+            b.extend_from_slice(s.as_bytes());
+            b.push(0);
+        };
+        u32_(&mut b, msg.header.stamp.sec as u32);
+        u32_(&mut b, msg.header.stamp.nanosec);
+        string_(&mut b, &msg.header.frame_id);
+        u32_(&mut b, msg.height);
+        u32_(&mut b, msg.width);
+        u32_(&mut b, msg.fields.len() as u32);
+        for f in &msg.fields {
+            string_(&mut b, &f.name);
+            u32_(&mut b, f.offset);
+            b.push(f.datatype);
+            u32_(&mut b, f.count);
+        }
+        b.push(msg.is_bigendian as u8);
+        u32_(&mut b, msg.point_step);
+        u32_(&mut b, msg.row_step);
+        u32_(&mut b, msg.data.len() as u32);
+        b.extend_from_slice(&msg.data);
+        b.push(msg.is_dense as u8);
+        let mut out = vec![0x00, 0x01, 0x00, 0x00];
+        out.extend(b);
+        out
+    }
+
+    let mut schema = 1u16.to_le_bytes().to_vec();
+    string(&mut schema, "sensor_msgs/msg/PointCloud2");
+    string(&mut schema, "ros2msg");
+    schema.extend_from_slice(&0u32.to_le_bytes());
+    let mut channel = 7u16.to_le_bytes().to_vec();
+    channel.extend_from_slice(&1u16.to_le_bytes());
+    string(&mut channel, "/lidar");
+    string(&mut channel, "cdr");
+    channel.extend_from_slice(&0u32.to_le_bytes());
+
+    let mut inner = Vec::new();
+    inner.extend(record(0x03, &schema));
+    inner.extend(record(0x04, &channel));
+    for i in 0..2 {
+        let mut msg = cloud_with_x(&[i as f32, 5.0])
+            .to_ros2_pointcloud2()
+            .unwrap();
+        msg.header.frame_id = "lidar".to_string();
+        msg.header.stamp.sec = 10 + i;
+        let mut body = 7u16.to_le_bytes().to_vec();
+        body.extend_from_slice(&[0; 20]);
+        body.extend(cdr(&msg));
+        inner.extend(record(0x05, &body));
+    }
+    let mut chunk = vec![0; 28];
+    string(&mut chunk, "");
+    chunk.extend_from_slice(&(inner.len() as u64).to_le_bytes());
+    chunk.extend(inner);
+
+    let mut file = b"\x89MCAP0\r\n".to_vec();
+    let mut header = Vec::new();
+    string(&mut header, "ros2");
+    string(&mut header, "");
+    file.extend(record(0x01, &header));
+    file.extend(record(0x06, &chunk));
+    file.extend(record(0x02, &[0; 20]));
+    file.extend_from_slice(b"\x89MCAP0\r\n");
+
+    let frames: Vec<_> = McapReader::new(&file[..])
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[1].topic, "/lidar");
+    assert_eq!(frames[1].frame_id, "lidar");
+    assert_eq!(frames[1].stamp_sec, 11);
+    assert_eq!(
+        frames[1].block.get_column("x").unwrap().as_f32().unwrap(),
+        &[1.0, 5.0]
+    );
+}
+
+#[cfg(feature = "rosbag")]
+#[test]
+fn test_rosbag_rejects_bad_lengths() {
+    use rs_pcd::ros::RosbagReader;
+
+    // A record header claiming 4 GiB.
+    let mut bag = b"#ROSBAG V2.0\n".to_vec();
+    bag.extend(u32::MAX.to_le_bytes());
+    let mut frames = RosbagReader::new(&bag[..]).unwrap();
+    assert!(frames.next().unwrap().is_err());
+    assert!(frames.next().is_none());
+
+    // A chunk whose inner record runs past its end.
+    let mut chunk = 8u32.to_le_bytes().to_vec();
+    chunk.extend(u32::MAX.to_le_bytes());
+    let mut bag = b"#ROSBAG V2.0\n".to_vec();
+    bag.extend(bag_record(
+        &[("op", &[0x05]), ("compression", b"none")],
+        &chunk,
+    ));
+    let mut frames = RosbagReader::new(&bag[..]).unwrap();
+    assert!(frames.next().unwrap().is_err());
+}
+
+#[cfg(feature = "mcap")]
+#[test]
+fn test_mcap_rejects_bad_lengths() {
+    use rs_pcd::ros::McapReader;
+
+    let file = |op: u8, body: &[u8]| {
+        let mut file = b"\x89MCAP0\r\n".to_vec();
+        file.push(op);
+        file.extend((body.len() as u64).to_le_bytes());
+        file.extend(body);
+        file
+    };
+    let first = |file: Vec<u8>| McapReader::new(&file[..]).unwrap().next().unwrap();
+
+    // Chunk records of u64::MAX bytes.
+    let mut chunk = vec![0; 28];
+    chunk.extend(0u32.to_le_bytes());
+    chunk.extend(u64::MAX.to_le_bytes());
+    assert!(first(file(0x06, &chunk)).is_err());
+
+    // An inner record of u64::MAX bytes.
+    let mut inner = vec![0x05];
+    inner.extend(u64::MAX.to_le_bytes());
+    let mut chunk = vec![0; 28];
+    chunk.extend(0u32.to_le_bytes());
+    chunk.extend((inner.len() as u64).to_le_bytes());
+    chunk.extend(inner);
+    assert!(first(file(0x06, &chunk)).is_err());
+
+    // A schema name of u32::MAX bytes.
+    let mut schema = 1u16.to_le_bytes().to_vec();
+    schema.extend(u32::MAX.to_le_bytes());
+    assert!(first(file(0x03, &schema)).is_err());
+}
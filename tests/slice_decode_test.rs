@@ -0,0 +1,682 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rs_pcd::PcdError;
+use rs_pcd::decoder::decode_slice;
+use rs_pcd::header::{DataFormat, PcdHeaderBuilder, ValueType, parse_header_bytes};
+use rs_pcd::io::PcdWriter;
+use rs_pcd::layout::PcdLayout;
+use rs_pcd::storage::PointBlock;
+
+fn encode(n: usize, format: DataFormat) -> Vec<u8> {
+    let header = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .add_field("label", ValueType::U16)
+        .width(n as u32)
+        .data_format(format)
+        .build()
+        .unwrap();
+    let schema = [
+        ("x".to_string(), ValueType::F32),
+        ("label".to_string(), ValueType::U16),
+    ];
+    let mut block = PointBlock::new(&schema, n);
+    for i in 0..n {
+        block.get_column_mut("x").unwrap().as_f32_mut().unwrap()[i] = (i % 50) as f32;
+        block.get_column_mut("label").unwrap().as_u16_mut().unwrap()[i] = (i / 100) as u16;
+    }
+    let mut buf = Vec::new();
+    PcdWriter::new(&mut buf).write_pcd(&header, &block).unwrap();
+    buf
+}
+
+#[test]
+fn test_decode_slice_binary_and_compressed() {
+    for format in [DataFormat::Binary, DataFormat::BinaryCompressed] {
+        let bytes = encode(5000, format);
+        let (header, start) = parse_header_bytes(&bytes).unwrap();
+        assert_eq!(header.points, 5000);
+        assert_eq!(header.data, format);
+
+        let layout = PcdLayout::from_header(&header).unwrap();
+        let block = decode_slice(&bytes[start..], header.data, &layout, header.points).unwrap();
+        let x = block.get_column("x").unwrap().as_f32().unwrap();
+        let label = block.get_column("label").unwrap().as_u16().unwrap();
+        assert_eq!(x[4321], 21.0);
+        assert_eq!(label[4321], 43);
+    }
+}
+
+#[test]
+fn test_decode_slice_truncated() {
+    for format in [DataFormat::Binary, DataFormat::BinaryCompressed] {
+        let bytes = encode(100, format);
+        let (header, start) = parse_header_bytes(&bytes).unwrap();
+        let layout = PcdLayout::from_header(&header).unwrap();
+        let cut = &bytes[start..bytes.len() - 10];
+        assert!(matches!(
+            decode_slice(cut, header.data, &layout, header.points),
+            Err(PcdError::BufferTooSmall { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_parse_header_bytes_errors() {
+    assert!(matches!(
+        parse_header_bytes(b"VERSION 0.7\nFIELDS x\n"),
+        Err(PcdError::InvalidHeader { line: 2, .. })
+    ));
+    assert!(matches!(
+        parse_header_bytes(b"VERSION 0.7\nFIELDS \xff\n"),
+        Err(PcdError::InvalidHeader { line: 2, .. })
+    ));
+}
+
+#[test]
+fn test_decode_slice_padded_layout() {
+    // Records of x, 3 pad bytes, label and 2 more pad bytes.
+    let text = "FIELDS x _ label\nSIZE 4 1 2\nTYPE F U U\nCOUNT 1 3 1\nWIDTH 2\nHEIGHT 1\n\
+                POINTS 2\nDATA binary\n";
+    let mut data = Vec::new();
+    for (x, label) in [(1.5f32, 7u16), (-2.0, 9)] {
+        data.extend_from_slice(&x.to_le_bytes());
+        data.extend_from_slice(&[0xAA; 3]);
+        data.extend_from_slice(&label.to_le_bytes());
+        data.extend_from_slice(&[0xBB; 2]);
+    }
+    let (header, _) = parse_header_bytes(text.as_bytes()).unwrap();
+    let layout = PcdLayout::from_header(&header)
+        .unwrap()
+        .without_padding()
+        .with_stride(11)
+        .unwrap();
+    assert_eq!(layout.packed_size(), 6);
+    let block = decode_slice(&data, DataFormat::Binary, &layout, 2).unwrap();
+    assert_eq!(block.schema().names().collect::<Vec<_>>(), ["x", "label"]);
+    assert_eq!(
+        block.get_column("x").unwrap().as_f32().unwrap(),
+        [1.5, -2.0]
+    );
+    assert_eq!(block.get_column("label").unwrap().as_u16().unwrap(), [7, 9]);
+
+    // Compressed payloads have no padding, whatever the stride.
+    let bytes = encode(100, DataFormat::BinaryCompressed);
+    let (header, start) = parse_header_bytes(&bytes).unwrap();
+    let layout = PcdLayout::from_header(&header)
+        .unwrap()
+        .with_stride(16)
+        .unwrap();
+    let block = decode_slice(&bytes[start..], header.data, &layout, 100).unwrap();
+    assert_eq!(block.get_column("x").unwrap().as_f32().unwrap()[73], 23.0);
+}
+
+#[test]
+fn test_layout_new_rejects_bad_offsets() {
+    let (header, _) = parse_header_bytes(
+        b"FIELDS x y\nSIZE 4 4\nTYPE F F\nWIDTH 1\nHEIGHT 1\nPOINTS 1\nDATA binary\n",
+    )
+    .unwrap();
+    let mut fields = PcdLayout::from_header(&header).unwrap().fields;
+    assert!(
+        PcdLayout::from_header(&header)
+            .unwrap()
+            .with_stride(7)
+            .is_err()
+    );
+
+    fields[1].offset = 2;
+    assert!(PcdLayout::new(fields.clone(), 8).is_err());
+    fields[1].offset = 12;
+    assert!(PcdLayout::new(fields.clone(), 16).is_ok());
+    assert!(PcdLayout::new(fields, 14).is_err());
+}
+
+#[test]
+fn test_corrupt_data_location() {
+    // A binary stream ending inside the `label` of point 3.
+    let bytes = encode(5, DataFormat::Binary);
+    let (_, start) = parse_header_bytes(&bytes).unwrap();
+    let cut = &bytes[..start + 3 * 6 + 5];
+    let err = rs_pcd::io::PcdReader::new(cut)
+        .unwrap()
+        .read_all()
+        .unwrap_err();
+    assert!(matches!(
+        &err,
+        PcdError::Corrupt { offset, point: 3, field: Some(field), .. }
+            if *offset == (start + 23) as u64 && field == "label"
+    ));
+
+    // The first LZF instruction of a compressed block turned into a back
+    // reference, which has nothing to refer to.
+    let mut bytes = encode(100, DataFormat::BinaryCompressed);
+    let (_, start) = parse_header_bytes(&bytes).unwrap();
+    bytes[start + 8] = 0xE0;
+    let located = |err: PcdError| {
+        matches!(
+            &err,
+            PcdError::Corrupt { offset, point: 0, field: Some(field), msg }
+                if *offset == (start + 8) as u64 && field == "x" && msg == "DataCorrupted"
+        )
+    };
+    let reader = rs_pcd::io::PcdReader::from_bytes(&bytes).unwrap();
+    assert!(located(reader.read_all().unwrap_err()));
+    assert!(located(rs_pcd::io::read_pcd_bytes(&bytes).unwrap_err()));
+    let err = rs_pcd::io::read_pcd_bytes(&bytes).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Corrupt data at byte {} (point 0, field x): DataCorrupted",
+            start + 8
+        )
+    );
+}
+
+#[test]
+fn test_decode_slice_ascii() {
+    let layout = PcdLayout::from_header(
+        &PcdHeaderBuilder::new()
+            .add_field("x", ValueType::F32)
+            .add_field_with_count("n", ValueType::U16, 2)
+            .width(1)
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+    let mut text = String::new();
+    for i in 0..10_000 {
+        text.push_str(&format!("{}.5 {} {}\n", i, i % 100, i % 7));
+    }
+
+    // Trailing lines past the point count are not parsed.
+    let junk = format!("{}not a point\n", text);
+    let block = decode_slice(junk.as_bytes(), DataFormat::Ascii, &layout, 10_000).unwrap();
+    let x = block.get_column("x").unwrap().as_f32().unwrap();
+    let n = block.get_column("n").unwrap().as_u16().unwrap();
+    assert_eq!(x.len(), 10_000);
+    assert_eq!((x[0], x[9_999]), (0.5, 9_999.5));
+    assert_eq!(&n[2 * 7_654..2 * 7_655], [54, 3]);
+
+    // An unterminated last line still counts.
+    let block = decode_slice(
+        text.trim_end().as_bytes(),
+        DataFormat::Ascii,
+        &layout,
+        10_000,
+    )
+    .unwrap();
+    assert_eq!(
+        block.get_column("x").unwrap().as_f32().unwrap()[9_999],
+        9_999.5
+    );
+
+    let bad = text.replacen("6000.5 0 1", "6000.5 0 x", 1);
+    let err = decode_slice(bad.as_bytes(), DataFormat::Ascii, &layout, 10_000).unwrap_err();
+    assert!(err.to_string().contains("ASCII data line 6001:"), "{}", err);
+    // Of several bad lines, the first is reported.
+    let bad = bad.replacen("2000.5 0 5", "2000.5 x 5", 1);
+    let err = decode_slice(bad.as_bytes(), DataFormat::Ascii, &layout, 10_000).unwrap_err();
+    assert!(err.to_string().contains("ASCII data line 2001:"), "{}", err);
+
+    let err = decode_slice(text.as_bytes(), DataFormat::Ascii, &layout, 10_001).unwrap_err();
+    assert!(err.to_string().contains("at line 10001"), "{}", err);
+}
+
+#[test]
+fn test_decode_compressed_across_fields() {
+    // Repetitive fields, so liblzf back references run across field
+    // boundaries, and a 3-byte field that ends mid-pattern.
+    let layout = PcdLayout::from_header(
+        &PcdHeaderBuilder::new()
+            .add_field("x", ValueType::F32)
+            .add_field_with_count("rgb", ValueType::U8, 3)
+            .add_field("t", ValueType::F64)
+            .width(1)
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+    let points = 3_001;
+    let mut soa = Vec::new();
+    for i in 0..points {
+        soa.extend_from_slice(&((i % 5) as f32).to_le_bytes());
+    }
+    let rgb: Vec<u8> = (0..3 * points).map(|i| (i % 7) as u8).collect();
+    soa.extend_from_slice(&rgb);
+    for i in 0..points {
+        soa.extend_from_slice(&((i % 3) as f64).to_le_bytes());
+    }
+    let compressed = lzf::compress(&soa).unwrap();
+    let mut data = Vec::new();
+    data.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+    data.extend_from_slice(&(soa.len() as u32).to_le_bytes());
+    data.extend_from_slice(&compressed);
+
+    let block = decode_slice(&data, DataFormat::BinaryCompressed, &layout, points).unwrap();
+    let x = block.get_column("x").unwrap().as_f32().unwrap();
+    let t = block.get_column("t").unwrap().as_f64().unwrap();
+    assert!(x.iter().enumerate().all(|(i, &v)| v == (i % 5) as f32));
+    assert_eq!(block.get_column("rgb").unwrap().as_u8().unwrap(), rgb);
+    assert!(t.iter().enumerate().all(|(i, &v)| v == (i % 3) as f64));
+
+    // Data that stops short of the layout.
+    let short = lzf::compress(&soa[..soa.len() - 8]).unwrap();
+    let mut data = Vec::new();
+    data.extend_from_slice(&(short.len() as u32).to_le_bytes());
+    data.extend_from_slice(&(soa.len() as u32).to_le_bytes());
+    data.extend_from_slice(&short);
+    let err = decode_slice(&data, DataFormat::BinaryCompressed, &layout, points).unwrap_err();
+    assert!(
+        matches!(&err, PcdError::Corrupt { point: 3_000, field: Some(f), msg, .. }
+            if f == "t" && msg.starts_with("decompressed to")),
+        "{}",
+        err
+    );
+}
+
+#[cfg(feature = "memmap2")]
+#[test]
+fn test_mmap_compressed() {
+    use std::io::Write;
+
+    let mut bytes = encode(5000, DataFormat::BinaryCompressed);
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(&bytes).unwrap();
+    let block = rs_pcd::io::PcdReader::from_path_mmap(file.path())
+        .unwrap()
+        .read_all()
+        .unwrap();
+    assert_eq!(block.len, 5000);
+    assert_eq!(block.get_column("x").unwrap().as_f32().unwrap()[4321], 21.0);
+    assert_eq!(
+        block.get_column("label").unwrap().as_u16().unwrap()[4321],
+        43
+    );
+
+    // Errors found in the mapping still carry file offsets.
+    let (_, start) = parse_header_bytes(&bytes).unwrap();
+    bytes[start + 8] = 0xE0;
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(&bytes).unwrap();
+    let err = rs_pcd::io::PcdReader::from_path_mmap(file.path())
+        .unwrap()
+        .read_all()
+        .unwrap_err();
+    assert!(matches!(
+        &err,
+        PcdError::Corrupt { offset, point: 0, field: Some(field), .. }
+            if *offset == (start + 8) as u64 && field == "x"
+    ));
+}
+
+#[cfg(feature = "memmap2")]
+#[test]
+fn test_mmap_target_schema() {
+    use rs_pcd::header::Schema;
+    use std::io::Write;
+
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(&encode(5000, DataFormat::Binary)).unwrap();
+    let block = rs_pcd::io::PcdReader::from_path_mmap(file.path())
+        .unwrap()
+        .target_schema(Schema::new().with_field("label", ValueType::F32))
+        .read_all()
+        .unwrap();
+    assert_eq!(block.schema().names().collect::<Vec<_>>(), &["label"]);
+    assert_eq!(
+        block.get_column("label").unwrap().as_f32().unwrap()[4321],
+        43.0
+    );
+}
+
+#[test]
+fn test_strided_column_view() {
+    use rs_pcd::storage::StridedColumnView;
+
+    // Three 6-byte records, a u16 at offset 4 of each.
+    let data = [0, 0, 0, 0, 1, 0, 9, 9, 9, 9, 2, 1, 9, 9, 9, 9, 3, 0];
+    let view = StridedColumnView::<u16>::new(&data, 4, 6, 3).unwrap();
+    assert_eq!(view.iter().collect::<Vec<_>>(), [1, 258, 3]);
+    assert_eq!(view.big_endian().get(1), Some(513));
+    let mut iter = view.iter();
+    assert_eq!(iter.next_back(), Some(3));
+    assert_eq!(iter.len(), 2);
+    assert_eq!(iter.nth(5), None);
+
+    assert!(matches!(
+        StridedColumnView::<u32>::new(&data, 4, 6, 3),
+        Err(PcdError::BufferTooSmall {
+            expected: 20,
+            got: 18
+        })
+    ));
+    assert!(StridedColumnView::<u16>::new(&data, 4, usize::MAX, 2).is_err());
+    assert!(
+        StridedColumnView::<f64>::new(&[], 0, 8, 0)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[cfg(feature = "memmap2")]
+#[test]
+fn test_lazy_view() {
+    use rs_pcd::io::LazyPcdView;
+    use std::io::Write;
+
+    let bytes = encode(5000, DataFormat::Binary);
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(&bytes).unwrap();
+    let view = LazyPcdView::open(file.path()).unwrap();
+    assert_eq!(view.len(), 5000);
+    assert_eq!(view.record(4999).unwrap().len(), 6);
+    assert!(view.record(5000).is_none());
+
+    let x = view.field("x").unwrap();
+    assert_eq!(x.len(), 5000);
+    let max = x.iter().fold(f32::MIN, f32::max);
+    assert_eq!(max, 49.0);
+    assert_eq!(view.field("label").unwrap().get(4321), Some(43.0));
+    let labels = view.column::<u16>("label").unwrap();
+    assert_eq!(labels.iter().nth(4321), Some(43));
+    assert!(matches!(
+        view.column::<u32>("label"),
+        Err(PcdError::FieldType { .. })
+    ));
+    assert!(view.field("y").is_none());
+    assert!(view.element("x", 1).is_none());
+
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(&encode(10, DataFormat::BinaryCompressed))
+        .unwrap();
+    assert!(matches!(
+        LazyPcdView::open(file.path()),
+        Err(PcdError::UnsupportedDataFormat(_))
+    ));
+
+    // Too few records for POINTS.
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(&bytes[..bytes.len() - 1]).unwrap();
+    assert!(matches!(
+        LazyPcdView::open(file.path()),
+        Err(PcdError::InvalidDataFormat(_))
+    ));
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_decode_par_checks_columns() {
+    use rs_pcd::Operation;
+    use rs_pcd::decoder::binary_par::BinaryParallelDecoder;
+
+    // Trailing records beyond the points asked for are left alone.
+    let bytes = encode(100, DataFormat::Binary);
+    let (header, start) = parse_header_bytes(&bytes).unwrap();
+    let layout = PcdLayout::from_header(&header).unwrap();
+    let decoder = BinaryParallelDecoder::new(&layout, 60);
+    let schema = [
+        ("x".to_string(), ValueType::F32),
+        ("label".to_string(), ValueType::U16),
+    ];
+    let mut block = PointBlock::new(&schema, 0);
+    decoder.decode_par(&bytes[start..], &mut block).unwrap();
+    assert_eq!(block.len, 60);
+    assert_eq!(block.get_column("x").unwrap().as_f32().unwrap()[59], 9.0);
+
+    let mut missing = PointBlock::new(&schema[..1], 0);
+    let err = decoder
+        .decode_par(&bytes[start..], &mut missing)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        PcdError::MissingField { field, op: Operation::Read } if field == "label"
+    ));
+    assert_eq!(missing.len, 0);
+
+    let mut wrong = PointBlock::new(
+        &[schema[0].clone(), ("label".to_string(), ValueType::U8)],
+        0,
+    );
+    let err = decoder.decode_par(&bytes[start..], &mut wrong).unwrap_err();
+    assert!(matches!(
+        err,
+        PcdError::FieldType { field, expected: ValueType::U16, got: ValueType::U8, .. }
+            if field == "label"
+    ));
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_decode_par_across_tasks() {
+    use rs_pcd::decoder::binary::decode_binary;
+    use rs_pcd::decoder::binary_par::BinaryParallelDecoder;
+
+    // 25-byte records, so 256 KiB tasks end mid-record in the input.
+    let builder = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .add_field_with_count("h", ValueType::I16, 3)
+        .add_field("t", ValueType::F64)
+        .add_field_with_count("rgb", ValueType::U8, 3)
+        .add_field("n", ValueType::U32)
+        .width(50_000);
+    let header = builder.clone().build().unwrap();
+    let layout = PcdLayout::from_header(&header).unwrap();
+    let data: Vec<u8> = (0..50_000 * layout.total_size)
+        .map(|i| (i * 31 % 251) as u8)
+        .collect();
+
+    let mut seq = PointBlock::new(builder.schema(), 0);
+    decode_binary(&data, &layout, 50_000, &mut seq).unwrap();
+    let mut par = PointBlock::new(builder.schema(), 0);
+    BinaryParallelDecoder::new(&layout, 50_000)
+        .decode_par(&data, &mut par)
+        .unwrap();
+    assert_eq!(par.len, 50_000);
+    let get = |b: &PointBlock| {
+        (
+            b.get_column("x")
+                .unwrap()
+                .as_f32()
+                .unwrap()
+                .iter()
+                .map(|v| v.to_bits())
+                .collect::<Vec<_>>(),
+            b.get_column("h").unwrap().as_i16().unwrap().to_vec(),
+            b.get_column("t")
+                .unwrap()
+                .as_f64()
+                .unwrap()
+                .iter()
+                .map(|v| v.to_bits())
+                .collect::<Vec<_>>(),
+            b.get_column("rgb").unwrap().as_u8().unwrap().to_vec(),
+            b.get_column("n").unwrap().as_u32().unwrap().to_vec(),
+        )
+    };
+    assert!(get(&par) == get(&seq));
+}
+
+#[test]
+fn test_decode_binary_gathers_every_width() {
+    use rs_pcd::decoder::binary::decode_binary;
+
+    // 18-byte records with fields of every width, narrow ones at the start,
+    // middle and end, and a count that does not fill the vector kernels.
+    let builder = PcdHeaderBuilder::new()
+        .add_field("a", ValueType::U8)
+        .add_field("t", ValueType::F64)
+        .add_field("i", ValueType::U16)
+        .add_field("x", ValueType::F32)
+        .add_field("s", ValueType::I8)
+        .add_field("e", ValueType::I16)
+        .width(1003);
+    let header = builder.clone().build().unwrap();
+    let layout = PcdLayout::from_header(&header).unwrap();
+    assert_eq!(layout.total_size, 18);
+    let data: Vec<u8> = (0..1003 * 18).map(|i| (i * 37 % 253) as u8).collect();
+    let mut block = PointBlock::new(builder.schema(), 0);
+    decode_binary(&data, &layout, 1003, &mut block).unwrap();
+
+    let col = |name| block.get_column(name).unwrap();
+    for (p, r) in data.chunks_exact(18).enumerate() {
+        assert_eq!(col("a").as_u8().unwrap()[p], r[0]);
+        let t = f64::from_le_bytes(r[1..9].try_into().unwrap());
+        assert_eq!(col("t").as_f64().unwrap()[p].to_bits(), t.to_bits());
+        let i = u16::from_le_bytes(r[9..11].try_into().unwrap());
+        assert_eq!(col("i").as_u16().unwrap()[p], i);
+        let x = f32::from_le_bytes(r[11..15].try_into().unwrap());
+        assert_eq!(col("x").as_f32().unwrap()[p].to_bits(), x.to_bits());
+        assert_eq!(col("s").as_i8().unwrap()[p], r[15] as i8);
+        let e = i16::from_le_bytes(r[16..18].try_into().unwrap());
+        assert_eq!(col("e").as_i16().unwrap()[p], e, "point {}", p);
+    }
+}
+
+#[test]
+fn test_absurd_point_counts() {
+    use rs_pcd::io::PcdReader;
+    use rs_pcd::layout::DEFAULT_MEMORY_LIMIT;
+    use std::io::Cursor;
+
+    // 2^60 points of 8 bytes; the byte count overflows u64.
+    let text = "VERSION .7\nFIELDS x y\nSIZE 4 4\nTYPE F F\nCOUNT 1 1\n\
+                WIDTH 1\nHEIGHT 1\nPOINTS 1152921504606846976\nDATA binary\n";
+    let too_large = |err: PcdError, limit: u64| matches!(err, PcdError::TooLarge { points, limit: l } if points == 1 << 60 && l == limit);
+    let reader = PcdReader::new(Cursor::new(text.as_bytes())).unwrap();
+    assert!(too_large(
+        reader.read_all().unwrap_err(),
+        DEFAULT_MEMORY_LIMIT
+    ));
+    let mut reader = PcdReader::new(Cursor::new(text.as_bytes())).unwrap();
+    assert!(too_large(
+        reader.read_chunk(usize::MAX).unwrap_err(),
+        DEFAULT_MEMORY_LIMIT
+    ));
+    let (header, start) = parse_header_bytes(text.as_bytes()).unwrap();
+    let layout = PcdLayout::from_header(&header).unwrap();
+    for format in [
+        DataFormat::Binary,
+        DataFormat::BinaryCompressed,
+        DataFormat::Ascii,
+    ] {
+        let err = decode_slice(&[], format, &layout, header.points).unwrap_err();
+        assert!(too_large(err, DEFAULT_MEMORY_LIMIT), "{:?}", format);
+    }
+    assert_eq!(start, text.len());
+
+    // A lower limit still allows chunks that fit it.
+    let bytes = encode(1000, DataFormat::Binary);
+    let mut reader = PcdReader::from_bytes(&bytes).unwrap().memory_limit(600);
+    assert_eq!(reader.read_chunk(100).unwrap().unwrap().len, 100);
+    let err = reader.read_all().unwrap_err();
+    assert!(matches!(
+        err,
+        PcdError::TooLarge {
+            points: 900,
+            limit: 600
+        }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "900 points need more than the memory limit of 600 bytes"
+    );
+
+    // COUNT so large a record's size overflows.
+    let text = text.replace("COUNT 1 1", "COUNT 1 4611686018427387904");
+    let err = rs_pcd::io::read_pcd_bytes(text.as_bytes()).unwrap_err();
+    assert!(matches!(err, PcdError::InvalidHeader { msg, .. } if msg == "Field y is too large"));
+}
+
+#[test]
+fn test_streamed_point_counts() {
+    use rs_pcd::io::PcdReader;
+    use std::io::Cursor;
+
+    // POINTS within the memory limit but far beyond the data; reads of
+    // unknown length grow with the data instead of allocating for POINTS.
+    let header = "VERSION .7\nFIELDS x y\nSIZE 4 4\nTYPE F F\nCOUNT 1 1\n\
+                  WIDTH 400000000\nHEIGHT 1\nPOINTS 400000000\n";
+    let ascii = format!("{}DATA ascii\n1 2\n3 4\n", header);
+    let reader = PcdReader::new(Cursor::new(ascii.as_bytes())).unwrap();
+    assert!(reader.read_all().is_err());
+    let mut binary = format!("{}DATA binary\n", header).into_bytes();
+    binary.extend_from_slice(&[0; 16]);
+    let reader = PcdReader::new(Cursor::new(&binary[..])).unwrap();
+    assert!(reader.read_all().is_err());
+}
+
+#[test]
+#[should_panic(expected = "capacity overflow")]
+fn test_block_capacity_overflow() {
+    let schema = rs_pcd::header::Schema::new().with_field_count("h", ValueType::U8, 1 << 40);
+    PointBlock::new(schema, 1 << 40);
+}
+
+#[test]
+fn test_read_points() {
+    use rs_pcd::io::PcdReader;
+    use std::io::Cursor;
+
+    let bytes = encode(1000, DataFormat::Binary);
+    let check = |block: &PointBlock, indices: &[usize]| {
+        let x = block.get_column("x").unwrap().as_f32().unwrap();
+        let label = block.get_column("label").unwrap().as_u16().unwrap();
+        assert_eq!(block.len, indices.len());
+        for (k, &i) in indices.iter().enumerate() {
+            assert_eq!((x[k], label[k]), ((i % 50) as f32, (i / 100) as u16));
+        }
+    };
+
+    // Random reads leave sequential reading where it was.
+    let mut reader = PcdReader::from_bytes(&bytes).unwrap();
+    let first = reader.read_chunk(10).unwrap().unwrap();
+    check(&first, &(0..10).collect::<Vec<_>>());
+    check(&reader.read_point(999).unwrap(), &[999]);
+    let indices = [512, 3, 3, 871, 0];
+    check(&reader.read_points(&indices).unwrap(), &indices);
+    let next = reader.read_chunk(5).unwrap().unwrap();
+    check(&next, &[10, 11, 12, 13, 14]);
+
+    let err = reader.read_points(&[4, 1000]).unwrap_err();
+    assert_eq!(err.to_string(), "Invalid data: Point 1000 outside 0..1000");
+
+    // A record cut short by the end of the data.
+    let cut = &bytes[..bytes.len() - 1];
+    let mut reader = PcdReader::new(Cursor::new(cut)).unwrap();
+    let err = reader.read_point(999).unwrap_err();
+    assert!(matches!(
+        err,
+        PcdError::Corrupt { point: 999, field: Some(field), .. } if field == "label"
+    ));
+    check(&reader.read_point(998).unwrap(), &[998]);
+
+    let ascii = encode(10, DataFormat::Ascii);
+    let err = PcdReader::from_bytes(&ascii)
+        .unwrap()
+        .read_point(0)
+        .unwrap_err();
+    assert!(matches!(err, PcdError::UnsupportedDataFormat(_)));
+
+    #[cfg(feature = "memmap2")]
+    {
+        use std::io::Write;
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&bytes).unwrap();
+        let mut reader = PcdReader::from_path_mmap(file.path()).unwrap();
+        check(&reader.read_points(&indices).unwrap(), &indices);
+        assert_eq!(reader.read_all().unwrap().len, 1000);
+    }
+}
@@ -0,0 +1,231 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rand::Rng;
+use rs_pcd::header::ValueType;
+use rs_pcd::spatial::KdTree;
+use rs_pcd::storage::PointBlock;
+
+/// `n` random points in the unit cube, F64 `x`, `y`, `z`, with a NaN x at 0.
+fn random_cloud(n: usize) -> PointBlock {
+    let mut rng = rand::rng();
+    let schema = [
+        ("x".to_string(), ValueType::F64),
+        ("y".to_string(), ValueType::F64),
+        ("z".to_string(), ValueType::F64),
+    ];
+    let mut block = PointBlock::new(&schema, n);
+    for name in ["x", "y", "z"] {
+        for v in block.get_column_mut(name).unwrap().as_f64_mut().unwrap() {
+            *v = rng.random();
+        }
+    }
+    block.get_column_mut("x").unwrap().as_f64_mut().unwrap()[0] = f64::NAN;
+    block
+}
+
+/// Indices of the finite points of `block` with their distance to `q`,
+/// nearest first.
+fn brute_force(block: &PointBlock, q: [f64; 3]) -> Vec<(usize, f64)> {
+    let mut all: Vec<(usize, f64)> = block
+        .iter_xyz_finite()
+        .unwrap()
+        .map(|(i, p)| {
+            let d: f64 = (0..3).map(|a| (p[a] - q[a]) * (p[a] - q[a])).sum();
+            (i, d.sqrt())
+        })
+        .collect();
+    all.sort_by(|a, b| a.1.total_cmp(&b.1));
+    all
+}
+
+#[test]
+fn test_kdtree_queries() {
+    let block = random_cloud(2000);
+    let tree = KdTree::from_block(&block).unwrap();
+    assert_eq!(tree.len(), 1999);
+
+    let mut rng = rand::rng();
+    let queries: Vec<[f64; 3]> = (0..50)
+        .map(|_| [rng.random(), rng.random(), rng.random()])
+        .collect();
+    let knn = tree.nearest_batch(&queries, 10);
+    let within = tree.radius_search_batch(&queries, 0.1);
+    for (q, (knn, within)) in queries.iter().zip(knn.iter().zip(&within)) {
+        let expected = brute_force(&block, *q);
+        let got: Vec<usize> = knn.iter().map(|n| n.index).collect();
+        let want: Vec<usize> = expected[..10].iter().map(|e| e.0).collect();
+        assert_eq!(got, want);
+        assert!((knn[0].distance - expected[0].1).abs() < 1e-12);
+
+        let got: Vec<usize> = within.iter().map(|n| n.index).collect();
+        let want: Vec<usize> = expected
+            .iter()
+            .take_while(|e| e.1 <= 0.1)
+            .map(|e| e.0)
+            .collect();
+        assert_eq!(got, want);
+    }
+
+    // The NaN point is never found, and k may exceed the tree.
+    assert_eq!(tree.nearest([0.5; 3], 5000).len(), 1999);
+    assert!(
+        tree.radius_search([0.5; 3], 10.0)
+            .iter()
+            .all(|n| n.index != 0)
+    );
+    assert!(tree.nearest([0.5; 3], 0).is_empty());
+    assert!(KdTree::new([]).nearest([0.0; 3], 3).is_empty());
+}
+
+#[test]
+fn test_octree() {
+    use rs_pcd::spatial::Octree;
+
+    let block = random_cloud(3000);
+    let mut tree = Octree::from_block(&block, 16).unwrap();
+    assert_eq!(tree.len(), 2999);
+    let leaves: Vec<_> = tree.leaves().collect();
+    assert!(leaves.len() > 100);
+    for leaf in &leaves {
+        assert!(leaf.points.len() <= 16);
+        // Up to rounding of the halved edges.
+        for (_, p) in leaf.points {
+            assert!((0..3).all(|a| p[a] >= leaf.min[a] && p[a] <= leaf.min[a] + leaf.size + 1e-12));
+        }
+    }
+
+    let q = [0.3, 0.6, 0.5];
+    let got: Vec<usize> = tree
+        .radius_search(q, 0.15)
+        .iter()
+        .map(|n| n.index)
+        .collect();
+    let want: Vec<usize> = brute_force(&block, q)
+        .iter()
+        .take_while(|e| e.1 <= 0.15)
+        .map(|e| e.0)
+        .collect();
+    assert_eq!(got, want);
+
+    let (min, max) = ([0.2, 0.1, 0.4], [0.5, 0.3, 0.9]);
+    let want: Vec<usize> = block
+        .iter_xyz_finite()
+        .unwrap()
+        .filter(|(_, p)| (0..3).all(|a| p[a] >= min[a] && p[a] <= max[a]))
+        .map(|(i, _)| i)
+        .collect();
+    assert_eq!(tree.box_search(min, max), want);
+
+    // A second frame, numbered after the first; points outside are skipped.
+    let (origin, size) = tree.bounds();
+    assert!(tree.insert(5000, origin));
+    assert!(!tree.insert(5001, [origin[0] + size * 2.0, 0.5, 0.5]));
+    assert_eq!(tree.insert_block(&block, 3000).unwrap(), 2999);
+    assert_eq!(tree.len(), 2 * 2999 + 1);
+
+    // Round trip through the binary form.
+    let mut bytes = Vec::new();
+    tree.write(&mut bytes).unwrap();
+    let read = Octree::read(bytes.as_slice()).unwrap();
+    assert_eq!(read, tree);
+    assert_eq!(read.box_search(min, max).len(), 2 * want.len());
+    assert!(Octree::read(&bytes[..bytes.len() - 1]).is_err());
+    let mut bad = bytes.clone();
+    bad[0] = b'X';
+    assert!(Octree::read(bad.as_slice()).is_err());
+}
+
+#[test]
+fn test_voxel_hash_map() {
+    use rs_pcd::spatial::VoxelHashMap;
+
+    let block = random_cloud(3000);
+    let mut map = VoxelHashMap::new(0.25).max_points_per_voxel(5);
+    assert_eq!(map.key([-0.1, 0.3, 0.25]), [-1, 1, 1]);
+    let added = map.insert_block(&block, 0).unwrap();
+    assert_eq!(map.len(), 64);
+    assert_eq!(added, 64 * 5);
+    assert_eq!(map.num_points(), added);
+    assert!(map.is_occupied([0.9, 0.9, 0.9]));
+    assert!(!map.is_occupied([1.1, 0.9, 0.9]));
+
+    // Each voxel holds its first points, which lie inside it.
+    for (key, points) in map.voxels() {
+        assert_eq!(points.len(), 5);
+        assert_eq!(map.points(key), points);
+        for &(i, p) in points {
+            assert_eq!(map.key(p), key);
+            assert!(i > 0 && i < 3000);
+        }
+    }
+    assert!(map.points([7, 7, 7]).is_empty());
+
+    // A second frame shifted by a voxel only fills the new layer.
+    let mut shifted = block.clone();
+    for x in shifted.get_column_mut("x").unwrap().as_f64_mut().unwrap() {
+        *x += 0.25;
+    }
+    assert_eq!(map.insert_block(&shifted, 3000).unwrap(), 16 * 5);
+    assert_eq!(map.len(), 80);
+    assert!(!map.insert(0, [f64::NAN, 0.0, 0.0]));
+
+    map.remove_far([0.0; 3], 0.5);
+    assert!(map.len() < 10);
+    assert!(map.voxels().all(|(k, _)| k.iter().all(|&c| c < 2)));
+}
+
+#[test]
+fn test_neighbor_search_trait() {
+    use rs_pcd::spatial::{NeighborSearch, Octree, VoxelHashMap};
+
+    fn knn_indices(search: &impl NeighborSearch, q: [f64; 3], k: usize) -> Vec<usize> {
+        search.nearest(q, k).iter().map(|n| n.index).collect()
+    }
+
+    let block = random_cloud(2000);
+    let kdtree = KdTree::from_block(&block).unwrap();
+    let octree = Octree::from_block(&block, 8).unwrap();
+    let mut voxels = VoxelHashMap::new(0.1);
+    voxels.insert_block(&block, 0).unwrap();
+    let indexes: [&dyn NeighborSearch; 3] = [&kdtree, &octree, &voxels];
+
+    let mut rng = rand::rng();
+    for _ in 0..20 {
+        // Inside the cloud and well outside it.
+        let q: [f64; 3] = [rng.random(), rng.random(), rng.random::<f64>() * 3.0];
+        let expected = brute_force(&block, q);
+        let want: Vec<usize> = expected[..12].iter().map(|e| e.0).collect();
+        let within: Vec<usize> = expected
+            .iter()
+            .take_while(|e| e.1 <= 0.2)
+            .map(|e| e.0)
+            .collect();
+        for search in indexes {
+            let got: Vec<usize> = search.nearest(q, 12).iter().map(|n| n.index).collect();
+            assert_eq!(got, want);
+            let got: Vec<usize> = search
+                .radius_search(q, 0.2)
+                .iter()
+                .map(|n| n.index)
+                .collect();
+            assert_eq!(got, within);
+        }
+        assert_eq!(knn_indices(&voxels, q, 12), want);
+    }
+    for search in indexes {
+        assert_eq!(search.nearest([0.5; 3], 3000).len(), 1999);
+        assert!(search.nearest([0.5; 3], 0).is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rs_pcd::header::{PcdHeaderBuilder, ValueType};
+use rs_pcd::io::read_pcd_file;
+use rs_pcd::storage::PointBlock;
+use rs_pcd::tiling::{
+    EptWriter, GridTileWriter, TilingOptions, grid_indices, split_grid, write_ept,
+    write_grid_tiles,
+};
+
+fn grid_block(n: usize) -> PointBlock {
+    let schema = vec![
+        ("x".to_string(), ValueType::F32),
+        ("y".to_string(), ValueType::F32),
+        ("z".to_string(), ValueType::F32),
+        ("intensity".to_string(), ValueType::U16),
+    ];
+    let mut block = PointBlock::new(&schema, n * n);
+    for i in 0..n * n {
+        block.get_column_mut("x").unwrap().as_f32_mut().unwrap()[i] = (i % n) as f32;
+        block.get_column_mut("y").unwrap().as_f32_mut().unwrap()[i] = (i / n) as f32;
+        block
+            .get_column_mut("intensity")
+            .unwrap()
+            .as_u16_mut()
+            .unwrap()[i] = i as u16;
+    }
+    block
+}
+
+#[test]
+fn test_ept_hierarchy_counts() {
+    let dir = tempfile::tempdir().unwrap();
+    let block = grid_block(32);
+    let options = TilingOptions {
+        span: 4,
+        max_depth: 6,
+    };
+    write_ept(dir.path(), &block, options).unwrap();
+
+    let ept = std::fs::read_to_string(dir.path().join("ept.json")).unwrap();
+    assert!(ept.contains("\"points\": 1024"));
+    assert!(ept.contains("\"name\": \"intensity\", \"type\": \"unsigned\", \"size\": 2"));
+
+    // Every point lands in exactly one node file: 3 * 8 + 2 bytes per point.
+    let mut total = 0;
+    for entry in std::fs::read_dir(dir.path().join("ept-data")).unwrap() {
+        total += entry.unwrap().metadata().unwrap().len();
+    }
+    assert_eq!(total, 1024 * 26);
+
+    // The 4x4x4 root grid over a flat 32x32 cloud keeps one point per cell.
+    let root = std::fs::metadata(dir.path().join("ept-data/0-0-0-0.bin")).unwrap();
+    assert_eq!(root.len(), 16 * 26);
+    let hierarchy = std::fs::read_to_string(dir.path().join("ept-hierarchy/0-0-0-0.json")).unwrap();
+    assert!(hierarchy.contains("\"0-0-0-0\": 16"));
+}
+
+#[test]
+fn test_ept_rejects_deep_octree() {
+    let dir = tempfile::tempdir().unwrap();
+    let block = grid_block(4);
+    for options in [
+        TilingOptions {
+            span: 4,
+            max_depth: 64,
+        },
+        TilingOptions {
+            span: u32::MAX,
+            max_depth: 4,
+        },
+    ] {
+        assert!(options.validate().is_err());
+        assert!(write_ept(dir.path(), &block, options).is_err());
+    }
+    let deepest = TilingOptions {
+        span: 1,
+        max_depth: TilingOptions::MAX_DEPTH,
+    };
+    write_ept(dir.path(), &block, deepest).unwrap();
+}
+
+#[test]
+fn test_ept_streaming_chunks() {
+    let dir = tempfile::tempdir().unwrap();
+    let block = grid_block(8);
+    let schema = vec![
+        ("x".to_string(), ValueType::F32),
+        ("y".to_string(), ValueType::F32),
+        ("z".to_string(), ValueType::F32),
+        ("intensity".to_string(), ValueType::U16),
+    ];
+    let mut writer = EptWriter::new(
+        dir.path(),
+        &schema,
+        ([0.0, 0.0, 0.0], [8.0, 8.0, 8.0]),
+        TilingOptions::default(),
+    )
+    .unwrap();
+    writer.add(&block).unwrap();
+    writer.add(&block).unwrap();
+    writer.finish().unwrap();
+
+    let ept = std::fs::read_to_string(dir.path().join("ept.json")).unwrap();
+    assert!(ept.contains("\"points\": 128"));
+}
+
+#[test]
+fn test_split_grid() {
+    let mut block = grid_block(10);
+    block.get_column_mut("x").unwrap().as_f32_mut().unwrap()[3] = f32::NAN;
+    let tiles = split_grid(&block, 4.0).unwrap();
+    // Columns 0-3, 4-7 and 8-9 by the same rows.
+    assert_eq!(tiles.len(), 9);
+    assert_eq!(tiles[&[0, 0]].len, 15);
+    assert_eq!(tiles[&[1, 2]].len, 8);
+    assert_eq!(tiles[&[2, 2]].len, 4);
+    assert_eq!(tiles.values().map(|t| t.len).sum::<usize>(), 99);
+    let intensity = tiles[&[2, 2]].get_column("intensity").unwrap().as_u16().unwrap();
+    assert_eq!(intensity, [88, 89, 98, 99]);
+
+    let indices = grid_indices(&block, 4.0).unwrap();
+    assert_eq!(indices[&[0, 0]][..4], [0, 1, 2, 10]);
+    assert!(split_grid(&block, 0.0).is_err());
+}
+
+#[test]
+fn test_grid_tile_writer() {
+    let dir = tempfile::tempdir().unwrap();
+    let block = grid_block(10);
+    let header = PcdHeaderBuilder::xyz()
+        .add_field("intensity", ValueType::U16)
+        .width(100)
+        .height(1)
+        .build()
+        .unwrap();
+
+    // Two chunks of rows, each touching the same tiles.
+    let mut writer = GridTileWriter::new(dir.path(), &header, 5.0).unwrap();
+    let mask: Vec<bool> = (0..100).map(|i| i % 2 == 0).collect();
+    let rest: Vec<bool> = mask.iter().map(|m| !m).collect();
+    writer.add(&block.filter(&mask)).unwrap();
+    writer.add(&block.filter(&rest)).unwrap();
+    let tiles = writer.finish().unwrap();
+
+    assert_eq!(tiles.len(), 4);
+    assert!(tiles.iter().all(|t| t.points == 25));
+    let tile = read_pcd_file(dir.path().join("tile_1_0.pcd")).unwrap();
+    assert_eq!(tile.len, 25);
+    let mut intensity = tile.get_column("intensity").unwrap().as_u16().unwrap().to_vec();
+    intensity.sort_unstable();
+    assert_eq!(intensity[..6], [5, 6, 7, 8, 9, 15]);
+    let files = std::fs::read_dir(dir.path()).unwrap().count();
+    assert_eq!(files, 4);
+
+    let again = write_grid_tiles(dir.path(), &header, &block, 5.0).unwrap();
+    assert_eq!(again, tiles);
+}
@@ -0,0 +1,251 @@
+// Copyright 2025 bigpear0201
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+
+//     http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rs_pcd::header::{DataFormat, PcdHeaderBuilder, ValueType};
+use rs_pcd::io::{Issue, PcdWriter, read_pcd_file, repair_pcd, validate_pcd};
+use rs_pcd::storage::PointBlock;
+use std::path::Path;
+
+/// A 10-point cloud with x = 0..10, with `edit` applied to the header text
+/// and `tail` appended.
+fn write(path: &Path, format: DataFormat, edit: (&str, &str), tail: &[u8]) {
+    let header = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .add_field("y", ValueType::F32)
+        .width(10)
+        .data_format(format)
+        .build()
+        .unwrap();
+    let schema = [
+        ("x".to_string(), ValueType::F32),
+        ("y".to_string(), ValueType::F32),
+    ];
+    let mut block = PointBlock::new(&schema, 10);
+    for (i, x) in block
+        .get_column_mut("x")
+        .unwrap()
+        .as_f32_mut()
+        .unwrap()
+        .iter_mut()
+        .enumerate()
+    {
+        *x = i as f32;
+    }
+    let mut bytes = Vec::new();
+    PcdWriter::new(&mut bytes)
+        .write_pcd(&header, &block)
+        .unwrap();
+    let split = bytes.windows(5).position(|w| w == b"DATA ").unwrap();
+    let text = String::from_utf8(bytes[..split].to_vec()).unwrap();
+    let mut out = text.replace(edit.0, edit.1).into_bytes();
+    out.extend_from_slice(&bytes[split..]);
+    out.extend_from_slice(tail);
+    std::fs::write(path, out).unwrap();
+}
+
+#[test]
+fn test_validate_ok() {
+    let dir = tempfile::tempdir().unwrap();
+    for format in [
+        DataFormat::Ascii,
+        DataFormat::Binary,
+        DataFormat::BinaryCompressed,
+    ] {
+        let path = dir.path().join("ok.pcd");
+        write(&path, format, ("", ""), b"");
+        let validation = validate_pcd(&path).unwrap();
+        assert!(validation.is_valid(), "{:?}", validation.issues);
+        assert_eq!(validation.available, Some(10));
+    }
+}
+
+#[test]
+fn test_repair_points_count() {
+    let dir = tempfile::tempdir().unwrap();
+    for format in [
+        DataFormat::Ascii,
+        DataFormat::Binary,
+        DataFormat::BinaryCompressed,
+    ] {
+        let path = dir.path().join("bad.pcd");
+        write(&path, format, ("POINTS 10", "POINTS 25"), b"");
+        let validation = validate_pcd(&path).unwrap();
+        assert_eq!(
+            validation.issues,
+            [
+                Issue::PointsMismatch {
+                    points: 25,
+                    width: 10,
+                    height: 1
+                },
+                Issue::PointCount {
+                    declared: 25,
+                    available: 10
+                },
+            ]
+        );
+
+        let fixed = dir.path().join("fixed.pcd");
+        repair_pcd(&path, &fixed).unwrap();
+        assert!(validate_pcd(&fixed).unwrap().is_valid());
+        let x = read_pcd_file(&fixed).unwrap();
+        assert_eq!(x.get_column("x").unwrap().as_f32().unwrap()[9], 9.0);
+    }
+}
+
+#[test]
+fn test_repair_trailing_garbage() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bad.pcd");
+    write(&path, DataFormat::Binary, ("", ""), b"garbage");
+    let validation = validate_pcd(&path).unwrap();
+    assert_eq!(validation.issues, [Issue::TrailingBytes(7)]);
+    let fixed = dir.path().join("fixed.pcd");
+    repair_pcd(&path, &fixed).unwrap();
+    assert!(validate_pcd(&fixed).unwrap().is_valid());
+
+    // Repairing onto the source would truncate it.
+    let len = std::fs::metadata(&path).unwrap().len();
+    let same = dir.path().join(".").join("bad.pcd");
+    assert!(repair_pcd(&path, &same).is_err());
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), len);
+
+    // A truncated binary file keeps its whole points.
+    write(&path, DataFormat::Binary, ("", ""), b"");
+    let len = std::fs::metadata(&path).unwrap().len();
+    let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+    file.set_len(len - 12).unwrap();
+    repair_pcd(&path, &fixed).unwrap();
+    let header = rs_pcd::io::PcdReader::open(&fixed)
+        .unwrap()
+        .header()
+        .clone();
+    assert_eq!((header.points, header.width, header.height), (8, 8, 1));
+    assert!(validate_pcd(&fixed).unwrap().is_valid());
+
+    // Text after the ascii data.
+    write(&path, DataFormat::Ascii, ("", ""), b"trailing text\n");
+    let validation = validate_pcd(&path).unwrap();
+    assert_eq!(validation.issues, [Issue::MalformedLine(11)]);
+    repair_pcd(&path, &fixed).unwrap();
+    assert!(validate_pcd(&fixed).unwrap().is_valid());
+}
+
+#[test]
+fn test_repair_ascii_bad_lines() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bad.pcd");
+
+    // Blank lines at the end are not an issue.
+    write(&path, DataFormat::Ascii, ("", ""), b"\n  \n\n");
+    assert!(validate_pcd(&path).unwrap().is_valid());
+
+    // Bad lines are reported and dropped; the points after them are kept.
+    write(&path, DataFormat::Ascii, ("", ""), b"\n10.0 0.0\n");
+    let text = std::fs::read_to_string(&path).unwrap();
+    let text = text.replacen("3.000000 0.000000", "3.000000 zero", 1);
+    std::fs::write(&path, text.replacen("5.000000 0.000000", "5.000000", 1)).unwrap();
+    let validation = validate_pcd(&path).unwrap();
+    assert_eq!(
+        validation.issues,
+        [
+            Issue::MalformedLine(4),
+            Issue::MalformedLine(6),
+            Issue::MalformedLine(11),
+            Issue::PointCount {
+                declared: 10,
+                available: 9
+            },
+        ]
+    );
+    assert_eq!(validation.available, Some(9));
+    let fixed = dir.path().join("fixed.pcd");
+    repair_pcd(&path, &fixed).unwrap();
+    assert!(validate_pcd(&fixed).unwrap().is_valid());
+    let block = read_pcd_file(&fixed).unwrap();
+    assert_eq!(
+        block.get_column("x").unwrap().as_f32().unwrap(),
+        [0.0, 1.0, 2.0, 4.0, 6.0, 7.0, 8.0, 9.0, 10.0]
+    );
+}
+
+#[test]
+fn test_unrepairable_compressed() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bad.pcd");
+    write(&path, DataFormat::BinaryCompressed, ("", ""), b"");
+    let len = std::fs::metadata(&path).unwrap().len();
+    let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+    file.set_len(len - 4).unwrap();
+    let validation = validate_pcd(&path).unwrap();
+    assert!(matches!(
+        validation.issues[..],
+        [Issue::CompressedSize { .. }]
+    ));
+    assert!(validation.repaired_header().is_err());
+    assert!(repair_pcd(&path, dir.path().join("fixed.pcd")).is_err());
+}
+
+#[test]
+fn test_reader_header_checks() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cloud.pcd");
+
+    write(&path, DataFormat::Binary, ("WIDTH 10", "WIDTH 5"), b"xx");
+    let reader = rs_pcd::io::PcdReader::open(&path).unwrap();
+    assert_eq!(
+        reader.issues(),
+        [
+            Issue::PointsMismatch {
+                points: 10,
+                width: 5,
+                height: 1
+            },
+            Issue::TrailingBytes(2),
+        ]
+    );
+    assert_eq!(reader.read_all().unwrap().len, 10);
+
+    // Too few points fails on opening, naming the cause.
+    write(&path, DataFormat::Binary, ("POINTS 10", "POINTS 11"), b"");
+    let err = match rs_pcd::io::PcdReader::open(&path) {
+        Err(e) => e.to_string(),
+        Ok(_) => panic!("truncated data accepted"),
+    };
+    assert!(err.contains("holds 10 points, POINTS says 11"), "{}", err);
+    let bytes = std::fs::read(&path).unwrap();
+    assert!(rs_pcd::io::PcdReader::from_bytes(&bytes).is_err());
+}
+
+#[test]
+fn test_header_normalize() {
+    let mut header = PcdHeaderBuilder::new()
+        .add_field("x", ValueType::F32)
+        .width(4)
+        .height(3)
+        .build()
+        .unwrap();
+    assert!(!header.normalize());
+
+    header.points = 10;
+    assert!(header.normalize());
+    assert_eq!((header.width, header.height, header.points), (10, 1, 10));
+
+    header.points = 0;
+    header.height = 0;
+    header.counts.clear();
+    assert!(header.normalize());
+    assert_eq!((header.width, header.height, header.points), (10, 1, 10));
+    assert_eq!(header.counts, [1]);
+}